@@ -48,12 +48,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("═══════════════════════════════════════════════════════════════\n");
 
     // Load credentials from environment variables or use demo values
-    let api_key =
-        std::env::var("KRAKEN_API_KEY").unwrap_or_else(|_| "DEMO_API_KEY_NOT_REAL".to_string());
-    let api_secret = std::env::var("KRAKEN_API_SECRET")
-        .unwrap_or_else(|_| "DEMO_API_SECRET_NOT_REAL_BASE64".to_string());
+    let credentials = Credentials::from_env().unwrap_or_else(|_| {
+        Credentials::new("DEMO_API_KEY_NOT_REAL", "DEMO_API_SECRET_NOT_REAL_BASE64")
+    });
 
-    let is_demo = api_key.starts_with("DEMO_");
+    let is_demo = credentials.api_key().starts_with("DEMO_");
 
     if is_demo {
         println!("⚠️  Running in DEMO mode (no real credentials)");
@@ -62,7 +61,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("   export KRAKEN_API_SECRET=\"your_base64_secret\"\n");
     }
 
-    let credentials = Credentials::new(api_key, api_secret);
     println!("✅ Credentials created");
     println!(
         "   API Key: {}...",
@@ -77,10 +75,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  STEP 2: Generating Authentication Token");
     println!("═══════════════════════════════════════════════════════════════\n");
 
-    // Generate nonce (timestamp in nanoseconds)
-    let nonce = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)?
-        .as_nanos() as u64;
+    // Next nonce for this key (clock-based, never repeats)
+    let nonce = credentials.next_nonce();
 
     println!("📝 Nonce generated: {}", nonce);
 
@@ -5,6 +5,9 @@
 //! ## Run
 //! ```bash
 //! cargo run --example benchmark --features trades,ticker --release
+//!
+//! # With exchange-to-delivery latency histograms
+//! cargo run --example benchmark --features trades,ticker,latency --release
 //! ```
 //!
 //! ## Metrics tracked
@@ -12,6 +15,7 @@
 //! - Message processing latency
 //! - Messages per second throughput
 //! - Orderbook update latency
+//! - Exchange timestamp to delivery latency per channel (with `latency`)
 
 use kraky::KrakyClient;
 use std::time::{Duration, Instant};
@@ -255,6 +259,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // ═══════════════════════════════════════════════════════════════════════
     stats.print_report();
 
+    #[cfg(feature = "latency")]
+    {
+        println!("🌐 EXCHANGE-TO-DELIVERY LATENCY (includes clock offset)");
+        let mut channels: Vec<_> = client.latency_stats().into_iter().collect();
+        channels.sort_by(|a, b| a.0.cmp(&b.0));
+        for (channel, latency) in channels {
+            let summary = latency.dispatch.summary();
+            println!(
+                "   {:<8} n={:<7} p50={:?} p99={:?} p99.9={:?} max={:?}",
+                channel, summary.count, summary.p50, summary.p99, summary.p999, summary.max
+            );
+        }
+        println!();
+    }
+
     // Save results to file for comparison
     let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
     let filename = format!("benchmark_{}.txt", timestamp);
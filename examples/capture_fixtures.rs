@@ -0,0 +1,148 @@
+//! 📼 Fixture Capture - Refresh the Regression Corpus from the Live Feed
+//!
+//! This example connects to the public Kraken v2 WebSocket directly, without
+//! going through the client, and writes the first frame of each kind it sees
+//! to `fixtures/v2/<name>.json`, byte for byte as Kraken sent it. Extra
+//! fields, `null`s and number formats are kept, which is the point: the
+//! fixtures exist to catch parser drift from the real wire format.
+//!
+//! ## What This Captures
+//! - `status_update`, `heartbeat` and `pong`
+//! - `subscribe_book_ack` and `subscribe_error` (an unknown pair)
+//! - `book_snapshot` / `book_update` for BTC/USD at depth 10
+//! - `trade_snapshot` / `trade_update`, `ticker_snapshot`
+//! - `ohlc_snapshot` / `ohlc_update` at the 1 minute interval
+//!
+//! ## Setup
+//! ```bash
+//! cargo run --example capture_fixtures
+//! ```
+//!
+//! Pass a directory as the first argument to write somewhere other than
+//! `fixtures/v2`. Review the diff before committing: the tests in
+//! `kraky::testing::fixtures` and the handler tests that replay the
+//! fixtures must still pass against the new frames.
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+const URL: &str = "wss://ws.kraken.com/v2";
+
+const NAMES: &[&str] = &[
+    "status_update",
+    "heartbeat",
+    "pong",
+    "subscribe_book_ack",
+    "subscribe_error",
+    "book_snapshot",
+    "book_update",
+    "trade_snapshot",
+    "trade_update",
+    "ticker_snapshot",
+    "ohlc_snapshot",
+    "ohlc_update",
+];
+
+const REQUESTS: &[&str] = &[
+    r#"{"method":"subscribe","params":{"channel":"book","symbol":["BTC/USD"],"depth":10},"req_id":1}"#,
+    r#"{"method":"subscribe","params":{"channel":"book","symbol":["XYZ/USD"],"depth":10},"req_id":2}"#,
+    r#"{"method":"subscribe","params":{"channel":"trade","symbol":["BTC/USD"]},"req_id":3}"#,
+    r#"{"method":"subscribe","params":{"channel":"ticker","symbol":["BTC/USD"]},"req_id":4}"#,
+    r#"{"method":"subscribe","params":{"channel":"ohlc","symbol":["BTC/USD"],"interval":1},"req_id":5}"#,
+    r#"{"method":"ping","req_id":101}"#,
+];
+
+/// Name the fixture a frame belongs to, if any
+fn classify(frame: &Value) -> Option<&'static str> {
+    if let Some(method) = frame["method"].as_str() {
+        return match (method, frame["success"].as_bool()) {
+            ("pong", _) => Some("pong"),
+            ("subscribe", Some(false)) => Some("subscribe_error"),
+            ("subscribe", Some(true)) if frame["result"]["channel"] == "book" => {
+                Some("subscribe_book_ack")
+            }
+            _ => None,
+        };
+    }
+
+    let kind = frame["type"].as_str().unwrap_or_default();
+    match (frame["channel"].as_str()?, kind) {
+        ("status", "update") => Some("status_update"),
+        ("heartbeat", _) => Some("heartbeat"),
+        ("book", "snapshot") => Some("book_snapshot"),
+        ("book", "update") => Some("book_update"),
+        ("trade", "snapshot") => Some("trade_snapshot"),
+        ("trade", "update") => Some("trade_update"),
+        ("ticker", "snapshot") => Some("ticker_snapshot"),
+        ("ohlc", "snapshot") => Some("ohlc_snapshot"),
+        ("ohlc", "update") => Some("ohlc_update"),
+        _ => None,
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("\n╔══════════════════════════════════════════════════════════════╗");
+    println!("║           📼 Fixture Capture - Kraken v2 Public Feed         ║");
+    println!("╚══════════════════════════════════════════════════════════════╝\n");
+
+    let dir = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("fixtures/v2"));
+    std::fs::create_dir_all(&dir)?;
+
+    println!("📡 Connecting to {}...", URL);
+    let (mut socket, _) = tokio_tungstenite::connect_async(URL).await?;
+    for request in REQUESTS {
+        socket.send(Message::Text(request.to_string())).await?;
+    }
+
+    let mut captured = BTreeMap::new();
+    let capture = async {
+        while captured.len() < NAMES.len() {
+            let Some(message) = socket.next().await else {
+                break;
+            };
+            let Message::Text(text) = message? else {
+                continue;
+            };
+            let Some(name) = serde_json::from_str(&text).ok().and_then(|v| classify(&v)) else {
+                continue;
+            };
+            if !captured.contains_key(name) {
+                println!("   ✅ {}", name);
+                captured.insert(name, text);
+            }
+        }
+        Ok::<_, Box<dyn std::error::Error>>(())
+    };
+    // OHLC updates only arrive once a trade prints, so allow a couple of minutes
+    match tokio::time::timeout(Duration::from_secs(180), capture).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => println!("\n❌ Connection error: {}", e),
+        Err(_) => println!("\n⏱️  Timed out before every frame kind arrived"),
+    }
+
+    for (name, text) in &captured {
+        std::fs::write(dir.join(format!("{}.json", name)), format!("{}\n", text))?;
+    }
+    let missing: Vec<_> = NAMES
+        .iter()
+        .filter(|name| !captured.contains_key(*name))
+        .collect();
+    println!(
+        "\n💾 Wrote {} fixtures to {}",
+        captured.len(),
+        dir.display()
+    );
+    if !missing.is_empty() {
+        println!("⚠️  Not captured: {:?}", missing);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+//! Terminal dashboard example
+//!
+//! This example shows a live dashboard for a pair: book ladder, recent
+//! trades, ticker, imbalance gauge and connection stats.
+//!
+//! Usage: cargo run --example dashboard --features tui -- ETH/USD
+
+use kraky::{Dashboard, KrakyClient};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let symbol = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "BTC/USD".to_string());
+
+    println!("Connecting to Kraken WebSocket...");
+    let client = KrakyClient::connect().await?;
+
+    Dashboard::new(symbol).depth(25).run(&client).await?;
+    Ok(())
+}
@@ -66,7 +66,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut events = client.subscribe_events();
     println!("📌 Subscribed to connection events");
     println!("   Events: Connected, Disconnected, Reconnecting, Reconnected,");
-    println!("           ReconnectFailed, ReconnectExhausted\n");
+    println!("           ReconnectFailed, ReconnectExhausted, Degraded, Stale\n");
 
     // Spawn event handler in background
     tokio::spawn(async move {
@@ -86,6 +86,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 ConnectionEvent::ReconnectExhausted => {
                     println!("🔔 EVENT: Reconnect exhausted")
                 }
+                ConnectionEvent::Degraded(reason) => {
+                    println!("🔔 EVENT: Degraded - {}", reason)
+                }
+                ConnectionEvent::Stale(idle) => {
+                    println!("🔔 EVENT: No data for {:?}", idle)
+                }
+                ConnectionEvent::DataStale(channel, symbol, idle) => {
+                    println!(
+                        "🔔 EVENT: No {} updates for {} in {:?}",
+                        channel, symbol, idle
+                    )
+                }
+                ConnectionEvent::DataResumed(channel, symbol) => {
+                    println!("🔔 EVENT: {} updates for {} resumed", channel, symbol)
+                }
+                ConnectionEvent::OrderbookCorrupted { symbol, .. } => {
+                    println!("🔔 EVENT: {} orderbook checksum mismatch", symbol)
+                }
+                ConnectionEvent::ChecksumFailures {
+                    symbol,
+                    consecutive,
+                } => {
+                    println!(
+                        "🔔 EVENT: {} failed {} checksums in a row",
+                        symbol, consecutive
+                    )
+                }
+                ConnectionEvent::OrderbookUnverified { symbol } => {
+                    println!("🔔 EVENT: {} orderbook checksums not validated yet", symbol)
+                }
+                ConnectionEvent::SystemStateChanged(state) => {
+                    println!("🔔 EVENT: Kraken is {}", state)
+                }
             }
         }
     });
@@ -367,8 +400,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("║    ✅ Managed Orderbook State                                 ║");
     println!("╚══════════════════════════════════════════════════════════════╝\n");
 
-    // Disconnect cleanly
-    client.disconnect();
+    // Disconnect cleanly and wait for background tasks
+    client.shutdown().await;
 
     Ok(())
 }
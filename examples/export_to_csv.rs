@@ -16,6 +16,9 @@
 //! - Historical data collection
 //! - Debugging and monitoring
 //!
+//! To record raw channels without writing any code, see the `recorder`
+//! feature (`examples/record_market_data.rs`).
+//!
 //! ## Setup
 //! ```bash
 //! cargo run --example export_to_csv --features trades,analytics
@@ -5,7 +5,7 @@
 //!
 //! ## Features
 //! - Track total bid/ask volume
-//! - Monitor spread changes
+//! - Detect spread blowouts against the rolling average (`LiquidityMonitor`)
 //! - Detect liquidity dry-ups
 //! - Telegram alerts on low liquidity
 //!
@@ -20,7 +20,9 @@
 //! cargo run --example liquidity_monitor --features telegram-alerts
 //! ```
 
-use kraky::KrakyClient;
+#[cfg(feature = "telegram")]
+use kraky::Notifier;
+use kraky::{KrakyClient, LiquidityEventKind, LiquidityMonitor};
 use std::time::Duration;
 
 #[tokio::main]
@@ -36,14 +38,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Configuration
     let trading_pair = "BTC/USD";
-    let min_total_liquidity = 50.0; // BTC
-    let max_spread_bps = 50.0; // basis points
+    let min_side_liquidity = 5.0; // BTC within the top 10 levels per side
+    let spread_multiplier = 3.0; // x the rolling average spread
     let check_interval = Duration::from_secs(10);
 
     println!("⚙️  Configuration:");
     println!("   Trading Pair: {}", trading_pair);
-    println!("   Min Total Liquidity: {} BTC", min_total_liquidity);
-    println!("   Max Spread: {} bps", max_spread_bps);
+    println!("   Min Liquidity: {} BTC per side", min_side_liquidity);
+    println!("   Max Spread: {}x average", spread_multiplier);
     println!("   Check Interval: {:?}\n", check_interval);
 
     // Telegram setup (optional)
@@ -83,118 +85,111 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("   Press Ctrl+C to stop\n");
     println!("{}", "═".repeat(70));
 
+    // Spread blowouts against the rolling baseline, and droughts when a
+    // side of the top 10 levels shows less than a tenth of its usual size
+    // or less than the minimum
+    let mut monitor = LiquidityMonitor::new()
+        .with_spread_multiplier(spread_multiplier)
+        .with_depth(10)
+        .with_min_size(min_side_liquidity)
+        .with_size_drop(0.1);
+
     // Track state
     let mut update_count = 0;
     let mut last_check = std::time::Instant::now();
-    let mut spread_history: Vec<f64> = Vec::new();
 
     // Main loop
     while let Some(_update) = orderbook_sub.next().await {
         update_count += 1;
 
-        if last_check.elapsed() >= check_interval {
-            if let Some(ob) = client.get_orderbook(trading_pair) {
-                // Calculate total liquidity
-                let total_bid_volume: f64 = ob.bids.values().sum();
-                let total_ask_volume: f64 = ob.asks.values().sum();
-                let total_liquidity = total_bid_volume + total_ask_volume;
-
-                // Calculate spread
-                if let (Some(best_bid), Some(best_ask)) = (ob.best_bid(), ob.best_ask()) {
-                    let spread = best_ask - best_bid;
-                    let mid_price = (best_bid + best_ask) / 2.0;
-                    let spread_bps = (spread / mid_price) * 10000.0;
-
-                    // Track spread history
-                    spread_history.push(spread_bps);
-                    if spread_history.len() > 100 {
-                        spread_history.remove(0);
+        let Some(ob) = client.get_orderbook(trading_pair) else {
+            continue;
+        };
+
+        for event in monitor.check_orderbook(&ob) {
+            let baseline = event.baseline_bps.unwrap_or(event.spread_bps);
+            match event.kind {
+                LiquidityEventKind::SpreadBlowout => {
+                    println!("\n⚠️  WIDE SPREAD WARNING!");
+                    println!(
+                        "   Spread: {:.1} bps (baseline: {:.1} bps)",
+                        event.spread_bps, baseline
+                    );
+
+                    #[cfg(feature = "telegram")]
+                    if let Some(ref bot) = bot {
+                        let _ = bot
+                            .send_spread_alert(
+                                &event.symbol,
+                                event.spread_bps,
+                                baseline,
+                                event.spread_bps / baseline,
+                            )
+                            .await;
                     }
-
-                    let avg_spread = if !spread_history.is_empty() {
-                        spread_history.iter().sum::<f64>() / spread_history.len() as f64
-                    } else {
-                        spread_bps
-                    };
-
-                    // Display status
-                    println!("\n💧 Liquidity Status (Update #{})", update_count);
-                    println!("{}", "─".repeat(70));
-                    println!("   Total Liquidity: {:.2} BTC", total_liquidity);
-                    println!("   Bid Volume: {:.2} BTC", total_bid_volume);
-                    println!("   Ask Volume: {:.2} BTC", total_ask_volume);
-                    println!("   Best Bid: ${:.2}", best_bid);
-                    println!("   Best Ask: ${:.2}", best_ask);
-                    println!("   Spread: ${:.2} ({:.1} bps)", spread, spread_bps);
-                    println!("   Avg Spread: {:.1} bps", avg_spread);
-
-                    // Check for low liquidity
-                    if total_liquidity < min_total_liquidity {
-                        println!("\n⚠️  LOW LIQUIDITY WARNING!");
-                        println!(
-                            "   Total: {:.2} BTC (threshold: {} BTC)",
-                            total_liquidity, min_total_liquidity
+                }
+                LiquidityEventKind::Drought => {
+                    println!("\n⚠️  LOW LIQUIDITY WARNING!");
+                    println!(
+                        "   Bid: {:.2} BTC, Ask: {:.2} BTC (minimum: {} BTC per side)",
+                        event.bid_size, event.ask_size, min_side_liquidity
+                    );
+
+                    #[cfg(feature = "telegram")]
+                    if let Some(ref bot) = bot {
+                        let message = format!(
+                            "⚠️ {} Low Liquidity Alert\n\
+                            \n\
+                            Bid Volume: {:.2} BTC\n\
+                            Ask Volume: {:.2} BTC\n\
+                            \n\
+                            💡 Low liquidity may indicate increased volatility risk.",
+                            event.symbol, event.bid_size, event.ask_size
                         );
-
-                        #[cfg(feature = "telegram")]
-                        if let Some(ref bot) = bot {
-                            let message = format!(
-                                "⚠️ {} Low Liquidity Alert\n\
-                                \n\
-                                Total Liquidity: {:.2} BTC\n\
-                                Threshold: {} BTC\n\
-                                \n\
-                                Bid Volume: {:.2} BTC\n\
-                                Ask Volume: {:.2} BTC\n\
-                                \n\
-                                💡 Low liquidity may indicate increased volatility risk.",
-                                trading_pair,
-                                total_liquidity,
-                                min_total_liquidity,
-                                total_bid_volume,
-                                total_ask_volume
-                            );
-                            let _ = bot.send_alert(&message).await;
-                        }
+                        let _ = bot.send_alert(&message).await;
                     }
+                }
+                LiquidityEventKind::SpreadNormal => println!("\n✅ Spread back to normal"),
+                LiquidityEventKind::DroughtEnded => println!("\n✅ Liquidity recovered"),
+            }
+        }
 
-                    // Check for wide spread
-                    if spread_bps > max_spread_bps {
-                        println!("\n⚠️  WIDE SPREAD WARNING!");
-                        println!(
-                            "   Spread: {:.1} bps (threshold: {} bps)",
-                            spread_bps, max_spread_bps
-                        );
-
-                        #[cfg(feature = "telegram")]
-                        if let Some(ref bot) = bot {
-                            if spread_bps > avg_spread * 2.0 {
-                                let _ = bot
-                                    .send_spread_alert(
-                                        trading_pair,
-                                        spread_bps,
-                                        avg_spread,
-                                        spread_bps / avg_spread,
-                                    )
-                                    .await;
-                            }
-                        }
-                    }
+        if last_check.elapsed() >= check_interval {
+            // Calculate total liquidity
+            let total_bid_volume: f64 = ob.bids.values().sum();
+            let total_ask_volume: f64 = ob.asks.values().sum();
+            let total_liquidity = total_bid_volume + total_ask_volume;
+
+            if let (Some(best_bid), Some(best_ask), Some(spread_bps)) =
+                (ob.best_bid(), ob.best_ask(), ob.spread_bps())
+            {
+                // Display status
+                println!("\n💧 Liquidity Status (Update #{})", update_count);
+                println!("{}", "─".repeat(70));
+                println!("   Total Liquidity: {:.2} BTC", total_liquidity);
+                println!("   Bid Volume: {:.2} BTC", total_bid_volume);
+                println!("   Ask Volume: {:.2} BTC", total_ask_volume);
+                println!("   Best Bid: ${:.2}", best_bid);
+                println!("   Best Ask: ${:.2}", best_ask);
+                println!(
+                    "   Spread: ${:.2} ({:.1} bps)",
+                    best_ask - best_bid,
+                    spread_bps
+                );
+                if let Some(stats) = monitor.spread_stats(trading_pair) {
+                    println!("   Avg Spread: {:.1} bps", stats.mean);
+                }
 
-                    // Calculate imbalance
-                    #[cfg(feature = "analytics")]
-                    {
-                        let metrics = ob.imbalance_metrics();
-                        let signal = metrics.signal(0.15);
+                // Calculate imbalance
+                let metrics = ob.imbalance_metrics();
+                let signal = metrics.signal(0.15);
 
-                        println!("\n📊 Market Analytics:");
-                        println!("   Bid/Ask Ratio: {:.2}", metrics.bid_ask_ratio);
-                        println!("   Imbalance: {:+.2}%", metrics.imbalance_ratio * 100.0);
-                        println!("   Signal: {:?}", signal);
-                    }
+                println!("\n📊 Market Analytics:");
+                println!("   Bid/Ask Ratio: {:.2}", metrics.bid_ask_ratio);
+                println!("   Imbalance: {:+.2}%", metrics.imbalance_ratio * 100.0);
+                println!("   Signal: {:?}", signal);
 
-                    println!("{}", "─".repeat(70));
-                }
+                println!("{}", "─".repeat(70));
             }
 
             last_check = std::time::Instant::now();
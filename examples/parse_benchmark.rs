@@ -0,0 +1,64 @@
+//! Message Parsing Benchmark
+//!
+//! Measures `KrakyMessage::parse` on recorded-style frames, offline. Run it
+//! with and without the `simd` feature to compare the two parsers.
+//!
+//! ## Run
+//! ```bash
+//! cargo run --example parse_benchmark --features market-data --release
+//! cargo run --example parse_benchmark --features market-data,simd --release
+//! ```
+
+use kraky::messages::KrakyMessage;
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 200_000;
+
+const TRADE: &str = r#"{"channel":"trade","type":"update","data":[{"symbol":"BTC/USD","side":"buy","price":42000.5,"qty":0.01,"ord_type":"market","trade_id":67028120,"timestamp":"2024-01-15T10:00:00.123456Z"},{"symbol":"BTC/USD","side":"sell","price":42000.4,"qty":0.25,"ord_type":"limit","trade_id":67028121,"timestamp":"2024-01-15T10:00:00.123789Z"}]}"#;
+
+const TICKER: &str = r#"{"channel":"ticker","type":"update","data":[{"symbol":"BTC/USD","bid":42000.4,"bid_qty":1.25,"ask":42000.5,"ask_qty":0.5,"last":42000.5,"volume":1523.4,"vwap":41890.2,"low":41210.0,"high":42310.0,"change":512.3,"change_pct":1.23}]}"#;
+
+const BOOK: &str = r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":[{"price":42000.4,"qty":1.25},{"price":42000.1,"qty":0.5},{"price":41999.8,"qty":3.1}],"asks":[{"price":42000.5,"qty":0.5},{"price":42001.0,"qty":2.0}],"checksum":2439117997,"timestamp":"2024-01-15T10:00:00.123456Z"}]}"#;
+
+const HEARTBEAT: &str = r#"{"channel":"heartbeat"}"#;
+
+fn bench(name: &str, frame: &str) {
+    // Warm up
+    for _ in 0..ITERATIONS / 10 {
+        black_box(KrakyMessage::parse(black_box(frame)).unwrap());
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(KrakyMessage::parse(black_box(frame)).unwrap());
+    }
+    let elapsed = start.elapsed();
+
+    let per_message = elapsed.as_nanos() as f64 / f64::from(ITERATIONS);
+    let mb_per_sec = (frame.len() as f64 * f64::from(ITERATIONS)) / elapsed.as_secs_f64() / 1e6;
+    println!(
+        "  {:<10} {:>5} bytes {:>9.0} ns/msg {:>9.1} MB/s",
+        name,
+        frame.len(),
+        per_message,
+        mb_per_sec
+    );
+}
+
+fn main() {
+    let parser = if cfg!(feature = "simd") {
+        "simd-json"
+    } else {
+        "serde_json"
+    };
+    println!(
+        "KrakyMessage::parse ({}, {} iterations)",
+        parser, ITERATIONS
+    );
+
+    bench("trade", TRADE);
+    bench("ticker", TICKER);
+    bench("book", BOOK);
+    bench("heartbeat", HEARTBEAT);
+}
@@ -0,0 +1,71 @@
+//! 💾 Market Data Recorder - Collect Datasets Without Custom Code
+//!
+//! This example uses the built-in `Recorder` to write live trades, ticker
+//! updates and orderbook updates to files, one per channel and pair.
+//!
+//! ## What This Shows
+//! - Recording several channels with a single builder
+//! - CSV or JSON Lines output
+//! - File rotation by size
+//!
+//! ## Setup
+//! ```bash
+//! cargo run --example record_market_data --features recorder,trades,ticker
+//! ```
+//!
+//! Pass `jsonl` as the first argument to write JSON Lines instead of CSV.
+//!
+//! ## Output Files
+//! - `recordings/trade_BTCUSD_YYYYMMDD_HHMMSS.csv`
+//! - `recordings/ticker_BTCUSD_YYYYMMDD_HHMMSS.csv`
+//! - `recordings/book_BTCUSD_YYYYMMDD_HHMMSS.csv`
+
+use kraky::recorder::{RecordFormat, Recorder};
+use kraky::KrakyClient;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("\n╔══════════════════════════════════════════════════════════════╗");
+    println!("║           💾 Market Data Recorder - CSV / JSON Lines         ║");
+    println!("╚══════════════════════════════════════════════════════════════╝\n");
+
+    let format = match std::env::args().nth(1).as_deref() {
+        Some("jsonl") => RecordFormat::JsonLines,
+        _ => RecordFormat::Csv,
+    };
+    let duration = Duration::from_secs(30);
+
+    println!("📡 Connecting to Kraken WebSocket...");
+    let client = Arc::new(KrakyClient::connect().await?);
+    println!("✅ Connected!\n");
+
+    let recorder = Recorder::new(client, "recordings")
+        .with_format(format)
+        .rotate_size(10 * 1024 * 1024) // 10 MB per file
+        .trades("BTC/USD")
+        .ticker("BTC/USD")
+        .orderbook("BTC/USD", 10)
+        .start()
+        .await?;
+
+    println!(
+        "🚀 Recording BTC/USD ({}) for {} seconds...\n",
+        format.extension(),
+        duration.as_secs()
+    );
+
+    let start = tokio::time::Instant::now();
+    while start.elapsed() < duration {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        println!("   {} records written", recorder.records());
+    }
+
+    let files = recorder.stop().await?;
+    println!("\n✅ Done! Files written:");
+    for file in files {
+        println!("   {}", file.display());
+    }
+    Ok(())
+}
@@ -5,7 +5,7 @@
 //!
 //! ## Features
 //! - Simple configuration
-//! - Price threshold alerts
+//! - Price threshold alerts (`AlertManager`), saved to alerts.json
 //! - Telegram notifications
 //! - Perfect for beginners
 //!
@@ -16,7 +16,9 @@
 //! cargo run --example simple_price_alerts --features telegram-alerts
 //! ```
 
-use kraky::KrakyClient;
+#[cfg(feature = "telegram")]
+use kraky::Notifier;
+use kraky::{AlertManager, KrakyClient, PriceAlert, PriceTrigger};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -32,9 +34,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let price_high = 100_000.0; // Alert if BTC goes above $100k
     let price_low = 90_000.0; // Alert if BTC goes below $90k
 
+    // Repeating alerts re-arm once the price moves back past the level.
+    // Alerts armed in a previous run are restored from alerts.json.
+    let alerts_file = "alerts.json";
+    let mut alerts = AlertManager::load(alerts_file).unwrap_or_default();
+    if alerts.is_empty() {
+        alerts.add(PriceAlert::above("BTC/USD", price_high).repeating());
+        alerts.add(PriceAlert::below("BTC/USD", price_low).repeating());
+    }
+
     println!("⚙️  Alert Configuration:");
-    println!("   High Alert: ${:.2}", price_high);
-    println!("   Low Alert:  ${:.2}\n", price_low);
+    for (id, alert) in alerts.alerts() {
+        println!("   #{} {}: {}", id, alert.symbol, alert.trigger);
+    }
+    println!();
 
     // ═══════════════════════════════════════════════════════════════════════
     // STEP 2: Setup Telegram
@@ -91,8 +104,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // STEP 5: Monitor Prices and Send Alerts
     // ═══════════════════════════════════════════════════════════════════════
 
-    let mut alert_sent_high = false;
-    let mut alert_sent_low = false;
     let mut update_count = 0;
 
     while let Some(tick) = ticker.next().await {
@@ -107,50 +118,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             );
         }
 
-        // Check HIGH threshold
-        if current_price >= price_high && !alert_sent_high {
-            println!("\n🚨 HIGH PRICE ALERT!");
-            println!("   Current: ${:.2}", current_price);
-            println!("   Threshold: ${:.2}", price_high);
-
-            #[cfg(feature = "telegram")]
-            {
-                bot.send_threshold_alert(
-                    "BTC/USD",
-                    current_price,
-                    price_high,
-                    true, // above threshold
-                )
-                .await?;
-
-                println!("   ✅ Telegram alert sent!");
-            }
-
-            alert_sent_high = true;
-            alert_sent_low = false; // Reset low alert
-        }
-
-        // Check LOW threshold
-        if current_price <= price_low && !alert_sent_low {
-            println!("\n🚨 LOW PRICE ALERT!");
-            println!("   Current: ${:.2}", current_price);
-            println!("   Threshold: ${:.2}", price_low);
+        for fired in alerts.on_ticker(&tick) {
+            let above = matches!(fired.trigger, PriceTrigger::Above(_));
+            println!("\n🚨 {} PRICE ALERT!", if above { "HIGH" } else { "LOW" });
+            println!("   Current: ${:.2}", fired.price);
+            println!("   Threshold: ${:.2}", fired.level);
 
             #[cfg(feature = "telegram")]
             {
-                bot.send_threshold_alert(
-                    "BTC/USD",
-                    current_price,
-                    price_low,
-                    false, // below threshold
-                )
-                .await?;
+                bot.send_threshold_alert(&fired.symbol, fired.price, fired.level, above)
+                    .await?;
 
                 println!("   ✅ Telegram alert sent!");
             }
 
-            alert_sent_low = true;
-            alert_sent_high = false; // Reset high alert
+            alerts.save(alerts_file)?;
         }
     }
 
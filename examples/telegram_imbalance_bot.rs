@@ -36,7 +36,12 @@
 //! - **Real-world application** - Practical trading alert system
 //! - **Lightweight** - Only 800KB added when enabled
 
-use kraky::{ConnectionEvent, ImbalanceSignal, KrakyClient, TelegramNotifier};
+use kraky::{
+    AlertBridge, DivergenceConfig, DivergenceDetector, ImbalanceSignal, KrakyClient, Notifier,
+    SpreadMonitor, SummarySchedule, SummaryScheduler, TelegramNotifier, WhaleDetector, WhaleSide,
+    WhaleThreshold,
+};
+use std::sync::Arc;
 use std::time::Duration;
 
 #[tokio::main]
@@ -77,7 +82,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // ═══════════════════════════════════════════════════════════════════════
 
     println!("🔧 Initializing Kraky client...");
-    let client = KrakyClient::connect().await?;
+    let client = Arc::new(KrakyClient::connect().await?);
     println!("✅ Connected to Kraken WebSocket API\n");
 
     println!("🤖 Initializing Telegram bot...");
@@ -92,36 +97,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     .await?;
 
     // ═══════════════════════════════════════════════════════════════════════
-    // SUBSCRIBE: Set up connection events monitoring
+    // SUBSCRIBE: Forward connection, data gap and checksum events to Telegram
     // ═══════════════════════════════════════════════════════════════════════
 
-    let mut events = client.subscribe_events();
-    let bot_clone = TelegramNotifier::new(&bot_token, chat_id);
-
-    tokio::spawn(async move {
-        while let Some(event) = events.recv().await {
-            let message = match event {
-                ConnectionEvent::Connected => "✅ Connected to Kraken".to_string(),
-                ConnectionEvent::Disconnected(reason) => {
-                    format!("❌ Disconnected: {:?}", reason)
-                }
-                ConnectionEvent::Reconnecting(n) => {
-                    format!("🔄 Reconnecting (attempt #{})", n)
-                }
-                ConnectionEvent::Reconnected => "✅ Reconnected to Kraken".to_string(),
-                ConnectionEvent::ReconnectFailed(n, e) => {
-                    format!("⚠️ Reconnect failed #{}: {}", n, e)
-                }
-                ConnectionEvent::ReconnectExhausted => {
-                    "💀 Reconnection attempts exhausted".to_string()
-                }
-            };
-
-            if let Err(e) = bot_clone.send_alert(&message).await {
-                eprintln!("Failed to send event notification: {}", e);
-            }
-        }
-    });
+    let _event_alerts =
+        AlertBridge::new(Arc::new(TelegramNotifier::new(&bot_token, chat_id))).spawn(&client);
 
     // ═══════════════════════════════════════════════════════════════════════
     // SUBSCRIBE: Orderbook and Ticker for BTC/USD
@@ -136,6 +116,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("✅ Subscribed to orderbook (depth: 10)");
     println!("✅ Subscribed to ticker\n");
 
+    // Hourly summary of messages, price change and alerts sent
+    let summaries =
+        SummaryScheduler::new(Arc::clone(&client), SummarySchedule::Hourly { minute: 0 })
+            .pair(trading_pair)
+            .notifier(Arc::new(TelegramNotifier::new(&bot_token, chat_id)))
+            .spawn();
+    let counters = summaries.counters();
+
     // ═══════════════════════════════════════════════════════════════════════
     // CONFIGURATION: Alert thresholds
     // ═══════════════════════════════════════════════════════════════════════
@@ -183,9 +171,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut last_price: Option<f64> = None;
 
     // NEW: State tracking for advanced features
-    let mut spread_history: Vec<f64> = Vec::new();
+    let mut spread_monitor = SpreadMonitor::new(100).with_min_samples(20);
+    let mut whale_detector =
+        WhaleDetector::new(WhaleThreshold::BaseQty(whale_volume_threshold)).with_depth(3);
     let mut last_whale_check = std::time::Instant::now();
-    let mut price_history: Vec<(std::time::Instant, f64)> = Vec::new();
+    let mut divergence_detector = DivergenceDetector::new(
+        DivergenceConfig::default()
+            .with_lookback(Duration::from_secs(120))
+            .with_min_price_change_pct(0.5)
+            .with_imbalance_threshold(imbalance_threshold),
+    );
 
     loop {
         tokio::select! {
@@ -207,6 +202,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             eprintln!("Failed to send imbalance alert: {}", e);
                         } else {
                             alert_count += 1;
+                            counters.alert();
                             println!("✅ Alert #{} sent successfully", alert_count);
                         }
 
@@ -217,32 +213,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     // NEW FEATURE: Whale Alert - Detect large orders
                     // ═══════════════════════════════════════════════════════════
                     if last_whale_check.elapsed() >= Duration::from_secs(10) {
-                        // Check top 3 bids and asks for whale orders
-                        for (i, (price, volume)) in ob.bids.iter().take(3).enumerate() {
-                            if *volume >= whale_volume_threshold {
-                                let price_f64 = price.0;  // Extract f64 from OrderedFloat
-                                println!("🐋 Whale detected: {} BTC bid @ ${:.2}", volume, price_f64);
-                                if let Err(e) = bot.send_whale_alert(trading_pair, "bid", price_f64, *volume).await {
-                                    eprintln!("Failed to send whale alert: {}", e);
-                                } else {
-                                    alert_count += 1;
-                                    println!("✅ Whale alert #{} sent (bid #{}, {} BTC)", alert_count, i + 1, volume);
-                                }
-                                break; // Only alert once per check
-                            }
-                        }
-
-                        for (i, (price, volume)) in ob.asks.iter().take(3).enumerate() {
-                            if *volume >= whale_volume_threshold {
-                                let price_f64 = price.0;  // Extract f64 from OrderedFloat
-                                println!("🐋 Whale detected: {} BTC ask @ ${:.2}", volume, price_f64);
-                                if let Err(e) = bot.send_whale_alert(trading_pair, "ask", price_f64, *volume).await {
-                                    eprintln!("Failed to send whale alert: {}", e);
-                                } else {
-                                    alert_count += 1;
-                                    println!("✅ Whale alert #{} sent (ask #{}, {} BTC)", alert_count, i + 1, volume);
-                                }
-                                break; // Only alert once per check
+                        // Check top 3 bids and asks; each whale order is only reported once
+                        for whale in whale_detector.check_orderbook(&ob) {
+                            let side = match whale.side {
+                                WhaleSide::Buy => "bid",
+                                WhaleSide::Sell => "ask",
+                            };
+                            println!("🐋 Whale detected: {} BTC {} @ ${:.2}", whale.qty, side, whale.price);
+                            if let Err(e) = bot.send_whale_alert(trading_pair, side, whale.price, whale.qty).await {
+                                eprintln!("Failed to send whale alert: {}", e);
+                            } else {
+                                alert_count += 1;
+                                counters.alert();
+                                println!("✅ Whale alert #{} sent ({}, {} BTC)", alert_count, side, whale.qty);
                             }
                         }
 
@@ -252,29 +235,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     // ═══════════════════════════════════════════════════════════
                     // NEW FEATURE: Spread Volatility Alert
                     // ═══════════════════════════════════════════════════════════
-                    if let (Some(best_bid), Some(best_ask)) = (ob.best_bid(), ob.best_ask()) {
-                        let spread = best_ask - best_bid;
-                        let mid_price = (best_bid + best_ask) / 2.0;
-                        let spread_bps = (spread / mid_price) * 10000.0;
-
-                        // Track spread history (keep last 100 values)
-                        spread_history.push(spread_bps);
-                        if spread_history.len() > 100 {
-                            spread_history.remove(0);
-                        }
-
-                        // Calculate average spread (need at least 20 samples)
-                        if spread_history.len() >= 20 {
-                            let avg_spread: f64 = spread_history.iter().sum::<f64>() / spread_history.len() as f64;
-                            let multiplier = spread_bps / avg_spread;
+                    // Track rolling spread statistics (last 100 samples, min 20)
+                    if let Some(spread_bps) = spread_monitor.record_orderbook(&ob) {
+                        if let Some(stats) = spread_monitor.stats(trading_pair) {
+                            let multiplier = stats.current_multiplier();
 
                             // Alert if spread is significantly wider than average
                             if multiplier >= spread_multiplier_threshold {
                                 println!("⚠️ Spread volatility: {:.1} bps ({:.1}x average)", spread_bps, multiplier);
-                                if let Err(e) = bot.send_spread_alert(trading_pair, spread_bps, avg_spread, multiplier).await {
+                                if let Err(e) = bot.send_spread_alert(trading_pair, spread_bps, stats.mean, multiplier).await {
                                     eprintln!("Failed to send spread alert: {}", e);
                                 } else {
                                     alert_count += 1;
+                                    counters.alert();
                                     println!("✅ Spread alert #{} sent", alert_count);
                                 }
                             }
@@ -284,35 +257,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     // ═══════════════════════════════════════════════════════════
                     // NEW FEATURE: Order Flow Divergence Detection
                     // ═══════════════════════════════════════════════════════════
+                    // Price moved >0.5% over the last 2 minutes against orderbook pressure
                     if let Some(current_price) = last_price {
-                        // Track price with timestamp
-                        price_history.push((std::time::Instant::now(), current_price));
-
-                        // Keep only last 5 minutes of price history
-                        price_history.retain(|(time, _)| time.elapsed() < Duration::from_secs(300));
-
-                        // Calculate price change over last 2 minutes (if enough data)
-                        if let Some(&(_oldest_time, oldest_price)) = price_history.iter()
-                            .find(|(time, _)| time.elapsed() >= Duration::from_secs(120)) {
-
-                            let price_change_pct = ((current_price - oldest_price) / oldest_price) * 100.0;
-
-                            // Only check for divergence if price moved significantly (>0.5%)
-                            if price_change_pct.abs() >= 0.5 {
-                                // Check if this is a divergence
-                                let is_divergence =
-                                    (price_change_pct > 0.0 && matches!(signal, ImbalanceSignal::Bearish)) ||
-                                    (price_change_pct < 0.0 && matches!(signal, ImbalanceSignal::Bullish));
-
-                                if is_divergence {
-                                    println!("⚡ Divergence: Price {:+.2}% but orderbook {:?}", price_change_pct, signal);
-                                    if let Err(e) = bot.send_divergence_alert(trading_pair, price_change_pct, signal).await {
-                                        eprintln!("Failed to send divergence alert: {}", e);
-                                    } else {
-                                        alert_count += 1;
-                                        println!("✅ Divergence alert #{} sent", alert_count);
-                                    }
-                                }
+                        if let Some(divergence) = divergence_detector.update(trading_pair, current_price, metrics.imbalance_ratio) {
+                            println!("⚡ Divergence: Price {:+.2}% but orderbook {:?}", divergence.price_change_pct, divergence.signal);
+                            if let Err(e) = bot.send_divergence_alert(trading_pair, divergence.price_change_pct, divergence.signal).await {
+                                eprintln!("Failed to send divergence alert: {}", e);
+                            } else {
+                                alert_count += 1;
+                                counters.alert();
+                                println!("✅ Divergence alert #{} sent", alert_count);
                             }
                         }
                     }
@@ -354,6 +308,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             eprintln!("Failed to send trade alert: {}", e);
                         } else {
                             alert_count += 1;
+                            counters.alert();
                             println!("✅ Trade alert #{} sent", alert_count);
                         }
                     }
@@ -388,34 +343,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     last_price_check = std::time::Instant::now();
                 }
             }
-
-            // Periodic status update
-            _ = tokio::time::sleep(Duration::from_secs(60)) => {
-                if let Some(ob) = client.get_orderbook(trading_pair) {
-                    if let (Some(bid), Some(ask)) = (ob.best_bid(), ob.best_ask()) {
-                        let summary = format!(
-                            "📊 *Hourly Summary - {}*\n\
-                            \n\
-                            Updates Received: {}\n\
-                            Alerts Sent: {}\n\
-                            Best Bid: ${:.2}\n\
-                            Best Ask: ${:.2}\n\
-                            Current Price: ${:.2}\n\
-                            Status: 🟢 Active",
-                            trading_pair,
-                            orderbook_update_count,
-                            alert_count,
-                            bid,
-                            ask,
-                            last_price.unwrap_or(0.0)
-                        );
-
-                        if let Err(e) = bot.send_alert(&summary).await {
-                            eprintln!("Failed to send hourly summary: {}", e);
-                        }
-                    }
-                }
-            }
         }
     }
 }
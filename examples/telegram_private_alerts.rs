@@ -26,7 +26,7 @@
 //!    cargo run --example telegram_private_alerts --features telegram,private
 //!    ```
 
-use kraky::{BalanceUpdate, ExecutionUpdate, OrderUpdate, TelegramNotifier};
+use kraky::{BalanceUpdate, ExecutionUpdate, Notifier, OrderUpdate, TelegramNotifier};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -47,7 +47,7 @@
 //! - To enable real trading, set ENABLE_REAL_TRADING=true
 
 use kraky::{
-    AmendOrderParams, Credentials, KrakyClient, OrderParams, OrderSide, TelegramNotifier,
+    AmendOrderParams, Credentials, KrakyClient, Notifier, OrderParams, OrderSide, TelegramNotifier,
 };
 
 #[tokio::main]
@@ -69,12 +69,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("⚙️  Loading configuration...\n");
 
     // Kraken API credentials
-    let api_key =
-        std::env::var("KRAKEN_API_KEY").expect("Please set KRAKEN_API_KEY environment variable");
-    let api_secret = std::env::var("KRAKEN_API_SECRET")
-        .expect("Please set KRAKEN_API_SECRET environment variable");
-
-    let credentials = Credentials::new(api_key, api_secret);
+    let credentials = Credentials::from_env()
+        .expect("Please set KRAKEN_API_KEY and KRAKEN_API_SECRET environment variables");
 
     // Telegram bot
     let bot_token = std::env::var("TELEGRAM_BOT_TOKEN")
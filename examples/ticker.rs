@@ -43,6 +43,15 @@ async fn main() -> anyhow::Result<()> {
         println!("  24h VWAP:      ${:.2}", ticker.vwap);
         println!("  24h High:      ${:.2}", ticker.high);
         println!("  24h Low:       ${:.2}", ticker.low);
+        println!(
+            "  Spread:        ${:.2} ({:.1} bps)",
+            ticker.spread(),
+            ticker.spread_bps().unwrap_or_default()
+        );
+        println!(
+            "  From 24h High: {:.2}%",
+            ticker.pct_from_high().unwrap_or_default()
+        );
         println!();
 
         count += 1;
@@ -16,7 +16,9 @@
 //! cargo run --example whale_watcher --features telegram-alerts
 //! ```
 
-use kraky::KrakyClient;
+#[cfg(feature = "telegram")]
+use kraky::Notifier;
+use kraky::{KrakyClient, WhaleDetector, WhaleSide, WhaleThreshold};
 use std::time::Duration;
 
 #[tokio::main]
@@ -44,7 +46,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Configuration
     let trading_pair = "BTC/USD";
-    let whale_threshold_btc = 1.0; // Orders >= 10 BTC are "whales"
+    let whale_threshold_btc = 1.0; // Orders >= 1 BTC are "whales"
     let check_interval = Duration::from_secs(5);
 
     println!("⚙️  Configuration:");
@@ -80,6 +82,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("   Monitoring for orders >= {} BTC", whale_threshold_btc);
     println!("   Press Ctrl+C to stop\n");
 
+    // Whale detection: each resting whale order is only reported once
+    let mut detector = WhaleDetector::new(WhaleThreshold::BaseQty(whale_threshold_btc));
+
     // Track state
     let mut update_count = 0;
     let mut whale_count = 0;
@@ -92,49 +97,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Only check periodically to avoid spam
         if last_check.elapsed() >= check_interval {
             if let Some(ob) = client.get_orderbook(trading_pair) {
-                // Check top 10 bids for whales
-                for (i, (price, volume)) in ob.bids.iter().take(10).enumerate() {
-                    if *volume >= whale_threshold_btc {
-                        whale_count += 1;
-                        let price_f64 = price.0;
-
-                        println!("🐋 WHALE DETECTED!");
-                        println!("   Side: BID (Buy)");
-                        println!("   Position: #{}", i + 1);
-                        println!("   Volume: {:.4} BTC", volume);
-                        println!("   Price: ${:.2}", price_f64);
-                        println!("   Total Value: ${:.2}\n", volume * price_f64);
-
-                        #[cfg(feature = "telegram")]
-                        {
-                            bot.send_whale_alert(trading_pair, "bid", price_f64, *volume)
-                                .await?;
-                        }
-
-                        break; // Only alert once per check
-                    }
-                }
-
-                // Check top 10 asks for whales
-                for (i, (price, volume)) in ob.asks.iter().take(10).enumerate() {
-                    if *volume >= whale_threshold_btc {
-                        whale_count += 1;
-                        let price_f64 = price.0;
-
-                        println!("🐋 WHALE DETECTED!");
-                        println!("   Side: ASK (Sell)");
-                        println!("   Position: #{}", i + 1);
-                        println!("   Volume: {:.4} BTC", volume);
-                        println!("   Price: ${:.2}", price_f64);
-                        println!("   Total Value: ${:.2}\n", volume * price_f64);
-
-                        #[cfg(feature = "telegram")]
-                        {
-                            bot.send_whale_alert(trading_pair, "ask", price_f64, *volume)
-                                .await?;
-                        }
-
-                        break; // Only alert once per check
+                for whale in detector.check_orderbook(&ob) {
+                    whale_count += 1;
+                    let side = match whale.side {
+                        WhaleSide::Buy => "bid",
+                        WhaleSide::Sell => "ask",
+                    };
+
+                    println!("🐋 WHALE DETECTED!");
+                    println!("   Side: {} ({})", side.to_uppercase(), whale.side);
+                    println!("   Volume: {:.4} BTC", whale.qty);
+                    println!("   Price: ${:.2}", whale.price);
+                    println!("   Total Value: ${:.2}\n", whale.notional());
+
+                    #[cfg(feature = "telegram")]
+                    {
+                        bot.send_whale_alert(trading_pair, side, whale.price, whale.qty)
+                            .await?;
                     }
                 }
 
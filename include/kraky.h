@@ -0,0 +1,86 @@
+/*
+ * C API of the kraky Kraken WebSocket client
+ *
+ * Build the library with:
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * kraky_subscribe_trades needs the `trades` feature and
+ * kraky_subscribe_book the `orderbook` feature (both on by default).
+ * Callbacks run on the handle's worker threads.
+ */
+
+#ifndef KRAKY_H
+#define KRAKY_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Success */
+#define KRAKY_OK 0
+/* Failure; see kraky_last_error */
+#define KRAKY_ERR (-1)
+
+/* Taker bought */
+#define KRAKY_SIDE_BUY 0
+/* Taker sold */
+#define KRAKY_SIDE_SELL 1
+
+/* Client, runtime and callback tasks */
+typedef struct KrakyHandle KrakyHandle;
+
+/* Trade; the strings are only valid during the callback */
+typedef struct KrakyTrade {
+    const char *symbol;
+    int side;
+    double price;
+    double qty;
+    int64_t trade_id;
+    const char *timestamp;
+} KrakyTrade;
+
+/* One price level of a KrakyBook */
+typedef struct KrakyLevel {
+    double price;
+    double qty;
+} KrakyLevel;
+
+/* Managed book; the symbol and level arrays are only valid during the callback */
+typedef struct KrakyBook {
+    const char *symbol;
+    const KrakyLevel *bids;
+    size_t bid_count;
+    const KrakyLevel *asks;
+    size_t ask_count;
+    uint64_t sequence;
+} KrakyBook;
+
+typedef void (*KrakyTradeCallback)(const KrakyTrade *trade, void *user_data);
+typedef void (*KrakyBookCallback)(const KrakyBook *book, void *user_data);
+
+/* Connect to Kraken, or to `url` when it isn't NULL; returns NULL on failure */
+KrakyHandle *kraky_connect(const char *url);
+
+/* Call `callback` with every trade of `symbol` */
+int kraky_subscribe_trades(const KrakyHandle *handle, const char *symbol,
+                           KrakyTradeCallback callback, void *user_data);
+
+/* Call `callback` with the top `depth` levels of `symbol`'s book after every update */
+int kraky_subscribe_book(const KrakyHandle *handle, const char *symbol, uint32_t depth,
+                         KrakyBookCallback callback, void *user_data);
+
+/* Disconnect, stop all callbacks and free the handle */
+void kraky_shutdown(KrakyHandle *handle);
+
+/* Message of the last error on the calling thread, or NULL */
+const char *kraky_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* KRAKY_H */
@@ -0,0 +1,441 @@
+//! Python bindings for kraky
+//!
+//! Exposes the client, subscriptions as async iterators, the managed
+//! orderbook and its imbalance analytics to Python:
+//!
+//! ```python
+//! import asyncio
+//! import kraky
+//!
+//! async def main():
+//!     client = await kraky.Client.connect()
+//!     async for book in await client.subscribe_orderbook("BTC/USD", 10):
+//!         print(book.mid_price(), book.imbalance(), book.signal(0.1))
+//!
+//! asyncio.run(main())
+//! ```
+//!
+//! The connection runs on kraky's background tokio runtime; Python's
+//! asyncio loop only awaits the subscription channels. Build with
+//! `maturin develop` from this directory.
+
+use kraky::models::{ImbalanceSignal, Orderbook as RustOrderbook, OrderbookUpdate};
+use kraky::models::{Ticker as RustTicker, Trade as RustTrade};
+use kraky::{KrakyClient, Subscription as RustSubscription};
+use pyo3::exceptions::{PyConnectionError, PyStopAsyncIteration};
+use pyo3::prelude::*;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll, Wake, Waker};
+use tokio::sync::Mutex;
+
+/// Convert a kraky error to a Python exception
+fn py_err(e: kraky::KrakyError) -> PyErr {
+    PyConnectionError::new_err(e.to_string())
+}
+
+/// Whether the interpreter is shutting down
+///
+/// Wakers hold it for reading while they call into Python from kraky's
+/// runtime threads; the `atexit` hook takes it for writing, so no wake
+/// reaches Python once finalization starts.
+static SHUTDOWN: RwLock<bool> = RwLock::new(false);
+
+/// Stop waking Python coroutines; registered with `atexit`
+#[pyfunction]
+fn _shutdown(py: Python<'_>) {
+    // Wakers in flight need the GIL to finish
+    py.allow_threads(|| *SHUTDOWN.write().unwrap_or_else(|e| e.into_inner()) = true);
+}
+
+/// Waker passing wakes on to a coroutine until shutdown
+struct GatedWaker(Waker);
+
+impl Wake for GatedWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let shutdown = SHUTDOWN.read().unwrap_or_else(|e| e.into_inner());
+        if !*shutdown {
+            self.0.wake_by_ref();
+        }
+    }
+}
+
+/// Future awaited by a Python coroutine, woken through a [`GatedWaker`]
+struct Gated<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for Gated<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let waker = Waker::from(Arc::new(GatedWaker(cx.waker().clone())));
+        self.0.as_mut().poll(&mut Context::from_waker(&waker))
+    }
+}
+
+/// Await a kraky future from a Python coroutine
+fn gated<F: Future>(future: F) -> Gated<F> {
+    Gated(Box::pin(future))
+}
+
+/// Kraken WebSocket v2 client
+#[pyclass(module = "kraky", frozen)]
+struct Client {
+    inner: Arc<KrakyClient>,
+}
+
+#[pymethods]
+impl Client {
+    /// Connect to Kraken, or to `url` when given
+    #[staticmethod]
+    #[pyo3(signature = (url=None))]
+    async fn connect(url: Option<String>) -> PyResult<Client> {
+        let mut builder = KrakyClient::builder();
+        if let Some(url) = &url {
+            builder = builder.url(url);
+        }
+        let inner = gated(builder.connect()).await.map_err(py_err)?;
+        Ok(Client {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Subscribe to a pair's book, yielding the managed `Orderbook` after
+    /// every update
+    async fn subscribe_orderbook(&self, symbol: String, depth: u32) -> PyResult<Subscription> {
+        let client = Arc::clone(&self.inner);
+        let updates = gated(client.subscribe_orderbook(symbol.as_str(), depth))
+            .await
+            .map_err(py_err)?;
+        Ok(Subscription::new(Stream::Book {
+            client,
+            symbol,
+            updates,
+        }))
+    }
+
+    /// Subscribe to a pair's trades, yielding `Trade`s
+    async fn subscribe_trades(&self, symbol: String) -> PyResult<Subscription> {
+        let trades = gated(self.inner.subscribe_trades(symbol.as_str()))
+            .await
+            .map_err(py_err)?;
+        Ok(Subscription::new(Stream::Trades(trades)))
+    }
+
+    /// Subscribe to a pair's ticker, yielding `Ticker`s
+    async fn subscribe_ticker(&self, symbol: String) -> PyResult<Subscription> {
+        let tickers = gated(self.inner.subscribe_ticker(symbol.as_str()))
+            .await
+            .map_err(py_err)?;
+        Ok(Subscription::new(Stream::Ticker(tickers)))
+    }
+
+    /// Get the managed book of a subscribed pair, or `None`
+    fn orderbook(&self, symbol: &str) -> Option<Orderbook> {
+        self.inner.get_orderbook(symbol).map(Orderbook)
+    }
+
+    /// Check whether the client is connected
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    /// Close the connection and stop reconnecting
+    fn disconnect(&self) {
+        self.inner.disconnect();
+    }
+}
+
+/// Source of a Python subscription
+enum Stream {
+    Book {
+        client: Arc<KrakyClient>,
+        symbol: String,
+        updates: RustSubscription<Arc<OrderbookUpdate>>,
+    },
+    Trades(RustSubscription<Arc<RustTrade>>),
+    Ticker(RustSubscription<Arc<RustTicker>>),
+}
+
+/// Next item of a stream, converted outside the GIL-free await
+enum Item {
+    Book(RustOrderbook),
+    Trade(RustTrade),
+    Ticker(RustTicker),
+}
+
+impl Stream {
+    async fn next(&mut self) -> Option<Item> {
+        match self {
+            Stream::Book {
+                client,
+                symbol,
+                updates,
+            } => loop {
+                updates.next().await?;
+                if let Some(book) = client.get_orderbook(symbol.as_str()) {
+                    return Some(Item::Book(book));
+                }
+            },
+            Stream::Trades(trades) => trades.next().await.map(|t| Item::Trade((*t).clone())),
+            Stream::Ticker(tickers) => tickers.next().await.map(|t| Item::Ticker((*t).clone())),
+        }
+    }
+}
+
+/// Async iterator over a subscription's messages
+#[pyclass(module = "kraky", frozen)]
+struct Subscription {
+    stream: Arc<Mutex<Stream>>,
+}
+
+impl Subscription {
+    fn new(stream: Stream) -> Self {
+        Self {
+            stream: Arc::new(Mutex::new(stream)),
+        }
+    }
+}
+
+#[pymethods]
+impl Subscription {
+    fn __aiter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __anext__<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        slf.call_method0("next")
+    }
+
+    /// Wait for the next message; raises `StopAsyncIteration` once the
+    /// subscription ends
+    async fn next(&self) -> PyResult<PyObject> {
+        let stream = Arc::clone(&self.stream);
+        let item = gated(async move { stream.lock().await.next().await }).await;
+        Python::with_gil(|py| match item {
+            Some(Item::Book(book)) => Ok(Orderbook(book).into_pyobject(py)?.into_any().unbind()),
+            Some(Item::Trade(trade)) => Ok(Trade(trade).into_pyobject(py)?.into_any().unbind()),
+            Some(Item::Ticker(ticker)) => Ok(Ticker(ticker).into_pyobject(py)?.into_any().unbind()),
+            None => Err(PyStopAsyncIteration::new_err(())),
+        })
+    }
+}
+
+/// Managed orderbook with imbalance analytics
+#[pyclass(module = "kraky", frozen)]
+#[derive(Clone)]
+struct Orderbook(RustOrderbook);
+
+#[pymethods]
+impl Orderbook {
+    #[getter]
+    fn symbol(&self) -> &str {
+        &self.0.symbol
+    }
+
+    #[getter]
+    fn sequence(&self) -> u64 {
+        self.0.sequence
+    }
+
+    /// Top `n` bids as `(price, qty)`, best first
+    #[pyo3(signature = (n=10))]
+    fn bids(&self, n: usize) -> Vec<(f64, f64)> {
+        self.0
+            .top_bids(n)
+            .iter()
+            .map(|l| (l.price, l.qty))
+            .collect()
+    }
+
+    /// Top `n` asks as `(price, qty)`, best first
+    #[pyo3(signature = (n=10))]
+    fn asks(&self, n: usize) -> Vec<(f64, f64)> {
+        self.0
+            .top_asks(n)
+            .iter()
+            .map(|l| (l.price, l.qty))
+            .collect()
+    }
+
+    fn best_bid(&self) -> Option<f64> {
+        self.0.best_bid()
+    }
+
+    fn best_ask(&self) -> Option<f64> {
+        self.0.best_ask()
+    }
+
+    fn spread(&self) -> Option<f64> {
+        self.0.spread()
+    }
+
+    fn mid_price(&self) -> Option<f64> {
+        self.0.mid_price()
+    }
+
+    fn spread_bps(&self) -> Option<f64> {
+        self.0.spread_bps()
+    }
+
+    /// Volume imbalance over the whole book, from -1.0 (asks) to 1.0 (bids)
+    fn imbalance(&self) -> f64 {
+        self.0.imbalance()
+    }
+
+    /// Volume imbalance over the top `n` levels
+    fn imbalance_top_n(&self, n: usize) -> f64 {
+        self.0.imbalance_top_n(n)
+    }
+
+    /// Volume imbalance within `depth_percent` of the mid price
+    fn imbalance_within_depth(&self, depth_percent: f64) -> Option<f64> {
+        self.0.imbalance_within_depth(depth_percent)
+    }
+
+    /// `"bullish"`, `"bearish"` or `"neutral"` at an imbalance threshold
+    fn signal(&self, threshold: f64) -> &'static str {
+        match self.0.imbalance_metrics().signal(threshold) {
+            ImbalanceSignal::Bullish => "bullish",
+            ImbalanceSignal::Bearish => "bearish",
+            ImbalanceSignal::Neutral => "neutral",
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Orderbook(symbol={:?}, bids={}, asks={}, sequence={})",
+            self.0.symbol,
+            self.0.bids.len(),
+            self.0.asks.len(),
+            self.0.sequence
+        )
+    }
+}
+
+/// A trade
+#[pyclass(module = "kraky", frozen)]
+struct Trade(RustTrade);
+
+#[pymethods]
+impl Trade {
+    #[getter]
+    fn symbol(&self) -> &str {
+        &self.0.symbol
+    }
+
+    /// `"buy"` or `"sell"`, from the taker's side
+    #[getter]
+    fn side(&self) -> String {
+        self.0.side.to_string()
+    }
+
+    #[getter]
+    fn price(&self) -> f64 {
+        self.0.price
+    }
+
+    #[getter]
+    fn qty(&self) -> f64 {
+        self.0.qty
+    }
+
+    #[getter]
+    fn trade_id(&self) -> i64 {
+        self.0.trade_id
+    }
+
+    #[getter]
+    fn timestamp(&self) -> &str {
+        &self.0.timestamp
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Trade(symbol={:?}, side={:?}, price={}, qty={})",
+            self.0.symbol,
+            self.0.side.to_string(),
+            self.0.price,
+            self.0.qty
+        )
+    }
+}
+
+/// A ticker update
+#[pyclass(module = "kraky", frozen)]
+struct Ticker(RustTicker);
+
+#[pymethods]
+impl Ticker {
+    #[getter]
+    fn symbol(&self) -> &str {
+        &self.0.symbol
+    }
+
+    #[getter]
+    fn last(&self) -> f64 {
+        self.0.last
+    }
+
+    #[getter]
+    fn bid(&self) -> f64 {
+        self.0.bid
+    }
+
+    #[getter]
+    fn ask(&self) -> f64 {
+        self.0.ask
+    }
+
+    #[getter]
+    fn volume(&self) -> f64 {
+        self.0.volume
+    }
+
+    #[getter]
+    fn vwap(&self) -> f64 {
+        self.0.vwap
+    }
+
+    #[getter]
+    fn low(&self) -> f64 {
+        self.0.low
+    }
+
+    #[getter]
+    fn high(&self) -> f64 {
+        self.0.high
+    }
+
+    #[getter]
+    fn change_pct(&self) -> f64 {
+        self.0.change_pct
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Ticker(symbol={:?}, last={}, change_pct={})",
+            self.0.symbol, self.0.last, self.0.change_pct
+        )
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "kraky")]
+fn kraky_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Client>()?;
+    m.add_class::<Subscription>()?;
+    m.add_class::<Orderbook>()?;
+    m.add_class::<Trade>()?;
+    m.add_class::<Ticker>()?;
+
+    let shutdown = wrap_pyfunction!(_shutdown, m)?;
+    m.py()
+        .import("atexit")?
+        .call_method1("register", (shutdown,))?;
+    Ok(())
+}
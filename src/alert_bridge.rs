@@ -0,0 +1,267 @@
+//! Forward client events to a notifier
+//!
+//! [`AlertBridge`] reads the client's [`ConnectionEvent`] stream and sends
+//! each event through [`Notifier::send_connection_event`]: connection
+//! changes, per-symbol data gaps and checksum failures. Events can be
+//! filtered by kind and symbol, and repeats of the same event within a
+//! cooldown are dropped so a flapping connection doesn't flood the chat.
+//!
+//! [`spawn`](AlertBridge::spawn) takes over the client's event stream, as
+//! [`KrakyClient::subscribe_events`] keeps a single receiver. To handle the
+//! events yourself as well, feed them to [`forward`](AlertBridge::forward)
+//! from your own loop.
+//!
+//! Requires the `notify` and `events` feature flags.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "telegram")]
+//! # {
+//! use kraky::{AlertBridge, ConnectionEvent, KrakyClient, TelegramNotifier};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! let bot = TelegramNotifier::new("bot_token", 123456789);
+//!
+//! let _alerts = AlertBridge::new(Arc::new(bot))
+//!     .filter(|event| !matches!(event, ConnectionEvent::Connected))
+//!     .symbols(["BTC/USD"])
+//!     .cooldown(Duration::from_secs(60))
+//!     .spawn(&client);
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+//!
+//! [`KrakyClient::subscribe_events`]: crate::KrakyClient::subscribe_events
+
+use crate::client::{ConnectionEvent, KrakyClient};
+use crate::notifier::Notifier;
+use std::collections::{HashMap, HashSet};
+use std::mem::Discriminant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Default time before the same event is sent again
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Decides whether an event is sent
+type EventFilter = Box<dyn Fn(&ConnectionEvent) -> bool + Send + Sync>;
+
+/// Sends client events through a [`Notifier`]
+pub struct AlertBridge {
+    notifier: Arc<dyn Notifier>,
+    filter: EventFilter,
+    symbols: Option<HashSet<String>>,
+    cooldown: Duration,
+    /// When each kind of event was last sent, per symbol
+    last_sent: HashMap<(Discriminant<ConnectionEvent>, String), Instant>,
+}
+
+impl std::fmt::Debug for AlertBridge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlertBridge")
+            .field("symbols", &self.symbols)
+            .field("cooldown", &self.cooldown)
+            .finish()
+    }
+}
+
+impl AlertBridge {
+    /// Create a bridge sending every event except reconnect attempts
+    ///
+    /// Attempts are skipped since the outcome follows as `Reconnected`,
+    /// `ReconnectFailed` or `ReconnectExhausted`.
+    pub fn new(notifier: Arc<dyn Notifier>) -> Self {
+        Self {
+            notifier,
+            filter: Box::new(|event| !matches!(event, ConnectionEvent::Reconnecting(_))),
+            symbols: None,
+            cooldown: DEFAULT_COOLDOWN,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Send only events `filter` returns `true` for, replacing the default
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&ConnectionEvent) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Box::new(filter);
+        self
+    }
+
+    /// Send data gap and checksum events only for these symbols
+    ///
+    /// Connection events are not tied to a symbol and always pass.
+    pub fn symbols<I, S>(mut self, symbols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.symbols = Some(symbols.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Drop repeats of the same event within `cooldown` (default 30 seconds)
+    ///
+    /// Events of the same kind count as repeats when they concern the same
+    /// symbol. `Duration::ZERO` sends every event.
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Send an event if it passes the filters and cooldown
+    ///
+    /// Returns whether it was sent. Notifier errors are logged.
+    pub async fn forward(&mut self, event: &ConnectionEvent) -> bool {
+        if !self.accepts(event) {
+            return false;
+        }
+        if let Err(e) = self.notifier.send_connection_event(event).await {
+            warn!("Failed to send event alert: {}", e);
+        }
+        true
+    }
+
+    /// Forward events until the stream closes
+    pub async fn run(mut self, mut events: mpsc::Receiver<ConnectionEvent>) {
+        while let Some(event) = events.recv().await {
+            self.forward(&event).await;
+        }
+    }
+
+    /// Subscribe to the client's events and forward them in the background
+    ///
+    /// Replaces any earlier [`subscribe_events`](KrakyClient::subscribe_events)
+    /// receiver. The task ends when the client is dropped; abort the handle
+    /// to stop it earlier.
+    pub fn spawn(self, client: &KrakyClient) -> JoinHandle<()> {
+        let events = client.subscribe_events();
+        crate::runtime::spawn(self.run(events))
+    }
+
+    /// Check the filters and cooldown, recording the event as sent
+    fn accepts(&mut self, event: &ConnectionEvent) -> bool {
+        if !(self.filter)(event) {
+            return false;
+        }
+        let symbol = event_symbol(event);
+        if let (Some(symbols), Some(symbol)) = (&self.symbols, symbol) {
+            if !symbols.contains(symbol) {
+                return false;
+            }
+        }
+
+        let key = (
+            std::mem::discriminant(event),
+            symbol.unwrap_or_default().to_string(),
+        );
+        let now = Instant::now();
+        if let Some(last) = self.last_sent.get(&key) {
+            if now.duration_since(*last) < self.cooldown {
+                return false;
+            }
+        }
+        self.last_sent.insert(key, now);
+        true
+    }
+}
+
+/// The symbol an event concerns, if any
+fn event_symbol(event: &ConnectionEvent) -> Option<&str> {
+    match event {
+        ConnectionEvent::DataStale(_, symbol, _)
+        | ConnectionEvent::DataResumed(_, symbol)
+        | ConnectionEvent::OrderbookCorrupted { symbol, .. }
+        | ConnectionEvent::ChecksumFailures { symbol, .. }
+        | ConnectionEvent::OrderbookUnverified { symbol } => Some(symbol),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+
+    /// Keeps every alert it is asked to send
+    #[derive(Default)]
+    struct RecordingNotifier {
+        sent: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn send_alert(&self, message: &str) -> Result<()> {
+            self.sent.lock().push(message.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bridge_filters_and_cools_down() {
+        let notifier = Arc::new(RecordingNotifier::default());
+        let bridge = AlertBridge::new(Arc::clone(&notifier) as Arc<dyn Notifier>)
+            .symbols(["BTC/USD"])
+            .cooldown(Duration::from_secs(60));
+
+        let (tx, rx) = mpsc::channel(16);
+        let stale = |symbol: &str| {
+            ConnectionEvent::DataStale(
+                "book".to_string(),
+                symbol.to_string(),
+                Duration::from_secs(30),
+            )
+        };
+        for event in [
+            ConnectionEvent::Reconnecting(1),
+            ConnectionEvent::Reconnected,
+            ConnectionEvent::Reconnected,
+            stale("BTC/USD"),
+            stale("ETH/USD"),
+            ConnectionEvent::DataResumed("book".to_string(), "BTC/USD".to_string()),
+            ConnectionEvent::ChecksumFailures {
+                symbol: "BTC/USD".to_string(),
+                consecutive: 3,
+            },
+        ] {
+            tx.send(event).await.unwrap();
+        }
+        drop(tx);
+        bridge.run(rx).await;
+
+        let sent = notifier.sent.lock();
+        assert_eq!(sent.len(), 4);
+        assert!(sent[0].contains("Connection Status: Reconnected"));
+        assert!(sent[1].contains("No book updates for BTC/USD"));
+        assert!(sent[2].contains("BTC/USD resumed"));
+        assert!(sent[3].contains("3 failed validations in a row"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_filter_without_cooldown() {
+        let notifier = Arc::new(RecordingNotifier::default());
+        let mut bridge = AlertBridge::new(Arc::clone(&notifier) as Arc<dyn Notifier>)
+            .filter(|event| matches!(event, ConnectionEvent::Disconnected(_)))
+            .cooldown(Duration::ZERO);
+
+        assert!(!bridge.forward(&ConnectionEvent::Connected).await);
+        let disconnected = ConnectionEvent::Disconnected(Some("closed by peer".to_string()));
+        assert!(bridge.forward(&disconnected).await);
+        assert!(bridge.forward(&disconnected).await);
+
+        let sent = notifier.sent.lock();
+        assert_eq!(sent.len(), 2);
+        assert!(sent[0].contains("closed by peer"));
+    }
+}
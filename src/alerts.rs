@@ -0,0 +1,444 @@
+//! Declarative alert rules
+//!
+//! Register rules against symbols and receive a single [`AlertEvent`] stream
+//! whenever a rule fires. Feed the engine with market data (orderbooks,
+//! tickers, trades) and attach any notifier to the resulting stream.
+//!
+//! Rules are edge-triggered: a rule fires once when its condition becomes
+//! true and re-arms once the condition clears, so a price sitting above a
+//! threshold does not produce an alert on every update.
+//!
+//! Requires the `alerts` feature flag.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::{AlertCondition, AlertEngine, AlertRule, KrakyClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! let mut orderbook = client.subscribe_orderbook("BTC/USD", 10).await?;
+//!
+//! let mut engine = AlertEngine::new();
+//! engine.add_rule(AlertRule::new("BTC/USD", AlertCondition::PriceAbove(100_000.0)));
+//! engine.add_rule(AlertRule::new("BTC/USD", AlertCondition::ImbalanceBeyond(0.3)));
+//! engine.add_rule(AlertRule::new("*", AlertCondition::SpreadAbove(10.0)).with_name("wide spread"));
+//!
+//! let mut alerts = engine.subscribe();
+//! tokio::spawn(async move {
+//!     while let Some(alert) = alerts.next().await {
+//!         println!("🔔 {}", alert);
+//!     }
+//! });
+//!
+//! while let Some(_update) = orderbook.next().await {
+//!     if let Some(ob) = client.get_orderbook("BTC/USD") {
+//!         engine.on_orderbook(&ob);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::models::Orderbook;
+use crate::subscriptions::{Subscription, SubscriptionSender};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+#[cfg(feature = "trades")]
+use crate::models::Trade;
+
+/// Condition that triggers an alert
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// Price rises to or above the threshold
+    PriceAbove(f64),
+    /// Price falls to or below the threshold
+    PriceBelow(f64),
+    /// Absolute orderbook imbalance reaches the threshold (0.0 - 1.0)
+    ImbalanceBeyond(f64),
+    /// Spread widens to or above the given basis points
+    SpreadAbove(f64),
+    /// A volume sample is at least `multiplier` times the average of the
+    /// previous `window` samples
+    VolumeSpike {
+        /// Multiple of the rolling average that counts as a spike
+        multiplier: f64,
+        /// Number of previous samples in the rolling average
+        window: usize,
+    },
+}
+
+impl fmt::Display for AlertCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertCondition::PriceAbove(p) => write!(f, "price >= {}", p),
+            AlertCondition::PriceBelow(p) => write!(f, "price <= {}", p),
+            AlertCondition::ImbalanceBeyond(t) => write!(f, "|imbalance| >= {:.1}%", t * 100.0),
+            AlertCondition::SpreadAbove(bps) => write!(f, "spread >= {:.1} bps", bps),
+            AlertCondition::VolumeSpike { multiplier, window } => {
+                write!(f, "volume >= {:.1}x avg of last {}", multiplier, window)
+            }
+        }
+    }
+}
+
+/// A rule binding a condition to a symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// Trading pair symbol, or `"*"` for all symbols
+    pub symbol: String,
+    /// Condition to evaluate
+    pub condition: AlertCondition,
+    /// Optional human-readable name
+    pub name: Option<String>,
+}
+
+impl AlertRule {
+    /// Create a new rule for a symbol (`"*"` matches every symbol)
+    pub fn new(symbol: impl Into<String>, condition: AlertCondition) -> Self {
+        Self {
+            symbol: symbol.into(),
+            condition,
+            name: None,
+        }
+    }
+
+    /// Give the rule a human-readable name, included in its alerts
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    fn matches(&self, symbol: &str) -> bool {
+        self.symbol == "*" || self.symbol == symbol
+    }
+}
+
+/// An alert emitted when a rule fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertEvent {
+    /// ID of the rule that fired (as returned by [`AlertEngine::add_rule`])
+    pub rule_id: u64,
+    /// Name of the rule, if set
+    pub name: Option<String>,
+    /// Symbol the rule fired for
+    pub symbol: String,
+    /// Condition that was met
+    pub condition: AlertCondition,
+    /// Observed value (price, imbalance ratio, spread in bps, or volume)
+    pub value: f64,
+    /// Time the alert fired
+    pub timestamp: DateTime<Utc>,
+}
+
+impl fmt::Display for AlertEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = &self.name {
+            write!(f, "[{}] ", name)?;
+        }
+        write!(
+            f,
+            "{}: {} (value: {:.4})",
+            self.symbol, self.condition, self.value
+        )
+    }
+}
+
+/// Registered rule with its evaluation state
+struct RuleState {
+    id: u64,
+    rule: AlertRule,
+    /// Whether the condition currently holds, per symbol
+    active: HashMap<String, bool>,
+    /// Recent volume samples per symbol (volume spike rules only)
+    volumes: HashMap<String, VecDeque<f64>>,
+}
+
+/// Evaluates alert rules against market data and emits [`AlertEvent`]s
+///
+/// Only available when the `alerts` feature is enabled.
+#[derive(Default)]
+pub struct AlertEngine {
+    rules: Vec<RuleState>,
+    next_id: u64,
+    senders: Vec<SubscriptionSender<AlertEvent>>,
+}
+
+impl AlertEngine {
+    /// Create an engine with no rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a rule and return its ID
+    pub fn add_rule(&mut self, rule: AlertRule) -> u64 {
+        self.next_id += 1;
+        self.rules.push(RuleState {
+            id: self.next_id,
+            rule,
+            active: HashMap::new(),
+            volumes: HashMap::new(),
+        });
+        self.next_id
+    }
+
+    /// Remove a rule by ID
+    ///
+    /// Returns `true` if the rule existed.
+    pub fn remove_rule(&mut self, id: u64) -> bool {
+        let before = self.rules.len();
+        self.rules.retain(|r| r.id != id);
+        self.rules.len() != before
+    }
+
+    /// Get a rule by ID
+    pub fn rule(&self, id: u64) -> Option<&AlertRule> {
+        self.rules.iter().find(|r| r.id == id).map(|r| &r.rule)
+    }
+
+    /// Get all registered rules with their IDs
+    pub fn rules(&self) -> Vec<(u64, &AlertRule)> {
+        self.rules.iter().map(|r| (r.id, &r.rule)).collect()
+    }
+
+    /// Remove all rules
+    pub fn clear(&mut self) {
+        self.rules.clear();
+    }
+
+    /// Subscribe to alerts
+    ///
+    /// Every subscriber receives all alerts fired after it subscribed.
+    pub fn subscribe(&mut self) -> Subscription<AlertEvent> {
+        let (sender, subscription) = SubscriptionSender::new("alerts".to_string(), "*".to_string());
+        self.senders.push(sender);
+        subscription
+    }
+
+    /// Evaluate price rules against a price observation
+    ///
+    /// Returns the alerts that fired (which are also sent to subscribers).
+    pub fn on_price(&mut self, symbol: &str, price: f64) -> Vec<AlertEvent> {
+        self.evaluate(symbol, |state, _| match state.rule.condition {
+            AlertCondition::PriceAbove(threshold) => Some((price >= threshold, price)),
+            AlertCondition::PriceBelow(threshold) => Some((price <= threshold, price)),
+            _ => None,
+        })
+    }
+
+    /// Evaluate volume spike rules against a volume sample
+    ///
+    /// Samples can be trade sizes or candle volumes, as long as a rule is
+    /// always fed the same kind of sample.
+    pub fn on_volume(&mut self, symbol: &str, volume: f64) -> Vec<AlertEvent> {
+        self.evaluate(symbol, |state, symbol| {
+            let AlertCondition::VolumeSpike { multiplier, window } = state.rule.condition else {
+                return None;
+            };
+
+            let history = state.volumes.entry(symbol.to_string()).or_default();
+            let result = if history.len() >= window.max(1) {
+                let avg = history.iter().sum::<f64>() / history.len() as f64;
+                Some((avg > 0.0 && volume >= avg * multiplier, volume))
+            } else {
+                None
+            };
+
+            history.push_back(volume);
+            while history.len() > window.max(1) {
+                history.pop_front();
+            }
+            result
+        })
+    }
+
+    /// Evaluate imbalance and spread rules against an orderbook
+    pub fn on_orderbook(&mut self, orderbook: &Orderbook) -> Vec<AlertEvent> {
+        let imbalance = orderbook.imbalance();
+        let spread_bps = orderbook.spread_bps();
+
+        self.evaluate(&orderbook.symbol, |state, _| match state.rule.condition {
+            AlertCondition::ImbalanceBeyond(threshold) => {
+                Some((imbalance.abs() >= threshold, imbalance))
+            }
+            AlertCondition::SpreadAbove(bps) => spread_bps.map(|s| (s >= bps, s)),
+            _ => None,
+        })
+    }
+
+    /// Evaluate price rules against a ticker's last traded price
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub fn on_ticker(&mut self, ticker: &Ticker) -> Vec<AlertEvent> {
+        self.on_price(&ticker.symbol, ticker.last)
+    }
+
+    /// Evaluate price and volume spike rules against a trade
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub fn on_trade(&mut self, trade: &Trade) -> Vec<AlertEvent> {
+        let mut events = self.on_price(&trade.symbol, trade.price);
+        events.extend(self.on_volume(&trade.symbol, trade.qty));
+        events
+    }
+
+    /// Run `observe` on every rule matching `symbol`
+    ///
+    /// `observe` returns `None` if the rule does not apply to this input,
+    /// otherwise whether the condition holds and the observed value.
+    fn evaluate(
+        &mut self,
+        symbol: &str,
+        mut observe: impl FnMut(&mut RuleState, &str) -> Option<(bool, f64)>,
+    ) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+
+        for state in self.rules.iter_mut().filter(|s| s.rule.matches(symbol)) {
+            let Some((hit, value)) = observe(state, symbol) else {
+                continue;
+            };
+
+            let active = state.active.entry(symbol.to_string()).or_insert(false);
+            if hit && !*active {
+                events.push(AlertEvent {
+                    rule_id: state.id,
+                    name: state.rule.name.clone(),
+                    symbol: symbol.to_string(),
+                    condition: state.rule.condition,
+                    value,
+                    timestamp: Utc::now(),
+                });
+            }
+            *active = hit;
+        }
+
+        for event in &events {
+            self.senders.retain(|s| s.send(event.clone()).is_ok());
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrderbookData, PriceLevelRaw};
+
+    fn orderbook(bid: (f64, f64), ask: (f64, f64)) -> Orderbook {
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.apply_update(&OrderbookData {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![PriceLevelRaw {
+                price: bid.0,
+                qty: bid.1,
+            }],
+            asks: vec![PriceLevelRaw {
+                price: ask.0,
+                qty: ask.1,
+            }],
+            checksum: 0,
+            timestamp: "".to_string(),
+        });
+        ob
+    }
+
+    #[test]
+    fn test_price_rule_is_edge_triggered() {
+        let mut engine = AlertEngine::new();
+        let id = engine.add_rule(AlertRule::new("BTC/USD", AlertCondition::PriceAbove(100.0)));
+
+        assert!(engine.on_price("BTC/USD", 99.0).is_empty());
+        let fired = engine.on_price("BTC/USD", 101.0);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].rule_id, id);
+        assert_eq!(fired[0].value, 101.0);
+
+        // Still above: no repeat
+        assert!(engine.on_price("BTC/USD", 102.0).is_empty());
+
+        // Clears and crosses again: fires again
+        assert!(engine.on_price("BTC/USD", 95.0).is_empty());
+        assert_eq!(engine.on_price("BTC/USD", 100.0).len(), 1);
+
+        // Other symbols are ignored
+        assert!(engine.on_price("ETH/USD", 1_000.0).is_empty());
+    }
+
+    #[test]
+    fn test_wildcard_rule_tracks_symbols_separately() {
+        let mut engine = AlertEngine::new();
+        engine.add_rule(AlertRule::new("*", AlertCondition::PriceBelow(10.0)));
+
+        assert_eq!(engine.on_price("BTC/USD", 5.0).len(), 1);
+        assert_eq!(engine.on_price("ETH/USD", 5.0).len(), 1);
+        assert!(engine.on_price("BTC/USD", 4.0).is_empty());
+    }
+
+    #[test]
+    fn test_orderbook_rules() {
+        let mut engine = AlertEngine::new();
+        engine.add_rule(AlertRule::new(
+            "BTC/USD",
+            AlertCondition::ImbalanceBeyond(0.5),
+        ));
+        engine.add_rule(
+            AlertRule::new("BTC/USD", AlertCondition::SpreadAbove(10.0)).with_name("spread"),
+        );
+
+        // Balanced and tight
+        assert!(engine
+            .on_orderbook(&orderbook((99.99, 1.0), (100.01, 1.0)))
+            .is_empty());
+
+        // Heavy ask side and 20 bps wide
+        let fired = engine.on_orderbook(&orderbook((99.9, 1.0), (100.1, 9.0)));
+        assert_eq!(fired.len(), 2);
+        assert!((fired[0].value + 0.8).abs() < 1e-9);
+        assert_eq!(fired[1].name.as_deref(), Some("spread"));
+    }
+
+    #[test]
+    fn test_volume_spike() {
+        let mut engine = AlertEngine::new();
+        engine.add_rule(AlertRule::new(
+            "BTC/USD",
+            AlertCondition::VolumeSpike {
+                multiplier: 3.0,
+                window: 3,
+            },
+        ));
+
+        // Not enough history yet
+        assert!(engine.on_volume("BTC/USD", 100.0).is_empty());
+        for _ in 0..3 {
+            assert!(engine.on_volume("BTC/USD", 1.0).is_empty());
+        }
+
+        assert_eq!(engine.on_volume("BTC/USD", 3.0).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_remove_rule() {
+        let mut engine = AlertEngine::new();
+        let id = engine.add_rule(AlertRule::new("BTC/USD", AlertCondition::PriceAbove(100.0)));
+        let mut alerts = engine.subscribe();
+
+        engine.on_price("BTC/USD", 150.0);
+        let alert = alerts.next().await.unwrap();
+        assert_eq!(alert.symbol, "BTC/USD");
+        assert_eq!(alert.to_string(), "BTC/USD: price >= 100 (value: 150.0000)");
+
+        assert!(engine.remove_rule(id));
+        assert!(!engine.remove_rule(id));
+        assert!(engine.on_price("BTC/USD", 90.0).is_empty());
+        assert!(engine.rules().is_empty());
+    }
+}
@@ -0,0 +1,329 @@
+//! Rolling cross-pair return correlations
+
+use crate::subscriptions::{Subscription, SubscriptionSender};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+#[cfg(feature = "ohlc")]
+use crate::models::OHLC;
+
+/// Minimum number of aligned returns before a correlation is reported
+const MIN_CORRELATION_SAMPLES: usize = 3;
+
+/// Emitted when two symbols that were correlated stop moving together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecouplingEvent {
+    /// First symbol of the pair
+    pub symbol_a: String,
+    /// Second symbol of the pair
+    pub symbol_b: String,
+    /// Current rolling correlation
+    pub correlation: f64,
+    /// Correlation at the previous sample
+    pub previous: f64,
+}
+
+/// Per-symbol price state
+#[derive(Debug, Clone, Default)]
+struct Series {
+    /// Latest price seen since the last sample
+    latest: Option<f64>,
+    /// Price at the last sample
+    sampled: Option<f64>,
+    /// Log returns between samples, oldest first
+    returns: VecDeque<f64>,
+}
+
+/// Maintains rolling return correlations between symbols
+///
+/// Feed it prices (from tickers, trades or candle closes) as they arrive and
+/// call [`sample`](Self::sample) at a fixed cadence (e.g. every minute, or on
+/// each new candle). Each sample records one log return per symbol, so all
+/// series stay aligned in time even though updates arrive independently.
+///
+/// With a decoupling threshold set, [`sample`](Self::sample) reports pairs
+/// whose correlation drops below the threshold after being above it.
+///
+/// Only available when the `analytics` feature is enabled.
+///
+/// # Example
+///
+/// ```
+/// use kraky::CorrelationTracker;
+///
+/// let mut tracker = CorrelationTracker::new(30).with_decoupling_threshold(0.5);
+/// for (btc, eth) in [(100.0, 10.0), (101.0, 10.1), (99.0, 9.9), (102.0, 10.2)] {
+///     tracker.update_price("BTC/USD", btc);
+///     tracker.update_price("ETH/USD", eth);
+///     tracker.sample();
+/// }
+///
+/// let corr = tracker.correlation("BTC/USD", "ETH/USD").unwrap();
+/// assert!(corr > 0.99);
+/// ```
+pub struct CorrelationTracker {
+    /// Number of returns kept per symbol
+    window: usize,
+    /// Correlation below which a previously correlated pair counts as decoupled
+    decoupling_threshold: Option<f64>,
+    /// Price state per symbol (sorted for stable pair ordering)
+    series: BTreeMap<String, Series>,
+    /// Pairs currently above the decoupling threshold
+    coupled: HashSet<(String, String)>,
+    /// Last computed correlation per pair
+    last: BTreeMap<(String, String), f64>,
+    /// Event subscribers
+    senders: Vec<SubscriptionSender<DecouplingEvent>>,
+}
+
+impl CorrelationTracker {
+    /// Create a tracker keeping `window` returns per symbol
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(MIN_CORRELATION_SAMPLES),
+            decoupling_threshold: None,
+            series: BTreeMap::new(),
+            coupled: HashSet::new(),
+            last: BTreeMap::new(),
+            senders: Vec::new(),
+        }
+    }
+
+    /// Report pairs whose correlation falls below `threshold`
+    pub fn with_decoupling_threshold(mut self, threshold: f64) -> Self {
+        self.decoupling_threshold = Some(threshold);
+        self
+    }
+
+    /// Get the rolling window size
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Get all tracked symbols
+    pub fn symbols(&self) -> Vec<String> {
+        self.series.keys().cloned().collect()
+    }
+
+    /// Subscribe to decoupling events
+    pub fn subscribe(&mut self) -> Subscription<DecouplingEvent> {
+        let (sender, subscription) =
+            SubscriptionSender::new("correlation".to_string(), "*".to_string());
+        self.senders.push(sender);
+        subscription
+    }
+
+    /// Record the latest price for a symbol
+    pub fn update_price(&mut self, symbol: &str, price: f64) {
+        if price.is_finite() && price > 0.0 {
+            self.series.entry(symbol.to_string()).or_default().latest = Some(price);
+        }
+    }
+
+    /// Record a ticker's last price
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub fn update_ticker(&mut self, ticker: &Ticker) {
+        self.update_price(&ticker.symbol, ticker.last);
+    }
+
+    /// Record a candle's close price
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub fn update_ohlc(&mut self, candle: &OHLC) {
+        self.update_price(&candle.symbol, candle.close);
+    }
+
+    /// Take a sample: record one return per symbol and update correlations
+    ///
+    /// Returns the pairs that decoupled with this sample (also sent to
+    /// subscribers). Always empty without a decoupling threshold.
+    pub fn sample(&mut self) -> Vec<DecouplingEvent> {
+        for series in self.series.values_mut() {
+            let Some(price) = series.latest else {
+                continue;
+            };
+            if let Some(prev) = series.sampled {
+                if series.returns.len() == self.window {
+                    series.returns.pop_front();
+                }
+                series.returns.push_back((price / prev).ln());
+            }
+            series.sampled = Some(price);
+        }
+
+        let mut events = Vec::new();
+        for (a, b, correlation) in self.matrix() {
+            let key = (a, b);
+            let previous = self.last.insert(key.clone(), correlation);
+
+            let Some(threshold) = self.decoupling_threshold else {
+                continue;
+            };
+            if correlation >= threshold {
+                self.coupled.insert(key);
+            } else if self.coupled.remove(&key) {
+                events.push(DecouplingEvent {
+                    symbol_a: key.0,
+                    symbol_b: key.1,
+                    correlation,
+                    previous: previous.unwrap_or(correlation),
+                });
+            }
+        }
+
+        for event in &events {
+            self.senders.retain(|s| s.send(event.clone()).is_ok());
+        }
+        events
+    }
+
+    /// Get the rolling correlation between two symbols (-1.0 to 1.0)
+    ///
+    /// Uses the most recent returns both symbols have in common. Returns
+    /// `None` with fewer than 3 aligned returns or if either series is flat.
+    pub fn correlation(&self, a: &str, b: &str) -> Option<f64> {
+        let a = &self.series.get(a)?.returns;
+        let b = &self.series.get(b)?.returns;
+        pearson(a, b)
+    }
+
+    /// Get the correlation for every pair of symbols that has one
+    ///
+    /// Pairs are returned as `(a, b, correlation)` with `a < b`.
+    pub fn matrix(&self) -> Vec<(String, String, f64)> {
+        let symbols: Vec<&String> = self.series.keys().collect();
+        let mut result = Vec::new();
+        for (i, a) in symbols.iter().enumerate() {
+            for b in &symbols[i + 1..] {
+                if let Some(c) = self.correlation(a, b) {
+                    result.push(((*a).clone(), (*b).clone(), c));
+                }
+            }
+        }
+        result
+    }
+
+    /// Stop tracking a symbol
+    pub fn remove(&mut self, symbol: &str) {
+        self.series.remove(symbol);
+        self.coupled.retain(|(a, b)| a != symbol && b != symbol);
+        self.last.retain(|(a, b), _| a != symbol && b != symbol);
+    }
+
+    /// Clear all state
+    pub fn reset(&mut self) {
+        self.series.clear();
+        self.coupled.clear();
+        self.last.clear();
+    }
+}
+
+/// Pearson correlation of the most recent common samples
+fn pearson(a: &VecDeque<f64>, b: &VecDeque<f64>) -> Option<f64> {
+    let n = a.len().min(b.len());
+    if n < MIN_CORRELATION_SAMPLES {
+        return None;
+    }
+
+    let xs = a.iter().skip(a.len() - n);
+    let ys = b.iter().skip(b.len() - n);
+    let mean_x = xs.clone().sum::<f64>() / n as f64;
+    let mean_y = ys.clone().sum::<f64>() / n as f64;
+
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.zip(ys) {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some((cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(tracker: &mut CorrelationTracker, prices: &[(f64, f64)]) -> Vec<DecouplingEvent> {
+        let mut events = Vec::new();
+        for &(a, b) in prices {
+            tracker.update_price("BTC/USD", a);
+            tracker.update_price("ETH/USD", b);
+            events.extend(tracker.sample());
+        }
+        events
+    }
+
+    #[test]
+    fn test_perfect_correlation() {
+        let mut tracker = CorrelationTracker::new(10);
+        feed(
+            &mut tracker,
+            &[(100.0, 50.0), (110.0, 55.0), (99.0, 49.5), (105.0, 52.5)],
+        );
+
+        let corr = tracker.correlation("BTC/USD", "ETH/USD").unwrap();
+        assert!((corr - 1.0).abs() < 1e-9);
+        assert_eq!(tracker.matrix().len(), 1);
+    }
+
+    #[test]
+    fn test_negative_correlation() {
+        let mut tracker = CorrelationTracker::new(10);
+        feed(
+            &mut tracker,
+            &[(100.0, 100.0), (110.0, 90.0), (100.0, 100.0), (110.0, 90.0)],
+        );
+
+        assert!(tracker.correlation("BTC/USD", "ETH/USD").unwrap() < -0.99);
+    }
+
+    #[test]
+    fn test_needs_enough_samples() {
+        let mut tracker = CorrelationTracker::new(10);
+        feed(
+            &mut tracker,
+            &[(100.0, 100.0), (101.0, 101.0), (102.0, 100.0)],
+        );
+        assert!(tracker.correlation("BTC/USD", "ETH/USD").is_none());
+        assert!(tracker.correlation("BTC/USD", "SOL/USD").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_decoupling_event() {
+        let mut tracker = CorrelationTracker::new(4).with_decoupling_threshold(0.5);
+        let mut events = tracker.subscribe();
+
+        let coupled = feed(
+            &mut tracker,
+            &[
+                (100.0, 100.0),
+                (102.0, 102.0),
+                (100.0, 100.0),
+                (103.0, 103.0),
+            ],
+        );
+        assert!(coupled.is_empty());
+
+        // ETH starts moving against BTC
+        let decoupled = feed(
+            &mut tracker,
+            &[(100.0, 106.0), (104.0, 101.0), (100.0, 105.0)],
+        );
+        assert_eq!(decoupled.len(), 1);
+        assert_eq!(decoupled[0].symbol_a, "BTC/USD");
+        assert!(decoupled[0].correlation < 0.5);
+        assert!(decoupled[0].previous >= 0.5);
+
+        assert_eq!(events.next().await.unwrap(), decoupled[0]);
+    }
+}
@@ -0,0 +1,315 @@
+//! Order flow divergence detection (price vs orderbook pressure)
+
+use crate::models::{ImbalanceSignal, Orderbook};
+use crate::subscriptions::{Subscription, SubscriptionSender};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Configuration for a [`DivergenceDetector`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DivergenceConfig {
+    /// How far back to measure the price change
+    pub lookback: Duration,
+    /// Minimum absolute price change (in percent) to consider
+    pub min_price_change_pct: f64,
+    /// Imbalance ratio (0.0 - 1.0) that counts as bullish/bearish pressure
+    pub imbalance_threshold: f64,
+}
+
+impl Default for DivergenceConfig {
+    fn default() -> Self {
+        Self {
+            lookback: Duration::from_secs(120),
+            min_price_change_pct: 0.5,
+            imbalance_threshold: 0.15,
+        }
+    }
+}
+
+impl DivergenceConfig {
+    /// Set the lookback period
+    pub fn with_lookback(mut self, lookback: Duration) -> Self {
+        self.lookback = lookback;
+        self
+    }
+
+    /// Set the minimum price change in percent
+    pub fn with_min_price_change_pct(mut self, pct: f64) -> Self {
+        self.min_price_change_pct = pct.abs();
+        self
+    }
+
+    /// Set the imbalance threshold
+    pub fn with_imbalance_threshold(mut self, threshold: f64) -> Self {
+        self.imbalance_threshold = threshold.abs();
+        self
+    }
+}
+
+/// Direction of a divergence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DivergenceKind {
+    /// Price falling while the orderbook is bullish
+    Bullish,
+    /// Price rising while the orderbook is bearish
+    Bearish,
+}
+
+/// A detected divergence between price action and orderbook pressure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceEvent {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Direction of the divergence
+    pub kind: DivergenceKind,
+    /// Price change over the lookback period in percent
+    pub price_change_pct: f64,
+    /// Orderbook imbalance ratio at detection time
+    pub imbalance: f64,
+    /// Orderbook signal at detection time
+    pub signal: ImbalanceSignal,
+    /// Current price
+    pub price: f64,
+}
+
+/// Detects when price moves against orderbook pressure
+///
+/// A divergence is reported when the price changed by at least
+/// `min_price_change_pct` over the lookback period while the orderbook
+/// imbalance points the other way (price up with a bearish book, or price
+/// down with a bullish book). This can signal exhaustion or a reversal.
+///
+/// Each divergence is reported once; the detector re-arms when it clears.
+///
+/// Only available when the `analytics` feature is enabled.
+///
+/// # Example
+///
+/// ```
+/// use kraky::{DivergenceConfig, DivergenceDetector};
+/// use std::time::Duration;
+///
+/// let config = DivergenceConfig::default()
+///     .with_lookback(Duration::from_secs(300))
+///     .with_min_price_change_pct(1.0);
+/// let mut detector = DivergenceDetector::new(config);
+///
+/// // Feed it (symbol, price, imbalance) observations, e.g. from ticker + orderbook
+/// if let Some(event) = detector.update("BTC/USD", 97_000.0, -0.25) {
+///     println!("{:?} divergence: price {:+.2}%", event.kind, event.price_change_pct);
+/// }
+/// ```
+pub struct DivergenceDetector {
+    config: DivergenceConfig,
+    /// Recent (time, price) samples per symbol, oldest first
+    history: HashMap<String, VecDeque<(Instant, f64)>>,
+    /// Whether a divergence is currently active per symbol
+    active: HashMap<String, bool>,
+    /// Event subscribers
+    senders: Vec<SubscriptionSender<DivergenceEvent>>,
+}
+
+impl Default for DivergenceDetector {
+    fn default() -> Self {
+        Self::new(DivergenceConfig::default())
+    }
+}
+
+impl DivergenceDetector {
+    /// Create a new detector
+    pub fn new(config: DivergenceConfig) -> Self {
+        Self {
+            config,
+            history: HashMap::new(),
+            active: HashMap::new(),
+            senders: Vec::new(),
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &DivergenceConfig {
+        &self.config
+    }
+
+    /// Subscribe to divergence events
+    pub fn subscribe(&mut self) -> Subscription<DivergenceEvent> {
+        let (sender, subscription) =
+            SubscriptionSender::new("divergence".to_string(), "*".to_string());
+        self.senders.push(sender);
+        subscription
+    }
+
+    /// Feed a price and the current orderbook imbalance ratio for a symbol
+    ///
+    /// Returns the divergence if one started with this observation (it is
+    /// also sent to subscribers). Nothing is reported until the price
+    /// history covers the lookback period.
+    pub fn update(&mut self, symbol: &str, price: f64, imbalance: f64) -> Option<DivergenceEvent> {
+        self.update_at(symbol, price, imbalance, Instant::now())
+    }
+
+    /// Feed an orderbook, using its mid price and full-book imbalance
+    pub fn update_orderbook(&mut self, orderbook: &Orderbook) -> Option<DivergenceEvent> {
+        let price = orderbook.mid_price()?;
+        self.update(&orderbook.symbol, price, orderbook.imbalance())
+    }
+
+    /// Get the price change (in percent) over the lookback period, if known
+    pub fn price_change_pct(&self, symbol: &str) -> Option<f64> {
+        let history = self.history.get(symbol)?;
+        let (_, current) = *history.back()?;
+        let (_, reference) = *history.front()?;
+        Some((current - reference) / reference * 100.0)
+    }
+
+    /// Forget the history for a symbol
+    pub fn reset(&mut self, symbol: &str) {
+        self.history.remove(symbol);
+        self.active.remove(symbol);
+    }
+
+    fn update_at(
+        &mut self,
+        symbol: &str,
+        price: f64,
+        imbalance: f64,
+        now: Instant,
+    ) -> Option<DivergenceEvent> {
+        if !price.is_finite() || price <= 0.0 {
+            return None;
+        }
+
+        let history = self.history.entry(symbol.to_string()).or_default();
+        history.push_back((now, price));
+
+        // Keep exactly one sample at or before the start of the lookback window
+        let cutoff = now.checked_sub(self.config.lookback)?;
+        while history.len() > 1 && history[1].0 <= cutoff {
+            history.pop_front();
+        }
+        let (first_time, reference) = *history.front()?;
+        if first_time > cutoff {
+            return None;
+        }
+
+        let price_change_pct = (price - reference) / reference * 100.0;
+        let signal = if imbalance > self.config.imbalance_threshold {
+            ImbalanceSignal::Bullish
+        } else if imbalance < -self.config.imbalance_threshold {
+            ImbalanceSignal::Bearish
+        } else {
+            ImbalanceSignal::Neutral
+        };
+
+        let kind = if price_change_pct.abs() < self.config.min_price_change_pct {
+            None
+        } else if price_change_pct > 0.0 && signal == ImbalanceSignal::Bearish {
+            Some(DivergenceKind::Bearish)
+        } else if price_change_pct < 0.0 && signal == ImbalanceSignal::Bullish {
+            Some(DivergenceKind::Bullish)
+        } else {
+            None
+        };
+
+        let active = self.active.entry(symbol.to_string()).or_insert(false);
+        let was_active = *active;
+        *active = kind.is_some();
+        if was_active {
+            return None;
+        }
+
+        let event = DivergenceEvent {
+            symbol: symbol.to_string(),
+            kind: kind?,
+            price_change_pct,
+            imbalance,
+            signal,
+            price,
+        };
+        self.senders.retain(|s| s.send(event.clone()).is_ok());
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> (DivergenceDetector, Instant) {
+        let config = DivergenceConfig::default()
+            .with_lookback(Duration::from_secs(60))
+            .with_min_price_change_pct(1.0)
+            .with_imbalance_threshold(0.2);
+        (DivergenceDetector::new(config), Instant::now())
+    }
+
+    #[test]
+    fn test_needs_full_lookback() {
+        let (mut detector, start) = detector();
+        assert!(detector.update_at("BTC/USD", 100.0, -0.5, start).is_none());
+        assert!(detector
+            .update_at("BTC/USD", 110.0, -0.5, start + Duration::from_secs(30))
+            .is_none());
+    }
+
+    #[test]
+    fn test_bearish_divergence_reported_once() {
+        let (mut detector, start) = detector();
+        detector.update_at("BTC/USD", 100.0, 0.0, start);
+
+        let event = detector
+            .update_at("BTC/USD", 102.0, -0.3, start + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(event.kind, DivergenceKind::Bearish);
+        assert_eq!(event.signal, ImbalanceSignal::Bearish);
+        assert!((event.price_change_pct - 2.0).abs() < 1e-9);
+
+        // Still diverging: not reported again
+        assert!(detector
+            .update_at("BTC/USD", 102.5, -0.3, start + Duration::from_secs(61))
+            .is_none());
+
+        // Book flips, then diverges again: reported again
+        assert!(detector
+            .update_at("BTC/USD", 102.5, 0.3, start + Duration::from_secs(62))
+            .is_none());
+        assert!(detector
+            .update_at("BTC/USD", 102.5, -0.3, start + Duration::from_secs(63))
+            .is_some());
+    }
+
+    #[test]
+    fn test_bullish_divergence_and_small_moves() {
+        let (mut detector, start) = detector();
+        detector.update_at("ETH/USD", 100.0, 0.0, start);
+
+        // 0.5% move is below the 1% minimum
+        assert!(detector
+            .update_at("ETH/USD", 99.5, 0.5, start + Duration::from_secs(60))
+            .is_none());
+
+        let event = detector
+            .update_at("ETH/USD", 98.0, 0.5, start + Duration::from_secs(61))
+            .unwrap();
+        assert_eq!(event.kind, DivergenceKind::Bullish);
+    }
+
+    #[test]
+    fn test_history_trimmed_to_lookback() {
+        let (mut detector, start) = detector();
+        for i in 0..10 {
+            detector.update_at(
+                "BTC/USD",
+                100.0 + i as f64,
+                0.0,
+                start + Duration::from_secs(i * 30),
+            );
+        }
+
+        // Reference is the sample at 210s (60s before 270s)
+        let change = detector.price_change_pct("BTC/USD").unwrap();
+        assert!((change - (109.0 - 107.0) / 107.0 * 100.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,282 @@
+//! Imbalance signal transitions with hysteresis and debounce
+
+use crate::models::{ImbalanceSignal, Orderbook};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Default number of book levels subscribed when a signal stream needs its own book
+pub const DEFAULT_IMBALANCE_BOOK_DEPTH: u32 = 10;
+
+/// Configuration for an imbalance signal stream
+///
+/// # Example
+///
+/// ```
+/// use kraky::ImbalanceConfig;
+/// use std::time::Duration;
+///
+/// // Enter at ±15%, leave only once back inside ±10%, and require the
+/// // new signal to hold for 2 seconds before reporting it.
+/// let config = ImbalanceConfig::new(0.15)
+///     .with_hysteresis(0.05)
+///     .with_debounce(Duration::from_secs(2))
+///     .with_levels(5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImbalanceConfig {
+    /// Imbalance ratio (0.0 - 1.0) needed to enter a bullish/bearish signal
+    pub threshold: f64,
+    /// How far back inside the threshold the ratio must fall to leave a signal
+    pub hysteresis: f64,
+    /// How long a new signal must persist before it is reported
+    pub debounce: Duration,
+    /// Only use the top N levels per side (`None` = whole book)
+    pub levels: Option<usize>,
+    /// Book depth to subscribe to if the pair has no orderbook subscription yet
+    pub book_depth: u32,
+}
+
+impl ImbalanceConfig {
+    /// Create a config with the given threshold and no hysteresis or debounce
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            hysteresis: 0.0,
+            debounce: Duration::ZERO,
+            levels: None,
+            book_depth: DEFAULT_IMBALANCE_BOOK_DEPTH,
+        }
+    }
+
+    /// Set the hysteresis band
+    pub fn with_hysteresis(mut self, hysteresis: f64) -> Self {
+        self.hysteresis = hysteresis.max(0.0);
+        self
+    }
+
+    /// Set the debounce duration
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Only use the top `levels` price levels per side
+    pub fn with_levels(mut self, levels: usize) -> Self {
+        self.levels = Some(levels);
+        self
+    }
+
+    /// Set the book depth used if a new orderbook subscription is needed
+    pub fn with_book_depth(mut self, depth: u32) -> Self {
+        self.book_depth = depth;
+        self
+    }
+}
+
+/// A change of the imbalance signal for a symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImbalanceTransition {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Signal before the transition
+    pub from: ImbalanceSignal,
+    /// Signal after the transition
+    pub to: ImbalanceSignal,
+    /// Imbalance ratio that confirmed the transition
+    pub imbalance: f64,
+}
+
+/// Turns a stream of imbalance ratios into signal transitions
+///
+/// The signal starts out [`ImbalanceSignal::Neutral`]. A bullish signal is
+/// entered when the ratio exceeds `threshold` and left once it drops below
+/// `threshold - hysteresis` (bearish is symmetric). With a debounce, a new
+/// signal has to hold for the configured duration before it is reported.
+///
+/// # Example
+///
+/// ```
+/// use kraky::{ImbalanceConfig, ImbalanceSignal, ImbalanceTracker};
+///
+/// let mut tracker = ImbalanceTracker::new(ImbalanceConfig::new(0.2).with_hysteresis(0.1));
+///
+/// assert_eq!(tracker.update(0.25), Some(ImbalanceSignal::Bullish));
+/// assert_eq!(tracker.update(0.15), None); // still inside the hysteresis band
+/// assert_eq!(tracker.update(0.05), Some(ImbalanceSignal::Neutral));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ImbalanceTracker {
+    config: ImbalanceConfig,
+    signal: ImbalanceSignal,
+    /// Candidate signal waiting for the debounce period, and when it was first seen
+    pending: Option<(ImbalanceSignal, Instant)>,
+}
+
+impl ImbalanceTracker {
+    /// Create a new tracker
+    pub fn new(config: ImbalanceConfig) -> Self {
+        Self {
+            config,
+            signal: ImbalanceSignal::Neutral,
+            pending: None,
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &ImbalanceConfig {
+        &self.config
+    }
+
+    /// Get the current (confirmed) signal
+    pub fn signal(&self) -> ImbalanceSignal {
+        self.signal
+    }
+
+    /// Feed an imbalance ratio
+    ///
+    /// Returns the new signal if it changed.
+    pub fn update(&mut self, imbalance: f64) -> Option<ImbalanceSignal> {
+        self.update_at(imbalance, Instant::now())
+    }
+
+    /// Feed an orderbook
+    ///
+    /// Returns the transition if the signal changed.
+    pub fn update_orderbook(&mut self, orderbook: &Orderbook) -> Option<ImbalanceTransition> {
+        let imbalance = match self.config.levels {
+            Some(n) => orderbook.imbalance_top_n(n),
+            None => orderbook.imbalance(),
+        };
+
+        let from = self.signal;
+        let to = self.update(imbalance)?;
+        Some(ImbalanceTransition {
+            symbol: orderbook.symbol.clone(),
+            from,
+            to,
+            imbalance,
+        })
+    }
+
+    /// Reset to neutral
+    pub fn reset(&mut self) {
+        self.signal = ImbalanceSignal::Neutral;
+        self.pending = None;
+    }
+
+    fn update_at(&mut self, imbalance: f64, now: Instant) -> Option<ImbalanceSignal> {
+        let raw = self.classify(imbalance);
+        if raw == self.signal {
+            self.pending = None;
+            return None;
+        }
+
+        if !self.config.debounce.is_zero() {
+            match self.pending {
+                Some((candidate, since)) if candidate == raw => {
+                    if now.duration_since(since) < self.config.debounce {
+                        return None;
+                    }
+                }
+                _ => {
+                    self.pending = Some((raw, now));
+                    return None;
+                }
+            }
+        }
+
+        self.pending = None;
+        self.signal = raw;
+        Some(raw)
+    }
+
+    /// Classify a ratio, taking the current signal into account for hysteresis
+    fn classify(&self, imbalance: f64) -> ImbalanceSignal {
+        let enter = self.config.threshold;
+        let exit = self.config.threshold - self.config.hysteresis;
+
+        match self.signal {
+            ImbalanceSignal::Bullish if imbalance > exit => ImbalanceSignal::Bullish,
+            ImbalanceSignal::Bearish if imbalance < -exit => ImbalanceSignal::Bearish,
+            _ if imbalance > enter => ImbalanceSignal::Bullish,
+            _ if imbalance < -enter => ImbalanceSignal::Bearish,
+            _ => ImbalanceSignal::Neutral,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions_without_hysteresis() {
+        let mut tracker = ImbalanceTracker::new(ImbalanceConfig::new(0.1));
+
+        assert_eq!(tracker.update(0.05), None);
+        assert_eq!(tracker.update(0.2), Some(ImbalanceSignal::Bullish));
+        assert_eq!(tracker.update(0.3), None);
+        assert_eq!(tracker.update(-0.2), Some(ImbalanceSignal::Bearish));
+        assert_eq!(tracker.update(0.0), Some(ImbalanceSignal::Neutral));
+    }
+
+    #[test]
+    fn test_hysteresis_prevents_flapping() {
+        let mut tracker = ImbalanceTracker::new(ImbalanceConfig::new(0.2).with_hysteresis(0.1));
+
+        assert_eq!(tracker.update(-0.21), Some(ImbalanceSignal::Bearish));
+        assert_eq!(tracker.update(-0.19), None);
+        assert_eq!(tracker.update(-0.21), None);
+        assert_eq!(tracker.update(-0.11), None);
+        assert_eq!(tracker.update(-0.09), Some(ImbalanceSignal::Neutral));
+
+        // Re-entering needs the full threshold again
+        assert_eq!(tracker.update(-0.15), None);
+    }
+
+    #[test]
+    fn test_debounce() {
+        let config = ImbalanceConfig::new(0.1).with_debounce(Duration::from_secs(2));
+        let mut tracker = ImbalanceTracker::new(config);
+        let start = Instant::now();
+
+        assert_eq!(tracker.update_at(0.2, start), None);
+        assert_eq!(tracker.update_at(0.2, start + Duration::from_secs(1)), None);
+
+        // Blip back to neutral restarts the debounce
+        assert_eq!(tracker.update_at(0.0, start + Duration::from_secs(1)), None);
+        assert_eq!(tracker.update_at(0.2, start + Duration::from_secs(2)), None);
+        assert_eq!(
+            tracker.update_at(0.2, start + Duration::from_secs(4)),
+            Some(ImbalanceSignal::Bullish)
+        );
+        assert_eq!(tracker.signal(), ImbalanceSignal::Bullish);
+    }
+
+    #[test]
+    fn test_update_orderbook() {
+        use crate::models::{OrderbookData, PriceLevelRaw};
+
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.apply_update(&OrderbookData {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![PriceLevelRaw {
+                price: 100.0,
+                qty: 3.0,
+            }],
+            asks: vec![PriceLevelRaw {
+                price: 101.0,
+                qty: 1.0,
+            }],
+            checksum: 0,
+            timestamp: "".to_string(),
+        });
+
+        let mut tracker = ImbalanceTracker::new(ImbalanceConfig::new(0.3));
+        let transition = tracker.update_orderbook(&ob).unwrap();
+        assert_eq!(transition.from, ImbalanceSignal::Neutral);
+        assert_eq!(transition.to, ImbalanceSignal::Bullish);
+        assert_eq!(transition.imbalance, 0.5);
+        assert!(tracker.update_orderbook(&ob).is_none());
+    }
+}
@@ -0,0 +1,356 @@
+//! Spread blowout and liquidity drought detection
+
+use crate::analytics::{SpreadMonitor, SpreadStats, DEFAULT_SPREAD_WINDOW};
+use crate::models::Orderbook;
+use crate::subscriptions::{Subscription, SubscriptionSender};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Default multiple of the baseline spread that counts as a blowout
+pub const DEFAULT_SPREAD_MULTIPLIER: f64 = 3.0;
+
+/// Default number of book levels per side counted as displayed size
+pub const DEFAULT_LIQUIDITY_DEPTH: usize = 1;
+
+/// What a [`LiquidityEvent`] reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidityEventKind {
+    /// Spread reached the blowout multiple of its baseline
+    SpreadBlowout,
+    /// Spread fell back below the blowout multiple
+    SpreadNormal,
+    /// Displayed size on a side fell below the threshold
+    Drought,
+    /// Displayed size on both sides is back above the threshold
+    DroughtEnded,
+}
+
+/// A change in the liquidity of a symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityEvent {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// What changed
+    pub kind: LiquidityEventKind,
+    /// Current spread in basis points
+    pub spread_bps: f64,
+    /// Rolling mean spread before this update, once enough samples exist
+    pub baseline_bps: Option<f64>,
+    /// Displayed bid size within the inspected depth
+    pub bid_size: f64,
+    /// Displayed ask size within the inspected depth
+    pub ask_size: f64,
+}
+
+/// Evaluation state of one symbol
+#[derive(Debug, Default)]
+struct LiquidityState {
+    /// Whether the spread is blown out
+    wide: bool,
+    /// Whether a side is in drought
+    dry: bool,
+    /// Recent displayed sizes (bid, ask), oldest first
+    sizes: VecDeque<(f64, f64)>,
+}
+
+/// Watches spread and displayed size for liquidity events
+///
+/// The spread baseline is the rolling mean from a [`SpreadMonitor`]; a
+/// spread at [`spread_multiplier`](Self::with_spread_multiplier) times the
+/// baseline is a blowout. A side whose displayed size within the top
+/// [`depth`](Self::with_depth) levels falls below
+/// [`min_size`](Self::with_min_size), or below
+/// [`size_drop`](Self::with_size_drop) times its rolling average, is in
+/// drought. Events are edge-triggered: each condition reports once when it
+/// starts and once when it ends.
+///
+/// Only available when the `analytics` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use kraky::{KrakyClient, LiquidityMonitor};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = KrakyClient::connect().await?;
+/// let mut orderbook = client.subscribe_orderbook("BTC/USD", 10).await?;
+///
+/// let mut monitor = LiquidityMonitor::new()
+///     .with_depth(5)
+///     .with_min_size(1.0)
+///     .with_size_drop(0.2);
+///
+/// while let Some(_update) = orderbook.next().await {
+///     if let Some(ob) = client.get_orderbook("BTC/USD") {
+///         for event in monitor.check_orderbook(&ob) {
+///             println!("💧 {} {:?} spread {:.1} bps", event.symbol, event.kind, event.spread_bps);
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct LiquidityMonitor {
+    /// Spread baseline per symbol
+    spreads: SpreadMonitor,
+    /// Multiple of the baseline spread that counts as a blowout
+    spread_multiplier: f64,
+    /// Number of levels per side counted as displayed size
+    depth: usize,
+    /// Absolute size threshold per side
+    min_size: Option<f64>,
+    /// Size threshold per side as a fraction of its rolling average
+    size_drop: Option<f64>,
+    /// State per symbol
+    states: HashMap<String, LiquidityState>,
+    /// Event subscribers
+    senders: Vec<SubscriptionSender<LiquidityEvent>>,
+}
+
+impl Default for LiquidityMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiquidityMonitor {
+    /// Create a monitor detecting spread blowouts only
+    ///
+    /// Set [`with_min_size`](Self::with_min_size) or
+    /// [`with_size_drop`](Self::with_size_drop) to detect droughts.
+    pub fn new() -> Self {
+        Self {
+            spreads: SpreadMonitor::new(DEFAULT_SPREAD_WINDOW),
+            spread_multiplier: DEFAULT_SPREAD_MULTIPLIER,
+            depth: DEFAULT_LIQUIDITY_DEPTH,
+            min_size: None,
+            size_drop: None,
+            states: HashMap::new(),
+            senders: Vec::new(),
+        }
+    }
+
+    /// Use this spread monitor for the baseline (window and minimum samples)
+    pub fn with_spread_monitor(mut self, spreads: SpreadMonitor) -> Self {
+        self.spreads = spreads;
+        self
+    }
+
+    /// Set the multiple of the baseline spread that counts as a blowout
+    pub fn with_spread_multiplier(mut self, multiplier: f64) -> Self {
+        self.spread_multiplier = multiplier;
+        self
+    }
+
+    /// Set the number of levels per side counted as displayed size
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Report a drought when a side shows less than `size` (base asset)
+    pub fn with_min_size(mut self, size: f64) -> Self {
+        self.min_size = Some(size);
+        self
+    }
+
+    /// Report a drought when a side shows less than `fraction` of its
+    /// rolling average size (e.g. 0.2 for an 80% collapse)
+    pub fn with_size_drop(mut self, fraction: f64) -> Self {
+        self.size_drop = Some(fraction);
+        self
+    }
+
+    /// Subscribe to liquidity events
+    ///
+    /// Every subscriber receives all events emitted after it subscribed.
+    pub fn subscribe(&mut self) -> Subscription<LiquidityEvent> {
+        let (sender, subscription) =
+            SubscriptionSender::new("liquidity".to_string(), "*".to_string());
+        self.senders.push(sender);
+        subscription
+    }
+
+    /// Check an orderbook for liquidity changes
+    ///
+    /// Returns the events (which are also sent to subscribers). Orderbooks
+    /// without a two-sided market are skipped.
+    pub fn check_orderbook(&mut self, orderbook: &Orderbook) -> Vec<LiquidityEvent> {
+        let Some(spread_bps) = orderbook.spread_bps() else {
+            return Vec::new();
+        };
+        let symbol = orderbook.symbol.as_str();
+        let bid_size: f64 = orderbook
+            .top_bids(self.depth)
+            .iter()
+            .map(|level| level.qty)
+            .sum();
+        let ask_size: f64 = orderbook
+            .top_asks(self.depth)
+            .iter()
+            .map(|level| level.qty)
+            .sum();
+
+        let baseline_bps = self.spreads.stats(symbol).map(|stats| stats.mean);
+        let wide = self
+            .spreads
+            .is_wide(symbol, spread_bps, self.spread_multiplier);
+        self.spreads.record(symbol, spread_bps);
+
+        let window = self.spreads.window();
+        let state = self.states.entry(symbol.to_string()).or_default();
+        let average = (state.sizes.len() >= self.spreads.min_samples()).then(|| {
+            let n = state.sizes.len() as f64;
+            let (bids, asks) = state
+                .sizes
+                .iter()
+                .fold((0.0, 0.0), |(b, a), (bid, ask)| (b + bid, a + ask));
+            (bids / n, asks / n)
+        });
+        let thin = |size: f64, average: Option<f64>| {
+            self.min_size.is_some_and(|min| size < min)
+                || self
+                    .size_drop
+                    .zip(average)
+                    .is_some_and(|(fraction, average)| size < average * fraction)
+        };
+        let dry = thin(bid_size, average.map(|a| a.0)) || thin(ask_size, average.map(|a| a.1));
+        state.sizes.push_back((bid_size, ask_size));
+        if state.sizes.len() > window {
+            state.sizes.pop_front();
+        }
+
+        let mut kinds = Vec::new();
+        if wide != state.wide {
+            kinds.push(if wide {
+                LiquidityEventKind::SpreadBlowout
+            } else {
+                LiquidityEventKind::SpreadNormal
+            });
+        }
+        if dry != state.dry {
+            kinds.push(if dry {
+                LiquidityEventKind::Drought
+            } else {
+                LiquidityEventKind::DroughtEnded
+            });
+        }
+        state.wide = wide;
+        state.dry = dry;
+
+        let events: Vec<_> = kinds
+            .into_iter()
+            .map(|kind| LiquidityEvent {
+                symbol: symbol.to_string(),
+                kind,
+                spread_bps,
+                baseline_bps,
+                bid_size,
+                ask_size,
+            })
+            .collect();
+        for event in &events {
+            self.senders.retain(|s| s.send(event.clone()).is_ok());
+        }
+        events
+    }
+
+    /// Get the spread baseline statistics of a symbol
+    pub fn spread_stats(&self, symbol: &str) -> Option<SpreadStats> {
+        self.spreads.stats(symbol)
+    }
+
+    /// Check whether a symbol's spread is currently blown out
+    pub fn is_wide(&self, symbol: &str) -> bool {
+        self.states.get(symbol).is_some_and(|s| s.wide)
+    }
+
+    /// Check whether a symbol is currently in drought
+    pub fn is_dry(&self, symbol: &str) -> bool {
+        self.states.get(symbol).is_some_and(|s| s.dry)
+    }
+
+    /// Forget the history of a symbol (e.g. after a reconnect)
+    pub fn reset(&mut self, symbol: &str) {
+        self.spreads.reset(symbol);
+        self.states.remove(symbol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrderbookData, PriceLevelRaw};
+
+    fn orderbook(bid: (f64, f64), ask: (f64, f64)) -> Orderbook {
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.apply_update(&OrderbookData {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![PriceLevelRaw {
+                price: bid.0,
+                qty: bid.1,
+            }],
+            asks: vec![PriceLevelRaw {
+                price: ask.0,
+                qty: ask.1,
+            }],
+            checksum: 0,
+            timestamp: "".to_string(),
+        });
+        ob
+    }
+
+    #[test]
+    fn test_spread_blowout_against_baseline() {
+        let mut monitor = LiquidityMonitor::new()
+            .with_spread_monitor(SpreadMonitor::new(10).with_min_samples(5))
+            .with_spread_multiplier(3.0);
+
+        // ~2 bps while the baseline builds up
+        for _ in 0..5 {
+            assert!(monitor
+                .check_orderbook(&orderbook((99.99, 1.0), (100.01, 1.0)))
+                .is_empty());
+        }
+
+        // ~10 bps is 5x the baseline
+        let events = monitor.check_orderbook(&orderbook((99.95, 1.0), (100.05, 1.0)));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, LiquidityEventKind::SpreadBlowout);
+        assert!((events[0].baseline_bps.unwrap() - 2.0).abs() < 1e-3);
+        assert!(monitor.is_wide("BTC/USD"));
+
+        let events = monitor.check_orderbook(&orderbook((99.99, 1.0), (100.01, 1.0)));
+        assert_eq!(events[0].kind, LiquidityEventKind::SpreadNormal);
+    }
+
+    #[test]
+    fn test_drought() {
+        let mut monitor = LiquidityMonitor::new()
+            .with_spread_monitor(SpreadMonitor::new(10).with_min_samples(3))
+            .with_min_size(0.5)
+            .with_size_drop(0.2);
+
+        for _ in 0..3 {
+            assert!(monitor
+                .check_orderbook(&orderbook((99.99, 10.0), (100.01, 10.0)))
+                .is_empty());
+        }
+
+        // Above the absolute threshold but 90% below the average
+        let events = monitor.check_orderbook(&orderbook((99.99, 10.0), (100.01, 1.0)));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, LiquidityEventKind::Drought);
+        assert_eq!(events[0].ask_size, 1.0);
+        assert!(monitor.is_dry("BTC/USD"));
+
+        // Still dry: no repeat
+        assert!(monitor
+            .check_orderbook(&orderbook((99.99, 0.1), (100.01, 1.0)))
+            .is_empty());
+
+        let events = monitor.check_orderbook(&orderbook((99.99, 10.0), (100.01, 10.0)));
+        assert_eq!(events[0].kind, LiquidityEventKind::DroughtEnded);
+    }
+}
@@ -0,0 +1,63 @@
+//! Market analytics components.
+//!
+//! This module contains stateful analytics helpers that sit on top of the raw
+//! market data streams. Each component is fed with updates (orderbooks, trades,
+//! tickers, candles) and maintains rolling statistics that alerting and sizing
+//! logic can query.
+//!
+//! Requires the `analytics` feature flag.
+//!
+//! # Components
+//!
+//! - [`CorrelationTracker`] - Rolling cross-pair return correlations and decoupling events
+//! - [`DivergenceDetector`] - Price action diverging from orderbook pressure
+//! - [`ImbalanceTracker`] - Imbalance signal transitions with hysteresis and debounce
+//! - [`LiquidityMonitor`] - Spread blowouts against a rolling baseline and displayed-size droughts
+//! - [`SpreadMonitor`] - Rolling spread statistics (mean, stddev, percentiles) per symbol
+//! - [`Volatility`] - Rolling realized volatility (close-to-close or Parkinson)
+//! - [`VolumeProfile`] - Traded volume by price with point of control and value area
+//! - [`Vpin`] - Flow toxicity (volume-synchronized probability of informed trading)
+//! - [`WhaleDetector`] - Large order and trade detection with an event stream
+//!
+//! # Example Usage
+//!
+//! ```no_run
+//! use kraky::{KrakyClient, SpreadMonitor};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! let mut orderbook = client.subscribe_orderbook("BTC/USD", 10).await?;
+//! let mut spreads = SpreadMonitor::new(100);
+//!
+//! while let Some(_update) = orderbook.next().await {
+//!     if let Some(ob) = client.get_orderbook("BTC/USD") {
+//!         if let Some(spread_bps) = spreads.record_orderbook(&ob) {
+//!             if spreads.is_wide("BTC/USD", spread_bps, 3.0) {
+//!                 println!("Spread is 3x wider than normal: {:.1} bps", spread_bps);
+//!             }
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod correlation;
+mod divergence;
+mod imbalance;
+mod liquidity;
+mod spread;
+mod volatility;
+mod volume_profile;
+mod vpin;
+mod whale;
+
+pub use correlation::*;
+pub use divergence::*;
+pub use imbalance::*;
+pub use liquidity::*;
+pub use spread::*;
+pub use volatility::*;
+pub use volume_profile::*;
+pub use vpin::*;
+pub use whale::*;
@@ -0,0 +1,327 @@
+//! Rolling spread statistics
+
+use crate::models::Orderbook;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Default number of spread samples kept per symbol
+pub const DEFAULT_SPREAD_WINDOW: usize = 100;
+
+/// Default number of samples required before statistics are reported
+pub const DEFAULT_SPREAD_MIN_SAMPLES: usize = 20;
+
+/// Tracks rolling spread statistics (in basis points) per symbol
+///
+/// Keeps the last `window` spread samples for every symbol it sees and
+/// answers questions like "is the current spread 3x wider than normal?".
+///
+/// # Example
+///
+/// ```
+/// use kraky::SpreadMonitor;
+///
+/// let mut monitor = SpreadMonitor::new(100).with_min_samples(5);
+/// for _ in 0..10 {
+///     monitor.record("BTC/USD", 2.0);
+/// }
+///
+/// let stats = monitor.stats("BTC/USD").unwrap();
+/// assert_eq!(stats.mean, 2.0);
+/// assert!(monitor.is_wide("BTC/USD", 6.0, 3.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpreadMonitor {
+    /// Maximum number of samples kept per symbol
+    window: usize,
+    /// Minimum number of samples before stats are available
+    min_samples: usize,
+    /// Spread history per symbol (oldest first)
+    history: HashMap<String, VecDeque<f64>>,
+}
+
+impl Default for SpreadMonitor {
+    fn default() -> Self {
+        Self::new(DEFAULT_SPREAD_WINDOW)
+    }
+}
+
+impl SpreadMonitor {
+    /// Create a new spread monitor keeping the last `window` samples per symbol
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            min_samples: DEFAULT_SPREAD_MIN_SAMPLES.min(window),
+            history: HashMap::new(),
+        }
+    }
+
+    /// Set the minimum number of samples required before statistics are reported
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.clamp(1, self.window);
+        self
+    }
+
+    /// Get the rolling window size
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Get the minimum number of samples required for statistics
+    pub fn min_samples(&self) -> usize {
+        self.min_samples
+    }
+
+    /// Record a spread sample (in basis points) for a symbol
+    ///
+    /// Non-finite or negative values (crossed books) are ignored.
+    pub fn record(&mut self, symbol: &str, spread_bps: f64) {
+        if !spread_bps.is_finite() || spread_bps < 0.0 {
+            return;
+        }
+
+        let history = self
+            .history
+            .entry(symbol.to_string())
+            .or_insert_with(|| VecDeque::with_capacity(self.window));
+
+        if history.len() == self.window {
+            history.pop_front();
+        }
+        history.push_back(spread_bps);
+    }
+
+    /// Record the current spread of an orderbook
+    ///
+    /// Returns the recorded spread in basis points, or `None` if the
+    /// orderbook has no two-sided market.
+    pub fn record_orderbook(&mut self, orderbook: &Orderbook) -> Option<f64> {
+        let spread_bps = orderbook.spread_bps()?;
+        self.record(&orderbook.symbol, spread_bps);
+        Some(spread_bps)
+    }
+
+    /// Get the number of samples recorded for a symbol
+    pub fn samples(&self, symbol: &str) -> usize {
+        self.history.get(symbol).map(|h| h.len()).unwrap_or(0)
+    }
+
+    /// Get the most recently recorded spread for a symbol
+    pub fn last(&self, symbol: &str) -> Option<f64> {
+        self.history.get(symbol)?.back().copied()
+    }
+
+    /// Get rolling spread statistics for a symbol
+    ///
+    /// Returns `None` until at least `min_samples` samples have been recorded.
+    pub fn stats(&self, symbol: &str) -> Option<SpreadStats> {
+        let history = self.history.get(symbol)?;
+        if history.len() < self.min_samples {
+            return None;
+        }
+        SpreadStats::from_samples(history.iter().copied())
+    }
+
+    /// Get how many times wider `current_bps` is than the rolling mean
+    ///
+    /// Returns `None` if there are not enough samples or the mean is zero.
+    pub fn multiplier(&self, symbol: &str, current_bps: f64) -> Option<f64> {
+        let stats = self.stats(symbol)?;
+        if stats.mean <= 0.0 {
+            return None;
+        }
+        Some(current_bps / stats.mean)
+    }
+
+    /// Check whether `current_bps` is at least `multiplier` times the normal spread
+    ///
+    /// Always returns `false` until enough samples have been recorded.
+    pub fn is_wide(&self, symbol: &str, current_bps: f64, multiplier: f64) -> bool {
+        self.multiplier(symbol, current_bps)
+            .map(|m| m >= multiplier)
+            .unwrap_or(false)
+    }
+
+    /// Get the z-score of `current_bps` relative to the rolling distribution
+    ///
+    /// Returns `None` if there are not enough samples or the spread has not varied.
+    pub fn z_score(&self, symbol: &str, current_bps: f64) -> Option<f64> {
+        let stats = self.stats(symbol)?;
+        if stats.std_dev == 0.0 {
+            return None;
+        }
+        Some((current_bps - stats.mean) / stats.std_dev)
+    }
+
+    /// Get all symbols currently tracked
+    pub fn symbols(&self) -> Vec<String> {
+        self.history.keys().cloned().collect()
+    }
+
+    /// Clear the history for a single symbol
+    pub fn reset(&mut self, symbol: &str) {
+        self.history.remove(symbol);
+    }
+
+    /// Clear the history for all symbols
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+}
+
+/// Rolling spread statistics for a symbol (all values in basis points)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpreadStats {
+    /// Number of samples in the window
+    pub samples: usize,
+    /// Most recent spread
+    pub current: f64,
+    /// Mean spread
+    pub mean: f64,
+    /// Population standard deviation of the spread
+    pub std_dev: f64,
+    /// Minimum spread
+    pub min: f64,
+    /// Maximum spread
+    pub max: f64,
+    /// Median spread
+    pub p50: f64,
+    /// 90th percentile spread
+    pub p90: f64,
+    /// 99th percentile spread
+    pub p99: f64,
+}
+
+impl SpreadStats {
+    /// Compute statistics from a set of samples (oldest first)
+    ///
+    /// Returns `None` if there are no samples.
+    pub fn from_samples(samples: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let ordered: Vec<f64> = samples.into_iter().collect();
+        let current = *ordered.last()?;
+        let count = ordered.len() as f64;
+
+        let mean = ordered.iter().sum::<f64>() / count;
+        let variance = ordered.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+
+        let mut sorted = ordered;
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        Some(Self {
+            samples: sorted.len(),
+            current,
+            mean,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            p50: percentile(&sorted, 0.50),
+            p90: percentile(&sorted, 0.90),
+            p99: percentile(&sorted, 0.99),
+        })
+    }
+
+    /// How many times wider the current spread is than the mean
+    pub fn current_multiplier(&self) -> f64 {
+        if self.mean > 0.0 {
+            self.current / self.mean
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Nearest-rank percentile of already sorted samples
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let rank = (pct * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrderbookData, PriceLevelRaw};
+
+    #[test]
+    fn test_stats_require_min_samples() {
+        let mut monitor = SpreadMonitor::new(10).with_min_samples(3);
+        monitor.record("BTC/USD", 1.0);
+        monitor.record("BTC/USD", 2.0);
+        assert!(monitor.stats("BTC/USD").is_none());
+
+        monitor.record("BTC/USD", 3.0);
+        let stats = monitor.stats("BTC/USD").unwrap();
+        assert_eq!(stats.samples, 3);
+        assert_eq!(stats.mean, 2.0);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 3.0);
+        assert_eq!(stats.current, 3.0);
+    }
+
+    #[test]
+    fn test_window_rolls_over() {
+        let mut monitor = SpreadMonitor::new(3).with_min_samples(1);
+        for value in [1.0, 2.0, 3.0, 10.0] {
+            monitor.record("BTC/USD", value);
+        }
+
+        assert_eq!(monitor.samples("BTC/USD"), 3);
+        let stats = monitor.stats("BTC/USD").unwrap();
+        assert_eq!(stats.min, 2.0);
+        assert_eq!(stats.max, 10.0);
+    }
+
+    #[test]
+    fn test_percentiles_and_std_dev() {
+        let samples: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+        let stats = SpreadStats::from_samples(samples).unwrap();
+
+        assert_eq!(stats.p50, 50.0);
+        assert_eq!(stats.p90, 90.0);
+        assert_eq!(stats.p99, 99.0);
+        assert!((stats.std_dev - 28.866).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_is_wide() {
+        let mut monitor = SpreadMonitor::new(100).with_min_samples(5);
+        for _ in 0..5 {
+            monitor.record("BTC/USD", 2.0);
+        }
+
+        assert_eq!(monitor.multiplier("BTC/USD", 6.0), Some(3.0));
+        assert!(monitor.is_wide("BTC/USD", 6.0, 3.0));
+        assert!(!monitor.is_wide("BTC/USD", 5.0, 3.0));
+        assert!(!monitor.is_wide("ETH/USD", 50.0, 3.0));
+    }
+
+    #[test]
+    fn test_ignores_invalid_samples() {
+        let mut monitor = SpreadMonitor::new(10);
+        monitor.record("BTC/USD", f64::NAN);
+        monitor.record("BTC/USD", -1.0);
+        assert_eq!(monitor.samples("BTC/USD"), 0);
+    }
+
+    #[test]
+    fn test_record_orderbook() {
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.apply_update(&OrderbookData {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![PriceLevelRaw {
+                price: 49995.0,
+                qty: 1.0,
+            }],
+            asks: vec![PriceLevelRaw {
+                price: 50005.0,
+                qty: 1.0,
+            }],
+            checksum: 0,
+            timestamp: "".to_string(),
+        });
+
+        let mut monitor = SpreadMonitor::new(10).with_min_samples(1);
+        let spread_bps = monitor.record_orderbook(&ob).unwrap();
+        assert!((spread_bps - 2.0).abs() < 1e-9);
+        assert_eq!(monitor.last("BTC/USD"), Some(spread_bps));
+    }
+}
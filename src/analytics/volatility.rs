@@ -0,0 +1,330 @@
+//! Rolling realized volatility estimation
+
+use std::collections::VecDeque;
+
+#[cfg(feature = "trades")]
+use crate::models::Trade;
+#[cfg(feature = "ohlc")]
+use crate::models::{Interval, OHLC};
+
+/// Minutes in a (365 day) year, used for annualization
+const MINUTES_PER_YEAR: f64 = 525_600.0;
+
+/// Realized volatility estimation method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolatilityMethod {
+    /// Standard deviation of log returns between consecutive closes
+    CloseToClose,
+    /// Parkinson high/low range estimator (more efficient, needs candles)
+    Parkinson,
+}
+
+/// Rolling realized volatility estimator
+///
+/// Maintains a rolling window of per-period samples and reports the
+/// per-period volatility (as a fraction, e.g. `0.01` = 1%). Feed it
+/// closing prices, trades or OHLC candles depending on the method.
+///
+/// - [`VolatilityMethod::CloseToClose`] uses log returns between consecutive prices
+/// - [`VolatilityMethod::Parkinson`] uses the high/low range of each candle
+///
+/// # Example
+///
+/// ```
+/// use kraky::Volatility;
+///
+/// let mut vol = Volatility::close_to_close(20);
+/// for price in [100.0, 101.0, 99.5, 100.5, 102.0] {
+///     vol.update_price(price);
+/// }
+///
+/// let per_period = vol.value().unwrap();
+/// let annualized = vol.annualized(365.0).unwrap(); // daily closes
+/// assert!(annualized > per_period);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Volatility {
+    /// Estimation method
+    method: VolatilityMethod,
+    /// Number of samples in the rolling window
+    window: usize,
+    /// Rolling samples: log returns (close-to-close) or ln(H/L)^2 (Parkinson)
+    samples: VecDeque<f64>,
+    /// Last close seen (close-to-close only)
+    last_close: Option<f64>,
+    /// Candle still being updated by the OHLC stream: (interval_begin, high, low, close)
+    #[cfg(feature = "ohlc")]
+    pending: Option<(String, f64, f64, f64)>,
+}
+
+impl Volatility {
+    /// Create a new estimator with the given method and window size
+    pub fn new(method: VolatilityMethod, window: usize) -> Self {
+        Self {
+            method,
+            window: window.max(2),
+            samples: VecDeque::new(),
+            last_close: None,
+            #[cfg(feature = "ohlc")]
+            pending: None,
+        }
+    }
+
+    /// Create a close-to-close estimator
+    pub fn close_to_close(window: usize) -> Self {
+        Self::new(VolatilityMethod::CloseToClose, window)
+    }
+
+    /// Create a Parkinson (high/low) estimator
+    pub fn parkinson(window: usize) -> Self {
+        Self::new(VolatilityMethod::Parkinson, window)
+    }
+
+    /// Get the estimation method
+    pub fn method(&self) -> VolatilityMethod {
+        self.method
+    }
+
+    /// Get the rolling window size
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Get the number of samples currently in the window
+    pub fn samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Check whether the rolling window is full
+    pub fn is_warm(&self) -> bool {
+        self.samples.len() >= self.window
+    }
+
+    /// Record a closing price for one period
+    ///
+    /// Only used by [`VolatilityMethod::CloseToClose`]; ignored otherwise.
+    pub fn update_price(&mut self, price: f64) {
+        if self.method != VolatilityMethod::CloseToClose || !price.is_finite() || price <= 0.0 {
+            return;
+        }
+
+        if let Some(prev) = self.last_close {
+            self.push_sample((price / prev).ln());
+        }
+        self.last_close = Some(price);
+    }
+
+    /// Record a completed candle
+    ///
+    /// Close-to-close uses the close price, Parkinson uses the high/low range.
+    pub fn update_candle(&mut self, high: f64, low: f64, close: f64) {
+        match self.method {
+            VolatilityMethod::CloseToClose => self.update_price(close),
+            VolatilityMethod::Parkinson => {
+                if high.is_finite() && low.is_finite() && low > 0.0 && high >= low {
+                    self.push_sample((high / low).ln().powi(2));
+                }
+            }
+        }
+    }
+
+    /// Record a trade price
+    ///
+    /// Every trade is treated as one period, so this is best combined with
+    /// [`VolatilityMethod::CloseToClose`] on low-frequency pairs. Prefer
+    /// candles for time-based volatility.
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub fn update_trade(&mut self, trade: &Trade) {
+        self.update_price(trade.price);
+    }
+
+    /// Record a candle from the OHLC stream
+    ///
+    /// Kraken sends repeated updates for the candle that is still open, so a
+    /// candle is only committed once an update for the next interval arrives.
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub fn update_ohlc(&mut self, candle: &OHLC) {
+        if let Some((begin, high, low, close)) = self.pending.take() {
+            if begin != candle.interval_begin {
+                self.update_candle(high, low, close);
+            }
+        }
+        self.pending = Some((
+            candle.interval_begin.clone(),
+            candle.high,
+            candle.low,
+            candle.close,
+        ));
+    }
+
+    /// Get the per-period realized volatility
+    ///
+    /// Returns `None` until at least two samples have been recorded.
+    pub fn value(&self) -> Option<f64> {
+        let n = self.samples.len();
+        if n < 2 {
+            return None;
+        }
+
+        match self.method {
+            VolatilityMethod::CloseToClose => {
+                let mean = self.samples.iter().sum::<f64>() / n as f64;
+                let variance =
+                    self.samples.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+                Some(variance.sqrt())
+            }
+            VolatilityMethod::Parkinson => {
+                let mean_sq = self.samples.iter().sum::<f64>() / n as f64;
+                Some((mean_sq / (4.0 * std::f64::consts::LN_2)).sqrt())
+            }
+        }
+    }
+
+    /// Get the volatility scaled to a year
+    ///
+    /// `periods_per_year` is the number of sampling periods in a year
+    /// (e.g. `365.0` for daily closes, `525600.0` for 1-minute candles).
+    pub fn annualized(&self, periods_per_year: f64) -> Option<f64> {
+        Some(self.value()? * periods_per_year.sqrt())
+    }
+
+    /// Get the volatility annualized for a candle interval
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub fn annualized_for(&self, interval: Interval) -> Option<f64> {
+        self.annualized(MINUTES_PER_YEAR / interval.minutes() as f64)
+    }
+
+    /// Get the volatility annualized assuming one sample per `minutes`
+    pub fn annualized_per_minutes(&self, minutes: f64) -> Option<f64> {
+        if minutes <= 0.0 {
+            return None;
+        }
+        self.annualized(MINUTES_PER_YEAR / minutes)
+    }
+
+    /// Clear all samples
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.last_close = None;
+        #[cfg(feature = "ohlc")]
+        {
+            self.pending = None;
+        }
+    }
+
+    fn push_sample(&mut self, sample: f64) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_to_close_constant_returns() {
+        let mut vol = Volatility::close_to_close(10);
+        let mut price = 100.0;
+        for _ in 0..5 {
+            vol.update_price(price);
+            price *= 1.01;
+        }
+
+        // Identical returns have zero dispersion
+        assert!(vol.value().unwrap() < 1e-12);
+    }
+
+    #[test]
+    fn test_close_to_close_value() {
+        let mut vol = Volatility::close_to_close(10);
+        for price in [100.0, 110.0, 100.0] {
+            vol.update_price(price);
+        }
+
+        let up = (110.0f64 / 100.0).ln();
+        let down = (100.0f64 / 110.0).ln();
+        let mean = (up + down) / 2.0;
+        let expected = (((up - mean).powi(2) + (down - mean).powi(2)) / 1.0).sqrt();
+        assert!((vol.value().unwrap() - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_parkinson_value() {
+        let mut vol = Volatility::parkinson(10);
+        vol.update_candle(101.0, 99.0, 100.0);
+        vol.update_candle(101.0, 99.0, 100.0);
+
+        let expected = ((101.0f64 / 99.0).ln().powi(2) / (4.0 * std::f64::consts::LN_2)).sqrt();
+        assert!((vol.value().unwrap() - expected).abs() < 1e-12);
+
+        // Parkinson ignores bare prices
+        vol.update_price(120.0);
+        assert_eq!(vol.samples(), 2);
+    }
+
+    #[test]
+    fn test_window_limit() {
+        let mut vol = Volatility::close_to_close(3);
+        for price in [100.0, 101.0, 102.0, 103.0, 104.0, 105.0] {
+            vol.update_price(price);
+        }
+        assert_eq!(vol.samples(), 3);
+        assert!(vol.is_warm());
+    }
+
+    #[test]
+    fn test_annualized() {
+        let mut vol = Volatility::close_to_close(10);
+        for price in [100.0, 101.0, 99.0, 100.0] {
+            vol.update_price(price);
+        }
+        let daily = vol.value().unwrap();
+        assert!((vol.annualized(365.0).unwrap() - daily * 365f64.sqrt()).abs() < 1e-12);
+        assert!(vol.annualized_per_minutes(0.0).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "ohlc")]
+    fn test_update_ohlc_commits_on_new_interval() {
+        let candle = |begin: &str, high: f64, low: f64| OHLC {
+            symbol: "BTC/USD".to_string(),
+            open: low,
+            high,
+            low,
+            close: high,
+            vwap: low,
+            volume: 1.0,
+            count: 1,
+            interval: 1,
+            timestamp: begin.to_string(),
+            interval_begin: begin.to_string(),
+            synthetic: false,
+        };
+
+        let mut vol = Volatility::parkinson(10);
+        vol.update_ohlc(&candle("00:00", 101.0, 99.0));
+        vol.update_ohlc(&candle("00:00", 102.0, 99.0));
+        assert_eq!(vol.samples(), 0);
+
+        vol.update_ohlc(&candle("00:01", 101.0, 100.0));
+        assert_eq!(vol.samples(), 1);
+    }
+
+    #[test]
+    fn test_not_enough_samples() {
+        let mut vol = Volatility::close_to_close(10);
+        assert!(vol.value().is_none());
+        vol.update_price(100.0);
+        vol.update_price(101.0);
+        assert!(vol.value().is_none());
+    }
+}
@@ -0,0 +1,290 @@
+//! Volume profile (volume traded at each price)
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+#[cfg(feature = "trades")]
+use crate::models::Trade;
+
+/// Default share of volume contained in the value area (70%)
+pub const DEFAULT_VALUE_AREA_PCT: f64 = 0.70;
+
+/// Value area of a volume profile
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ValueArea {
+    /// Point of control (price level with the most volume)
+    pub poc: f64,
+    /// Value area high (upper bound of the highest included bin)
+    pub high: f64,
+    /// Value area low (lower bound of the lowest included bin)
+    pub low: f64,
+    /// Volume inside the value area
+    pub volume: f64,
+}
+
+/// Bins traded volume by price
+///
+/// Each trade is added to the bin containing its price. Bins are identified
+/// by their lower edge (`floor(price / bin_size) * bin_size`). The profile
+/// covers the whole session until [`reset`](Self::reset) is called, or only
+/// the most recent trades when built [`with_window`](Self::with_window).
+///
+/// # Example
+///
+/// ```
+/// use kraky::VolumeProfile;
+///
+/// let mut profile = VolumeProfile::new(10.0);
+/// profile.add(100.0, 1.0);
+/// profile.add(112.0, 5.0);
+/// profile.add(125.0, 1.0);
+///
+/// assert_eq!(profile.poc(), Some(110.0));
+/// let va = profile.value_area(0.7).unwrap();
+/// assert_eq!((va.low, va.high), (110.0, 120.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VolumeProfile {
+    /// Width of each price bin
+    bin_size: f64,
+    /// Volume per bin index
+    bins: BTreeMap<i64, f64>,
+    /// Maximum number of trades kept (`None` = whole session)
+    window: Option<usize>,
+    /// Trades in the window as (bin index, volume), oldest first
+    trades: VecDeque<(i64, f64)>,
+    /// Total volume in the profile
+    total: f64,
+}
+
+impl VolumeProfile {
+    /// Create a session profile with the given bin size (price increment)
+    pub fn new(bin_size: f64) -> Self {
+        Self {
+            bin_size: if bin_size > 0.0 { bin_size } else { 1.0 },
+            bins: BTreeMap::new(),
+            window: None,
+            trades: VecDeque::new(),
+            total: 0.0,
+        }
+    }
+
+    /// Only keep the last `trades` trades in the profile
+    pub fn with_window(mut self, trades: usize) -> Self {
+        self.window = Some(trades.max(1));
+        self
+    }
+
+    /// Get the bin size
+    pub fn bin_size(&self) -> f64 {
+        self.bin_size
+    }
+
+    /// Add traded volume at a price
+    ///
+    /// Non-finite or non-positive values are ignored.
+    pub fn add(&mut self, price: f64, volume: f64) {
+        if !price.is_finite() || !volume.is_finite() || price <= 0.0 || volume <= 0.0 {
+            return;
+        }
+
+        let bin = (price / self.bin_size).floor() as i64;
+        *self.bins.entry(bin).or_insert(0.0) += volume;
+        self.total += volume;
+
+        if let Some(window) = self.window {
+            self.trades.push_back((bin, volume));
+            while self.trades.len() > window {
+                if let Some((old_bin, old_volume)) = self.trades.pop_front() {
+                    self.remove(old_bin, old_volume);
+                }
+            }
+        }
+    }
+
+    /// Add a trade to the profile
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub fn add_trade(&mut self, trade: &Trade) {
+        self.add(trade.price, trade.qty);
+    }
+
+    /// Total volume in the profile
+    pub fn total_volume(&self) -> f64 {
+        self.total
+    }
+
+    /// Check whether the profile has no volume
+    pub fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+
+    /// Volume in the bin containing `price`
+    pub fn volume_at(&self, price: f64) -> f64 {
+        let bin = (price / self.bin_size).floor() as i64;
+        self.bins.get(&bin).copied().unwrap_or(0.0)
+    }
+
+    /// All non-empty bins as (price, volume), ascending by price
+    pub fn levels(&self) -> Vec<(f64, f64)> {
+        self.bins
+            .iter()
+            .map(|(&bin, &volume)| (self.price_of(bin), volume))
+            .collect()
+    }
+
+    /// Point of control: the price bin with the most volume
+    ///
+    /// Ties go to the lower price.
+    pub fn poc(&self) -> Option<f64> {
+        self.poc_bin().map(|bin| self.price_of(bin))
+    }
+
+    /// Value area containing `pct` (0.0 - 1.0) of the total volume
+    ///
+    /// Starts at the point of control and repeatedly adds the neighbouring
+    /// bin (above or below) with more volume until the target is reached.
+    pub fn value_area(&self, pct: f64) -> Option<ValueArea> {
+        let poc = self.poc_bin()?;
+        let target = self.total * pct.clamp(0.0, 1.0);
+
+        let mut volume = self.bins[&poc];
+        let mut below = self.bins.range(..poc).rev().peekable();
+        let mut above = self.bins.range(poc + 1..).peekable();
+        let (mut low, mut high) = (poc, poc);
+
+        while volume < target {
+            let take_above = match (below.peek(), above.peek()) {
+                (Some((_, b)), Some((_, a))) => *a > *b,
+                (None, Some(_)) => true,
+                (Some(_), None) => false,
+                (None, None) => break,
+            };
+
+            let (&bin, &v) = if take_above {
+                above.next()?
+            } else {
+                below.next()?
+            };
+            volume += v;
+            low = low.min(bin);
+            high = high.max(bin);
+        }
+
+        Some(ValueArea {
+            poc: self.price_of(poc),
+            high: self.price_of(high + 1),
+            low: self.price_of(low),
+            volume,
+        })
+    }
+
+    /// Value area containing 70% of the volume
+    pub fn default_value_area(&self) -> Option<ValueArea> {
+        self.value_area(DEFAULT_VALUE_AREA_PCT)
+    }
+
+    /// Clear the profile (e.g. at the start of a new session)
+    pub fn reset(&mut self) {
+        self.bins.clear();
+        self.trades.clear();
+        self.total = 0.0;
+    }
+
+    fn poc_bin(&self) -> Option<i64> {
+        self.bins
+            .iter()
+            .fold(
+                None,
+                |best: Option<(i64, f64)>, (&bin, &volume)| match best {
+                    Some((_, best_volume)) if best_volume >= volume => best,
+                    _ => Some((bin, volume)),
+                },
+            )
+            .map(|(bin, _)| bin)
+    }
+
+    fn price_of(&self, bin: i64) -> f64 {
+        bin as f64 * self.bin_size
+    }
+
+    fn remove(&mut self, bin: i64, volume: f64) {
+        self.total = (self.total - volume).max(0.0);
+        if let Some(v) = self.bins.get_mut(&bin) {
+            *v -= volume;
+            if *v <= f64::EPSILON {
+                self.bins.remove(&bin);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binning_and_poc() {
+        let mut profile = VolumeProfile::new(5.0);
+        profile.add(101.0, 1.0);
+        profile.add(104.9, 2.0);
+        profile.add(106.0, 2.5);
+
+        assert_eq!(profile.levels(), vec![(100.0, 3.0), (105.0, 2.5)]);
+        assert_eq!(profile.poc(), Some(100.0));
+        assert_eq!(profile.volume_at(102.0), 3.0);
+        assert_eq!(profile.total_volume(), 5.5);
+    }
+
+    #[test]
+    fn test_value_area_expands_towards_larger_neighbour() {
+        let mut profile = VolumeProfile::new(1.0);
+        for (price, volume) in [
+            (1.0, 5.0),
+            (2.0, 10.0),
+            (3.0, 40.0),
+            (4.0, 30.0),
+            (5.0, 15.0),
+        ] {
+            profile.add(price, volume);
+        }
+
+        // 70 of 100: POC (40) + above (30) = 70
+        let va = profile.value_area(0.7).unwrap();
+        assert_eq!(va.poc, 3.0);
+        assert_eq!(va.low, 3.0);
+        assert_eq!(va.high, 5.0);
+        assert_eq!(va.volume, 70.0);
+
+        let full = profile.value_area(1.0).unwrap();
+        assert_eq!((full.low, full.high), (1.0, 6.0));
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let mut profile = VolumeProfile::new(1.0).with_window(2);
+        profile.add(10.0, 5.0);
+        profile.add(20.0, 1.0);
+        profile.add(30.0, 2.0);
+
+        assert_eq!(profile.levels(), vec![(20.0, 1.0), (30.0, 2.0)]);
+        assert_eq!(profile.total_volume(), 3.0);
+        assert_eq!(profile.poc(), Some(30.0));
+    }
+
+    #[test]
+    fn test_empty_and_reset() {
+        let mut profile = VolumeProfile::new(1.0);
+        assert!(profile.poc().is_none());
+        assert!(profile.default_value_area().is_none());
+
+        profile.add(10.0, 1.0);
+        profile.add(f64::NAN, 1.0);
+        profile.add(10.0, -1.0);
+        assert_eq!(profile.total_volume(), 1.0);
+
+        profile.reset();
+        assert!(profile.is_empty());
+    }
+}
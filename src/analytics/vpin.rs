@@ -0,0 +1,205 @@
+//! VPIN (volume-synchronized probability of informed trading)
+
+use std::collections::VecDeque;
+
+#[cfg(feature = "trades")]
+use crate::models::{Trade, TradeSide};
+
+/// Default number of volume buckets in the VPIN window
+pub const DEFAULT_VPIN_WINDOW: usize = 50;
+
+/// VPIN flow toxicity estimator
+///
+/// Trades are grouped into buckets of equal traded volume. For each full
+/// bucket the absolute difference between buy and sell volume is recorded,
+/// and VPIN is the average order imbalance over the last `window` buckets:
+///
+/// `VPIN = Σ |V_buy - V_sell| / (window * bucket_volume)`
+///
+/// Kraken reports the taker side of every trade, so trades are classified
+/// exactly rather than with bulk volume classification. Values close to 1.0
+/// indicate one-sided (potentially informed) flow, a risk signal for market
+/// makers.
+///
+/// # Example
+///
+/// ```
+/// use kraky::Vpin;
+///
+/// let mut vpin = Vpin::new(10.0, 2);
+/// vpin.add_buy(10.0);   // bucket 1: fully one-sided
+/// vpin.add_buy(5.0);
+/// vpin.add_sell(5.0);   // bucket 2: balanced
+///
+/// assert_eq!(vpin.value(), Some(0.5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Vpin {
+    /// Volume per bucket
+    bucket_volume: f64,
+    /// Number of buckets in the window
+    window: usize,
+    /// Buy volume in the bucket being filled
+    current_buy: f64,
+    /// Sell volume in the bucket being filled
+    current_sell: f64,
+    /// |buy - sell| of completed buckets, oldest first
+    buckets: VecDeque<f64>,
+}
+
+impl Vpin {
+    /// Create a new estimator with the given bucket volume and window (in buckets)
+    ///
+    /// A common choice for `bucket_volume` is 1/50th of the average daily volume.
+    pub fn new(bucket_volume: f64, window: usize) -> Self {
+        Self {
+            bucket_volume: if bucket_volume > 0.0 {
+                bucket_volume
+            } else {
+                1.0
+            },
+            window: window.max(1),
+            current_buy: 0.0,
+            current_sell: 0.0,
+            buckets: VecDeque::new(),
+        }
+    }
+
+    /// Get the bucket volume
+    pub fn bucket_volume(&self) -> f64 {
+        self.bucket_volume
+    }
+
+    /// Get the window size in buckets
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Number of completed buckets in the window
+    pub fn buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Check whether the window is full
+    pub fn is_warm(&self) -> bool {
+        self.buckets.len() >= self.window
+    }
+
+    /// Record buyer-initiated volume
+    pub fn add_buy(&mut self, volume: f64) {
+        self.add(volume, true);
+    }
+
+    /// Record seller-initiated volume
+    pub fn add_sell(&mut self, volume: f64) {
+        self.add(volume, false);
+    }
+
+    /// Record a trade, classified by its taker side
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub fn add_trade(&mut self, trade: &Trade) {
+        match trade.side {
+            TradeSide::Buy => self.add_buy(trade.qty),
+            TradeSide::Sell => self.add_sell(trade.qty),
+        }
+    }
+
+    /// Get the current VPIN (0.0 - 1.0)
+    ///
+    /// Returns `None` until `window` buckets have been filled.
+    pub fn value(&self) -> Option<f64> {
+        if !self.is_warm() {
+            return None;
+        }
+        let imbalance: f64 = self.buckets.iter().sum();
+        Some(imbalance / (self.buckets.len() as f64 * self.bucket_volume))
+    }
+
+    /// Clear all buckets
+    pub fn reset(&mut self) {
+        self.current_buy = 0.0;
+        self.current_sell = 0.0;
+        self.buckets.clear();
+    }
+
+    fn add(&mut self, volume: f64, buy: bool) {
+        if !volume.is_finite() || volume <= 0.0 {
+            return;
+        }
+
+        // Large trades are split across as many buckets as they fill
+        let mut remaining = volume;
+        while remaining > 0.0 {
+            let space = self.bucket_volume - (self.current_buy + self.current_sell);
+            let take = remaining.min(space);
+            if buy {
+                self.current_buy += take;
+            } else {
+                self.current_sell += take;
+            }
+            remaining -= take;
+
+            if self.current_buy + self.current_sell >= self.bucket_volume * (1.0 - 1e-9) {
+                if self.buckets.len() == self.window {
+                    self.buckets.pop_front();
+                }
+                self.buckets
+                    .push_back((self.current_buy - self.current_sell).abs());
+                self.current_buy = 0.0;
+                self.current_sell = 0.0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_sided_flow() {
+        let mut vpin = Vpin::new(5.0, 3);
+        for _ in 0..15 {
+            vpin.add_sell(1.0);
+        }
+        assert_eq!(vpin.value(), Some(1.0));
+    }
+
+    #[test]
+    fn test_balanced_flow() {
+        let mut vpin = Vpin::new(2.0, 4);
+        for _ in 0..4 {
+            vpin.add_buy(1.0);
+            vpin.add_sell(1.0);
+        }
+        assert_eq!(vpin.value(), Some(0.0));
+    }
+
+    #[test]
+    fn test_large_trade_spans_buckets() {
+        let mut vpin = Vpin::new(10.0, 3);
+        vpin.add_sell(5.0);
+        vpin.add_buy(25.0);
+
+        // Buckets: |5 - 5| = 0, 10, 10 -> 20 / 30
+        assert_eq!(vpin.buckets(), 3);
+        assert!((vpin.value().unwrap() - 2.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_window_rolls_and_warmup() {
+        let mut vpin = Vpin::new(1.0, 2);
+        vpin.add_buy(1.0);
+        assert!(vpin.value().is_none());
+
+        vpin.add_buy(1.0);
+        vpin.add_buy(0.5);
+        vpin.add_sell(0.5);
+        vpin.add_buy(0.5);
+        vpin.add_sell(0.5);
+        assert_eq!(vpin.buckets(), 2);
+        assert_eq!(vpin.value(), Some(0.0));
+    }
+}
@@ -0,0 +1,331 @@
+//! Whale (large order and trade) detection
+
+use crate::models::{Orderbook, OrderedFloat};
+use crate::subscriptions::{Subscription, SubscriptionSender};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "trades")]
+use crate::models::{Trade, TradeSide};
+
+/// Default number of book levels per side inspected for whales
+pub const DEFAULT_WHALE_DEPTH: usize = 10;
+
+/// Size threshold above which an order or trade counts as a whale
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WhaleThreshold {
+    /// Minimum size in the base asset (e.g. 10.0 BTC)
+    BaseQty(f64),
+    /// Minimum notional value in the quote asset (e.g. 1,000,000 USD)
+    Notional(f64),
+}
+
+impl WhaleThreshold {
+    /// Check whether a price/quantity pair meets the threshold
+    pub fn is_whale(&self, price: f64, qty: f64) -> bool {
+        match *self {
+            WhaleThreshold::BaseQty(min) => qty >= min,
+            WhaleThreshold::Notional(min) => price * qty >= min,
+        }
+    }
+}
+
+/// Side of a whale order or trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WhaleSide {
+    /// Resting bid or aggressive buy
+    Buy,
+    /// Resting ask or aggressive sell
+    Sell,
+}
+
+impl std::fmt::Display for WhaleSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WhaleSide::Buy => write!(f, "buy"),
+            WhaleSide::Sell => write!(f, "sell"),
+        }
+    }
+}
+
+/// Where a whale was observed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhaleKind {
+    /// A large resting order appeared in the orderbook
+    BookLevel,
+    /// A large trade was executed
+    Trade,
+}
+
+/// A detected whale order or trade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhaleEvent {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Side of the order or trade
+    pub side: WhaleSide,
+    /// Price level or execution price
+    pub price: f64,
+    /// Size in the base asset
+    pub qty: f64,
+    /// Whether this came from the book or the trade stream
+    pub kind: WhaleKind,
+}
+
+impl WhaleEvent {
+    /// Notional value (price * quantity)
+    pub fn notional(&self) -> f64 {
+        self.price * self.qty
+    }
+}
+
+/// Detects large orders in the orderbook and large trades
+///
+/// Feed it orderbooks (and trades, with the `trades` feature) and it emits a
+/// [`WhaleEvent`] to every subscriber when a whale shows up. Book levels are
+/// only reported once while they stay above the threshold, so polling the
+/// same orderbook repeatedly does not produce duplicate events.
+///
+/// Only available when the `analytics` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use kraky::{KrakyClient, WhaleDetector, WhaleThreshold};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = KrakyClient::connect().await?;
+/// let mut orderbook = client.subscribe_orderbook("BTC/USD", 25).await?;
+///
+/// let mut detector = WhaleDetector::new(WhaleThreshold::BaseQty(10.0));
+/// let mut whales = detector.subscribe();
+///
+/// tokio::spawn(async move {
+///     while let Some(whale) = whales.next().await {
+///         println!("🐋 {} {} {} @ {}", whale.symbol, whale.side, whale.qty, whale.price);
+///     }
+/// });
+///
+/// while let Some(_update) = orderbook.next().await {
+///     if let Some(ob) = client.get_orderbook("BTC/USD") {
+///         detector.check_orderbook(&ob);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct WhaleDetector {
+    /// Size threshold
+    threshold: WhaleThreshold,
+    /// Number of levels per side to inspect
+    depth: usize,
+    /// Whale levels currently resting in each book (side, price)
+    active_levels: HashMap<String, HashSet<(WhaleSide, OrderedFloat)>>,
+    /// Event subscribers
+    senders: Vec<SubscriptionSender<WhaleEvent>>,
+}
+
+impl WhaleDetector {
+    /// Create a new detector with the given threshold
+    pub fn new(threshold: WhaleThreshold) -> Self {
+        Self {
+            threshold,
+            depth: DEFAULT_WHALE_DEPTH,
+            active_levels: HashMap::new(),
+            senders: Vec::new(),
+        }
+    }
+
+    /// Set the number of levels per side inspected in the orderbook
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Get the configured threshold
+    pub fn threshold(&self) -> WhaleThreshold {
+        self.threshold
+    }
+
+    /// Subscribe to whale events
+    ///
+    /// Every subscriber receives all events emitted after it subscribed.
+    pub fn subscribe(&mut self) -> Subscription<WhaleEvent> {
+        let (sender, subscription) = SubscriptionSender::new("whale".to_string(), "*".to_string());
+        self.senders.push(sender);
+        subscription
+    }
+
+    /// Inspect the top levels of an orderbook for new whale orders
+    ///
+    /// Returns the newly detected whales (which are also sent to subscribers).
+    /// Levels that were already reported are not reported again until they
+    /// drop below the threshold or leave the inspected depth.
+    pub fn check_orderbook(&mut self, orderbook: &Orderbook) -> Vec<WhaleEvent> {
+        let mut current = HashSet::new();
+        let mut events = Vec::new();
+
+        let bids = orderbook
+            .bids
+            .iter()
+            .rev()
+            .take(self.depth)
+            .map(|(p, q)| (WhaleSide::Buy, *p, *q));
+        let asks = orderbook
+            .asks
+            .iter()
+            .take(self.depth)
+            .map(|(p, q)| (WhaleSide::Sell, *p, *q));
+
+        let previous = self.active_levels.get(&orderbook.symbol);
+        for (side, price, qty) in bids.chain(asks) {
+            if !self.threshold.is_whale(price.0, qty) {
+                continue;
+            }
+            current.insert((side, price));
+            if previous.map(|p| p.contains(&(side, price))) != Some(true) {
+                events.push(WhaleEvent {
+                    symbol: orderbook.symbol.clone(),
+                    side,
+                    price: price.0,
+                    qty,
+                    kind: WhaleKind::BookLevel,
+                });
+            }
+        }
+
+        self.active_levels.insert(orderbook.symbol.clone(), current);
+        for event in &events {
+            self.emit(event.clone());
+        }
+        events
+    }
+
+    /// Check a trade against the threshold
+    ///
+    /// Returns the whale event if the trade is large enough (it is also sent
+    /// to subscribers).
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub fn check_trade(&mut self, trade: &Trade) -> Option<WhaleEvent> {
+        if !self.threshold.is_whale(trade.price, trade.qty) {
+            return None;
+        }
+
+        let event = WhaleEvent {
+            symbol: trade.symbol.clone(),
+            side: match trade.side {
+                TradeSide::Buy => WhaleSide::Buy,
+                TradeSide::Sell => WhaleSide::Sell,
+            },
+            price: trade.price,
+            qty: trade.qty,
+            kind: WhaleKind::Trade,
+        };
+        self.emit(event.clone());
+        Some(event)
+    }
+
+    /// Forget the tracked whale levels for a symbol (e.g. after a reconnect)
+    pub fn reset(&mut self, symbol: &str) {
+        self.active_levels.remove(symbol);
+    }
+
+    fn emit(&mut self, event: WhaleEvent) {
+        self.senders.retain(|s| s.send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrderbookData, PriceLevelRaw};
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> OrderbookData {
+        let levels = |l: &[(f64, f64)]| {
+            l.iter()
+                .map(|&(price, qty)| PriceLevelRaw { price, qty })
+                .collect()
+        };
+        OrderbookData {
+            symbol: "BTC/USD".to_string(),
+            bids: levels(bids),
+            asks: levels(asks),
+            checksum: 0,
+            timestamp: "".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_threshold() {
+        assert!(WhaleThreshold::BaseQty(10.0).is_whale(1.0, 10.0));
+        assert!(!WhaleThreshold::BaseQty(10.0).is_whale(1_000_000.0, 9.9));
+        assert!(WhaleThreshold::Notional(1_000_000.0).is_whale(50_000.0, 20.0));
+        assert!(!WhaleThreshold::Notional(1_000_000.0).is_whale(50_000.0, 19.0));
+    }
+
+    #[tokio::test]
+    async fn test_book_whales_reported_once() {
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.apply_update(&book(
+            &[(50000.0, 12.0), (49990.0, 1.0)],
+            &[(50010.0, 15.0)],
+        ));
+
+        let mut detector = WhaleDetector::new(WhaleThreshold::BaseQty(10.0));
+        let mut whales = detector.subscribe();
+
+        let events = detector.check_orderbook(&ob);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].side, WhaleSide::Buy);
+        assert_eq!(events[0].kind, WhaleKind::BookLevel);
+        assert_eq!(events[1].side, WhaleSide::Sell);
+
+        // Same book again: nothing new
+        assert!(detector.check_orderbook(&ob).is_empty());
+
+        // Level drops below and comes back: reported again
+        ob.apply_update(&book(&[(50000.0, 1.0)], &[]));
+        assert!(detector.check_orderbook(&ob).is_empty());
+        ob.apply_update(&book(&[(50000.0, 11.0)], &[]));
+        assert_eq!(detector.check_orderbook(&ob).len(), 1);
+
+        assert_eq!(whales.next().await.unwrap().qty, 12.0);
+        assert_eq!(whales.next().await.unwrap().qty, 15.0);
+        assert_eq!(whales.next().await.unwrap().qty, 11.0);
+    }
+
+    #[test]
+    fn test_depth_limits_inspection() {
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.apply_update(&book(&[(50000.0, 1.0), (49990.0, 20.0)], &[]));
+
+        let mut detector = WhaleDetector::new(WhaleThreshold::BaseQty(10.0)).with_depth(1);
+        assert!(detector.check_orderbook(&ob).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "trades")]
+    fn test_trade_whale() {
+        use crate::models::TradeOrderType;
+
+        let mut detector = WhaleDetector::new(WhaleThreshold::Notional(1_000_000.0));
+        let trade = Trade {
+            symbol: "BTC/USD".to_string(),
+            side: TradeSide::Sell,
+            price: 50_000.0,
+            qty: 25.0,
+            ord_type: TradeOrderType::Market,
+            trade_id: 1,
+            timestamp: "".to_string(),
+        };
+
+        let event = detector.check_trade(&trade).unwrap();
+        assert_eq!(event.side, WhaleSide::Sell);
+        assert_eq!(event.kind, WhaleKind::Trade);
+        assert_eq!(event.notional(), 1_250_000.0);
+    }
+}
@@ -0,0 +1,348 @@
+//! Apache Arrow conversions
+//!
+//! Turns buffered `Trade`, `OHLC` and `OrderbookSnapshot` collections into Arrow
+//! [`RecordBatch`]es with [`ToRecordBatch`], so live data can go straight
+//! into Polars, DataFusion, DuckDB or Parquet without manual mapping.
+//!
+//! Requires the `arrow` feature flag (conversions also need the matching
+//! data type feature). Timestamps are converted to
+//! `Timestamp(Nanosecond, "UTC")` columns; unparseable timestamps become
+//! nulls.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::arrow::ToRecordBatch;
+//! use kraky::KrakyClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! let mut trades = client.subscribe_trades("BTC/USD").await?;
+//!
+//! let mut buffer = Vec::new();
+//! while let Some(trade) = trades.next().await {
+//!     buffer.push((*trade).clone());
+//!     if buffer.len() == 1000 {
+//!         let batch = buffer.to_record_batch()?;
+//!         println!("{} rows, schema: {:?}", batch.num_rows(), batch.schema());
+//!         buffer.clear();
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With Polars, a batch can be loaded through the Arrow C data interface or
+//! by writing it to Arrow IPC / Parquet and reading it back.
+
+use crate::error::{KrakyError, Result};
+use arrow_array::RecordBatch;
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use std::sync::Arc;
+
+#[cfg(any(feature = "trades", feature = "ohlc", feature = "orderbook"))]
+use arrow_array::{ArrayRef, Float64Array, StringArray, TimestampNanosecondArray};
+
+/// Conversion of a collection into an Arrow [`RecordBatch`]
+pub trait ToRecordBatch {
+    /// Schema of the produced batches
+    fn schema() -> SchemaRef;
+
+    /// Convert the collection into a record batch
+    fn to_record_batch(&self) -> Result<RecordBatch>;
+}
+
+/// `Timestamp(Nanosecond, "UTC")`
+fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
+}
+
+fn arrow_error(e: ArrowError) -> KrakyError {
+    KrakyError::InvalidMessage(format!("Arrow error: {}", e))
+}
+
+/// Parse an RFC 3339 timestamp into nanoseconds since the epoch
+#[cfg(any(feature = "trades", feature = "ohlc"))]
+fn parse_nanos(timestamp: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()?
+        .timestamp_nanos_opt()
+}
+
+/// Build a UTC timestamp column
+#[cfg(any(feature = "trades", feature = "ohlc", feature = "orderbook"))]
+fn timestamps(values: impl IntoIterator<Item = Option<i64>>) -> ArrayRef {
+    Arc::new(TimestampNanosecondArray::from_iter(values).with_timezone("UTC"))
+}
+
+/// Trades, one row per trade
+///
+/// Columns: `symbol`, `side`, `price`, `qty`, `ord_type`, `trade_id`,
+/// `timestamp`.
+#[cfg(feature = "trades")]
+impl ToRecordBatch for [crate::models::Trade] {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("symbol", DataType::Utf8, false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("qty", DataType::Float64, false),
+            Field::new("ord_type", DataType::Utf8, false),
+            Field::new("trade_id", DataType::Int64, false),
+            Field::new("timestamp", timestamp_type(), true),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        use crate::models::TradeOrderType;
+        use arrow_array::Int64Array;
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                self.iter().map(|t| t.symbol.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                self.iter().map(|t| t.side.to_string()),
+            )),
+            Arc::new(Float64Array::from_iter_values(self.iter().map(|t| t.price))),
+            Arc::new(Float64Array::from_iter_values(self.iter().map(|t| t.qty))),
+            Arc::new(StringArray::from_iter_values(self.iter().map(
+                |t| match t.ord_type {
+                    TradeOrderType::Market => "market",
+                    TradeOrderType::Limit => "limit",
+                },
+            ))),
+            Arc::new(Int64Array::from_iter_values(
+                self.iter().map(|t| t.trade_id),
+            )),
+            timestamps(self.iter().map(|t| parse_nanos(&t.timestamp))),
+        ];
+        RecordBatch::try_new(Self::schema(), columns).map_err(arrow_error)
+    }
+}
+
+/// Candles, one row per candle
+///
+/// Columns: `symbol`, `open`, `high`, `low`, `close`, `vwap`, `volume`,
+/// `count`, `interval`, `interval_begin`, `timestamp`.
+#[cfg(feature = "ohlc")]
+impl ToRecordBatch for [crate::models::OHLC] {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("symbol", DataType::Utf8, false),
+            Field::new("open", DataType::Float64, false),
+            Field::new("high", DataType::Float64, false),
+            Field::new("low", DataType::Float64, false),
+            Field::new("close", DataType::Float64, false),
+            Field::new("vwap", DataType::Float64, false),
+            Field::new("volume", DataType::Float64, false),
+            Field::new("count", DataType::Int64, false),
+            Field::new("interval", DataType::UInt32, false),
+            Field::new("interval_begin", timestamp_type(), true),
+            Field::new("timestamp", timestamp_type(), true),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        use arrow_array::{Int64Array, UInt32Array};
+
+        let prices = |f: fn(&crate::models::OHLC) -> f64| -> ArrayRef {
+            Arc::new(Float64Array::from_iter_values(self.iter().map(f)))
+        };
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                self.iter().map(|c| c.symbol.as_str()),
+            )),
+            prices(|c| c.open),
+            prices(|c| c.high),
+            prices(|c| c.low),
+            prices(|c| c.close),
+            prices(|c| c.vwap),
+            prices(|c| c.volume),
+            Arc::new(Int64Array::from_iter_values(self.iter().map(|c| c.count))),
+            Arc::new(UInt32Array::from_iter_values(
+                self.iter().map(|c| c.interval),
+            )),
+            timestamps(self.iter().map(|c| parse_nanos(&c.interval_begin))),
+            timestamps(self.iter().map(|c| parse_nanos(&c.timestamp))),
+        ];
+        RecordBatch::try_new(Self::schema(), columns).map_err(arrow_error)
+    }
+}
+
+/// Orderbook snapshots in long format, one row per price level
+///
+/// Columns: `snapshot_id`, `symbol`, `timestamp`, `sequence`, `side`
+/// (`bid`/`ask`), `level` (0 = best price), `price`, `qty`.
+#[cfg(feature = "orderbook")]
+impl ToRecordBatch for [crate::models::OrderbookSnapshot] {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("snapshot_id", DataType::Utf8, false),
+            Field::new("symbol", DataType::Utf8, false),
+            Field::new("timestamp", timestamp_type(), true),
+            Field::new("sequence", DataType::UInt64, false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("level", DataType::UInt32, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("qty", DataType::Float64, false),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        use arrow_array::{UInt32Array, UInt64Array};
+
+        let rows: Vec<_> = self
+            .iter()
+            .flat_map(|snapshot| {
+                let bids = snapshot.bids.iter().enumerate().map(|(i, l)| ("bid", i, l));
+                let asks = snapshot.asks.iter().enumerate().map(|(i, l)| ("ask", i, l));
+                bids.chain(asks).map(move |level| (snapshot, level))
+            })
+            .collect();
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|(s, _)| s.id.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|(s, _)| s.symbol.as_str()),
+            )),
+            timestamps(rows.iter().map(|(s, _)| s.timestamp.timestamp_nanos_opt())),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|(s, _)| s.sequence),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|(_, (side, _, _))| *side),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                rows.iter().map(|(_, (_, i, _))| *i as u32),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|(_, (_, _, l))| l.price),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|(_, (_, _, l))| l.qty),
+            )),
+        ];
+        RecordBatch::try_new(Self::schema(), columns).map_err(arrow_error)
+    }
+}
+
+/// Implement `ToRecordBatch` for `Vec<T>` in terms of `[T]`
+macro_rules! vec_to_record_batch {
+    ($($feature:literal => $ty:ty),* $(,)?) => {$(
+        #[cfg(feature = $feature)]
+        impl ToRecordBatch for Vec<$ty> {
+            fn schema() -> SchemaRef {
+                <[$ty]>::schema()
+            }
+
+            fn to_record_batch(&self) -> Result<RecordBatch> {
+                self.as_slice().to_record_batch()
+            }
+        }
+    )*};
+}
+
+vec_to_record_batch! {
+    "trades" => crate::models::Trade,
+    "ohlc" => crate::models::OHLC,
+    "orderbook" => crate::models::OrderbookSnapshot,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "trades")]
+    #[test]
+    fn test_trades_batch() {
+        use crate::models::{Trade, TradeOrderType, TradeSide};
+        use arrow_array::Array;
+
+        let trades = vec![
+            Trade {
+                symbol: "BTC/USD".to_string(),
+                side: TradeSide::Buy,
+                price: 42501.0,
+                qty: 0.5,
+                ord_type: TradeOrderType::Market,
+                trade_id: 1,
+                timestamp: "2024-01-15T10:30:00.123456Z".to_string(),
+            },
+            Trade {
+                symbol: "BTC/USD".to_string(),
+                side: TradeSide::Sell,
+                price: 42500.0,
+                qty: 1.0,
+                ord_type: TradeOrderType::Limit,
+                trade_id: 2,
+                timestamp: "not a timestamp".to_string(),
+            },
+        ];
+
+        let batch = trades.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), Vec::<Trade>::schema());
+
+        let side = batch
+            .column_by_name("side")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(side.value(1), "sell");
+
+        let timestamp = batch
+            .column_by_name("timestamp")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap();
+        assert_eq!(timestamp.value(0), 1_705_314_600_123_456_000);
+        assert!(timestamp.is_null(1));
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_snapshots_batch() {
+        use crate::models::{Orderbook, OrderbookSnapshot, OrderedFloat};
+
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.bids.insert(OrderedFloat(100.0), 1.0);
+        ob.bids.insert(OrderedFloat(99.0), 2.0);
+        ob.asks.insert(OrderedFloat(101.0), 0.5);
+        let snapshots = vec![OrderbookSnapshot::from_orderbook(&ob, 10)];
+
+        let batch = snapshots.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 3);
+
+        let price = batch
+            .column_by_name("price")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(price.values(), &[100.0, 99.0, 101.0]);
+    }
+
+    #[cfg(feature = "ohlc")]
+    #[test]
+    fn test_ohlc_batch() {
+        use crate::models::OHLC;
+
+        let candles: Vec<OHLC> = Vec::new();
+        let batch = candles.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 0);
+        assert_eq!(batch.num_columns(), 11);
+        assert_eq!(
+            batch
+                .schema()
+                .field_with_name("interval_begin")
+                .unwrap()
+                .data_type(),
+            &timestamp_type()
+        );
+    }
+}
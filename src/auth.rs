@@ -1,23 +1,160 @@
 //! Authentication module for Kraken WebSocket API
 //!
-//! This module provides HMAC-SHA256 signing for authenticated WebSocket subscriptions.
+//! This module provides HMAC-SHA256 signing for authenticated WebSocket subscriptions,
+//! and HMAC-SHA512 request signing for the REST API when the `rest` feature is enabled.
 //!
 //! Requires the `auth` feature flag.
+//!
+//! # Loading credentials
+//!
+//! [`Credentials::from_env`] reads `KRAKEN_API_KEY` and `KRAKEN_API_SECRET`,
+//! [`Credentials::from_file`] reads a TOML file:
+//!
+//! ```toml
+//! api_key = "your_api_key"
+//! api_secret = "your_base64_secret"
+//! ```
+//!
+//! With the `keyring` feature, `Credentials::from_keyring` reads them from
+//! the OS keychain. The secret is zeroed in memory when the credentials are
+//! dropped.
+//!
+//! # Two-factor authentication
+//!
+//! Keys created with a 2FA password need a one-time password on every
+//! request. Set a static password with [`Credentials::with_otp`], or a
+//! generator (e.g. TOTP) with [`Credentials::with_otp_provider`]; it is then
+//! sent as the `otp` field of signed REST requests and included in
+//! WebSocket tokens.
 
 use crate::error::{KrakyError, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use hmac::{Hmac, Mac};
+use serde::Deserialize;
 use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroize;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Environment variable read by [`Credentials::from_env`] for the API key
+pub const API_KEY_ENV: &str = "KRAKEN_API_KEY";
+
+/// Environment variable read by [`Credentials::from_env`] for the API secret
+pub const API_SECRET_ENV: &str = "KRAKEN_API_SECRET";
+
+/// Keyring entry names used by `Credentials::from_keyring`
+#[cfg(feature = "keyring")]
+const KEYRING_KEY_ENTRY: &str = "api_key";
+#[cfg(feature = "keyring")]
+const KEYRING_SECRET_ENTRY: &str = "api_secret";
+
+/// Clock returning the current time in nanoseconds
+type NonceClock = Arc<dyn Fn() -> u64 + Send + Sync>;
+
+/// Source of one-time passwords for keys with 2FA
+type OtpProvider = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// Strictly increasing nonces for one API key
+///
+/// Nonces follow the system clock in nanoseconds, but never repeat or go
+/// backwards: two calls in the same nanosecond, or after the clock was set
+/// back, still get increasing values. [`Credentials`] and its clones share
+/// one provider, so every signed WebSocket and REST request made with a key
+/// draws from the same sequence.
+///
+/// To stay ahead of nonces used before a restart, save [`last`](Self::last)
+/// and start the next provider with [`resume_after`](Self::resume_after).
+///
+/// # Example
+///
+/// ```
+/// use kraky::NonceProvider;
+///
+/// let nonces = NonceProvider::with_clock(|| 1_000);
+/// assert_eq!(nonces.next(), 1_000);
+/// assert_eq!(nonces.next(), 1_001);
+///
+/// let resumed = NonceProvider::new().resume_after(u64::MAX - 1);
+/// assert_eq!(resumed.next(), u64::MAX);
+/// ```
+pub struct NonceProvider {
+    last: AtomicU64,
+    clock: NonceClock,
+}
+
+impl Default for NonceProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for NonceProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NonceProvider")
+            .field("last", &self.last())
+            .finish()
+    }
+}
+
+impl NonceProvider {
+    /// Create a provider following the system clock
+    pub fn new() -> Self {
+        Self::with_clock(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        })
+    }
+
+    /// Create a provider following another clock (for testing)
+    pub fn with_clock(clock: impl Fn() -> u64 + Send + Sync + 'static) -> Self {
+        Self {
+            last: AtomicU64::new(0),
+            clock: Arc::new(clock),
+        }
+    }
+
+    /// Only hand out nonces greater than `last`
+    pub fn resume_after(self, last: u64) -> Self {
+        self.last.fetch_max(last, Ordering::SeqCst);
+        self
+    }
+
+    /// Get the next nonce
+    pub fn next(&self) -> u64 {
+        let now = (self.clock)();
+        let previous = self
+            .last
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last.saturating_add(1)))
+            })
+            .unwrap_or_else(|last| last);
+        now.max(previous.saturating_add(1))
+    }
+
+    /// Get the last nonce handed out, to persist across restarts
+    pub fn last(&self) -> u64 {
+        self.last.load(Ordering::SeqCst)
+    }
+}
+
 /// Authentication credentials for Kraken API
+///
+/// The API secret is zeroed in memory when the credentials are dropped.
 #[derive(Clone)]
 pub struct Credentials {
     /// API key (public)
     pub api_key: String,
     /// API secret (private, base64 encoded)
     api_secret: String,
+    /// Nonces for requests signed with this key
+    nonces: Arc<NonceProvider>,
+    /// One-time passwords, if the key has 2FA enabled
+    otp: Option<OtpProvider>,
 }
 
 impl Credentials {
@@ -40,12 +177,150 @@ impl Credentials {
         Self {
             api_key: api_key.into(),
             api_secret: api_secret.into(),
+            nonces: Arc::new(NonceProvider::new()),
+            otp: None,
         }
     }
 
+    /// Load credentials from the `KRAKEN_API_KEY` and `KRAKEN_API_SECRET`
+    /// environment variables
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kraky::Credentials;
+    ///
+    /// let creds = Credentials::from_env()?;
+    /// # Ok::<(), kraky::KrakyError>(())
+    /// ```
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| KrakyError::Authentication(format!("{} is not set", name)))
+        };
+        Ok(Self::new(var(API_KEY_ENV)?, var(API_SECRET_ENV)?))
+    }
+
+    /// Load credentials from a TOML file with `api_key` and `api_secret`
+    ///
+    /// A leading `~/` is expanded to the home directory.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kraky::Credentials;
+    ///
+    /// let creds = Credentials::from_file("~/.kraky/credentials.toml")?;
+    /// # Ok::<(), kraky::KrakyError>(())
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = expand_home(path.as_ref());
+        let mut contents = std::fs::read_to_string(&path)?;
+        let parsed = toml::from_str::<CredentialsFile>(&contents);
+        contents.zeroize();
+        let mut file = parsed.map_err(|e| {
+            KrakyError::Authentication(format!(
+                "invalid credentials file {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })?;
+        Ok(Self::new(
+            std::mem::take(&mut file.api_key),
+            std::mem::take(&mut file.api_secret),
+        ))
+    }
+
+    /// Load credentials stored under `service` in the OS keyring
+    ///
+    /// Reads the `api_key` and `api_secret` entries, as written by
+    /// [`store_in_keyring`](Self::store_in_keyring).
+    ///
+    /// Only available when the `keyring` feature is enabled.
+    #[cfg(feature = "keyring")]
+    pub fn from_keyring(service: &str) -> Result<Self> {
+        let read = |entry: &str| {
+            keyring::Entry::new(service, entry)
+                .and_then(|entry| entry.get_password())
+                .map_err(|e| keyring_error(service, entry, e))
+        };
+        Ok(Self::new(
+            read(KEYRING_KEY_ENTRY)?,
+            read(KEYRING_SECRET_ENTRY)?,
+        ))
+    }
+
+    /// Save the credentials under `service` in the OS keyring
+    ///
+    /// Only available when the `keyring` feature is enabled.
+    #[cfg(feature = "keyring")]
+    pub fn store_in_keyring(&self, service: &str) -> Result<()> {
+        let write = |entry: &str, value: &str| {
+            keyring::Entry::new(service, entry)
+                .and_then(|entry| entry.set_password(value))
+                .map_err(|e| keyring_error(service, entry, e))
+        };
+        write(KEYRING_KEY_ENTRY, &self.api_key)?;
+        write(KEYRING_SECRET_ENTRY, &self.api_secret)
+    }
+
+    /// Use a shared or pre-seeded nonce provider
+    ///
+    /// Useful when several `Credentials` values for the same key are
+    /// created independently, or to resume after a persisted nonce.
+    pub fn with_nonce_provider(mut self, nonces: Arc<NonceProvider>) -> Self {
+        self.nonces = nonces;
+        self
+    }
+
+    /// Get the nonce provider for this key
+    pub fn nonce_provider(&self) -> &Arc<NonceProvider> {
+        &self.nonces
+    }
+
+    /// Get the next nonce for a request signed with this key
+    pub fn next_nonce(&self) -> u64 {
+        self.nonces.next()
+    }
+
+    /// Send a static 2FA password with every signed request
+    pub fn with_otp(self, password: impl Into<String>) -> Self {
+        let password = password.into();
+        self.with_otp_provider(move || password.clone())
+    }
+
+    /// Ask `provider` for a one-time password before every signed request
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kraky::Credentials;
+    ///
+    /// # fn current_totp() -> String { String::new() }
+    /// let creds = Credentials::new("key", "secret").with_otp_provider(current_totp);
+    /// ```
+    pub fn with_otp_provider(
+        mut self,
+        provider: impl Fn() -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.otp = Some(Arc::new(provider));
+        self
+    }
+
+    /// Check whether requests carry a one-time password
+    pub fn has_otp(&self) -> bool {
+        self.otp.is_some()
+    }
+
+    /// Get the current one-time password, if 2FA is configured
+    pub fn otp(&self) -> Option<String> {
+        self.otp.as_ref().map(|provider| provider())
+    }
+
     /// Generate authentication token for WebSocket subscription
     ///
     /// Uses HMAC-SHA256 to sign the request according to Kraken API specs.
+    /// When 2FA is configured, the one-time password is signed after the
+    /// nonce.
     ///
     /// # Arguments
     /// * `nonce` - Unique nonce (timestamp in nanoseconds recommended)
@@ -58,8 +333,11 @@ impl Credentials {
             .decode(&self.api_secret)
             .map_err(|e| KrakyError::InvalidMessage(format!("Invalid API secret: {}", e)))?;
 
-        // Create the message to sign: nonce as string
-        let message = nonce.to_string();
+        // Create the message to sign: nonce as string, then the OTP if any
+        let mut message = nonce.to_string();
+        if let Some(otp) = self.otp() {
+            message.push_str(&otp);
+        }
 
         // Create HMAC-SHA256 with the decoded secret
         let mut mac = HmacSha256::new_from_slice(&secret_bytes)
@@ -75,12 +353,74 @@ impl Credentials {
         Ok(BASE64.encode(signature))
     }
 
+    /// Sign a private REST request (the `API-Sign` header)
+    ///
+    /// Computes HMAC-SHA512 of the URI path followed by SHA256(nonce + POST
+    /// data), keyed with the decoded API secret.
+    ///
+    /// Only available when the `rest` feature is enabled.
+    ///
+    /// # Arguments
+    /// * `path` - URI path, e.g. `/0/private/AddOrder`
+    /// * `nonce` - The nonce included in `post_data`
+    /// * `post_data` - URL-encoded request body
+    #[cfg(feature = "rest")]
+    pub fn sign_rest(&self, path: &str, nonce: u64, post_data: &str) -> Result<String> {
+        use sha2::{Digest, Sha512};
+
+        let secret_bytes = BASE64
+            .decode(&self.api_secret)
+            .map_err(|e| KrakyError::InvalidMessage(format!("Invalid API secret: {}", e)))?;
+
+        let digest = Sha256::digest(format!("{}{}", nonce, post_data).as_bytes());
+
+        let mut mac = Hmac::<Sha512>::new_from_slice(&secret_bytes)
+            .map_err(|e| KrakyError::InvalidMessage(format!("HMAC error: {}", e)))?;
+        mac.update(path.as_bytes());
+        mac.update(&digest);
+
+        Ok(BASE64.encode(mac.finalize().into_bytes()))
+    }
+
     /// Get API key
     pub fn api_key(&self) -> &str {
         &self.api_key
     }
 }
 
+impl Drop for Credentials {
+    fn drop(&mut self) {
+        self.api_secret.zeroize();
+    }
+}
+
+/// Contents of a credentials file
+#[derive(Deserialize)]
+struct CredentialsFile {
+    api_key: String,
+    api_secret: String,
+}
+
+impl Drop for CredentialsFile {
+    fn drop(&mut self) {
+        self.api_secret.zeroize();
+    }
+}
+
+/// Expand a leading `~/` to the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (path.strip_prefix("~"), home) {
+        (Ok(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_error(service: &str, entry: &str, error: keyring::Error) -> KrakyError {
+    KrakyError::Authentication(format!("keyring {}/{}: {}", service, entry, error))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,6 +457,90 @@ mod tests {
         assert_eq!(token1, token2);
     }
 
+    #[test]
+    fn test_nonces_never_repeat_or_regress() {
+        let clock = Arc::new(AtomicU64::new(100));
+        let nonces = {
+            let clock = Arc::clone(&clock);
+            NonceProvider::with_clock(move || clock.load(Ordering::SeqCst))
+        };
+        assert_eq!(nonces.next(), 100);
+        // Same clock reading
+        assert_eq!(nonces.next(), 101);
+        // Clock set back
+        clock.store(50, Ordering::SeqCst);
+        assert_eq!(nonces.next(), 102);
+        clock.store(500, Ordering::SeqCst);
+        assert_eq!(nonces.next(), 500);
+        assert_eq!(nonces.last(), 500);
+
+        // Clones of the credentials share the sequence
+        let creds = Credentials::new("test_key", "dGVzdF9zZWNyZXQ=")
+            .with_nonce_provider(Arc::new(NonceProvider::with_clock(|| 7)));
+        let clone = creds.clone();
+        assert_eq!((creds.next_nonce(), clone.next_nonce()), (7, 8));
+    }
+
+    #[test]
+    fn test_otp() {
+        let plain = Credentials::new("test_key", "dGVzdF9zZWNyZXQ=");
+        assert!(!plain.has_otp());
+        assert_eq!(plain.otp(), None);
+
+        let with_otp = plain.clone().with_otp("123456");
+        assert_eq!(with_otp.otp().as_deref(), Some("123456"));
+        assert_ne!(
+            plain.generate_token(1).unwrap(),
+            with_otp.generate_token(1).unwrap()
+        );
+
+        // Providers are asked for every request
+        let calls = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&calls);
+        let creds = plain
+            .with_otp_provider(move || (counter.fetch_add(1, Ordering::SeqCst) + 1).to_string());
+        assert_eq!(creds.otp().as_deref(), Some("1"));
+        assert_eq!(creds.otp().as_deref(), Some("2"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let dir = std::env::temp_dir().join(format!("kraky-creds-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("credentials.toml");
+
+        std::fs::write(
+            &path,
+            "api_key = \"file_key\"\napi_secret = \"dGVzdF9zZWNyZXQ=\"\n",
+        )
+        .unwrap();
+        let creds = Credentials::from_file(&path).unwrap();
+        assert_eq!(creds.api_key(), "file_key");
+        assert!(creds.generate_token(1).is_ok());
+
+        std::fs::write(&path, "api_key = \"file_key\"\n").unwrap();
+        let err = Credentials::from_file(&path).err().unwrap();
+        assert!(err.to_string().contains("api_secret"));
+        assert!(Credentials::from_file(dir.join("missing.toml")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_expand_home() {
+        if let Some(home) = std::env::var_os("HOME") {
+            assert_eq!(
+                expand_home(Path::new("~/.kraky/credentials.toml")),
+                Path::new(&home).join(".kraky/credentials.toml")
+            );
+        }
+        assert_eq!(
+            expand_home(Path::new("/etc/kraky")),
+            Path::new("/etc/kraky")
+        );
+    }
+
     #[test]
     fn test_different_nonces() {
         // Different nonces should produce different signatures
@@ -125,4 +549,25 @@ mod tests {
         let token2 = creds.generate_token(9876543210).unwrap();
         assert_ne!(token1, token2);
     }
+
+    #[cfg(feature = "rest")]
+    #[test]
+    fn test_rest_signature() {
+        // Example from Kraken's REST authentication documentation
+        let creds = Credentials::new(
+            "test_key",
+            "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==",
+        );
+        let signature = creds
+            .sign_rest(
+                "/0/private/AddOrder",
+                1616492376594,
+                "nonce=1616492376594&ordertype=limit&pair=XBTUSD&price=37500&type=buy&volume=1.25",
+            )
+            .unwrap();
+        assert_eq!(
+            signature,
+            "4/dpxb3iT4tp/ZCVEwSnEsLxx0bqyhLpdfOpc6fn7OR8+UClSV5n9E6aSS8MPtnRfp32bAb0nmbRn6H8ndwLUQ=="
+        );
+    }
 }
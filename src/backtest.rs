@@ -0,0 +1,626 @@
+//! Backtesting on recorded market data
+//!
+//! [`Backtest`] runs a strategy callback over the trades of a
+//! [`ReplayClient`] recording. Trades are aggregated into candles with a
+//! [`CandleAggregator`], and orders placed through the [`PaperBroker`] are
+//! filled against later trades, so a strategy never trades on a price it has
+//! not seen yet. The run produces a [`BacktestReport`] with the trade log and
+//! summary statistics (PnL, max drawdown, hit rate).
+//!
+//! Requires the `backtest` feature flag.
+//!
+//! ## Fill model
+//!
+//! - Market orders fill at the price of the next trade on their symbol
+//! - Buy limits fill at the limit price once a trade prints at or below it,
+//!   sell limits once a trade prints at or above it
+//! - Each fill pays `fee_rate` times its notional value
+//!
+//! Queue position, partial fills and slippage are not modelled.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use kraky::backtest::{Backtest, BacktestEvent};
+//! use kraky::replay::ReplayClient;
+//! use kraky::Interval;
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let replay = ReplayClient::open("recordings")?;
+//!
+//!     let report = Backtest::new(&replay)
+//!         .with_cash(10_000.0)
+//!         .with_fee_rate(0.0026)
+//!         .with_candles(Interval::Min5)
+//!         .run(|event, broker| {
+//!             // Buy green candles, sell red ones
+//!             if let BacktestEvent::Candle(candle) = event {
+//!                 let position = broker.position(&candle.symbol);
+//!                 if candle.close > candle.open && position == 0.0 {
+//!                     broker.buy_market(&candle.symbol, 0.01);
+//!                 } else if candle.close < candle.open && position > 0.0 {
+//!                     broker.sell_market(&candle.symbol, position);
+//!                 }
+//!             }
+//!         });
+//!
+//!     println!("PnL: {:.2}", report.pnl);
+//!     println!("Max drawdown: {:.2}%", report.max_drawdown * 100.0);
+//!     println!("Hit rate: {:.1}%", report.hit_rate() * 100.0);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::models::{Interval, Trade, TradeSide, OHLC};
+use crate::replay::ReplayClient;
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Market event passed to the strategy callback
+#[derive(Debug, Clone, Copy)]
+pub enum BacktestEvent<'a> {
+    /// A recorded trade
+    Trade(&'a Trade),
+    /// A candle completed by the aggregator
+    Candle(&'a OHLC),
+}
+
+/// Builds candles from a stream of trades
+///
+/// A candle is complete once a trade for the same symbol lands in a later
+/// interval; intervals without trades produce no candle.
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+    interval: Interval,
+    open: HashMap<String, OpenCandle>,
+}
+
+#[derive(Debug, Clone)]
+struct OpenCandle {
+    begin: i64,
+    candle: OHLC,
+    notional: f64,
+}
+
+impl CandleAggregator {
+    /// Create an aggregator for one interval
+    pub fn new(interval: Interval) -> Self {
+        Self {
+            interval,
+            open: HashMap::new(),
+        }
+    }
+
+    /// Add a trade, returning the previous candle if the trade closed it
+    ///
+    /// Trades whose timestamp cannot be parsed are ignored.
+    pub fn push(&mut self, trade: &Trade) -> Option<OHLC> {
+        let time = DateTime::parse_from_rfc3339(&trade.timestamp).ok()?;
+        self.push_at(trade, time.with_timezone(&Utc))
+    }
+
+    fn push_at(&mut self, trade: &Trade, time: DateTime<Utc>) -> Option<OHLC> {
+        let width = i64::from(self.interval.minutes()) * 60;
+        let begin = time.timestamp().div_euclid(width) * width;
+
+        match self.open.get_mut(&trade.symbol) {
+            Some(open) if open.begin == begin => {
+                let candle = &mut open.candle;
+                candle.high = candle.high.max(trade.price);
+                candle.low = candle.low.min(trade.price);
+                candle.close = trade.price;
+                candle.volume += trade.qty;
+                candle.count += 1;
+                open.notional += trade.price * trade.qty;
+                if candle.volume > 0.0 {
+                    candle.vwap = open.notional / candle.volume;
+                }
+                None
+            }
+            // Late trades for an already closed interval are dropped
+            Some(open) if open.begin > begin => None,
+            _ => {
+                let next = self.start_candle(trade, begin, width);
+                self.open
+                    .insert(trade.symbol.clone(), next)
+                    .map(|closed| closed.candle)
+            }
+        }
+    }
+
+    fn start_candle(&self, trade: &Trade, begin: i64, width: i64) -> OpenCandle {
+        let format = |seconds: i64| {
+            Utc.timestamp_opt(seconds, 0)
+                .single()
+                .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+                .unwrap_or_default()
+        };
+        OpenCandle {
+            begin,
+            candle: OHLC {
+                symbol: trade.symbol.clone(),
+                open: trade.price,
+                high: trade.price,
+                low: trade.price,
+                close: trade.price,
+                vwap: trade.price,
+                volume: trade.qty,
+                count: 1,
+                interval: self.interval.minutes(),
+                timestamp: format(begin + width),
+                interval_begin: format(begin),
+                synthetic: false,
+            },
+            notional: trade.price * trade.qty,
+        }
+    }
+
+    /// Candles still open, one per symbol
+    pub fn pending(&self) -> impl Iterator<Item = &OHLC> {
+        self.open.values().map(|open| &open.candle)
+    }
+}
+
+/// Order type of a paper order
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaperOrderType {
+    /// Fill at the next trade price
+    Market,
+    /// Fill at the limit price once the market trades through it
+    Limit(f64),
+}
+
+/// An order resting with the [`PaperBroker`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperOrder {
+    /// Order ID assigned by the broker
+    pub id: u64,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Order side
+    pub side: TradeSide,
+    /// Order quantity
+    pub qty: f64,
+    /// Market or limit
+    pub order_type: PaperOrderType,
+}
+
+/// An executed paper order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fill {
+    /// ID of the filled order
+    pub order_id: u64,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Fill side
+    pub side: TradeSide,
+    /// Filled quantity
+    pub qty: f64,
+    /// Fill price
+    pub price: f64,
+    /// Fee paid in quote currency
+    pub fee: f64,
+    /// Part of the quantity that reduced an existing position
+    pub closed_qty: f64,
+    /// Realized PnL of the closed quantity (before fees)
+    pub realized_pnl: f64,
+    /// Time of the trade that triggered the fill
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Position {
+    qty: f64,
+    avg_price: f64,
+}
+
+/// Simulated broker filling orders against replayed trades
+///
+/// Positions can go short. Cash is in the quote currency and assumes every
+/// symbol shares it.
+#[derive(Debug, Clone)]
+pub struct PaperBroker {
+    cash: f64,
+    fee_rate: f64,
+    next_id: u64,
+    orders: Vec<PaperOrder>,
+    positions: HashMap<String, Position>,
+    marks: HashMap<String, f64>,
+    fills: Vec<Fill>,
+}
+
+impl PaperBroker {
+    /// Create a broker with starting cash and a fee rate (e.g. `0.0026`)
+    pub fn new(cash: f64, fee_rate: f64) -> Self {
+        Self {
+            cash,
+            fee_rate,
+            next_id: 1,
+            orders: Vec::new(),
+            positions: HashMap::new(),
+            marks: HashMap::new(),
+            fills: Vec::new(),
+        }
+    }
+
+    /// Queue a market buy, returning the order ID
+    pub fn buy_market(&mut self, symbol: &str, qty: f64) -> u64 {
+        self.submit(symbol, TradeSide::Buy, qty, PaperOrderType::Market)
+    }
+
+    /// Queue a market sell, returning the order ID
+    pub fn sell_market(&mut self, symbol: &str, qty: f64) -> u64 {
+        self.submit(symbol, TradeSide::Sell, qty, PaperOrderType::Market)
+    }
+
+    /// Place a limit buy, returning the order ID
+    pub fn buy_limit(&mut self, symbol: &str, qty: f64, price: f64) -> u64 {
+        self.submit(symbol, TradeSide::Buy, qty, PaperOrderType::Limit(price))
+    }
+
+    /// Place a limit sell, returning the order ID
+    pub fn sell_limit(&mut self, symbol: &str, qty: f64, price: f64) -> u64 {
+        self.submit(symbol, TradeSide::Sell, qty, PaperOrderType::Limit(price))
+    }
+
+    fn submit(
+        &mut self,
+        symbol: &str,
+        side: TradeSide,
+        qty: f64,
+        order_type: PaperOrderType,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.orders.push(PaperOrder {
+            id,
+            symbol: symbol.to_string(),
+            side,
+            qty,
+            order_type,
+        });
+        id
+    }
+
+    /// Cancel an open order, returning whether it was still open
+    pub fn cancel(&mut self, order_id: u64) -> bool {
+        let before = self.orders.len();
+        self.orders.retain(|order| order.id != order_id);
+        self.orders.len() != before
+    }
+
+    /// Orders waiting to be filled
+    pub fn open_orders(&self) -> &[PaperOrder] {
+        &self.orders
+    }
+
+    /// Signed position for a symbol (negative when short)
+    pub fn position(&self, symbol: &str) -> f64 {
+        self.positions.get(symbol).map_or(0.0, |p| p.qty)
+    }
+
+    /// Available cash in quote currency
+    pub fn cash(&self) -> f64 {
+        self.cash
+    }
+
+    /// Last traded price seen for a symbol
+    pub fn last_price(&self, symbol: &str) -> Option<f64> {
+        self.marks.get(symbol).copied()
+    }
+
+    /// Cash plus positions marked at their last traded price
+    pub fn equity(&self) -> f64 {
+        self.cash
+            + self
+                .positions
+                .iter()
+                .map(|(symbol, p)| p.qty * self.marks.get(symbol).copied().unwrap_or(p.avg_price))
+                .sum::<f64>()
+    }
+
+    /// Fills so far, oldest first
+    pub fn fills(&self) -> &[Fill] {
+        &self.fills
+    }
+
+    /// Fill resting orders that the trade reaches, then mark the symbol
+    fn on_trade(&mut self, trade: &Trade, time: DateTime<Utc>) {
+        let mut i = 0;
+        while i < self.orders.len() {
+            let order = &self.orders[i];
+            let price = if order.symbol != trade.symbol {
+                None
+            } else {
+                match (order.order_type, order.side) {
+                    (PaperOrderType::Market, _) => Some(trade.price),
+                    (PaperOrderType::Limit(limit), TradeSide::Buy) if trade.price <= limit => {
+                        Some(limit)
+                    }
+                    (PaperOrderType::Limit(limit), TradeSide::Sell) if trade.price >= limit => {
+                        Some(limit)
+                    }
+                    _ => None,
+                }
+            };
+            match price {
+                Some(price) => {
+                    let order = self.orders.remove(i);
+                    self.fill(order, price, time);
+                }
+                None => i += 1,
+            }
+        }
+        self.marks.insert(trade.symbol.clone(), trade.price);
+    }
+
+    fn fill(&mut self, order: PaperOrder, price: f64, time: DateTime<Utc>) {
+        let signed = match order.side {
+            TradeSide::Buy => order.qty,
+            TradeSide::Sell => -order.qty,
+        };
+        let fee = order.qty * price * self.fee_rate;
+        self.cash -= signed * price + fee;
+
+        let position = self.positions.entry(order.symbol.clone()).or_default();
+        let mut closed_qty = 0.0;
+        let mut realized_pnl = 0.0;
+        if position.qty == 0.0 || position.qty.signum() == signed.signum() {
+            let qty = position.qty.abs() + order.qty;
+            position.avg_price =
+                (position.avg_price * position.qty.abs() + price * order.qty) / qty;
+        } else {
+            closed_qty = order.qty.min(position.qty.abs());
+            realized_pnl = closed_qty * (price - position.avg_price) * position.qty.signum();
+            if order.qty > position.qty.abs() {
+                // Flipped through zero: the remainder opens at this price
+                position.avg_price = price;
+            }
+        }
+        position.qty += signed;
+        if position.qty.abs() < f64::EPSILON {
+            *position = Position::default();
+        }
+
+        self.fills.push(Fill {
+            order_id: order.id,
+            symbol: order.symbol,
+            side: order.side,
+            qty: order.qty,
+            price,
+            fee,
+            closed_qty,
+            realized_pnl,
+            time,
+        });
+    }
+}
+
+/// Results of a backtest run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestReport {
+    /// Every fill, oldest first
+    pub fills: Vec<Fill>,
+    /// Equity after each replayed trade
+    pub equity_curve: Vec<(DateTime<Utc>, f64)>,
+    /// Starting cash
+    pub initial_equity: f64,
+    /// Cash plus open positions at the last traded prices
+    pub final_equity: f64,
+    /// `final_equity - initial_equity`, after fees
+    pub pnl: f64,
+    /// Total fees paid
+    pub total_fees: f64,
+    /// Largest peak-to-trough equity decline, as a fraction of the peak
+    pub max_drawdown: f64,
+    /// Fills that reduced or closed a position
+    pub closed_trades: usize,
+    /// Closing fills with a positive realized PnL after fees
+    pub winning_trades: usize,
+}
+
+impl BacktestReport {
+    /// Share of closing fills that made money (0.0 without any)
+    pub fn hit_rate(&self) -> f64 {
+        if self.closed_trades == 0 {
+            0.0
+        } else {
+            self.winning_trades as f64 / self.closed_trades as f64
+        }
+    }
+}
+
+/// Backtest of a strategy callback over a replay recording
+pub struct Backtest<'a> {
+    replay: &'a ReplayClient,
+    cash: f64,
+    fee_rate: f64,
+    candles: Option<Interval>,
+}
+
+impl<'a> Backtest<'a> {
+    /// Create a backtest over a recording with 10,000 quote units and no fees
+    pub fn new(replay: &'a ReplayClient) -> Self {
+        Self {
+            replay,
+            cash: 10_000.0,
+            fee_rate: 0.0,
+            candles: None,
+        }
+    }
+
+    /// Set the starting cash
+    pub fn with_cash(mut self, cash: f64) -> Self {
+        self.cash = cash;
+        self
+    }
+
+    /// Set the fee charged on each fill's notional (e.g. `0.0026` for 0.26%)
+    pub fn with_fee_rate(mut self, fee_rate: f64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Aggregate trades into candles of this interval for the strategy
+    pub fn with_candles(mut self, interval: Interval) -> Self {
+        self.candles = Some(interval);
+        self
+    }
+
+    /// Run the strategy over every recorded trade
+    ///
+    /// For each trade, resting orders are filled first, then any candle the
+    /// trade closed is passed to the strategy, then the trade itself.
+    /// Playback runs as fast as possible regardless of the replay speed.
+    pub fn run<F>(self, mut strategy: F) -> BacktestReport
+    where
+        F: FnMut(BacktestEvent<'_>, &mut PaperBroker),
+    {
+        let mut broker = PaperBroker::new(self.cash, self.fee_rate);
+        let mut aggregator = self.candles.map(CandleAggregator::new);
+        let mut equity_curve = Vec::new();
+
+        for (recorded_at, trade) in self.replay.recorded_trades() {
+            let time = DateTime::parse_from_rfc3339(&trade.timestamp)
+                .map(|time| time.with_timezone(&Utc))
+                .unwrap_or(recorded_at);
+
+            broker.on_trade(trade, time);
+            equity_curve.push((time, broker.equity()));
+
+            if let Some(aggregator) = aggregator.as_mut() {
+                if let Some(candle) = aggregator.push_at(trade, time) {
+                    strategy(BacktestEvent::Candle(&candle), &mut broker);
+                }
+            }
+            strategy(BacktestEvent::Trade(trade), &mut broker);
+        }
+
+        let final_equity = broker.equity();
+        let closing: Vec<_> = broker
+            .fills
+            .iter()
+            .filter(|fill| fill.closed_qty > 0.0)
+            .collect();
+
+        BacktestReport {
+            initial_equity: self.cash,
+            final_equity,
+            pnl: final_equity - self.cash,
+            total_fees: broker.fills.iter().map(|fill| fill.fee).sum(),
+            max_drawdown: max_drawdown(&equity_curve),
+            closed_trades: closing.len(),
+            winning_trades: closing
+                .iter()
+                .filter(|fill| fill.realized_pnl - fill.fee > 0.0)
+                .count(),
+            equity_curve,
+            fills: broker.fills,
+        }
+    }
+}
+
+fn max_drawdown(curve: &[(DateTime<Utc>, f64)]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut worst: f64 = 0.0;
+    for &(_, equity) in curve {
+        peak = peak.max(equity);
+        if peak > 0.0 {
+            worst = worst.max((peak - equity) / peak);
+        }
+    }
+    worst
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(prices: &[(u32, f64)]) -> ReplayClient {
+        let lines: String = prices
+            .iter()
+            .enumerate()
+            .map(|(i, (second, price))| {
+                let timestamp = format!("2024-01-01T00:{:02}:{:02}Z", second / 60, second % 60);
+                format!(
+                    r#"{{"recorded_at":"{timestamp}","channel":"trade","data":{{"symbol":"BTC/USD","side":"buy","price":{price},"qty":1.0,"ord_type":"market","trade_id":{i},"timestamp":"{timestamp}"}}}}"#,
+                ) + "\n"
+            })
+            .collect();
+        ReplayClient::from_reader(lines.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_candle_aggregation() {
+        let replay = recording(&[(0, 100.0), (20, 104.0), (40, 98.0), (61, 101.0)]);
+        let mut aggregator = CandleAggregator::new(Interval::Min1);
+        let candles: Vec<_> = replay
+            .recorded_trades()
+            .filter_map(|(_, trade)| aggregator.push(trade))
+            .collect();
+
+        assert_eq!(candles.len(), 1);
+        let candle = &candles[0];
+        assert_eq!(
+            (candle.open, candle.high, candle.low, candle.close),
+            (100.0, 104.0, 98.0, 98.0)
+        );
+        assert_eq!(candle.count, 3);
+        assert_eq!(candle.interval_begin, "2024-01-01T00:00:00Z");
+        assert_eq!(candle.timestamp, "2024-01-01T00:01:00Z");
+        assert!((candle.vwap - 302.0 / 3.0).abs() < 1e-9);
+        assert_eq!(aggregator.pending().next().unwrap().open, 101.0);
+    }
+
+    #[test]
+    fn test_market_orders_fill_on_next_trade() {
+        let replay = recording(&[(0, 100.0), (1, 102.0), (2, 110.0), (3, 105.0)]);
+        let report = Backtest::new(&replay)
+            .with_cash(1_000.0)
+            .run(|event, broker| {
+                if let BacktestEvent::Trade(trade) = event {
+                    match trade.trade_id {
+                        0 => {
+                            broker.buy_market("BTC/USD", 1.0);
+                        }
+                        2 => {
+                            broker.sell_market("BTC/USD", 1.0);
+                        }
+                        _ => {}
+                    }
+                }
+            });
+
+        let prices: Vec<_> = report.fills.iter().map(|fill| fill.price).collect();
+        assert_eq!(prices, vec![102.0, 105.0]);
+        assert_eq!(report.pnl, 3.0);
+        assert_eq!((report.closed_trades, report.winning_trades), (1, 1));
+        assert_eq!(report.hit_rate(), 1.0);
+        // Peak 1008 at 110, then 1003 at 105
+        assert!((report.max_drawdown - 5.0 / 1008.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_limit_orders_and_fees() {
+        let mut broker = PaperBroker::new(1_000.0, 0.01);
+        let buy = broker.buy_limit("BTC/USD", 2.0, 95.0);
+        let sell = broker.sell_limit("BTC/USD", 2.0, 90.0);
+        assert!(broker.cancel(sell));
+        assert!(!broker.cancel(sell));
+
+        let replay = recording(&[(0, 100.0), (1, 94.0)]);
+        for (time, trade) in replay.recorded_trades() {
+            broker.on_trade(trade, time);
+        }
+
+        assert_eq!(broker.fills().len(), 1);
+        let fill = &broker.fills()[0];
+        assert_eq!((fill.order_id, fill.price), (buy, 95.0));
+        assert!((fill.fee - 1.9).abs() < 1e-9);
+        assert_eq!(broker.position("BTC/USD"), 2.0);
+        assert!((broker.cash() - (1_000.0 - 190.0 - 1.9)).abs() < 1e-9);
+        assert!((broker.equity() - (broker.cash() + 188.0)).abs() < 1e-9);
+    }
+}
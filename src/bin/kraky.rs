@@ -0,0 +1,318 @@
+//! Command-line access to Kraken market data
+//!
+//! Streams, books and recordings from the terminal, as plain text or JSON
+//! Lines for piping into other tools:
+//!
+//! ```text
+//! kraky stream trades BTC/USD ETH/USD
+//! kraky stream ohlc BTC/USD --interval 5m --json
+//! kraky book BTC/USD --depth 25 --json
+//! kraky record --out data/ BTC/USD ETH/USD
+//! ```
+//!
+//! Requires the `cli` feature flag: `cargo install kraky --features cli`.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use kraky::models::PriceLevel;
+use kraky::recorder::{RecordFormat, Recorder};
+use kraky::{Interval, KrakyClient};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "kraky",
+    version,
+    about = "Kraken WebSocket v2 market data from the terminal"
+)]
+struct Cli {
+    /// WebSocket endpoint to connect to instead of Kraken's public one
+    #[arg(long, global = true)]
+    url: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print every message of a channel for one or more pairs
+    Stream {
+        /// Channel to stream
+        channel: StreamChannel,
+        /// Trading pairs, e.g. BTC/USD
+        #[arg(required = true)]
+        pairs: Vec<String>,
+        /// Print JSON Lines instead of text
+        #[arg(long)]
+        json: bool,
+        /// Candle interval for `ohlc`, e.g. 1m, 1h, 1d
+        #[arg(long, default_value = "1m")]
+        interval: Interval,
+    },
+    /// Print the top of the managed orderbook after every update
+    Book {
+        /// Trading pair, e.g. BTC/USD
+        pair: String,
+        /// Book depth to subscribe to (10, 25, 100, 500 or 1000)
+        #[arg(long, default_value_t = 10)]
+        depth: u32,
+        /// Print JSON Lines instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Record channels to CSV or JSON Lines files until interrupted
+    Record {
+        /// Directory to write files to
+        #[arg(long, default_value = "data")]
+        out: PathBuf,
+        /// Trading pairs, e.g. BTC/USD
+        #[arg(required = true)]
+        pairs: Vec<String>,
+        /// Channels to record
+        #[arg(long, value_delimiter = ',', default_value = "trades,ticker,book")]
+        channels: Vec<StreamChannel>,
+        /// File format
+        #[arg(long, default_value = "csv")]
+        format: FileFormat,
+        /// Book depth to record
+        #[arg(long, default_value_t = 10)]
+        depth: u32,
+        /// Candle interval for `ohlc`
+        #[arg(long, default_value = "1m")]
+        interval: Interval,
+        /// Start a new file after this many seconds
+        #[arg(long)]
+        rotate_secs: Option<u64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum StreamChannel {
+    Trades,
+    Ticker,
+    Book,
+    Ohlc,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum FileFormat {
+    Csv,
+    Jsonl,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let mut builder = KrakyClient::builder();
+    if let Some(url) = &cli.url {
+        builder = builder.url(url);
+    }
+    let client = Arc::new(builder.connect().await?);
+    match cli.command {
+        Command::Stream {
+            channel,
+            pairs,
+            json,
+            interval,
+        } => stream(&client, channel, pairs, json, interval).await,
+        Command::Book { pair, depth, json } => book(&client, &pair, depth, json).await,
+        Command::Record {
+            out,
+            pairs,
+            channels,
+            format,
+            depth,
+            interval,
+            rotate_secs,
+        } => {
+            let mut recorder = Recorder::new(Arc::clone(&client), out).with_format(match format {
+                FileFormat::Csv => RecordFormat::Csv,
+                FileFormat::Jsonl => RecordFormat::JsonLines,
+            });
+            if let Some(secs) = rotate_secs {
+                recorder = recorder.rotate_interval(Duration::from_secs(secs));
+            }
+            for pair in &pairs {
+                for channel in &channels {
+                    recorder = match channel {
+                        StreamChannel::Trades => recorder.trades(pair),
+                        StreamChannel::Ticker => recorder.ticker(pair),
+                        StreamChannel::Book => recorder.orderbook(pair, depth),
+                        StreamChannel::Ohlc => recorder.ohlc(pair, interval),
+                    };
+                }
+            }
+            let recording = recorder.start().await?;
+            eprintln!("Recording {} pair(s), Ctrl-C to stop", pairs.len());
+            tokio::signal::ctrl_c().await?;
+
+            let records = recording.records();
+            for file in recording.stop().await? {
+                eprintln!("Wrote {}", file.display());
+            }
+            eprintln!("{} records", records);
+            Ok(())
+        }
+    }
+}
+
+/// Print one channel of every pair until the subscriptions end
+async fn stream(
+    client: &KrakyClient,
+    channel: StreamChannel,
+    pairs: Vec<String>,
+    json: bool,
+    interval: Interval,
+) -> Result<()> {
+    let (tx, mut lines) = mpsc::unbounded_channel();
+    for pair in pairs {
+        let tx = tx.clone();
+        match channel {
+            StreamChannel::Trades => {
+                let mut trades = client.subscribe_trades(pair.as_str()).await?;
+                tokio::spawn(async move {
+                    while let Some(t) = trades.next().await {
+                        let text = format!(
+                            "{} {:<10} {:<4} {:>14.2} {:>14.8}",
+                            t.timestamp, t.symbol, t.side, t.price, t.qty
+                        );
+                        if tx.send(line(&*t, text, json)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            StreamChannel::Ticker => {
+                let mut tickers = client.subscribe_ticker(pair.as_str()).await?;
+                tokio::spawn(async move {
+                    while let Some(t) = tickers.next().await {
+                        let text = format!(
+                            "{:<10} last {:.2} bid {:.2} ask {:.2} {:+.2}% vol {:.2}",
+                            t.symbol, t.last, t.bid, t.ask, t.change_pct, t.volume
+                        );
+                        if tx.send(line(&*t, text, json)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            StreamChannel::Book => {
+                let mut updates = client.subscribe_orderbook(pair.as_str(), 10).await?;
+                tokio::spawn(async move {
+                    while let Some(update) = updates.next().await {
+                        let text = update
+                            .data
+                            .iter()
+                            .map(|d| {
+                                format!(
+                                    "{:<10} {:?} bids {} asks {} checksum {}",
+                                    d.symbol,
+                                    update.update_type,
+                                    d.bids.len(),
+                                    d.asks.len(),
+                                    d.checksum
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        if tx.send(line(&*update, text, json)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            StreamChannel::Ohlc => {
+                let mut candles = client.subscribe_ohlc(pair.as_str(), interval).await?;
+                tokio::spawn(async move {
+                    while let Some(c) = candles.next().await {
+                        let text = format!(
+                            "{} {:<10} O {:.2} H {:.2} L {:.2} C {:.2} V {:.4}",
+                            c.interval_begin, c.symbol, c.open, c.high, c.low, c.close, c.volume
+                        );
+                        if tx.send(line(&*c, text, json)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        }
+    }
+    drop(tx);
+
+    while let Some(line) = lines.recv().await {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Print the top levels of a managed book after every update
+async fn book(client: &KrakyClient, pair: &str, depth: u32, json: bool) -> Result<()> {
+    let mut updates = client.subscribe_orderbook(pair, depth).await?;
+    while updates.next().await.is_some() {
+        let Some(book) = client.get_orderbook(pair) else {
+            continue;
+        };
+        let (bids, asks) = (book.top_bids(depth as usize), book.top_asks(depth as usize));
+        if json {
+            #[derive(Serialize)]
+            struct BookLine<'a> {
+                symbol: &'a str,
+                sequence: u64,
+                bids: &'a [PriceLevel],
+                asks: &'a [PriceLevel],
+            }
+            let line = BookLine {
+                symbol: &book.symbol,
+                sequence: book.sequence,
+                bids: &bids,
+                asks: &asks,
+            };
+            println!("{}", serde_json::to_string(&line)?);
+            continue;
+        }
+
+        println!("{} #{}", book.symbol, book.sequence);
+        println!(
+            "{:>16} {:>16} | {:<16} {:<16}",
+            "bid qty", "bid", "ask", "ask qty"
+        );
+        for i in 0..bids.len().max(asks.len()) {
+            let bid = bids
+                .get(i)
+                .map(|l| (format!("{:.8}", l.qty), format!("{:.2}", l.price)));
+            let ask = asks
+                .get(i)
+                .map(|l| (format!("{:.2}", l.price), format!("{:.8}", l.qty)));
+            let (bid_qty, bid_price) = bid.unwrap_or_default();
+            let (ask_price, ask_qty) = ask.unwrap_or_default();
+            println!(
+                "{:>16} {:>16} | {:<16} {:<16}",
+                bid_qty, bid_price, ask_price, ask_qty
+            );
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// Format a message as JSON or the given text
+fn line<T: Serialize>(message: &T, text: String, json: bool) -> String {
+    if json {
+        serde_json::to_string(message).unwrap_or(text)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,286 @@
+//! Synchronous client for code that doesn't use async
+//!
+//! [`KrakyClient`] wraps the async client together with a tokio runtime
+//! that has one dedicated worker thread. The connection, heartbeats and
+//! reconnects run on that thread; the calling thread only blocks while
+//! connecting, subscribing or waiting for the next update. Subscriptions
+//! are plain [`Iterator`]s, and [`Subscription::try_recv`] polls without
+//! waiting, e.g. once per frame of a GUI.
+//!
+//! The client's synchronous methods, such as
+//! [`get_orderbook`](crate::KrakyClient::get_orderbook), are available
+//! through `Deref`; any other async method runs through
+//! [`KrakyClient::block_on`].
+//!
+//! Don't call the blocking methods or drop the client from inside an async
+//! runtime: blocking there stalls or panics the runtime.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "orderbook")]
+//! # {
+//! use kraky::blocking::KrakyClient;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect()?;
+//! let orderbook = client.subscribe_orderbook("BTC/USD", 10)?;
+//!
+//! for update in orderbook.take(5) {
+//!     println!("Orderbook update: {:?}", update.data[0].symbol);
+//! }
+//! if let Some(book) = client.get_orderbook("BTC/USD") {
+//!     println!("Spread: {:?}", book.spread());
+//! }
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+
+use crate::client::ClientBuilder;
+use crate::error::{KrakyError, Result};
+use crate::subscriptions::SubscriptionStats;
+use crate::symbol::Symbol;
+use std::future::Future;
+use std::ops::Deref;
+use std::sync::mpsc::{RecvTimeoutError, TryRecvError};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc::error::TryRecvError as AsyncTryRecvError;
+
+#[cfg(feature = "orderbook")]
+use crate::models::OrderbookUpdate;
+
+#[cfg(feature = "trades")]
+use crate::models::Trade;
+
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+
+#[cfg(feature = "ohlc")]
+use crate::models::{Interval, OHLC};
+
+/// Blocking Kraken WebSocket client
+///
+/// Dereferences to the async [`KrakyClient`](crate::KrakyClient) for its
+/// synchronous methods. Dropping it disconnects and then stops the runtime
+/// thread once every subscription is dropped as well.
+pub struct KrakyClient {
+    client: crate::KrakyClient,
+    runtime: Arc<Runtime>,
+}
+
+impl std::fmt::Debug for KrakyClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KrakyClient")
+            .field("instance_id", &self.client.instance_id())
+            .finish()
+    }
+}
+
+impl KrakyClient {
+    /// Connect to Kraken WebSocket API
+    pub fn connect() -> Result<Self> {
+        crate::KrakyClient::builder().connect_blocking()
+    }
+
+    /// Connect to a custom WebSocket URL (for testing)
+    pub fn connect_with_url(url: &str) -> Result<Self> {
+        crate::KrakyClient::builder().url(url).connect_blocking()
+    }
+
+    /// Start the runtime thread and connect with the builder's options
+    pub(crate) fn with_builder(builder: ClientBuilder) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("kraky-blocking")
+            .enable_all()
+            .build()?;
+        let client = runtime.block_on(builder.connect())?;
+        Ok(Self {
+            client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Run a future on the client's runtime, blocking until it completes
+    ///
+    /// Use this for async methods without a blocking counterpart, e.g.
+    /// private channels and trading; wrap returned subscriptions with
+    /// [`wrap`](Self::wrap).
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Get the underlying async client
+    pub fn inner(&self) -> &crate::KrakyClient {
+        &self.client
+    }
+
+    /// Subscribe to orderbook updates, see
+    /// [`KrakyClient::subscribe_orderbook`](crate::KrakyClient::subscribe_orderbook)
+    #[cfg(feature = "orderbook")]
+    pub fn subscribe_orderbook(
+        &self,
+        pair: impl Into<Symbol>,
+        depth: u32,
+    ) -> Result<Subscription<Arc<OrderbookUpdate>>> {
+        let subscription = self.block_on(self.client.subscribe_orderbook(pair, depth))?;
+        Ok(self.wrap(subscription))
+    }
+
+    /// Subscribe to trades, see
+    /// [`KrakyClient::subscribe_trades`](crate::KrakyClient::subscribe_trades)
+    #[cfg(feature = "trades")]
+    pub fn subscribe_trades(&self, pair: impl Into<Symbol>) -> Result<Subscription<Arc<Trade>>> {
+        let subscription = self.block_on(self.client.subscribe_trades(pair))?;
+        Ok(self.wrap(subscription))
+    }
+
+    /// Subscribe to ticker updates, see
+    /// [`KrakyClient::subscribe_ticker`](crate::KrakyClient::subscribe_ticker)
+    #[cfg(feature = "ticker")]
+    pub fn subscribe_ticker(&self, pair: impl Into<Symbol>) -> Result<Subscription<Arc<Ticker>>> {
+        let subscription = self.block_on(self.client.subscribe_ticker(pair))?;
+        Ok(self.wrap(subscription))
+    }
+
+    /// Subscribe to candles, see
+    /// [`KrakyClient::subscribe_ohlc`](crate::KrakyClient::subscribe_ohlc)
+    #[cfg(feature = "ohlc")]
+    pub fn subscribe_ohlc(
+        &self,
+        pair: impl Into<Symbol>,
+        interval: Interval,
+    ) -> Result<Subscription<Arc<OHLC>>> {
+        let subscription = self.block_on(self.client.subscribe_ohlc(pair, interval))?;
+        Ok(self.wrap(subscription))
+    }
+
+    /// Wrap an async subscription of this client
+    ///
+    /// For subscriptions made through [`block_on`](Self::block_on).
+    pub fn wrap<T>(&self, subscription: crate::Subscription<T>) -> Subscription<T> {
+        Subscription {
+            inner: subscription,
+            runtime: Arc::clone(&self.runtime),
+        }
+    }
+
+    /// Disconnect and wait for the connection tasks to finish
+    pub fn shutdown(&self) {
+        self.block_on(self.client.shutdown());
+    }
+}
+
+impl Deref for KrakyClient {
+    type Target = crate::KrakyClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+/// Blocking subscription, iterating over updates
+///
+/// The iterator ends when the subscription is closed.
+pub struct Subscription<T> {
+    inner: crate::Subscription<T>,
+    runtime: Arc<Runtime>,
+}
+
+impl<T> Subscription<T> {
+    /// Block until the next update, or `None` once the subscription is closed
+    pub fn recv(&mut self) -> Option<T> {
+        self.runtime.block_on(self.inner.next())
+    }
+
+    /// Block until the next update or until `timeout` elapses
+    pub fn recv_timeout(&mut self, timeout: Duration) -> std::result::Result<T, RecvTimeoutError> {
+        let inner = &mut self.inner;
+        match self
+            .runtime
+            .block_on(async { tokio::time::timeout(timeout, inner.next()).await })
+        {
+            Ok(Some(update)) => Ok(update),
+            Ok(None) => Err(RecvTimeoutError::Disconnected),
+            Err(_) => Err(RecvTimeoutError::Timeout),
+        }
+    }
+
+    /// Get the next update if one is buffered, without blocking
+    pub fn try_recv(&mut self) -> std::result::Result<T, TryRecvError> {
+        self.inner.try_next().map_err(|e| match e {
+            AsyncTryRecvError::Empty => TryRecvError::Empty,
+            AsyncTryRecvError::Disconnected => TryRecvError::Disconnected,
+        })
+    }
+
+    /// Block until Kraken confirmed the subscription, see
+    /// [`Subscription::ready`](crate::Subscription::ready)
+    pub fn ready(&self) -> Result<()> {
+        self.runtime.block_on(self.inner.ready())
+    }
+
+    /// Get the subscription ID
+    pub fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    /// Get subscription statistics
+    pub fn stats(&self) -> &SubscriptionStats {
+        self.inner.stats()
+    }
+
+    /// Get the error that ended the subscription, if any
+    pub fn error(&self) -> Option<KrakyError> {
+        self.inner.error()
+    }
+
+    /// Get the underlying async subscription
+    pub fn into_inner(self) -> crate::Subscription<T> {
+        self.inner
+    }
+}
+
+impl<T> Iterator for Subscription<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.recv()
+    }
+}
+
+#[cfg(all(test, feature = "orderbook", feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::{fixtures, MockScript, MockServer};
+
+    #[test]
+    fn test_blocking_client_iterates_updates() {
+        let server_runtime = Runtime::new().unwrap();
+        let server = server_runtime
+            .block_on(MockServer::start(
+                MockScript::new().message(fixtures::get("book_snapshot").unwrap().text),
+            ))
+            .unwrap();
+
+        let client = KrakyClient::connect_with_url(server.url()).unwrap();
+        let mut book = client.subscribe_orderbook("BTC/USD", 10).unwrap();
+        book.ready().unwrap();
+
+        let update = book.next().unwrap();
+        assert_eq!(update.data[0].symbol, "BTC/USD");
+        assert!(matches!(book.try_recv(), Err(TryRecvError::Empty)));
+        assert!(matches!(
+            book.recv_timeout(Duration::from_millis(20)),
+            Err(RecvTimeoutError::Timeout)
+        ));
+        assert!(client.get_orderbook("BTC/USD").is_some());
+
+        client.shutdown();
+        drop(client);
+        drop(book);
+    }
+}
@@ -0,0 +1,770 @@
+//! Message bus bridge
+//!
+//! [`Bridge`] republishes parsed market data, and optionally the raw
+//! WebSocket frames, to a message bus so kraky can act as the ingestion edge
+//! of a larger pipeline. Each message is published as JSON to a subject (NATS)
+//! or topic (Kafka) per channel and symbol.
+//!
+//! Requires the `bridge` feature flag for the [`BridgePublisher`] trait, plus
+//! a backend:
+//!
+//! - `bridge-nats` - `NatsPublisher` (pure Rust)
+//! - `bridge-kafka` - `KafkaPublisher` (builds librdkafka, needs a C
+//!   toolchain)
+//! - `redis` - `RedisPublisher` to pub/sub channels or Streams
+//!
+//! ## Subjects
+//!
+//! Subjects are built from a pattern with `{channel}` and `{symbol}`
+//! placeholders, `kraky.{channel}.{symbol}` by default, with the symbol made
+//! subject safe: `kraky.trade.BTC-USD`. Raw frames use `raw` as the channel
+//! and no symbol (`kraky.raw`). Kafka messages and Redis stream entries also
+//! carry the original symbol as their key.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # #[cfg(all(feature = "bridge-nats", feature = "trades"))]
+//! # {
+//! use kraky::bridge::{Bridge, NatsPublisher};
+//! use kraky::KrakyClient;
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Arc::new(KrakyClient::connect().await?);
+//! let nats = NatsPublisher::connect("nats://localhost:4222").await?;
+//!
+//! let bridge = Bridge::new(client, nats)
+//!     .with_prefix("md.kraken")
+//!     .trades("BTC/USD")
+//!     .orderbook("BTC/USD", 10)
+//!     .raw_frames()
+//!     .start()
+//!     .await?;
+//!
+//! tokio::signal::ctrl_c().await?;
+//! println!("Published {} messages", bridge.published());
+//! bridge.stop().await;
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+//!
+//! ## Redis
+//!
+//! Fan out to web frontends through Redis Streams with colon-separated keys:
+//!
+//! ```no_run
+//! # #[cfg(all(feature = "redis", feature = "ticker"))]
+//! # {
+//! use kraky::bridge::{Bridge, RedisMode, RedisPublisher};
+//! # use kraky::KrakyClient;
+//! # use std::sync::Arc;
+//!
+//! # async fn example(client: Arc<KrakyClient>) -> Result<(), Box<dyn std::error::Error>> {
+//! let redis = RedisPublisher::connect("redis://localhost:6379")
+//!     .await?
+//!     .with_mode(RedisMode::Stream { max_len: Some(10_000) });
+//!
+//! let bridge = Bridge::new(client, redis)
+//!     .with_pattern("md:{symbol}:{channel}")
+//!     .ticker("BTC/USD")
+//!     .start()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+
+use crate::client::KrakyClient;
+use crate::error::{KrakyError, Result};
+use crate::subscriptions::Subscription;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "orderbook")]
+use crate::models::OrderbookUpdate;
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+#[cfg(feature = "trades")]
+use crate::models::Trade;
+#[cfg(feature = "ohlc")]
+use crate::models::{Interval, OHLC};
+
+/// A message bus the bridge can publish to
+///
+/// Implement this to forward to a bus without a built-in backend.
+#[async_trait]
+pub trait BridgePublisher: Send + Sync {
+    /// Publish a payload
+    ///
+    /// `subject` is the NATS subject or Kafka topic; `key` is the symbol, or
+    /// `None` for raw frames.
+    async fn publish(&self, subject: &str, key: Option<&str>, payload: Vec<u8>) -> Result<()>;
+
+    /// Wait until published messages have been handed to the bus
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A channel to forward
+#[derive(Debug, Clone)]
+enum Channel {
+    #[cfg(feature = "trades")]
+    Trades(String),
+    #[cfg(feature = "ticker")]
+    Ticker(String),
+    #[cfg(feature = "ohlc")]
+    Ohlc(String, Interval),
+    #[cfg(feature = "orderbook")]
+    Orderbook(String, u32),
+}
+
+/// Republishes subscriptions to a message bus
+///
+/// Configure the channels to forward, then [`start`](Self::start) it. See
+/// the [module documentation](self) for subject naming.
+pub struct Bridge {
+    client: Arc<KrakyClient>,
+    publisher: Arc<dyn BridgePublisher>,
+    pattern: String,
+    raw: bool,
+    channels: Vec<Channel>,
+}
+
+impl Bridge {
+    /// Create a bridge publishing through `publisher`
+    pub fn new(client: Arc<KrakyClient>, publisher: impl BridgePublisher + 'static) -> Self {
+        Self {
+            client,
+            publisher: Arc::new(publisher),
+            pattern: "kraky.{channel}.{symbol}".to_string(),
+            raw: false,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Publish to `{prefix}.{channel}.{symbol}` (`kraky` by default)
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.pattern = format!("{}.{{channel}}.{{symbol}}", prefix);
+        self
+    }
+
+    /// Set the subject pattern, e.g. `md:{symbol}:{channel}` for Redis keys
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = pattern.into();
+        self
+    }
+
+    /// Also publish every raw WebSocket text frame (to `kraky.raw` by default)
+    pub fn raw_frames(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+
+    /// Forward trades for a pair
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub fn trades(mut self, pair: &str) -> Self {
+        self.channels.push(Channel::Trades(pair.to_string()));
+        self
+    }
+
+    /// Forward ticker updates for a pair
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub fn ticker(mut self, pair: &str) -> Self {
+        self.channels.push(Channel::Ticker(pair.to_string()));
+        self
+    }
+
+    /// Forward OHLC candles for a pair
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub fn ohlc(mut self, pair: &str, interval: Interval) -> Self {
+        self.channels
+            .push(Channel::Ohlc(pair.to_string(), interval));
+        self
+    }
+
+    /// Forward orderbook snapshots and updates for a pair
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    pub fn orderbook(mut self, pair: &str, depth: u32) -> Self {
+        self.channels
+            .push(Channel::Orderbook(pair.to_string(), depth));
+        self
+    }
+
+    /// Subscribe to the configured channels and start publishing
+    ///
+    /// Publishing continues until [`BridgeHandle::stop`] is called or the
+    /// client disconnects. Publish failures are logged and counted, and do
+    /// not stop the bridge.
+    pub async fn start(self) -> Result<BridgeHandle> {
+        if self.channels.is_empty() && !self.raw {
+            return Err(KrakyError::InvalidMessage(
+                "Bridge has no channels configured".to_string(),
+            ));
+        }
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let shared = Arc::new(Shared::default());
+        let mut tasks = Vec::new();
+        let target = Target {
+            publisher: Arc::clone(&self.publisher),
+            pattern: self.pattern.clone(),
+            shared: Arc::clone(&shared),
+        };
+
+        if self.raw {
+            let frames = self.client.subscribe_frames();
+            tasks.push(tokio::spawn(forward_frames(
+                frames,
+                target.clone(),
+                stop_rx.clone(),
+            )));
+        }
+
+        for channel in &self.channels {
+            let task = match channel {
+                #[cfg(feature = "trades")]
+                Channel::Trades(pair) => {
+                    let subscription = self.client.subscribe_trades(pair).await?;
+                    tokio::spawn(forward(subscription, target.clone(), stop_rx.clone()))
+                }
+                #[cfg(feature = "ticker")]
+                Channel::Ticker(pair) => {
+                    let subscription = self.client.subscribe_ticker(pair).await?;
+                    tokio::spawn(forward(subscription, target.clone(), stop_rx.clone()))
+                }
+                #[cfg(feature = "ohlc")]
+                Channel::Ohlc(pair, interval) => {
+                    let subscription = self.client.subscribe_ohlc(pair, *interval).await?;
+                    tokio::spawn(forward(subscription, target.clone(), stop_rx.clone()))
+                }
+                #[cfg(feature = "orderbook")]
+                Channel::Orderbook(pair, depth) => {
+                    let subscription = self.client.subscribe_orderbook(pair, *depth).await?;
+                    tokio::spawn(forward(subscription, target.clone(), stop_rx.clone()))
+                }
+            };
+            tasks.push(task);
+        }
+
+        Ok(BridgeHandle {
+            stop: stop_tx,
+            tasks,
+            publisher: self.publisher,
+            shared,
+        })
+    }
+}
+
+/// Counters shared by the forwarding tasks
+#[derive(Default)]
+struct Shared {
+    published: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Where a forwarding task publishes
+#[derive(Clone)]
+struct Target {
+    publisher: Arc<dyn BridgePublisher>,
+    pattern: String,
+    shared: Arc<Shared>,
+}
+
+impl Target {
+    async fn publish(&self, subject: &str, key: Option<&str>, payload: Vec<u8>) {
+        match self.publisher.publish(subject, key, payload).await {
+            Ok(()) => {
+                self.shared.published.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.shared.failed.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("Bridge failed to publish to {}: {}", subject, e);
+            }
+        }
+    }
+}
+
+/// Handle to a running [`Bridge`]
+pub struct BridgeHandle {
+    stop: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+    publisher: Arc<dyn BridgePublisher>,
+    shared: Arc<Shared>,
+}
+
+impl BridgeHandle {
+    /// Number of messages published so far
+    pub fn published(&self) -> u64 {
+        self.shared.published.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages that failed to publish
+    pub fn failed(&self) -> u64 {
+        self.shared.failed.load(Ordering::Relaxed)
+    }
+
+    /// Stop forwarding and flush the publisher
+    pub async fn stop(self) {
+        let _ = self.stop.send(true);
+        for task in self.tasks {
+            let _ = task.await;
+        }
+        if let Err(e) = self.publisher.flush().await {
+            tracing::warn!("Bridge failed to flush: {}", e);
+        }
+    }
+}
+
+/// A message type the bridge can forward
+trait Bridged: Serialize + Send + Sync + 'static {
+    /// Channel name used in subjects
+    const CHANNEL: &'static str;
+    /// Symbol used in the subject and as the message key
+    fn symbol(&self) -> &str;
+}
+
+#[cfg(feature = "trades")]
+impl Bridged for Trade {
+    const CHANNEL: &'static str = "trade";
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+#[cfg(feature = "ticker")]
+impl Bridged for Ticker {
+    const CHANNEL: &'static str = "ticker";
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+#[cfg(feature = "ohlc")]
+impl Bridged for OHLC {
+    const CHANNEL: &'static str = "ohlc";
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+#[cfg(feature = "orderbook")]
+impl Bridged for OrderbookUpdate {
+    const CHANNEL: &'static str = "book";
+    fn symbol(&self) -> &str {
+        self.data.first().map_or("", |data| data.symbol.as_str())
+    }
+}
+
+/// Subject for a channel and symbol, e.g. `kraky.trade.BTC-USD`
+///
+/// An empty symbol drops its placeholder along with trailing separators.
+fn subject(pattern: &str, channel: &str, symbol: &str) -> String {
+    let symbol: String = symbol
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let subject = pattern
+        .replace("{channel}", channel)
+        .replace("{symbol}", &symbol);
+    if symbol.is_empty() {
+        subject
+            .trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+            .to_string()
+    } else {
+        subject
+    }
+}
+
+/// Publish a subscription's messages until stopped or closed
+async fn forward<T: Bridged>(
+    mut subscription: Subscription<Arc<T>>,
+    target: Target,
+    mut stop: watch::Receiver<bool>,
+) {
+    loop {
+        tokio::select! {
+            item = subscription.next() => match item {
+                Some(item) => {
+                    let payload = match serde_json::to_vec(item.as_ref()) {
+                        Ok(payload) => payload,
+                        Err(e) => {
+                            tracing::warn!("Bridge failed to serialize {}: {}", T::CHANNEL, e);
+                            continue;
+                        }
+                    };
+                    let subject = subject(&target.pattern, T::CHANNEL, item.symbol());
+                    target.publish(&subject, Some(item.symbol()), payload).await;
+                }
+                None => break,
+            },
+            _ = stop.changed() => break,
+        }
+    }
+}
+
+/// Publish raw frames until stopped or closed
+async fn forward_frames(
+    mut frames: Subscription<String>,
+    target: Target,
+    mut stop: watch::Receiver<bool>,
+) {
+    let subject = subject(&target.pattern, "raw", "");
+    loop {
+        tokio::select! {
+            frame = frames.next() => match frame {
+                Some(frame) => target.publish(&subject, None, frame.into_bytes()).await,
+                None => break,
+            },
+            _ = stop.changed() => break,
+        }
+    }
+}
+
+/// Publishes to NATS subjects
+///
+/// Only available when the `bridge-nats` feature is enabled.
+#[cfg(feature = "bridge-nats")]
+pub struct NatsPublisher {
+    client: async_nats::Client,
+}
+
+#[cfg(feature = "bridge-nats")]
+impl NatsPublisher {
+    /// Connect to a NATS server (e.g. `nats://localhost:4222`)
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|e| KrakyError::Storage(format!("NATS connect failed: {}", e)))?;
+        Ok(Self { client })
+    }
+
+    /// Use an existing NATS client
+    pub fn from_client(client: async_nats::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "bridge-nats")]
+#[async_trait]
+impl BridgePublisher for NatsPublisher {
+    async fn publish(&self, subject: &str, _key: Option<&str>, payload: Vec<u8>) -> Result<()> {
+        self.client
+            .publish(subject.to_string(), payload.into())
+            .await
+            .map_err(|e| KrakyError::Storage(format!("NATS publish failed: {}", e)))
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.client
+            .flush()
+            .await
+            .map_err(|e| KrakyError::Storage(format!("NATS flush failed: {}", e)))
+    }
+}
+
+/// Publishes to Kafka topics
+///
+/// Only available when the `bridge-kafka` feature is enabled.
+#[cfg(feature = "bridge-kafka")]
+pub struct KafkaPublisher {
+    producer: rdkafka::producer::FutureProducer,
+    timeout: std::time::Duration,
+}
+
+#[cfg(feature = "bridge-kafka")]
+impl KafkaPublisher {
+    /// Create a producer for a comma-separated list of brokers
+    pub fn new(brokers: &str) -> Result<Self> {
+        let mut config = rdkafka::ClientConfig::new();
+        config
+            .set("bootstrap.servers", brokers)
+            .set("linger.ms", "5");
+        Self::from_config(&config)
+    }
+
+    /// Create a producer from a full librdkafka configuration
+    pub fn from_config(config: &rdkafka::ClientConfig) -> Result<Self> {
+        let producer = config
+            .create()
+            .map_err(|e| KrakyError::Storage(format!("Kafka producer failed: {}", e)))?;
+        Ok(Self {
+            producer,
+            timeout: std::time::Duration::from_secs(5),
+        })
+    }
+
+    /// Set how long a publish may wait for queue space (5 seconds by default)
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[cfg(feature = "bridge-kafka")]
+#[async_trait]
+impl BridgePublisher for KafkaPublisher {
+    async fn publish(&self, subject: &str, key: Option<&str>, payload: Vec<u8>) -> Result<()> {
+        let mut record = rdkafka::producer::FutureRecord::to(subject).payload(&payload);
+        if let Some(key) = key {
+            record = record.key(key);
+        }
+        self.producer
+            .send(record, self.timeout)
+            .await
+            .map(|_| ())
+            .map_err(|(e, _)| KrakyError::Storage(format!("Kafka publish failed: {}", e)))
+    }
+
+    async fn flush(&self) -> Result<()> {
+        use rdkafka::producer::Producer;
+        self.producer
+            .flush(self.timeout)
+            .map_err(|e| KrakyError::Storage(format!("Kafka flush failed: {}", e)))
+    }
+}
+
+/// How [`RedisPublisher`] delivers messages
+///
+/// Only available when the `redis` feature is enabled.
+#[cfg(feature = "redis")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedisMode {
+    /// `PUBLISH` to a pub/sub channel named by the subject
+    PubSub,
+    /// `XADD` to a stream named by the subject, with `symbol` and `data`
+    /// fields, trimmed to roughly `max_len` entries when set
+    Stream {
+        /// Approximate maximum stream length
+        max_len: Option<usize>,
+    },
+}
+
+#[cfg(feature = "redis")]
+impl RedisMode {
+    /// The command publishing one message
+    fn command(self, subject: &str, key: Option<&str>, payload: Vec<u8>) -> redis::Cmd {
+        match self {
+            RedisMode::PubSub => {
+                let mut cmd = redis::cmd("PUBLISH");
+                cmd.arg(subject).arg(payload);
+                cmd
+            }
+            RedisMode::Stream { max_len } => {
+                let mut cmd = redis::cmd("XADD");
+                cmd.arg(subject);
+                if let Some(max_len) = max_len {
+                    cmd.arg("MAXLEN").arg("~").arg(max_len);
+                }
+                cmd.arg("*");
+                if let Some(key) = key {
+                    cmd.arg("symbol").arg(key);
+                }
+                cmd.arg("data").arg(payload);
+                cmd
+            }
+        }
+    }
+}
+
+/// Publishes to Redis pub/sub channels or Streams
+///
+/// The connection is re-established automatically after failures.
+///
+/// Only available when the `redis` feature is enabled.
+#[cfg(feature = "redis")]
+pub struct RedisPublisher {
+    connection: redis::aio::ConnectionManager,
+    mode: RedisMode,
+}
+
+#[cfg(feature = "redis")]
+impl RedisPublisher {
+    /// Connect to Redis (e.g. `redis://localhost:6379`), publishing to
+    /// pub/sub channels
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(redis_error)?;
+        let connection = redis::aio::ConnectionManager::new(client)
+            .await
+            .map_err(redis_error)?;
+        Ok(Self {
+            connection,
+            mode: RedisMode::PubSub,
+        })
+    }
+
+    /// Set the delivery mode (pub/sub by default)
+    pub fn with_mode(mut self, mode: RedisMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+#[cfg(feature = "redis")]
+fn redis_error(e: redis::RedisError) -> KrakyError {
+    KrakyError::Storage(format!("Redis error: {}", e))
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl BridgePublisher for RedisPublisher {
+    async fn publish(&self, subject: &str, key: Option<&str>, payload: Vec<u8>) -> Result<()> {
+        let mut connection = self.connection.clone();
+        self.mode
+            .command(subject, key, payload)
+            .query_async::<redis::Value>(&mut connection)
+            .await
+            .map(|_| ())
+            .map_err(redis_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    /// Subject, key and payload of a published message
+    type Published = (String, Option<String>, Vec<u8>);
+
+    #[derive(Default)]
+    struct Collect {
+        messages: Mutex<Vec<Published>>,
+    }
+
+    #[async_trait]
+    impl BridgePublisher for Arc<Collect> {
+        async fn publish(&self, subject: &str, key: Option<&str>, payload: Vec<u8>) -> Result<()> {
+            self.messages
+                .lock()
+                .push((subject.to_string(), key.map(str::to_string), payload));
+            Ok(())
+        }
+    }
+
+    fn target(collect: &Arc<Collect>) -> Target {
+        Target {
+            publisher: Arc::new(Arc::clone(collect)),
+            pattern: "md.{channel}.{symbol}".to_string(),
+            shared: Arc::new(Shared::default()),
+        }
+    }
+
+    #[test]
+    fn test_subject() {
+        let pattern = "kraky.{channel}.{symbol}";
+        assert_eq!(subject(pattern, "trade", "BTC/USD"), "kraky.trade.BTC-USD");
+        assert_eq!(subject(pattern, "raw", ""), "kraky.raw");
+        assert_eq!(
+            subject("md:{symbol}:{channel}", "book", "ETH/EUR"),
+            "md:ETH-EUR:book"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forward_frames() {
+        use crate::subscriptions::SubscriptionSender;
+
+        let collect = Arc::new(Collect::default());
+        let target = target(&collect);
+        let shared = Arc::clone(&target.shared);
+        let (sender, frames) = SubscriptionSender::new("frames".to_string(), "*".to_string());
+        let (_stop_tx, stop_rx) = watch::channel(false);
+
+        sender
+            .send(r#"{"channel":"heartbeat"}"#.to_string())
+            .unwrap();
+        drop(sender);
+        forward_frames(frames, target, stop_rx).await;
+
+        let messages = collect.messages.lock();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].0, "md.raw");
+        assert_eq!(messages[0].1, None);
+        assert_eq!(messages[0].2, br#"{"channel":"heartbeat"}"#);
+        assert_eq!(shared.published.load(Ordering::Relaxed), 1);
+    }
+
+    #[cfg(feature = "trades")]
+    #[tokio::test]
+    async fn test_forward_trades() {
+        use crate::models::{TradeOrderType, TradeSide};
+        use crate::subscriptions::SubscriptionSender;
+
+        let collect = Arc::new(Collect::default());
+        let (sender, trades) = SubscriptionSender::new("trade".to_string(), "*".to_string());
+        let (_stop_tx, stop_rx) = watch::channel(false);
+
+        sender
+            .send(Arc::new(Trade {
+                symbol: "BTC/USD".to_string(),
+                side: TradeSide::Sell,
+                price: 42000.0,
+                qty: 0.1,
+                ord_type: TradeOrderType::Market,
+                trade_id: 7,
+                timestamp: "2024-01-15T10:30:00.000000Z".to_string(),
+            }))
+            .unwrap();
+        drop(sender);
+        forward(trades, target(&collect), stop_rx).await;
+
+        let messages = collect.messages.lock();
+        let (subject, key, payload) = &messages[0];
+        assert_eq!(subject, "md.trade.BTC-USD");
+        assert_eq!(key.as_deref(), Some("BTC/USD"));
+        let json: serde_json::Value = serde_json::from_slice(payload).unwrap();
+        assert_eq!(json["trade_id"], 7);
+        assert_eq!(json["side"], "sell");
+    }
+
+    #[cfg(feature = "redis")]
+    #[test]
+    fn test_redis_commands() {
+        let args = |cmd: redis::Cmd| -> Vec<String> {
+            cmd.args_iter()
+                .map(|arg| match arg {
+                    redis::Arg::Simple(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+                    redis::Arg::Cursor => "<cursor>".to_string(),
+                })
+                .collect()
+        };
+
+        let publish = RedisMode::PubSub.command("kraky:trade", Some("BTC/USD"), b"{}".to_vec());
+        assert_eq!(args(publish), ["PUBLISH", "kraky:trade", "{}"]);
+
+        let stream = RedisMode::Stream {
+            max_len: Some(1000),
+        };
+        assert_eq!(
+            args(stream.command("kraky:trade", Some("BTC/USD"), b"{}".to_vec())),
+            [
+                "XADD",
+                "kraky:trade",
+                "MAXLEN",
+                "~",
+                "1000",
+                "*",
+                "symbol",
+                "BTC/USD",
+                "data",
+                "{}"
+            ]
+        );
+        let raw = RedisMode::Stream { max_len: None }.command("kraky:raw", None, b"x".to_vec());
+        assert_eq!(args(raw), ["XADD", "kraky:raw", "*", "data", "x"]);
+    }
+}
@@ -0,0 +1,229 @@
+//! Candlestick chart rendering
+//!
+//! Renders recent OHLC candles to a PNG image, e.g. to attach visual context
+//! to alerts with [`TelegramNotifier::send_chart`](crate::telegram::TelegramNotifier::send_chart).
+//!
+//! Requires the `charts` feature flag. Text is rendered with system fonts
+//! (via fontconfig on Linux).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::charts::{render_candlestick_png, ChartConfig};
+//! use kraky::{Interval, KrakyClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! let mut ohlc = client.subscribe_ohlc("BTC/USD", Interval::Min1).await?;
+//!
+//! let mut candles = Vec::new();
+//! while let Some(candle) = ohlc.next().await {
+//!     candles.push((*candle).clone());
+//!     if candles.len() == 60 {
+//!         let png = render_candlestick_png("BTC/USD", &candles, &ChartConfig::default())?;
+//!         std::fs::write("btc.png", png)?;
+//!         break;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{KrakyError, Result};
+use crate::models::OHLC;
+use plotters::prelude::*;
+use std::io::Cursor;
+
+/// Chart size and appearance
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartConfig {
+    /// Image width in pixels
+    pub width: u32,
+    /// Image height in pixels
+    pub height: u32,
+    /// Chart title (defaults to the symbol)
+    pub title: Option<String>,
+    /// Draw a volume histogram below the candles
+    pub show_volume: bool,
+}
+
+impl Default for ChartConfig {
+    fn default() -> Self {
+        Self {
+            width: 1024,
+            height: 640,
+            title: None,
+            show_volume: true,
+        }
+    }
+}
+
+impl ChartConfig {
+    /// Set the image size
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Set the chart title
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Show or hide the volume histogram
+    pub fn with_volume(mut self, show_volume: bool) -> Self {
+        self.show_volume = show_volume;
+        self
+    }
+}
+
+/// Render candles (oldest first) as a PNG candlestick chart
+///
+/// Returns the encoded PNG bytes.
+pub fn render_candlestick_png(
+    symbol: &str,
+    candles: &[OHLC],
+    config: &ChartConfig,
+) -> Result<Vec<u8>> {
+    if candles.is_empty() {
+        return Err(KrakyError::InvalidMessage(
+            "Cannot render a chart without candles".to_string(),
+        ));
+    }
+
+    let (width, height) = (config.width.max(200), config.height.max(150));
+    let mut pixels = vec![0u8; (width * height * 3) as usize];
+    draw(symbol, candles, config, &mut pixels, (width, height))
+        .map_err(|e| KrakyError::InvalidMessage(format!("Chart error: {}", e)))?;
+
+    let image = image::RgbImage::from_raw(width, height, pixels)
+        .ok_or_else(|| KrakyError::InvalidMessage("Chart error: invalid buffer".to_string()))?;
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageOutputFormat::Png)
+        .map_err(|e| KrakyError::InvalidMessage(format!("Chart error: {}", e)))?;
+    Ok(png.into_inner())
+}
+
+fn draw(
+    symbol: &str,
+    candles: &[OHLC],
+    config: &ChartConfig,
+    pixels: &mut [u8],
+    size: (u32, u32),
+) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let root = BitMapBackend::with_buffer(pixels, size).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let (price_area, volume_area) = if config.show_volume {
+        let (upper, lower) = root.split_vertically(size.1 * 3 / 4);
+        (upper, Some(lower))
+    } else {
+        (root.clone(), None)
+    };
+
+    let low = candles.iter().map(|c| c.low).fold(f64::INFINITY, f64::min);
+    let high = candles
+        .iter()
+        .map(|c| c.high)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let padding = ((high - low) * 0.05).max(high.abs() * 1e-6);
+    let x_range = -1i32..candles.len() as i32;
+    let label = |i: &i32| {
+        candles
+            .get(*i as usize)
+            .and_then(|c| chrono::DateTime::parse_from_rfc3339(&c.interval_begin).ok())
+            .map(|t| t.format("%H:%M").to_string())
+            .unwrap_or_default()
+    };
+
+    let title = config.title.as_deref().unwrap_or(symbol);
+    let mut chart = ChartBuilder::on(&price_area)
+        .caption(title, ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(if config.show_volume { 0 } else { 30 })
+        .y_label_area_size(70)
+        .build_cartesian_2d(x_range.clone(), (low - padding)..(high + padding))?;
+    chart
+        .configure_mesh()
+        .x_label_formatter(&label)
+        .y_label_formatter(&|p| format!("{:.2}", p))
+        .light_line_style(WHITE.mix(0.0))
+        .draw()?;
+    chart.draw_series(candles.iter().enumerate().map(|(i, c)| {
+        CandleStick::new(
+            i as i32,
+            c.open,
+            c.high,
+            c.low,
+            c.close,
+            GREEN.filled(),
+            RED.filled(),
+            (size.0 / candles.len().max(1) as u32 * 2 / 3).clamp(1, 15),
+        )
+    }))?;
+
+    if let Some(volume_area) = volume_area {
+        let max_volume = candles.iter().map(|c| c.volume).fold(0.0, f64::max);
+        let mut chart = ChartBuilder::on(&volume_area)
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(70)
+            .build_cartesian_2d(x_range, 0.0..(max_volume * 1.1).max(1e-9))?;
+        chart
+            .configure_mesh()
+            .x_label_formatter(&label)
+            .y_labels(3)
+            .light_line_style(WHITE.mix(0.0))
+            .draw()?;
+        chart.draw_series(candles.iter().enumerate().map(|(i, c)| {
+            let color = if c.close >= c.open { GREEN } else { RED };
+            Rectangle::new(
+                [(i as i32, 0.0), (i as i32 + 1, c.volume)],
+                color.mix(0.5).filled(),
+            )
+        }))?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(i: usize, open: f64, close: f64) -> OHLC {
+        OHLC {
+            symbol: "BTC/USD".to_string(),
+            open,
+            high: open.max(close) + 1.0,
+            low: open.min(close) - 1.0,
+            close,
+            vwap: (open + close) / 2.0,
+            volume: 10.0 + i as f64,
+            count: 5,
+            interval: 1,
+            timestamp: String::new(),
+            interval_begin: format!("2024-01-01T12:{:02}:00.000000000Z", i),
+            synthetic: false,
+        }
+    }
+
+    #[test]
+    fn test_render_png() {
+        let candles: Vec<OHLC> = (0..20)
+            .map(|i| candle(i, 100.0 + i as f64, 101.0 + (i % 3) as f64 + i as f64))
+            .collect();
+
+        let png = render_candlestick_png("BTC/USD", &candles, &ChartConfig::default()).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+    }
+
+    #[test]
+    fn test_empty_candles() {
+        assert!(render_candlestick_png("BTC/USD", &[], &ChartConfig::default()).is_err());
+    }
+}
@@ -65,6 +65,8 @@
 //!     max_delay: Duration::from_secs(30),
 //!     backoff_multiplier: 2.0,
 //!     max_attempts: Some(10),
+//!     stale_timeout: Some(Duration::from_secs(60)),
+//!     ..Default::default()
 //! };
 //!
 //! let client = KrakyClient::connect_with_config("wss://ws.kraken.com/v2", config).await?;
@@ -74,9 +76,20 @@
 //! ```
 
 use crate::error::{KrakyError, Result};
-use crate::messages::{KrakyMessage, PingRequest, SubscribeRequest, KRAKEN_WS_URL};
-use crate::subscriptions::{Subscription, SubscriptionManager, SubscriptionSender};
+use crate::health::{ConnectionHealth, DataTransition, HealthConfig, HealthMonitor};
+use crate::messages::{
+    KrakyMessage, ParseDiagnostic, PingRequest, SubscribeRequest, UnsubscribeRequest, KRAKEN_WS_URL,
+};
+use crate::rate_limit::{Outbox, RateLimitConfig};
+use crate::router::{ReqIdRouter, ResponseStream, RouteGuard};
+use crate::subscriptions::{
+    AckReceiver, AckRegistry, BackpressureConfig, CallbackHandle, Conflate, Subscription,
+    SubscriptionInfo, SubscriptionManager, SubscriptionSender, Upstream,
+};
+use crate::symbol::Symbol;
 
+#[cfg(feature = "analytics")]
+use crate::analytics::{ImbalanceConfig, ImbalanceTransition};
 #[cfg(feature = "ticker")]
 use crate::models::Ticker;
 #[cfg(feature = "trades")]
@@ -85,27 +98,38 @@ use crate::models::Trade;
 use crate::models::{Interval, OHLC};
 #[cfg(feature = "orderbook")]
 use crate::models::{Orderbook, OrderbookUpdate};
+#[cfg(feature = "analytics")]
+use crate::subscriptions::ImbalanceSubscription;
 
+use futures_util::future::BoxFuture;
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpStream;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
 use tokio_tungstenite::{
-    connect_async_tls_with_config,
+    client_async_tls_with_config,
     tungstenite::{protocol::WebSocketConfig, Message},
     Connector, MaybeTlsStream, WebSocketStream,
 };
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, info_span, warn, Instrument, Span};
+
+/// Instance ID of the next client created in this process
+static NEXT_INSTANCE_ID: AtomicU64 = AtomicU64::new(1);
 
 /// Connection event emitted by the client
 ///
 /// Only available when the `events` feature is enabled.
 #[cfg(feature = "events")]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ConnectionEvent {
     /// Successfully connected
     Connected,
@@ -119,10 +143,64 @@ pub enum ConnectionEvent {
     ReconnectFailed(u32, String),
     /// Max reconnection attempts reached
     ReconnectExhausted,
+    /// Latency or heartbeat staleness exceeded the health thresholds (reason)
+    ///
+    /// Emitted once per degradation; see [`KrakyClient::connection_health`].
+    Degraded(String),
+    /// No message received within the stale timeout (silence duration)
+    ///
+    /// The connection is dropped and reconnected as usual.
+    Stale(Duration),
+    /// No updates for a subscribed symbol within the channel's data gap
+    /// (channel, symbol, silence duration)
+    ///
+    /// Only emitted for channels with a gap in [`HealthConfig::data_gaps`].
+    /// Heartbeats still arriving meanwhile mean the connection is fine and
+    /// either the market is quiet or the subscription stopped delivering.
+    DataStale(String, String, Duration),
+    /// Updates arrived again after [`ConnectionEvent::DataStale`]
+    /// (channel, symbol)
+    DataResumed(String, String),
+    /// An orderbook failed checksum validation after passing it
+    ///
+    /// Emitted once per corruption with the `checksum` feature. Resubscribe
+    /// or call [`KrakyClient::reconnect`] for a fresh snapshot.
+    OrderbookCorrupted {
+        /// Trading pair of the book
+        symbol: String,
+        /// Checksum sent by Kraken
+        expected: u32,
+        /// Checksum of the local book
+        calculated: u32,
+    },
+    /// An orderbook failed checksum validation several times in a row
+    ///
+    /// Emitted when the run reaches
+    /// [`ClientBuilder::checksum_failure_threshold`], pointing at drift that
+    /// a fresh snapshot didn't fix.
+    ChecksumFailures {
+        /// Trading pair of the book
+        symbol: String,
+        /// Consecutive failed validations
+        consecutive: u64,
+    },
+    /// An orderbook's checksums can't be validated yet
+    ///
+    /// Emitted once per book with the `checksum` feature, on the first
+    /// checksummed update that arrives before the pair's price and quantity
+    /// precision is known. Until then [`KrakyClient::is_orderbook_valid`]
+    /// returns `None`; see [`KrakyClient::set_book_precision`].
+    OrderbookUnverified {
+        /// Trading pair of the book
+        symbol: String,
+    },
+    /// Kraken's system status changed, see [`KrakyClient::system_state`]
+    SystemStateChanged(SystemState),
 }
 
 /// Connection state for the WebSocket client
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 #[repr(u8)]
 pub enum ConnectionState {
     /// Not connected
@@ -147,8 +225,187 @@ impl From<u8> for ConnectionState {
     }
 }
 
+/// Trading state of the Kraken exchange, from the `status` channel
+///
+/// Kraken sends it on connect and whenever it changes; see
+/// [`KrakyClient::system_state`] and [`KrakyClient::watch_system_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemState {
+    /// Operating normally
+    Online,
+    /// Offline for maintenance; no orders are accepted or cancelled
+    Maintenance,
+    /// Only order cancellations are accepted
+    CancelOnly,
+    /// Only post-only limit orders and cancellations are accepted
+    PostOnly,
+}
+
+impl SystemState {
+    /// Parse the `system` field of a status message
+    pub fn parse(system: &str) -> Option<Self> {
+        match system {
+            "online" => Some(Self::Online),
+            "maintenance" => Some(Self::Maintenance),
+            "cancel_only" => Some(Self::CancelOnly),
+            "post_only" => Some(Self::PostOnly),
+            _ => None,
+        }
+    }
+
+    /// Get the name Kraken uses for the state
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Online => "online",
+            Self::Maintenance => "maintenance",
+            Self::CancelOnly => "cancel_only",
+            Self::PostOnly => "post_only",
+        }
+    }
+
+    /// Check whether new orders are accepted, post-only ones at least
+    pub fn accepts_orders(&self) -> bool {
+        matches!(self, Self::Online | Self::PostOnly)
+    }
+
+    /// Check whether open orders can be cancelled
+    pub fn accepts_cancels(&self) -> bool {
+        !matches!(self, Self::Maintenance)
+    }
+}
+
+impl std::fmt::Display for SystemState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why a connection dropped, used to pick a backoff policy
+///
+/// Only available when the `reconnect` feature is enabled.
+#[cfg(feature = "reconnect")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisconnectKind {
+    /// The server sent a close frame
+    ServerClose,
+    /// The TCP connection was reset or the pipe broke
+    ConnectionReset,
+    /// Kraken rejected the connection for exceeding rate limits
+    RateLimited,
+    /// The stream ended without a close frame
+    StreamEnded,
+    /// No data arrived within the stale timeout
+    Stale,
+    /// Any disconnect while Kraken reports maintenance
+    ///
+    /// Attempts made during maintenance don't count towards
+    /// [`ReconnectConfig::max_attempts`], so the client reconnects once
+    /// Kraken is back however long the window lasts.
+    Maintenance,
+    /// Any other connection or protocol error
+    Error,
+}
+
+#[cfg(feature = "reconnect")]
+impl DisconnectKind {
+    /// Classify an error message
+    fn from_error(message: &str) -> Self {
+        let message = message.to_ascii_lowercase();
+        if message.contains("429")
+            || message.contains("too many requests")
+            || message.contains("rate limit")
+        {
+            Self::RateLimited
+        } else if message.contains("reset")
+            || message.contains("broken pipe")
+            || message.contains("connection aborted")
+        {
+            Self::ConnectionReset
+        } else {
+            Self::Error
+        }
+    }
+}
+
+/// Exponential backoff parameters
+///
+/// Only available when the `reconnect` feature is enabled.
+#[cfg(feature = "reconnect")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackoffPolicy {
+    /// Delay before the first attempt
+    pub initial_delay: Duration,
+    /// Maximum delay between attempts
+    pub max_delay: Duration,
+    /// Multiplier applied to the delay after each failed attempt
+    pub backoff_multiplier: f64,
+}
+
+#[cfg(feature = "reconnect")]
+impl BackoffPolicy {
+    /// Calculate delay for a given attempt number
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let delay_ms =
+            self.initial_delay.as_millis() as f64 * self.backoff_multiplier.powi(attempt as i32);
+        let delay = Duration::from_millis(delay_ms as u64);
+        delay.min(self.max_delay)
+    }
+}
+
+/// Which stored subscriptions are sent again after a reconnect
+///
+/// Only available when the `reconnect` feature is enabled.
+#[cfg(feature = "reconnect")]
+#[derive(Clone, Default)]
+pub enum RestorePolicy {
+    /// Re-subscribe to every stored subscription
+    #[default]
+    RestoreAll,
+    /// Re-subscribe to nothing, for apps that manage subscriptions themselves
+    RestoreNone,
+    /// Re-subscribe to the subscriptions the filter accepts
+    Custom(Arc<dyn Fn(&StoredSubscription) -> bool + Send + Sync>),
+}
+
+#[cfg(feature = "reconnect")]
+impl RestorePolicy {
+    /// Create a policy restoring only the subscriptions `filter` accepts
+    pub fn custom<F>(filter: F) -> Self
+    where
+        F: Fn(&StoredSubscription) -> bool + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(filter))
+    }
+
+    /// Check whether a subscription should be restored
+    pub fn restores(&self, subscription: &StoredSubscription) -> bool {
+        match self {
+            Self::RestoreAll => true,
+            Self::RestoreNone => false,
+            Self::Custom(filter) => filter(subscription),
+        }
+    }
+}
+
+#[cfg(feature = "reconnect")]
+impl std::fmt::Debug for RestorePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RestoreAll => f.write_str("RestoreAll"),
+            Self::RestoreNone => f.write_str("RestoreNone"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
 /// Configuration for automatic reconnection
 ///
+/// Start from [`ReconnectConfig::default`] or a preset and set only the
+/// fields you need, ending struct literals with `..Default::default()` so
+/// fields added later (such as `jitter`) keep their defaults.
+///
 /// Only available when the `reconnect` feature is enabled.
 #[cfg(feature = "reconnect")]
 #[derive(Debug, Clone)]
@@ -163,6 +420,30 @@ pub struct ReconnectConfig {
     pub backoff_multiplier: f64,
     /// Maximum number of reconnection attempts (None = unlimited)
     pub max_attempts: Option<u32>,
+    /// Reconnect when no message arrives for this long (None = never)
+    ///
+    /// Connections can go silent without a close frame. Kraken sends
+    /// heartbeats on subscribed connections and the client pings every
+    /// 30 seconds by default, so a healthy connection is never quiet for a
+    /// minute.
+    pub stale_timeout: Option<Duration>,
+    /// Fraction of each delay that is randomized, from 0.0 to 1.0
+    ///
+    /// With 0.2 a 10 second delay becomes anything between 8 and 10
+    /// seconds, so clients dropped together (e.g. by Kraken maintenance)
+    /// don't all reconnect at the same instant. Defaults to 0.2; set 0.0 for
+    /// exact delays.
+    pub jitter: f64,
+    /// Backoff overrides per disconnect kind
+    ///
+    /// Kinds without an entry use `initial_delay`, `max_delay` and
+    /// `backoff_multiplier`.
+    pub policies: HashMap<DisconnectKind, BackoffPolicy>,
+    /// Which subscriptions are sent again after reconnecting
+    ///
+    /// Subscriptions that aren't restored are forgotten, so their streams
+    /// stay open but receive no data until the app subscribes again.
+    pub restore: RestorePolicy,
 }
 
 #[cfg(feature = "reconnect")]
@@ -174,6 +455,10 @@ impl Default for ReconnectConfig {
             max_delay: Duration::from_secs(30),
             backoff_multiplier: 2.0,
             max_attempts: None, // Unlimited retries
+            stale_timeout: Some(Duration::from_secs(60)),
+            jitter: 0.2,
+            policies: Self::default_policies(),
+            restore: RestorePolicy::RestoreAll,
         }
     }
 }
@@ -196,6 +481,10 @@ impl ReconnectConfig {
             max_delay: Duration::from_secs(5),
             backoff_multiplier: 1.5,
             max_attempts: None,
+            stale_timeout: Some(Duration::from_secs(60)),
+            jitter: 0.2,
+            policies: Self::default_policies(),
+            restore: RestorePolicy::RestoreAll,
         }
     }
 
@@ -207,1121 +496,5199 @@ impl ReconnectConfig {
             max_delay: Duration::from_secs(60),
             backoff_multiplier: 2.0,
             max_attempts: Some(10),
+            stale_timeout: Some(Duration::from_secs(60)),
+            jitter: 0.5,
+            policies: Self::default_policies(),
+            restore: RestorePolicy::RestoreAll,
         }
     }
 
-    /// Calculate delay for a given attempt number
-    fn delay_for_attempt(&self, attempt: u32) -> Duration {
-        let delay_ms =
-            self.initial_delay.as_millis() as f64 * self.backoff_multiplier.powi(attempt as i32);
-        let delay = Duration::from_millis(delay_ms as u64);
-        delay.min(self.max_delay)
+    /// Default overrides: back off hard when rate limited or during
+    /// maintenance, retry quickly after a plain connection reset
+    pub fn default_policies() -> HashMap<DisconnectKind, BackoffPolicy> {
+        HashMap::from([
+            (
+                DisconnectKind::Maintenance,
+                BackoffPolicy {
+                    initial_delay: Duration::from_secs(5),
+                    max_delay: Duration::from_secs(60),
+                    backoff_multiplier: 1.5,
+                },
+            ),
+            (
+                DisconnectKind::RateLimited,
+                BackoffPolicy {
+                    initial_delay: Duration::from_secs(10),
+                    max_delay: Duration::from_secs(120),
+                    backoff_multiplier: 2.0,
+                },
+            ),
+            (
+                DisconnectKind::ConnectionReset,
+                BackoffPolicy {
+                    initial_delay: Duration::from_millis(100),
+                    max_delay: Duration::from_secs(10),
+                    backoff_multiplier: 2.0,
+                },
+            ),
+        ])
     }
-}
 
-/// Stored subscription info for re-subscription after reconnect
-#[cfg(feature = "reconnect")]
-#[derive(Debug, Clone)]
-enum StoredSubscription {
-    #[cfg(feature = "orderbook")]
-    Orderbook { pair: String, depth: u32 },
-    #[cfg(feature = "trades")]
-    Trades { pair: String },
-    #[cfg(feature = "ticker")]
-    Ticker { pair: String },
-    #[cfg(feature = "ohlc")]
-    OHLC { pair: String, interval: u32 },
+    /// Override the backoff for one disconnect kind
+    pub fn with_policy(mut self, kind: DisconnectKind, policy: BackoffPolicy) -> Self {
+        self.policies.insert(kind, policy);
+        self
+    }
+
+    /// Set which subscriptions are restored after a reconnect
+    pub fn with_restore_policy(mut self, restore: RestorePolicy) -> Self {
+        self.restore = restore;
+        self
+    }
+
+    /// Set the jitter fraction (clamped to 0.0..=1.0)
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Get the backoff policy used after a disconnect of the given kind
+    pub fn policy_for(&self, kind: DisconnectKind) -> BackoffPolicy {
+        self.policies.get(&kind).copied().unwrap_or(BackoffPolicy {
+            initial_delay: self.initial_delay,
+            max_delay: self.max_delay,
+            backoff_multiplier: self.backoff_multiplier,
+        })
+    }
+
+    /// Calculate the jittered delay after a disconnect of the given kind
+    fn delay_for(&self, attempt: u32, kind: DisconnectKind) -> Duration {
+        let delay = self.policy_for(kind).delay_for_attempt(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+        delay.mul_f64(1.0 - jitter * random_fraction())
+    }
 }
 
-/// WebSocket connection type
-type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+/// Uniform random number in `[0, 1)` from the std hasher's random keys
+#[cfg(feature = "reconnect")]
+fn random_fraction() -> f64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
 
-/// Command to send to the WebSocket handler
-#[derive(Debug, Clone)]
-enum Command {
-    Subscribe(SubscribeRequest),
-    Ping,
-    Shutdown,
-    /// Trigger reconnection
-    Reconnect,
-    /// Send a raw JSON message (for trading, etc.)
-    #[cfg(feature = "trading")]
-    RawMessage(String),
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
 }
 
-/// Kraken WebSocket client
+/// Configuration for the parallel message pipeline
 ///
-/// Provides a high-level interface for connecting to Kraken's WebSocket API
-/// and subscribing to market data streams.
+/// By default one task reads the socket, parses every message and applies
+/// orderbook updates. With a pipeline, the socket task only routes raw
+/// frames to a pool of workers that parse and dispatch them. Frames are
+/// sharded by symbol, so updates for one pair are always processed in order
+/// by the same worker.
 ///
 /// # Example
 ///
 /// ```no_run
-/// use kraky::KrakyClient;
+/// use kraky::{KrakyClient, PipelineConfig};
 ///
-/// #[tokio::main]
-/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-///     let client = KrakyClient::connect().await?;
-///     
-///     let mut orderbook_sub = client.subscribe_orderbook("BTC/USD", 10).await?;
-///     
-///     while let Some(update) = orderbook_sub.next().await {
-///         println!("Orderbook update: {:?}", update);
-///     }
-///     
-///     Ok(())
-/// }
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = KrakyClient::connect_with_pipeline(PipelineConfig::with_workers(4)).await?;
+/// # Ok(())
+/// # }
 /// ```
-pub struct KrakyClient {
-    /// Command sender for the WebSocket handler
-    command_tx: tokio::sync::mpsc::UnboundedSender<Command>,
-    /// Subscription manager
-    subscriptions: Arc<RwLock<SubscriptionManager>>,
-    /// Managed orderbooks
-    #[cfg(feature = "orderbook")]
-    orderbooks: Arc<RwLock<HashMap<String, Orderbook>>>,
-    /// Connection state (lock-free atomic)
-    state: Arc<AtomicU8>,
-    /// Reconnection configuration
-    #[cfg(feature = "reconnect")]
-    reconnect_config: Arc<ReconnectConfig>,
-    /// Stored subscriptions for re-subscription after reconnect
-    #[cfg(feature = "reconnect")]
-    stored_subscriptions: Arc<RwLock<Vec<StoredSubscription>>>,
-    /// URL for reconnection
-    url: Arc<String>,
-    /// Shutdown flag
-    shutdown: Arc<AtomicBool>,
-    /// Connection event broadcaster
-    #[cfg(feature = "events")]
-    event_tx: Arc<RwLock<Option<mpsc::Sender<ConnectionEvent>>>>,
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    /// Number of worker tasks parsing messages
+    pub workers: usize,
+    /// Frames buffered per worker before the socket task waits
+    pub queue_size: usize,
 }
 
-impl KrakyClient {
-    /// Connect to Kraken WebSocket API with default reconnection settings
-    ///
-    /// Establishes a WebSocket connection to Kraken's public data API
-    /// and starts the message handling loop with automatic reconnection.
-    pub async fn connect() -> Result<Self> {
-        Self::connect_with_config(KRAKEN_WS_URL, ReconnectConfig::default()).await
-    }
-
-    /// Connect with a custom reconnection configuration
-    pub async fn connect_with_reconnect(config: ReconnectConfig) -> Result<Self> {
-        Self::connect_with_config(KRAKEN_WS_URL, config).await
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            workers: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(2)
+                .min(8),
+            queue_size: 1024,
+        }
     }
+}
 
-    /// Connect to a custom WebSocket URL (for testing)
-    pub async fn connect_with_url(url: &str) -> Result<Self> {
-        Self::connect_with_config(url, ReconnectConfig::default()).await
+impl PipelineConfig {
+    /// Create a pipeline config with a fixed number of workers
+    pub fn with_workers(workers: usize) -> Self {
+        Self {
+            workers: workers.max(1),
+            ..Default::default()
+        }
     }
+}
 
-    /// Connect with full configuration options
-    pub async fn connect_with_config(url: &str, reconnect_config: ReconnectConfig) -> Result<Self> {
-        let state = Arc::new(AtomicU8::new(ConnectionState::Connecting as u8));
-        let shutdown = Arc::new(AtomicBool::new(false));
-        let url = Arc::new(url.to_string());
-        let reconnect_config = Arc::new(reconnect_config);
-        let stored_subscriptions = Arc::new(RwLock::new(Vec::new()));
-        let subscriptions = Arc::new(RwLock::new(SubscriptionManager::new()));
-        #[cfg(feature = "orderbook")]
-        let orderbooks = Arc::new(RwLock::new(HashMap::new()));
-        let (command_tx, command_rx) = tokio::sync::mpsc::unbounded_channel();
-        let event_tx: Arc<RwLock<Option<mpsc::Sender<ConnectionEvent>>>> =
-            Arc::new(RwLock::new(None));
+/// Options for strict parsing mode
+///
+/// By default frames that fail to parse are only logged at warn level and
+/// unrecognized messages at debug level, so schema drift in Kraken's API can
+/// go unnoticed. In strict mode both are also reported on
+/// [`KrakyClient::subscribe_diagnostics`], with the raw text and the error.
+///
+/// # Example
+///
+/// ```no_run
+/// use kraky::{KrakyClient, StrictConfig};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = KrakyClient::builder()
+///     .strict(StrictConfig::failing())
+///     .connect()
+///     .await?;
+/// let mut diagnostics = client.subscribe_diagnostics();
+/// while let Some(diagnostic) = diagnostics.next().await {
+///     eprintln!("{:?}: {} ({})", diagnostic.channel, diagnostic.error, diagnostic.raw);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StrictConfig {
+    /// End the subscriptions a rejected frame was meant for
+    ///
+    /// The affected subscriptions yield `None` after their buffered updates
+    /// and report the parse error from [`Subscription::error`]. The channel
+    /// stays subscribed on the server.
+    pub fail_subscriptions: bool,
+}
 
-        // Initial connection
-        let ws_stream = Self::create_connection(&url).await?;
-        state.store(ConnectionState::Connected as u8, Ordering::SeqCst);
-        info!("WebSocket connection established (TCP_NODELAY enabled)");
+impl StrictConfig {
+    /// Report rejected frames and end the subscriptions they were meant for
+    pub fn failing() -> Self {
+        Self {
+            fail_subscriptions: true,
+        }
+    }
+}
 
-        // Spawn the connection manager task
-        let manager = ConnectionManager {
-            subscriptions: Arc::clone(&subscriptions),
-            #[cfg(feature = "orderbook")]
-            orderbooks: Arc::clone(&orderbooks),
-            state: Arc::clone(&state),
-            reconnect_config: Arc::clone(&reconnect_config),
-            stored_subscriptions: Arc::clone(&stored_subscriptions),
-            url: Arc::clone(&url),
-            shutdown: Arc::clone(&shutdown),
-            event_tx: Arc::clone(&event_tx),
-        };
+/// TLS implementation used for the WebSocket connection
+///
+/// Each variant requires the feature of the same name. `native-tls` is
+/// enabled by default; build with `default-features = false` and the
+/// `rustls` feature to drop the OpenSSL dependency, e.g. for musl targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TlsBackend {
+    /// Platform TLS library (OpenSSL, SChannel or Security.framework)
+    #[cfg(feature = "native-tls")]
+    #[default]
+    NativeTls,
+    /// Pure-Rust rustls with the Mozilla root certificates
+    #[cfg(feature = "rustls")]
+    #[cfg_attr(not(feature = "native-tls"), default)]
+    Rustls,
+}
 
-        tokio::spawn(manager.run(ws_stream, command_rx));
+/// TLS options for the WebSocket connection
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    /// TLS implementation
+    pub backend: TlsBackend,
+    /// Accept invalid certificates and hostnames
+    ///
+    /// Only meant for testing against local servers with self-signed
+    /// certificates. Only supported by the native-tls backend.
+    pub danger_accept_invalid_certs: bool,
+}
 
-        // Spawn heartbeat task
-        let heartbeat_tx = command_tx.clone();
-        let heartbeat_state = Arc::clone(&state);
-        let heartbeat_shutdown = Arc::clone(&shutdown);
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(30));
-            loop {
-                interval.tick().await;
-                if heartbeat_shutdown.load(Ordering::Relaxed) {
-                    break;
-                }
-                let current_state = ConnectionState::from(heartbeat_state.load(Ordering::Relaxed));
-                if current_state == ConnectionState::Connected {
-                    if heartbeat_tx.send(Command::Ping).is_err() {
-                        break;
-                    }
+impl TlsConfig {
+    /// Build the connector for the selected backend
+    fn connector(&self) -> Result<Connector> {
+        match self.backend {
+            #[cfg(feature = "native-tls")]
+            TlsBackend::NativeTls => {
+                let tls = native_tls::TlsConnector::builder()
+                    .danger_accept_invalid_certs(self.danger_accept_invalid_certs)
+                    .danger_accept_invalid_hostnames(self.danger_accept_invalid_certs)
+                    .build()
+                    .map_err(|e| {
+                        KrakyError::from(tokio_tungstenite::tungstenite::Error::Tls(e.into()))
+                    })?;
+                Ok(Connector::NativeTls(tls))
+            }
+            #[cfg(feature = "rustls")]
+            TlsBackend::Rustls => {
+                if self.danger_accept_invalid_certs {
+                    return Err(KrakyError::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "danger_accept_invalid_certs requires the native-tls backend",
+                    )));
                 }
+                let mut roots = rustls::RootCertStore::empty();
+                roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+                let config = rustls::ClientConfig::builder()
+                    .with_root_certificates(roots)
+                    .with_no_client_auth();
+                Ok(Connector::Rustls(Arc::new(config)))
             }
-        });
-
-        Ok(Self {
-            command_tx,
-            subscriptions,
-            #[cfg(feature = "orderbook")]
-            orderbooks,
-            state,
-            reconnect_config,
-            stored_subscriptions,
-            url,
-            shutdown,
-            event_tx,
-        })
+        }
     }
+}
 
-    /// Create a new WebSocket connection (used for initial connect and reconnect)
-    async fn create_connection(url: &str) -> Result<WsStream> {
-        info!("Connecting to Kraken WebSocket: {}", url);
-
-        // Configure WebSocket for low latency
-        let ws_config = WebSocketConfig {
-            write_buffer_size: 0,
-            max_message_size: Some(16 * 1024 * 1024),
-            max_frame_size: Some(16 * 1024 * 1024),
-            accept_unmasked_frames: false,
-            ..Default::default()
-        };
-
-        let connector = Connector::NativeTls(native_tls::TlsConnector::new().map_err(|e| {
-            KrakyError::Connection(tokio_tungstenite::tungstenite::Error::Tls(e.into()))
-        })?);
-
-        let (ws_stream, _) =
-            connect_async_tls_with_config(url, Some(ws_config), false, Some(connector)).await?;
+/// Which IP address family to use when resolving the endpoint
+///
+/// Only applies to direct connections; with a proxy, the proxy resolves
+/// the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpPreference {
+    /// Use addresses in the order the resolver returns them
+    #[default]
+    Any,
+    /// Try IPv4 addresses first, then IPv6
+    PreferIpv4,
+    /// Try IPv6 addresses first, then IPv4
+    PreferIpv6,
+    /// Only connect over IPv4
+    Ipv4Only,
+    /// Only connect over IPv6
+    Ipv6Only,
+}
 
-        Ok(ws_stream)
+impl IpPreference {
+    /// Order and filter resolved addresses
+    fn apply(self, addrs: &mut Vec<SocketAddr>) {
+        match self {
+            Self::Any => {}
+            Self::PreferIpv4 => addrs.sort_by_key(|addr| !addr.is_ipv4()),
+            Self::PreferIpv6 => addrs.sort_by_key(|addr| !addr.is_ipv6()),
+            Self::Ipv4Only => addrs.retain(|addr| addr.is_ipv4()),
+            Self::Ipv6Only => addrs.retain(|addr| addr.is_ipv6()),
+        }
     }
+}
 
-    /// Get the current connection state
-    pub fn connection_state(&self) -> ConnectionState {
-        ConnectionState::from(self.state.load(Ordering::Relaxed))
-    }
+/// Async hook run after a disconnect, with the reason
+type DisconnectedHook = Arc<dyn Fn(String) -> BoxFuture<'static, ()> + Send + Sync>;
 
-    /// Check if the client is connected (lock-free)
-    pub fn is_connected(&self) -> bool {
-        self.connection_state() == ConnectionState::Connected
-    }
+/// Async hook run after a successful reconnection
+type ReconnectedHook = Arc<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
 
-    /// Check if reconnection is in progress
-    pub fn is_reconnecting(&self) -> bool {
-        self.connection_state() == ConnectionState::Reconnecting
+/// Async hooks run inside the reconnect flow
+#[derive(Clone, Default)]
+struct ReconnectHooks {
+    on_disconnected: Vec<DisconnectedHook>,
+    on_reconnected: Vec<ReconnectedHook>,
+}
+
+impl std::fmt::Debug for ReconnectHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectHooks")
+            .field("on_disconnected", &self.on_disconnected.len())
+            .field("on_reconnected", &self.on_reconnected.len())
+            .finish()
     }
+}
 
-    /// Get the reconnection configuration
-    pub fn reconnect_config(&self) -> &ReconnectConfig {
-        &self.reconnect_config
+/// Builder for a [`KrakyClient`] with custom connection options
+///
+/// Created with [`KrakyClient::builder`]. Every option has the same default
+/// as [`KrakyClient::connect`].
+///
+/// # Example
+///
+/// ```no_run
+/// use kraky::{BackpressureConfig, KrakyClient, ReconnectConfig};
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = KrakyClient::builder()
+///     .reconnect(ReconnectConfig::aggressive())
+///     .backpressure(BackpressureConfig::with_buffer_size(5000))
+///     .heartbeat_interval(Duration::from_secs(10))
+///     .connect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    urls: Vec<String>,
+    ip_preference: IpPreference,
+    reconnect: ReconnectConfig,
+    pipeline: Option<PipelineConfig>,
+    backpressure: BackpressureConfig,
+    heartbeat_interval: Duration,
+    health: HealthConfig,
+    tls: TlsConfig,
+    max_message_size: usize,
+    event_buffer_size: usize,
+    connections: usize,
+    rate_limit: Option<RateLimitConfig>,
+    hooks: ReconnectHooks,
+    strict: Option<StrictConfig>,
+    #[cfg(feature = "checksum")]
+    checksum_failure_threshold: Option<u64>,
+    #[cfg(feature = "checksum")]
+    fetch_book_precision: bool,
+    #[cfg(all(feature = "private", feature = "rest"))]
+    token_manager: Option<Arc<crate::token::TokenManager>>,
+    #[cfg(feature = "proxy")]
+    proxy: Option<crate::proxy::ProxyConfig>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self {
+            urls: vec![KRAKEN_WS_URL.to_string()],
+            ip_preference: IpPreference::default(),
+            reconnect: ReconnectConfig::default(),
+            pipeline: None,
+            backpressure: BackpressureConfig::default(),
+            heartbeat_interval: Duration::from_secs(30),
+            health: HealthConfig::default(),
+            tls: TlsConfig::default(),
+            max_message_size: 16 * 1024 * 1024,
+            event_buffer_size: 100,
+            connections: 1,
+            rate_limit: Some(RateLimitConfig::default()),
+            hooks: ReconnectHooks::default(),
+            strict: None,
+            #[cfg(feature = "checksum")]
+            checksum_failure_threshold: None,
+            #[cfg(feature = "checksum")]
+            fetch_book_precision: false,
+            #[cfg(all(feature = "private", feature = "rest"))]
+            token_manager: None,
+            #[cfg(feature = "proxy")]
+            proxy: None,
+        }
     }
+}
 
-    /// Get the WebSocket URL this client is connected to
-    pub fn url(&self) -> &str {
-        &self.url
+impl ClientBuilder {
+    /// Set the WebSocket URL
+    pub fn url(mut self, url: &str) -> Self {
+        self.urls = vec![url.to_string()];
+        self
     }
 
-    /// Subscribe to connection events
+    /// Set several WebSocket URLs to fail over between
     ///
-    /// Returns a receiver that will receive connection state changes.
-    /// Only one subscriber is supported at a time; calling this again
-    /// replaces the previous subscriber.
+    /// The first URL is tried first. After a failed connection attempt the
+    /// client moves on to the next one, wrapping around at the end, so a
+    /// regional outage of one endpoint does not stall reconnection. An
+    /// empty list is ignored.
+    pub fn urls(mut self, urls: &[&str]) -> Self {
+        if !urls.is_empty() {
+            self.urls = urls.iter().map(|url| url.to_string()).collect();
+        }
+        self
+    }
+
+    /// Set which IP address family to connect over
+    pub fn ip_preference(mut self, preference: IpPreference) -> Self {
+        self.ip_preference = preference;
+        self
+    }
+
+    /// Set the reconnection behaviour
+    pub fn reconnect(mut self, config: ReconnectConfig) -> Self {
+        self.reconnect = config;
+        self
+    }
+
+    /// Enable the parallel message pipeline
+    pub fn pipeline(mut self, config: PipelineConfig) -> Self {
+        self.pipeline = Some(config);
+        self
+    }
+
+    /// Set the default backpressure config for new subscriptions
+    pub fn backpressure(mut self, config: BackpressureConfig) -> Self {
+        self.backpressure = config;
+        self
+    }
+
+    /// Set how often the client pings the server
+    pub fn heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    /// Set the connection health thresholds
+    pub fn health(mut self, config: HealthConfig) -> Self {
+        self.health = config;
+        self
+    }
+
+    /// Set the TLS options
+    pub fn tls(mut self, config: TlsConfig) -> Self {
+        self.tls = config;
+        self
+    }
+
+    /// Set the largest WebSocket message accepted, in bytes
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = bytes;
+        self
+    }
+
+    /// Set how many connection events are buffered for [`KrakyClient::subscribe_events`]
+    pub fn event_buffer_size(mut self, size: usize) -> Self {
+        self.event_buffer_size = size;
+        self
+    }
+
+    /// Spread subscriptions over several WebSocket connections
     ///
-    /// Only available when the `events` feature is enabled.
+    /// Kraken limits the number of subscriptions per connection. In pool
+    /// mode each pair is assigned to one of `count` sockets by hashing its
+    /// symbol, and every socket runs its own reconnect loop. Subscriptions
+    /// and the managed orderbooks are shared, so the client API is the same
+    /// as with a single connection. Trading requests use the first socket.
+    pub fn connections(mut self, count: usize) -> Self {
+        self.connections = count.max(1);
+        self
+    }
+
+    /// Set the rate limit for outgoing subscribe and order messages
+    ///
+    /// Messages over the limit wait in a queue instead of risking an
+    /// `EAPI:Rate limit exceeded` disconnect. Each connection of a pool has
+    /// its own limit. Enabled with [`RateLimitConfig::default`] unless
+    /// changed.
+    pub fn rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limit = Some(config);
+        self
+    }
+
+    /// Send outgoing messages without rate limiting
+    pub fn without_rate_limit(mut self) -> Self {
+        self.rate_limit = None;
+        self
+    }
+
+    /// Run an async hook when the connection drops unexpectedly
+    ///
+    /// The hook receives the disconnect reason and runs inside the
+    /// reconnect flow, after the [`ConnectionEvent::Disconnected`] event and
+    /// before the first reconnection attempt. It doesn't run for
+    /// [`KrakyClient::disconnect`] or a manual reconnect. In pool mode it
+    /// runs for each connection that drops.
     ///
     /// # Example
     ///
-    /// ```ignore
-    /// let mut events = client.subscribe_events();
+    /// ```no_run
+    /// use kraky::KrakyClient;
     ///
-    /// tokio::spawn(async move {
-    ///     while let Some(event) = events.recv().await {
-    ///         match event {
-    ///             ConnectionEvent::Connected => println!("Connected!"),
-    ///             ConnectionEvent::Disconnected(reason) => println!("Lost: {:?}", reason),
-    ///             ConnectionEvent::Reconnecting(n) => println!("Reconnecting #{}", n),
-    ///             ConnectionEvent::Reconnected => println!("Reconnected!"),
-    ///             ConnectionEvent::ReconnectFailed(n, e) => println!("Failed #{}: {}", n, e),
-    ///             ConnectionEvent::ReconnectExhausted => println!("Gave up reconnecting"),
-    ///         }
-    ///     }
-    /// });
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::builder()
+    ///     .on_disconnected(|reason| async move {
+    ///         eprintln!("Lost Kraken connection: {}", reason);
+    ///     })
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
     /// ```
-    #[cfg(feature = "events")]
-    pub fn subscribe_events(&self) -> mpsc::Receiver<ConnectionEvent> {
-        let (tx, rx) = mpsc::channel(100);
-        *self.event_tx.write() = Some(tx);
-        rx
+    pub fn on_disconnected<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.hooks
+            .on_disconnected
+            .push(Arc::new(move |reason| Box::pin(hook(reason))));
+        self
     }
 
-    /// Subscribe to orderbook updates for a trading pair
+    /// Run an async hook after every successful reconnection
     ///
-    /// # Arguments
+    /// The hook runs before the stored subscriptions are sent again, so it
+    /// can refresh auth tokens or clear caches first. Commands sent while
+    /// it runs are queued until it returns. In pool mode it runs for each
+    /// connection that reconnects.
     ///
-    /// * `pair` - Trading pair symbol (e.g., "BTC/USD")
-    /// * `depth` - Number of price levels (10, 25, 100, 500, or 1000)
+    /// # Example
     ///
-    /// # Returns
+    /// ```no_run
+    /// use kraky::KrakyClient;
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicU32, Ordering};
     ///
-    /// A subscription stream that yields orderbook updates
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let reconnects = Arc::new(AtomicU32::new(0));
+    /// let counter = Arc::clone(&reconnects);
+    /// let client = KrakyClient::builder()
+    ///     .on_reconnected(move || {
+    ///         let counter = Arc::clone(&counter);
+    ///         async move {
+    ///             counter.fetch_add(1, Ordering::Relaxed);
+    ///         }
+    ///     })
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_reconnected<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.hooks
+            .on_reconnected
+            .push(Arc::new(move || Box::pin(hook())));
+        self
+    }
+
+    /// Report frames that can't be parsed instead of only logging them
     ///
-    /// Only available when the `orderbook` feature is enabled.
-    #[cfg(feature = "orderbook")]
-    pub async fn subscribe_orderbook(
-        &self,
-        pair: &str,
-        depth: u32,
-    ) -> Result<Subscription<OrderbookUpdate>> {
-        let (sender, subscription) = SubscriptionSender::new("book".to_string(), pair.to_string());
+    /// See [`StrictConfig`].
+    pub fn strict(mut self, config: StrictConfig) -> Self {
+        self.strict = Some(config);
+        self
+    }
 
-        // Initialize orderbook state
-        {
-            let mut orderbooks = self.orderbooks.write();
-            orderbooks.insert(pair.to_string(), Orderbook::new(pair.to_string()));
-        }
+    /// Warn and emit [`ConnectionEvent::ChecksumFailures`] once a book
+    /// fails this many checksum validations in a row
+    ///
+    /// Counts are available through [`KrakyClient::checksum_stats`] either
+    /// way.
+    ///
+    /// Only available when the `checksum` feature is enabled.
+    #[cfg(feature = "checksum")]
+    pub fn checksum_failure_threshold(mut self, failures: u64) -> Self {
+        self.checksum_failure_threshold = Some(failures);
+        self
+    }
 
-        // Add subscription
-        {
-            let mut subs = self.subscriptions.write();
-            subs.orderbook.push(sender);
-        }
+    /// Learn book checksum precisions from Kraken's `instrument` channel
+    ///
+    /// Off by default. When enabled, the first book subscription also
+    /// subscribes the connection to the `instrument` channel, whose snapshot
+    /// lists every pair on the exchange, and reconnects repeat it while a
+    /// book still lacks its precision. Otherwise set precisions with
+    /// [`KrakyClient::set_book_precision`], e.g. from
+    /// `RestClient::book_precision`.
+    ///
+    /// Only available when the `checksum` feature is enabled.
+    #[cfg(feature = "checksum")]
+    pub fn fetch_book_precision(mut self, enabled: bool) -> Self {
+        self.fetch_book_precision = enabled;
+        self
+    }
 
-        // Store for reconnection
-        {
-            let mut stored = self.stored_subscriptions.write();
-            stored.push(StoredSubscription::Orderbook {
-                pair: pair.to_string(),
-                depth,
-            });
-        }
+    /// Keep a WebSocket token for private requests
+    ///
+    /// The token is refreshed after every reconnection, before stored
+    /// subscriptions are sent again, and trading requests made with
+    /// credentials for the same API key use it instead of a locally signed
+    /// token.
+    ///
+    /// Only available when the `private` and `rest` features are enabled.
+    #[cfg(all(feature = "private", feature = "rest"))]
+    pub fn token_manager(mut self, manager: Arc<crate::token::TokenManager>) -> Self {
+        let refreshed = Arc::clone(&manager);
+        self.token_manager = Some(manager);
+        self.on_reconnected(move || {
+            let manager = Arc::clone(&refreshed);
+            async move {
+                if let Err(e) = manager.refresh().await {
+                    warn!("WebSocket token refresh after reconnect failed: {}", e);
+                }
+            }
+        })
+    }
 
-        // Send subscribe request
-        let request = SubscribeRequest::orderbook(vec![pair.to_string()], depth);
-        self.command_tx
-            .send(Command::Subscribe(request))
-            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+    /// Connect through an HTTP or SOCKS5 proxy
+    ///
+    /// Only available when the `proxy` feature is enabled.
+    #[cfg(feature = "proxy")]
+    pub fn proxy(mut self, config: crate::proxy::ProxyConfig) -> Self {
+        self.proxy = Some(config);
+        self
+    }
 
-        Ok(subscription)
+    /// Connect with the configured options
+    pub async fn connect(self) -> Result<KrakyClient> {
+        crate::runtime::run(KrakyClient::connect_with_builder(self)).await?
     }
 
-    /// Subscribe to trade updates for a trading pair
+    /// Connect with the configured options, returning a blocking client
     ///
-    /// Only available when the `trades` feature is enabled.
-    #[cfg(feature = "trades")]
-    pub async fn subscribe_trades(&self, pair: &str) -> Result<Subscription<Trade>> {
-        let (sender, subscription) = SubscriptionSender::new("trade".to_string(), pair.to_string());
+    /// Only available when the `blocking` feature is enabled. See
+    /// [`blocking::KrakyClient`](crate::blocking::KrakyClient).
+    #[cfg(feature = "blocking")]
+    pub fn connect_blocking(self) -> Result<crate::blocking::KrakyClient> {
+        crate::blocking::KrakyClient::with_builder(self)
+    }
+}
 
-        {
-            let mut subs = self.subscriptions.write();
-            subs.trades.push(sender);
-        }
+/// Options needed to (re)establish the WebSocket connection
+#[derive(Debug)]
+struct ConnectOptions {
+    /// Endpoints to fail over between (never empty)
+    urls: Vec<String>,
+    /// Index of the endpoint used for the next attempt
+    current: AtomicUsize,
+    ip_preference: IpPreference,
+    tls: TlsConfig,
+    max_message_size: usize,
+    #[cfg(feature = "proxy")]
+    proxy: Option<crate::proxy::ProxyConfig>,
+}
 
-        // Store for reconnection
-        {
-            let mut stored = self.stored_subscriptions.write();
-            stored.push(StoredSubscription::Trades {
-                pair: pair.to_string(),
-            });
+impl ConnectOptions {
+    /// Get the endpoint used for the next attempt
+    fn url(&self) -> &str {
+        &self.urls[self.current.load(Ordering::Relaxed) % self.urls.len()]
+    }
+
+    /// Move on to the next endpoint
+    fn rotate(&self) {
+        if self.urls.len() > 1 {
+            self.current.fetch_add(1, Ordering::Relaxed);
+            info!("Failing over to {}", self.url());
         }
+    }
+}
 
-        let request = SubscribeRequest::trades(vec![pair.to_string()]);
-        self.command_tx
-            .send(Command::Subscribe(request))
-            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+/// Managed orderbooks with one lock per pair
+///
+/// The map itself is only written when a pair is first subscribed; updates
+/// lock just their own book, so applying BTC/USD never blocks readers of
+/// ETH/USD.
+#[cfg(feature = "orderbook")]
+#[derive(Default)]
+struct OrderbookMap {
+    books: RwLock<HashMap<String, Arc<RwLock<Orderbook>>>>,
+    /// Checksum precision per pair, applied to books created later
+    #[cfg(feature = "checksum")]
+    precisions: RwLock<HashMap<String, crate::models::BookPrecision>>,
+    /// Whether to subscribe the instrument channel to learn precisions
+    #[cfg(feature = "checksum")]
+    fetch_precision: bool,
+    /// Whether the instrument channel was subscribed to learn precisions
+    #[cfg(feature = "checksum")]
+    instruments_requested: AtomicBool,
+    /// Checksum validation counts per pair
+    #[cfg(feature = "checksum")]
+    checksum_stats: parking_lot::Mutex<HashMap<String, crate::models::ChecksumStats>>,
+}
 
-        Ok(subscription)
+#[cfg(feature = "orderbook")]
+impl OrderbookMap {
+    /// The book for a pair, if maintained
+    fn get(&self, pair: &str) -> Option<Arc<RwLock<Orderbook>>> {
+        self.books.read().get(pair).cloned()
     }
 
-    /// Subscribe to ticker updates for a trading pair
-    ///
-    /// Only available when the `ticker` feature is enabled.
-    #[cfg(feature = "ticker")]
-    pub async fn subscribe_ticker(&self, pair: &str) -> Result<Subscription<Ticker>> {
-        let (sender, subscription) =
-            SubscriptionSender::new("ticker".to_string(), pair.to_string());
-
-        {
-            let mut subs = self.subscriptions.write();
-            subs.ticker.push(sender);
-        }
+    /// A copy of the book for a pair
+    fn snapshot(&self, pair: &str) -> Option<Orderbook> {
+        self.get(pair).map(|book| book.read().clone())
+    }
 
-        // Store for reconnection
-        {
-            let mut stored = self.stored_subscriptions.write();
-            stored.push(StoredSubscription::Ticker {
-                pair: pair.to_string(),
-            });
+    /// Start (or restart) maintaining an empty book for a pair
+    fn reset(&self, pair: &str) {
+        match self.get(pair) {
+            Some(book) => book.write().clear(),
+            None => {
+                self.insert_if_absent(pair);
+            }
         }
-
-        let request = SubscribeRequest::ticker(vec![pair.to_string()]);
-        self.command_tx
-            .send(Command::Subscribe(request))
-            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
-
-        Ok(subscription)
     }
 
-    /// Subscribe to OHLC (candlestick) updates for a trading pair
-    ///
-    /// Only available when the `ohlc` feature is enabled.
-    #[cfg(feature = "ohlc")]
-    pub async fn subscribe_ohlc(
-        &self,
-        pair: &str,
-        interval: Interval,
-    ) -> Result<Subscription<OHLC>> {
-        let (sender, subscription) = SubscriptionSender::new("ohlc".to_string(), pair.to_string());
+    /// Stop maintaining the book for a pair
+    fn remove(&self, pair: &str) {
+        self.books.write().remove(pair);
+    }
 
-        {
-            let mut subs = self.subscriptions.write();
-            subs.ohlc.push(sender);
+    /// Start maintaining a book unless one exists; returns whether it was added
+    fn insert_if_absent(&self, pair: &str) -> bool {
+        let mut books = self.books.write();
+        if books.contains_key(pair) {
+            return false;
         }
+        let book = Orderbook::new(pair.to_string());
+        #[cfg(feature = "checksum")]
+        let book = Orderbook {
+            precision: self.precisions.read().get(pair).copied(),
+            ..book
+        };
+        books.insert(pair.to_string(), Arc::new(RwLock::new(book)));
+        true
+    }
 
-        // Store for reconnection
-        {
-            let mut stored = self.stored_subscriptions.write();
-            stored.push(StoredSubscription::OHLC {
-                pair: pair.to_string(),
-                interval: interval.minutes(),
-            });
-        }
+    /// Count a checksum validation of a pair's book, returning the totals
+    #[cfg(feature = "checksum")]
+    fn record_checksum(&self, pair: &str, valid: bool) -> crate::models::ChecksumStats {
+        let mut stats = self.checksum_stats.lock();
+        let entry = match stats.get_mut(pair) {
+            Some(entry) => entry,
+            None => stats.entry(pair.to_string()).or_default(),
+        };
+        entry.record(valid);
+        *entry
+    }
 
-        let request = SubscribeRequest::ohlc(vec![pair.to_string()], interval.minutes());
-        self.command_tx
-            .send(Command::Subscribe(request))
-            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+    /// Count a checksummed update of a pair's book that couldn't be validated
+    #[cfg(feature = "checksum")]
+    fn record_unverified(&self, pair: &str) -> crate::models::ChecksumStats {
+        let mut stats = self.checksum_stats.lock();
+        let entry = stats.entry(pair.to_string()).or_default();
+        entry.unverified += 1;
+        *entry
+    }
 
-        Ok(subscription)
+    /// Set the checksum precision of a pair's current and future book
+    #[cfg(feature = "checksum")]
+    fn set_precision(&self, pair: &str, precision: crate::models::BookPrecision) {
+        self.precisions.write().insert(pair.to_string(), precision);
+        if let Some(book) = self.get(pair) {
+            book.write().precision = Some(precision);
+        }
     }
 
-    /// Get the current orderbook for a trading pair
-    pub fn get_orderbook(&self, pair: &str) -> Option<Orderbook> {
-        self.orderbooks.read().get(pair).cloned()
+    /// Whether a pair's checksum precision is still unknown
+    #[cfg(feature = "checksum")]
+    fn lacks_precision(&self, pair: &str) -> bool {
+        !self.precisions.read().contains_key(pair)
     }
 
-    /// Check if the orderbook for a pair has a valid checksum
-    ///
-    /// Returns `None` if no orderbook exists for the pair.
-    /// Returns `Some(true)` if the last checksum validation passed.
-    /// Returns `Some(false)` if the orderbook might be corrupted.
-    ///
-    /// Only available when the `checksum` feature is enabled.
-    ///
-    /// # Example
+    /// Whether subscribing `pair` should subscribe the instrument channel
     ///
-    /// ```ignore
-    /// if client.is_orderbook_valid("BTC/USD") == Some(false) {
-    ///     // Orderbook is corrupted, trigger reconnect
-    ///     client.reconnect()?;
-    /// }
-    /// ```
+    /// True once, for the first pair without a known precision, when
+    /// fetching precisions is enabled.
     #[cfg(feature = "checksum")]
-    pub fn is_orderbook_valid(&self, pair: &str) -> Option<bool> {
-        self.orderbooks.read().get(pair).map(|ob| ob.checksum_valid)
+    fn request_instruments(&self, pair: &str) -> bool {
+        self.fetch_precision
+            && self.lacks_precision(pair)
+            && !self.instruments_requested.swap(true, Ordering::SeqCst)
     }
 
-    /// Validate all orderbooks and reconnect if any are corrupted
-    ///
-    /// Returns the number of corrupted orderbooks found.
-    /// If any are corrupted, a reconnection is triggered automatically.
+    /// Take the precision of every pair in an instrument channel message
     ///
-    /// Only available when the `checksum` feature is enabled.
+    /// Returns whether the message listed pairs.
     #[cfg(feature = "checksum")]
-    pub fn validate_orderbooks_and_reconnect(&self) -> Result<usize> {
-        let corrupted: Vec<String> = self
-            .orderbooks
+    fn apply_instruments(&self, message: &serde_json::Value) -> bool {
+        let Some(pairs) = message["data"]["pairs"].as_array() else {
+            return false;
+        };
+        for pair in pairs {
+            let (Some(symbol), Some(price), Some(qty)) = (
+                pair["symbol"].as_str(),
+                pair["price_precision"].as_u64(),
+                pair["qty_precision"].as_u64(),
+            ) else {
+                continue;
+            };
+            self.set_precision(
+                symbol,
+                crate::models::BookPrecision::new(price as u32, qty as u32),
+            );
+        }
+        true
+    }
+
+    /// Pairs whose last checksum validation failed
+    #[cfg(feature = "checksum")]
+    fn corrupted(&self) -> Vec<String> {
+        self.books
             .read()
             .iter()
-            .filter(|(_, ob)| !ob.checksum_valid)
+            .filter(|(_, book)| !book.read().checksum_valid)
             .map(|(pair, _)| pair.clone())
-            .collect();
+            .collect()
+    }
+}
 
-        let count = corrupted.len();
+/// Latest ticker per pair, behind [`KrakyClient::watch_ticker`]
+///
+/// Fed by every ticker update, whichever subscription asked for it.
+#[cfg(feature = "ticker")]
+#[derive(Default)]
+struct TickerMap {
+    tickers: RwLock<HashMap<String, watch::Sender<Ticker>>>,
+    /// Woken when a pair receives its first ticker
+    added: tokio::sync::Notify,
+}
 
-        if count > 0 {
-            warn!(
-                "Found {} corrupted orderbook(s): {:?}. Triggering reconnect.",
-                count, corrupted
-            );
-            self.reconnect()?;
+#[cfg(feature = "ticker")]
+impl TickerMap {
+    /// Store the latest ticker for its pair
+    fn update(&self, ticker: Ticker) {
+        if let Some(tx) = self.tickers.read().get(&ticker.symbol) {
+            tx.send_replace(ticker);
+            return;
         }
+        self.tickers
+            .write()
+            .entry(ticker.symbol.clone())
+            .and_modify(|tx| {
+                tx.send_replace(ticker.clone());
+            })
+            .or_insert_with(|| watch::channel(ticker).0);
+        self.added.notify_waiters();
+    }
 
-        Ok(count)
+    /// A receiver for a pair that already has a ticker
+    fn watch(&self, pair: &str) -> Option<watch::Receiver<Ticker>> {
+        self.tickers.read().get(pair).map(watch::Sender::subscribe)
     }
 
-    // ============================================================================
-    // Trading Methods (requires 'trading' feature)
-    // ============================================================================
+    /// The last trade price for a pair
+    fn last_price(&self, pair: &str) -> Option<f64> {
+        self.tickers.read().get(pair).map(|tx| tx.borrow().last)
+    }
 
-    /// Place an order
+    /// Wait for the first ticker of a pair
+    async fn first(&self, pair: &str) -> watch::Receiver<Ticker> {
+        loop {
+            let added = self.added.notified();
+            tokio::pin!(added);
+            added.as_mut().enable();
+            if let Some(rx) = self.watch(pair) {
+                return rx;
+            }
+            added.await;
+        }
+    }
+}
+
+/// Worker pool that parses frames off the socket task
+struct Pipeline {
+    workers: Vec<mpsc::Sender<String>>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Pipeline {
+    /// Spawn the workers
+    fn spawn(config: &PipelineConfig, handler: &MessageHandler) -> Self {
+        let (workers, tasks) = (0..config.workers.max(1))
+            .map(|_| {
+                let (tx, mut rx) = mpsc::channel::<String>(config.queue_size.max(1));
+                let handler = handler.clone();
+                let task = crate::runtime::spawn(
+                    async move {
+                        while let Some(text) = rx.recv().await {
+                            handler.handle_message(&text);
+                        }
+                    }
+                    .in_current_span(),
+                );
+                (tx, task)
+            })
+            .unzip();
+        Self { workers, tasks }
+    }
+
+    /// Let the workers finish their queues, then wait for them
+    async fn close(self) {
+        drop(self.workers);
+        for task in self.tasks {
+            let _ = task.await;
+        }
+    }
+
+    /// Hand a frame to the worker owning its symbol
+    async fn dispatch(&self, text: String) {
+        let worker = &self.workers[shard(&text, self.workers.len())];
+        if worker.send(text).await.is_err() {
+            warn!("Pipeline worker stopped, dropping message");
+        }
+    }
+}
+
+/// Worker index for a frame, keyed by its first `symbol` value
+///
+/// Frames without a symbol (heartbeats, status) go to the first worker.
+fn shard(text: &str, workers: usize) -> usize {
+    match raw_field(text, "\"symbol\":\"") {
+        Some(symbol) => bucket(symbol, workers),
+        None => 0,
+    }
+}
+
+/// First string value following `key` (e.g. `"symbol":"`) in a raw frame
+fn raw_field<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let start = text.find(key)? + key.len();
+    text[start..].split('"').next()
+}
+
+/// Stable index in `0..buckets` for a key
+fn bucket(key: &str, buckets: usize) -> usize {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % buckets as u64) as usize
+}
+
+/// Stored subscription info for re-subscription after reconnect
+///
+/// Only available when the `reconnect` feature is enabled.
+#[cfg(feature = "reconnect")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StoredSubscription {
+    /// Orderbook subscription with its depth
+    #[cfg(feature = "orderbook")]
+    Orderbook { pair: String, depth: u32 },
+    /// Trade subscription
+    #[cfg(feature = "trades")]
+    Trades { pair: String },
+    /// Ticker subscription
+    #[cfg(feature = "ticker")]
+    Ticker { pair: String },
+    /// OHLC subscription with its interval in minutes
+    #[cfg(feature = "ohlc")]
+    #[serde(rename = "ohlc")]
+    OHLC { pair: String, interval: u32 },
+    /// Untyped subscription with its request parameters besides the channel
+    Raw {
+        channel: String,
+        params: serde_json::Value,
+    },
+}
+
+#[cfg(feature = "reconnect")]
+impl StoredSubscription {
+    /// Get the Kraken channel name
+    pub fn channel(&self) -> &str {
+        match self {
+            #[cfg(feature = "orderbook")]
+            Self::Orderbook { .. } => "book",
+            #[cfg(feature = "trades")]
+            Self::Trades { .. } => "trade",
+            #[cfg(feature = "ticker")]
+            Self::Ticker { .. } => "ticker",
+            #[cfg(feature = "ohlc")]
+            Self::OHLC { .. } => "ohlc",
+            Self::Raw { channel, .. } => channel,
+        }
+    }
+
+    /// Get the trading pair
     ///
-    /// Requires authentication credentials to be set up.
+    /// Raw subscriptions without a symbol report `*`.
+    pub fn pair(&self) -> &str {
+        match self {
+            #[cfg(feature = "orderbook")]
+            Self::Orderbook { pair, .. } => pair,
+            #[cfg(feature = "trades")]
+            Self::Trades { pair } => pair,
+            #[cfg(feature = "ticker")]
+            Self::Ticker { pair } => pair,
+            #[cfg(feature = "ohlc")]
+            Self::OHLC { pair, .. } => pair,
+            Self::Raw { params, .. } => params
+                .get("symbol")
+                .and_then(|symbols| symbols.get(0))
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("*"),
+        }
+    }
+
+    /// Build the command subscribing to this channel
+    fn subscribe_command(&self) -> Command {
+        match self {
+            #[cfg(feature = "orderbook")]
+            Self::Orderbook { pair, depth } => {
+                Command::Subscribe(SubscribeRequest::orderbook(vec![pair.clone()], *depth))
+            }
+            #[cfg(feature = "trades")]
+            Self::Trades { pair } => {
+                Command::Subscribe(SubscribeRequest::trades(vec![pair.clone()]))
+            }
+            #[cfg(feature = "ticker")]
+            Self::Ticker { pair } => {
+                Command::Subscribe(SubscribeRequest::ticker(vec![pair.clone()]))
+            }
+            #[cfg(feature = "ohlc")]
+            Self::OHLC { pair, interval } => {
+                Command::Subscribe(SubscribeRequest::ohlc(vec![pair.clone()], *interval))
+            }
+            Self::Raw { channel, params } => {
+                Command::RawMessage(raw_request("subscribe", channel, params.clone(), None))
+            }
+        }
+    }
+
+    /// Build the command unsubscribing from this channel
+    fn unsubscribe_command(&self) -> Command {
+        if let Self::Raw { channel, params } = self {
+            let mut params = params.clone();
+            if let Some(params) = params.as_object_mut() {
+                // Only meaningful when subscribing
+                params.remove("snapshot");
+            }
+            return Command::RawMessage(raw_request("unsubscribe", channel, params, None));
+        }
+        let mut request =
+            UnsubscribeRequest::new(self.channel().to_string(), vec![self.pair().to_string()]);
+        match self {
+            #[cfg(feature = "orderbook")]
+            Self::Orderbook { depth, .. } => request.params.depth = Some(*depth),
+            #[cfg(feature = "ohlc")]
+            Self::OHLC { interval, .. } => request.params.interval = Some(*interval),
+            _ => {}
+        }
+        Command::Unsubscribe(request)
+    }
+}
+
+/// Serialize a (un)subscribe request for a raw channel
+#[cfg(feature = "reconnect")]
+fn raw_request(
+    method: &str,
+    channel: &str,
+    mut params: serde_json::Value,
+    req_id: Option<u64>,
+) -> String {
+    if let Some(fields) = params.as_object_mut() {
+        fields.insert("channel".to_string(), channel.into());
+    }
+    let mut request = serde_json::json!({ "method": method, "params": params });
+    if let Some(req_id) = req_id {
+        request["req_id"] = req_id.into();
+    }
+    request.to_string()
+}
+
+/// Subscribes to the instrument channel, which carries each pair's precision
+#[cfg(feature = "checksum")]
+const INSTRUMENT_SUBSCRIBE: &str =
+    r#"{"method":"subscribe","params":{"channel":"instrument","snapshot":true}}"#;
+
+/// Pauses a channel at the server once all its subscriptions are paused
+///
+/// A channel paused at the server is left out of the stored subscriptions,
+/// so reconnects don't restore it until it is resumed.
+struct ClientUpstream {
+    stored: StoredSubscription,
+    command_tx: tokio::sync::mpsc::UnboundedSender<Command>,
+    stored_subscriptions: Arc<RwLock<Vec<StoredSubscription>>>,
+    subscriptions: Arc<RwLock<SubscriptionManager>>,
+    #[cfg(feature = "orderbook")]
+    orderbooks: Arc<OrderbookMap>,
+}
+
+impl ClientUpstream {
+    fn is_stored(&self, stored: &[StoredSubscription]) -> bool {
+        stored
+            .iter()
+            .any(|s| s.channel() == self.stored.channel() && s.pair() == self.stored.pair())
+    }
+
+    fn send(&self, command: Command) -> Result<()> {
+        self.command_tx
+            .send(command)
+            .map_err(|e| KrakyError::ChannelSend(e.to_string()))
+    }
+}
+
+impl Upstream for ClientUpstream {
+    fn pause(&self) -> Result<()> {
+        let (channel, pair) = (self.stored.channel(), self.stored.pair());
+        let paused = self.subscriptions.read().paused_flags(channel, pair);
+        if paused.contains(&false) {
+            return Ok(());
+        }
+        {
+            let mut stored = self.stored_subscriptions.write();
+            if !self.is_stored(&stored) {
+                // Already paused at the server, or unsubscribed
+                return Ok(());
+            }
+            stored.retain(|s| s.channel() != channel || s.pair() != pair);
+        }
+        debug!("Pausing {} {} at the server", channel, pair);
+        self.send(self.stored.unsubscribe_command())
+    }
+
+    fn resume(&self) -> Result<()> {
+        let (channel, pair) = (self.stored.channel(), self.stored.pair());
+        if self
+            .subscriptions
+            .read()
+            .paused_flags(channel, pair)
+            .is_empty()
+        {
+            // Unsubscribed in the meantime
+            return Ok(());
+        }
+        {
+            let mut stored = self.stored_subscriptions.write();
+            if self.is_stored(&stored) {
+                return Ok(());
+            }
+            stored.push(self.stored.clone());
+        }
+        #[cfg(feature = "orderbook")]
+        if channel == "book" {
+            // Rebuilt from the snapshot sent on subscribe
+            self.orderbooks.reset(pair);
+        }
+        debug!("Resuming {} {} at the server", channel, pair);
+        self.send(self.stored.subscribe_command())
+    }
+}
+
+/// How often the connection task checks the health thresholds
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for the server to answer our close frame
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// WebSocket connection type
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Command to send to the WebSocket handler
+#[derive(Debug, Clone)]
+enum Command {
+    Subscribe(SubscribeRequest),
+    Unsubscribe(UnsubscribeRequest),
+    Ping,
+    Shutdown,
+    /// Trigger reconnection
+    Reconnect,
+    /// Send a raw JSON message (for trading, custom requests, etc.)
+    RawMessage(String),
+}
+
+/// One WebSocket connection and its connection manager task
+struct Connection {
+    /// Command sender for the WebSocket handler
+    command_tx: tokio::sync::mpsc::UnboundedSender<Command>,
+    /// Connection state (lock-free atomic)
+    state: Arc<AtomicU8>,
+    /// RTT and heartbeat measurements
+    health: Arc<HealthMonitor>,
+    /// Stored subscriptions for re-subscription after reconnect
+    #[cfg(feature = "reconnect")]
+    stored_subscriptions: Arc<RwLock<Vec<StoredSubscription>>>,
+    /// Connection manager and heartbeat tasks, taken by `shutdown`
+    tasks: parking_lot::Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// Kraken WebSocket client
+///
+/// Provides a high-level interface for connecting to Kraken's WebSocket API
+/// and subscribing to market data streams.
+///
+/// # Example
+///
+/// ```no_run
+/// use kraky::KrakyClient;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = KrakyClient::connect().await?;
+///     
+///     let mut orderbook_sub = client.subscribe_orderbook("BTC/USD", 10).await?;
+///     
+///     while let Some(update) = orderbook_sub.next().await {
+///         println!("Orderbook update: {:?}", update);
+///     }
+///     
+///     Ok(())
+/// }
+/// ```
+pub struct KrakyClient {
+    /// WebSocket connections (more than one in pool mode)
+    connections: Vec<Connection>,
+    /// Subscription manager
+    subscriptions: Arc<RwLock<SubscriptionManager>>,
+    /// Subscribe requests waiting for their acknowledgment
+    acks: Arc<AckRegistry>,
+    /// Request IDs and responses to custom requests
+    router: Arc<ReqIdRouter>,
+    /// Latest exchange status, shared by all connections
+    system_state: Arc<watch::Sender<Option<SystemState>>>,
+    /// Managed orderbooks
+    #[cfg(feature = "orderbook")]
+    orderbooks: Arc<OrderbookMap>,
+    /// Latest ticker per pair
+    #[cfg(feature = "ticker")]
+    tickers: Arc<TickerMap>,
+    /// Reconnection configuration
+    #[cfg(feature = "reconnect")]
+    reconnect_config: Arc<ReconnectConfig>,
+    /// URL and transport options for reconnection
+    connect_options: Arc<ConnectOptions>,
+    /// Default backpressure config for new subscriptions
+    backpressure: BackpressureConfig,
+    /// Capacity of the connection event channel
+    event_buffer_size: usize,
+    /// Shutdown flag
+    shutdown: Arc<AtomicBool>,
+    /// Wakes background tasks when shutting down
+    shutdown_signal: watch::Sender<bool>,
+    /// Connection event broadcaster
+    #[cfg(feature = "events")]
+    event_tx: Arc<RwLock<Option<mpsc::Sender<ConnectionEvent>>>>,
+    /// REST fallback policy for order management
+    #[cfg(all(feature = "trading", feature = "rest"))]
+    order_fallback: Arc<RwLock<crate::rest::OrderFallback>>,
+    /// REST client for candle backfill, account history and the order fallback
+    #[cfg(all(feature = "rest", any(feature = "ohlc", feature = "private")))]
+    rest: crate::rest::RestClient,
+    /// Cached WebSocket token for private requests
+    #[cfg(all(feature = "private", feature = "rest"))]
+    token_manager: Option<Arc<crate::token::TokenManager>>,
+    /// Exchange-to-delivery latency per channel
+    #[cfg(feature = "latency")]
+    latency: Arc<crate::latency::LatencyRecorder>,
+    /// Process-wide ID of this client, carried by its tracing span
+    instance_id: u64,
+    /// Parent span of the connection and subscription spans
+    span: Span,
+}
+
+impl KrakyClient {
+    /// Connect to Kraken WebSocket API with default reconnection settings
     ///
-    /// # Example
+    /// Establishes a WebSocket connection to Kraken's public data API
+    /// and starts the message handling loop with automatic reconnection.
+    pub async fn connect() -> Result<Self> {
+        Self::builder().connect().await
+    }
+
+    /// Create a builder for custom connection options
     ///
-    /// ```ignore
-    /// use kraky::{OrderParams, Credentials};
+    /// See [`ClientBuilder`].
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Connect with a custom reconnection configuration
+    pub async fn connect_with_reconnect(config: ReconnectConfig) -> Result<Self> {
+        Self::builder().reconnect(config).connect().await
+    }
+
+    /// Connect to a custom WebSocket URL (for testing)
+    pub async fn connect_with_url(url: &str) -> Result<Self> {
+        Self::builder().url(url).connect().await
+    }
+
+    /// Connect with the parallel message pipeline enabled
     ///
-    /// let creds = Credentials::new("api_key", "api_secret");
-    /// let order = OrderParams::market_buy("BTC/USD", 0.1);
-    /// let response = client.place_order(&creds, order).await?;
-    /// println!("Order placed: {}", response.order_id);
-    /// ```
-    #[cfg(feature = "trading")]
-    pub async fn place_order(
-        &self,
-        credentials: &crate::auth::Credentials,
-        params: crate::models::OrderParams,
-    ) -> Result<crate::models::OrderResponse> {
-        use crate::models::OrderResponse;
+    /// See [`PipelineConfig`].
+    pub async fn connect_with_pipeline(pipeline: PipelineConfig) -> Result<Self> {
+        Self::builder().pipeline(pipeline).connect().await
+    }
 
-        // Generate authentication token
-        let nonce = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
-        let token = credentials.generate_token(nonce)?;
+    /// Connect with a custom URL and reconnection configuration
+    pub async fn connect_with_config(url: &str, reconnect_config: ReconnectConfig) -> Result<Self> {
+        Self::builder()
+            .url(url)
+            .reconnect(reconnect_config)
+            .connect()
+            .await
+    }
 
-        // Build request message
-        let request = serde_json::json!({
-            "method": "add_order",
-            "params": {
-                "token": token,
-                "symbol": params.symbol,
-                "side": params.side,
-                "order_type": params.order_type,
-                "order_qty": params.order_qty,
-                "limit_price": params.limit_price,
-                "trigger_price": params.trigger_price,
-                "time_in_force": params.time_in_force,
-                "post_only": params.post_only,
-                "reduce_only": params.reduce_only,
-                "stp": params.stp,
-                "cl_ord_id": params.cl_ord_id,
-                "validate": params.validate,
+    async fn connect_with_builder(builder: ClientBuilder) -> Result<Self> {
+        let instance_id = NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed);
+        let span = info_span!("kraky", client = instance_id);
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (shutdown_signal, _) = watch::channel(false);
+        let connect_options = Arc::new(ConnectOptions {
+            urls: builder.urls,
+            current: AtomicUsize::new(0),
+            ip_preference: builder.ip_preference,
+            tls: builder.tls,
+            max_message_size: builder.max_message_size,
+            #[cfg(feature = "proxy")]
+            proxy: builder.proxy,
+        });
+        let reconnect_config = Arc::new(builder.reconnect);
+        let hooks = Arc::new(builder.hooks);
+        let subscriptions = Arc::new(RwLock::new(SubscriptionManager::new()));
+        let router = Arc::new(ReqIdRouter::new());
+        let acks = Arc::new(AckRegistry::new(Arc::clone(&router)));
+        #[cfg(feature = "orderbook")]
+        let orderbooks = Arc::new(OrderbookMap {
+            #[cfg(feature = "checksum")]
+            fetch_precision: builder.fetch_book_precision,
+            ..OrderbookMap::default()
+        });
+        #[cfg(feature = "ticker")]
+        let tickers = Arc::new(TickerMap::default());
+        let event_tx: Arc<RwLock<Option<mpsc::Sender<ConnectionEvent>>>> =
+            Arc::new(RwLock::new(None));
+        let system_state = Arc::new(watch::channel(None).0);
+        #[cfg(feature = "latency")]
+        let latency = Arc::new(crate::latency::LatencyRecorder::default());
+
+        let mut connections: Vec<Connection> = Vec::with_capacity(builder.connections);
+        for index in 0..builder.connections {
+            let state = Arc::new(AtomicU8::new(ConnectionState::Connecting as u8));
+            let stored_subscriptions = Arc::new(RwLock::new(Vec::new()));
+            let health = Arc::new(HealthMonitor::default());
+            health.set_config(builder.health.clone());
+            let (command_tx, command_rx) = tokio::sync::mpsc::unbounded_channel();
+            let connection_span = info_span!(parent: &span, "connection", index);
+
+            // Initial connection, trying each endpoint once
+            let ws_stream = match Self::connect_any(&connect_options)
+                .instrument(info_span!(parent: &connection_span, "connect", attempt = 0))
+                .await
+            {
+                Ok(ws_stream) => ws_stream,
+                Err(e) => {
+                    // Stop the connections opened so far
+                    shutdown.store(true, Ordering::SeqCst);
+                    shutdown_signal.send_replace(true);
+                    for connection in &connections {
+                        let _ = connection.command_tx.send(Command::Shutdown);
+                    }
+                    return Err(e);
+                }
+            };
+            state.store(ConnectionState::Connected as u8, Ordering::SeqCst);
+            info!(
+                parent: &connection_span,
+                "WebSocket connection {} established over {:?} (TCP_NODELAY enabled)",
+                index, connect_options.tls.backend
+            );
+
+            // Spawn the connection manager task
+            let handler = MessageHandler {
+                subscriptions: Arc::clone(&subscriptions),
+                #[cfg(feature = "orderbook")]
+                orderbooks: Arc::clone(&orderbooks),
+                #[cfg(feature = "ticker")]
+                tickers: Arc::clone(&tickers),
+                health: Arc::clone(&health),
+                acks: Arc::clone(&acks),
+                router: Arc::clone(&router),
+                system_state: Arc::clone(&system_state),
+                strict: builder.strict.clone(),
+                #[cfg(feature = "trace-messages")]
+                seq: Arc::new(AtomicU64::new(0)),
+                #[cfg(feature = "latency")]
+                latency: Arc::clone(&latency),
+                #[cfg(feature = "events")]
+                event_tx: Arc::clone(&event_tx),
+                #[cfg(feature = "checksum")]
+                checksum_failure_threshold: builder.checksum_failure_threshold,
+            };
+            let manager = ConnectionManager {
+                pipeline: connection_span.in_scope(|| {
+                    builder
+                        .pipeline
+                        .as_ref()
+                        .map(|config| Pipeline::spawn(config, &handler))
+                }),
+                handler,
+                state: Arc::clone(&state),
+                reconnect_config: Arc::clone(&reconnect_config),
+                stored_subscriptions: Arc::clone(&stored_subscriptions),
+                connect_options: Arc::clone(&connect_options),
+                shutdown: Arc::clone(&shutdown),
+                shutdown_signal: shutdown_signal.subscribe(),
+                event_tx: Arc::clone(&event_tx),
+                hooks: Arc::clone(&hooks),
+                rate_limit: builder.rate_limit.clone(),
+            };
+
+            let manager_task = crate::runtime::spawn(
+                manager
+                    .run(ws_stream, command_rx)
+                    .instrument(connection_span.clone()),
+            );
+
+            // Spawn heartbeat task
+            let heartbeat_tx = command_tx.clone();
+            let heartbeat_state = Arc::clone(&state);
+            let mut heartbeat_shutdown = shutdown_signal.subscribe();
+            let heartbeat_interval = builder.heartbeat_interval;
+            let heartbeat_task = crate::runtime::spawn(
+                async move {
+                    let mut interval = tokio::time::interval(heartbeat_interval);
+                    loop {
+                        tokio::select! {
+                            _ = interval.tick() => {}
+                            _ = heartbeat_shutdown.changed() => break,
+                        }
+                        let current_state =
+                            ConnectionState::from(heartbeat_state.load(Ordering::Relaxed));
+                        if current_state == ConnectionState::Connected {
+                            // A closed command channel means the connection task is gone
+                            if heartbeat_tx.send(Command::Ping).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+                .instrument(connection_span),
+            );
+
+            connections.push(Connection {
+                command_tx,
+                state,
+                health,
+                stored_subscriptions,
+                tasks: parking_lot::Mutex::new(vec![manager_task, heartbeat_task]),
+            });
+        }
+
+        Ok(Self {
+            connections,
+            subscriptions,
+            acks,
+            router,
+            system_state,
+            #[cfg(feature = "orderbook")]
+            orderbooks,
+            #[cfg(feature = "ticker")]
+            tickers,
+            reconnect_config,
+            connect_options,
+            backpressure: builder.backpressure,
+            event_buffer_size: builder.event_buffer_size,
+            shutdown,
+            shutdown_signal,
+            event_tx,
+            #[cfg(all(feature = "trading", feature = "rest"))]
+            order_fallback: Arc::new(RwLock::new(Default::default())),
+            #[cfg(all(feature = "rest", any(feature = "ohlc", feature = "private")))]
+            rest: crate::rest::RestClient::new(),
+            #[cfg(all(feature = "private", feature = "rest"))]
+            token_manager: builder.token_manager,
+            #[cfg(feature = "latency")]
+            latency,
+            instance_id,
+            span,
+        })
+    }
+
+    /// Get the process-wide ID of this client
+    ///
+    /// Log lines of the client run inside a `kraky` span with this ID as
+    /// the `client` field, and a `connection` span with the socket `index`
+    /// below it, so the output of several clients in one process can be
+    /// told apart.
+    pub fn instance_id(&self) -> u64 {
+        self.instance_id
+    }
+
+    /// Get the exchange-to-delivery latency histograms per channel
+    ///
+    /// Keyed by channel (`book`, `trade`, `ticker`, `ohlc`), covering every
+    /// connection of the client since it connected or the last
+    /// [`reset_latency_stats`](Self::reset_latency_stats). See
+    /// [`crate::latency`] for what is measured.
+    ///
+    /// Only available when the `latency` feature is enabled.
+    #[cfg(feature = "latency")]
+    pub fn latency_stats(&self) -> HashMap<String, crate::latency::LatencyStats> {
+        self.latency.snapshot()
+    }
+
+    /// Drop all latency samples, e.g. after a warm-up period
+    ///
+    /// Only available when the `latency` feature is enabled.
+    #[cfg(feature = "latency")]
+    pub fn reset_latency_stats(&self) {
+        self.latency.reset();
+    }
+
+    /// Create a subscription pair whose span is a child of the client span
+    fn subscription<T>(
+        &self,
+        channel: &str,
+        pair: &str,
+    ) -> (SubscriptionSender<T>, Subscription<T>) {
+        self.span.in_scope(|| {
+            SubscriptionSender::with_config(
+                channel.to_string(),
+                pair.to_string(),
+                self.backpressure.clone(),
+            )
+        })
+    }
+
+    /// Create a conflated subscription pair whose span is a child of the client span
+    #[cfg(any(feature = "orderbook", feature = "ticker"))]
+    fn conflated_subscription<T: Conflate>(
+        &self,
+        channel: &str,
+        pair: &str,
+    ) -> (SubscriptionSender<T>, Subscription<T>) {
+        self.span.in_scope(|| {
+            SubscriptionSender::conflated(
+                channel.to_string(),
+                pair.to_string(),
+                self.backpressure.clone(),
+            )
+        })
+    }
+
+    /// Get the first connection, used for requests not tied to a pair
+    fn primary(&self) -> &Connection {
+        &self.connections[0]
+    }
+
+    /// Get the connection that carries subscriptions for `pair`
+    fn connection_for(&self, pair: &str) -> &Connection {
+        &self.connections[bucket(pair, self.connections.len())]
+    }
+
+    /// Server-side pause and resume for a subscription to `stored`
+    fn upstream(&self, stored: StoredSubscription) -> Arc<dyn Upstream> {
+        let connection = self.connection_for(stored.pair());
+        Arc::new(ClientUpstream {
+            command_tx: connection.command_tx.clone(),
+            stored_subscriptions: Arc::clone(&connection.stored_subscriptions),
+            subscriptions: Arc::clone(&self.subscriptions),
+            #[cfg(feature = "orderbook")]
+            orderbooks: Arc::clone(&self.orderbooks),
+            stored,
+        })
+    }
+
+    /// Send a subscribe request for `pair`, tracking its acknowledgment
+    fn send_subscribe(&self, pair: &str, request: SubscribeRequest) -> Result<AckReceiver> {
+        let (req_id, ack) = self.acks.register();
+        let connection = self.connection_for(pair);
+        // Start the data clock, so a subscription that never delivers is reported too
+        connection
+            .health
+            .data_received(&request.params.channel, pair);
+        connection
+            .command_tx
+            .send(Command::Subscribe(request.with_req_id(req_id)))
+            .map_err(|e| {
+                self.acks.resolve(req_id, Err(e.to_string()));
+                KrakyError::ChannelSend(e.to_string())
+            })?;
+        Ok(ack)
+    }
+
+    /// Connect to the first reachable endpoint, starting with the current one
+    async fn connect_any(options: &ConnectOptions) -> Result<WsStream> {
+        let mut attempts = options.urls.len();
+        loop {
+            match Self::create_connection(options).await {
+                Ok(ws_stream) => return Ok(ws_stream),
+                Err(e) if attempts > 1 => {
+                    warn!("Failed to connect to {}: {}", options.url(), e);
+                    options.rotate();
+                    attempts -= 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Create a new WebSocket connection (used for initial connect and reconnect)
+    async fn create_connection(options: &ConnectOptions) -> Result<WsStream> {
+        let url = options.url();
+        info!("Connecting to Kraken WebSocket: {}", url);
+
+        // Configure WebSocket for low latency
+        let ws_config = WebSocketConfig {
+            write_buffer_size: 0,
+            max_message_size: Some(options.max_message_size),
+            max_frame_size: Some(options.max_message_size),
+            accept_unmasked_frames: false,
+            ..Default::default()
+        };
+
+        let connector = options.tls.connector()?;
+
+        let target = url::Url::parse(url)?;
+        let host = target
+            .host_str()
+            .ok_or(url::ParseError::EmptyHost)?
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let port = target.port_or_known_default().unwrap_or(443);
+
+        #[cfg(feature = "proxy")]
+        let stream = match &options.proxy {
+            Some(proxy) => {
+                info!(
+                    "Tunnelling through {:?} proxy {}:{}",
+                    proxy.kind, proxy.host, proxy.port
+                );
+                proxy.connect(host, port).await?
+            }
+            None => Self::connect_tcp(host, port, options.ip_preference).await?,
+        };
+        #[cfg(not(feature = "proxy"))]
+        let stream = Self::connect_tcp(host, port, options.ip_preference).await?;
+        stream.set_nodelay(true)?;
+
+        let (ws_stream, _) =
+            client_async_tls_with_config(url, stream, Some(ws_config), Some(connector)).await?;
+
+        Ok(ws_stream)
+    }
+
+    /// Resolve `host` and connect to the first reachable address
+    async fn connect_tcp(host: &str, port: u16, preference: IpPreference) -> Result<TcpStream> {
+        let mut addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await?.collect();
+        preference.apply(&mut addrs);
+
+        let mut last_error = None;
+        for addr in addrs {
+            match TcpStream::connect(addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => {
+                    debug!("Failed to connect to {}: {}", addr, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("no {:?} address found for {}", preference, host),
+                )
+            })
+            .into())
+    }
+
+    /// Get the current connection state
+    ///
+    /// With a connection pool, this is the least healthy state of all
+    /// connections.
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connections
+            .iter()
+            .map(|connection| ConnectionState::from(connection.state.load(Ordering::Relaxed)))
+            .max_by_key(|state| match state {
+                ConnectionState::Connected => 0,
+                ConnectionState::Connecting => 1,
+                ConnectionState::Reconnecting => 2,
+                ConnectionState::Disconnected => 3,
+            })
+            .unwrap_or(ConnectionState::Disconnected)
+    }
+
+    /// Get the trading state Kraken last reported
+    ///
+    /// `None` until the first status message, which Kraken sends right
+    /// after connecting.
+    pub fn system_state(&self) -> Option<SystemState> {
+        *self.system_state.borrow()
+    }
+
+    /// Watch the trading state Kraken reports
+    ///
+    /// The receiver sees every change, so trading logic can stop placing
+    /// orders during maintenance or cancel-only windows and carry on once
+    /// Kraken is back online. Changes are also sent as
+    /// [`ConnectionEvent::SystemStateChanged`] with the `events` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kraky::KrakyClient;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let mut states = client.watch_system_state();
+    ///
+    /// while states.changed().await.is_ok() {
+    ///     let state = *states.borrow_and_update();
+    ///     if state.is_some_and(|state| !state.accepts_orders()) {
+    ///         println!("Pausing order entry, Kraken is {:?}", state);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch_system_state(&self) -> watch::Receiver<Option<SystemState>> {
+        self.system_state.subscribe()
+    }
+
+    /// Get the number of WebSocket connections
+    ///
+    /// See [`ClientBuilder::connections`].
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Check if the client is connected (lock-free)
+    pub fn is_connected(&self) -> bool {
+        self.connection_state() == ConnectionState::Connected
+    }
+
+    /// Check if reconnection is in progress
+    pub fn is_reconnecting(&self) -> bool {
+        self.connection_state() == ConnectionState::Reconnecting
+    }
+
+    /// Get a snapshot of the connection health
+    ///
+    /// Includes ping round-trip percentiles and the time since the last
+    /// heartbeat and message. See the [`health`](crate::health) module.
+    ///
+    /// With a connection pool, this is the first connection; use
+    /// [`pool_health`](Self::pool_health) for all of them.
+    pub fn connection_health(&self) -> ConnectionHealth {
+        self.primary().health.snapshot()
+    }
+
+    /// Get a health snapshot of every connection in the pool
+    pub fn pool_health(&self) -> Vec<ConnectionHealth> {
+        self.connections
+            .iter()
+            .map(|connection| connection.health.snapshot())
+            .collect()
+    }
+
+    /// Set the thresholds above which the connection counts as degraded
+    pub fn set_health_config(&self, config: HealthConfig) {
+        for connection in &self.connections {
+            connection.health.set_config(config.clone());
+        }
+    }
+
+    /// Get the reconnection configuration
+    pub fn reconnect_config(&self) -> &ReconnectConfig {
+        &self.reconnect_config
+    }
+
+    /// Get the subscriptions stored for restoring after a reconnect
+    ///
+    /// Which of them are actually sent again depends on
+    /// [`ReconnectConfig::restore`].
+    #[cfg(feature = "reconnect")]
+    pub fn stored_subscriptions(&self) -> Vec<StoredSubscription> {
+        self.connections
+            .iter()
+            .flat_map(|connection| connection.stored_subscriptions.read().clone())
+            .collect()
+    }
+
+    /// Get the WebSocket URL this client is connected to
+    ///
+    /// With several endpoints, this is the one currently in use.
+    pub fn url(&self) -> &str {
+        self.connect_options.url()
+    }
+
+    /// Subscribe to connection events
+    ///
+    /// Returns a receiver that will receive connection state changes.
+    /// Only one subscriber is supported at a time; calling this again
+    /// replaces the previous subscriber.
+    ///
+    /// Only available when the `events` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut events = client.subscribe_events();
+    ///
+    /// tokio::spawn(async move {
+    ///     while let Some(event) = events.recv().await {
+    ///         match event {
+    ///             ConnectionEvent::Connected => println!("Connected!"),
+    ///             ConnectionEvent::Disconnected(reason) => println!("Lost: {:?}", reason),
+    ///             ConnectionEvent::Reconnecting(n) => println!("Reconnecting #{}", n),
+    ///             ConnectionEvent::Reconnected => println!("Reconnected!"),
+    ///             ConnectionEvent::ReconnectFailed(n, e) => println!("Failed #{}: {}", n, e),
+    ///             ConnectionEvent::ReconnectExhausted => println!("Gave up reconnecting"),
+    ///             ConnectionEvent::Degraded(reason) => println!("Degraded: {}", reason),
+    ///             ConnectionEvent::Stale(idle) => println!("Silent for {:?}", idle),
+    ///             ConnectionEvent::DataStale(channel, symbol, idle) => {
+    ///                 println!("No {} updates for {} in {:?}", channel, symbol, idle)
+    ///             }
+    ///             ConnectionEvent::DataResumed(channel, symbol) => {
+    ///                 println!("{} updates for {} resumed", channel, symbol)
+    ///             }
+    ///             ConnectionEvent::OrderbookCorrupted { symbol, expected, calculated } => {
+    ///                 println!("{} checksum {} != {}", symbol, calculated, expected)
+    ///             }
+    ///             ConnectionEvent::ChecksumFailures { symbol, consecutive } => {
+    ///                 println!("{} failed {} checksums in a row", symbol, consecutive)
+    ///             }
+    ///             ConnectionEvent::OrderbookUnverified { symbol } => {
+    ///                 println!("{} checksums not validated yet", symbol)
+    ///             }
+    ///             ConnectionEvent::SystemStateChanged(state) => println!("Kraken is {}", state),
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    #[cfg(feature = "events")]
+    pub fn subscribe_events(&self) -> mpsc::Receiver<ConnectionEvent> {
+        let (tx, rx) = mpsc::channel(self.event_buffer_size);
+        *self.event_tx.write() = Some(tx);
+        rx
+    }
+
+    /// Receive every text frame from the WebSocket, before parsing
+    ///
+    /// Frames include heartbeats and status messages of all subscribed
+    /// channels. This does not subscribe to anything by itself.
+    ///
+    /// Only available when the `bridge` feature is enabled.
+    #[cfg(feature = "bridge")]
+    pub fn subscribe_frames(&self) -> Subscription<String> {
+        let (sender, subscription) = self.subscription("frames", "*");
+        self.subscriptions.write().frames.push(sender);
+        subscription
+    }
+
+    /// Receive frames that could not be parsed into typed messages
+    ///
+    /// Only fed in strict mode (see [`ClientBuilder::strict`]), with frames
+    /// that fail to parse and with unrecognized messages. Parse failures
+    /// carry the byte offset and path of the failing value;
+    /// [`ParseDiagnostic::to_error`] turns a diagnostic into a
+    /// [`KrakyError::Parse`].
+    pub fn subscribe_diagnostics(&self) -> Subscription<ParseDiagnostic> {
+        let (sender, subscription) = self.subscription("diagnostics", "*");
+        self.subscriptions.write().diagnostics.push(sender);
+        subscription
+    }
+
+    /// Subscribe to orderbook updates for a trading pair
+    ///
+    /// # Arguments
+    ///
+    /// * `pair` - Trading pair (e.g., "BTC/USD"), normalized as a [`Symbol`]
+    /// * `depth` - Number of price levels (10, 25, 100, 500, or 1000)
+    ///
+    /// # Returns
+    ///
+    /// A subscription stream that yields orderbook updates, shared with
+    /// other subscribers through an `Arc`
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    pub async fn subscribe_orderbook(
+        &self,
+        pair: impl Into<Symbol>,
+        depth: u32,
+    ) -> Result<Subscription<Arc<OrderbookUpdate>>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        self.add_orderbook_subscription(pair, depth, self.subscription("book", pair))
+    }
+
+    /// Subscribe to orderbook updates, merging them when the consumer falls behind
+    ///
+    /// Like [`subscribe_orderbook`](Self::subscribe_orderbook), but updates
+    /// arriving while the buffer is full are merged into one pending delta
+    /// (see [`OrderbookUpdate::merge`]) instead of being dropped, so a slow
+    /// consumer can still rebuild the book correctly. Merged updates are
+    /// counted in [`SubscriptionStats::conflated`](crate::SubscriptionStats::conflated).
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    pub async fn subscribe_orderbook_conflated(
+        &self,
+        pair: impl Into<Symbol>,
+        depth: u32,
+    ) -> Result<Subscription<Arc<OrderbookUpdate>>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        self.add_orderbook_subscription(pair, depth, self.conflated_subscription("book", pair))
+    }
+
+    /// Register an orderbook subscription pair and subscribe to `pair`
+    #[cfg(feature = "orderbook")]
+    fn add_orderbook_subscription(
+        &self,
+        pair: &str,
+        depth: u32,
+        (sender, subscription): (
+            SubscriptionSender<Arc<OrderbookUpdate>>,
+            Subscription<Arc<OrderbookUpdate>>,
+        ),
+    ) -> Result<Subscription<Arc<OrderbookUpdate>>> {
+        // Initialize orderbook state
+        self.orderbooks.reset(pair);
+
+        // Add subscription
+        {
+            let mut subs = self.subscriptions.write();
+            subs.orderbook.push(sender.with_depth(depth));
+        }
+
+        // Store for reconnection
+        let stored_subscription = StoredSubscription::Orderbook {
+            pair: pair.to_string(),
+            depth,
+        };
+        {
+            let mut stored = self.connection_for(pair).stored_subscriptions.write();
+            stored.push(stored_subscription.clone());
+        }
+
+        // Learn the pair's precision, without which checksums aren't validated
+        #[cfg(feature = "checksum")]
+        if self.orderbooks.request_instruments(pair) {
+            self.connection_for(pair)
+                .command_tx
+                .send(Command::RawMessage(INSTRUMENT_SUBSCRIBE.to_string()))
+                .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+        }
+
+        // Send subscribe request
+        let request = SubscribeRequest::orderbook(vec![pair.to_string()], depth);
+        let ack = self.send_subscribe(pair, request)?;
+
+        Ok(subscription
+            .with_ack(ack)
+            .with_upstream(self.upstream(stored_subscription)))
+    }
+
+    /// Subscribe to trade updates for a trading pair
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub async fn subscribe_trades(
+        &self,
+        pair: impl Into<Symbol>,
+    ) -> Result<Subscription<Arc<Trade>>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        let (sender, subscription) = self.subscription("trade", pair);
+
+        {
+            let mut subs = self.subscriptions.write();
+            subs.trades.push(sender);
+        }
+
+        // Store for reconnection
+        let stored_subscription = StoredSubscription::Trades {
+            pair: pair.to_string(),
+        };
+        {
+            let mut stored = self.connection_for(pair).stored_subscriptions.write();
+            stored.push(stored_subscription.clone());
+        }
+
+        let request = SubscribeRequest::trades(vec![pair.to_string()]);
+        let ack = self.send_subscribe(pair, request)?;
+
+        Ok(subscription
+            .with_ack(ack)
+            .with_upstream(self.upstream(stored_subscription)))
+    }
+
+    /// Subscribe to ticker updates for a trading pair
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub async fn subscribe_ticker(
+        &self,
+        pair: impl Into<Symbol>,
+    ) -> Result<Subscription<Arc<Ticker>>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        self.add_ticker_subscription(pair, self.subscription("ticker", pair))
+    }
+
+    /// Subscribe to ticker updates, keeping only the latest when the consumer falls behind
+    ///
+    /// Like [`subscribe_ticker`](Self::subscribe_ticker), but while the
+    /// buffer is full newer tickers replace the pending one instead of being
+    /// dropped, so the consumer always catches up to the current state.
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub async fn subscribe_ticker_conflated(
+        &self,
+        pair: impl Into<Symbol>,
+    ) -> Result<Subscription<Arc<Ticker>>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        self.add_ticker_subscription(pair, self.conflated_subscription("ticker", pair))
+    }
+
+    /// Register a ticker subscription pair and subscribe to `pair`
+    #[cfg(feature = "ticker")]
+    fn add_ticker_subscription(
+        &self,
+        pair: &str,
+        (sender, subscription): (SubscriptionSender<Arc<Ticker>>, Subscription<Arc<Ticker>>),
+    ) -> Result<Subscription<Arc<Ticker>>> {
+        {
+            let mut subs = self.subscriptions.write();
+            subs.ticker.push(sender);
+        }
+
+        // Store for reconnection
+        let stored_subscription = StoredSubscription::Ticker {
+            pair: pair.to_string(),
+        };
+        {
+            let mut stored = self.connection_for(pair).stored_subscriptions.write();
+            stored.push(stored_subscription.clone());
+        }
+
+        let request = SubscribeRequest::ticker(vec![pair.to_string()]);
+        let ack = self.send_subscribe(pair, request)?;
+
+        Ok(subscription
+            .with_ack(ack)
+            .with_upstream(self.upstream(stored_subscription)))
+    }
+
+    /// Watch the latest ticker for a trading pair
+    ///
+    /// For apps that only need the current price rather than every tick.
+    /// Subscribes to the ticker channel unless the pair already has a ticker
+    /// subscription, then waits for the first ticker, so the receiver
+    /// always holds a value. Wrap it in `tokio::time::timeout` to bound the
+    /// wait.
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "ticker")]
+    /// # {
+    /// # use kraky::KrakyClient;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let mut btc = client.watch_ticker("BTC/USD").await?;
+    /// println!("BTC/USD: {}", btc.borrow().last);
+    ///
+    /// // Wake up only when the ticker changes
+    /// while btc.changed().await.is_ok() {
+    ///     println!("BTC/USD: {}", btc.borrow().last);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # }
+    /// ```
+    #[cfg(feature = "ticker")]
+    pub async fn watch_ticker(&self, pair: impl Into<Symbol>) -> Result<watch::Receiver<Ticker>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        if let Some(rx) = self.tickers.watch(pair) {
+            return Ok(rx);
+        }
+
+        let subscribed = self
+            .connection_for(pair)
+            .stored_subscriptions
+            .read()
+            .iter()
+            .any(|sub| matches!(sub, StoredSubscription::Ticker { pair: p } if p == pair));
+        if !subscribed {
+            // The stream itself is not needed; the ticker map is fed regardless
+            let subscription = self.subscribe_ticker(pair).await?;
+            subscription.ready().await?;
+        }
+
+        Ok(self.tickers.first(pair).await)
+    }
+
+    /// Get the last trade price of a pair from the latest ticker
+    ///
+    /// Returns `None` until a ticker for the pair arrived. Available for any
+    /// pair with a ticker subscription or [`watch_ticker`](Self::watch_ticker).
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub fn last_price(&self, pair: impl Into<Symbol>) -> Option<f64> {
+        self.tickers.last_price(pair.into().as_str())
+    }
+
+    /// Subscribe to OHLC (candlestick) updates for a trading pair
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub async fn subscribe_ohlc(
+        &self,
+        pair: impl Into<Symbol>,
+        interval: Interval,
+    ) -> Result<Subscription<Arc<OHLC>>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        let (sender, subscription) = self.subscription("ohlc", pair);
+
+        {
+            let mut subs = self.subscriptions.write();
+            subs.ohlc.push(sender.with_interval(interval.minutes()));
+        }
+
+        // Store for reconnection
+        let stored_subscription = StoredSubscription::OHLC {
+            pair: pair.to_string(),
+            interval: interval.minutes(),
+        };
+        {
+            let mut stored = self.connection_for(pair).stored_subscriptions.write();
+            stored.push(stored_subscription.clone());
+        }
+
+        let request = SubscribeRequest::ohlc(vec![pair.to_string()], interval.minutes());
+        let ack = self.send_subscribe(pair, request)?;
+
+        Ok(subscription
+            .with_ack(ack)
+            .with_upstream(self.upstream(stored_subscription)))
+    }
+
+    /// Subscribe to OHLC candles with history and without gaps
+    ///
+    /// Fetches recent candles from the REST API (up to 720) and then
+    /// follows the live channel, so the stream starts with history instead
+    /// of the candle forming at the time of subscribing. Candles are run
+    /// through a [`CandleGapFiller`](crate::CandleGapFiller): intervals
+    /// without trades, or missed during a reconnect, arrive as flat candles
+    /// marked [`synthetic`](OHLC::synthetic). Candles are not dropped when
+    /// the consumer falls behind; the live channel buffers instead.
+    ///
+    /// Only available when the `ohlc` and `rest` features are enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use kraky::{Interval, KrakyClient};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let mut candles = client
+    ///     .subscribe_ohlc_backfilled("BTC/USD", Interval::Min1)
+    ///     .await?;
+    ///
+    /// while let Some(candle) = candles.next().await {
+    ///     let kind = if candle.synthetic { "filled" } else { "traded" };
+    ///     println!("{} close {} ({})", candle.interval_begin, candle.close, kind);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "ohlc", feature = "rest"))]
+    pub async fn subscribe_ohlc_backfilled(
+        &self,
+        pair: impl Into<Symbol>,
+        interval: Interval,
+    ) -> Result<Subscription<Arc<OHLC>>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        // Subscribe first so no live candle is missed while fetching history
+        let mut live = self.subscribe_ohlc(pair, interval).await?;
+        let history = self.rest.ohlc(pair, interval, None).await?;
+
+        let (sender, subscription) = self.subscription("ohlc", pair);
+        let ack = live.ack();
+
+        crate::runtime::spawn(async move {
+            let mut filler = crate::CandleGapFiller::new(interval);
+            for candle in history {
+                for candle in filler.push(candle) {
+                    if sender.send_wait(Arc::new(candle)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            while let Some(candle) = live.next().await {
+                // The live channel carries every interval subscribed for the pair
+                if candle.interval != interval.minutes() {
+                    continue;
+                }
+                for candle in filler.push(OHLC::clone(&candle)) {
+                    if sender.send_wait(Arc::new(candle)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(match ack {
+            Some(ack) => subscription.with_ack(ack),
+            None => subscription,
+        })
+    }
+
+    /// Subscribe to imbalance signal changes for a trading pair
+    ///
+    /// Emits only when the [`ImbalanceSignal`](crate::ImbalanceSignal) changes
+    /// (Bullish ↔ Neutral ↔ Bearish) instead of on every orderbook update.
+    /// Subscribes to the orderbook if the pair is not subscribed yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `pair` - Trading pair (e.g., "BTC/USD"), normalized as a [`Symbol`]
+    /// * `threshold` - Imbalance ratio (0.0 - 1.0) that triggers a signal
+    ///
+    /// Only available when the `analytics` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kraky::KrakyClient;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let mut signals = client.subscribe_imbalance("BTC/USD", 0.15).await?;
+    ///
+    /// while let Some(change) = signals.next().await {
+    ///     println!("{}: {:?} -> {:?} ({:+.1}%)", change.symbol, change.from, change.to, change.imbalance * 100.0);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "analytics")]
+    pub async fn subscribe_imbalance(
+        &self,
+        pair: impl Into<Symbol>,
+        threshold: f64,
+    ) -> Result<Subscription<ImbalanceTransition>> {
+        self.subscribe_imbalance_with_config(pair, ImbalanceConfig::new(threshold))
+            .await
+    }
+
+    /// Subscribe to imbalance signal changes with hysteresis/debounce options
+    ///
+    /// See [`ImbalanceConfig`] for the available options.
+    ///
+    /// Only available when the `analytics` feature is enabled.
+    #[cfg(feature = "analytics")]
+    pub async fn subscribe_imbalance_with_config(
+        &self,
+        pair: impl Into<Symbol>,
+        config: ImbalanceConfig,
+    ) -> Result<Subscription<ImbalanceTransition>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        let (sender, subscription) = self.subscription("imbalance", pair);
+
+        {
+            let mut subs = self.subscriptions.write();
+            subs.imbalance.push(ImbalanceSubscription {
+                sender: sender.with_depth(config.book_depth),
+                config,
+                trackers: parking_lot::Mutex::new(HashMap::new()),
+            });
+        }
+
+        // Signals are derived from the local orderbook, so make sure it is maintained
+        let needs_book = self.orderbooks.insert_if_absent(pair);
+
+        if needs_book {
+            {
+                let mut stored = self.connection_for(pair).stored_subscriptions.write();
+                stored.push(StoredSubscription::Orderbook {
+                    pair: pair.to_string(),
+                    depth: config.book_depth,
+                });
+            }
+
+            let request = SubscribeRequest::orderbook(vec![pair.to_string()], config.book_depth);
+            let ack = self.send_subscribe(pair, request)?;
+            return Ok(subscription.with_ack(ack));
+        }
+
+        Ok(subscription)
+    }
+
+    /// Unsubscribe from a channel for a pair
+    ///
+    /// Every subscription of the channel for the pair ends, it is no longer
+    /// restored after a reconnect, and Kraken stops sending its updates.
+    /// Unsubscribing from `book` also drops the managed orderbook.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use kraky::{KrakyClient, StoredSubscription};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let _book = client.subscribe_orderbook("BTC/USD", 10).await?;
+    ///
+    /// client.unsubscribe(&StoredSubscription::Orderbook {
+    ///     pair: "BTC/USD".to_string(),
+    ///     depth: 10,
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn unsubscribe(&self, subscription: &StoredSubscription) -> Result<()> {
+        let pair = subscription.pair();
+        let channel = subscription.channel();
+        let connection = self.connection_for(pair);
+        connection
+            .stored_subscriptions
+            .write()
+            .retain(|stored| stored.channel() != channel || stored.pair() != pair);
+        self.subscriptions.write().close(channel, pair);
+        #[cfg(feature = "orderbook")]
+        if channel == "book" {
+            self.orderbooks.remove(pair);
+        }
+
+        connection
+            .command_tx
+            .send(subscription.unsubscribe_command())
+            .map_err(|e| KrakyError::ChannelSend(e.to_string()))
+    }
+
+    /// Unsubscribe from every market data channel
+    ///
+    /// Ends all orderbook, trade, ticker, OHLC, raw and derived subscriptions
+    /// like [`unsubscribe`](Self::unsubscribe), including those paused at
+    /// the server. Frame and diagnostic subscriptions are client-side and
+    /// stay open.
+    pub fn unsubscribe_all(&self) -> Result<()> {
+        let stored: Vec<StoredSubscription> = self
+            .connections
+            .iter()
+            .flat_map(|connection| connection.stored_subscriptions.read().clone())
+            .collect();
+        for subscription in &stored {
+            self.unsubscribe(subscription)?;
+        }
+
+        // Channels paused at the server are no longer stored
+        let mut subscriptions = self.subscriptions.write();
+        for info in subscriptions.infos() {
+            subscriptions.close(&info.channel, &info.symbol);
+            #[cfg(feature = "orderbook")]
+            if info.channel == "book" {
+                self.orderbooks.remove(&info.symbol);
+            }
+        }
+        Ok(())
+    }
+
+    /// List the open subscriptions, oldest first
+    ///
+    /// Covers every subscription made through this client that hasn't been
+    /// dropped or unsubscribed, with its parameters and delivery stats, so
+    /// long-running services can audit what they consume.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kraky::KrakyClient;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let _book = client.subscribe_orderbook("BTC/USD", 10).await?;
+    ///
+    /// for info in client.subscriptions() {
+    ///     println!(
+    ///         "{} {} since {}: {} delivered, {} dropped",
+    ///         info.channel, info.symbol, info.created_at, info.stats.delivered, info.stats.dropped
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subscriptions(&self) -> Vec<SubscriptionInfo> {
+        self.subscriptions.read().infos()
+    }
+
+    /// Subscribe to a channel the SDK has no typed support for
+    ///
+    /// An escape hatch for new or unsupported channels: `params` are the
+    /// subscribe parameters besides `channel` (a JSON object, or `null` for
+    /// none), and every message of the channel is delivered as is. With a
+    /// `symbol` parameter only messages whose data carries that symbol are
+    /// delivered; subscribe to one symbol per call. Channels with typed
+    /// support keep going to their typed subscriptions.
+    ///
+    /// Raw subscriptions are restored after reconnects and can be paused
+    /// and unsubscribed like typed ones.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kraky::KrakyClient;
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let mut instruments = client
+    ///     .subscribe_raw("instrument", json!({ "snapshot": true }))
+    ///     .await?;
+    ///
+    /// while let Some(message) = instruments.next().await {
+    ///     println!("{} {}", message["type"], message["data"]);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "reconnect")]
+    pub async fn subscribe_raw(
+        &self,
+        channel: &str,
+        params: serde_json::Value,
+    ) -> Result<Subscription<serde_json::Value>> {
+        let params = match params {
+            serde_json::Value::Null => serde_json::Value::Object(Default::default()),
+            serde_json::Value::Object(mut fields) => {
+                fields.remove("channel");
+                serde_json::Value::Object(fields)
+            }
+            _ => {
+                return Err(KrakyError::InvalidMessage(
+                    "subscription params must be a JSON object".to_string(),
+                ))
+            }
+        };
+        if params
+            .get("symbol")
+            .and_then(serde_json::Value::as_array)
+            .is_some_and(|symbols| symbols.len() > 1)
+        {
+            return Err(KrakyError::InvalidMessage(
+                "raw subscriptions take one symbol".to_string(),
+            ));
+        }
+
+        let (req_id, ack) = self.acks.register();
+        let request = raw_request("subscribe", channel, params.clone(), Some(req_id));
+        let stored_subscription = StoredSubscription::Raw {
+            channel: channel.to_string(),
+            params,
+        };
+        let pair = stored_subscription.pair().to_string();
+        let (sender, subscription) = self.subscription(channel, &pair);
+        self.subscriptions.write().raw.push(sender);
+
+        let connection = self.connection_for(&pair);
+        connection
+            .stored_subscriptions
+            .write()
+            .push(stored_subscription.clone());
+
+        connection
+            .command_tx
+            .send(Command::RawMessage(request))
+            .map_err(|e| {
+                self.acks.resolve(req_id, Err(e.to_string()));
+                KrakyError::ChannelSend(e.to_string())
+            })?;
+
+        Ok(subscription
+            .with_ack(ack)
+            .with_upstream(self.upstream(stored_subscription)))
+    }
+
+    /// Send a custom request and wait for its response
+    ///
+    /// For v2 methods the SDK doesn't wrap. `request` must be a JSON object;
+    /// its `req_id` is set to a fresh ID, and the first message echoing it
+    /// is returned as is, whether it reports success or not. Resolves with
+    /// [`KrakyError::ConnectionClosed`] if the client shuts down first; wrap
+    /// it in `tokio::time::timeout` to bound the wait.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kraky::KrakyClient;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let response = client
+    ///     .send_request(serde_json::json!({ "method": "ping" }))
+    ///     .await?;
+    /// assert_eq!(response["method"], "pong");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_request(&self, request: serde_json::Value) -> Result<serde_json::Value> {
+        let (req_id, response) = self.router.register();
+        let _guard = RouteGuard {
+            router: &self.router,
+            req_id,
+        };
+        self.send_routed(request, req_id)?;
+        response.await.map_err(|_| KrakyError::ConnectionClosed)
+    }
+
+    /// Send a custom request and receive every response to it
+    ///
+    /// Like [`send_request`](Self::send_request), for methods answering
+    /// more than once. Responses are routed until the stream is dropped.
+    pub fn send_request_stream(&self, request: serde_json::Value) -> Result<ResponseStream> {
+        let (req_id, responses) = self.router.register_stream();
+        let stream = ResponseStream::new(req_id, responses, Arc::clone(&self.router));
+        self.send_routed(request, req_id)?;
+        Ok(stream)
+    }
+
+    /// Get the router assigning request IDs and matching responses
+    pub fn router(&self) -> &Arc<ReqIdRouter> {
+        &self.router
+    }
+
+    /// Send `request` on the primary connection with `req_id` set
+    fn send_routed(&self, mut request: serde_json::Value, req_id: u64) -> Result<()> {
+        let Some(object) = request.as_object_mut() else {
+            return Err(KrakyError::InvalidMessage(
+                "request must be a JSON object".to_string(),
+            ));
+        };
+        object.insert("req_id".to_string(), req_id.into());
+        self.primary()
+            .command_tx
+            .send(Command::RawMessage(request.to_string()))
+            .map_err(|e| KrakyError::ChannelSend(e.to_string()))
+    }
+
+    /// Call `handler` for every orderbook update
+    ///
+    /// Subscribes like [`subscribe_orderbook`](Self::subscribe_orderbook)
+    /// and runs the handler from a background task until the returned
+    /// handle is cancelled or the client shuts down. Callbacks and streams
+    /// can be mixed freely on one client.
+    #[cfg(feature = "orderbook")]
+    pub async fn on_orderbook<F>(
+        &self,
+        pair: impl Into<Symbol>,
+        depth: u32,
+        handler: F,
+    ) -> Result<CallbackHandle>
+    where
+        F: FnMut(Arc<OrderbookUpdate>) + Send + 'static,
+    {
+        Ok(self
+            .subscribe_orderbook(pair, depth)
+            .await?
+            .for_each(handler))
+    }
+
+    /// Call an async `handler` for every orderbook update
+    ///
+    /// Updates are handled in order, each future completing before the
+    /// next update is passed in.
+    #[cfg(feature = "orderbook")]
+    pub async fn on_orderbook_async<F, Fut>(
+        &self,
+        pair: impl Into<Symbol>,
+        depth: u32,
+        handler: F,
+    ) -> Result<CallbackHandle>
+    where
+        F: FnMut(Arc<OrderbookUpdate>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Ok(self
+            .subscribe_orderbook(pair, depth)
+            .await?
+            .for_each_async(handler))
+    }
+
+    /// Call `handler` for every trade
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use kraky::KrakyClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let trades = client
+    ///     .on_trade("BTC/USD", |trade| println!("{:?} {} @ {}", trade.side, trade.qty, trade.price))
+    ///     .await?;
+    ///
+    /// // Async handlers are awaited one update at a time
+    /// let large = client
+    ///     .on_trade_async("ETH/USD", |trade| async move {
+    ///         if trade.qty > 100.0 {
+    ///             println!("Large trade: {}", trade.qty);
+    ///         }
+    ///     })
+    ///     .await?;
+    ///
+    /// tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    /// trades.cancel();
+    /// large.cancel();
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub async fn on_trade<F>(&self, pair: impl Into<Symbol>, handler: F) -> Result<CallbackHandle>
+    where
+        F: FnMut(Arc<Trade>) + Send + 'static,
+    {
+        Ok(self.subscribe_trades(pair).await?.for_each(handler))
+    }
+
+    /// Call an async `handler` for every trade
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub async fn on_trade_async<F, Fut>(
+        &self,
+        pair: impl Into<Symbol>,
+        handler: F,
+    ) -> Result<CallbackHandle>
+    where
+        F: FnMut(Arc<Trade>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Ok(self.subscribe_trades(pair).await?.for_each_async(handler))
+    }
+
+    /// Call `handler` for every ticker update
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub async fn on_ticker<F>(&self, pair: impl Into<Symbol>, handler: F) -> Result<CallbackHandle>
+    where
+        F: FnMut(Arc<Ticker>) + Send + 'static,
+    {
+        Ok(self.subscribe_ticker(pair).await?.for_each(handler))
+    }
+
+    /// Call an async `handler` for every ticker update
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub async fn on_ticker_async<F, Fut>(
+        &self,
+        pair: impl Into<Symbol>,
+        handler: F,
+    ) -> Result<CallbackHandle>
+    where
+        F: FnMut(Arc<Ticker>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Ok(self.subscribe_ticker(pair).await?.for_each_async(handler))
+    }
+
+    /// Call `handler` for every candle update
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub async fn on_ohlc<F>(
+        &self,
+        pair: impl Into<Symbol>,
+        interval: Interval,
+        handler: F,
+    ) -> Result<CallbackHandle>
+    where
+        F: FnMut(Arc<OHLC>) + Send + 'static,
+    {
+        Ok(self.subscribe_ohlc(pair, interval).await?.for_each(handler))
+    }
+
+    /// Call an async `handler` for every candle update
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub async fn on_ohlc_async<F, Fut>(
+        &self,
+        pair: impl Into<Symbol>,
+        interval: Interval,
+        handler: F,
+    ) -> Result<CallbackHandle>
+    where
+        F: FnMut(Arc<OHLC>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Ok(self
+            .subscribe_ohlc(pair, interval)
+            .await?
+            .for_each_async(handler))
+    }
+
+    /// Get the current orderbook for a trading pair
+    pub fn get_orderbook(&self, pair: impl Into<Symbol>) -> Option<Orderbook> {
+        self.orderbooks.snapshot(pair.into().as_str())
+    }
+
+    /// Check if the orderbook for a pair has a valid checksum
+    ///
+    /// Returns `None` if no orderbook exists for the pair.
+    /// Returns `Some(true)` if the last checksum validation passed.
+    /// Returns `Some(false)` if the orderbook might be corrupted.
+    /// Returns `None` if the pair has no book, or its checksums can't be
+    /// validated because its precision is unknown (see
+    /// [`KrakyClient::set_book_precision`]).
+    ///
+    /// Only available when the `checksum` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// if client.is_orderbook_valid("BTC/USD") == Some(false) {
+    ///     // Orderbook is corrupted, trigger reconnect
+    ///     client.reconnect()?;
+    /// }
+    /// ```
+    #[cfg(feature = "checksum")]
+    pub fn is_orderbook_valid(&self, pair: impl Into<Symbol>) -> Option<bool> {
+        let book = self.orderbooks.get(pair.into().as_str())?;
+        let book = book.read();
+        book.precision.map(|_| book.checksum_valid)
+    }
+
+    /// Set the price and quantity decimals used to checksum a pair's book
+    ///
+    /// Checksums of a book are not validated until its precision is known:
+    /// updates are counted in [`ChecksumStats::unverified`](crate::models::ChecksumStats::unverified)
+    /// and [`ConnectionEvent::OrderbookUnverified`] is emitted instead. Set
+    /// it here to validate from the first snapshot, or enable
+    /// [`ClientBuilder::fetch_book_precision`]. Can be called before
+    /// subscribing.
+    ///
+    /// Only available when the `checksum` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "rest")]
+    /// # {
+    /// use kraky::rest::RestClient;
+    /// use kraky::KrakyClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let precision = RestClient::new().book_precision("XRP/EUR").await?;
+    /// client.set_book_precision("XRP/EUR", precision);
+    /// let _book = client.subscribe_orderbook("XRP/EUR", 10).await?;
+    /// # Ok(())
+    /// # }
+    /// # }
+    /// ```
+    #[cfg(feature = "checksum")]
+    pub fn set_book_precision(
+        &self,
+        pair: impl Into<Symbol>,
+        precision: crate::models::BookPrecision,
+    ) {
+        self.orderbooks
+            .set_precision(pair.into().as_str(), precision);
+    }
+
+    /// Get the checksum validation counts of a pair's orderbook
+    ///
+    /// Returns `None` until the first checksummed update for the pair.
+    ///
+    /// Only available when the `checksum` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use kraky::KrakyClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let _book = client.subscribe_orderbook("BTC/USD", 10).await?;
+    /// tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    ///
+    /// if let Some(stats) = client.checksum_stats("BTC/USD") {
+    ///     println!(
+    ///         "{} validations, {:.2}% failed, longest run {}",
+    ///         stats.validations,
+    ///         stats.failure_rate(),
+    ///         stats.max_consecutive_failures
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "checksum")]
+    pub fn checksum_stats(&self, pair: impl Into<Symbol>) -> Option<crate::models::ChecksumStats> {
+        self.orderbooks
+            .checksum_stats
+            .lock()
+            .get(pair.into().as_str())
+            .copied()
+    }
+
+    /// Validate all orderbooks and reconnect if any are corrupted
+    ///
+    /// Returns the number of corrupted orderbooks found.
+    /// If any are corrupted, a reconnection is triggered automatically.
+    ///
+    /// Only available when the `checksum` feature is enabled.
+    #[cfg(feature = "checksum")]
+    pub fn validate_orderbooks_and_reconnect(&self) -> Result<usize> {
+        let corrupted = self.orderbooks.corrupted();
+
+        let count = corrupted.len();
+
+        if count > 0 {
+            warn!(
+                parent: &self.span,
+                "Found {} corrupted orderbook(s): {:?}. Triggering reconnect.",
+                count, corrupted
+            );
+            self.reconnect()?;
+        }
+
+        Ok(count)
+    }
+
+    // ============================================================================
+    // Account History (requires 'private' and 'rest' features)
+    // ============================================================================
+
+    /// Fetch the account's fills in a time range via REST, oldest first
+    ///
+    /// Use this to reconcile local positions and PnL against the exchange.
+    ///
+    /// Only available when the `private` and `rest` features are enabled.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use kraky::rest::HistoryRange;
+    ///
+    /// let since = chrono::Utc::now() - chrono::Duration::days(7);
+    /// for trade in client.trade_history(&creds, HistoryRange::since(since)).await? {
+    ///     println!("{} {} {} @ {}", trade.time, trade.side, trade.qty, trade.price);
+    /// }
+    /// ```
+    #[cfg(all(feature = "private", feature = "rest"))]
+    pub async fn trade_history(
+        &self,
+        credentials: &crate::auth::Credentials,
+        range: crate::rest::HistoryRange,
+    ) -> Result<Vec<crate::rest::HistoricalTrade>> {
+        self.rest.trade_history(credentials, range).await
+    }
+
+    /// Fetch the account's ledger entries in a time range via REST, oldest first
+    ///
+    /// Only available when the `private` and `rest` features are enabled.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use kraky::rest::{HistoryRange, LedgerType};
+    ///
+    /// let ledger = client.ledger(&creds, HistoryRange::all()).await?;
+    /// let fees: f64 = ledger
+    ///     .iter()
+    ///     .filter(|entry| entry.entry_type == LedgerType::Trade && entry.asset == "ZUSD")
+    ///     .map(|entry| entry.fee)
+    ///     .sum();
+    /// ```
+    #[cfg(all(feature = "private", feature = "rest"))]
+    pub async fn ledger(
+        &self,
+        credentials: &crate::auth::Credentials,
+        range: crate::rest::HistoryRange,
+    ) -> Result<Vec<crate::rest::LedgerEntry>> {
+        self.rest.ledger(credentials, range).await
+    }
+
+    /// Get the token manager set with [`ClientBuilder::token_manager`]
+    ///
+    /// Only available when the `private` and `rest` features are enabled.
+    #[cfg(all(feature = "private", feature = "rest"))]
+    pub fn token_manager(&self) -> Option<&Arc<crate::token::TokenManager>> {
+        self.token_manager.as_ref()
+    }
+
+    /// Check that credentials can authenticate, via a cheap REST token fetch
+    ///
+    /// Tells a wrong key or secret, missing permissions, a rejected nonce and
+    /// network failures apart, so bots can fail fast at startup.
+    ///
+    /// Only available when the `private` and `rest` features are enabled.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use kraky::rest::CredentialError;
+    ///
+    /// match client.validate_credentials(&creds).await {
+    ///     Ok(()) => println!("Credentials OK"),
+    ///     Err(CredentialError::PermissionDenied(e)) => eprintln!("Enable WebSocket access: {}", e),
+    ///     Err(e) => eprintln!("Credentials rejected: {}", e),
+    /// }
+    /// ```
+    #[cfg(all(feature = "private", feature = "rest"))]
+    pub async fn validate_credentials(
+        &self,
+        credentials: &crate::auth::Credentials,
+    ) -> std::result::Result<(), crate::rest::CredentialError> {
+        self.rest.validate_credentials(credentials).await
+    }
+
+    // ============================================================================
+    // Trading Methods (requires 'trading' feature)
+    // ============================================================================
+
+    /// Set when `place_order`/`cancel_order` fall back to the REST API
+    ///
+    /// With [`OrderFallback::WhenDisconnected`](crate::rest::OrderFallback),
+    /// orders are sent to the REST `AddOrder`/`CancelOrder` endpoints, signed
+    /// with the same credentials, while the WebSocket is not connected.
+    ///
+    /// Only available when the `trading` and `rest` features are enabled.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use kraky::rest::OrderFallback;
+    ///
+    /// client.set_order_fallback(OrderFallback::WhenDisconnected);
+    /// ```
+    #[cfg(all(feature = "trading", feature = "rest"))]
+    pub fn set_order_fallback(&self, policy: crate::rest::OrderFallback) {
+        *self.order_fallback.write() = policy;
+    }
+
+    /// Get the current REST fallback policy for order management
+    ///
+    /// Only available when the `trading` and `rest` features are enabled.
+    #[cfg(all(feature = "trading", feature = "rest"))]
+    pub fn order_fallback(&self) -> crate::rest::OrderFallback {
+        *self.order_fallback.read()
+    }
+
+    /// The REST client to use instead of the WebSocket, if the policy applies
+    #[cfg(all(feature = "trading", feature = "rest"))]
+    fn rest_fallback(&self) -> Option<&crate::rest::RestClient> {
+        let fallback = self.order_fallback() == crate::rest::OrderFallback::WhenDisconnected
+            && !self.is_connected();
+        fallback.then_some(&self.rest)
+    }
+
+    /// Hold back an order request Kraken's current state doesn't accept
+    ///
+    /// Requests go out while the state is unknown, before the first status
+    /// message.
+    #[cfg(feature = "trading")]
+    fn ensure_trading(&self, accepted: impl Fn(SystemState) -> bool) -> Result<()> {
+        match self.system_state() {
+            Some(state) if !accepted(state) => {
+                warn!(parent: &self.span, "Holding back order request, Kraken is {}", state);
+                Err(KrakyError::TradingSuspended(state))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Token for a private WebSocket request
+    ///
+    /// Uses the token manager's token when it belongs to the same API key.
+    #[cfg(feature = "trading")]
+    async fn ws_token(&self, credentials: &crate::auth::Credentials) -> Result<String> {
+        #[cfg(feature = "rest")]
+        if let Some(manager) = &self.token_manager {
+            if manager.api_key() == credentials.api_key() {
+                return manager.token().await;
+            }
+        }
+        credentials.generate_token(credentials.next_nonce())
+    }
+
+    /// Place an order
+    ///
+    /// Requires authentication credentials to be set up. With the `rest`
+    /// feature, the order can go through REST while disconnected, see
+    /// `set_order_fallback`.
+    ///
+    /// Returns [`KrakyError::TradingSuspended`] during maintenance and
+    /// cancel-only windows, and for orders that aren't post-only while
+    /// Kraken is post-only. Orders are accepted again as soon as Kraken
+    /// reports it is back online, see [`system_state`](Self::system_state).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use kraky::{OrderParams, Credentials};
+    ///
+    /// let creds = Credentials::new("api_key", "api_secret");
+    /// let order = OrderParams::market_buy("BTC/USD", 0.1);
+    /// let response = client.place_order(&creds, order).await?;
+    /// println!("Order placed: {}", response.order_id);
+    /// ```
+    #[cfg(feature = "trading")]
+    pub async fn place_order(
+        &self,
+        credentials: &crate::auth::Credentials,
+        params: crate::models::OrderParams,
+    ) -> Result<crate::models::OrderResponse> {
+        use crate::models::OrderResponse;
+
+        params.validate()?;
+        self.ensure_trading(|state| match state {
+            SystemState::PostOnly => params.post_only == Some(true),
+            state => state.accepts_orders(),
+        })?;
+
+        #[cfg(feature = "rest")]
+        if let Some(rest) = self.rest_fallback() {
+            warn!(parent: &self.span, "WebSocket not connected, placing order via REST");
+            return rest.add_order(credentials, &params).await;
+        }
+
+        // Generate authentication token
+        let token = self.ws_token(credentials).await?;
+
+        // Build request message; unset options are left out
+        let mut order = serde_json::to_value(&params)?;
+        order["token"] = serde_json::Value::String(token);
+        let request = serde_json::json!({
+            "method": "add_order",
+            "params": order,
+        });
+
+        // Send request and wait for response
+        // Note: This is a simplified implementation
+        // A full implementation would need proper response handling
+        self.primary()
+            .command_tx
+            .send(Command::RawMessage(request.to_string()))
+            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+
+        // For now, return a placeholder
+        // A complete implementation would parse the actual response
+        Ok(OrderResponse {
+            order_id: "pending".to_string(),
+            cl_ord_id: params.cl_ord_id,
+            order_status: crate::models::OrderStatus::Pending,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Cancel an order by ID
+    ///
+    /// Falls back to REST like [`place_order`](Self::place_order), and
+    /// returns [`KrakyError::TradingSuspended`] during maintenance.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let response = client.cancel_order(&creds, "order-id-123").await?;
+    /// ```
+    #[cfg(feature = "trading")]
+    pub async fn cancel_order(
+        &self,
+        credentials: &crate::auth::Credentials,
+        order_id: impl Into<String>,
+    ) -> Result<crate::models::CancelOrderResponse> {
+        use crate::models::CancelOrderResponse;
+
+        let order_id = order_id.into();
+        self.ensure_trading(|state| state.accepts_cancels())?;
+
+        #[cfg(feature = "rest")]
+        if let Some(rest) = self.rest_fallback() {
+            warn!(parent: &self.span, "WebSocket not connected, cancelling order via REST");
+            return rest.cancel_order(credentials, &order_id).await;
+        }
+
+        // Generate authentication token
+        let token = self.ws_token(credentials).await?;
+
+        // Build request message
+        let request = serde_json::json!({
+            "method": "cancel_order",
+            "params": {
+                "token": token,
+                "order_id": [order_id.clone()],
+            }
+        });
+
+        // Send request
+        self.primary()
+            .command_tx
+            .send(Command::RawMessage(request.to_string()))
+            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+
+        // Return placeholder
+        Ok(CancelOrderResponse {
+            order_id,
+            success: true,
+        })
+    }
+
+    /// Cancel all open orders
+    ///
+    /// Returns [`KrakyError::TradingSuspended`] during maintenance.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let response = client.cancel_all_orders(&creds).await?;
+    /// println!("Cancelled {} orders", response.count);
+    /// ```
+    #[cfg(feature = "trading")]
+    pub async fn cancel_all_orders(
+        &self,
+        credentials: &crate::auth::Credentials,
+    ) -> Result<crate::models::CancelAllResponse> {
+        use crate::models::CancelAllResponse;
+
+        self.ensure_trading(|state| state.accepts_cancels())?;
+
+        // Generate authentication token
+        let token = self.ws_token(credentials).await?;
+
+        // Build request message
+        let request = serde_json::json!({
+            "method": "cancel_all",
+            "params": {
+                "token": token,
+            }
+        });
+
+        // Send request
+        self.primary()
+            .command_tx
+            .send(Command::RawMessage(request.to_string()))
+            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+
+        // Return placeholder
+        Ok(CancelAllResponse { count: 0 })
+    }
+
+    /// Amend (modify) an existing order
+    ///
+    /// Returns [`KrakyError::TradingSuspended`] during maintenance and
+    /// cancel-only windows.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use kraky::AmendOrderParams;
+    ///
+    /// let amend = AmendOrderParams {
+    ///     order_id: "order-123".to_string(),
+    ///     order_qty: Some(0.2),
+    ///     limit_price: Some(51000.0),
+    ///     trigger_price: None,
+    /// };
+    /// let response = client.amend_order(&creds, amend).await?;
+    /// ```
+    #[cfg(feature = "trading")]
+    pub async fn amend_order(
+        &self,
+        credentials: &crate::auth::Credentials,
+        params: crate::models::AmendOrderParams,
+    ) -> Result<crate::models::AmendOrderResponse> {
+        use crate::models::AmendOrderResponse;
+
+        self.ensure_trading(|state| state.accepts_orders())?;
+
+        // Generate authentication token
+        let token = self.ws_token(credentials).await?;
+
+        // Build request message
+        let request = serde_json::json!({
+            "method": "amend_order",
+            "params": {
+                "token": token,
+                "order_id": params.order_id.clone(),
+                "order_qty": params.order_qty,
+                "limit_price": params.limit_price,
+                "trigger_price": params.trigger_price,
+            }
+        });
+
+        // Send request
+        self.primary()
+            .command_tx
+            .send(Command::RawMessage(request.to_string()))
+            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+
+        // Return placeholder
+        Ok(AmendOrderResponse {
+            order_id: params.order_id,
+            success: true,
+            error: None,
+        })
+    }
+
+    /// Disconnect from the WebSocket (lock-free)
+    ///
+    /// This will stop reconnection attempts and close the connection.
+    pub fn disconnect(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.shutdown_signal.send_replace(true);
+        for connection in &self.connections {
+            connection
+                .state
+                .store(ConnectionState::Disconnected as u8, Ordering::SeqCst);
+            let _ = connection.command_tx.send(Command::Shutdown);
+        }
+    }
+
+    /// Shut down gracefully and wait for the background work to finish
+    ///
+    /// Unlike [`disconnect`](Self::disconnect), this returns only once:
+    ///
+    /// - commands queued before the call (subscriptions, orders) were sent
+    /// - each socket was closed with a close frame
+    /// - the connection, heartbeat and pipeline tasks have exited
+    /// - every subscription stream has ended
+    ///
+    /// Sinks fed by subscriptions, such as the recorder or a database
+    /// writer, see their streams end and flush; await their own `stop()`
+    /// afterwards to collect the result.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kraky::KrakyClient;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let mut updates = client.subscribe_orderbook("BTC/USD", 10).await?;
+    ///
+    /// client.shutdown().await;
+    /// // Buffered messages are still delivered, then the stream ends
+    /// while let Some(update) = updates.next().await {
+    ///     println!("{:?}", update);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn shutdown(&self) {
+        self.disconnect();
+        for connection in &self.connections {
+            let tasks = std::mem::take(&mut *connection.tasks.lock());
+            for task in tasks {
+                let _ = task.await;
+            }
+        }
+
+        // Dropping the senders ends every subscription stream
+        *self.subscriptions.write() = SubscriptionManager::new();
+        self.acks.clear();
+        self.router.clear();
+        info!(parent: &self.span, "Client shut down");
+    }
+
+    /// Manually trigger a reconnection
+    ///
+    /// Useful if you want to force a fresh connection. With a connection
+    /// pool, every connection reconnects.
+    pub fn reconnect(&self) -> Result<()> {
+        if self.shutdown.load(Ordering::Relaxed) {
+            return Err(KrakyError::ChannelSend("Client is shut down".to_string()));
+        }
+        for connection in &self.connections {
+            connection
+                .command_tx
+                .send(Command::Reconnect)
+                .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for KrakyClient {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+/// Connection manager that handles WebSocket messages and reconnection
+struct ConnectionManager {
+    handler: MessageHandler,
+    pipeline: Option<Pipeline>,
+    state: Arc<AtomicU8>,
+    reconnect_config: Arc<ReconnectConfig>,
+    stored_subscriptions: Arc<RwLock<Vec<StoredSubscription>>>,
+    connect_options: Arc<ConnectOptions>,
+    shutdown: Arc<AtomicBool>,
+    shutdown_signal: watch::Receiver<bool>,
+    event_tx: Arc<RwLock<Option<mpsc::Sender<ConnectionEvent>>>>,
+    hooks: Arc<ReconnectHooks>,
+    rate_limit: Option<RateLimitConfig>,
+}
+
+impl ConnectionManager {
+    /// Emit a connection event to subscribers
+    fn emit_event(&self, event: ConnectionEvent) {
+        if let Some(tx) = self.event_tx.read().as_ref() {
+            // Use try_send to avoid blocking; drop event if channel is full
+            let _ = tx.try_send(event);
+        }
+    }
+
+    async fn run(
+        mut self,
+        initial_stream: WsStream,
+        mut command_rx: tokio::sync::mpsc::UnboundedReceiver<Command>,
+    ) {
+        let mut ws_stream = Some(initial_stream);
+        let mut reconnect_attempt = 0u32;
+        let mut disconnect_kind = DisconnectKind::Error;
+        let mut pending_commands: Vec<Command> = Vec::new();
+
+        // Emit initial connected event
+        self.emit_event(ConnectionEvent::Connected);
+
+        loop {
+            // Check shutdown flag (a live socket is closed by the message
+            // loop, after the commands queued before `Shutdown`)
+            if ws_stream.is_none() && self.shutdown.load(Ordering::Relaxed) {
+                info!("Connection manager shutting down");
+                self.emit_event(ConnectionEvent::Disconnected(Some(
+                    "Shutdown requested".to_string(),
+                )));
+                break;
+            }
+
+            // If we have a connection, run the message loop
+            if let Some(stream) = ws_stream.take() {
+                let disconnect_reason = self
+                    .run_message_loop(stream, &mut command_rx, &mut pending_commands)
+                    .await;
+
+                let disconnect_msg = match &disconnect_reason {
+                    DisconnectReason::Shutdown => {
+                        info!("WebSocket handler shut down");
+                        self.emit_event(ConnectionEvent::Disconnected(Some(
+                            "Shutdown".to_string(),
+                        )));
+                        break;
+                    }
+                    DisconnectReason::ServerClose => {
+                        warn!("Server closed connection");
+                        disconnect_kind = DisconnectKind::ServerClose;
+                        Some("Server closed connection".to_string())
+                    }
+                    DisconnectReason::Error(e) => {
+                        error!("WebSocket error: {}", e);
+                        disconnect_kind = DisconnectKind::from_error(e);
+                        Some(e.clone())
+                    }
+                    DisconnectReason::StreamEnded => {
+                        warn!("WebSocket stream ended unexpectedly");
+                        disconnect_kind = DisconnectKind::StreamEnded;
+                        Some("Stream ended".to_string())
+                    }
+                    DisconnectReason::Stale(idle) => {
+                        warn!("No data received for {:?}, dropping connection", idle);
+                        disconnect_kind = DisconnectKind::Stale;
+                        Some(format!("No data received for {:?}", idle))
+                    }
+                    DisconnectReason::ManualReconnect => {
+                        info!("Manual reconnection requested");
+                        reconnect_attempt = 0; // Reset attempts for manual reconnect
+                        disconnect_kind = DisconnectKind::Error;
+                        None
+                    }
+                };
+
+                // Emit disconnect event and run hooks (unless it's a manual reconnect)
+                if let Some(reason) = disconnect_msg {
+                    self.emit_event(ConnectionEvent::Disconnected(Some(reason.clone())));
+                    for hook in &self.hooks.on_disconnected {
+                        hook(reason.clone()).await;
+                    }
+                }
+
+                // Should we reconnect?
+                if !self.reconnect_config.enabled || self.shutdown.load(Ordering::Relaxed) {
+                    self.state
+                        .store(ConnectionState::Disconnected as u8, Ordering::SeqCst);
+                    break;
+                }
+            }
+
+            // Wait out maintenance with a wider backoff, however long it takes
+            let maintenance = self.handler.system_state() == Some(SystemState::Maintenance);
+            if maintenance {
+                disconnect_kind = DisconnectKind::Maintenance;
+            }
+
+            // Check max attempts
+            if let Some(max) = self.reconnect_config.max_attempts {
+                if reconnect_attempt >= max && !maintenance {
+                    error!("Max reconnection attempts ({}) reached, giving up", max);
+                    self.emit_event(ConnectionEvent::ReconnectExhausted);
+                    self.state
+                        .store(ConnectionState::Disconnected as u8, Ordering::SeqCst);
+                    break;
+                }
+            }
+
+            // Attempt reconnection (again, after a failed attempt)
+            self.state
+                .store(ConnectionState::Reconnecting as u8, Ordering::SeqCst);
+            self.emit_event(ConnectionEvent::Reconnecting(reconnect_attempt + 1));
+
+            let delay = self
+                .reconnect_config
+                .delay_for(reconnect_attempt, disconnect_kind);
+            info!(
+                "Reconnecting in {:?} after {:?} (attempt {}/{})",
+                delay,
+                disconnect_kind,
+                reconnect_attempt + 1,
+                self.reconnect_config
+                    .max_attempts
+                    .map(|m| m.to_string())
+                    .unwrap_or_else(|| "∞".to_string())
+            );
+
+            // Wait out the backoff, unless shutdown is requested meanwhile
+            let mut shutdown_signal = self.shutdown_signal.clone();
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = shutdown_signal.changed() => {}
+            }
+
+            // Check shutdown again after sleep
+            if self.shutdown.load(Ordering::Relaxed) {
+                self.emit_event(ConnectionEvent::Disconnected(Some(
+                    "Shutdown during reconnect".to_string(),
+                )));
+                break;
+            }
+
+            let attempt = info_span!("connect", attempt = reconnect_attempt + 1);
+            let connecting = tokio::select! {
+                result = KrakyClient::create_connection(&self.connect_options).instrument(attempt) => result,
+                _ = shutdown_signal.changed() => break,
+            };
+            match connecting {
+                Ok(new_stream) => {
+                    info!("Reconnection successful!");
+                    self.state
+                        .store(ConnectionState::Connected as u8, Ordering::SeqCst);
+                    self.emit_event(ConnectionEvent::Reconnected);
+                    for hook in &self.hooks.on_reconnected {
+                        hook().await;
+                    }
+                    reconnect_attempt = 0;
+                    ws_stream = Some(new_stream);
+
+                    // Re-subscribe to all stored subscriptions
+                    self.resubscribe_all(&mut pending_commands);
+                }
+                Err(e) => {
+                    let err_msg = e.to_string();
+                    disconnect_kind = DisconnectKind::from_error(&err_msg);
+                    warn!(
+                        "Reconnection attempt {} failed: {}",
+                        reconnect_attempt + 1,
+                        err_msg
+                    );
+                    self.emit_event(ConnectionEvent::ReconnectFailed(
+                        reconnect_attempt + 1,
+                        err_msg,
+                    ));
+                    reconnect_attempt += 1;
+                    self.connect_options.rotate();
+                }
+            }
+        }
+
+        self.state
+            .store(ConnectionState::Disconnected as u8, Ordering::SeqCst);
+
+        // Let the workers finish frames already read off the socket
+        if let Some(pipeline) = self.pipeline.take() {
+            pipeline.close().await;
+        }
+    }
+
+    fn resubscribe_all(&self, pending_commands: &mut Vec<Command>) {
+        let mut subs = self.stored_subscriptions.write();
+        let stored = subs.len();
+        subs.retain(|sub| self.reconnect_config.restore.restores(sub));
+        if subs.len() < stored {
+            info!(
+                "Restore policy dropped {} subscriptions",
+                stored - subs.len()
+            );
+        }
+        info!("Re-subscribing to {} subscriptions", subs.len());
+
+        for sub in subs.iter() {
+            #[cfg(feature = "orderbook")]
+            if let StoredSubscription::Orderbook { pair, .. } = sub {
+                // Reset orderbook state for fresh snapshot
+                if let Some(ob) = self.handler.orderbooks.get(pair) {
+                    ob.write().clear();
+                }
+            }
+            pending_commands.push(sub.subscribe_command());
+        }
+
+        // The instrument snapshot may not have arrived before the disconnect
+        #[cfg(feature = "checksum")]
+        if self.handler.orderbooks.fetch_precision
+            && subs.iter().any(|sub| {
+                matches!(sub, StoredSubscription::Orderbook { pair, .. }
+                if self.handler.orderbooks.lacks_precision(pair))
+            })
+        {
+            pending_commands.push(Command::RawMessage(INSTRUMENT_SUBSCRIBE.to_string()));
+        }
+    }
+
+    async fn run_message_loop(
+        &self,
+        ws_stream: WsStream,
+        command_rx: &mut tokio::sync::mpsc::UnboundedReceiver<Command>,
+        pending_commands: &mut Vec<Command>,
+    ) -> DisconnectReason {
+        let (mut write, mut read) = ws_stream.split();
+        self.handler.health.reset();
+        let mut health_check = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+        let mut outbox = Outbox::new(self.rate_limit.clone());
+
+        // Queue any pending commands (e.g., re-subscriptions)
+        for cmd in pending_commands.drain(..) {
+            let json = match cmd {
+                Command::Subscribe(request) => serde_json::to_string(&request).ok(),
+                Command::RawMessage(json) => Some(json),
+                _ => None,
+            };
+            if let Some(json) = json {
+                debug!("Queueing pending subscribe: {}", json);
+                outbox.push(json);
+            }
+        }
+
+        loop {
+            // Send queued messages as far as the rate limit allows
+            while let Some(json) = outbox.pop_ready() {
+                debug!("Sending: {}", json);
+                if let Err(e) = write.send(Message::Text(json)).await {
+                    error!("Failed to send message: {}", e);
+                }
+            }
+            let throttled = outbox.throttled_for();
+
+            tokio::select! {
+                // Handle incoming WebSocket messages
+                msg = read.next() => {
+                    match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            self.handler.health.message_received();
+                            #[cfg(feature = "bridge")]
+                            self.handler.subscriptions.read().dispatch_frame(&text);
+                            match &self.pipeline {
+                                Some(pipeline) => pipeline.dispatch(text).await,
+                                None => self.handler.handle_message(&text),
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) => {
+                            return DisconnectReason::ServerClose;
+                        }
+                        Some(Ok(Message::Ping(data))) => {
+                            if let Err(e) = write.send(Message::Pong(data)).await {
+                                error!("Failed to send pong: {}", e);
+                            }
+                        }
+                        Some(Err(e)) => {
+                            return DisconnectReason::Error(e.to_string());
+                        }
+                        None => {
+                            return DisconnectReason::StreamEnded;
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Handle outgoing commands
+                cmd = command_rx.recv() => {
+                    match cmd {
+                        Some(Command::Subscribe(request)) => {
+                            match serde_json::to_string(&request) {
+                                Ok(json) => {
+                                    if !outbox.push(json) {
+                                        warn!("Outgoing queue full, dropping subscribe request");
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Failed to serialize subscribe request: {}", e);
+                                }
+                            }
+                        }
+                        Some(Command::Unsubscribe(request)) => {
+                            match serde_json::to_string(&request) {
+                                Ok(json) => {
+                                    if !outbox.push(json) {
+                                        warn!("Outgoing queue full, dropping unsubscribe request");
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Failed to serialize unsubscribe request: {}", e);
+                                }
+                            }
+                        }
+                        Some(Command::Ping) => {
+                            let req_id = self.handler.router.next_req_id();
+                            self.handler.health.ping_sent(req_id);
+                            let ping = PingRequest {
+                                req_id: Some(req_id),
+                                ..Default::default()
+                            };
+                            if let Ok(json) = serde_json::to_string(&ping) {
+                                if let Err(e) = write.send(Message::Text(json)).await {
+                                    error!("Failed to send ping: {}", e);
+                                }
+                            }
+                        }
+                        Some(Command::Reconnect) => {
+                            return DisconnectReason::ManualReconnect;
+                        }
+                        Some(Command::RawMessage(json)) => {
+                            if !outbox.push(json) {
+                                warn!("Outgoing queue full, dropping raw message");
+                            }
+                        }
+                        Some(Command::Shutdown) | None => {
+                            // Send what is still queued, then close cleanly
+                            // and give the server a moment to answer
+                            for json in outbox.drain() {
+                                if let Err(e) = write.send(Message::Text(json)).await {
+                                    error!("Failed to send message: {}", e);
+                                }
+                            }
+                            if let Err(e) = write.send(Message::Close(None)).await {
+                                debug!("Failed to send close frame: {}", e);
+                            }
+                            let _ = tokio::time::timeout(CLOSE_TIMEOUT, async {
+                                while let Some(Ok(msg)) = read.next().await {
+                                    if msg.is_close() {
+                                        break;
+                                    }
+                                }
+                            })
+                            .await;
+                            return DisconnectReason::Shutdown;
+                        }
+                    }
+                }
+
+                // Wait for the rate limiter to allow the next queued message
+                _ = tokio::time::sleep(throttled.unwrap_or_default()), if throttled.is_some() => {}
+
+                // Report latency or heartbeat degradation
+                _ = health_check.tick() => {
+                    if let Some(reason) = self.handler.health.check() {
+                        warn!("Connection degraded: {}", reason);
+                        self.emit_event(ConnectionEvent::Degraded(reason));
+                    }
+                    for transition in self.handler.health.check_data() {
+                        self.emit_event(match transition {
+                            DataTransition::Stale { channel, symbol, idle } => {
+                                warn!("No {} updates for {} in {:?}", channel, symbol, idle);
+                                ConnectionEvent::DataStale(channel, symbol, idle)
+                            }
+                            DataTransition::Resumed { channel, symbol } => {
+                                info!("{} updates for {} resumed", channel, symbol);
+                                ConnectionEvent::DataResumed(channel, symbol)
+                            }
+                        });
+                    }
+
+                    // Watchdog: a silent socket may never deliver a close frame
+                    if let (Some(timeout), Some(idle)) = (
+                        self.reconnect_config.stale_timeout,
+                        self.handler.health.since_last_message(),
+                    ) {
+                        if idle > timeout {
+                            self.emit_event(ConnectionEvent::Stale(idle));
+                            return DisconnectReason::Stale(idle);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parses messages and dispatches them to subscriptions
+///
+/// Runs on the connection task, or on each pipeline worker.
+#[derive(Clone)]
+struct MessageHandler {
+    subscriptions: Arc<RwLock<SubscriptionManager>>,
+    #[cfg(feature = "orderbook")]
+    orderbooks: Arc<OrderbookMap>,
+    #[cfg(feature = "ticker")]
+    tickers: Arc<TickerMap>,
+    health: Arc<HealthMonitor>,
+    acks: Arc<AckRegistry>,
+    /// Routes responses to custom requests
+    router: Arc<ReqIdRouter>,
+    /// Latest exchange status, shared with the client
+    system_state: Arc<watch::Sender<Option<SystemState>>>,
+    /// Strict parsing options, if enabled
+    strict: Option<StrictConfig>,
+    /// Frames handled on this connection
+    #[cfg(feature = "trace-messages")]
+    seq: Arc<AtomicU64>,
+    /// Latency histograms shared by all connections
+    #[cfg(feature = "latency")]
+    latency: Arc<crate::latency::LatencyRecorder>,
+    /// Event subscriber, for system status and orderbook corruption events
+    #[cfg(feature = "events")]
+    event_tx: Arc<RwLock<Option<mpsc::Sender<ConnectionEvent>>>>,
+    /// Consecutive checksum failures that are reported
+    #[cfg(feature = "checksum")]
+    checksum_failure_threshold: Option<u64>,
+}
+
+impl MessageHandler {
+    fn handle_message(&self, text: &str) {
+        #[cfg(feature = "trace-messages")]
+        let span = self.message_span(text);
+        #[cfg(feature = "trace-messages")]
+        let _entered = span.enter();
+        #[cfg(feature = "latency")]
+        let received = chrono::Utc::now();
+
+        let message = KrakyMessage::parse(text);
+        // Responses to custom requests go to whoever sent them
+        if self.route_response(text, &message) {
+            return;
+        }
+
+        match message {
+            Ok(msg) => match msg {
+                KrakyMessage::SystemStatus(status) => {
+                    if let Some(data) = status.data.first() {
+                        info!(
+                            "Connected to Kraken API v{} (system: {})",
+                            data.api_version, data.system
+                        );
+                        match SystemState::parse(&data.system) {
+                            Some(state) => self.set_system_state(state),
+                            None => warn!("Unknown system status: {}", data.system),
+                        }
+                    }
+                }
+                KrakyMessage::Heartbeat => {
+                    debug!("Received heartbeat");
+                    self.health.heartbeat_received();
+                }
+                KrakyMessage::Pong { req_id } => {
+                    debug!("Received pong (req_id: {:?})", req_id);
+                    self.health.pong_received(req_id);
+                }
+                KrakyMessage::SubscriptionStatus {
+                    success,
+                    channel,
+                    symbol,
+                    error,
+                    req_id,
+                } => {
+                    if let Some(req_id) = req_id {
+                        let result = match (success, &error) {
+                            (true, _) => Ok(()),
+                            (false, Some(error)) => Err(error.clone()),
+                            (false, None) => Err("unknown error".to_string()),
+                        };
+                        self.acks.resolve(req_id, result);
+                    }
+                    if success {
+                        info!("Subscribed to {} for {:?}", channel, symbol);
+                    } else if let Some(err_str) = error {
+                        let parsed = crate::error::KrakenApiError::parse(&err_str);
+                        if parsed.is_retryable() {
+                            warn!(
+                                "Subscription failed for {} (retryable): [{}:{}] {}",
+                                channel, parsed.severity, parsed.category, parsed.message
+                            );
+                        } else if parsed.is_invalid_pair() {
+                            error!("Invalid trading pair for {}: {}", channel, parsed.message);
+                        } else if parsed.is_rate_limited() {
+                            warn!("Rate limited on {} subscription", channel);
+                        } else {
+                            warn!(
+                                "Subscription failed for {}: [{}:{}] {}",
+                                channel, parsed.severity, parsed.category, parsed.message
+                            );
+                        }
+                    } else {
+                        warn!("Subscription failed for {}: unknown error", channel);
+                    }
+                }
+                #[cfg(feature = "orderbook")]
+                KrakyMessage::Orderbook(update) => {
+                    for data in &update.data {
+                        self.health.data_received("book", &data.symbol);
+                        if let Some(orderbook) = self.orderbooks.get(&data.symbol) {
+                            let mut orderbook = orderbook.write();
+                            #[cfg(all(feature = "events", feature = "checksum"))]
+                            let was_valid = orderbook.checksum_valid;
+                            orderbook.apply_update(data);
+                            #[cfg(all(feature = "events", feature = "checksum"))]
+                            if was_valid && !orderbook.checksum_valid {
+                                self.orderbook_corrupted(&orderbook);
+                            }
+                            #[cfg(feature = "checksum")]
+                            if data.checksum != 0 {
+                                if orderbook.precision.is_some() {
+                                    self.checksum_validated(&orderbook);
+                                } else {
+                                    self.checksum_unverified(&orderbook);
+                                }
+                            }
+                            #[cfg(feature = "analytics")]
+                            self.subscriptions.read().dispatch_imbalance(&orderbook);
+                        }
+                    }
+                    let update = Arc::new(update);
+                    self.subscriptions
+                        .read()
+                        .dispatch_orderbook(Arc::clone(&update));
+                    #[cfg(feature = "latency")]
+                    self.latency.record(
+                        "book",
+                        update.data.iter().map(|data| data.timestamp.as_str()),
+                        received,
+                    );
+                }
+                #[cfg(feature = "trades")]
+                KrakyMessage::Trade(update) => {
+                    for data in &update.data {
+                        self.health.data_received("trade", &data.symbol);
+                    }
+                    self.subscriptions.read().dispatch_trade(&update);
+                    #[cfg(feature = "latency")]
+                    self.latency.record(
+                        "trade",
+                        update.data.iter().map(|data| data.timestamp.as_str()),
+                        received,
+                    );
+                }
+                #[cfg(feature = "ticker")]
+                KrakyMessage::Ticker(update) => {
+                    for data in &update.data {
+                        self.health.data_received("ticker", &data.symbol);
+                        self.tickers.update(data.to_ticker());
+                    }
+                    self.subscriptions.read().dispatch_ticker(&update);
+                    #[cfg(feature = "latency")]
+                    self.latency.record(
+                        "ticker",
+                        update.data.iter().map(|data| data.timestamp.as_str()),
+                        received,
+                    );
+                }
+                #[cfg(feature = "ohlc")]
+                KrakyMessage::OHLC(update) => {
+                    for data in &update.data {
+                        self.health.data_received("ohlc", &data.symbol);
+                    }
+                    self.subscriptions.read().dispatch_ohlc(&update);
+                    #[cfg(feature = "latency")]
+                    self.latency.record(
+                        "ohlc",
+                        update.data.iter().map(|data| data.timestamp.as_str()),
+                        received,
+                    );
+                }
+                KrakyMessage::Unknown(value) => {
+                    #[cfg(feature = "checksum")]
+                    if value["channel"] == "instrument" && self.orderbooks.apply_instruments(&value)
+                    {
+                        debug!("Instrument metadata received");
+                        self.subscriptions.read().dispatch_raw(&value);
+                        return;
+                    }
+                    if !self.subscriptions.read().dispatch_raw(&value) {
+                        debug!("Unknown message: {}", value);
+                        self.reject(text, "unrecognized message".to_string());
+                    }
+                }
+            },
+            Err(e) => {
+                let diagnostic = ParseDiagnostic::from_error(text, &e);
+                warn!("{}", diagnostic.to_error());
+                debug!("Unparsed message: {}", text);
+                self.reject_diagnostic(diagnostic);
             }
-        });
+        }
+    }
 
-        // Send request and wait for response
-        // Note: This is a simplified implementation
-        // A full implementation would need proper response handling
-        self.command_tx
-            .send(Command::RawMessage(request.to_string()))
-            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+    /// Report a book whose checksum just stopped matching
+    #[cfg(all(feature = "events", feature = "checksum"))]
+    fn orderbook_corrupted(&self, orderbook: &Orderbook) {
+        let calculated = orderbook.calculate_checksum();
+        warn!(
+            "Orderbook checksum mismatch for {}: expected {}, calculated {}",
+            orderbook.symbol, orderbook.last_checksum, calculated
+        );
+        self.emit_event(ConnectionEvent::OrderbookCorrupted {
+            symbol: orderbook.symbol.clone(),
+            expected: orderbook.last_checksum,
+            calculated,
+        });
+    }
 
-        // For now, return a placeholder
-        // A complete implementation would parse the actual response
-        Ok(OrderResponse {
-            order_id: "pending".to_string(),
-            cl_ord_id: params.cl_ord_id,
-            order_status: crate::models::OrderStatus::Pending,
-            timestamp: chrono::Utc::now().to_rfc3339(),
-        })
+    /// Count a checksum validation, reporting long runs of failures
+    #[cfg(feature = "checksum")]
+    fn checksum_validated(&self, orderbook: &Orderbook) {
+        let stats = self
+            .orderbooks
+            .record_checksum(&orderbook.symbol, orderbook.checksum_valid);
+        if self.checksum_failure_threshold != Some(stats.consecutive_failures) {
+            return;
+        }
+        warn!(
+            "Orderbook for {} failed {} checksum validations in a row ({} of {} overall)",
+            orderbook.symbol, stats.consecutive_failures, stats.failures, stats.validations
+        );
+        #[cfg(feature = "events")]
+        self.emit_event(ConnectionEvent::ChecksumFailures {
+            symbol: orderbook.symbol.clone(),
+            consecutive: stats.consecutive_failures,
+        });
     }
 
-    /// Cancel an order by ID
+    /// Route a response to a custom request by its `req_id`
     ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// let response = client.cancel_order(&creds, "order-id-123").await?;
-    /// ```
-    #[cfg(feature = "trading")]
-    pub async fn cancel_order(
+    /// The ID comes from the typed parse, so the frame is only built as a
+    /// `Value` once a route for it is confirmed.
+    fn route_response(
         &self,
-        credentials: &crate::auth::Credentials,
-        order_id: impl Into<String>,
-    ) -> Result<crate::models::CancelOrderResponse> {
-        use crate::models::CancelOrderResponse;
+        text: &str,
+        message: &std::result::Result<KrakyMessage, serde_json::Error>,
+    ) -> bool {
+        if self.router.is_empty() {
+            return false;
+        }
+        let req_id = match message {
+            Ok(KrakyMessage::Pong { req_id }) => *req_id,
+            Ok(KrakyMessage::SubscriptionStatus { req_id, .. }) => *req_id,
+            Ok(KrakyMessage::Unknown(value)) => return self.router.route(value),
+            Ok(_) => None,
+            Err(_) => return self.router.route_text(text),
+        };
+        req_id.is_some_and(|req_id| self.router.is_routed(req_id))
+            && serde_json::from_str(text).is_ok_and(|value| self.router.route(&value))
+    }
 
-        let order_id = order_id.into();
+    /// Count a checksum left unvalidated, reporting the first per book
+    #[cfg(feature = "checksum")]
+    fn checksum_unverified(&self, orderbook: &Orderbook) {
+        let stats = self.orderbooks.record_unverified(&orderbook.symbol);
+        if stats.unverified != 1 {
+            return;
+        }
+        warn!(
+            "Orderbook checksums for {} are not validated: its price and quantity precision is unknown",
+            orderbook.symbol
+        );
+        #[cfg(feature = "events")]
+        self.emit_event(ConnectionEvent::OrderbookUnverified {
+            symbol: orderbook.symbol.clone(),
+        });
+    }
 
-        // Generate authentication token
-        let nonce = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
-        let token = credentials.generate_token(nonce)?;
+    /// Get the exchange status last reported on any connection
+    fn system_state(&self) -> Option<SystemState> {
+        *self.system_state.borrow()
+    }
 
-        // Build request message
-        let request = serde_json::json!({
-            "method": "cancel_order",
-            "params": {
-                "token": token,
-                "order_id": [order_id.clone()],
-            }
+    /// Record the exchange status, announcing changes
+    ///
+    /// Every connection receives the status, so only changes are reported.
+    fn set_system_state(&self, state: SystemState) {
+        let changed = self.system_state.send_if_modified(|current| {
+            let changed = *current != Some(state);
+            *current = Some(state);
+            changed
         });
+        if changed {
+            info!("Kraken system status is {}", state);
+            #[cfg(feature = "events")]
+            self.emit_event(ConnectionEvent::SystemStateChanged(state));
+        }
+    }
 
-        // Send request
-        self.command_tx
-            .send(Command::RawMessage(request.to_string()))
-            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
-
-        // Return placeholder
-        Ok(CancelOrderResponse {
-            order_id,
-            success: true,
-        })
+    /// Emit an event to the client's event subscriber
+    #[cfg(feature = "events")]
+    fn emit_event(&self, event: ConnectionEvent) {
+        if let Some(tx) = self.event_tx.read().as_ref() {
+            let _ = tx.try_send(event);
+        }
     }
 
-    /// Cancel all open orders
+    /// Span for one frame, with its sequence number and routing fields
     ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// let response = client.cancel_all_orders(&creds).await?;
-    /// println!("Cancelled {} orders", response.count);
-    /// ```
-    #[cfg(feature = "trading")]
-    pub async fn cancel_all_orders(
-        &self,
-        credentials: &crate::auth::Credentials,
-    ) -> Result<crate::models::CancelAllResponse> {
-        use crate::models::CancelAllResponse;
+    /// The fields are read off the raw text, and only when a subscriber
+    /// listens at trace level.
+    #[cfg(feature = "trace-messages")]
+    fn message_span(&self, text: &str) -> Span {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed) + 1;
+        let span = tracing::trace_span!(
+            "message",
+            seq,
+            channel = tracing::field::Empty,
+            symbol = tracing::field::Empty
+        );
+        if !span.is_disabled() {
+            let channel =
+                raw_field(text, "\"channel\":\"").or_else(|| raw_field(text, "\"method\":\""));
+            if let Some(channel) = channel {
+                span.record("channel", channel);
+            }
+            if let Some(symbol) = raw_field(text, "\"symbol\":\"") {
+                span.record("symbol", symbol);
+            }
+        }
+        span
+    }
 
-        // Generate authentication token
-        let nonce = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
-        let token = credentials.generate_token(nonce)?;
+    /// Report a frame that didn't parse into a typed message (strict mode)
+    fn reject(&self, text: &str, error: String) {
+        if self.strict.is_some() {
+            self.reject_diagnostic(ParseDiagnostic::new(text, error));
+        }
+    }
 
-        // Build request message
-        let request = serde_json::json!({
-            "method": "cancel_all",
-            "params": {
-                "token": token,
+    /// Route a diagnostic to subscribers, ending affected subscriptions if configured
+    fn reject_diagnostic(&self, diagnostic: ParseDiagnostic) {
+        let Some(strict) = &self.strict else {
+            return;
+        };
+        if strict.fail_subscriptions {
+            if let Some(channel) = &diagnostic.channel {
+                let error = diagnostic.to_error().to_string();
+                self.subscriptions
+                    .write()
+                    .fail(channel, &diagnostic.symbols, &error);
             }
-        });
+        }
+        self.subscriptions.read().dispatch_diagnostic(&diagnostic);
+    }
+}
 
-        // Send request
-        self.command_tx
-            .send(Command::RawMessage(request.to_string()))
-            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+/// Reason for WebSocket disconnection
+#[derive(Debug)]
+enum DisconnectReason {
+    Shutdown,
+    ServerClose,
+    Error(String),
+    StreamEnded,
+    ManualReconnect,
+    /// No message within the stale timeout
+    Stale(Duration),
+}
 
-        // Return placeholder
-        Ok(CancelAllResponse { count: 0 })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Message handler with fresh shared state
+    fn test_handler(strict: Option<StrictConfig>) -> MessageHandler {
+        MessageHandler {
+            subscriptions: Arc::new(RwLock::new(SubscriptionManager::new())),
+            #[cfg(feature = "orderbook")]
+            orderbooks: Arc::new(OrderbookMap::default()),
+            #[cfg(feature = "ticker")]
+            tickers: Arc::new(TickerMap::default()),
+            health: Arc::new(HealthMonitor::default()),
+            acks: Arc::new(AckRegistry::new(Default::default())),
+            router: Default::default(),
+            system_state: Default::default(),
+            strict,
+            #[cfg(feature = "trace-messages")]
+            seq: Arc::new(AtomicU64::new(0)),
+            #[cfg(feature = "latency")]
+            latency: Default::default(),
+            #[cfg(feature = "events")]
+            event_tx: Default::default(),
+            #[cfg(feature = "checksum")]
+            checksum_failure_threshold: None,
+        }
     }
 
-    /// Amend (modify) an existing order
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// use kraky::AmendOrderParams;
-    ///
-    /// let amend = AmendOrderParams {
-    ///     order_id: "order-123".to_string(),
-    ///     order_qty: Some(0.2),
-    ///     limit_price: Some(51000.0),
-    ///     trigger_price: None,
-    /// };
-    /// let response = client.amend_order(&creds, amend).await?;
-    /// ```
-    #[cfg(feature = "trading")]
-    pub async fn amend_order(
-        &self,
-        credentials: &crate::auth::Credentials,
-        params: crate::models::AmendOrderParams,
-    ) -> Result<crate::models::AmendOrderResponse> {
-        use crate::models::AmendOrderResponse;
+    #[test]
+    fn test_reconnect_config_default() {
+        let config = ReconnectConfig::default();
+        assert!(config.enabled);
+        assert_eq!(config.initial_delay, Duration::from_millis(500));
+        assert_eq!(config.max_delay, Duration::from_secs(30));
+        assert_eq!(config.backoff_multiplier, 2.0);
+        assert_eq!(config.max_attempts, None);
+        assert_eq!(config.stale_timeout, Some(Duration::from_secs(60)));
+        assert_eq!(config.jitter, 0.2);
+    }
 
-        // Generate authentication token
-        let nonce = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
-        let token = credentials.generate_token(nonce)?;
+    #[test]
+    fn test_reconnect_config_disabled() {
+        let config = ReconnectConfig::disabled();
+        assert!(!config.enabled);
+    }
 
-        // Build request message
-        let request = serde_json::json!({
-            "method": "amend_order",
-            "params": {
-                "token": token,
-                "order_id": params.order_id.clone(),
-                "order_qty": params.order_qty,
-                "limit_price": params.limit_price,
-                "trigger_price": params.trigger_price,
-            }
-        });
+    #[test]
+    fn test_reconnect_config_aggressive() {
+        let config = ReconnectConfig::aggressive();
+        assert!(config.enabled);
+        assert_eq!(config.initial_delay, Duration::from_millis(100));
+        assert_eq!(config.max_delay, Duration::from_secs(5));
+        assert_eq!(config.backoff_multiplier, 1.5);
+    }
 
-        // Send request
-        self.command_tx
-            .send(Command::RawMessage(request.to_string()))
-            .map_err(|e| KrakyError::ChannelSend(e.to_string()))?;
+    #[test]
+    fn test_reconnect_config_conservative() {
+        let config = ReconnectConfig::conservative();
+        assert!(config.enabled);
+        assert_eq!(config.initial_delay, Duration::from_secs(1));
+        assert_eq!(config.max_delay, Duration::from_secs(60));
+        assert_eq!(config.max_attempts, Some(10));
+    }
 
-        // Return placeholder
-        Ok(AmendOrderResponse {
-            order_id: params.order_id,
-            success: true,
-            error: None,
-        })
+    #[test]
+    fn test_client_builder() {
+        let builder = KrakyClient::builder();
+        assert_eq!(builder.urls, vec![KRAKEN_WS_URL.to_string()]);
+        assert_eq!(builder.heartbeat_interval, Duration::from_secs(30));
+        assert_eq!(builder.backpressure.buffer_size, crate::DEFAULT_BUFFER_SIZE);
+        assert!(builder.pipeline.is_none());
+        assert!(builder.rate_limit.is_some());
+
+        let builder = builder
+            .url("wss://localhost:9000")
+            .reconnect(ReconnectConfig::disabled())
+            .pipeline(PipelineConfig::with_workers(2))
+            .backpressure(BackpressureConfig::with_buffer_size(10))
+            .heartbeat_interval(Duration::from_secs(5))
+            .max_message_size(1024)
+            .event_buffer_size(8)
+            .without_rate_limit();
+        assert_eq!(builder.urls, vec!["wss://localhost:9000".to_string()]);
+        assert!(!builder.reconnect.enabled);
+        assert_eq!(builder.pipeline.map(|p| p.workers), Some(2));
+        assert_eq!(builder.backpressure.buffer_size, 10);
+        assert_eq!(builder.heartbeat_interval, Duration::from_secs(5));
+        assert_eq!(builder.max_message_size, 1024);
+        assert_eq!(builder.event_buffer_size, 8);
+        assert!(builder.rate_limit.is_none());
+    }
+
+    #[cfg(feature = "rustls")]
+    #[test]
+    fn test_rustls_connector() {
+        let tls = TlsConfig {
+            backend: TlsBackend::Rustls,
+            ..Default::default()
+        };
+        assert!(matches!(tls.connector(), Ok(Connector::Rustls(_))));
+
+        let insecure = TlsConfig {
+            danger_accept_invalid_certs: true,
+            ..tls
+        };
+        assert!(insecure.connector().is_err());
+    }
+
+    #[test]
+    fn test_endpoint_failover() {
+        let builder = KrakyClient::builder()
+            .urls(&["wss://ws.kraken.com/v2", "wss://beta-ws.kraken.com/v2"])
+            .urls(&[]);
+        let options = ConnectOptions {
+            urls: builder.urls,
+            current: AtomicUsize::new(0),
+            ip_preference: IpPreference::Any,
+            tls: TlsConfig::default(),
+            max_message_size: 1024,
+            #[cfg(feature = "proxy")]
+            proxy: None,
+        };
+
+        assert_eq!(options.url(), "wss://ws.kraken.com/v2");
+        options.rotate();
+        assert_eq!(options.url(), "wss://beta-ws.kraken.com/v2");
+        options.rotate();
+        assert_eq!(options.url(), "wss://ws.kraken.com/v2");
     }
 
-    /// Disconnect from the WebSocket (lock-free)
-    ///
-    /// This will stop reconnection attempts and close the connection.
-    pub fn disconnect(&self) {
-        self.shutdown.store(true, Ordering::SeqCst);
-        self.state
-            .store(ConnectionState::Disconnected as u8, Ordering::SeqCst);
-        let _ = self.command_tx.send(Command::Shutdown);
+    #[tokio::test]
+    async fn test_connect_any_skips_unreachable_endpoint() {
+        let dead = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead_url = format!("ws://{}", dead.local_addr().unwrap());
+        drop(dead);
+
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let live_url = format!("ws://{}", server.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let _ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        });
+
+        let options = ConnectOptions {
+            urls: vec![dead_url, live_url.clone()],
+            current: AtomicUsize::new(0),
+            ip_preference: IpPreference::PreferIpv4,
+            tls: TlsConfig::default(),
+            max_message_size: 1024,
+            #[cfg(feature = "proxy")]
+            proxy: None,
+        };
+        KrakyClient::connect_any(&options).await.unwrap();
+        assert_eq!(options.url(), live_url);
     }
 
-    /// Manually trigger a reconnection
-    ///
-    /// Useful if you want to force a fresh connection.
-    pub fn reconnect(&self) -> Result<()> {
-        if self.shutdown.load(Ordering::Relaxed) {
-            return Err(KrakyError::ChannelSend("Client is shut down".to_string()));
+    /// Log output captured from a test subscriber
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<parking_lot::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
         }
-        self.command_tx
-            .send(Command::Reconnect)
-            .map_err(|e| KrakyError::ChannelSend(e.to_string()))
     }
-}
 
-impl Drop for KrakyClient {
-    fn drop(&mut self) {
-        self.disconnect();
+    #[tokio::test]
+    async fn test_log_lines_carry_instance_and_connection_ids() {
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = server.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(_)) = ws.next().await {}
+                });
+            }
+        });
+
+        let first = KrakyClient::connect_with_url(&url).await.unwrap();
+        let second = KrakyClient::connect_with_url(&url).await.unwrap();
+        assert!(second.instance_id() > first.instance_id());
+        second.shutdown().await;
+
+        let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+        let client = format!("kraky{{client={}}}", second.instance_id());
+        let connection = format!("{}:connection{{index=0}}", client);
+        assert!(
+            logs.contains(&format!("{}:connect{{attempt=0}}: ", connection)),
+            "{}",
+            logs
+        );
+        assert!(logs.contains(&format!(
+            "{}: kraky::client: WebSocket connection 0",
+            connection
+        )));
+        assert!(logs.contains(&format!("{}: kraky::client: Client shut down", client)));
     }
-}
 
-/// Connection manager that handles WebSocket messages and reconnection
-struct ConnectionManager {
-    subscriptions: Arc<RwLock<SubscriptionManager>>,
-    #[cfg(feature = "orderbook")]
-    orderbooks: Arc<RwLock<HashMap<String, Orderbook>>>,
-    state: Arc<AtomicU8>,
-    reconnect_config: Arc<ReconnectConfig>,
-    stored_subscriptions: Arc<RwLock<Vec<StoredSubscription>>>,
-    url: Arc<String>,
-    shutdown: Arc<AtomicBool>,
-    event_tx: Arc<RwLock<Option<mpsc::Sender<ConnectionEvent>>>>,
-}
+    #[cfg(feature = "trace-messages")]
+    #[test]
+    fn test_message_spans_carry_routing_fields() {
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
 
-impl ConnectionManager {
-    /// Emit a connection event to subscribers
-    fn emit_event(&self, event: ConnectionEvent) {
-        if let Some(tx) = self.event_tx.read().as_ref() {
-            // Use try_send to avoid blocking; drop event if channel is full
-            let _ = tx.try_send(event);
-        }
+        let handler = test_handler(None);
+        handler.handle_message(r#"{"channel":"heartbeat"}"#);
+        handler.handle_message(
+            r#"{"channel":"instrument","type":"update","data":[{"symbol":"BTC/USD"}]}"#,
+        );
+
+        let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+        assert!(
+            logs.contains("message{seq=1 channel=\"heartbeat\"}"),
+            "{}",
+            logs
+        );
+        assert!(
+            logs.contains("message{seq=2 channel=\"instrument\" symbol=\"BTC/USD\"}"),
+            "{}",
+            logs
+        );
     }
 
-    async fn run(
-        self,
-        initial_stream: WsStream,
-        mut command_rx: tokio::sync::mpsc::UnboundedReceiver<Command>,
-    ) {
-        let mut ws_stream = Some(initial_stream);
-        let mut reconnect_attempt = 0u32;
-        let mut pending_commands: Vec<Command> = Vec::new();
+    #[cfg(feature = "events")]
+    #[tokio::test]
+    async fn test_reconnect_retries_after_failed_attempt() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        tokio::spawn(async move {
+            // First connection is closed by the server
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            ws.close(None).await.unwrap();
 
-        // Emit initial connected event
-        self.emit_event(ConnectionEvent::Connected);
+            // First reconnect attempt fails during the handshake
+            let (stream, _) = server.accept().await.unwrap();
+            drop(stream);
 
-        loop {
-            // Check shutdown flag
-            if self.shutdown.load(Ordering::Relaxed) {
-                info!("Connection manager shutting down");
-                self.emit_event(ConnectionEvent::Disconnected(Some(
-                    "Shutdown requested".to_string(),
-                )));
-                break;
+            // Second attempt succeeds
+            let (stream, _) = server.accept().await.unwrap();
+            let _ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let config = ReconnectConfig::aggressive().with_jitter(0.0);
+        let client = KrakyClient::connect_with_config(&url, config)
+            .await
+            .unwrap();
+        let mut events = client.subscribe_events();
+
+        let mut failed = false;
+        let reconnected = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = events.recv().await {
+                match event {
+                    ConnectionEvent::ReconnectFailed(1, _) => failed = true,
+                    ConnectionEvent::Reconnected => return true,
+                    _ => {}
+                }
             }
+            false
+        })
+        .await
+        .unwrap();
 
-            // If we have a connection, run the message loop
-            if let Some(stream) = ws_stream.take() {
-                let disconnect_reason = self
-                    .run_message_loop(stream, &mut command_rx, &mut pending_commands)
-                    .await;
+        assert!(failed);
+        assert!(reconnected);
+    }
 
-                let disconnect_msg = match &disconnect_reason {
-                    DisconnectReason::Shutdown => {
-                        info!("WebSocket handler shut down");
-                        self.emit_event(ConnectionEvent::Disconnected(Some(
-                            "Shutdown".to_string(),
-                        )));
-                        break;
-                    }
-                    DisconnectReason::ServerClose => {
-                        warn!("Server closed connection");
-                        Some("Server closed connection".to_string())
-                    }
-                    DisconnectReason::Error(e) => {
-                        error!("WebSocket error: {}", e);
-                        Some(e.clone())
-                    }
-                    DisconnectReason::StreamEnded => {
-                        warn!("WebSocket stream ended unexpectedly");
-                        Some("Stream ended".to_string())
-                    }
-                    DisconnectReason::ManualReconnect => {
-                        info!("Manual reconnection requested");
-                        reconnect_attempt = 0; // Reset attempts for manual reconnect
-                        None
-                    }
-                };
+    #[cfg(all(feature = "events", feature = "orderbook", feature = "testing"))]
+    #[tokio::test]
+    async fn test_data_stale_events() {
+        use crate::testing::{fixtures, MockScript, MockServer};
 
-                // Emit disconnect event (unless it's a manual reconnect)
-                if disconnect_msg.is_some() {
-                    self.emit_event(ConnectionEvent::Disconnected(disconnect_msg));
+        let server = MockServer::start(
+            MockScript::new().message(fixtures::get("book_snapshot").unwrap().text),
+        )
+        .await
+        .unwrap();
+        let client = KrakyClient::builder()
+            .url(server.url())
+            .health(HealthConfig::default().data_gap("book", Duration::from_millis(100)))
+            .connect()
+            .await
+            .unwrap();
+        let mut events = client.subscribe_events();
+        let mut book = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+        book.next().await.unwrap();
+
+        // The snapshot is the only update, so the book goes stale
+        let stale = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = events.recv().await {
+                if let ConnectionEvent::DataStale(channel, symbol, idle) = event {
+                    return (channel, symbol, idle);
                 }
+            }
+            panic!("events closed");
+        })
+        .await
+        .unwrap();
+        assert_eq!((stale.0.as_str(), stale.1.as_str()), ("book", "BTC/USD"));
+        assert!(stale.2 > Duration::from_millis(100));
+    }
 
-                // Should we reconnect?
-                if !self.reconnect_config.enabled || self.shutdown.load(Ordering::Relaxed) {
-                    self.state
-                        .store(ConnectionState::Disconnected as u8, Ordering::SeqCst);
+    #[cfg(feature = "orderbook")]
+    #[tokio::test]
+    async fn test_reconnect_hooks_run_before_resubscribe() {
+        let log = Arc::new(parking_lot::Mutex::new(Vec::<String>::new()));
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let server_log = Arc::clone(&log);
+        tokio::spawn(async move {
+            // Drop the first connection once the subscription arrived
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if text.contains("subscribe") {
                     break;
                 }
+            }
+            ws.close(None).await.unwrap();
 
-                // Check max attempts
-                if let Some(max) = self.reconnect_config.max_attempts {
-                    if reconnect_attempt >= max {
-                        error!("Max reconnection attempts ({}) reached, giving up", max);
-                        self.emit_event(ConnectionEvent::ReconnectExhausted);
-                        self.state
-                            .store(ConnectionState::Disconnected as u8, Ordering::SeqCst);
-                        break;
-                    }
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if text.contains("subscribe") {
+                    server_log.lock().push("resubscribed".to_string());
                 }
+            }
+        });
 
-                // Attempt reconnection
-                self.state
-                    .store(ConnectionState::Reconnecting as u8, Ordering::SeqCst);
-                self.emit_event(ConnectionEvent::Reconnecting(reconnect_attempt + 1));
+        let disconnected_log = Arc::clone(&log);
+        let reconnected_log = Arc::clone(&log);
+        let client = KrakyClient::builder()
+            .url(&url)
+            .reconnect(ReconnectConfig::aggressive().with_jitter(0.0))
+            .on_disconnected(move |reason| {
+                let log = Arc::clone(&disconnected_log);
+                async move { log.lock().push(format!("disconnected: {}", reason)) }
+            })
+            .on_reconnected(move || {
+                let log = Arc::clone(&reconnected_log);
+                async move {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    log.lock().push("reconnected".to_string());
+                }
+            })
+            .connect()
+            .await
+            .unwrap();
+        let _updates = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
 
-                let delay = self.reconnect_config.delay_for_attempt(reconnect_attempt);
-                info!(
-                    "Reconnecting in {:?} (attempt {}/{})",
-                    delay,
-                    reconnect_attempt + 1,
-                    self.reconnect_config
-                        .max_attempts
-                        .map(|m| m.to_string())
-                        .unwrap_or_else(|| "∞".to_string())
-                );
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while log.lock().len() < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
 
-                tokio::time::sleep(delay).await;
+        assert_eq!(
+            *log.lock(),
+            vec![
+                "disconnected: Server closed connection".to_string(),
+                "reconnected".to_string(),
+                "resubscribed".to_string(),
+            ]
+        );
+    }
 
-                // Check shutdown again after sleep
-                if self.shutdown.load(Ordering::Relaxed) {
-                    self.emit_event(ConnectionEvent::Disconnected(Some(
-                        "Shutdown during reconnect".to_string(),
-                    )));
-                    break;
-                }
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_stored_subscription_serde() {
+        let stored = StoredSubscription::Orderbook {
+            pair: "BTC/USD".to_string(),
+            depth: 10,
+        };
+        let json = serde_json::to_value(&stored).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"kind": "orderbook", "pair": "BTC/USD", "depth": 10})
+        );
+        assert_eq!(
+            serde_json::from_value::<StoredSubscription>(json).unwrap(),
+            stored
+        );
+    }
 
-                match KrakyClient::create_connection(&self.url).await {
-                    Ok(new_stream) => {
-                        info!("Reconnection successful!");
-                        self.state
-                            .store(ConnectionState::Connected as u8, Ordering::SeqCst);
-                        self.emit_event(ConnectionEvent::Reconnected);
-                        reconnect_attempt = 0;
-                        ws_stream = Some(new_stream);
-
-                        // Re-subscribe to all stored subscriptions
-                        self.resubscribe_all(&mut pending_commands);
-                    }
-                    Err(e) => {
-                        let err_msg = e.to_string();
-                        warn!(
-                            "Reconnection attempt {} failed: {}",
-                            reconnect_attempt + 1,
-                            err_msg
-                        );
-                        self.emit_event(ConnectionEvent::ReconnectFailed(
-                            reconnect_attempt + 1,
-                            err_msg,
-                        ));
-                        reconnect_attempt += 1;
-                        ws_stream = None;
+    #[cfg(feature = "orderbook")]
+    #[tokio::test]
+    async fn test_restore_policy_filters_resubscribe() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (restored_tx, mut restored_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            // Drop the first connection once both subscriptions arrived
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut subscribes = 0;
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if text.contains("subscribe") {
+                    subscribes += 1;
+                    if subscribes == 2 {
+                        break;
                     }
                 }
-            } else {
-                // No connection, wait before retrying
-                tokio::time::sleep(Duration::from_millis(100)).await;
             }
-        }
+            ws.close(None).await.unwrap();
 
-        self.state
-            .store(ConnectionState::Disconnected as u8, Ordering::SeqCst);
-    }
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if text.contains("subscribe") {
+                    let _ = restored_tx.send(text);
+                }
+            }
+        });
 
-    fn resubscribe_all(&self, pending_commands: &mut Vec<Command>) {
-        let subs = self.stored_subscriptions.read();
-        info!("Re-subscribing to {} subscriptions", subs.len());
+        let reconnect = ReconnectConfig::aggressive()
+            .with_jitter(0.0)
+            .with_restore_policy(RestorePolicy::custom(|sub| sub.pair() == "BTC/USD"));
+        let client = KrakyClient::connect_with_config(&url, reconnect)
+            .await
+            .unwrap();
+        let _btc = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+        let _eth = client.subscribe_orderbook("ETH/USD", 10).await.unwrap();
+        assert_eq!(client.stored_subscriptions().len(), 2);
 
-        for sub in subs.iter() {
-            let request = match sub {
-                #[cfg(feature = "orderbook")]
-                StoredSubscription::Orderbook { pair, depth } => {
-                    // Reset orderbook state for fresh snapshot
-                    {
-                        let mut orderbooks = self.orderbooks.write();
-                        if let Some(ob) = orderbooks.get_mut(pair) {
-                            *ob = Orderbook::new(pair.clone());
-                        }
-                    }
-                    SubscribeRequest::orderbook(vec![pair.clone()], *depth)
-                }
-                #[cfg(feature = "trades")]
-                StoredSubscription::Trades { pair } => SubscribeRequest::trades(vec![pair.clone()]),
-                #[cfg(feature = "ticker")]
-                StoredSubscription::Ticker { pair } => SubscribeRequest::ticker(vec![pair.clone()]),
-                #[cfg(feature = "ohlc")]
-                StoredSubscription::OHLC { pair, interval } => {
-                    SubscribeRequest::ohlc(vec![pair.clone()], *interval)
+        let restored = tokio::time::timeout(Duration::from_secs(5), restored_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(restored.contains("BTC/USD"));
+        assert!(!restored.contains("ETH/USD"));
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), restored_rx.recv())
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            client.stored_subscriptions(),
+            vec![StoredSubscription::Orderbook {
+                pair: "BTC/USD".to_string(),
+                depth: 10
+            }]
+        );
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[tokio::test]
+    async fn test_rate_limit_spaces_out_subscribes() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if text.contains("subscribe") {
+                    let _ = seen_tx.send(std::time::Instant::now());
                 }
-            };
-            pending_commands.push(Command::Subscribe(request));
+            }
+        });
+
+        let client = KrakyClient::builder()
+            .url(&url)
+            .rate_limit(RateLimitConfig {
+                burst: 2,
+                per_second: 20.0,
+                ..Default::default()
+            })
+            .connect()
+            .await
+            .unwrap();
+        let mut subs = Vec::new();
+        for pair in ["BTC/USD", "ETH/USD", "SOL/USD", "XRP/USD"] {
+            subs.push(client.subscribe_orderbook(pair, 10).await.unwrap());
         }
+
+        let mut seen = Vec::new();
+        while seen.len() < 4 {
+            let at = tokio::time::timeout(Duration::from_secs(5), seen_rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            seen.push(at);
+        }
+        // Two go out at once, the other two wait for refills
+        assert!(seen[3] - seen[0] >= Duration::from_millis(80));
     }
 
-    async fn run_message_loop(
-        &self,
-        ws_stream: WsStream,
-        command_rx: &mut tokio::sync::mpsc::UnboundedReceiver<Command>,
-        pending_commands: &mut Vec<Command>,
-    ) -> DisconnectReason {
-        let (mut write, mut read) = ws_stream.split();
+    #[cfg(feature = "orderbook")]
+    #[tokio::test]
+    async fn test_subscription_ready_waits_for_ack() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if request["method"] != "subscribe" {
+                    continue;
+                }
+                let symbol = request["params"]["symbol"][0].as_str().unwrap();
+                let ack = if symbol == "FOO/BAR" {
+                    serde_json::json!({
+                        "method": "subscribe",
+                        "req_id": request["req_id"],
+                        "success": false,
+                        "error": "Currency pair not supported FOO/BAR",
+                    })
+                } else {
+                    serde_json::json!({
+                        "method": "subscribe",
+                        "req_id": request["req_id"],
+                        "success": true,
+                        "result": {"channel": "book", "symbol": symbol},
+                    })
+                };
+                ws.send(Message::Text(ack.to_string())).await.unwrap();
+            }
+        });
 
-        // Send any pending commands (e.g., re-subscriptions)
-        for cmd in pending_commands.drain(..) {
-            if let Command::Subscribe(request) = cmd {
-                if let Ok(json) = serde_json::to_string(&request) {
-                    debug!("Sending pending subscribe: {}", json);
-                    if let Err(e) = write.send(Message::Text(json)).await {
-                        error!("Failed to send pending subscribe: {}", e);
-                    }
+        let client = KrakyClient::builder().url(&url).connect().await.unwrap();
+        let accepted = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+        let rejected = client.subscribe_orderbook("FOO/BAR", 10).await.unwrap();
+
+        let timeout = Duration::from_secs(5);
+        tokio::time::timeout(timeout, accepted.ready())
+            .await
+            .unwrap()
+            .unwrap();
+        let err = tokio::time::timeout(timeout, rejected.ready())
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(err, KrakyError::Subscription(ref e) if e.contains("FOO/BAR")));
+
+        // Still answers once resolved
+        accepted.ready().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pairs_are_normalized() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (symbol_tx, mut symbol_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if request["method"] == "subscribe" {
+                    let symbol = request["params"]["symbol"][0].as_str().unwrap();
+                    symbol_tx.send(symbol.to_string()).unwrap();
                 }
             }
-        }
+        });
 
-        loop {
-            tokio::select! {
-                // Handle incoming WebSocket messages
-                msg = read.next() => {
-                    match msg {
-                        Some(Ok(Message::Text(text))) => {
-                            self.handle_message(&text);
-                        }
-                        Some(Ok(Message::Close(_))) => {
-                            return DisconnectReason::ServerClose;
-                        }
-                        Some(Ok(Message::Ping(data))) => {
-                            if let Err(e) = write.send(Message::Pong(data)).await {
-                                error!("Failed to send pong: {}", e);
-                            }
-                        }
-                        Some(Err(e)) => {
-                            return DisconnectReason::Error(e.to_string());
-                        }
-                        None => {
-                            return DisconnectReason::StreamEnded;
-                        }
-                        _ => {}
-                    }
+        let client = KrakyClient::builder().url(&url).connect().await.unwrap();
+        client.subscribe_orderbook("xbt-usd", 10).await.unwrap();
+
+        let sent = tokio::time::timeout(Duration::from_secs(5), symbol_rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(sent.as_deref(), Some("BTC/USD"));
+        assert!(client.get_orderbook("BTC/USD").is_some());
+        assert!(client.get_orderbook(Symbol::new("XBT", "USD")).is_some());
+        assert!(client.get_orderbook("ETH/USD").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pause_upstream_unsubscribes_until_resumed() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (method_tx, mut method_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if let Some(method @ ("subscribe" | "unsubscribe")) = request["method"].as_str() {
+                    method_tx.send(method.to_string()).unwrap();
                 }
+            }
+        });
+        async fn next_method(rx: &mut mpsc::UnboundedReceiver<String>) -> String {
+            tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .unwrap()
+                .unwrap()
+        }
 
-                // Handle outgoing commands
-                cmd = command_rx.recv() => {
-                    match cmd {
-                        Some(Command::Subscribe(request)) => {
-                            match serde_json::to_string(&request) {
-                                Ok(json) => {
-                                    debug!("Sending subscribe: {}", json);
-                                    if let Err(e) = write.send(Message::Text(json)).await {
-                                        error!("Failed to send subscribe: {}", e);
-                                    }
-                                }
-                                Err(e) => {
-                                    error!("Failed to serialize subscribe request: {}", e);
-                                }
-                            }
-                        }
-                        Some(Command::Ping) => {
-                            let ping = PingRequest::default();
-                            if let Ok(json) = serde_json::to_string(&ping) {
-                                if let Err(e) = write.send(Message::Text(json)).await {
-                                    error!("Failed to send ping: {}", e);
-                                }
-                            }
-                        }
-                        Some(Command::Reconnect) => {
-                            return DisconnectReason::ManualReconnect;
-                        }
-                        #[cfg(feature = "trading")]
-                        Some(Command::RawMessage(json)) => {
-                            debug!("Sending raw message: {}", json);
-                            if let Err(e) = write.send(Message::Text(json)).await {
-                                error!("Failed to send raw message: {}", e);
-                            }
-                        }
-                        Some(Command::Shutdown) | None => {
-                            return DisconnectReason::Shutdown;
-                        }
-                    }
+        let client = KrakyClient::builder().url(&url).connect().await.unwrap();
+        let first = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+        let second = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+        assert_eq!(next_method(&mut method_rx).await, "subscribe");
+        assert_eq!(next_method(&mut method_rx).await, "subscribe");
+
+        // Another subscription still wants the data
+        first.pause_upstream().unwrap();
+        assert!(first.is_paused());
+        second.pause_upstream().unwrap();
+        assert_eq!(next_method(&mut method_rx).await, "unsubscribe");
+        // Paused channels aren't restored on reconnect
+        assert!(client.stored_subscriptions().is_empty());
+
+        first.resume().unwrap();
+        assert_eq!(next_method(&mut method_rx).await, "subscribe");
+        second.resume().unwrap();
+        assert_eq!(client.stored_subscriptions().len(), 1);
+        assert!(!second.is_paused());
+    }
+
+    #[tokio::test]
+    async fn test_send_request_gets_matched_response() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if request["method"] != "echo" {
+                    continue;
+                }
+                // Answer twice, the first with an unrelated req_id
+                for req_id in [serde_json::json!(0), request["req_id"].clone()] {
+                    let response = serde_json::json!({
+                        "method": "echo",
+                        "req_id": req_id,
+                        "result": request["params"],
+                        "success": true,
+                    });
+                    ws.send(Message::Text(response.to_string())).await.unwrap();
                 }
             }
-        }
+        });
+
+        let client = KrakyClient::builder().url(&url).connect().await.unwrap();
+        let request = serde_json::json!({ "method": "echo", "params": { "n": 1 } });
+        let response = tokio::time::timeout(Duration::from_secs(5), client.send_request(request))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response["result"]["n"], 1);
+        assert_ne!(response["req_id"], 0);
+        assert!(client.router().is_empty());
+
+        let mut responses = client
+            .send_request_stream(serde_json::json!({ "method": "echo", "params": { "n": 2 } }))
+            .unwrap();
+        let response = tokio::time::timeout(Duration::from_secs(5), responses.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response["req_id"], responses.req_id());
+        drop(responses);
+        assert!(client.router().is_empty());
+
+        assert!(client.send_request(serde_json::json!([1])).await.is_err());
     }
 
-    fn handle_message(&self, text: &str) {
-        match KrakyMessage::parse(text) {
-            Ok(msg) => match msg {
-                KrakyMessage::SystemStatus(status) => {
-                    if let Some(data) = status.data.first() {
-                        info!(
-                            "Connected to Kraken API v{} (system: {})",
-                            data.api_version, data.system
-                        );
-                    }
-                }
-                KrakyMessage::Heartbeat => {
-                    debug!("Received heartbeat");
+    #[tokio::test]
+    async fn test_raw_subscription_gets_its_symbol() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if request["method"] != "subscribe" {
+                    continue;
                 }
-                KrakyMessage::Pong { req_id } => {
-                    debug!("Received pong (req_id: {:?})", req_id);
+                let ack = serde_json::json!({
+                    "method": "subscribe",
+                    "result": {
+                        "channel": request["params"]["channel"],
+                        "symbol": request["params"]["symbol"][0],
+                    },
+                    "success": true,
+                    "req_id": request["req_id"],
+                });
+                ws.send(Message::Text(ack.to_string())).await.unwrap();
+                for symbol in ["ETH/USD", "BTC/USD"] {
+                    let update = serde_json::json!({
+                        "channel": "level4",
+                        "type": "update",
+                        "data": [{ "symbol": symbol, "n": 1 }],
+                    });
+                    ws.send(Message::Text(update.to_string())).await.unwrap();
                 }
-                KrakyMessage::SubscriptionStatus {
-                    success,
-                    channel,
-                    symbol,
-                    error,
-                } => {
-                    if success {
-                        info!("Subscribed to {} for {:?}", channel, symbol);
-                    } else if let Some(err_str) = error {
-                        let parsed = crate::error::KrakenApiError::parse(&err_str);
-                        if parsed.is_retryable() {
-                            warn!(
-                                "Subscription failed for {} (retryable): [{}:{}] {}",
-                                channel, parsed.severity, parsed.category, parsed.message
-                            );
-                        } else if parsed.is_invalid_pair() {
-                            error!("Invalid trading pair for {}: {}", channel, parsed.message);
-                        } else if parsed.is_rate_limited() {
-                            warn!("Rate limited on {} subscription", channel);
-                        } else {
-                            warn!(
-                                "Subscription failed for {}: [{}:{}] {}",
-                                channel, parsed.severity, parsed.category, parsed.message
-                            );
-                        }
-                    } else {
-                        warn!("Subscription failed for {}: unknown error", channel);
+                request_tx.send(request).unwrap();
+            }
+        });
+
+        let client = KrakyClient::builder().url(&url).connect().await.unwrap();
+        assert!(client
+            .subscribe_raw("level4", serde_json::json!([]))
+            .await
+            .is_err());
+        let mut raw = client
+            .subscribe_raw(
+                "level4",
+                serde_json::json!({ "symbol": ["BTC/USD"], "snapshot": false }),
+            )
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), raw.ready())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let request = request_rx.recv().await.unwrap();
+        assert_eq!(request["params"]["channel"], "level4");
+        assert_eq!(request["params"]["symbol"][0], "BTC/USD");
+        let message = tokio::time::timeout(Duration::from_secs(5), raw.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message["data"][0]["symbol"], "BTC/USD");
+        assert!(raw.next_timeout(Duration::from_millis(100)).await.is_err());
+
+        let stored = client.primary().stored_subscriptions.read().clone();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].channel(), "level4");
+        assert_eq!(stored[0].pair(), "BTC/USD");
+        let infos = client.subscriptions();
+        assert_eq!(infos[0].channel, "level4");
+    }
+
+    #[cfg(feature = "trading")]
+    #[tokio::test]
+    async fn test_orders_held_back_during_maintenance() {
+        use crate::models::OrderParams;
+
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (status_tx, mut status_rx) = mpsc::unbounded_channel::<&'static str>();
+        let (method_tx, mut method_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            loop {
+                tokio::select! {
+                    Some(system) = status_rx.recv() => {
+                        let status = serde_json::json!({
+                            "channel": "status",
+                            "type": "update",
+                            "data": [{
+                                "api_version": "v2",
+                                "connection_id": 1,
+                                "system": system,
+                                "version": "2.0.4",
+                            }],
+                        });
+                        ws.send(Message::Text(status.to_string())).await.unwrap();
                     }
-                }
-                #[cfg(feature = "orderbook")]
-                KrakyMessage::Orderbook(update) => {
-                    for data in &update.data {
-                        let mut orderbooks = self.orderbooks.write();
-                        if let Some(orderbook) = orderbooks.get_mut(&data.symbol) {
-                            orderbook.apply_update(data);
+                    Some(Ok(Message::Text(text))) = ws.next() => {
+                        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        if let Some(method) = request["method"].as_str() {
+                            if method != "ping" {
+                                method_tx.send(method.to_string()).unwrap();
+                            }
                         }
                     }
-                    self.subscriptions.read().dispatch_orderbook(&update);
-                }
-                #[cfg(feature = "trades")]
-                KrakyMessage::Trade(update) => {
-                    self.subscriptions.read().dispatch_trade(&update);
-                }
-                #[cfg(feature = "ticker")]
-                KrakyMessage::Ticker(update) => {
-                    self.subscriptions.read().dispatch_ticker(&update);
+                    else => break,
                 }
-                #[cfg(feature = "ohlc")]
-                KrakyMessage::OHLC(update) => {
-                    self.subscriptions.read().dispatch_ohlc(&update);
-                }
-                KrakyMessage::Unknown(value) => {
-                    debug!("Unknown message: {}", value);
+            }
+        });
+
+        let client = KrakyClient::builder().url(&url).connect().await.unwrap();
+        let credentials = crate::auth::Credentials::new("key", "c2VjcmV0");
+        let mut states = client.watch_system_state();
+        /// Send a status update and wait until the client saw it
+        async fn announce(
+            status_tx: &mpsc::UnboundedSender<&'static str>,
+            states: &mut watch::Receiver<Option<SystemState>>,
+            system: &'static str,
+        ) {
+            status_tx.send(system).unwrap();
+            tokio::time::timeout(Duration::from_secs(5), states.changed())
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        announce(&status_tx, &mut states, "maintenance").await;
+        let order = OrderParams::limit_buy("BTC/USD", 0.1, 50000.0);
+        assert!(matches!(
+            client.place_order(&credentials, order.clone()).await,
+            Err(KrakyError::TradingSuspended(SystemState::Maintenance))
+        ));
+        assert!(client.cancel_order(&credentials, "O1").await.is_err());
+
+        announce(&status_tx, &mut states, "post_only").await;
+        assert!(client
+            .place_order(&credentials, order.clone())
+            .await
+            .is_err());
+        let post_only = order.clone().with_post_only(true);
+        client.place_order(&credentials, post_only).await.unwrap();
+
+        announce(&status_tx, &mut states, "online").await;
+        client.place_order(&credentials, order).await.unwrap();
+        for _ in 0..2 {
+            assert_eq!(method_rx.recv().await.unwrap(), "add_order");
+        }
+        assert!(method_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_and_unsubscribe_all() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (method_tx, mut method_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if request["method"] == "unsubscribe" {
+                    let symbol = request["params"]["symbol"][0].as_str().unwrap();
+                    method_tx.send(symbol.to_string()).unwrap();
                 }
-            },
-            Err(e) => {
-                warn!("Failed to parse message: {} - {}", e, text);
             }
+        });
+
+        let client = KrakyClient::builder().url(&url).connect().await.unwrap();
+        let btc = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+        let eth = client
+            .subscribe_orderbook_conflated("ETH/USD", 25)
+            .await
+            .unwrap();
+        let _diagnostics = client.subscribe_diagnostics();
+        eth.pause_upstream().unwrap();
+
+        let infos = client.subscriptions();
+        assert_eq!(infos.len(), 3);
+        assert_eq!(infos[0].id, btc.id());
+        assert_eq!((infos[0].depth, infos[0].conflated), (Some(10), false));
+        assert_eq!(infos[0].buffer_size, crate::DEFAULT_BUFFER_SIZE);
+        assert_eq!((infos[1].depth, infos[1].conflated), (Some(25), true));
+        assert!(infos[1].paused);
+        assert_eq!(infos[2].channel, "diagnostics");
+        assert!(infos[0].created_at <= infos[2].created_at);
+
+        // Dropped subscriptions are no longer listed
+        drop(btc);
+        assert_eq!(client.subscriptions().len(), 2);
+
+        client.unsubscribe_all().unwrap();
+        let infos = client.subscriptions();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].channel, "diagnostics");
+        assert!(client.get_orderbook("ETH/USD").is_none());
+
+        // ETH/USD was already unsubscribed at the server by the pause
+        let mut unsubscribed = Vec::new();
+        for _ in 0..2 {
+            let symbol = tokio::time::timeout(Duration::from_secs(5), method_rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            unsubscribed.push(symbol);
         }
+        assert_eq!(unsubscribed, vec!["ETH/USD", "BTC/USD"]);
     }
-}
 
-/// Reason for WebSocket disconnection
-#[derive(Debug)]
-enum DisconnectReason {
-    Shutdown,
-    ServerClose,
-    Error(String),
-    StreamEnded,
-    ManualReconnect,
-}
+    #[cfg(feature = "ticker")]
+    #[tokio::test]
+    async fn test_watch_ticker_and_last_price() {
+        fn ticker(last: f64) -> String {
+            serde_json::json!({
+                "channel": "ticker",
+                "type": "update",
+                "data": [{
+                    "symbol": "BTC/USD", "bid": last - 1.0, "bid_qty": 1.0,
+                    "ask": last + 1.0, "ask_qty": 1.0, "last": last,
+                    "volume": 10.0, "vwap": last, "low": last, "high": last,
+                    "change": 0.0, "change_pct": 0.0,
+                }],
+            })
+            .to_string()
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (next_tx, mut next_rx) = mpsc::unbounded_channel::<f64>();
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            loop {
+                tokio::select! {
+                    Some(Ok(Message::Text(text))) = ws.next() => {
+                        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        if request["method"] == "subscribe" {
+                            let ack = serde_json::json!({
+                                "method": "subscribe",
+                                "req_id": request["req_id"],
+                                "success": true,
+                            });
+                            ws.send(Message::Text(ack.to_string())).await.unwrap();
+                            ws.send(Message::Text(ticker(42000.0))).await.unwrap();
+                        }
+                    }
+                    Some(last) = next_rx.recv() => {
+                        ws.send(Message::Text(ticker(last))).await.unwrap();
+                    }
+                }
+            }
+        });
 
-    #[test]
-    fn test_reconnect_config_default() {
-        let config = ReconnectConfig::default();
-        assert!(config.enabled);
-        assert_eq!(config.initial_delay, Duration::from_millis(500));
-        assert_eq!(config.max_delay, Duration::from_secs(30));
-        assert_eq!(config.backoff_multiplier, 2.0);
-        assert_eq!(config.max_attempts, None);
+        let client = KrakyClient::builder().url(&url).connect().await.unwrap();
+        assert_eq!(client.last_price("BTC/USD"), None);
+
+        let timeout = Duration::from_secs(5);
+        let mut btc = tokio::time::timeout(timeout, client.watch_ticker("BTC/USD"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(btc.borrow().last, 42000.0);
+        assert_eq!(client.last_price("BTC/USD"), Some(42000.0));
+
+        next_tx.send(42100.0).unwrap();
+        tokio::time::timeout(timeout, btc.changed())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(btc.borrow().last, 42100.0);
+        assert_eq!(client.last_price("BTC/USD"), Some(42100.0));
+
+        // A second watcher shares the existing subscription
+        let again = client.watch_ticker("BTC/USD").await.unwrap();
+        assert_eq!(again.borrow().last, 42100.0);
+        assert_eq!(client.stored_subscriptions().len(), 1);
     }
 
-    #[test]
-    fn test_reconnect_config_disabled() {
-        let config = ReconnectConfig::disabled();
-        assert!(!config.enabled);
+    #[cfg(feature = "orderbook")]
+    #[tokio::test]
+    async fn test_shutdown_closes_socket_and_subscriptions() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (closed_tx, closed_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (stream, _) = server.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(msg)) = ws.next().await {
+                if msg.is_close() {
+                    let _ = closed_tx.send(());
+                    break;
+                }
+            }
+        });
+
+        let client = KrakyClient::builder()
+            .url(&url)
+            .pipeline(PipelineConfig::default())
+            .connect()
+            .await
+            .unwrap();
+        let mut updates = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), client.shutdown())
+            .await
+            .unwrap();
+
+        assert!(closed_rx.await.is_ok());
+        assert!(updates.next().await.is_none());
+        assert_eq!(client.connection_state(), ConnectionState::Disconnected);
     }
 
-    #[test]
-    fn test_reconnect_config_aggressive() {
-        let config = ReconnectConfig::aggressive();
-        assert!(config.enabled);
-        assert_eq!(config.initial_delay, Duration::from_millis(100));
-        assert_eq!(config.max_delay, Duration::from_secs(5));
-        assert_eq!(config.backoff_multiplier, 1.5);
+    #[cfg(feature = "orderbook")]
+    #[tokio::test]
+    async fn test_connection_pool_routes_by_pair() {
+        let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for index in 0.. {
+                let (stream, _) = server.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let seen_tx = seen_tx.clone();
+                tokio::spawn(async move {
+                    while let Some(Ok(Message::Text(text))) = ws.next().await {
+                        let _ = seen_tx.send((index, text));
+                    }
+                });
+            }
+        });
+
+        let client = KrakyClient::builder()
+            .url(&url)
+            .connections(3)
+            .connect()
+            .await
+            .unwrap();
+        assert_eq!(client.connection_count(), 3);
+        assert_eq!(client.pool_health().len(), 3);
+        assert!(client.is_connected());
+
+        let _book = client.subscribe_orderbook("ETH/USD", 10).await.unwrap();
+        // Skip the initial pings of each connection
+        let index = loop {
+            let (index, text) = seen_rx.recv().await.unwrap();
+            if text.contains("subscribe") {
+                assert!(text.contains("ETH/USD"));
+                break index;
+            }
+        };
+        assert_eq!(index, bucket("ETH/USD", 3));
     }
 
     #[test]
-    fn test_reconnect_config_conservative() {
-        let config = ReconnectConfig::conservative();
-        assert!(config.enabled);
-        assert_eq!(config.initial_delay, Duration::from_secs(1));
-        assert_eq!(config.max_delay, Duration::from_secs(60));
-        assert_eq!(config.max_attempts, Some(10));
+    fn test_ip_preference() {
+        let v4: SocketAddr = "1.2.3.4:443".parse().unwrap();
+        let v6: SocketAddr = "[::1]:443".parse().unwrap();
+
+        let mut addrs = vec![v6, v4];
+        IpPreference::PreferIpv4.apply(&mut addrs);
+        assert_eq!(addrs, vec![v4, v6]);
+
+        IpPreference::PreferIpv6.apply(&mut addrs);
+        assert_eq!(addrs, vec![v6, v4]);
+
+        IpPreference::Ipv4Only.apply(&mut addrs);
+        assert_eq!(addrs, vec![v4]);
     }
 
     #[test]
     fn test_exponential_backoff() {
-        let config = ReconnectConfig::default();
+        let config = ReconnectConfig::default().with_jitter(0.0);
 
         // First attempt: 500ms
-        assert_eq!(config.delay_for_attempt(0), Duration::from_millis(500));
+        assert_eq!(
+            config.delay_for(0, DisconnectKind::ServerClose),
+            Duration::from_millis(500)
+        );
 
         // Second attempt: 1000ms
-        assert_eq!(config.delay_for_attempt(1), Duration::from_millis(1000));
+        assert_eq!(
+            config.delay_for(1, DisconnectKind::ServerClose),
+            Duration::from_millis(1000)
+        );
 
         // Third attempt: 2000ms
-        assert_eq!(config.delay_for_attempt(2), Duration::from_millis(2000));
+        assert_eq!(
+            config.delay_for(2, DisconnectKind::ServerClose),
+            Duration::from_millis(2000)
+        );
 
         // Should cap at max_delay
-        assert_eq!(config.delay_for_attempt(10), Duration::from_secs(30));
+        assert_eq!(
+            config.delay_for(10, DisconnectKind::ServerClose),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn test_backoff_per_disconnect_kind() {
+        let config = ReconnectConfig::default().with_jitter(0.0);
+
+        assert_eq!(
+            config.delay_for(0, DisconnectKind::RateLimited),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            config.delay_for(0, DisconnectKind::ConnectionReset),
+            Duration::from_millis(100)
+        );
+        assert_eq!(
+            config.delay_for(0, DisconnectKind::Maintenance),
+            Duration::from_secs(5)
+        );
+
+        assert_eq!(
+            DisconnectKind::from_error("HTTP error: 429 Too Many Requests"),
+            DisconnectKind::RateLimited
+        );
+        assert_eq!(
+            DisconnectKind::from_error("IO error: Connection reset by peer (os error 104)"),
+            DisconnectKind::ConnectionReset
+        );
+        assert_eq!(
+            DisconnectKind::from_error("TLS error"),
+            DisconnectKind::Error
+        );
+    }
+
+    #[test]
+    fn test_reconnect_jitter() {
+        let config = ReconnectConfig::default().with_jitter(0.5);
+        for _ in 0..100 {
+            let delay = config.delay_for(1, DisconnectKind::ServerClose);
+            assert!(delay >= Duration::from_millis(500));
+            assert!(delay <= Duration::from_millis(1000));
+        }
     }
 
     #[test]
@@ -1332,4 +5699,319 @@ mod tests {
         assert_eq!(ConnectionState::from(3), ConnectionState::Reconnecting);
         assert_eq!(ConnectionState::from(255), ConnectionState::Disconnected); // Invalid -> Disconnected
     }
+
+    #[test]
+    fn test_pipeline_shard() {
+        let btc =
+            r#"{"channel":"trade","type":"update","data":[{"symbol":"BTC/USD","price":1.0}]}"#;
+        let btc_book =
+            r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":[]}]}"#;
+
+        assert_eq!(shard(btc, 8), shard(btc_book, 8));
+        assert_eq!(shard(r#"{"channel":"heartbeat"}"#, 8), 0);
+        assert_eq!(shard(btc, 1), 0);
+        assert_eq!(raw_field(btc_book, "\"channel\":\""), Some("book"));
+        assert_eq!(raw_field(btc, "\"method\":\""), None);
+    }
+
+    #[cfg(all(feature = "latency", feature = "trades"))]
+    #[test]
+    fn test_handler_records_latency_per_channel() {
+        let handler = test_handler(None);
+        let sent = chrono::Utc::now() - chrono::Duration::milliseconds(50);
+        handler.handle_message(&format!(
+            r#"{{"channel":"trade","type":"update","data":[{{"symbol":"BTC/USD","side":"buy","price":1.0,"qty":1.0,"ord_type":"market","trade_id":1,"timestamp":"{}"}}]}}"#,
+            sent.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+        ));
+
+        let stats = handler.latency.snapshot();
+        let trade = &stats["trade"];
+        assert_eq!((trade.receive.count(), trade.dispatch.count()), (1, 1));
+        assert!(trade.receive.min() >= Duration::from_millis(50));
+        assert!(trade.dispatch.min() >= trade.receive.min());
+    }
+
+    #[cfg(feature = "events")]
+    #[test]
+    fn test_system_status_transitions() {
+        let handler = test_handler(None);
+        let (tx, mut events) = mpsc::channel(8);
+        *handler.event_tx.write() = Some(tx);
+        let mut states = handler.system_state.subscribe();
+        let status = |system: &str| {
+            format!(
+                r#"{{"channel":"status","type":"update","data":[{{"api_version":"v2","connection_id":1,"system":"{}","version":"2.0.4"}}]}}"#,
+                system
+            )
+        };
+
+        handler.handle_message(&status("online"));
+        // Repeated by every connection, but only reported once
+        handler.handle_message(&status("online"));
+        handler.handle_message(&status("cancel_only"));
+        handler.handle_message(&status("bogus"));
+
+        assert_eq!(*states.borrow_and_update(), Some(SystemState::CancelOnly));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            ConnectionEvent::SystemStateChanged(SystemState::Online)
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            ConnectionEvent::SystemStateChanged(SystemState::CancelOnly)
+        );
+        assert!(events.try_recv().is_err());
+        assert!(!SystemState::CancelOnly.accepts_orders());
+        assert!(SystemState::CancelOnly.accepts_cancels());
+        assert_eq!(SystemState::parse("post_only"), Some(SystemState::PostOnly));
+    }
+
+    #[cfg(all(feature = "checksum", feature = "testing"))]
+    #[test]
+    fn test_checksum_failure_emits_event_once() {
+        let handler = test_handler(None);
+        let (tx, mut events) = mpsc::channel(8);
+        *handler.event_tx.write() = Some(tx);
+        handler.orderbooks.insert_if_absent("BTC/USD");
+
+        let fixture = crate::testing::fixtures::get("book_snapshot").unwrap();
+        let checksum = format!("\"checksum\":{}", fixture.json()["data"][0]["checksum"]);
+        let (snapshot, corrupted) = (
+            fixture.text,
+            fixture.text.replace(&checksum, "\"checksum\":1"),
+        );
+        assert_ne!(snapshot, corrupted);
+        // Not validated until the pair's precision is known, which is reported once
+        handler.handle_message(&corrupted);
+        handler.handle_message(&corrupted);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            ConnectionEvent::OrderbookUnverified {
+                symbol: "BTC/USD".to_string(),
+            }
+        );
+        assert!(events.try_recv().is_err());
+        let stats = handler.orderbooks.checksum_stats.lock()["BTC/USD"];
+        assert_eq!((stats.validations, stats.unverified), (0, 2));
+
+        handler.handle_message(
+            r#"{"channel":"instrument","type":"snapshot","data":{"assets":[],"pairs":[{"symbol":"BTC/USD","price_precision":1,"qty_precision":8},{"symbol":"XRP/EUR","price_precision":5,"qty_precision":8}]}}"#,
+        );
+        assert_eq!(
+            handler.orderbooks.snapshot("BTC/USD").unwrap().precision,
+            Some(crate::models::BookPrecision::new(1, 8))
+        );
+        assert!(!handler.orderbooks.lacks_precision("XRP/EUR"));
+        handler.handle_message(&corrupted);
+        handler.handle_message(&corrupted);
+
+        match events.try_recv().unwrap() {
+            ConnectionEvent::OrderbookCorrupted {
+                symbol,
+                expected,
+                calculated,
+            } => {
+                assert_eq!((symbol.as_str(), expected), ("BTC/USD", 1));
+                assert_ne!(calculated, 1);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        // Still corrupted, so not reported again
+        assert!(events.try_recv().is_err());
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_instrument_subscription_is_opt_in() {
+        let books = OrderbookMap::default();
+        assert!(!books.request_instruments("BTC/USD"));
+
+        let books = OrderbookMap {
+            fetch_precision: true,
+            ..OrderbookMap::default()
+        };
+        assert!(books.request_instruments("BTC/USD"));
+        assert!(!books.request_instruments("ETH/USD"));
+        books.set_precision("XRP/EUR", crate::models::BookPrecision::new(5, 8));
+        assert!(!books.lacks_precision("XRP/EUR"));
+    }
+
+    #[cfg(all(feature = "checksum", feature = "testing"))]
+    #[test]
+    fn test_checksum_stats_and_failure_threshold() {
+        let mut handler = test_handler(None);
+        handler.checksum_failure_threshold = Some(2);
+        let (tx, mut events) = mpsc::channel(8);
+        *handler.event_tx.write() = Some(tx);
+        handler.orderbooks.insert_if_absent("BTC/USD");
+        handler
+            .orderbooks
+            .set_precision("BTC/USD", crate::models::BookPrecision::new(1, 8));
+
+        let fixture = crate::testing::fixtures::get("book_snapshot").unwrap();
+        let checksum = format!("\"checksum\":{}", fixture.json()["data"][0]["checksum"]);
+        let (snapshot, corrupted) = (
+            fixture.text,
+            fixture.text.replace(&checksum, "\"checksum\":1"),
+        );
+        handler.handle_message(snapshot);
+        for _ in 0..3 {
+            handler.handle_message(&corrupted);
+        }
+
+        let stats = handler.orderbooks.checksum_stats.lock()["BTC/USD"];
+        assert_eq!((stats.validations, stats.failures), (4, 3));
+        assert_eq!(stats.consecutive_failures, 3);
+
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            ConnectionEvent::OrderbookCorrupted { .. }
+        ));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            ConnectionEvent::ChecksumFailures {
+                symbol: "BTC/USD".to_string(),
+                consecutive: 2,
+            }
+        );
+        // Reported once per run
+        assert!(events.try_recv().is_err());
+    }
+
+    #[cfg(feature = "trades")]
+    #[tokio::test]
+    async fn test_pipeline_preserves_order_per_symbol() {
+        let handler = test_handler(None);
+        let (sender, mut trades) = SubscriptionSender::new("trade".to_string(), "*".to_string());
+        handler.subscriptions.write().trades.push(sender);
+
+        let pipeline = Pipeline::spawn(&PipelineConfig::with_workers(4), &handler);
+        for id in 0..100 {
+            let symbol = if id % 2 == 0 { "BTC/USD" } else { "ETH/USD" };
+            pipeline
+                .dispatch(format!(
+                    r#"{{"channel":"trade","type":"update","data":[{{"symbol":"{}","side":"buy","price":1.0,"qty":1.0,"ord_type":"market","trade_id":{},"timestamp":"2024-01-15T10:00:00.000000Z"}}]}}"#,
+                    symbol, id
+                ))
+                .await;
+        }
+
+        let mut last: HashMap<String, i64> = HashMap::new();
+        for _ in 0..100 {
+            let trade = trades.next().await.unwrap();
+            let previous = last.insert(trade.symbol.clone(), trade.trade_id);
+            assert!(previous.map_or(true, |previous| previous < trade.trade_id));
+        }
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[tokio::test]
+    async fn test_strict_mode_reports_and_fails_subscriptions() {
+        let handler = test_handler(Some(StrictConfig::failing()));
+        let (sender, mut diagnostics) =
+            SubscriptionSender::new("diagnostics".to_string(), "*".to_string());
+        let (btc, mut btc_book) = SubscriptionSender::<Arc<crate::models::OrderbookUpdate>>::new(
+            "book".to_string(),
+            "BTC/USD".to_string(),
+        );
+        let (eth, eth_book) = SubscriptionSender::new("book".to_string(), "ETH/USD".to_string());
+        {
+            let mut subscriptions = handler.subscriptions.write();
+            subscriptions.diagnostics.push(sender);
+            subscriptions.orderbook.push(btc);
+            subscriptions.orderbook.push(eth);
+        }
+
+        handler.handle_message(r#"{"channel":"instrument","type":"snapshot","data":{}}"#);
+        let unknown = diagnostics.next().await.unwrap();
+        assert_eq!(unknown.channel.as_deref(), Some("instrument"));
+        assert_eq!(unknown.error, "unrecognized message");
+
+        let malformed =
+            r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":"oops"}]}"#;
+        handler.handle_message(malformed);
+        let diagnostic = diagnostics.next().await.unwrap();
+        assert_eq!(diagnostic.raw, malformed);
+        assert_eq!(diagnostic.symbols, ["BTC/USD"]);
+
+        // Only the BTC/USD book subscription ends, with the parse error
+        assert!(btc_book.next().await.is_none());
+        assert!(matches!(
+            btc_book.error(),
+            Some(KrakyError::InvalidMessage(_))
+        ));
+        assert!(eth_book.error().is_none());
+        assert_eq!(handler.subscriptions.read().orderbook.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ack_with_odd_req_id_is_dispatched() {
+        let handler = test_handler(Some(StrictConfig::failing()));
+        let (sender, mut diagnostics) =
+            SubscriptionSender::new("diagnostics".to_string(), "*".to_string());
+        handler.subscriptions.write().diagnostics.push(sender);
+        let (req_id, ack) = handler.acks.register();
+
+        // Handled as an ack without an id, not rejected as unparseable
+        handler.handle_message(
+            r#"{"method":"subscribe","req_id":"abc","result":{"channel":"book","symbol":"BTC/USD"},"success":true}"#,
+        );
+        handler.handle_message(&format!(
+            r#"{{"method":"subscribe","req_id":{},"result":{{"channel":"book","symbol":"BTC/USD"}},"success":true}}"#,
+            req_id
+        ));
+        assert_eq!(*ack.borrow(), Some(Ok(())));
+
+        handler.handle_message(r#"{"channel":"bogus"}"#);
+        let diagnostic = diagnostics.next().await.unwrap();
+        assert_eq!(diagnostic.channel.as_deref(), Some("bogus"));
+    }
+
+    #[tokio::test]
+    async fn test_responses_routed_by_typed_req_id() {
+        let handler = test_handler(None);
+        let (pong_id, pong) = handler.router.register();
+        let (custom_id, custom) = handler.router.register();
+
+        // Another request's pong is left to the client
+        handler.handle_message(&format!(
+            r#"{{"method":"pong","req_id":{}}}"#,
+            custom_id + 1
+        ));
+        assert_eq!(handler.router.len(), 2);
+
+        handler.handle_message(&format!(
+            r#"{{"method":"pong","req_id":{},"time_in":"2024-01-15T10:00:00.799685Z"}}"#,
+            pong_id
+        ));
+        let pong = pong.await.unwrap();
+        assert_eq!(
+            (pong["method"].as_str(), pong["req_id"].as_u64()),
+            (Some("pong"), Some(pong_id))
+        );
+
+        handler.handle_message(&format!(
+            r#"{{"method":"edit_order","req_id":{},"success":true}}"#,
+            custom_id
+        ));
+        assert_eq!(custom.await.unwrap()["method"], "edit_order");
+        assert!(handler.router.is_empty());
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_orderbook_map_per_pair_locks() {
+        let books = OrderbookMap::default();
+        assert!(books.insert_if_absent("BTC/USD"));
+        assert!(!books.insert_if_absent("BTC/USD"));
+        books.reset("ETH/USD");
+
+        let btc = books.get("BTC/USD").unwrap();
+        let _writing = btc.write();
+        // Another pair stays readable while BTC/USD is being updated
+        let eth = books.get("ETH/USD").unwrap();
+        assert!(eth.try_read().is_some());
+        assert_eq!(books.snapshot("ETH/USD").unwrap().symbol, "ETH/USD");
+        assert!(books.snapshot("SOL/USD").is_none());
+    }
 }
@@ -0,0 +1,148 @@
+//! Discord notification integration for Kraky SDK
+//!
+//! Sends market alerts to a Discord channel through an incoming webhook.
+//! [`DiscordNotifier`] implements the [`Notifier`] trait, so it supports the
+//! same alerts as the Telegram integration (price, imbalance, whale, spread,
+//! account and order events).
+//!
+//! ## Quick Start
+//!
+//! Create a webhook in *Server Settings → Integrations → Webhooks* and copy its URL.
+//!
+//! ```no_run
+//! use kraky::{DiscordNotifier, Notifier};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let discord = DiscordNotifier::new("https://discord.com/api/webhooks/ID/TOKEN")
+//!         .with_username("Kraky Alerts");
+//!
+//!     discord.send_price_alert("BTC/USD", 100_000.0, "Target reached!").await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::error::{KrakyError, Result};
+use crate::notifier::{truncate, Notifier};
+use crate::templates::AlertTemplates;
+use async_trait::async_trait;
+
+/// Maximum length of a Discord message (`content` field)
+const DISCORD_MAX_CONTENT: usize = 2000;
+
+/// Discord webhook notifier for real-time market alerts
+///
+/// See [`Notifier`] for the available alerts.
+pub struct DiscordNotifier {
+    client: reqwest::Client,
+    webhook_url: String,
+    username: Option<String>,
+    avatar_url: Option<String>,
+    templates: Option<AlertTemplates>,
+}
+
+impl DiscordNotifier {
+    /// Create a new Discord notifier
+    ///
+    /// # Arguments
+    /// * `webhook_url` - Discord webhook URL (`https://discord.com/api/webhooks/...`)
+    pub fn new(webhook_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhook_url: webhook_url.to_string(),
+            username: None,
+            avatar_url: None,
+            templates: None,
+        }
+    }
+
+    /// Replace built-in alert texts with templates
+    ///
+    /// See [`templates`](crate::templates) for alert types and variables.
+    pub fn with_templates(mut self, templates: AlertTemplates) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Override the webhook's display name
+    pub fn with_username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    /// Override the webhook's avatar
+    pub fn with_avatar_url(mut self, avatar_url: &str) -> Self {
+        self.avatar_url = Some(avatar_url.to_string());
+        self
+    }
+
+    /// Build the webhook payload for a message
+    fn payload(&self, message: &str) -> serde_json::Value {
+        let mut payload = serde_json::json!({
+            "content": truncate(message, DISCORD_MAX_CONTENT),
+        });
+        if let Some(username) = &self.username {
+            payload["username"] = serde_json::json!(username);
+        }
+        if let Some(avatar_url) = &self.avatar_url {
+            payload["avatar_url"] = serde_json::json!(avatar_url);
+        }
+        payload
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    fn templates(&self) -> Option<&AlertTemplates> {
+        self.templates.as_ref()
+    }
+
+    async fn send_alert(&self, message: &str) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.webhook_url)
+            .json(&self.payload(message))
+            .send()
+            .await
+            .map_err(|e| KrakyError::InvalidMessage(format!("Discord error: {}", e)))?;
+
+        let status = response.status();
+        if status.as_u16() == 429 {
+            return Err(KrakyError::RateLimited);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(KrakyError::InvalidMessage(format!(
+                "Discord error: {} {}",
+                status, body
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload() {
+        let notifier =
+            DiscordNotifier::new("https://discord.com/api/webhooks/1/abc").with_username("Kraky");
+        let payload = notifier.payload("hello");
+
+        assert_eq!(payload["content"], "hello");
+        assert_eq!(payload["username"], "Kraky");
+        assert!(payload.get("avatar_url").is_none());
+    }
+
+    #[test]
+    fn test_long_messages_truncated() {
+        let notifier = DiscordNotifier::new("https://discord.com/api/webhooks/1/abc");
+        let payload = notifier.payload(&"🐋".repeat(3000));
+
+        let content = payload["content"].as_str().unwrap();
+        assert_eq!(content.chars().count(), DISCORD_MAX_CONTENT);
+        assert!(content.ends_with('…'));
+    }
+}
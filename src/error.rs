@@ -2,6 +2,7 @@
 //!
 //! Provides structured error handling with Kraken-specific error parsing.
 
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use thiserror::Error;
 
@@ -9,7 +10,8 @@ use thiserror::Error;
 pub type Result<T> = std::result::Result<T, KrakyError>;
 
 /// Kraken API error severity level
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum KrakenSeverity {
     /// Error - operation failed
     Error,
@@ -30,7 +32,8 @@ impl fmt::Display for KrakenSeverity {
 }
 
 /// Kraken API error category
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum KrakenCategory {
     /// Query errors (invalid parameters, unknown pairs)
     Query,
@@ -74,7 +77,7 @@ impl fmt::Display for KrakenCategory {
 /// For example: `"EQuery:Unknown asset pair"` or `"EService:Unavailable"`
 ///
 /// This struct parses that format into structured fields for easier handling.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct KrakenApiError {
     /// Error severity (E = Error, W = Warning)
     pub severity: KrakenSeverity,
@@ -176,8 +179,11 @@ impl std::error::Error for KrakenApiError {}
 #[derive(Error, Debug)]
 pub enum KrakyError {
     /// WebSocket connection error
+    ///
+    /// The tungstenite error is boxed so it doesn't inflate every `Result`;
+    /// match on `Connection(err)` and use `*err` to get at the inner error.
     #[error("WebSocket connection error: {0}")]
-    Connection(#[from] tokio_tungstenite::tungstenite::Error),
+    Connection(Box<tokio_tungstenite::tungstenite::Error>),
 
     /// JSON serialization/deserialization error
     #[error("JSON error: {0}")]
@@ -187,6 +193,14 @@ pub enum KrakyError {
     #[error("Invalid URL: {0}")]
     Url(#[from] url::ParseError),
 
+    /// File or stream I/O error
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Database or message sink error
+    #[error("Storage error: {0}")]
+    Storage(String),
+
     /// Channel send error
     #[error("Channel send error: {0}")]
     ChannelSend(String),
@@ -203,6 +217,27 @@ pub enum KrakyError {
     #[error("Invalid message: {0}")]
     InvalidMessage(String),
 
+    /// A message failed to deserialize into its typed model
+    ///
+    /// Built from a [`ParseDiagnostic`](crate::messages::ParseDiagnostic)
+    /// with whatever context could be read off the raw frame.
+    #[error(
+        "Failed to parse {}: {message}",
+        describe_frame(.channel, .symbol, .offset, .path)
+    )]
+    Parse {
+        /// Channel (or method) named by the frame
+        channel: Option<String>,
+        /// First symbol named in the frame's data
+        symbol: Option<String>,
+        /// Byte offset in the frame where deserialization failed
+        offset: Option<usize>,
+        /// Path of the value that failed, e.g. `data[0].price`
+        path: Option<String>,
+        /// Deserializer error
+        message: String,
+    },
+
     /// Connection closed unexpectedly
     #[error("Connection closed")]
     ConnectionClosed,
@@ -219,11 +254,52 @@ pub enum KrakyError {
     #[error("Invalid trading pair: {0}")]
     InvalidPair(String),
 
+    /// Order parameters rejected before sending
+    #[error("Invalid order: {0}")]
+    InvalidOrder(String),
+
+    /// Order request held back while Kraken doesn't accept it
+    ///
+    /// Retry once [`KrakyClient::system_state`] is back to online.
+    ///
+    /// [`KrakyClient::system_state`]: crate::KrakyClient::system_state
+    #[error("Trading suspended while Kraken is {0}")]
+    TradingSuspended(crate::client::SystemState),
+
     /// Generic API error
     #[error("API error: {0}")]
     Api(String),
 }
 
+impl From<tokio_tungstenite::tungstenite::Error> for KrakyError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::Connection(Box::new(error))
+    }
+}
+
+/// Describe where a frame failed to parse, e.g. `book message for BTC/USD at byte 57 (data[0].bids)`
+fn describe_frame(
+    channel: &Option<String>,
+    symbol: &Option<String>,
+    offset: &Option<usize>,
+    path: &Option<String>,
+) -> String {
+    let mut description = match channel {
+        Some(channel) => format!("{} message", channel),
+        None => "message".to_string(),
+    };
+    if let Some(symbol) = symbol {
+        description.push_str(&format!(" for {}", symbol));
+    }
+    if let Some(offset) = offset {
+        description.push_str(&format!(" at byte {}", offset));
+    }
+    if let Some(path) = path {
+        description.push_str(&format!(" ({})", path));
+    }
+    description
+}
+
 impl KrakyError {
     /// Create a KrakyError from a Kraken API error string
     ///
@@ -247,6 +323,7 @@ impl KrakyError {
             KrakyError::KrakenApi(e) => e.is_retryable(),
             KrakyError::Connection(_) => true,
             KrakyError::ConnectionClosed => true,
+            KrakyError::TradingSuspended(_) => true,
             _ => false,
         }
     }
@@ -0,0 +1,255 @@
+//! Exchange-neutral traits for strategy code
+//!
+//! [`MarketDataSource`] covers market data subscriptions and
+//! [`OrderGateway`] order management. [`KrakyClient`] implements both, so
+//! a bot written against the traits runs on Kraken today and on any other
+//! exchange adapter implementing them later.
+//!
+//! Streams are boxed [`Stream`](futures_util::Stream)s of the SDK's models,
+//! so adapters can produce them from whatever their connection yields.
+//!
+//! Requires the `exchange` feature flag.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures_util::StreamExt;
+//! use kraky::{KrakyClient, MarketDataSource};
+//!
+//! /// Print trades from any exchange
+//! async fn print_trades(source: &dyn MarketDataSource, symbol: &str) -> kraky::Result<()> {
+//!     let mut trades = source.trades(symbol).await?;
+//!     while let Some(trade) = trades.next().await {
+//!         println!("{} {} {}", source.exchange(), trade.price, trade.qty);
+//!     }
+//!     Ok(())
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! print_trades(&client, "BTC/USD").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::KrakyClient;
+use crate::error::Result;
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use std::sync::Arc;
+
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+#[cfg(feature = "trades")]
+use crate::models::Trade;
+#[cfg(feature = "trading")]
+use crate::models::{
+    AmendOrderParams, AmendOrderResponse, CancelAllResponse, CancelOrderResponse, OrderParams,
+    OrderResponse,
+};
+#[cfg(feature = "orderbook")]
+use crate::models::{Orderbook, OrderbookUpdate};
+
+/// Stream of market data from a [`MarketDataSource`]
+pub type MarketStream<T> = BoxStream<'static, Arc<T>>;
+
+/// Source of market data for one exchange
+///
+/// Each data method is only available when its feature is enabled.
+#[async_trait]
+pub trait MarketDataSource: Send + Sync {
+    /// Name of the exchange, e.g. `"kraken"`
+    fn exchange(&self) -> &str;
+
+    /// Whether the market data connection is up
+    fn is_connected(&self) -> bool;
+
+    /// Stream orderbook updates for `symbol` with `depth` levels
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    async fn orderbook_updates(
+        &self,
+        symbol: &str,
+        depth: u32,
+    ) -> Result<MarketStream<OrderbookUpdate>>;
+
+    /// Get the current orderbook for `symbol`, if subscribed
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    fn orderbook(&self, symbol: &str) -> Option<Orderbook>;
+
+    /// Stream trades for `symbol`
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    async fn trades(&self, symbol: &str) -> Result<MarketStream<Trade>>;
+
+    /// Stream tickers for `symbol`
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    async fn tickers(&self, symbol: &str) -> Result<MarketStream<Ticker>>;
+
+    /// Get the last trade price of `symbol`, if known
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    fn last_price(&self, symbol: &str) -> Option<f64>;
+}
+
+/// Order management for one exchange
+///
+/// Credentials are passed per call, so one gateway can trade several
+/// accounts; their type is up to the exchange.
+///
+/// Only available when the `trading` feature is enabled.
+#[cfg(feature = "trading")]
+#[async_trait]
+pub trait OrderGateway: Send + Sync {
+    /// API credentials of an account
+    type Credentials: Send + Sync;
+
+    /// Name of the exchange, e.g. `"kraken"`
+    fn exchange(&self) -> &str;
+
+    /// Place an order
+    async fn place_order(
+        &self,
+        credentials: &Self::Credentials,
+        params: OrderParams,
+    ) -> Result<OrderResponse>;
+
+    /// Cancel an order by ID
+    async fn cancel_order(
+        &self,
+        credentials: &Self::Credentials,
+        order_id: &str,
+    ) -> Result<CancelOrderResponse>;
+
+    /// Cancel all open orders
+    async fn cancel_all_orders(&self, credentials: &Self::Credentials)
+        -> Result<CancelAllResponse>;
+
+    /// Amend an open order
+    async fn amend_order(
+        &self,
+        credentials: &Self::Credentials,
+        params: AmendOrderParams,
+    ) -> Result<AmendOrderResponse>;
+}
+
+#[async_trait]
+impl MarketDataSource for KrakyClient {
+    fn exchange(&self) -> &str {
+        "kraken"
+    }
+
+    fn is_connected(&self) -> bool {
+        KrakyClient::is_connected(self)
+    }
+
+    #[cfg(feature = "orderbook")]
+    async fn orderbook_updates(
+        &self,
+        symbol: &str,
+        depth: u32,
+    ) -> Result<MarketStream<OrderbookUpdate>> {
+        Ok(Box::pin(self.subscribe_orderbook(symbol, depth).await?))
+    }
+
+    #[cfg(feature = "orderbook")]
+    fn orderbook(&self, symbol: &str) -> Option<Orderbook> {
+        self.get_orderbook(symbol)
+    }
+
+    #[cfg(feature = "trades")]
+    async fn trades(&self, symbol: &str) -> Result<MarketStream<Trade>> {
+        Ok(Box::pin(self.subscribe_trades(symbol).await?))
+    }
+
+    #[cfg(feature = "ticker")]
+    async fn tickers(&self, symbol: &str) -> Result<MarketStream<Ticker>> {
+        Ok(Box::pin(self.subscribe_ticker(symbol).await?))
+    }
+
+    #[cfg(feature = "ticker")]
+    fn last_price(&self, symbol: &str) -> Option<f64> {
+        KrakyClient::last_price(self, symbol)
+    }
+}
+
+#[cfg(feature = "trading")]
+#[async_trait]
+impl OrderGateway for KrakyClient {
+    type Credentials = crate::auth::Credentials;
+
+    fn exchange(&self) -> &str {
+        "kraken"
+    }
+
+    async fn place_order(
+        &self,
+        credentials: &Self::Credentials,
+        params: OrderParams,
+    ) -> Result<OrderResponse> {
+        KrakyClient::place_order(self, credentials, params).await
+    }
+
+    async fn cancel_order(
+        &self,
+        credentials: &Self::Credentials,
+        order_id: &str,
+    ) -> Result<CancelOrderResponse> {
+        KrakyClient::cancel_order(self, credentials, order_id).await
+    }
+
+    async fn cancel_all_orders(
+        &self,
+        credentials: &Self::Credentials,
+    ) -> Result<CancelAllResponse> {
+        KrakyClient::cancel_all_orders(self, credentials).await
+    }
+
+    async fn amend_order(
+        &self,
+        credentials: &Self::Credentials,
+        params: AmendOrderParams,
+    ) -> Result<AmendOrderResponse> {
+        KrakyClient::amend_order(self, credentials, params).await
+    }
+}
+
+#[cfg(all(test, feature = "ticker", feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::{fixtures, MockScript, MockServer};
+    use futures_util::StreamExt;
+    use std::time::Duration;
+
+    /// Strategy code only knowing the trait
+    async fn first_ticker(source: &dyn MarketDataSource, symbol: &str) -> Arc<Ticker> {
+        let mut tickers = source.tickers(symbol).await.unwrap();
+        tickers.next().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_client_as_market_data_source() {
+        let server = MockServer::start(
+            MockScript::new().message(fixtures::get("ticker_snapshot").unwrap().text),
+        )
+        .await
+        .unwrap();
+        let client = KrakyClient::connect_with_url(server.url()).await.unwrap();
+        let source: &dyn MarketDataSource = &client;
+        assert_eq!(source.exchange(), "kraken");
+        assert!(source.is_connected());
+
+        let ticker = tokio::time::timeout(Duration::from_secs(5), first_ticker(source, "BTC/USD"))
+            .await
+            .unwrap();
+        assert_eq!(ticker.symbol, "BTC/USD");
+        assert_eq!(source.last_price("BTC/USD"), Some(ticker.last));
+    }
+}
@@ -0,0 +1,444 @@
+//! C-compatible API for embedding the client
+//!
+//! An opaque [`KrakyHandle`] owns a client and a tokio runtime with its own
+//! worker threads, so C, C++ or C# hosts need no async runtime. Callbacks
+//! registered for trades and book updates run on those worker threads;
+//! [`kraky_shutdown`] stops them and frees the handle. The declarations are
+//! in `include/kraky.h`.
+//!
+//! Build a shared or static library with:
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! cargo rustc --release --features ffi --crate-type staticlib
+//! ```
+//!
+//! Functions returning `int` return [`KRAKY_OK`] or [`KRAKY_ERR`]; after an
+//! error, [`kraky_last_error`] describes it.
+//!
+//! Requires the `ffi` feature flag.
+//!
+//! # Example
+//!
+//! ```c
+//! #include "kraky.h"
+//!
+//! static void on_trade(const KrakyTrade *trade, void *user_data) {
+//!     printf("%s %s %.2f x %.8f\n", trade->symbol,
+//!            trade->side == KRAKY_SIDE_BUY ? "buy" : "sell", trade->price, trade->qty);
+//! }
+//!
+//! int main(void) {
+//!     KrakyHandle *kraky = kraky_connect(NULL);
+//!     if (!kraky) {
+//!         fprintf(stderr, "connect failed: %s\n", kraky_last_error());
+//!         return 1;
+//!     }
+//!     kraky_subscribe_trades(kraky, "BTC/USD", on_trade, NULL);
+//!     sleep(60);
+//!     kraky_shutdown(kraky);
+//! }
+//! ```
+
+use crate::client::KrakyClient;
+use crate::error::{KrakyError, Result};
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+/// Success
+pub const KRAKY_OK: c_int = 0;
+/// Failure; see [`kraky_last_error`]
+pub const KRAKY_ERR: c_int = -1;
+
+/// Taker bought
+pub const KRAKY_SIDE_BUY: c_int = 0;
+/// Taker sold
+pub const KRAKY_SIDE_SELL: c_int = 1;
+
+thread_local! {
+    /// Message of the last error on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Remember an error for [`kraky_last_error`]
+fn set_last_error(error: impl std::fmt::Display) {
+    let message = CString::new(error.to_string().replace('\0', " "))
+        .expect("interior NUL bytes were replaced");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Read a C string argument
+///
+/// # Safety
+///
+/// `s` must be null or point to a NUL-terminated string.
+unsafe fn c_str<'a>(s: *const c_char, name: &str) -> Result<&'a str> {
+    if s.is_null() {
+        return Err(KrakyError::InvalidMessage(format!("{} is null", name)));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| KrakyError::InvalidMessage(format!("{} is not valid UTF-8", name)))
+}
+
+/// Client, runtime and callback tasks behind a C handle
+pub struct KrakyHandle {
+    client: Arc<KrakyClient>,
+    runtime: Runtime,
+    /// Set on shutdown; callbacks check it before every call
+    stopped: Arc<AtomicBool>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// Trade passed to a [`KrakyTradeCallback`]
+///
+/// The strings are only valid during the callback.
+#[repr(C)]
+#[derive(Debug)]
+pub struct KrakyTrade {
+    /// Trading pair, e.g. `BTC/USD`
+    pub symbol: *const c_char,
+    /// [`KRAKY_SIDE_BUY`] or [`KRAKY_SIDE_SELL`]
+    pub side: c_int,
+    /// Price
+    pub price: f64,
+    /// Quantity
+    pub qty: f64,
+    /// Kraken trade ID
+    pub trade_id: i64,
+    /// RFC 3339 timestamp
+    pub timestamp: *const c_char,
+}
+
+/// One price level of a [`KrakyBook`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct KrakyLevel {
+    /// Price
+    pub price: f64,
+    /// Quantity
+    pub qty: f64,
+}
+
+/// Managed book passed to a [`KrakyBookCallback`]
+///
+/// The symbol and level arrays are only valid during the callback.
+#[repr(C)]
+#[derive(Debug)]
+pub struct KrakyBook {
+    /// Trading pair, e.g. `BTC/USD`
+    pub symbol: *const c_char,
+    /// Bids, best first
+    pub bids: *const KrakyLevel,
+    /// Number of bids
+    pub bid_count: usize,
+    /// Asks, best first
+    pub asks: *const KrakyLevel,
+    /// Number of asks
+    pub ask_count: usize,
+    /// Sequence number of the managed book
+    pub sequence: u64,
+}
+
+/// Called with every trade
+pub type KrakyTradeCallback = extern "C" fn(trade: *const KrakyTrade, user_data: *mut c_void);
+
+/// Called with the managed book after every update
+pub type KrakyBookCallback = extern "C" fn(book: *const KrakyBook, user_data: *mut c_void);
+
+/// Callback and its user data, moved to a worker thread
+struct Callback<F> {
+    function: F,
+    user_data: *mut c_void,
+}
+
+// The caller promises `user_data` may be used from the worker threads
+unsafe impl<F: Send> Send for Callback<F> {}
+
+/// Connect to Kraken, or to `url` when it isn't null
+///
+/// Returns null on failure.
+///
+/// # Safety
+///
+/// `url` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kraky_connect(url: *const c_char) -> *mut KrakyHandle {
+    let connect = || -> Result<KrakyHandle> {
+        let mut builder = KrakyClient::builder();
+        if !url.is_null() {
+            builder = builder.url(c_str(url, "url")?);
+        }
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("kraky-ffi")
+            .enable_all()
+            .build()?;
+        let client = runtime.block_on(builder.connect())?;
+        Ok(KrakyHandle {
+            client: Arc::new(client),
+            runtime,
+            stopped: Arc::new(AtomicBool::new(false)),
+            tasks: Mutex::new(Vec::new()),
+        })
+    };
+    match connect() {
+        Ok(handle) => Box::into_raw(Box::new(handle)),
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Call `callback` with every trade of `symbol`
+///
+/// # Safety
+///
+/// `handle` must come from [`kraky_connect`] and not be shut down, and
+/// `symbol` must point to a NUL-terminated string. `user_data` is passed
+/// to the callback from a worker thread.
+#[cfg(feature = "trades")]
+#[no_mangle]
+pub unsafe extern "C" fn kraky_subscribe_trades(
+    handle: *const KrakyHandle,
+    symbol: *const c_char,
+    callback: KrakyTradeCallback,
+    user_data: *mut c_void,
+) -> c_int {
+    let Some(handle) = handle.as_ref() else {
+        set_last_error("handle is null");
+        return KRAKY_ERR;
+    };
+    let symbol = match c_str(symbol, "symbol") {
+        Ok(symbol) => symbol,
+        Err(e) => {
+            set_last_error(e);
+            return KRAKY_ERR;
+        }
+    };
+    let mut trades = match handle
+        .runtime
+        .block_on(handle.client.subscribe_trades(symbol))
+    {
+        Ok(trades) => trades,
+        Err(e) => {
+            set_last_error(e);
+            return KRAKY_ERR;
+        }
+    };
+
+    let callback = Callback {
+        function: callback,
+        user_data,
+    };
+    let stopped = Arc::clone(&handle.stopped);
+    let task = handle.runtime.spawn(async move {
+        let callback = callback;
+        while let Some(trade) = trades.next().await {
+            if stopped.load(Ordering::Acquire) {
+                break;
+            }
+            let symbol = CString::new(trade.symbol.as_str()).unwrap_or_default();
+            let timestamp = CString::new(trade.timestamp.as_str()).unwrap_or_default();
+            let trade = KrakyTrade {
+                symbol: symbol.as_ptr(),
+                side: if trade.is_buy() {
+                    KRAKY_SIDE_BUY
+                } else {
+                    KRAKY_SIDE_SELL
+                },
+                price: trade.price,
+                qty: trade.qty,
+                trade_id: trade.trade_id,
+                timestamp: timestamp.as_ptr(),
+            };
+            (callback.function)(&trade, callback.user_data);
+        }
+    });
+    handle.tasks.lock().push(task);
+    KRAKY_OK
+}
+
+/// Call `callback` with the top `depth` levels of `symbol`'s managed book
+/// after every update
+///
+/// # Safety
+///
+/// `handle` must come from [`kraky_connect`] and not be shut down, and
+/// `symbol` must point to a NUL-terminated string. `user_data` is passed
+/// to the callback from a worker thread.
+#[cfg(feature = "orderbook")]
+#[no_mangle]
+pub unsafe extern "C" fn kraky_subscribe_book(
+    handle: *const KrakyHandle,
+    symbol: *const c_char,
+    depth: u32,
+    callback: KrakyBookCallback,
+    user_data: *mut c_void,
+) -> c_int {
+    let Some(handle) = handle.as_ref() else {
+        set_last_error("handle is null");
+        return KRAKY_ERR;
+    };
+    let symbol = match c_str(symbol, "symbol") {
+        Ok(symbol) => symbol.to_string(),
+        Err(e) => {
+            set_last_error(e);
+            return KRAKY_ERR;
+        }
+    };
+    let mut updates = match handle
+        .runtime
+        .block_on(handle.client.subscribe_orderbook(symbol.as_str(), depth))
+    {
+        Ok(updates) => updates,
+        Err(e) => {
+            set_last_error(e);
+            return KRAKY_ERR;
+        }
+    };
+
+    let callback = Callback {
+        function: callback,
+        user_data,
+    };
+    let stopped = Arc::clone(&handle.stopped);
+    let client = Arc::clone(&handle.client);
+    let task = handle.runtime.spawn(async move {
+        let callback = callback;
+        let c_symbol = CString::new(symbol.as_str()).unwrap_or_default();
+        while updates.next().await.is_some() {
+            if stopped.load(Ordering::Acquire) {
+                break;
+            }
+            let Some(book) = client.get_orderbook(symbol.as_str()) else {
+                continue;
+            };
+            let level = |l: &crate::models::PriceLevel| KrakyLevel {
+                price: l.price,
+                qty: l.qty,
+            };
+            let bids: Vec<_> = book.top_bids(depth as usize).iter().map(level).collect();
+            let asks: Vec<_> = book.top_asks(depth as usize).iter().map(level).collect();
+            let book = KrakyBook {
+                symbol: c_symbol.as_ptr(),
+                bids: bids.as_ptr(),
+                bid_count: bids.len(),
+                asks: asks.as_ptr(),
+                ask_count: asks.len(),
+                sequence: book.sequence,
+            };
+            (callback.function)(&book, callback.user_data);
+        }
+    });
+    handle.tasks.lock().push(task);
+    KRAKY_OK
+}
+
+/// Disconnect, stop all callbacks and free the handle
+///
+/// Waits for callbacks in progress on other threads, so no callback runs
+/// once this returns. Called from inside a callback, it returns right away
+/// and the current callback is the last one.
+///
+/// # Safety
+///
+/// `handle` must be null or come from [`kraky_connect`], and must not be
+/// used again afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn kraky_shutdown(handle: *mut KrakyHandle) {
+    if handle.is_null() {
+        return;
+    }
+    let handle = Box::from_raw(handle);
+    handle.stopped.store(true, Ordering::Release);
+    handle.client.disconnect();
+    for task in handle.tasks.lock().drain(..) {
+        task.abort();
+    }
+
+    let KrakyHandle {
+        client, runtime, ..
+    } = *handle;
+    drop(client);
+    if tokio::runtime::Handle::try_current().is_ok() {
+        // A runtime can't be dropped from one of its own threads
+        runtime.shutdown_background();
+    } else {
+        drop(runtime);
+    }
+}
+
+/// Get the message of the last error on the calling thread, or null
+///
+/// The string stays valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn kraky_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[cfg(all(test, feature = "trades", feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::{fixtures, MockScript, MockServer};
+    use std::sync::atomic::AtomicUsize;
+    use std::time::{Duration, Instant};
+
+    extern "C" fn count_trade(trade: *const KrakyTrade, user_data: *mut c_void) {
+        let trade = unsafe { &*trade };
+        let symbol = unsafe { CStr::from_ptr(trade.symbol) };
+        assert_eq!(symbol.to_str().unwrap(), "BTC/USD");
+        let count = unsafe { &*(user_data as *const AtomicUsize) };
+        count.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_connect_subscribe_shutdown() {
+        let server_runtime = Runtime::new().unwrap();
+        let server = server_runtime
+            .block_on(MockServer::start(
+                MockScript::new().message(fixtures::get("trade_snapshot").unwrap().text),
+            ))
+            .unwrap();
+
+        unsafe {
+            let refused = CString::new("ws://127.0.0.1:1").unwrap();
+            assert!(kraky_connect(refused.as_ptr()).is_null());
+            let error = CStr::from_ptr(kraky_last_error()).to_str().unwrap();
+            assert!(!error.is_empty());
+
+            let url = CString::new(server.url()).unwrap();
+            let handle = kraky_connect(url.as_ptr());
+            assert!(!handle.is_null());
+
+            let count = AtomicUsize::new(0);
+            let user_data = &count as *const AtomicUsize as *mut c_void;
+            assert_eq!(
+                kraky_subscribe_trades(handle, std::ptr::null(), count_trade, user_data),
+                KRAKY_ERR
+            );
+            let symbol = CString::new("BTC/USD").unwrap();
+            assert_eq!(
+                kraky_subscribe_trades(handle, symbol.as_ptr(), count_trade, user_data),
+                KRAKY_OK
+            );
+
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while count.load(Ordering::SeqCst) == 0 && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            kraky_shutdown(handle);
+            assert!(count.load(Ordering::SeqCst) > 0);
+        }
+    }
+}
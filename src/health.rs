@@ -0,0 +1,454 @@
+//! Connection health diagnostics
+//!
+//! The client measures the round-trip time of its periodic pings and keeps
+//! track of when the last heartbeat and message arrived. A snapshot is
+//! available at any time through [`KrakyClient::connection_health`], and a
+//! [`ConnectionEvent::Degraded`] event is emitted when latency or staleness
+//! crosses the configured thresholds.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::{HealthConfig, KrakyClient};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! client.set_health_config(HealthConfig {
+//!     max_rtt: Duration::from_millis(250),
+//!     ..Default::default()
+//! });
+//!
+//! let health = client.connection_health();
+//! println!("RTT p50: {:?}, p99: {:?}", health.rtt_p50, health.rtt_p99);
+//! if let Some(reason) = &health.degraded {
+//!     println!("Connection degraded: {}", reason);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Data gaps
+//!
+//! Heartbeats only show that the socket is alive. To tell a quiet market
+//! from a subscription that stopped delivering, set a maximum gap per
+//! channel: the client then emits [`ConnectionEvent::DataStale`] when a
+//! subscribed symbol sees no updates on that channel for longer, and
+//! [`ConnectionEvent::DataResumed`] once updates arrive again.
+//!
+//! ```no_run
+//! use kraky::{HealthConfig, KrakyClient};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::builder()
+//!     .health(HealthConfig::default().data_gap("book", Duration::from_secs(10)))
+//!     .connect()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`KrakyClient::connection_health`]: crate::KrakyClient::connection_health
+//! [`ConnectionEvent::Degraded`]: crate::ConnectionEvent::Degraded
+//! [`ConnectionEvent::DataStale`]: crate::ConnectionEvent::DataStale
+//! [`ConnectionEvent::DataResumed`]: crate::ConnectionEvent::DataResumed
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Number of RTT samples kept for percentiles
+const RTT_SAMPLES: usize = 100;
+
+/// Thresholds above which the connection counts as degraded
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// Maximum acceptable ping round-trip time
+    pub max_rtt: Duration,
+    /// Maximum acceptable time between heartbeats
+    ///
+    /// Kraken only sends heartbeats while at least one channel is
+    /// subscribed, so this is checked once the first heartbeat arrived.
+    pub max_heartbeat_gap: Duration,
+    /// Maximum acceptable time between updates per channel (`book`,
+    /// `trade`, `ticker`, `ohlc`) and symbol
+    ///
+    /// Empty by default, as quiet markets can go minutes without trades.
+    pub data_gaps: HashMap<String, Duration>,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            max_rtt: Duration::from_secs(1),
+            max_heartbeat_gap: Duration::from_secs(10),
+            data_gaps: HashMap::new(),
+        }
+    }
+}
+
+impl HealthConfig {
+    /// Report symbols without updates on `channel` for longer than `gap`
+    pub fn data_gap(mut self, channel: impl Into<String>, gap: Duration) -> Self {
+        self.data_gaps.insert(channel.into(), gap);
+        self
+    }
+}
+
+/// Change in the update flow of one channel and symbol
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DataTransition {
+    /// No updates for longer than the channel's gap
+    Stale {
+        channel: String,
+        symbol: String,
+        idle: Duration,
+    },
+    /// Updates arrived again after being reported stale
+    Resumed { channel: String, symbol: String },
+}
+
+/// When a channel and symbol last saw an update
+#[derive(Debug)]
+struct DataClock {
+    last: Instant,
+    stale: bool,
+}
+
+/// Snapshot of the connection health
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionHealth {
+    /// Round-trip time of the most recent ping
+    pub rtt_last: Option<Duration>,
+    /// Median round-trip time over recent pings
+    pub rtt_p50: Option<Duration>,
+    /// 95th percentile round-trip time over recent pings
+    pub rtt_p95: Option<Duration>,
+    /// 99th percentile round-trip time over recent pings
+    pub rtt_p99: Option<Duration>,
+    /// Number of RTT samples the percentiles are based on
+    pub rtt_samples: usize,
+    /// Time since the last heartbeat, if one was received
+    pub since_last_heartbeat: Option<Duration>,
+    /// Time since the last message of any kind
+    pub since_last_message: Option<Duration>,
+    /// Messages received since the client connected, across reconnects
+    #[serde(default)]
+    pub messages_received: u64,
+    /// Why the connection is degraded, if it is
+    pub degraded: Option<String>,
+}
+
+impl ConnectionHealth {
+    /// Check whether any threshold is exceeded
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.is_some()
+    }
+}
+
+/// Health measurements shared by the connection task and the client
+#[derive(Debug)]
+pub(crate) struct HealthMonitor {
+    config: Mutex<HealthConfig>,
+    pending_pings: Mutex<HashMap<u64, Instant>>,
+    rtts: Mutex<VecDeque<Duration>>,
+    last_heartbeat: Mutex<Option<Instant>>,
+    last_message: Mutex<Option<Instant>>,
+    messages: AtomicU64,
+    degraded: AtomicBool,
+    /// Whether any channel has a data gap configured
+    track_data: AtomicBool,
+    /// Update clocks by channel and symbol
+    data: Mutex<HashMap<String, HashMap<String, DataClock>>>,
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self {
+            config: Mutex::new(HealthConfig::default()),
+            pending_pings: Mutex::new(HashMap::new()),
+            rtts: Mutex::new(VecDeque::with_capacity(RTT_SAMPLES)),
+            last_heartbeat: Mutex::new(None),
+            last_message: Mutex::new(None),
+            messages: AtomicU64::new(0),
+            degraded: AtomicBool::new(false),
+            track_data: AtomicBool::new(false),
+            data: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl HealthMonitor {
+    /// Replace the degradation thresholds
+    pub fn set_config(&self, config: HealthConfig) {
+        self.track_data
+            .store(!config.data_gaps.is_empty(), Ordering::Relaxed);
+        *self.config.lock() = config;
+    }
+
+    /// Register an outgoing ping with its request ID
+    pub fn ping_sent(&self, req_id: u64) {
+        self.pending_pings.lock().insert(req_id, Instant::now());
+    }
+
+    /// Record the pong for a ping sent earlier
+    pub fn pong_received(&self, req_id: Option<u64>) {
+        let sent = req_id.and_then(|id| self.pending_pings.lock().remove(&id));
+        if let Some(sent) = sent {
+            let mut rtts = self.rtts.lock();
+            if rtts.len() == RTT_SAMPLES {
+                rtts.pop_front();
+            }
+            rtts.push_back(sent.elapsed());
+        }
+    }
+
+    /// Record a heartbeat from the server
+    pub fn heartbeat_received(&self) {
+        *self.last_heartbeat.lock() = Some(Instant::now());
+    }
+
+    /// Record any incoming message
+    pub fn message_received(&self) {
+        *self.last_message.lock() = Some(Instant::now());
+        self.messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an update on `channel` for `symbol`
+    ///
+    /// Also called when subscribing, so a subscription that never delivers
+    /// is reported as well.
+    pub fn data_received(&self, channel: &str, symbol: &str) {
+        if !self.track_data.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut data = self.data.lock();
+        let symbols = match data.get_mut(channel) {
+            Some(symbols) => symbols,
+            None => data.entry(channel.to_string()).or_default(),
+        };
+        match symbols.get_mut(symbol) {
+            Some(clock) => clock.last = Instant::now(),
+            None => {
+                symbols.insert(
+                    symbol.to_string(),
+                    DataClock {
+                        last: Instant::now(),
+                        stale: false,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Return the channels and symbols that just went stale or resumed
+    pub fn check_data(&self) -> Vec<DataTransition> {
+        if !self.track_data.load(Ordering::Relaxed) {
+            return Vec::new();
+        }
+        let gaps = self.config.lock().data_gaps.clone();
+        let mut transitions = Vec::new();
+        for (channel, symbols) in self.data.lock().iter_mut() {
+            let Some(gap) = gaps.get(channel) else {
+                continue;
+            };
+            for (symbol, clock) in symbols.iter_mut() {
+                let idle = clock.last.elapsed();
+                if idle > *gap && !clock.stale {
+                    clock.stale = true;
+                    transitions.push(DataTransition::Stale {
+                        channel: channel.clone(),
+                        symbol: symbol.clone(),
+                        idle,
+                    });
+                } else if idle <= *gap && clock.stale {
+                    clock.stale = false;
+                    transitions.push(DataTransition::Resumed {
+                        channel: channel.clone(),
+                        symbol: symbol.clone(),
+                    });
+                }
+            }
+        }
+        transitions
+    }
+
+    /// Get the time since the last message of any kind
+    pub fn since_last_message(&self) -> Option<Duration> {
+        self.last_message.lock().map(|at| at.elapsed())
+    }
+
+    /// Forget per-connection state after a reconnect
+    ///
+    /// Pings in flight on the old socket will never be answered, and the
+    /// heartbeat and data clocks restart with the new connection. Symbols
+    /// reported stale stay so until their updates resume.
+    pub fn reset(&self) {
+        self.pending_pings.lock().clear();
+        *self.last_heartbeat.lock() = None;
+        *self.last_message.lock() = Some(Instant::now());
+        let now = Instant::now();
+        for clock in self.data.lock().values_mut().flat_map(HashMap::values_mut) {
+            clock.last = now;
+        }
+    }
+
+    /// Take a snapshot of the current health
+    pub fn snapshot(&self) -> ConnectionHealth {
+        let mut sorted: Vec<Duration> = self.rtts.lock().iter().copied().collect();
+        let rtt_last = sorted.last().copied();
+        sorted.sort();
+
+        let mut health = ConnectionHealth {
+            rtt_last,
+            rtt_p50: percentile(&sorted, 0.50),
+            rtt_p95: percentile(&sorted, 0.95),
+            rtt_p99: percentile(&sorted, 0.99),
+            rtt_samples: sorted.len(),
+            since_last_heartbeat: self.last_heartbeat.lock().map(|at| at.elapsed()),
+            since_last_message: self.since_last_message(),
+            messages_received: self.messages.load(Ordering::Relaxed),
+            degraded: None,
+        };
+        health.degraded = self.degraded_reason(&health);
+        health
+    }
+
+    /// Return the reason when the connection just became degraded
+    ///
+    /// Returns `None` while healthy and while it stays degraded, so each
+    /// degradation is reported once.
+    pub fn check(&self) -> Option<String> {
+        let reason = self.snapshot().degraded;
+        let was_degraded = self.degraded.swap(reason.is_some(), Ordering::Relaxed);
+        if was_degraded {
+            None
+        } else {
+            reason
+        }
+    }
+
+    fn degraded_reason(&self, health: &ConnectionHealth) -> Option<String> {
+        let config = self.config.lock().clone();
+
+        let oldest_ping = self.pending_pings.lock().values().min().copied();
+        if let Some(waiting) = oldest_ping.map(|sent| sent.elapsed()) {
+            if waiting > config.max_rtt {
+                return Some(format!("no pong after {:?}", waiting));
+            }
+        }
+        if let Some(rtt) = health.rtt_last {
+            if rtt > config.max_rtt {
+                return Some(format!(
+                    "round-trip time {:?} exceeds {:?}",
+                    rtt, config.max_rtt
+                ));
+            }
+        }
+        if let Some(gap) = health.since_last_heartbeat {
+            if gap > config.max_heartbeat_gap {
+                return Some(format!("no heartbeat for {:?}", gap));
+            }
+        }
+        None
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 0.50), Some(Duration::from_millis(50)));
+        assert_eq!(percentile(&samples, 0.99), Some(Duration::from_millis(99)));
+        assert_eq!(percentile(&[], 0.50), None);
+    }
+
+    #[test]
+    fn test_rtt_tracking() {
+        let monitor = HealthMonitor::default();
+        let req_id = 7;
+        monitor.ping_sent(req_id);
+        monitor.pong_received(Some(req_id));
+        // Unknown or missing IDs are ignored
+        monitor.pong_received(Some(req_id));
+        monitor.pong_received(None);
+        monitor.message_received();
+        monitor.message_received();
+
+        let health = monitor.snapshot();
+        assert_eq!(health.messages_received, 2);
+        assert_eq!(health.rtt_samples, 1);
+        assert!(health.rtt_last.is_some());
+        assert!(!health.is_degraded());
+    }
+
+    #[test]
+    fn test_degraded_reported_once() {
+        let monitor = HealthMonitor::default();
+        monitor.set_config(HealthConfig {
+            max_rtt: Duration::ZERO,
+            ..Default::default()
+        });
+        monitor.ping_sent(1);
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert!(monitor.snapshot().is_degraded());
+        assert!(monitor.check().unwrap().starts_with("no pong"));
+        assert!(monitor.check().is_none());
+
+        // Recovering re-arms the event
+        monitor.reset();
+        assert!(monitor.check().is_none());
+        monitor.ping_sent(2);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(monitor.check().is_some());
+    }
+
+    #[test]
+    fn test_data_gaps() {
+        let monitor = HealthMonitor::default();
+        // Nothing is tracked without a configured gap
+        monitor.data_received("book", "BTC/USD");
+        assert!(monitor.data.lock().is_empty());
+
+        monitor.set_config(HealthConfig::default().data_gap("book", Duration::from_millis(5)));
+        monitor.data_received("book", "BTC/USD");
+        monitor.data_received("trade", "BTC/USD");
+        assert!(monitor.check_data().is_empty());
+
+        std::thread::sleep(Duration::from_millis(10));
+        let stale = monitor.check_data();
+        assert_eq!(stale.len(), 1);
+        assert!(matches!(
+            &stale[0],
+            DataTransition::Stale { channel, symbol, idle }
+                if channel == "book" && symbol == "BTC/USD" && *idle > Duration::from_millis(5)
+        ));
+        // Reported once
+        assert!(monitor.check_data().is_empty());
+
+        monitor.data_received("book", "BTC/USD");
+        assert_eq!(
+            monitor.check_data(),
+            vec![DataTransition::Resumed {
+                channel: "book".to_string(),
+                symbol: "BTC/USD".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Exchange-to-delivery latency histograms
+//!
+//! Book, trade, ticker and candle messages carry Kraken's timestamp. With
+//! the `latency` feature the client compares it to the local clock twice:
+//! when it starts handling the frame and after the update has been handed
+//! to subscribers. Both samples go into HDR histograms per channel,
+//! available through [`KrakyClient::latency_stats`].
+//!
+//! The measurement includes network transit and the clock offset between
+//! Kraken and this machine, so keep the clock NTP-synchronized. Samples a
+//! skewed clock puts before the exchange timestamp are recorded as zero.
+//! With a [`PipelineConfig`], receive latency also includes the time a
+//! frame waited for its worker.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::KrakyClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! let _book = client.subscribe_orderbook("BTC/USD", 10).await?;
+//! tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+//!
+//! for (channel, stats) in client.latency_stats() {
+//!     println!(
+//!         "{}: p50 {:?}, p99 {:?} over {} updates",
+//!         channel,
+//!         stats.dispatch.percentile(50.0),
+//!         stats.dispatch.percentile(99.0),
+//!         stats.dispatch.count()
+//!     );
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`KrakyClient::latency_stats`]: crate::KrakyClient::latency_stats
+//! [`PipelineConfig`]: crate::PipelineConfig
+
+use chrono::{DateTime, Utc};
+use hdrhistogram::Histogram;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Largest latency recorded, in microseconds; longer samples are clamped
+const MAX_LATENCY_US: u64 = 60_000_000;
+
+/// Significant decimal digits kept by the histograms
+const SIGNIFICANT_DIGITS: u8 = 3;
+
+/// Latency distribution with microsecond resolution
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    histogram: Histogram<u64>,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            histogram: Histogram::new_with_bounds(1, MAX_LATENCY_US, SIGNIFICANT_DIGITS)
+                .expect("valid histogram bounds"),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// Record one sample, clamped to zero and the upper bound
+    fn record(&mut self, latency: chrono::Duration) {
+        let micros = latency.num_microseconds().unwrap_or(i64::MAX).max(0) as u64;
+        self.histogram.saturating_record(micros);
+    }
+
+    /// Get the number of samples
+    pub fn count(&self) -> u64 {
+        self.histogram.len()
+    }
+
+    /// Get the latency at a percentile between 0 and 100
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        Duration::from_micros(self.histogram.value_at_percentile(percentile))
+    }
+
+    /// Get the lowest sample
+    pub fn min(&self) -> Duration {
+        Duration::from_micros(self.histogram.min())
+    }
+
+    /// Get the highest sample
+    pub fn max(&self) -> Duration {
+        Duration::from_micros(self.histogram.max())
+    }
+
+    /// Get the mean latency
+    pub fn mean(&self) -> Duration {
+        Duration::from_secs_f64(self.histogram.mean() / 1_000_000.0)
+    }
+
+    /// Get the underlying histogram, with values in microseconds
+    pub fn histogram(&self) -> &Histogram<u64> {
+        &self.histogram
+    }
+
+    /// Summarize the distribution for logging or export
+    pub fn summary(&self) -> LatencySummary {
+        LatencySummary {
+            count: self.count(),
+            min: self.min(),
+            p50: self.percentile(50.0),
+            p90: self.percentile(90.0),
+            p99: self.percentile(99.0),
+            p999: self.percentile(99.9),
+            max: self.max(),
+        }
+    }
+}
+
+/// Percentiles of a [`LatencyHistogram`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    /// Number of samples
+    pub count: u64,
+    /// Lowest sample
+    pub min: Duration,
+    /// Median
+    pub p50: Duration,
+    /// 90th percentile
+    pub p90: Duration,
+    /// 99th percentile
+    pub p99: Duration,
+    /// 99.9th percentile
+    pub p999: Duration,
+    /// Highest sample
+    pub max: Duration,
+}
+
+/// Latency histograms of one channel
+#[derive(Debug, Clone, Default)]
+pub struct LatencyStats {
+    /// From the exchange timestamp to the client starting to handle the frame
+    pub receive: LatencyHistogram,
+    /// From the exchange timestamp to the update being handed to subscribers
+    pub dispatch: LatencyHistogram,
+}
+
+/// Histograms per channel, shared by the connections of a client
+#[derive(Debug, Default)]
+pub(crate) struct LatencyRecorder {
+    channels: Mutex<HashMap<String, LatencyStats>>,
+}
+
+impl LatencyRecorder {
+    /// Record the exchange timestamps of one dispatched message
+    ///
+    /// Empty or unparseable timestamps, such as those of book snapshots,
+    /// are skipped.
+    pub fn record<'a>(
+        &self,
+        channel: &str,
+        timestamps: impl IntoIterator<Item = &'a str>,
+        received: DateTime<Utc>,
+    ) {
+        let dispatched = Utc::now();
+        let sent: Vec<_> = timestamps
+            .into_iter()
+            .filter_map(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+            .collect();
+        if sent.is_empty() {
+            return;
+        }
+
+        let mut channels = self.channels.lock();
+        let stats = channels.entry(channel.to_string()).or_default();
+        for sent in sent {
+            stats.receive.record(received.signed_duration_since(sent));
+            stats
+                .dispatch
+                .record(dispatched.signed_duration_since(sent));
+        }
+    }
+
+    /// Copy the histograms of every channel
+    pub fn snapshot(&self) -> HashMap<String, LatencyStats> {
+        self.channels.lock().clone()
+    }
+
+    /// Drop all samples
+    pub fn reset(&self) {
+        self.channels.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_receive_and_dispatch_latency() {
+        let recorder = LatencyRecorder::default();
+        let now = Utc::now();
+        let sent = (now - chrono::Duration::milliseconds(20)).to_rfc3339();
+        let skewed = (now + chrono::Duration::seconds(1)).to_rfc3339();
+
+        recorder.record("trade", [sent.as_str(), skewed.as_str(), ""], now);
+        recorder.record("book", [""], now);
+
+        let stats = recorder.snapshot();
+        assert!(!stats.contains_key("book"));
+        let trade = &stats["trade"];
+        assert_eq!(trade.receive.count(), 2);
+        assert_eq!(trade.receive.min(), Duration::ZERO);
+        let max = trade.receive.max();
+        assert!(max >= Duration::from_millis(19) && max <= Duration::from_millis(21));
+        assert!(trade.dispatch.max() >= max);
+
+        let summary = trade.receive.summary();
+        assert_eq!(summary.count, 2);
+        assert!(summary.p50 <= summary.p99);
+
+        recorder.reset();
+        assert!(recorder.snapshot().is_empty());
+    }
+}
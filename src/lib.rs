@@ -27,6 +27,7 @@
 //! - `reconnect` - Smart reconnection with exponential backoff
 //! - `events` - Connection lifecycle event callbacks
 //! - `orderbook` - Orderbook depth subscription and managed state
+//! - `native-tls` - Platform TLS for the WebSocket connection
 //!
 //! ### Data Type Features
 //!
@@ -37,9 +38,23 @@
 //!
 //! ### Advanced Features
 //!
-//! - `analytics` - Orderbook imbalance analysis (requires `orderbook`)
+//! - `analytics` - Orderbook imbalance analysis and rolling market statistics (requires `orderbook`)
+//! - `alerts` - Declarative alert rules with a unified alert stream (requires `analytics`)
+//! - `price-alerts` - Price above/below/crossing and trailing alerts with persistence (requires `ticker`)
+//! - `screener` - Rank tradable pairs by 24h change, volume spikes or spread
+//! - `exchange` - `MarketDataSource` and `OrderGateway` traits implemented by `KrakyClient`
+//! - `server` - Fan-out server re-serving market data to local clients over WebSocket
+//! - `tui` - Terminal dashboard with book ladder, trades, ticker and connection stats
+//! - `cli` - `kraky` binary streaming, printing and recording market data
+//! - `ffi` - C API with an opaque client handle and trade/book callbacks
 //! - `checksum` - CRC32 orderbook validation (requires `orderbook`)
 //! - `simd` - SIMD-accelerated JSON parsing (2-3x faster)
+//! - `proxy` - Connect through an HTTP CONNECT or SOCKS5 proxy
+//! - `trace-messages` - Per-frame trace spans with sequence, channel and symbol fields
+//! - `latency` - HDR histograms of exchange-to-delivery latency per channel
+//! - `tokio-background` - Await the client from async-std, smol or any other executor via a background tokio runtime
+//! - `blocking` - Synchronous client with iterator subscriptions for non-async code
+//! - `rustls` - Pure-Rust TLS backend, for static/musl builds without OpenSSL
 //!
 //! ### Meta Features
 //!
@@ -223,12 +238,14 @@
 //! ```no_run
 //! # #[cfg(feature = "checksum")]
 //! # {
-//! use kraky::KrakyClient;
+//! use kraky::{BookPrecision, KrakyClient};
 //! use futures_util::StreamExt;
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let client = KrakyClient::connect().await?;
+//!     // BTC/USD prices have 1 decimal, quantities 8
+//!     client.set_book_precision("BTC/USD", BookPrecision::new(1, 8));
 //!     let mut orderbook = client.subscribe_orderbook("BTC/USD", 10).await?;
 //!
 //!     while let Some(update) = orderbook.next().await {
@@ -236,10 +253,10 @@
 //!             let expected_checksum = update.data[0].checksum;
 //!
 //!             // Validate checksum manually
-//!             if client.is_orderbook_valid("BTC/USD").unwrap_or(false) {
-//!                 println!("✓ Checksum valid: {}", expected_checksum);
-//!             } else {
-//!                 println!("✗ Checksum mismatch for {}", update.data[0].symbol);
+//!             match client.is_orderbook_valid("BTC/USD") {
+//!                 Some(true) => println!("✓ Checksum valid: {}", expected_checksum),
+//!                 Some(false) => println!("✗ Checksum mismatch for {}", update.data[0].symbol),
+//!                 None => println!("? Checksum not validated (precision unknown)"),
 //!             }
 //!         }
 //!     }
@@ -278,6 +295,8 @@
 //!         max_delay: Duration::from_secs(60),
 //!         backoff_multiplier: 2.0,
 //!         max_attempts: Some(10),
+//!         stale_timeout: Some(Duration::from_secs(60)),
+//!         ..Default::default()
 //!     };
 //!
 //!     let client = KrakyClient::connect_with_config("wss://ws.kraken.com/v2", config).await?;
@@ -317,6 +336,30 @@
 //!             ConnectionEvent::ReconnectExhausted => {
 //!                 println!("✗ Reconnection attempts exhausted");
 //!             }
+//!             ConnectionEvent::Degraded(reason) => {
+//!                 println!("⚠ Connection degraded: {}", reason);
+//!             }
+//!             ConnectionEvent::Stale(idle) => {
+//!                 println!("⚠ No data for {:?}, reconnecting", idle);
+//!             }
+//!             ConnectionEvent::DataStale(channel, symbol, idle) => {
+//!                 println!("⚠ No {} updates for {} in {:?}", channel, symbol, idle);
+//!             }
+//!             ConnectionEvent::DataResumed(channel, symbol) => {
+//!                 println!("✓ {} updates for {} resumed", channel, symbol);
+//!             }
+//!             ConnectionEvent::OrderbookCorrupted { symbol, .. } => {
+//!                 println!("⚠ {} orderbook failed its checksum", symbol);
+//!             }
+//!             ConnectionEvent::ChecksumFailures { symbol, consecutive } => {
+//!                 println!("⚠ {} failed {} checksums in a row", symbol, consecutive);
+//!             }
+//!             ConnectionEvent::OrderbookUnverified { symbol } => {
+//!                 println!("? {} orderbook checksums not validated yet", symbol);
+//!             }
+//!             ConnectionEvent::SystemStateChanged(state) => {
+//!                 println!("ℹ Kraken is now {}", state);
+//!             }
 //!         }
 //!     }
 //!     Ok(())
@@ -335,27 +378,14 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let api_key = std::env::var("KRAKEN_API_KEY")?;
-//!     let api_secret = std::env::var("KRAKEN_API_SECRET")?;
-//!     let credentials = Credentials::new(api_key, api_secret);
+//!     let credentials = Credentials::from_env()?;
 //!
 //!     let client = KrakyClient::connect().await?;
 //!
 //!     // Place a limit buy order for 0.001 BTC at $50,000
-//!     let order = OrderParams {
-//!         symbol: "BTC/USD".to_string(),
-//!         side: OrderSide::Buy,
-//!         order_type: kraky::OrderType::Limit,
-//!         order_qty: Some(0.001),
-//!         limit_price: Some(50000.0),
-//!         trigger_price: None,
-//!         time_in_force: None,
-//!         post_only: None,
-//!         reduce_only: None,
-//!         stp: None,
-//!         cl_ord_id: None,
-//!         validate: None,
-//!     };
+//!     let order = OrderParams::limit("BTC/USD", OrderSide::Buy, 0.001, 50000.0)
+//!         .post_only()
+//!         .build()?;
 //!
 //!     let response = client.place_order(&credentials, order).await?;
 //!     println!("Order placed! ID: {}", response.order_id);
@@ -400,7 +430,7 @@
 //! ```no_run
 //! # #[cfg(feature = "telegram-alerts")]
 //! # {
-//! use kraky::{KrakyClient, TelegramNotifier, ImbalanceSignal};
+//! use kraky::{KrakyClient, Notifier, TelegramNotifier, ImbalanceSignal};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -433,6 +463,11 @@
 //! See `examples/telegram_imbalance_bot.rs`, `examples/whale_watcher.rs`, and other Telegram
 //! examples in the repository.
 //!
+//! All alert methods come from the [`Notifier`] trait (`notify` feature), so bots written
+//! against `impl Notifier` work with any notification backend, such as the
+//! `DiscordNotifier` (`discord` feature), `SlackNotifier` (`slack` feature) or
+//! `WebhookNotifier` (`webhook` feature).
+//!
 //! ## Authentication & Private Channels
 //!
 //! Access private WebSocket channels for account data using HMAC-SHA256 authentication.
@@ -460,13 +495,8 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let api_key = std::env::var("KRAKEN_API_KEY")?;
-//!     let api_secret = std::env::var("KRAKEN_API_SECRET")?;
-//!
-//!     let credentials = Credentials::new(api_key, api_secret);
-//!     let nonce = std::time::SystemTime::now()
-//!         .duration_since(std::time::UNIX_EPOCH)?
-//!         .as_millis() as u64;
+//!     let credentials = Credentials::from_env()?;
+//!     let nonce = credentials.next_nonce();
 //!
 //!     let token = credentials.generate_token(nonce)?;
 //!     println!("Authentication token generated: {}...", &token[..20]);
@@ -500,8 +530,23 @@
 //!
 //! ```text
 //! Layer 4: INTEGRATIONS
-//!   ├─ telegram-alerts (Telegram + analytics + ticker)
-//!   └─ telegram (base Telegram integration)
+//!   ├─ telegram-alerts (Telegram + analytics + alerts + ticker)
+//!   ├─ telegram (base Telegram integration)
+//!   ├─ charts (candlestick PNG rendering, `send_chart` with telegram)
+//!   ├─ discord (Discord webhook integration)
+//!   ├─ slack (Slack webhook integration)
+//!   ├─ webhook (signed JSON alerts to any HTTP endpoint)
+//!   ├─ recorder (record subscriptions to CSV / JSON Lines files)
+//!   ├─ arrow (Arrow record batches from trades, candles and snapshots)
+//!   ├─ replay (drive subscriptions from recorded JSON Lines files)
+//!   ├─ backtest (strategy backtests with paper fills, requires replay)
+//!   ├─ sqlite (persist trades, tickers, candles and book snapshots to SQLite)
+//!   ├─ postgres (batched inserts into PostgreSQL / TimescaleDB)
+//!   ├─ bridge (republish to NATS with bridge-nats, Kafka with bridge-kafka)
+//!   ├─ redis (bridge publisher for Redis pub/sub and Streams)
+//!   ├─ proxy (HTTP CONNECT / SOCKS5 proxy for the WebSocket)
+//!   ├─ rest (historical market data, account history and order fallback via REST)
+//!   └─ notify (Notifier trait shared by all backends)
 //!
 //! Layer 3: TRADING & PRIVATE DATA
 //!   ├─ trading (place/cancel orders)
@@ -510,6 +555,7 @@
 //!
 //! Layer 2: ANALYTICS & PERFORMANCE
 //!   ├─ analytics (imbalance detection)
+//!   ├─ alerts (alert rules engine)
 //!   ├─ checksum (CRC32 validation)
 //!   └─ simd (SIMD JSON parsing)
 //!
@@ -571,31 +617,193 @@
 //!
 //! See the `examples/` directory for all examples with detailed documentation.
 
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+compile_error!("kraky needs a TLS backend: enable the `native-tls` or `rustls` feature");
+
 pub mod client;
 pub mod error;
+pub mod health;
 pub mod messages;
 pub mod models;
+pub mod rate_limit;
+pub mod router;
 pub mod subscriptions;
+pub mod symbol;
+pub mod watchlist;
+
+// Analytics components (requires 'analytics' feature)
+#[cfg(feature = "analytics")]
+pub mod analytics;
+
+// Alert rules engine (requires 'alerts' feature)
+#[cfg(feature = "alerts")]
+pub mod alerts;
+
+// Ticker price alerts (requires 'price-alerts' feature)
+#[cfg(feature = "price-alerts")]
+pub mod price_alerts;
 
 // Authentication module (requires 'auth' feature)
 #[cfg(feature = "auth")]
 pub mod auth;
 
+// Notifier trait (requires 'notify' feature)
+#[cfg(feature = "notify")]
+pub mod notifier;
+
+// Alert message templates (requires 'notify' feature)
+#[cfg(feature = "notify")]
+pub mod templates;
+
+// Scheduled activity summaries (requires 'notify' feature)
+#[cfg(feature = "notify")]
+pub mod summary;
+
+// Event alerts through a notifier (requires 'notify' and 'events' features)
+#[cfg(all(feature = "notify", feature = "events"))]
+pub mod alert_bridge;
+
+// Candlestick chart rendering (requires 'charts' feature)
+#[cfg(feature = "charts")]
+pub mod charts;
+
 // Telegram bot integration (requires 'telegram' feature)
 #[cfg(feature = "telegram")]
 pub mod telegram;
 
+// Discord webhook integration (requires 'discord' feature)
+#[cfg(feature = "discord")]
+pub mod discord;
+
+// Slack webhook integration (requires 'slack' feature)
+#[cfg(feature = "slack")]
+pub mod slack;
+
+// Generic HTTP webhook integration (requires 'webhook' feature)
+#[cfg(feature = "webhook")]
+pub mod webhook;
+
+// Arrow record batch conversions (requires 'arrow' feature)
+#[cfg(feature = "arrow")]
+pub mod arrow;
+
+// Market data recorder (requires 'recorder' feature)
+#[cfg(feature = "recorder")]
+pub mod recorder;
+
+// Replay of recorded data (requires 'replay' feature)
+#[cfg(feature = "replay")]
+pub mod replay;
+
+// Backtesting harness (requires 'backtest' feature)
+#[cfg(feature = "backtest")]
+pub mod backtest;
+
+// SQLite market data sink (requires 'sqlite' feature)
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+// PostgreSQL / TimescaleDB sink (requires 'postgres' feature)
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+// Message bus bridge (requires 'bridge' feature)
+#[cfg(feature = "bridge")]
+pub mod bridge;
+
+// REST client (requires 'rest' feature)
+#[cfg(feature = "rest")]
+pub mod rest;
+
+// Cached WebSocket tokens (requires 'private' and 'rest' features)
+#[cfg(all(feature = "private", feature = "rest"))]
+pub mod token;
+
+// Market screener (requires 'screener' feature)
+#[cfg(feature = "screener")]
+pub mod screener;
+
+// Exchange-neutral traits (requires 'exchange' feature)
+#[cfg(feature = "exchange")]
+pub mod exchange;
+
+// Local fan-out server (requires 'server' feature)
+#[cfg(feature = "server")]
+pub mod server;
+
+// Terminal dashboard (requires 'tui' feature)
+#[cfg(feature = "tui")]
+pub mod tui;
+
+// C-compatible API (requires 'ffi' feature)
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+// Exchange-to-delivery latency histograms (requires 'latency' feature)
+#[cfg(feature = "latency")]
+pub mod latency;
+
+// Portfolio valuation (requires 'private' and 'ticker' features)
+#[cfg(all(feature = "private", feature = "ticker"))]
+pub mod portfolio;
+
+// Executor shim for running outside tokio
+mod runtime;
+
+// Synchronous client wrapper (requires 'blocking' feature)
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+// Mock server with fault injection (requires 'testing' feature)
+#[cfg(feature = "testing")]
+pub mod testing;
+
+// HTTP / SOCKS5 proxy support (requires 'proxy' feature)
+#[cfg(feature = "proxy")]
+pub mod proxy;
+
 // Re-export main types
-pub use client::{ConnectionState, KrakyClient};
+pub use client::{
+    ClientBuilder, ConnectionState, IpPreference, KrakyClient, PipelineConfig, StrictConfig,
+    SystemState, TlsBackend, TlsConfig,
+};
+
+// Proxy types (requires 'proxy' feature)
+#[cfg(feature = "proxy")]
+pub use proxy::{ProxyConfig, ProxyKind};
 
 // Reconnection types (requires 'reconnect' feature)
 #[cfg(feature = "reconnect")]
-pub use client::ReconnectConfig;
+pub use client::{
+    BackoffPolicy, DisconnectKind, ReconnectConfig, RestorePolicy, StoredSubscription,
+};
 
 // Connection event types (requires 'events' feature)
 #[cfg(feature = "events")]
 pub use client::ConnectionEvent;
 
+// Latency histogram types (requires 'latency' feature)
+#[cfg(feature = "latency")]
+pub use latency::{LatencyHistogram, LatencyStats, LatencySummary};
+
+// Connection health types (always available)
+pub use health::{ConnectionHealth, HealthConfig};
+
+// Outgoing rate limit config (always available)
+pub use rate_limit::RateLimitConfig;
+
+// Request ID routing for custom requests (always available)
+pub use router::{ReqIdRouter, ResponseStream};
+
+// Strict mode parse diagnostics (always available)
+pub use messages::ParseDiagnostic;
+
+// Trading pair symbol (always available)
+pub use symbol::Symbol;
+
+// Watchlist types
+pub use watchlist::{WatchChannel, WatchUpdate, Watchlist};
+
 // Error types (always available)
 pub use error::{KrakenApiError, KrakenCategory, KrakenSeverity, KrakyError, Result};
 
@@ -604,21 +812,38 @@ pub use error::{KrakenApiError, KrakenCategory, KrakenSeverity, KrakyError, Resu
 pub use models::{Orderbook, OrderbookSnapshot, OrderbookUpdate};
 
 #[cfg(feature = "trades")]
-pub use models::{Trade, TradeSide};
+pub use models::{AggregateTrade, Trade, TradeAggregator, TradeSide};
 
 #[cfg(feature = "ticker")]
 pub use models::Ticker;
 
 #[cfg(feature = "ohlc")]
-pub use models::{Interval, OHLC};
+pub use models::{CandleGapFiller, Interval, ParseIntervalError, OHLC};
 
 // Analytics types (requires both 'orderbook' and 'analytics' features)
 #[cfg(all(feature = "orderbook", feature = "analytics"))]
 pub use models::{ImbalanceMetrics, ImbalanceSignal};
 
+#[cfg(feature = "analytics")]
+pub use analytics::{
+    CorrelationTracker, DecouplingEvent, DivergenceConfig, DivergenceDetector, DivergenceEvent,
+    DivergenceKind, ImbalanceConfig, ImbalanceTracker, ImbalanceTransition, LiquidityEvent,
+    LiquidityEventKind, LiquidityMonitor, SpreadMonitor, SpreadStats, ValueArea, Volatility,
+    VolatilityMethod, VolumeProfile, Vpin, WhaleDetector, WhaleEvent, WhaleKind, WhaleSide,
+    WhaleThreshold,
+};
+
+// Alert types (requires 'alerts' feature)
+#[cfg(feature = "alerts")]
+pub use alerts::{AlertCondition, AlertEngine, AlertEvent, AlertRule};
+
+// Price alert types (requires 'price-alerts' feature)
+#[cfg(feature = "price-alerts")]
+pub use price_alerts::{AlertFired, AlertManager, PriceAlert, PriceTrigger};
+
 // Checksum types (requires both 'orderbook' and 'checksum' features)
 #[cfg(all(feature = "orderbook", feature = "checksum"))]
-pub use models::ChecksumValidation;
+pub use models::{BookPrecision, ChecksumStats, ChecksumValidation};
 
 // Private channel types (requires 'private' feature)
 #[cfg(feature = "private")]
@@ -630,16 +855,76 @@ pub use models::{
 #[cfg(feature = "trading")]
 pub use models::{
     AmendOrderParams, AmendOrderResponse, CancelAllResponse, CancelOrderResponse, OrderParams,
-    OrderResponse, OrderSide, OrderStatus, OrderType, SelfTradePrevention, TimeInForce,
+    OrderParamsBuilder, OrderResponse, OrderSide, OrderStatus, OrderType, SelfTradePrevention,
+    TimeInForce,
 };
 
 // Subscription types (always available)
-pub use subscriptions::{BackpressureConfig, Subscription, SubscriptionStats, DEFAULT_BUFFER_SIZE};
+pub use subscriptions::{
+    BackpressureConfig, CallbackHandle, Conflate, Subscription, SubscriptionInfo,
+    SubscriptionStats, SubscriptionStatsSnapshot, DEFAULT_BUFFER_SIZE,
+};
 
 // Authentication types (requires 'auth' feature)
 #[cfg(feature = "auth")]
-pub use auth::Credentials;
+pub use auth::{Credentials, NonceProvider, API_KEY_ENV, API_SECRET_ENV};
+
+// Token cache (requires 'private' and 'rest' features)
+#[cfg(all(feature = "private", feature = "rest"))]
+pub use token::TokenManager;
+
+// Screener types (requires 'screener' feature)
+#[cfg(feature = "screener")]
+pub use screener::{PairMetrics, ScreenMetric, ScreenResult, Screener, ScreenerHandle};
+
+// Exchange traits (requires 'exchange' feature)
+#[cfg(all(feature = "exchange", feature = "trading"))]
+pub use exchange::OrderGateway;
+#[cfg(feature = "exchange")]
+pub use exchange::{MarketDataSource, MarketStream};
+
+// Fan-out server types (requires 'server' feature)
+#[cfg(all(feature = "server", feature = "orderbook"))]
+pub use server::ProxyBook;
+#[cfg(feature = "server")]
+pub use server::{ProxyChannel, ProxyMessage, ProxyRequest, ProxyServer};
+
+// Terminal dashboard (requires 'tui' feature)
+#[cfg(feature = "tui")]
+pub use tui::Dashboard;
+
+// Portfolio types (requires 'private' and 'ticker' features)
+#[cfg(all(feature = "private", feature = "ticker"))]
+pub use portfolio::{AssetValuation, Portfolio, PortfolioValuation};
+
+// Notifier types (requires 'notify' feature)
+#[cfg(all(feature = "notify", feature = "events"))]
+pub use alert_bridge::AlertBridge;
+#[cfg(feature = "notify")]
+pub use notifier::Notifier;
+#[cfg(feature = "notify")]
+pub use summary::{
+    PeriodSummary, PriceChange, SummaryCounters, SummaryHandle, SummarySchedule, SummaryScheduler,
+};
+#[cfg(feature = "notify")]
+pub use templates::{AlertTemplate, AlertTemplates};
 
 // Telegram types (requires 'telegram' feature)
+#[cfg(all(feature = "telegram", feature = "trading"))]
+pub use telegram::OrderAction;
+#[cfg(all(feature = "telegram", feature = "orderbook"))]
+pub use telegram::{BotCommand, TelegramCommandBot};
 #[cfg(feature = "telegram")]
-pub use telegram::TelegramNotifier;
+pub use telegram::{TelegramFormat, TelegramNotifier, TelegramRoutes};
+
+// Discord types (requires 'discord' feature)
+#[cfg(feature = "discord")]
+pub use discord::DiscordNotifier;
+
+// Slack types (requires 'slack' feature)
+#[cfg(feature = "slack")]
+pub use slack::SlackNotifier;
+
+// Webhook types (requires 'webhook' feature)
+#[cfg(feature = "webhook")]
+pub use webhook::WebhookNotifier;
@@ -1,6 +1,7 @@
 //! Kraken WebSocket protocol messages
 
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
 /// Kraken WebSocket API v2 endpoint
 pub const KRAKEN_WS_URL: &str = "wss://ws.kraken.com/v2";
@@ -152,7 +153,7 @@ impl Default for PingRequest {
 }
 
 /// Generic response from Kraken
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KrakenResponse {
     /// Channel name (for data messages)
     #[serde(default)]
@@ -178,7 +179,7 @@ pub struct KrakenResponse {
 }
 
 /// System status message
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemStatus {
     /// Channel name
     pub channel: String,
@@ -190,7 +191,7 @@ pub struct SystemStatus {
 }
 
 /// System status data
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemStatusData {
     /// API version
     pub api_version: String,
@@ -203,7 +204,7 @@ pub struct SystemStatusData {
 }
 
 /// Heartbeat message
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Heartbeat {
     /// Channel name
     pub channel: String,
@@ -227,6 +228,7 @@ pub enum KrakyMessage {
         channel: String,
         symbol: Option<String>,
         error: Option<String>,
+        req_id: Option<u64>,
     },
     /// Orderbook update
     #[cfg(feature = "orderbook")]
@@ -244,98 +246,673 @@ pub enum KrakyMessage {
     Unknown(serde_json::Value),
 }
 
+/// Routing fields of an incoming message
+///
+/// Deserialized first to pick the target type; every other field is skipped
+/// without allocating, and the strings borrow from the frame unless escaped.
+///
+/// Fields of an unexpected type read as missing instead of failing the frame.
+#[derive(Deserialize)]
+struct Envelope<'a> {
+    #[serde(borrow, default, deserialize_with = "lenient::string")]
+    channel: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "lenient::string")]
+    method: Option<Cow<'a, str>>,
+    #[serde(default, deserialize_with = "lenient::u64")]
+    req_id: Option<u64>,
+}
+
+/// Subscribe/unsubscribe acknowledgement
+#[derive(Deserialize)]
+struct SubscriptionAck<'a> {
+    #[serde(default, deserialize_with = "lenient::bool")]
+    success: bool,
+    #[serde(borrow, default, deserialize_with = "lenient::string")]
+    error: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "lenient::object")]
+    result: Option<SubscriptionAckResult<'a>>,
+}
+
+#[derive(Deserialize)]
+struct SubscriptionAckResult<'a> {
+    #[serde(borrow, default, deserialize_with = "lenient::string")]
+    channel: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "lenient::string")]
+    symbol: Option<Cow<'a, str>>,
+}
+
+/// Deserializers for routing fields that skip values of an unexpected type
+mod lenient {
+    use serde::de::{self, Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+    use std::borrow::Cow;
+    use std::fmt;
+
+    /// A JSON value reduced to the shapes routing fields use
+    enum Scalar<'a> {
+        Str(Cow<'a, str>),
+        U64(u64),
+        Bool(bool),
+        Other,
+    }
+
+    struct ScalarVisitor;
+
+    impl<'de> Visitor<'de> for ScalarVisitor {
+        type Value = Scalar<'de>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("any JSON value")
+        }
+
+        fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+            Ok(Scalar::Str(Cow::Borrowed(v)))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            Ok(Scalar::Str(Cow::Owned(v.to_string())))
+        }
+
+        fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+            Ok(Scalar::Str(Cow::Owned(v)))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(Scalar::U64(v))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(u64::try_from(v).map_or(Scalar::Other, Scalar::U64))
+        }
+
+        fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+            Ok(Scalar::Other)
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+            Ok(Scalar::Bool(v))
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(Scalar::Other)
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(Scalar::Other)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            d.deserialize_any(self)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            while seq.next_element::<IgnoredAny>()?.is_some() {}
+            Ok(Scalar::Other)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+            Ok(Scalar::Other)
+        }
+    }
+
+    /// A string, or `None`
+    pub(super) fn string<'de: 'a, 'a, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<Cow<'a, str>>, D::Error> {
+        Ok(match d.deserialize_any(ScalarVisitor)? {
+            Scalar::Str(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    /// An unsigned integer, or `None`
+    pub(super) fn u64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<u64>, D::Error> {
+        Ok(match d.deserialize_any(ScalarVisitor)? {
+            Scalar::U64(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    /// A boolean, or `false`
+    pub(super) fn bool<'de, D: Deserializer<'de>>(d: D) -> Result<bool, D::Error> {
+        Ok(matches!(
+            d.deserialize_any(ScalarVisitor)?,
+            Scalar::Bool(true)
+        ))
+    }
+
+    /// An object deserialized as `T`, or `None` for other values
+    pub(super) fn object<'de, D, T>(d: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        struct ObjectVisitor<T>(std::marker::PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for ObjectVisitor<T> {
+            type Value = Option<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("any JSON value")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                T::deserialize(de::value::MapAccessDeserializer::new(map)).map(Some)
+            }
+
+            fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+                d.deserialize_any(self)
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(None)
+            }
+
+            fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_u64<E: de::Error>(self, _: u64) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_i64<E: de::Error>(self, _: i64) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_str<E: de::Error>(self, _: &str) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+        }
+
+        d.deserialize_any(ObjectVisitor(std::marker::PhantomData))
+    }
+}
+
+/// A parsed frame that can be deserialized into several types in turn
+trait Frame<'de> {
+    fn decode<T: Deserialize<'de>>(&mut self) -> Result<T, serde_json::Error>;
+}
+
+impl<'de> Frame<'de> for &'de str {
+    fn decode<T: Deserialize<'de>>(&mut self) -> Result<T, serde_json::Error> {
+        serde_json::from_str(self)
+    }
+}
+
+/// The simd-json tape is built once; each decode replays it from the start
+#[cfg(feature = "simd")]
+impl<'de> Frame<'de> for simd_json::Deserializer<'de> {
+    fn decode<T: Deserialize<'de>>(&mut self) -> Result<T, serde_json::Error> {
+        self.restart();
+        T::deserialize(self).map_err(simd_error)
+    }
+}
+
+#[cfg(feature = "simd")]
+fn simd_error(e: simd_json::Error) -> serde_json::Error {
+    serde_json::Error::io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        e.to_string(),
+    ))
+}
+
 impl KrakyMessage {
     /// Parse a raw JSON message
     ///
-    /// Uses SIMD-accelerated parsing when the `simd` feature is enabled.
+    /// Reads the `method`/`channel` routing fields first, then deserializes
+    /// the frame straight into the matching typed model. Only unrecognized
+    /// messages are built as a [`serde_json::Value`].
+    ///
+    /// With the `simd` feature the frame is tokenized once by simd-json, using
+    /// per-thread buffers, and both steps deserialize from that tape.
     pub fn parse(text: &str) -> Result<Self, serde_json::Error> {
-        // Parse JSON - use SIMD if feature is enabled
         #[cfg(feature = "simd")]
-        let value: serde_json::Value = {
-            let mut bytes = text.as_bytes().to_vec();
-            simd_json::from_slice(&mut bytes).map_err(|e| {
-                serde_json::Error::io(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    e.to_string(),
-                ))
-            })?
-        };
+        {
+            thread_local! {
+                // Per-thread parse buffers, reused across frames
+                static SIMD_BUFFERS: std::cell::RefCell<(Vec<u8>, simd_json::Buffers)> =
+                    std::cell::RefCell::new((Vec::new(), simd_json::Buffers::default()));
+            }
+
+            SIMD_BUFFERS.with(|buffers| {
+                let (bytes, buffers) = &mut *buffers.borrow_mut();
+                bytes.clear();
+                bytes.extend_from_slice(text.as_bytes());
+                let mut frame = simd_json::Deserializer::from_slice_with_buffers(bytes, buffers)
+                    .map_err(simd_error)?;
+                Self::from_frame(&mut frame)
+            })
+        }
 
         #[cfg(not(feature = "simd"))]
-        let value: serde_json::Value = serde_json::from_str(text)?;
-
-        // Check for method responses (pong, subscribe, unsubscribe)
-        if let Some(method) = value.get("method").and_then(|m| m.as_str()) {
-            match method {
-                "pong" => {
-                    let req_id = value.get("req_id").and_then(|r| r.as_u64());
-                    return Ok(KrakyMessage::Pong { req_id });
-                }
-                "subscribe" | "unsubscribe" => {
-                    let success = value
-                        .get("success")
-                        .and_then(|s| s.as_bool())
-                        .unwrap_or(false);
-                    let error = value
-                        .get("error")
-                        .and_then(|e| e.as_str())
-                        .map(String::from);
-                    let result = value.get("result");
-                    let channel = result
-                        .and_then(|r| r.get("channel"))
-                        .and_then(|c| c.as_str())
+        {
+            let mut frame = text;
+            Self::from_frame(&mut frame)
+        }
+    }
+
+    /// Read a frame's top-level `req_id` without deserializing the rest
+    ///
+    /// Decodes just the routing envelope, skipping every other field.
+    pub(crate) fn peek_req_id(text: &str) -> Option<u64> {
+        serde_json::from_str::<Envelope>(text).ok()?.req_id
+    }
+
+    /// Route on the envelope and deserialize directly into typed models
+    fn from_frame<'de>(frame: &mut impl Frame<'de>) -> Result<Self, serde_json::Error> {
+        let envelope: Envelope = frame.decode()?;
+
+        // Method responses (pong, subscribe, unsubscribe)
+        match envelope.method.as_deref() {
+            Some("pong") => {
+                return Ok(KrakyMessage::Pong {
+                    req_id: envelope.req_id,
+                })
+            }
+            Some("subscribe") | Some("unsubscribe") => {
+                let ack: SubscriptionAck = frame.decode()?;
+                let result = ack.result.as_ref();
+                return Ok(KrakyMessage::SubscriptionStatus {
+                    success: ack.success,
+                    channel: result
+                        .and_then(|r| r.channel.as_deref())
                         .unwrap_or("")
-                        .to_string();
-                    let symbol = result
-                        .and_then(|r| r.get("symbol"))
-                        .and_then(|s| s.as_str())
-                        .map(String::from);
-
-                    return Ok(KrakyMessage::SubscriptionStatus {
-                        success,
-                        channel,
-                        symbol,
-                        error,
-                    });
-                }
-                _ => {}
+                        .to_string(),
+                    symbol: result.and_then(|r| r.symbol.as_deref()).map(String::from),
+                    error: ack.error.map(Cow::into_owned),
+                    req_id: envelope.req_id,
+                });
             }
+            _ => {}
         }
 
-        // Check channel-based messages
-        if let Some(channel) = value.get("channel").and_then(|c| c.as_str()) {
-            match channel {
-                "status" => {
-                    let status: SystemStatus = serde_json::from_value(value)?;
-                    return Ok(KrakyMessage::SystemStatus(status));
-                }
-                "heartbeat" => {
-                    return Ok(KrakyMessage::Heartbeat);
+        // Channel-based messages
+        match envelope.channel.as_deref() {
+            Some("status") => return Ok(KrakyMessage::SystemStatus(frame.decode()?)),
+            Some("heartbeat") => return Ok(KrakyMessage::Heartbeat),
+            #[cfg(feature = "orderbook")]
+            Some("book") => return Ok(KrakyMessage::Orderbook(frame.decode()?)),
+            #[cfg(feature = "trades")]
+            Some("trade") => return Ok(KrakyMessage::Trade(frame.decode()?)),
+            #[cfg(feature = "ticker")]
+            Some("ticker") => return Ok(KrakyMessage::Ticker(frame.decode()?)),
+            #[cfg(feature = "ohlc")]
+            Some("ohlc") => return Ok(KrakyMessage::OHLC(frame.decode()?)),
+            _ => {}
+        }
+
+        Ok(KrakyMessage::Unknown(frame.decode()?))
+    }
+}
+
+/// A frame the client could not turn into a typed message
+///
+/// Delivered by [`KrakyClient::subscribe_diagnostics`] in strict mode, for
+/// frames that fail to parse and for frames parsed as
+/// [`KrakyMessage::Unknown`].
+///
+/// [`KrakyClient::subscribe_diagnostics`]: crate::KrakyClient::subscribe_diagnostics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDiagnostic {
+    /// Raw frame text
+    pub raw: String,
+    /// Why the frame was rejected
+    pub error: String,
+    /// Channel (or method) named by the frame, if it is valid JSON
+    pub channel: Option<String>,
+    /// Symbols named in the frame's data
+    pub symbols: Vec<String>,
+    /// Byte offset in the frame where deserialization failed, if known
+    ///
+    /// Not known with the `simd` feature.
+    pub offset: Option<usize>,
+    /// Path of the value that failed to deserialize, e.g. `data[0].price`
+    pub path: Option<String>,
+}
+
+impl ParseDiagnostic {
+    /// Describe a rejected frame, reading its routing fields if possible
+    pub(crate) fn new(raw: &str, error: String) -> Self {
+        let json: Option<serde_json::Value> = serde_json::from_str(raw).ok();
+        let channel = json.as_ref().and_then(|json| {
+            json["channel"]
+                .as_str()
+                .or_else(|| json["method"].as_str())
+                .map(String::from)
+        });
+        let symbols = match json.as_ref().map(|json| &json["data"]) {
+            Some(serde_json::Value::Array(data)) => data
+                .iter()
+                .filter_map(|item| item["symbol"].as_str().map(String::from))
+                .collect(),
+            Some(data) => data["symbol"]
+                .as_str()
+                .map(String::from)
+                .into_iter()
+                .collect(),
+            None => Vec::new(),
+        };
+        Self {
+            raw: raw.to_string(),
+            error,
+            channel,
+            symbols,
+            offset: None,
+            path: None,
+        }
+    }
+
+    /// Describe a frame that failed to deserialize, locating the failure
+    pub(crate) fn from_error(raw: &str, error: &serde_json::Error) -> Self {
+        let mut diagnostic = Self::new(raw, error.to_string());
+        diagnostic.offset = error_offset(raw, error);
+        diagnostic.path = diagnostic
+            .offset
+            .map(|offset| json_path_at(raw, offset))
+            .filter(|path| !path.is_empty());
+        diagnostic
+    }
+
+    /// Get the diagnostic as a [`KrakyError::Parse`]
+    ///
+    /// [`KrakyError::Parse`]: crate::KrakyError::Parse
+    pub fn to_error(&self) -> crate::error::KrakyError {
+        crate::error::KrakyError::Parse {
+            channel: self.channel.clone(),
+            symbol: self.symbols.first().cloned(),
+            offset: self.offset,
+            path: self.path.clone(),
+            message: self.error.clone(),
+        }
+    }
+}
+
+/// Byte offset of a deserialization error, from its line and column
+///
+/// Errors without a position, such as those raised after parsing, have
+/// line 0.
+fn error_offset(raw: &str, error: &serde_json::Error) -> Option<usize> {
+    if error.line() == 0 {
+        return None;
+    }
+    let line_start: usize = raw
+        .split_inclusive('\n')
+        .take(error.line() - 1)
+        .map(str::len)
+        .sum();
+    Some((line_start + error.column().saturating_sub(1)).min(raw.len().saturating_sub(1)))
+}
+
+/// Path of the JSON value being read at `offset`, e.g. `data[0].price`
+///
+/// Scans the text up to the offset, tracking the keys and indices of the
+/// enclosing objects and arrays. The text doesn't need to be valid JSON.
+fn json_path_at(raw: &str, offset: usize) -> String {
+    /// An object (with the key being read) or an array (with its index)
+    enum Scope {
+        Object { key: Option<String>, in_value: bool },
+        Array(usize),
+    }
+
+    let mut scopes: Vec<Scope> = Vec::new();
+    let mut string: Option<Vec<u8>> = None;
+    let mut escaped = false;
+    for &byte in raw.as_bytes().iter().take(offset + 1) {
+        if let Some(buffer) = &mut string {
+            match byte {
+                _ if escaped => {
+                    escaped = false;
+                    buffer.push(byte);
                 }
-                #[cfg(feature = "orderbook")]
-                "book" => {
-                    let update: crate::models::OrderbookUpdate = serde_json::from_value(value)?;
-                    return Ok(KrakyMessage::Orderbook(update));
+                b'\\' => escaped = true,
+                b'"' => {
+                    let text = String::from_utf8_lossy(buffer).into_owned();
+                    string = None;
+                    if let Some(Scope::Object {
+                        key,
+                        in_value: false,
+                    }) = scopes.last_mut()
+                    {
+                        *key = Some(text);
+                    }
                 }
-                #[cfg(feature = "trades")]
-                "trade" => {
-                    let update: crate::models::TradeUpdate = serde_json::from_value(value)?;
-                    return Ok(KrakyMessage::Trade(update));
+                _ => buffer.push(byte),
+            }
+            continue;
+        }
+        match byte {
+            b'"' => string = Some(Vec::new()),
+            b'{' => scopes.push(Scope::Object {
+                key: None,
+                in_value: false,
+            }),
+            b'[' => scopes.push(Scope::Array(0)),
+            b'}' | b']' => {
+                scopes.pop();
+            }
+            b':' => {
+                if let Some(Scope::Object { in_value, .. }) = scopes.last_mut() {
+                    *in_value = true;
                 }
-                #[cfg(feature = "ticker")]
-                "ticker" => {
-                    let update: crate::models::TickerUpdate = serde_json::from_value(value)?;
-                    return Ok(KrakyMessage::Ticker(update));
+            }
+            b',' => match scopes.last_mut() {
+                Some(Scope::Object { key, in_value }) => {
+                    *key = None;
+                    *in_value = false;
                 }
-                #[cfg(feature = "ohlc")]
-                "ohlc" => {
-                    let update: crate::models::OHLCUpdate = serde_json::from_value(value)?;
-                    return Ok(KrakyMessage::OHLC(update));
+                Some(Scope::Array(index)) => *index += 1,
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    let mut path = String::new();
+    for scope in &scopes {
+        match scope {
+            Scope::Object { key: Some(key), .. } => {
+                if !path.is_empty() {
+                    path.push('.');
                 }
-                _ => {}
+                path.push_str(key);
+            }
+            Scope::Object { key: None, .. } => break,
+            Scope::Array(index) => path.push_str(&format!("[{}]", index)),
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_method_responses() {
+        let pong = KrakyMessage::parse(r#"{"method":"pong","req_id":7,"time_in":"x"}"#).unwrap();
+        assert!(matches!(pong, KrakyMessage::Pong { req_id: Some(7) }));
+
+        let ack = KrakyMessage::parse(
+            r#"{"method":"subscribe","req_id":3,"result":{"channel":"trade","symbol":"BTC/USD","snapshot":true},"success":true,"time_in":"x","time_out":"y"}"#,
+        )
+        .unwrap();
+        match ack {
+            KrakyMessage::SubscriptionStatus {
+                success,
+                channel,
+                symbol,
+                error,
+                req_id,
+            } => {
+                assert!(success);
+                assert_eq!(channel, "trade");
+                assert_eq!(symbol.as_deref(), Some("BTC/USD"));
+                assert_eq!(error, None);
+                assert_eq!(req_id, Some(3));
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        let failed = KrakyMessage::parse(
+            r#"{"method":"subscribe","error":"Currency pair not supported \"FOO/BAR\"","success":false}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            failed,
+            KrakyMessage::SubscriptionStatus { success: false, error: Some(ref e), .. }
+                if e == "Currency pair not supported \"FOO/BAR\""
+        ));
+    }
+
+    #[test]
+    fn test_parse_tolerates_odd_field_types() {
+        // A string req_id is ignored, as before typed routing
+        let ack = KrakyMessage::parse(
+            r#"{"method":"subscribe","req_id":"abc","result":{"channel":"book","symbol":["BTC/USD"]},"success":true}"#,
+        )
+        .unwrap();
+        match ack {
+            KrakyMessage::SubscriptionStatus {
+                success,
+                channel,
+                symbol,
+                req_id,
+                ..
+            } => {
+                assert!(success);
+                assert_eq!(channel, "book");
+                assert_eq!((symbol, req_id), (None, None));
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        let ack = KrakyMessage::parse(
+            r#"{"method":"unsubscribe","req_id":-1,"result":"gone","success":"yes","error":{"code":1}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            ack,
+            KrakyMessage::SubscriptionStatus {
+                success: false,
+                error: None,
+                req_id: None,
+                ..
+            }
+        ));
+
+        // A method that isn't a string routes on the channel
+        assert!(matches!(
+            KrakyMessage::parse(r#"{"method":7,"channel":"heartbeat"}"#).unwrap(),
+            KrakyMessage::Heartbeat
+        ));
+    }
+
+    #[test]
+    fn test_peek_req_id() {
+        let peek = KrakyMessage::peek_req_id;
+        assert_eq!(
+            peek(r#"{"method":"pong","req_id":42,"time_in":"x"}"#),
+            Some(42)
+        );
+        assert_eq!(peek(r#"{"channel":"heartbeat"}"#), None);
+        assert_eq!(peek(r#"{"method":"pong","req_id":"42"}"#), None);
+        // Only the top-level field counts
+        assert_eq!(peek(r#"{"data":{"req_id":7},"note":"\"req_id\":8"}"#), None);
+        assert_eq!(peek("not json"), None);
+    }
+
+    #[test]
+    fn test_parse_channels() {
+        assert!(matches!(
+            KrakyMessage::parse(r#"{"channel":"heartbeat"}"#).unwrap(),
+            KrakyMessage::Heartbeat
+        ));
+        assert!(matches!(
+            KrakyMessage::parse(r#"{"channel":"instrument","type":"snapshot","data":{}}"#).unwrap(),
+            KrakyMessage::Unknown(_)
+        ));
+        assert!(KrakyMessage::parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_diagnostic() {
+        let diagnostic = ParseDiagnostic::new(
+            r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":"x"},{"symbol":"ETH/USD"}]}"#,
+            "invalid type".to_string(),
+        );
+        assert_eq!(diagnostic.channel.as_deref(), Some("book"));
+        assert_eq!(diagnostic.symbols, ["BTC/USD", "ETH/USD"]);
+        assert_eq!(diagnostic.error, "invalid type");
+
+        let diagnostic = ParseDiagnostic::new("not json", "expected value".to_string());
+        assert_eq!((diagnostic.channel, diagnostic.symbols.len()), (None, 0));
+        assert_eq!(diagnostic.raw, "not json");
+    }
+
+    // simd-json errors don't carry a position
+    #[cfg(all(feature = "trades", not(feature = "simd")))]
+    #[test]
+    fn test_parse_error_context() {
+        let text = r#"{"channel":"trade","type":"update","data":[{"symbol":"BTC/USD","side":"buy","price":42000.5,"qty":0.01,"ord_type":"market","trade_id":1,"timestamp":"t"},{"symbol":"BTC/USD","side":"buy","price":"oops","qty":0.01,"ord_type":"market","trade_id":2,"timestamp":"t"}]}"#;
+        let error = KrakyMessage::parse(text).unwrap_err();
+        let diagnostic = ParseDiagnostic::from_error(text, &error);
+        assert_eq!(diagnostic.path.as_deref(), Some("data[1].price"));
+        let offset = diagnostic.offset.unwrap();
+        assert!(text[..=offset].ends_with(r#""price":"oops""#));
+
+        match diagnostic.to_error() {
+            error @ crate::error::KrakyError::Parse { .. } => {
+                let message = error.to_string();
+                assert!(message.contains("trade message for BTC/USD"), "{}", message);
+                assert!(message.contains("data[1].price"), "{}", message);
             }
+            other => panic!("unexpected error: {:?}", other),
         }
 
-        Ok(KrakyMessage::Unknown(value))
+        assert_eq!(json_path_at(r#"{"a":{"b":[1,{"c":"#, 17), "a.b[1].c");
+        assert_eq!(json_path_at(r#"{"a":1,"#, 6), "");
+    }
+
+    #[cfg(feature = "trades")]
+    #[test]
+    fn test_parse_trade() {
+        let text = r#"{"channel":"trade","type":"update","data":[{"symbol":"BTC/USD","side":"buy","price":42000.5,"qty":0.01,"ord_type":"market","trade_id":123,"timestamp":"2024-01-15T10:00:00.000000Z"}]}"#;
+        match KrakyMessage::parse(text).unwrap() {
+            KrakyMessage::Trade(update) => {
+                assert_eq!(update.data.len(), 1);
+                assert_eq!(update.data[0].price, 42000.5);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_parse_book() {
+        let text = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[{"price":42000.1,"qty":1.5}],"asks":[{"price":"42001.0","qty":"0.25"}],"checksum":2439117997,"timestamp":"2024-01-15T10:00:00.000000Z"}]}"#;
+        match KrakyMessage::parse(text).unwrap() {
+            KrakyMessage::Orderbook(update) => {
+                let data = &update.data[0];
+                assert_eq!(data.symbol, "BTC/USD");
+                assert_eq!((data.bids[0].price, data.bids[0].qty), (42000.1, 1.5));
+                assert_eq!((data.asks[0].price, data.asks[0].qty), (42001.0, 0.25));
+                assert_eq!(data.checksum, 2439117997);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
     }
 }
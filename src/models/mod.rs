@@ -66,6 +66,23 @@
 //! - [`ImbalanceMetrics`] - Bid/ask volume metrics
 //! - [`ImbalanceSignal`] - Bullish, Bearish, Neutral signals
 //!
+//! # Serialization
+//!
+//! Every model, along with the other data the crate hands out (connection
+//! events and health, subscription stats snapshots, analytics results,
+//! REST history and backtest reports), implements `Serialize` and
+//! `Deserialize`, so it can be persisted and republished as is:
+//!
+//! - Field names are the Rust field names documented on each type, in
+//!   `snake_case`; renaming one is treated as a breaking change
+//! - Enum variants are `snake_case` strings, except where the type mirrors
+//!   a Kraken value (e.g. `"buy"`, or an [`Interval`] as `"5"`)
+//! - Prices and quantities are `f64` and serialize as JSON numbers, so
+//!   amounts round-trip exactly but may not match Kraken's decimal strings
+//!   digit for digit
+//! - Timestamps keep the RFC 3339 strings Kraken sent; durations use
+//!   serde's `{"secs", "nanos"}` form
+//!
 //! # Example Usage
 //!
 //! ```no_run
@@ -1,9 +1,29 @@
 //! OHLC (candlestick) data types
 
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
 
 /// OHLC time interval
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+///
+/// Covers every interval Kraken supports. Parses from the names used by
+/// [`Display`](std::fmt::Display) (`"5m"`, `"4h"`, `"1d"`) as well as from
+/// plain minutes (`"240"`), and deserializes from either form or from a
+/// number, so intervals can be read straight from config files.
+///
+/// # Example
+///
+/// ```
+/// use kraky::Interval;
+/// use std::time::Duration;
+///
+/// let interval: Interval = "4h".parse().unwrap();
+/// assert_eq!(interval, Interval::Hour4);
+/// assert_eq!(Interval::try_from(240).unwrap(), Interval::Hour4);
+/// assert_eq!(interval.as_duration(), Duration::from_secs(4 * 3600));
+/// ```
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Hash)]
 pub enum Interval {
     /// 1 minute
     #[serde(rename = "1")]
@@ -35,11 +55,29 @@ pub enum Interval {
 }
 
 impl Interval {
+    /// Every interval Kraken supports, shortest first
+    pub const ALL: [Interval; 9] = [
+        Interval::Min1,
+        Interval::Min5,
+        Interval::Min15,
+        Interval::Min30,
+        Interval::Hour1,
+        Interval::Hour4,
+        Interval::Day1,
+        Interval::Week1,
+        Interval::Day15,
+    ];
+
     /// Get the interval value in minutes
     pub fn minutes(&self) -> u32 {
         *self as u32
     }
 
+    /// Get the length of the interval
+    pub fn as_duration(&self) -> Duration {
+        Duration::from_secs(u64::from(self.minutes()) * 60)
+    }
+
     /// Convert to Kraken API string representation
     pub fn to_api_string(&self) -> String {
         self.minutes().to_string()
@@ -62,6 +100,85 @@ impl std::fmt::Display for Interval {
     }
 }
 
+/// Error returned when a value is not a supported interval
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("unsupported interval: {0}")]
+pub struct ParseIntervalError(String);
+
+impl TryFrom<u32> for Interval {
+    type Error = ParseIntervalError;
+
+    /// Convert from minutes
+    fn try_from(minutes: u32) -> Result<Self, Self::Error> {
+        Interval::ALL
+            .into_iter()
+            .find(|interval| interval.minutes() == minutes)
+            .ok_or_else(|| ParseIntervalError(format!("{} minutes", minutes)))
+    }
+}
+
+impl FromStr for Interval {
+    type Err = ParseIntervalError;
+
+    /// Parse `"5m"`, `"1h"`, `"1d"`, `"1w"` style names or plain minutes
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseIntervalError(s.to_string());
+        let value = s.trim().to_ascii_lowercase();
+        let split = value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len());
+        let (count, unit) = value.split_at(split);
+        let count: u32 = count.parse().map_err(|_| invalid())?;
+        let scale = match unit.trim() {
+            "" | "m" | "min" => 1,
+            "h" => 60,
+            "d" => 1440,
+            "w" => 10080,
+            _ => return Err(invalid()),
+        };
+        let minutes = count.checked_mul(scale).ok_or_else(invalid)?;
+        Interval::try_from(minutes).map_err(|_| invalid())
+    }
+}
+
+impl<'de> Deserialize<'de> for Interval {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::{self, Visitor};
+
+        struct IntervalVisitor;
+
+        impl<'de> Visitor<'de> for IntervalVisitor {
+            type Value = Interval;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("an interval in minutes or a name like \"5m\"")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Interval, E> {
+                u32::try_from(value)
+                    .ok()
+                    .and_then(|minutes| Interval::try_from(minutes).ok())
+                    .ok_or_else(|| E::custom(format!("unsupported interval: {} minutes", value)))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Interval, E> {
+                let value = u64::try_from(value)
+                    .map_err(|_| E::custom(format!("unsupported interval: {}", value)))?;
+                self.visit_u64(value)
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Interval, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(IntervalVisitor)
+    }
+}
+
 /// OHLC candlestick data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OHLC {
@@ -87,6 +204,102 @@ pub struct OHLC {
     pub timestamp: String,
     /// Interval begin timestamp
     pub interval_begin: String,
+    /// Whether this candle was filled in for an interval without trades
+    #[serde(default)]
+    pub synthetic: bool,
+}
+
+/// Orders candles and fills the intervals between them
+///
+/// Kraken only publishes a candle for intervals with trades, and a
+/// reconnect loses the candles that closed while disconnected. The filler
+/// takes candles oldest first, passes updates to the current candle
+/// through, drops candles older than the current one, and inserts a flat
+/// [`synthetic`](OHLC::synthetic) candle at the previous close for each
+/// missing interval, so consecutive candles are always one interval apart.
+///
+/// # Example
+///
+/// ```
+/// use kraky::{CandleGapFiller, Interval};
+///
+/// let mut filler = CandleGapFiller::new(Interval::Min1);
+/// # let candles: Vec<kraky::OHLC> = Vec::new();
+/// for candle in candles {
+///     for candle in filler.push(candle) {
+///         println!("{} close {} synthetic {}", candle.interval_begin, candle.close, candle.synthetic);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CandleGapFiller {
+    width: chrono::Duration,
+    last: Option<(chrono::DateTime<chrono::Utc>, OHLC)>,
+}
+
+impl CandleGapFiller {
+    /// Create a filler for candles of one interval
+    pub fn new(interval: Interval) -> Self {
+        Self {
+            width: chrono::Duration::minutes(interval.minutes() as i64),
+            last: None,
+        }
+    }
+
+    /// Take the next candle and return the candles to emit, oldest first
+    ///
+    /// Candles with an unparseable `interval_begin` are passed through
+    /// unchanged.
+    pub fn push(&mut self, candle: OHLC) -> Vec<OHLC> {
+        let Some(begin) = parse_time(&candle.interval_begin) else {
+            return vec![candle];
+        };
+
+        let mut candles = Vec::new();
+        if let Some((last_begin, last)) = &self.last {
+            if begin < *last_begin {
+                return candles;
+            }
+            let mut next = *last_begin + self.width;
+            while next < begin {
+                candles.push(Self::synthetic(last, next, self.width));
+                next += self.width;
+            }
+        }
+        self.last = Some((begin, candle.clone()));
+        candles.push(candle);
+        candles
+    }
+
+    fn synthetic(
+        previous: &OHLC,
+        begin: chrono::DateTime<chrono::Utc>,
+        width: chrono::Duration,
+    ) -> OHLC {
+        let format = |time: chrono::DateTime<chrono::Utc>| {
+            time.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+        };
+        OHLC {
+            symbol: previous.symbol.clone(),
+            open: previous.close,
+            high: previous.close,
+            low: previous.close,
+            close: previous.close,
+            vwap: previous.close,
+            volume: 0.0,
+            count: 0,
+            interval: previous.interval,
+            timestamp: format(begin + width),
+            interval_begin: format(begin),
+            synthetic: true,
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&chrono::Utc))
 }
 
 /// Deserialize a value that could be either a number or a string representation of a number
@@ -178,6 +391,7 @@ impl OHLCDataRaw {
             interval: self.interval,
             timestamp: self.timestamp.clone(),
             interval_begin: self.interval_begin.clone(),
+            synthetic: false,
         }
     }
 }
@@ -194,3 +408,80 @@ pub struct OHLCUpdate {
     /// OHLC data
     pub data: Vec<OHLCDataRaw>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_parsing() {
+        for interval in Interval::ALL {
+            assert_eq!(interval.to_string().parse::<Interval>(), Ok(interval));
+            assert_eq!(interval.to_api_string().parse::<Interval>(), Ok(interval));
+            assert_eq!(Interval::try_from(interval.minutes()), Ok(interval));
+        }
+        assert_eq!(" 60M ".parse::<Interval>(), Ok(Interval::Hour1));
+        assert_eq!("1min".parse::<Interval>(), Ok(Interval::Min1));
+        assert!("2h".parse::<Interval>().is_err());
+        assert!("5x".parse::<Interval>().is_err());
+        assert!(Interval::try_from(2).is_err());
+        assert_eq!(Interval::Day1.as_duration(), Duration::from_secs(86_400));
+    }
+
+    #[test]
+    fn test_interval_deserialize() {
+        let parse = |json: &str| serde_json::from_str::<Interval>(json);
+        assert_eq!(parse("5").unwrap(), Interval::Min5);
+        assert_eq!(parse("\"15\"").unwrap(), Interval::Min15);
+        assert_eq!(parse("\"1w\"").unwrap(), Interval::Week1);
+        assert!(parse("7").is_err());
+        assert_eq!(serde_json::to_string(&Interval::Hour4).unwrap(), "\"240\"");
+    }
+
+    fn candle(minute: u32, close: f64) -> OHLC {
+        OHLC {
+            symbol: "BTC/USD".to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            vwap: close,
+            volume: 1.0,
+            count: 1,
+            interval: 1,
+            timestamp: String::new(),
+            interval_begin: format!("2024-01-15T10:{:02}:00.000000000Z", minute),
+            synthetic: false,
+        }
+    }
+
+    #[test]
+    fn test_gap_filler_fills_missing_intervals() {
+        let mut filler = CandleGapFiller::new(Interval::Min1);
+        assert_eq!(filler.push(candle(0, 100.0)).len(), 1);
+
+        let filled = filler.push(candle(3, 103.0));
+        assert_eq!(filled.len(), 3);
+        assert!(filled[0].synthetic && filled[1].synthetic);
+        assert_eq!(filled[0].interval_begin, "2024-01-15T10:01:00.000000000Z");
+        assert_eq!(filled[1].close, 100.0);
+        assert_eq!(filled[1].volume, 0.0);
+        assert!(!filled[2].synthetic);
+        assert_eq!(filled[2].close, 103.0);
+    }
+
+    #[test]
+    fn test_gap_filler_updates_and_stale_candles() {
+        let mut filler = CandleGapFiller::new(Interval::Min1);
+        filler.push(candle(5, 100.0));
+
+        // Updates to the forming candle pass through, older candles are dropped
+        assert_eq!(filler.push(candle(5, 101.0))[0].close, 101.0);
+        assert!(filler.push(candle(4, 99.0)).is_empty());
+
+        // REST timestamps use microseconds, WebSocket ones nanoseconds
+        let mut next = candle(0, 102.0);
+        next.interval_begin = "2024-01-15T10:06:00.000000Z".to_string();
+        assert_eq!(filler.push(next).len(), 1);
+    }
+}
@@ -127,6 +127,55 @@ impl PriceLevelRaw {
     }
 }
 
+impl OrderbookUpdate {
+    /// Fold a newer update into this one
+    ///
+    /// Applying the merged update has the same effect as applying both in
+    /// order: a newer snapshot replaces this update and a newer delta
+    /// overrides the levels it touches. Zero-quantity levels are kept in
+    /// deltas, where they remove the level, and dropped from snapshots.
+    pub fn merge(&mut self, newer: &OrderbookUpdate) {
+        if newer.update_type == OrderbookUpdateType::Snapshot {
+            *self = newer.clone();
+            return;
+        }
+        let snapshot = self.update_type == OrderbookUpdateType::Snapshot;
+        for data in &newer.data {
+            match self.data.iter_mut().find(|d| d.symbol == data.symbol) {
+                Some(merged) => merged.merge(data, snapshot),
+                None => self.data.push(data.clone()),
+            }
+        }
+    }
+}
+
+impl OrderbookData {
+    /// Fold newer levels into this payload
+    fn merge(&mut self, newer: &OrderbookData, snapshot: bool) {
+        merge_levels(&mut self.bids, &newer.bids, snapshot);
+        merge_levels(&mut self.asks, &newer.asks, snapshot);
+        if snapshot {
+            self.bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+            self.asks.sort_by(|a, b| a.price.total_cmp(&b.price));
+        }
+        self.checksum = newer.checksum;
+        self.timestamp = newer.timestamp.clone();
+    }
+}
+
+/// Upsert `newer` levels by price, removing emptied ones from snapshots
+fn merge_levels(levels: &mut Vec<PriceLevelRaw>, newer: &[PriceLevelRaw], snapshot: bool) {
+    for level in newer {
+        match levels.iter_mut().find(|l| l.price == level.price) {
+            Some(existing) => existing.qty = level.qty,
+            None => levels.push(level.clone()),
+        }
+    }
+    if snapshot {
+        levels.retain(|l| l.qty != 0.0);
+    }
+}
+
 /// Managed orderbook state
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Orderbook {
@@ -148,6 +197,12 @@ pub struct Orderbook {
     #[cfg(feature = "checksum")]
     #[serde(default = "default_checksum_valid")]
     pub checksum_valid: bool,
+    /// Decimal places of the pair's prices and quantities, for checksums
+    ///
+    /// Checksums are only validated once this is set.
+    #[cfg(feature = "checksum")]
+    #[serde(default)]
+    pub precision: Option<BookPrecision>,
 }
 
 #[cfg(feature = "checksum")]
@@ -155,6 +210,30 @@ fn default_checksum_valid() -> bool {
     true
 }
 
+/// Decimal places Kraken quotes a pair's prices and quantities with
+///
+/// Part of the pair's instrument metadata (`price_precision` and
+/// `qty_precision` in the WebSocket instrument channel, `pair_decimals` and
+/// `lot_decimals` in the REST `AssetPairs` endpoint).
+///
+/// Only available when the `checksum` feature is enabled.
+#[cfg(feature = "checksum")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookPrecision {
+    /// Price decimals
+    pub price: u32,
+    /// Quantity decimals
+    pub qty: u32,
+}
+
+#[cfg(feature = "checksum")]
+impl BookPrecision {
+    /// Create a precision from price and quantity decimals
+    pub fn new(price: u32, qty: u32) -> Self {
+        Self { price, qty }
+    }
+}
+
 /// Wrapper for f64 that implements Ord for use in BTreeMap
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct OrderedFloat(pub f64);
@@ -200,6 +279,23 @@ impl Orderbook {
             last_checksum: 0,
             #[cfg(feature = "checksum")]
             checksum_valid: true,
+            #[cfg(feature = "checksum")]
+            precision: None,
+        }
+    }
+
+    /// Drop all levels and checksum state, keeping the symbol and precision
+    ///
+    /// Used before applying a fresh snapshot.
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.timestamp.clear();
+        self.sequence = 0;
+        #[cfg(feature = "checksum")]
+        {
+            self.last_checksum = 0;
+            self.checksum_valid = true;
         }
     }
 
@@ -230,7 +326,11 @@ impl Orderbook {
         #[cfg(feature = "checksum")]
         if data.checksum != 0 {
             self.last_checksum = data.checksum;
-            self.checksum_valid = self.validate_checksum(data.checksum);
+            // Without the pair's precision the levels can't be formatted the
+            // way Kraken does, so a mismatch would mean nothing
+            if self.precision.is_some() {
+                self.checksum_valid = self.validate_checksum(data.checksum);
+            }
         }
     }
 
@@ -241,7 +341,8 @@ impl Orderbook {
     ///
     /// # Returns
     ///
-    /// `true` if the checksum is valid (or not provided), `false` if corrupted.
+    /// `true` if the checksum is valid (or not provided or not validated,
+    /// see [`Orderbook::precision`]), `false` if corrupted.
     /// Always returns `true` when the `checksum` feature is disabled.
     #[cfg(feature = "checksum")]
     pub fn apply_update_validated(&mut self, data: &OrderbookData) -> bool {
@@ -311,6 +412,16 @@ impl Orderbook {
         }
     }
 
+    /// Get the spread in basis points relative to the mid price
+    pub fn spread_bps(&self) -> Option<f64> {
+        let spread = self.spread()?;
+        let mid = self.mid_price()?;
+        if mid == 0.0 {
+            return None;
+        }
+        Some((spread / mid) * 10000.0)
+    }
+
     /// Calculate total bid volume
     pub fn total_bid_volume(&self) -> f64 {
         self.bids.values().sum()
@@ -436,25 +547,34 @@ impl Orderbook {
     ///
     /// Kraken's checksum algorithm:
     /// 1. Take top 10 asks (sorted ascending) and top 10 bids (sorted descending)
-    /// 2. For each level: format price and qty by removing decimal point and leading zeros
+    /// 2. For each level: format price and qty to the pair's precision, then
+    ///    remove the decimal point and leading zeros
     /// 3. Concatenate: asks first (price+qty for each), then bids
     /// 4. Calculate CRC32 of the resulting string
     ///
+    /// Uses [`Orderbook::precision`] when set. Otherwise levels are written
+    /// in their shortest form, which only matches Kraken when no price or
+    /// quantity ends in zeros; updates are not validated in that case.
+    ///
     /// Only available when the `checksum` feature is enabled.
     #[cfg(feature = "checksum")]
     pub fn calculate_checksum(&self) -> u32 {
-        let mut data = String::new();
+        let asks = self.asks.iter().take(10);
+        let bids = self.bids.iter().rev().take(10);
 
-        // Top 10 asks (lowest prices first - ascending order)
-        for (price, qty) in self.asks.iter().take(10) {
-            data.push_str(&Self::format_for_checksum(price.0));
-            data.push_str(&Self::format_for_checksum(*qty));
-        }
-
-        // Top 10 bids (highest prices first - descending order)
-        for (price, qty) in self.bids.iter().rev().take(10) {
-            data.push_str(&Self::format_for_checksum(price.0));
-            data.push_str(&Self::format_for_checksum(*qty));
+        // Asks lowest first, then bids highest first
+        let mut data = String::new();
+        for (price, qty) in asks.chain(bids) {
+            match self.precision {
+                Some(precision) => {
+                    data.push_str(&Self::format_to_precision(price.0, precision.price));
+                    data.push_str(&Self::format_to_precision(*qty, precision.qty));
+                }
+                None => {
+                    data.push_str(&Self::format_for_checksum(price.0));
+                    data.push_str(&Self::format_for_checksum(*qty));
+                }
+            }
         }
 
         crc32fast::hash(data.as_bytes())
@@ -496,26 +616,43 @@ impl Orderbook {
 
     /// Format a number for checksum calculation
     ///
-    /// Removes decimal point and leading zeros.
-    /// Example: 0.00123400 -> "123400", 50000.0 -> "500000"
+    /// Removes decimal point, leading and trailing zeros.
+    /// Example: 0.001234 -> "1234", 50000.0 -> "5"
     #[cfg(feature = "checksum")]
     fn format_for_checksum(value: f64) -> String {
         // Format with enough precision to capture all significant digits
         let formatted = format!("{:.10}", value);
+        let digits = strip_for_checksum(&formatted);
+        // Also remove trailing zeros after we've removed the decimal
+        match digits.trim_end_matches('0') {
+            "" => "0".to_string(),
+            trimmed => trimmed.to_string(),
+        }
+    }
 
-        // Remove the decimal point
-        let without_decimal = formatted.replace('.', "");
-
-        // Remove leading zeros
-        let trimmed = without_decimal.trim_start_matches('0');
+    /// Format a number to the pair's decimals for checksum calculation
+    ///
+    /// Example: 0.001234 at 8 -> "123400", 50000.0 at 1 -> "500000"
+    #[cfg(feature = "checksum")]
+    fn format_to_precision(value: f64, decimals: u32) -> String {
+        strip_for_checksum(&format!("{:.*}", decimals as usize, value))
+    }
+}
 
-        // If all zeros, return "0"
-        if trimmed.is_empty() {
-            "0".to_string()
-        } else {
-            // Also remove trailing zeros after we've removed the decimal
-            trimmed.trim_end_matches('0').to_string()
-        }
+/// Remove the decimal point and leading zeros of a formatted number
+#[cfg(feature = "checksum")]
+fn strip_for_checksum(formatted: &str) -> String {
+    // Remove the decimal point
+    let without_decimal = formatted.replace('.', "");
+
+    // Remove leading zeros
+    let trimmed = without_decimal.trim_start_matches('0');
+
+    // If all zeros, return "0"
+    if trimmed.is_empty() {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
     }
 }
 
@@ -587,7 +724,7 @@ pub enum ImbalanceSignal {
 ///
 /// Only available when the `checksum` feature is enabled.
 #[cfg(feature = "checksum")]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ChecksumValidation {
     /// Expected checksum from Kraken
     pub expected: u32,
@@ -629,6 +766,53 @@ impl std::fmt::Display for ChecksumValidation {
     }
 }
 
+/// Checksum validation counts of one pair
+///
+/// Kept across resubscribes and reconnects, so a pair whose book keeps
+/// drifting shows up in the totals.
+///
+/// Only available when the `checksum` feature is enabled.
+#[cfg(feature = "checksum")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecksumStats {
+    /// Updates whose checksum was validated
+    pub validations: u64,
+    /// Validations that failed
+    pub failures: u64,
+    /// Failures since the last successful validation
+    pub consecutive_failures: u64,
+    /// Longest run of consecutive failures
+    pub max_consecutive_failures: u64,
+    /// Checksummed updates not validated because the pair's price and
+    /// quantity precision was unknown
+    pub unverified: u64,
+}
+
+#[cfg(feature = "checksum")]
+impl ChecksumStats {
+    /// Count one validation
+    pub fn record(&mut self, valid: bool) {
+        self.validations += 1;
+        if valid {
+            self.consecutive_failures = 0;
+        } else {
+            self.failures += 1;
+            self.consecutive_failures += 1;
+            self.max_consecutive_failures =
+                self.max_consecutive_failures.max(self.consecutive_failures);
+        }
+    }
+
+    /// Get the failure rate as a percentage
+    pub fn failure_rate(&self) -> f64 {
+        if self.validations == 0 {
+            0.0
+        } else {
+            (self.failures as f64 / self.validations as f64) * 100.0
+        }
+    }
+}
+
 /// Orderbook snapshot for time-travel feature
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderbookSnapshot {
@@ -710,6 +894,175 @@ mod tests {
         assert_eq!(ob.sequence, 1);
     }
 
+    #[test]
+    fn test_merged_updates_apply_like_the_originals() {
+        fn update(
+            update_type: OrderbookUpdateType,
+            bids: &[(f64, f64)],
+            checksum: u32,
+        ) -> OrderbookUpdate {
+            let levels = |side: &[(f64, f64)]| {
+                side.iter()
+                    .map(|&(price, qty)| PriceLevelRaw { price, qty })
+                    .collect()
+            };
+            OrderbookUpdate {
+                channel: "book".to_string(),
+                update_type,
+                data: vec![OrderbookData {
+                    symbol: "BTC/USD".to_string(),
+                    bids: levels(bids),
+                    asks: levels(&[(101.0, 1.0)]),
+                    checksum,
+                    timestamp: String::new(),
+                }],
+            }
+        }
+        let updates = [
+            update(
+                OrderbookUpdateType::Snapshot,
+                &[(100.0, 1.0), (99.0, 2.0)],
+                1,
+            ),
+            update(OrderbookUpdateType::Update, &[(100.0, 0.0), (98.0, 3.0)], 2),
+            update(OrderbookUpdateType::Update, &[(99.5, 1.0), (99.0, 4.0)], 3),
+        ];
+
+        let mut expected = Orderbook::new("BTC/USD".to_string());
+        for update in &updates {
+            expected.apply_update(&update.data[0]);
+        }
+
+        // Deltas merge into a delta keeping removals
+        let mut delta = updates[1].clone();
+        delta.merge(&updates[2]);
+        assert_eq!(delta.update_type, OrderbookUpdateType::Update);
+        assert_eq!(delta.data[0].bids.len(), 4);
+        assert_eq!(delta.data[0].checksum, 3);
+
+        // Deltas merge into a snapshot, sorted and without removed levels
+        let mut merged = updates[0].clone();
+        merged.merge(&delta);
+        assert_eq!(merged.update_type, OrderbookUpdateType::Snapshot);
+        let prices: Vec<f64> = merged.data[0].bids.iter().map(|l| l.price).collect();
+        assert_eq!(prices, vec![99.5, 99.0, 98.0]);
+
+        let mut book = Orderbook::new("BTC/USD".to_string());
+        book.apply_update(&merged.data[0]);
+        assert_eq!(book.bids, expected.bids);
+        assert_eq!(book.asks, expected.asks);
+
+        // A newer snapshot replaces everything before it
+        delta.merge(&updates[0]);
+        assert_eq!(delta.update_type, OrderbookUpdateType::Snapshot);
+        assert_eq!(delta.data[0].checksum, 1);
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_checksum_matches_kraken_example() {
+        // The BTC/USD book and checksum from Kraken's v2 book checksum guide
+        let levels = |levels: &[(f64, f64)]| {
+            levels
+                .iter()
+                .map(|&(price, qty)| PriceLevelRaw { price, qty })
+                .collect()
+        };
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.precision = Some(BookPrecision::new(1, 8));
+        ob.apply_update(&OrderbookData {
+            symbol: "BTC/USD".to_string(),
+            bids: levels(&[
+                (45283.5, 0.1),
+                (45283.4, 1.54582015),
+                (45282.1, 0.1),
+                (45281.0, 0.1),
+                (45280.3, 1.54592586),
+                (45279.0, 0.0799),
+                (45277.6, 0.03310103),
+                (45277.5, 0.3),
+                (45277.3, 1.54602737),
+                (45276.6, 0.15445238),
+            ]),
+            asks: levels(&[
+                (45285.2, 0.001),
+                (45286.4, 1.54571953),
+                (45286.6, 1.54571109),
+                (45289.6, 1.54560911),
+                (45290.2, 0.1589066),
+                (45291.8, 1.54553491),
+                (45294.7, 0.04454749),
+                (45296.1, 0.3538),
+                (45297.5, 0.09945542),
+                (45299.5, 0.18772827),
+            ]),
+            checksum: 3310070434,
+            timestamp: String::new(),
+        });
+        assert!(ob.checksum_valid);
+        assert_eq!(ob.calculate_checksum(), 3310070434);
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_checksum_uses_pair_precision() {
+        let levels = |levels: &[(f64, f64)]| {
+            levels
+                .iter()
+                .map(|&(price, qty)| PriceLevelRaw { price, qty })
+                .collect()
+        };
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.precision = Some(BookPrecision::new(1, 8));
+        ob.apply_update(&OrderbookData {
+            symbol: "BTC/USD".to_string(),
+            bids: levels(&[
+                (42000.1, 0.5),
+                (41999.8, 1.20456),
+                (41999.5, 0.01),
+                (41998.0, 2.0),
+                (41997.3, 0.3521),
+            ]),
+            asks: levels(&[
+                (42000.2, 0.25),
+                (42000.9, 1.0),
+                (42001.5, 0.0453),
+                (42003.0, 3.12),
+                (42004.4, 0.8),
+            ]),
+            checksum: 1179172678,
+            timestamp: String::new(),
+        });
+        assert!(ob.checksum_valid);
+
+        assert!(ob.apply_update_validated(&OrderbookData {
+            symbol: "BTC/USD".to_string(),
+            bids: levels(&[(42000.1, 0.0)]),
+            asks: levels(&[(42000.5, 0.1)]),
+            checksum: 1790561697,
+            timestamp: String::new(),
+        }));
+
+        // A wrong precision breaks validation
+        ob.precision = Some(BookPrecision::new(2, 8));
+        assert!(!ob.validate_checksum(1790561697));
+
+        ob.clear();
+        assert!(ob.bids.is_empty() && ob.checksum_valid);
+        assert_eq!(ob.precision, Some(BookPrecision::new(2, 8)));
+
+        // Without a precision, updates are not validated
+        ob.precision = None;
+        assert!(ob.apply_update_validated(&OrderbookData {
+            symbol: "BTC/USD".to_string(),
+            bids: levels(&[(42000.1, 0.5)]),
+            asks: levels(&[(42000.5, 0.1)]),
+            checksum: 1,
+            timestamp: String::new(),
+        }));
+        assert_eq!(ob.last_checksum, 1);
+    }
+
     #[test]
     fn test_orderbook_best_bid_ask() {
         let mut ob = Orderbook::new("BTC/USD".to_string());
@@ -1047,6 +1400,20 @@ mod tests {
         assert!((top1_imbalance - 0.666666).abs() < 0.001);
     }
 
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn test_checksum_stats() {
+        let mut stats = ChecksumStats::default();
+        assert_eq!(stats.failure_rate(), 0.0);
+        for valid in [true, false, false, true, false] {
+            stats.record(valid);
+        }
+        assert_eq!((stats.validations, stats.failures), (5, 3));
+        assert_eq!(stats.consecutive_failures, 1);
+        assert_eq!(stats.max_consecutive_failures, 2);
+        assert_eq!(stats.failure_rate(), 60.0);
+    }
+
     #[test]
     #[cfg(feature = "checksum")]
     fn test_checksum_format_for_checksum() {
@@ -1057,6 +1424,15 @@ mod tests {
         assert_eq!(Orderbook::format_for_checksum(0.0), "0");
     }
 
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn test_checksum_format_to_precision() {
+        assert_eq!(Orderbook::format_to_precision(50000.0, 1), "500000");
+        assert_eq!(Orderbook::format_to_precision(0.001234, 8), "123400");
+        assert_eq!(Orderbook::format_to_precision(123.456, 3), "123456");
+        assert_eq!(Orderbook::format_to_precision(0.0, 8), "0");
+    }
+
     #[test]
     #[cfg(feature = "checksum")]
     fn test_checksum_calculate() {
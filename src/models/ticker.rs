@@ -117,6 +117,65 @@ pub struct Ticker {
     pub change_pct: f64,
 }
 
+impl Ticker {
+    /// Get the spread (ask - bid)
+    pub fn spread(&self) -> f64 {
+        self.ask - self.bid
+    }
+
+    /// Get the mid price between bid and ask
+    pub fn mid_price(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+
+    /// Get the spread in basis points relative to the mid price
+    ///
+    /// Returns `None` if the mid price is zero.
+    pub fn spread_bps(&self) -> Option<f64> {
+        let mid = self.mid_price();
+        if mid == 0.0 {
+            return None;
+        }
+        Some((self.spread() / mid) * 10000.0)
+    }
+
+    /// Get the 24h range (high - low)
+    pub fn range(&self) -> f64 {
+        self.high - self.low
+    }
+
+    /// Get where the last price sits in the 24h range, from 0.0 (low) to 1.0 (high)
+    ///
+    /// Returns `None` if the range is zero.
+    pub fn range_position(&self) -> Option<f64> {
+        let range = self.range();
+        if range == 0.0 {
+            return None;
+        }
+        Some((self.last - self.low) / range)
+    }
+
+    /// Get the percentage the last price is below the 24h high
+    ///
+    /// Zero at the high and negative below it. Returns `None` if the high is zero.
+    pub fn pct_from_high(&self) -> Option<f64> {
+        if self.high == 0.0 {
+            return None;
+        }
+        Some((self.last - self.high) / self.high * 100.0)
+    }
+
+    /// Get the percentage the last price is above the 24h low
+    ///
+    /// Zero at the low and positive above it. Returns `None` if the low is zero.
+    pub fn pct_from_low(&self) -> Option<f64> {
+        if self.low == 0.0 {
+            return None;
+        }
+        Some((self.last - self.low) / self.low * 100.0)
+    }
+}
+
 /// Raw ticker data from Kraken API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TickerDataRaw {
@@ -195,3 +254,51 @@ pub struct TickerUpdate {
     /// Ticker data
     pub data: Vec<TickerDataRaw>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticker() -> Ticker {
+        Ticker {
+            symbol: "BTC/USD".to_string(),
+            bid: 99.0,
+            bid_qty: 1.0,
+            ask: 101.0,
+            ask_qty: 1.0,
+            last: 100.0,
+            volume: 10.0,
+            vwap: 100.0,
+            low: 80.0,
+            high: 125.0,
+            change: 5.0,
+            change_pct: 5.0,
+        }
+    }
+
+    #[test]
+    fn test_spread_and_mid() {
+        let ticker = ticker();
+        assert_eq!(ticker.spread(), 2.0);
+        assert_eq!(ticker.mid_price(), 100.0);
+        assert_eq!(ticker.spread_bps(), Some(200.0));
+    }
+
+    #[test]
+    fn test_range_helpers() {
+        let ticker = ticker();
+        assert_eq!(ticker.range(), 45.0);
+        assert_eq!(ticker.range_position(), Some(20.0 / 45.0));
+        assert_eq!(ticker.pct_from_high(), Some(-20.0));
+        assert_eq!(ticker.pct_from_low(), Some(25.0));
+
+        let flat = Ticker {
+            low: 0.0,
+            high: 0.0,
+            ..ticker
+        };
+        assert_eq!(flat.range_position(), None);
+        assert_eq!(flat.pct_from_high(), None);
+        assert_eq!(flat.pct_from_low(), None);
+    }
+}
@@ -1,6 +1,8 @@
 //! Trade data types
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
 
 /// Deserialize a value that could be either a number or a string representation of a number
 fn deserialize_number<'de, D>(deserializer: D) -> Result<f64, D::Error>
@@ -89,6 +91,149 @@ pub struct Trade {
     pub timestamp: String,
 }
 
+impl Trade {
+    /// Notional value in the quote asset (price * quantity)
+    pub fn notional(&self) -> f64 {
+        self.price * self.qty
+    }
+
+    /// Check whether the taker bought
+    pub fn is_buy(&self) -> bool {
+        self.side == TradeSide::Buy
+    }
+}
+
+/// Consecutive prints at one price and side, coalesced into one trade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateTrade {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Trade side (buy/sell from taker's perspective)
+    pub side: TradeSide,
+    /// Price shared by all prints
+    pub price: f64,
+    /// Total quantity
+    pub qty: f64,
+    /// Number of prints coalesced
+    pub count: u32,
+    /// ID of the first print
+    pub first_trade_id: i64,
+    /// ID of the last print
+    pub last_trade_id: i64,
+    /// Timestamp of the first print
+    pub timestamp: String,
+    /// Timestamp of the last print
+    pub last_timestamp: String,
+}
+
+impl AggregateTrade {
+    fn from_trade(trade: &Trade) -> Self {
+        Self {
+            symbol: trade.symbol.clone(),
+            side: trade.side,
+            price: trade.price,
+            qty: trade.qty,
+            count: 1,
+            first_trade_id: trade.trade_id,
+            last_trade_id: trade.trade_id,
+            timestamp: trade.timestamp.clone(),
+            last_timestamp: trade.timestamp.clone(),
+        }
+    }
+
+    /// Notional value in the quote asset (price * quantity)
+    pub fn notional(&self) -> f64 {
+        self.price * self.qty
+    }
+}
+
+/// Coalesces rapid sequential prints into aggregate trades
+///
+/// A taker order filling against several resting orders at one level shows
+/// up as several prints. Consecutive trades of a pair with the same side
+/// and price, within `window` of the first print, are merged into one
+/// [`AggregateTrade`]. A run is emitted when the next trade of the pair
+/// breaks it, so the latest run of each pair is held back until then or
+/// until [`flush`](Self::flush).
+///
+/// Only available when the `trades` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use kraky::{KrakyClient, TradeAggregator};
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = KrakyClient::connect().await?;
+/// let mut trades = client.subscribe_trades("BTC/USD").await?;
+/// let mut aggregator = TradeAggregator::new(Duration::from_millis(50));
+///
+/// while let Some(trade) = trades.next().await {
+///     if let Some(agg) = aggregator.push(&trade) {
+///         println!("{} {} x{} prints @ {}", agg.side, agg.qty, agg.count, agg.price);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TradeAggregator {
+    /// Longest time between the first and last print of a run
+    window: Duration,
+    /// Run in progress per pair, with the time of its first print
+    pending: HashMap<String, (AggregateTrade, Option<chrono::DateTime<chrono::Utc>>)>,
+}
+
+impl TradeAggregator {
+    /// Create an aggregator merging prints up to `window` apart
+    ///
+    /// A zero window only merges prints with the same timestamp.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Add a trade, returning the previous run of its pair if this trade ends it
+    pub fn push(&mut self, trade: &Trade) -> Option<AggregateTrade> {
+        let time = parse_time(&trade.timestamp);
+        if let Some((run, started)) = self.pending.get_mut(&trade.symbol) {
+            let in_window = match (*started, time) {
+                (Some(started), Some(time)) => (time - started)
+                    .to_std()
+                    .map_or(true, |elapsed| elapsed <= self.window),
+                _ => run.timestamp == trade.timestamp,
+            };
+            if run.side == trade.side && run.price == trade.price && in_window {
+                run.qty += trade.qty;
+                run.count += 1;
+                run.last_trade_id = trade.trade_id;
+                run.last_timestamp = trade.timestamp.clone();
+                return None;
+            }
+        }
+        self.pending
+            .insert(
+                trade.symbol.clone(),
+                (AggregateTrade::from_trade(trade), time),
+            )
+            .map(|(run, _)| run)
+    }
+
+    /// Take the runs still in progress
+    pub fn flush(&mut self) -> Vec<AggregateTrade> {
+        self.pending.drain().map(|(_, (run, _))| run).collect()
+    }
+}
+
+fn parse_time(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&chrono::Utc))
+}
+
 /// Raw trade data from Kraken API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeDataRaw {
@@ -137,3 +282,70 @@ pub struct TradeUpdate {
     /// Trade data
     pub data: Vec<TradeDataRaw>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(id: i64, side: TradeSide, price: f64, millis: u32) -> Trade {
+        Trade {
+            symbol: "BTC/USD".to_string(),
+            side,
+            price,
+            qty: 0.5,
+            ord_type: TradeOrderType::Market,
+            trade_id: id,
+            timestamp: format!("2024-01-15T10:30:00.{:03}000Z", millis),
+        }
+    }
+
+    #[test]
+    fn test_notional() {
+        let trade = trade(1, TradeSide::Buy, 50000.0, 0);
+        assert_eq!(trade.notional(), 25000.0);
+        assert!(trade.is_buy());
+    }
+
+    #[test]
+    fn test_aggregator_coalesces_runs() {
+        let mut aggregator = TradeAggregator::new(Duration::from_millis(10));
+        assert!(aggregator
+            .push(&trade(1, TradeSide::Buy, 100.0, 0))
+            .is_none());
+        assert!(aggregator
+            .push(&trade(2, TradeSide::Buy, 100.0, 5))
+            .is_none());
+        assert!(aggregator
+            .push(&trade(3, TradeSide::Buy, 100.0, 10))
+            .is_none());
+
+        // A new price ends the run
+        let run = aggregator
+            .push(&trade(4, TradeSide::Buy, 101.0, 10))
+            .unwrap();
+        assert_eq!((run.count, run.qty), (3, 1.5));
+        assert_eq!((run.first_trade_id, run.last_trade_id), (1, 3));
+        assert_eq!(run.notional(), 150.0);
+
+        // So does a new side, or a print outside the window
+        assert_eq!(
+            aggregator
+                .push(&trade(5, TradeSide::Sell, 101.0, 11))
+                .unwrap()
+                .count,
+            1
+        );
+        assert_eq!(
+            aggregator
+                .push(&trade(6, TradeSide::Sell, 101.0, 30))
+                .unwrap()
+                .count,
+            1
+        );
+
+        let rest = aggregator.flush();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].first_trade_id, 6);
+        assert!(aggregator.flush().is_empty());
+    }
+}
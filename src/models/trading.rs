@@ -3,6 +3,8 @@
 //! Order placement, cancellation, and management via WebSocket.
 //! Requires the `trading` feature flag.
 
+use crate::error::{KrakyError, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Order side (buy or sell)
@@ -14,8 +16,10 @@ pub enum OrderSide {
 }
 
 /// Order type
+///
+/// Serialized as Kraken names it (`"stop-loss-limit"`).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "snake_case")]
+#[serde(rename_all = "kebab-case")]
 pub enum OrderType {
     /// Market order - executes immediately at best available price
     Market,
@@ -37,33 +41,91 @@ pub enum OrderType {
     Iceberg,
 }
 
+impl OrderType {
+    /// Check whether the order type needs a limit price
+    pub fn needs_limit_price(&self) -> bool {
+        matches!(
+            self,
+            OrderType::Limit
+                | OrderType::StopLossLimit
+                | OrderType::TakeProfitLimit
+                | OrderType::TrailingStopLimit
+                | OrderType::Iceberg
+        )
+    }
+
+    /// Check whether the order type needs a trigger price
+    pub fn needs_trigger_price(&self) -> bool {
+        matches!(
+            self,
+            OrderType::StopLoss
+                | OrderType::StopLossLimit
+                | OrderType::TakeProfit
+                | OrderType::TakeProfitLimit
+                | OrderType::TrailingStop
+                | OrderType::TrailingStopLimit
+        )
+    }
+}
+
 /// Time-in-force options
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum TimeInForce {
     /// Good-til-cancelled (default)
+    #[default]
     GTC,
     /// Immediate-or-cancel
     IOC,
     /// Fill-or-kill
     FOK,
-    /// Good-til-date
+    /// Good-til-date, expiring at the order's `expire_time`
     GTD,
 }
 
+impl TimeInForce {
+    /// Get the REST API name (`"GTC"`, `"IOC"`, ...)
+    pub fn as_rest_str(&self) -> &'static str {
+        match self {
+            TimeInForce::GTC => "GTC",
+            TimeInForce::IOC => "IOC",
+            TimeInForce::FOK => "FOK",
+            TimeInForce::GTD => "GTD",
+        }
+    }
+}
+
 /// Self-trade prevention mode
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// Decides what happens when an order would match another order of the
+/// same account. Sent as `stp_type`; Kraken cancels the newest order when
+/// none is given.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum SelfTradePrevention {
-    /// Cancel the newest order
+    /// Cancel the incoming (newest) order
+    #[default]
     CancelNewest,
-    /// Cancel the oldest order
+    /// Cancel the resting (oldest) order
     CancelOldest,
     /// Cancel both orders
     CancelBoth,
 }
 
+impl SelfTradePrevention {
+    /// Get the REST API name (`"cancel-newest"`, ...)
+    pub fn as_rest_str(&self) -> &'static str {
+        match self {
+            SelfTradePrevention::CancelNewest => "cancel-newest",
+            SelfTradePrevention::CancelOldest => "cancel-oldest",
+            SelfTradePrevention::CancelBoth => "cancel-both",
+        }
+    }
+}
+
 /// Parameters for placing an order
+///
+/// Serializes to the `params` of a WebSocket v2 `add_order` request.
 #[derive(Debug, Clone, Serialize)]
 pub struct OrderParams {
     /// Trading pair (e.g., "BTC/USD")
@@ -78,12 +140,21 @@ pub struct OrderParams {
     /// Limit price (required for limit orders)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit_price: Option<f64>,
-    /// Trigger price (for stop orders)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Trigger price (for stop and take-profit orders)
+    ///
+    /// Sent as `triggers.price`, triggering on the last trade price.
+    #[serde(
+        rename = "triggers",
+        serialize_with = "serialize_triggers",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub trigger_price: Option<f64>,
     /// Time in force
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_in_force: Option<TimeInForce>,
+    /// Expiry of a [`TimeInForce::GTD`] order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire_time: Option<DateTime<Utc>>,
     /// Post-only (order will only be placed if it would be a maker order)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub post_only: Option<bool>,
@@ -91,7 +162,7 @@ pub struct OrderParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reduce_only: Option<bool>,
     /// Self-trade prevention mode
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "stp_type", skip_serializing_if = "Option::is_none")]
     pub stp: Option<SelfTradePrevention>,
     /// Client order ID (optional, for tracking)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -102,6 +173,144 @@ pub struct OrderParams {
 }
 
 impl OrderParams {
+    /// Start building an order of any type
+    ///
+    /// Prefer the [`market`](Self::market), [`limit`](Self::limit),
+    /// [`stop_loss`](Self::stop_loss) and [`take_profit`](Self::take_profit)
+    /// shortcuts, which set the required prices.
+    pub fn builder(
+        symbol: impl Into<String>,
+        side: OrderSide,
+        order_type: OrderType,
+        quantity: f64,
+    ) -> OrderParamsBuilder {
+        OrderParamsBuilder {
+            params: Self {
+                symbol: symbol.into(),
+                side,
+                order_type,
+                order_qty: Some(quantity),
+                limit_price: None,
+                trigger_price: None,
+                time_in_force: None,
+                expire_time: None,
+                post_only: None,
+                reduce_only: None,
+                stp: None,
+                cl_ord_id: None,
+                validate: None,
+            },
+        }
+    }
+
+    /// Start building a market order
+    pub fn market(symbol: impl Into<String>, side: OrderSide, quantity: f64) -> OrderParamsBuilder {
+        Self::builder(symbol, side, OrderType::Market, quantity)
+    }
+
+    /// Start building a limit order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kraky::{OrderParams, OrderSide, TimeInForce};
+    ///
+    /// let order = OrderParams::limit("BTC/USD", OrderSide::Buy, 0.5, 50000.0)
+    ///     .post_only()
+    ///     .tif(TimeInForce::GTC)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(order.post_only, Some(true));
+    ///
+    /// // Incompatible options are rejected before anything is sent
+    /// let err = OrderParams::limit("BTC/USD", OrderSide::Buy, 0.5, 50000.0)
+    ///     .post_only()
+    ///     .tif(TimeInForce::IOC)
+    ///     .build();
+    /// assert!(err.is_err());
+    /// ```
+    pub fn limit(
+        symbol: impl Into<String>,
+        side: OrderSide,
+        quantity: f64,
+        price: f64,
+    ) -> OrderParamsBuilder {
+        Self::builder(symbol, side, OrderType::Limit, quantity).limit_price(price)
+    }
+
+    /// Start building a stop-loss market order
+    pub fn stop_loss(
+        symbol: impl Into<String>,
+        side: OrderSide,
+        quantity: f64,
+        trigger_price: f64,
+    ) -> OrderParamsBuilder {
+        Self::builder(symbol, side, OrderType::StopLoss, quantity).trigger_price(trigger_price)
+    }
+
+    /// Start building a take-profit market order
+    pub fn take_profit(
+        symbol: impl Into<String>,
+        side: OrderSide,
+        quantity: f64,
+        trigger_price: f64,
+    ) -> OrderParamsBuilder {
+        Self::builder(symbol, side, OrderType::TakeProfit, quantity).trigger_price(trigger_price)
+    }
+
+    /// Check the parameters for missing or incompatible fields
+    ///
+    /// Called by [`OrderParamsBuilder::build`] and before an order is sent,
+    /// so mistakes fail locally with [`KrakyError::InvalidOrder`] instead of
+    /// as a Kraken rejection.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: &str| Err(KrakyError::InvalidOrder(message.to_string()));
+        let positive = |value: Option<f64>| value.map_or(true, |v| v.is_finite() && v > 0.0);
+
+        if self.symbol.is_empty() {
+            return invalid("symbol is empty");
+        }
+        match self.order_qty {
+            None => return invalid("order quantity is required"),
+            Some(qty) if !positive(Some(qty)) => return invalid("order quantity must be positive"),
+            _ => {}
+        }
+        if !positive(self.limit_price) || !positive(self.trigger_price) {
+            return invalid("prices must be positive");
+        }
+
+        let order_type = &self.order_type;
+        match (order_type.needs_limit_price(), self.limit_price) {
+            (true, None) => return invalid("limit price is required for this order type"),
+            (false, Some(_)) => return invalid("limit price is not allowed for this order type"),
+            _ => {}
+        }
+        match (order_type.needs_trigger_price(), self.trigger_price) {
+            (true, None) => return invalid("trigger price is required for this order type"),
+            (false, Some(_)) => return invalid("trigger price is not allowed for this order type"),
+            _ => {}
+        }
+
+        if self.post_only == Some(true) {
+            if !order_type.needs_limit_price() {
+                return invalid("post-only requires a limit order");
+            }
+            if matches!(
+                self.time_in_force,
+                Some(TimeInForce::IOC) | Some(TimeInForce::FOK)
+            ) {
+                return invalid("post-only orders cannot be IOC or FOK");
+            }
+        }
+
+        match (&self.time_in_force, self.expire_time) {
+            (Some(TimeInForce::GTD), None) => return invalid("GTD orders need an expire time"),
+            (Some(TimeInForce::GTD), Some(_)) | (_, None) => {}
+            (_, Some(_)) => return invalid("an expire time requires GTD time in force"),
+        }
+        Ok(())
+    }
+
     /// Create a market buy order
     pub fn market_buy(symbol: impl Into<String>, quantity: f64) -> Self {
         Self {
@@ -112,6 +321,7 @@ impl OrderParams {
             limit_price: None,
             trigger_price: None,
             time_in_force: None,
+            expire_time: None,
             post_only: None,
             reduce_only: None,
             stp: None,
@@ -130,6 +340,7 @@ impl OrderParams {
             limit_price: None,
             trigger_price: None,
             time_in_force: None,
+            expire_time: None,
             post_only: None,
             reduce_only: None,
             stp: None,
@@ -148,6 +359,7 @@ impl OrderParams {
             limit_price: Some(price),
             trigger_price: None,
             time_in_force: None,
+            expire_time: None,
             post_only: None,
             reduce_only: None,
             stp: None,
@@ -166,6 +378,7 @@ impl OrderParams {
             limit_price: Some(price),
             trigger_price: None,
             time_in_force: None,
+            expire_time: None,
             post_only: None,
             reduce_only: None,
             stp: None,
@@ -180,6 +393,13 @@ impl OrderParams {
         self
     }
 
+    /// Make the order good-til-date, expiring at `time`
+    pub fn with_expire_time(mut self, time: DateTime<Utc>) -> Self {
+        self.time_in_force = Some(TimeInForce::GTD);
+        self.expire_time = Some(time);
+        self
+    }
+
     /// Set post-only flag
     pub fn with_post_only(mut self, post_only: bool) -> Self {
         self.post_only = Some(post_only);
@@ -205,6 +425,92 @@ impl OrderParams {
     }
 }
 
+/// Builder for [`OrderParams`], validated on [`build`](Self::build)
+///
+/// Created with [`OrderParams::market`], [`OrderParams::limit`] and the
+/// other constructors.
+#[derive(Debug, Clone)]
+#[must_use = "call build() to get the order parameters"]
+pub struct OrderParamsBuilder {
+    params: OrderParams,
+}
+
+impl OrderParamsBuilder {
+    /// Set the limit price
+    pub fn limit_price(mut self, price: f64) -> Self {
+        self.params.limit_price = Some(price);
+        self
+    }
+
+    /// Set the trigger price
+    pub fn trigger_price(mut self, price: f64) -> Self {
+        self.params.trigger_price = Some(price);
+        self
+    }
+
+    /// Set the time in force
+    pub fn tif(mut self, tif: TimeInForce) -> Self {
+        self.params.time_in_force = Some(tif);
+        self
+    }
+
+    /// Make the order good-til-date, expiring at `time`
+    pub fn expire_at(mut self, time: DateTime<Utc>) -> Self {
+        self.params = self.params.with_expire_time(time);
+        self
+    }
+
+    /// Only place the order if it would rest on the book as a maker
+    pub fn post_only(mut self) -> Self {
+        self.params.post_only = Some(true);
+        self
+    }
+
+    /// Only allow the order to reduce an existing position
+    pub fn reduce_only(mut self) -> Self {
+        self.params.reduce_only = Some(true);
+        self
+    }
+
+    /// Set the self-trade prevention mode
+    pub fn stp(mut self, stp: SelfTradePrevention) -> Self {
+        self.params.stp = Some(stp);
+        self
+    }
+
+    /// Set the client order ID
+    pub fn client_id(mut self, id: impl Into<String>) -> Self {
+        self.params.cl_ord_id = Some(id.into());
+        self
+    }
+
+    /// Have Kraken validate the order without placing it
+    pub fn validate_only(mut self) -> Self {
+        self.params.validate = Some(true);
+        self
+    }
+
+    /// Validate and return the order parameters
+    pub fn build(self) -> Result<OrderParams> {
+        self.params.validate()?;
+        Ok(self.params)
+    }
+}
+
+/// Serialize a trigger price as the `triggers` object of `add_order`
+fn serialize_triggers<S>(price: &Option<f64>, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeMap;
+
+    let mut map = serializer.serialize_map(Some(1))?;
+    if let Some(price) = price {
+        map.serialize_entry("price", price)?;
+    }
+    map.end()
+}
+
 /// Order status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -218,7 +524,7 @@ pub enum OrderStatus {
 }
 
 /// Response from placing an order
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderResponse {
     /// Order ID assigned by Kraken
     pub order_id: String,
@@ -248,7 +554,7 @@ pub struct AmendOrderParams {
 }
 
 /// Response from amending an order
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AmendOrderResponse {
     /// Order ID
     pub order_id: String,
@@ -260,7 +566,7 @@ pub struct AmendOrderResponse {
 }
 
 /// Response from canceling an order
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CancelOrderResponse {
     /// Order ID that was cancelled
     pub order_id: String,
@@ -269,7 +575,7 @@ pub struct CancelOrderResponse {
 }
 
 /// Response from cancel all orders
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CancelAllResponse {
     /// Number of orders cancelled
     pub count: usize,
@@ -310,6 +616,73 @@ mod tests {
         assert_eq!(order.cl_ord_id, Some("my-order-123".to_string()));
     }
 
+    #[test]
+    fn test_builder_validation() {
+        let order = OrderParams::stop_loss("BTC/USD", OrderSide::Sell, 0.5, 45000.0)
+            .reduce_only()
+            .build()
+            .unwrap();
+        assert_eq!(order.order_type, OrderType::StopLoss);
+        assert_eq!(order.trigger_price, Some(45000.0));
+        assert_eq!(order.reduce_only, Some(true));
+
+        let invalid = |builder: OrderParamsBuilder| {
+            matches!(builder.build(), Err(KrakyError::InvalidOrder(_)))
+        };
+        assert!(invalid(
+            OrderParams::market("BTC/USD", OrderSide::Buy, 0.1).limit_price(50000.0)
+        ));
+        assert!(invalid(
+            OrderParams::market("BTC/USD", OrderSide::Buy, 0.1).post_only()
+        ));
+        assert!(invalid(
+            OrderParams::builder("BTC/USD", OrderSide::Buy, OrderType::StopLossLimit, 0.1)
+                .trigger_price(45000.0)
+        ));
+        assert!(invalid(OrderParams::market("BTC/USD", OrderSide::Buy, 0.0)));
+        assert!(invalid(OrderParams::limit(
+            "BTC/USD",
+            OrderSide::Buy,
+            0.1,
+            f64::NAN
+        )));
+    }
+
+    #[test]
+    fn test_add_order_json() {
+        let expiry = DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let order = OrderParams::builder("BTC/USD", OrderSide::Sell, OrderType::StopLossLimit, 0.5)
+            .trigger_price(45000.0)
+            .limit_price(44900.0)
+            .expire_at(expiry)
+            .stp(SelfTradePrevention::CancelBoth)
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["order_type"], "stop-loss-limit");
+        assert_eq!(json["triggers"], serde_json::json!({"price": 45000.0}));
+        assert_eq!(json["time_in_force"], "gtd");
+        assert_eq!(json["expire_time"], "2024-06-01T12:00:00Z");
+        assert_eq!(json["stp_type"], "cancel_both");
+        assert!(json.get("stp").is_none());
+        assert!(json.get("post_only").is_none());
+    }
+
+    #[test]
+    fn test_gtd_needs_expire_time() {
+        let gtd = OrderParams::limit("BTC/USD", OrderSide::Buy, 0.1, 50000.0).tif(TimeInForce::GTD);
+        assert!(gtd.build().is_err());
+
+        let mut order =
+            OrderParams::limit_buy("BTC/USD", 0.1, 50000.0).with_expire_time(Utc::now());
+        assert!(order.validate().is_ok());
+        order.time_in_force = Some(TimeInForce::IOC);
+        assert!(order.validate().is_err());
+    }
+
     #[test]
     fn test_validate_mode() {
         let order = OrderParams::market_buy("BTC/USD", 0.1).with_validate(true);
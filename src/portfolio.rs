@@ -0,0 +1,374 @@
+//! Portfolio valuation from balances and tickers
+//!
+//! [`Portfolio`] values account balances in one quote currency. Balances
+//! come from [`BalanceUpdate`]s of the private `balances` channel (or are
+//! set directly), prices from the `ASSET/QUOTE` ticker of every held asset.
+//! [`track`](Portfolio::track) subscribes to the tickers of held assets
+//! that aren't tracked yet, so call it again after balances add an asset.
+//!
+//! [`valuation`](Portfolio::valuation) returns the total value, per-asset
+//! weights and the 24h change derived from each ticker's price change;
+//! [`summaries`](Portfolio::summaries) emits one periodically.
+//!
+//! Requires the `private` and `ticker` feature flags.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::{KrakyClient, Portfolio};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! let portfolio = Arc::new(Portfolio::new("USD"));
+//! portfolio.set_balance("BTC", 0.5);
+//! portfolio.set_balance("ETH", 4.0);
+//! portfolio.set_balance("USD", 2_500.0);
+//! portfolio.track(&client).await?;
+//!
+//! let mut summaries = portfolio.summaries(Duration::from_secs(60));
+//! while let Some(valuation) = summaries.recv().await {
+//!     println!(
+//!         "{:.2} {} ({:+.2}% 24h)",
+//!         valuation.total, valuation.quote, valuation.change_pct
+//!     );
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::KrakyClient;
+use crate::error::Result;
+use crate::models::{BalanceUpdate, Ticker};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Summaries buffered before new ones are skipped
+const SUMMARY_BUFFER: usize = 16;
+
+/// Value of one asset in a [`PortfolioValuation`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetValuation {
+    /// Asset, e.g. `BTC`
+    pub asset: String,
+    /// Balance
+    pub amount: f64,
+    /// Last price in the quote currency
+    pub price: f64,
+    /// Value in the quote currency
+    pub value: f64,
+    /// Share of the total value in percent
+    pub weight: f64,
+    /// 24h price change in percent
+    pub change_pct: f64,
+}
+
+/// Valuation of a [`Portfolio`] at one point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioValuation {
+    /// Quote currency the values are in
+    pub quote: String,
+    /// Total value of the priced assets
+    pub total: f64,
+    /// Change of the total value over 24h, at the current balances
+    pub change_24h: f64,
+    /// Change of the total value over 24h in percent
+    pub change_pct: f64,
+    /// Priced assets, largest value first
+    pub assets: Vec<AssetValuation>,
+    /// Held assets without a ticker yet, left out of the total
+    pub unpriced: Vec<String>,
+    /// When the valuation was taken
+    pub timestamp: DateTime<Utc>,
+}
+
+impl PortfolioValuation {
+    /// Get the valuation of one asset
+    pub fn asset(&self, asset: &str) -> Option<&AssetValuation> {
+        self.assets.iter().find(|a| a.asset == asset)
+    }
+}
+
+/// Balances valued in one quote currency
+#[derive(Debug)]
+pub struct Portfolio {
+    quote: String,
+    balances: Mutex<HashMap<String, f64>>,
+    /// Latest ticker per asset
+    tickers: Mutex<HashMap<String, Ticker>>,
+    /// Pairs with a ticker subscription feeding this portfolio
+    tracked: Mutex<HashSet<String>>,
+}
+
+impl Portfolio {
+    /// Create an empty portfolio valued in `quote`, e.g. `USD`
+    pub fn new(quote: impl Into<String>) -> Self {
+        Self {
+            quote: quote.into(),
+            balances: Mutex::new(HashMap::new()),
+            tickers: Mutex::new(HashMap::new()),
+            tracked: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Get the quote currency
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
+
+    /// Set the balance of an asset; zero removes it
+    pub fn set_balance(&self, asset: impl Into<String>, amount: f64) {
+        let asset = asset.into();
+        let mut balances = self.balances.lock();
+        if amount == 0.0 {
+            balances.remove(&asset);
+        } else {
+            balances.insert(asset, amount);
+        }
+    }
+
+    /// Apply a `balances` channel update
+    ///
+    /// Unparseable amounts are skipped.
+    pub fn apply_balances(&self, update: &BalanceUpdate) {
+        for data in &update.data {
+            for (asset, amount) in &data.balances {
+                if let Ok(amount) = amount.parse() {
+                    self.set_balance(asset.clone(), amount);
+                }
+            }
+        }
+    }
+
+    /// Get the balance of an asset
+    pub fn balance(&self, asset: &str) -> Option<f64> {
+        self.balances.lock().get(asset).copied()
+    }
+
+    /// Apply a ticker of an `ASSET/QUOTE` pair; other pairs are ignored
+    pub fn apply_ticker(&self, ticker: &Ticker) {
+        if let Some(asset) = self.asset_of(&ticker.symbol) {
+            self.tickers
+                .lock()
+                .insert(asset.to_string(), ticker.clone());
+        }
+    }
+
+    /// Get the `ASSET/QUOTE` pairs of the held assets
+    pub fn pairs(&self) -> Vec<String> {
+        let mut pairs: Vec<_> = self
+            .balances
+            .lock()
+            .keys()
+            .filter(|asset| **asset != self.quote)
+            .map(|asset| format!("{}/{}", asset, self.quote))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    /// Subscribe to the tickers of held assets that aren't tracked yet
+    ///
+    /// Each subscription feeds this portfolio from a background task until
+    /// the client closes it.
+    pub async fn track(self: &Arc<Self>, client: &KrakyClient) -> Result<()> {
+        for pair in self.pairs() {
+            if !self.tracked.lock().insert(pair.clone()) {
+                continue;
+            }
+            let mut tickers = match client.subscribe_ticker(pair.as_str()).await {
+                Ok(tickers) => tickers,
+                Err(e) => {
+                    self.tracked.lock().remove(&pair);
+                    return Err(e);
+                }
+            };
+            let portfolio = Arc::clone(self);
+            crate::runtime::spawn(async move {
+                while let Some(ticker) = tickers.next().await {
+                    portfolio.apply_ticker(&ticker);
+                }
+                portfolio.tracked.lock().remove(&pair);
+            });
+        }
+        Ok(())
+    }
+
+    /// Value the current balances at the latest prices
+    pub fn valuation(&self) -> PortfolioValuation {
+        let balances = self.balances.lock().clone();
+        let tickers = self.tickers.lock();
+
+        let mut assets = Vec::new();
+        let mut unpriced = Vec::new();
+        let mut previous_total = 0.0;
+        for (asset, amount) in balances {
+            let (price, change) = if asset == self.quote {
+                (1.0, 0.0)
+            } else if let Some(ticker) = tickers.get(&asset) {
+                (ticker.last, ticker.change)
+            } else {
+                unpriced.push(asset);
+                continue;
+            };
+            let previous_price = price - change;
+            previous_total += amount * previous_price;
+            assets.push(AssetValuation {
+                asset,
+                amount,
+                price,
+                value: amount * price,
+                weight: 0.0,
+                change_pct: if previous_price != 0.0 {
+                    change / previous_price * 100.0
+                } else {
+                    0.0
+                },
+            });
+        }
+
+        let total: f64 = assets.iter().map(|a| a.value).sum();
+        if total != 0.0 {
+            for asset in &mut assets {
+                asset.weight = asset.value / total * 100.0;
+            }
+        }
+        assets.sort_by(|a, b| b.value.total_cmp(&a.value));
+        unpriced.sort();
+
+        let change_24h = total - previous_total;
+        PortfolioValuation {
+            quote: self.quote.clone(),
+            total,
+            change_24h,
+            change_pct: if previous_total != 0.0 {
+                change_24h / previous_total * 100.0
+            } else {
+                0.0
+            },
+            assets,
+            unpriced,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Emit a valuation every `interval`, starting now
+    ///
+    /// Summaries are skipped while the receiver is full; the task ends when
+    /// the receiver is dropped.
+    pub fn summaries(self: &Arc<Self>, interval: Duration) -> mpsc::Receiver<PortfolioValuation> {
+        let (tx, rx) = mpsc::channel(SUMMARY_BUFFER);
+        let portfolio = Arc::clone(self);
+        crate::runtime::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                ticks.tick().await;
+                match tx.try_send(portfolio.valuation()) {
+                    Err(mpsc::error::TrySendError::Closed(_)) => break,
+                    _ => continue,
+                }
+            }
+        });
+        rx
+    }
+
+    /// The asset of an `ASSET/QUOTE` pair
+    fn asset_of<'a>(&self, pair: &'a str) -> Option<&'a str> {
+        let (asset, quote) = pair.split_once('/')?;
+        (quote == self.quote).then_some(asset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticker(symbol: &str, last: f64, change: f64) -> Ticker {
+        Ticker {
+            symbol: symbol.to_string(),
+            bid: last,
+            bid_qty: 1.0,
+            ask: last,
+            ask_qty: 1.0,
+            last,
+            volume: 0.0,
+            vwap: last,
+            low: last,
+            high: last,
+            change,
+            change_pct: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_valuation() {
+        let portfolio = Portfolio::new("USD");
+        let update: BalanceUpdate = serde_json::from_str(
+            r#"{"channel":"balances","type":"snapshot","data":[{"BTC":"0.5","ETH":"4","USD":"1000","SOL":"10"}]}"#,
+        )
+        .unwrap();
+        portfolio.apply_balances(&update);
+        assert_eq!(portfolio.pairs(), ["BTC/USD", "ETH/USD", "SOL/USD"]);
+
+        portfolio.apply_ticker(&ticker("BTC/USD", 40_000.0, 2_000.0));
+        portfolio.apply_ticker(&ticker("ETH/USD", 2_000.0, -500.0));
+        portfolio.apply_ticker(&ticker("ETH/EUR", 1.0, 0.0));
+
+        let valuation = portfolio.valuation();
+        assert_eq!(valuation.total, 29_000.0);
+        assert_eq!(valuation.unpriced, ["SOL"]);
+        assert_eq!(valuation.assets[0].asset, "BTC");
+        assert_eq!(valuation.asset("ETH").unwrap().value, 8_000.0);
+        assert!((valuation.asset("USD").unwrap().weight - 1000.0 / 290.0).abs() < 1e-9);
+        assert!((valuation.asset("BTC").unwrap().change_pct - 2000.0 / 380.0).abs() < 1e-9);
+
+        // 24h ago: 0.5 * 38000 + 4 * 2500 + 1000
+        assert_eq!(valuation.change_24h, -1_000.0);
+        assert!((valuation.change_pct + 100.0 / 30.0).abs() < 1e-9);
+
+        portfolio.set_balance("ETH", 0.0);
+        assert_eq!(portfolio.valuation().total, 21_000.0);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_track_and_summaries() {
+        use crate::testing::{fixtures, MockScript, MockServer};
+
+        let server = MockServer::start(
+            MockScript::new().message(fixtures::get("ticker_snapshot").unwrap().text),
+        )
+        .await
+        .unwrap();
+        let client = KrakyClient::connect_with_url(server.url()).await.unwrap();
+
+        let portfolio = Arc::new(Portfolio::new("USD"));
+        portfolio.set_balance("BTC", 2.0);
+        portfolio.track(&client).await.unwrap();
+        portfolio.track(&client).await.unwrap();
+
+        let mut summaries = portfolio.summaries(Duration::from_millis(20));
+        let valuation = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let valuation = summaries.recv().await.unwrap();
+                if valuation.unpriced.is_empty() {
+                    return valuation;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        let ticker = &fixtures::get("ticker_snapshot").unwrap().json()["data"][0];
+        let last = ticker["last"].as_f64().unwrap();
+        let change = ticker["change"].as_f64().unwrap();
+        assert!((valuation.total - 2.0 * last).abs() < 1e-6);
+        assert!((valuation.change_24h - 2.0 * change).abs() < 1e-6);
+        assert_eq!(portfolio.tracked.lock().len(), 1);
+    }
+}
@@ -0,0 +1,726 @@
+//! PostgreSQL / TimescaleDB market data sink
+//!
+//! [`PostgresSink`] subscribes to chosen channels and inserts every message
+//! into PostgreSQL, for production collectors that already run a database.
+//! Messages are queued and written in batches: each batch is one transaction
+//! with its inserts pipelined over the connection, so throughput doesn't
+//! depend on round-trip latency.
+//!
+//! Requires the `postgres` feature flag, plus the feature of each stored
+//! channel (`trades`, `ticker`, `ohlc`, `orderbook`).
+//!
+//! ## Schema
+//!
+//! The tables below are created on [`start`](PostgresSink::start) unless
+//! disabled with [`create_schema(false)`](PostgresSink::create_schema); the
+//! full statements are in [`SCHEMA`]. Every table has a `time` column that
+//! is part of its primary key, so each can be turned into a TimescaleDB
+//! hypertable with [`with_timescale`](PostgresSink::with_timescale).
+//!
+//! | Table | `time` | Key | Notes |
+//! |-------|--------|-----|-------|
+//! | `trades` | trade time | `(symbol, trade_id, time)` | Duplicates after a reconnect are ignored |
+//! | `tickers` | receive time | `(symbol, time)` | Later updates in the same microsecond are ignored |
+//! | `candles` | interval begin | `(symbol, interval, time)` | Keeps the latest update of each candle |
+//! | `book_levels` | snapshot time | `(snapshot_id, side, level, time)` | One row per level; level 0 is the best price |
+//!
+//! Orderbooks are stored as periodic snapshots of the maintained book rather
+//! than as raw updates. All tables also have `recorded_at`, the time the
+//! message was received.
+//!
+//! ## TLS
+//!
+//! Connections use the system TLS library. Control it with `sslmode` in the
+//! connection string (`disable`, `prefer` or `require`).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use kraky::postgres::PostgresSink;
+//! use kraky::KrakyClient;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Arc::new(KrakyClient::connect().await?);
+//!
+//!     let sink = PostgresSink::new(client, "host=localhost user=kraky dbname=market")
+//!         .with_timescale()
+//!         .trades("BTC/USD")
+//!         .orderbook("BTC/USD", 25)
+//!         .start()
+//!         .await?;
+//!
+//!     tokio::time::sleep(Duration::from_secs(60)).await;
+//!
+//!     sink.stop().await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::KrakyClient;
+use crate::error::{KrakyError, Result};
+use crate::subscriptions::Subscription;
+use chrono::{DateTime, Utc};
+use futures_util::future::try_join_all;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Client, Statement};
+
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+#[cfg(feature = "ohlc")]
+use crate::models::{Interval, OHLC};
+#[cfg(feature = "orderbook")]
+use crate::models::{OrderbookSnapshot, OrderbookUpdate};
+#[cfg(feature = "trades")]
+use crate::models::{Trade, TradeOrderType};
+
+/// Rows buffered between the subscriptions and the database writer
+const QUEUE_SIZE: usize = 10_000;
+
+/// Tables created by [`PostgresSink`]
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS trades (
+    time TIMESTAMPTZ NOT NULL,
+    symbol TEXT NOT NULL,
+    trade_id BIGINT NOT NULL,
+    side TEXT NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    qty DOUBLE PRECISION NOT NULL,
+    ord_type TEXT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (symbol, trade_id, time)
+);
+CREATE INDEX IF NOT EXISTS trades_symbol_time ON trades (symbol, time DESC);
+
+CREATE TABLE IF NOT EXISTS tickers (
+    time TIMESTAMPTZ NOT NULL,
+    symbol TEXT NOT NULL,
+    bid DOUBLE PRECISION NOT NULL,
+    bid_qty DOUBLE PRECISION NOT NULL,
+    ask DOUBLE PRECISION NOT NULL,
+    ask_qty DOUBLE PRECISION NOT NULL,
+    last DOUBLE PRECISION NOT NULL,
+    volume DOUBLE PRECISION NOT NULL,
+    vwap DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    change DOUBLE PRECISION NOT NULL,
+    change_pct DOUBLE PRECISION NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (symbol, time)
+);
+
+CREATE TABLE IF NOT EXISTS candles (
+    time TIMESTAMPTZ NOT NULL,
+    symbol TEXT NOT NULL,
+    interval INTEGER NOT NULL,
+    open DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    close DOUBLE PRECISION NOT NULL,
+    vwap DOUBLE PRECISION NOT NULL,
+    volume DOUBLE PRECISION NOT NULL,
+    count BIGINT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (symbol, interval, time)
+);
+
+CREATE TABLE IF NOT EXISTS book_levels (
+    time TIMESTAMPTZ NOT NULL,
+    snapshot_id UUID NOT NULL,
+    symbol TEXT NOT NULL,
+    sequence BIGINT NOT NULL,
+    side TEXT NOT NULL,
+    level INTEGER NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    qty DOUBLE PRECISION NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (snapshot_id, side, level, time)
+);
+CREATE INDEX IF NOT EXISTS book_levels_symbol_time ON book_levels (symbol, time DESC);
+";
+
+/// Tables converted to hypertables by [`PostgresSink::with_timescale`]
+const HYPERTABLES: &[&str] = &["trades", "tickers", "candles", "book_levels"];
+
+/// A channel to store
+#[derive(Debug, Clone)]
+enum Channel {
+    #[cfg(feature = "trades")]
+    Trades(String),
+    #[cfg(feature = "ticker")]
+    Ticker(String),
+    #[cfg(feature = "ohlc")]
+    Ohlc(String, Interval),
+    #[cfg(feature = "orderbook")]
+    Orderbook(String, u32),
+}
+
+/// A message waiting to be written
+#[derive(Debug)]
+enum Row {
+    #[cfg(feature = "trades")]
+    Trade(Trade),
+    #[cfg(feature = "ticker")]
+    Ticker(Ticker),
+    #[cfg(feature = "ohlc")]
+    Candle(OHLC),
+    #[cfg(feature = "orderbook")]
+    Book(OrderbookSnapshot),
+}
+
+/// Inserts live market data into PostgreSQL
+///
+/// Configure the channels to store, then [`start`](Self::start) it. See the
+/// [module documentation](self) for the schema.
+pub struct PostgresSink {
+    client: Arc<KrakyClient>,
+    config: String,
+    create_schema: bool,
+    timescale: bool,
+    batch_size: usize,
+    snapshot_interval: Duration,
+    channels: Vec<Channel>,
+}
+
+impl PostgresSink {
+    /// Create a sink for a connection string
+    ///
+    /// Accepts both `key=value` (`host=localhost user=kraky`) and URL
+    /// (`postgresql://kraky@localhost/market`) forms.
+    pub fn new(client: Arc<KrakyClient>, config: impl Into<String>) -> Self {
+        Self {
+            client,
+            config: config.into(),
+            create_schema: true,
+            timescale: false,
+            batch_size: 1_000,
+            snapshot_interval: Duration::from_secs(10),
+            channels: Vec::new(),
+        }
+    }
+
+    /// Create missing tables on start (enabled by default)
+    pub fn create_schema(mut self, create: bool) -> Self {
+        self.create_schema = create;
+        self
+    }
+
+    /// Convert the tables to TimescaleDB hypertables on start
+    ///
+    /// Requires the `timescaledb` extension in the target database. Tables
+    /// that are already hypertables are left unchanged.
+    pub fn with_timescale(mut self) -> Self {
+        self.timescale = true;
+        self
+    }
+
+    /// Set the most messages written in one transaction (1,000 by default)
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Set how often orderbook snapshots are stored (10 seconds by default)
+    pub fn snapshot_interval(mut self, interval: Duration) -> Self {
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// Store trades for a pair
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub fn trades(mut self, pair: &str) -> Self {
+        self.channels.push(Channel::Trades(pair.to_string()));
+        self
+    }
+
+    /// Store ticker updates for a pair
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub fn ticker(mut self, pair: &str) -> Self {
+        self.channels.push(Channel::Ticker(pair.to_string()));
+        self
+    }
+
+    /// Store OHLC candles for a pair
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub fn ohlc(mut self, pair: &str, interval: Interval) -> Self {
+        self.channels
+            .push(Channel::Ohlc(pair.to_string(), interval));
+        self
+    }
+
+    /// Store periodic snapshots of the top `depth` levels of a pair's book
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    pub fn orderbook(mut self, pair: &str, depth: u32) -> Self {
+        self.channels
+            .push(Channel::Orderbook(pair.to_string(), depth));
+        self
+    }
+
+    /// Connect, prepare the schema, subscribe to the configured channels and
+    /// start writing
+    ///
+    /// Writing continues until [`PostgresSinkHandle::stop`] is called, the
+    /// client disconnects, or a database error occurs.
+    pub async fn start(self) -> Result<PostgresSinkHandle> {
+        if self.channels.is_empty() {
+            return Err(KrakyError::InvalidMessage(
+                "Postgres sink has no channels configured".to_string(),
+            ));
+        }
+        let db = connect(&self.config).await?;
+        if self.create_schema {
+            db.batch_execute(SCHEMA).await.map_err(storage_error)?;
+        }
+        if self.timescale {
+            for table in HYPERTABLES {
+                let query = format!(
+                    "SELECT create_hypertable('{}', 'time', if_not_exists => TRUE)",
+                    table
+                );
+                db.batch_execute(&query).await.map_err(storage_error)?;
+            }
+        }
+        let statements = Statements::prepare(&db).await?;
+
+        let (rows_tx, rows_rx) = mpsc::channel(QUEUE_SIZE);
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let mut tasks = Vec::new();
+
+        for channel in &self.channels {
+            let task = match channel {
+                #[cfg(feature = "trades")]
+                Channel::Trades(pair) => {
+                    let subscription = self.client.subscribe_trades(pair).await?;
+                    tokio::spawn(forward(
+                        subscription,
+                        Row::Trade,
+                        rows_tx.clone(),
+                        stop_rx.clone(),
+                    ))
+                }
+                #[cfg(feature = "ticker")]
+                Channel::Ticker(pair) => {
+                    let subscription = self.client.subscribe_ticker(pair).await?;
+                    tokio::spawn(forward(
+                        subscription,
+                        Row::Ticker,
+                        rows_tx.clone(),
+                        stop_rx.clone(),
+                    ))
+                }
+                #[cfg(feature = "ohlc")]
+                Channel::Ohlc(pair, interval) => {
+                    let subscription = self.client.subscribe_ohlc(pair, *interval).await?;
+                    tokio::spawn(forward(
+                        subscription,
+                        Row::Candle,
+                        rows_tx.clone(),
+                        stop_rx.clone(),
+                    ))
+                }
+                #[cfg(feature = "orderbook")]
+                Channel::Orderbook(pair, depth) => {
+                    let subscription = self.client.subscribe_orderbook(pair, *depth).await?;
+                    tokio::spawn(snapshot(
+                        Arc::clone(&self.client),
+                        pair.clone(),
+                        *depth as usize,
+                        self.snapshot_interval,
+                        subscription,
+                        rows_tx.clone(),
+                        stop_rx.clone(),
+                    ))
+                }
+            };
+            tasks.push(task);
+        }
+        // The writer finishes once every channel task has dropped its sender
+        drop(rows_tx);
+
+        let records = Arc::new(AtomicU64::new(0));
+        let writer = tokio::spawn(write_rows(
+            db,
+            statements,
+            rows_rx,
+            self.batch_size,
+            Arc::clone(&records),
+        ));
+
+        Ok(PostgresSinkHandle {
+            stop: stop_tx,
+            tasks,
+            writer,
+            records,
+        })
+    }
+}
+
+/// Handle to a running [`PostgresSink`]
+pub struct PostgresSinkHandle {
+    stop: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+    writer: JoinHandle<Result<()>>,
+    records: Arc<AtomicU64>,
+}
+
+impl PostgresSinkHandle {
+    /// Number of messages written so far
+    ///
+    /// An orderbook snapshot counts once, regardless of its depth.
+    pub fn records(&self) -> u64 {
+        self.records.load(Ordering::Relaxed)
+    }
+
+    /// Stop all subscriptions and wait until queued messages are written
+    ///
+    /// Returns the database error that stopped the writer, if any.
+    pub async fn stop(self) -> Result<()> {
+        let _ = self.stop.send(true);
+        for task in self.tasks {
+            let _ = task.await;
+        }
+        self.writer
+            .await
+            .map_err(|e| KrakyError::Storage(format!("Postgres writer failed: {}", e)))?
+    }
+}
+
+/// Open a connection and drive it in the background
+async fn connect(config: &str) -> Result<Client> {
+    let tls = native_tls::TlsConnector::new()
+        .map_err(|e| KrakyError::Storage(format!("TLS setup failed: {}", e)))?;
+    let tls = postgres_native_tls::MakeTlsConnector::new(tls);
+    let (client, connection) = tokio_postgres::connect(config, tls)
+        .await
+        .map_err(storage_error)?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            tracing::error!("Postgres connection closed: {}", e);
+        }
+    });
+    Ok(client)
+}
+
+fn storage_error(e: tokio_postgres::Error) -> KrakyError {
+    KrakyError::Storage(e.to_string())
+}
+
+/// Insert statements, prepared once per connection
+struct Statements {
+    #[cfg(feature = "trades")]
+    trade: Statement,
+    #[cfg(feature = "ticker")]
+    ticker: Statement,
+    #[cfg(feature = "ohlc")]
+    candle: Statement,
+    #[cfg(feature = "orderbook")]
+    level: Statement,
+}
+
+impl Statements {
+    async fn prepare(db: &Client) -> Result<Self> {
+        Ok(Self {
+            #[cfg(feature = "trades")]
+            trade: db
+                .prepare(
+                    "INSERT INTO trades
+                     (time, symbol, trade_id, side, price, qty, ord_type, recorded_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                     ON CONFLICT DO NOTHING",
+                )
+                .await
+                .map_err(storage_error)?,
+            #[cfg(feature = "ticker")]
+            ticker: db
+                .prepare(
+                    "INSERT INTO tickers
+                     (time, symbol, bid, bid_qty, ask, ask_qty, last, volume, vwap, low, high,
+                      change, change_pct, recorded_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                     ON CONFLICT DO NOTHING",
+                )
+                .await
+                .map_err(storage_error)?,
+            #[cfg(feature = "ohlc")]
+            candle: db
+                .prepare(
+                    "INSERT INTO candles
+                     (time, symbol, interval, open, high, low, close, vwap, volume, count,
+                      recorded_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                     ON CONFLICT (symbol, interval, time) DO UPDATE SET
+                     high = EXCLUDED.high, low = EXCLUDED.low, close = EXCLUDED.close,
+                     vwap = EXCLUDED.vwap, volume = EXCLUDED.volume, count = EXCLUDED.count,
+                     recorded_at = EXCLUDED.recorded_at",
+                )
+                .await
+                .map_err(storage_error)?,
+            #[cfg(feature = "orderbook")]
+            level: db
+                .prepare(
+                    "INSERT INTO book_levels
+                     (time, snapshot_id, symbol, sequence, side, level, price, qty, recorded_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                )
+                .await
+                .map_err(storage_error)?,
+        })
+    }
+}
+
+/// Queue a subscription's messages until stopped or closed
+#[cfg(any(feature = "trades", feature = "ticker", feature = "ohlc"))]
+async fn forward<T: Clone>(
+    mut subscription: Subscription<Arc<T>>,
+    row: fn(T) -> Row,
+    rows: mpsc::Sender<(DateTime<Utc>, Row)>,
+    mut stop: watch::Receiver<bool>,
+) {
+    loop {
+        tokio::select! {
+            item = subscription.next() => match item {
+                Some(item) => {
+                    if rows.send((Utc::now(), row(Arc::try_unwrap(item).unwrap_or_else(|item| (*item).clone())))).await.is_err() {
+                        break;
+                    }
+                }
+                None => break,
+            },
+            _ = stop.changed() => break,
+        }
+    }
+}
+
+/// Queue snapshots of a maintained orderbook until stopped or closed
+#[cfg(feature = "orderbook")]
+async fn snapshot(
+    client: Arc<KrakyClient>,
+    pair: String,
+    depth: usize,
+    every: Duration,
+    mut subscription: Subscription<Arc<OrderbookUpdate>>,
+    rows: mpsc::Sender<(DateTime<Utc>, Row)>,
+    mut stop: watch::Receiver<bool>,
+) {
+    let mut ticks = tokio::time::interval(every);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        tokio::select! {
+            // The client maintains the book; updates only need draining
+            update = subscription.next() => if update.is_none() {
+                break;
+            },
+            _ = ticks.tick() => {
+                let Some(book) = client.get_orderbook(&pair) else {
+                    continue;
+                };
+                if book.bids.is_empty() && book.asks.is_empty() {
+                    continue;
+                }
+                let snapshot = OrderbookSnapshot::from_orderbook(&book, depth);
+                if rows.send((Utc::now(), Row::Book(snapshot))).await.is_err() {
+                    break;
+                }
+            }
+            _ = stop.changed() => break,
+        }
+    }
+}
+
+/// Parse an exchange timestamp, falling back to the receive time
+#[cfg(any(feature = "trades", feature = "ohlc"))]
+fn time_or(timestamp: &str, recorded_at: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or(recorded_at)
+}
+
+/// Values bound to one insert
+type Params = Vec<Box<dyn ToSql + Sync + Send>>;
+
+/// Statement and parameters for each database row of a message
+fn bind(
+    statements: &Statements,
+    recorded_at: DateTime<Utc>,
+    row: Row,
+) -> Vec<(&Statement, Params)> {
+    match row {
+        #[cfg(feature = "trades")]
+        Row::Trade(trade) => {
+            let ord_type = match trade.ord_type {
+                TradeOrderType::Market => "market",
+                TradeOrderType::Limit => "limit",
+            };
+            let params: Params = vec![
+                Box::new(time_or(&trade.timestamp, recorded_at)),
+                Box::new(trade.symbol),
+                Box::new(trade.trade_id),
+                Box::new(trade.side.to_string()),
+                Box::new(trade.price),
+                Box::new(trade.qty),
+                Box::new(ord_type),
+                Box::new(recorded_at),
+            ];
+            vec![(&statements.trade, params)]
+        }
+        #[cfg(feature = "ticker")]
+        Row::Ticker(ticker) => {
+            let params: Params = vec![
+                Box::new(recorded_at),
+                Box::new(ticker.symbol),
+                Box::new(ticker.bid),
+                Box::new(ticker.bid_qty),
+                Box::new(ticker.ask),
+                Box::new(ticker.ask_qty),
+                Box::new(ticker.last),
+                Box::new(ticker.volume),
+                Box::new(ticker.vwap),
+                Box::new(ticker.low),
+                Box::new(ticker.high),
+                Box::new(ticker.change),
+                Box::new(ticker.change_pct),
+                Box::new(recorded_at),
+            ];
+            vec![(&statements.ticker, params)]
+        }
+        #[cfg(feature = "ohlc")]
+        Row::Candle(candle) => {
+            let params: Params = vec![
+                Box::new(time_or(&candle.interval_begin, recorded_at)),
+                Box::new(candle.symbol),
+                Box::new(candle.interval as i32),
+                Box::new(candle.open),
+                Box::new(candle.high),
+                Box::new(candle.low),
+                Box::new(candle.close),
+                Box::new(candle.vwap),
+                Box::new(candle.volume),
+                Box::new(candle.count),
+                Box::new(recorded_at),
+            ];
+            vec![(&statements.candle, params)]
+        }
+        #[cfg(feature = "orderbook")]
+        Row::Book(snapshot) => {
+            let id = uuid::Uuid::parse_str(&snapshot.id).unwrap_or_else(|_| uuid::Uuid::new_v4());
+            let sides = [("bid", &snapshot.bids), ("ask", &snapshot.asks)];
+            sides
+                .into_iter()
+                .flat_map(|(side, levels)| {
+                    levels
+                        .iter()
+                        .enumerate()
+                        .map(move |(i, level)| (side, i, level))
+                })
+                .map(|(side, i, level)| {
+                    let params: Params = vec![
+                        Box::new(snapshot.timestamp),
+                        Box::new(id),
+                        Box::new(snapshot.symbol.clone()),
+                        Box::new(snapshot.sequence as i64),
+                        Box::new(side),
+                        Box::new(i as i32),
+                        Box::new(level.price),
+                        Box::new(level.qty),
+                        Box::new(recorded_at),
+                    ];
+                    (&statements.level, params)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Write queued rows in batches until every sender is dropped
+async fn write_rows(
+    mut db: Client,
+    statements: Statements,
+    mut rows: mpsc::Receiver<(DateTime<Utc>, Row)>,
+    batch_size: usize,
+    records: Arc<AtomicU64>,
+) -> Result<()> {
+    while let Some((recorded_at, row)) = rows.recv().await {
+        let mut inserts = bind(&statements, recorded_at, row);
+        let mut messages = 1;
+        while messages < batch_size {
+            let Ok((recorded_at, row)) = rows.try_recv() else {
+                break;
+            };
+            inserts.extend(bind(&statements, recorded_at, row));
+            messages += 1;
+        }
+
+        if let Err(e) = write_batch(&mut db, &inserts).await {
+            tracing::error!("Postgres sink failed to write: {}", e);
+            return Err(storage_error(e));
+        }
+        records.fetch_add(messages as u64, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Run a batch of inserts pipelined in one transaction
+async fn write_batch(
+    db: &mut Client,
+    inserts: &[(&Statement, Params)],
+) -> std::result::Result<(), tokio_postgres::Error> {
+    let tx = db.transaction().await?;
+    try_join_all(inserts.iter().map(|(statement, params)| {
+        let params: Vec<&(dyn ToSql + Sync)> = params
+            .iter()
+            .map(|p| p.as_ref() as &(dyn ToSql + Sync))
+            .collect();
+        let tx = &tx;
+        async move { tx.execute(*statement, &params).await }
+    }))
+    .await?;
+    tx.commit().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_is_hypertable_ready() {
+        // TimescaleDB requires `time` in every unique index of a hypertable
+        for table in HYPERTABLES {
+            let start = SCHEMA
+                .find(&format!("CREATE TABLE IF NOT EXISTS {} (", table))
+                .unwrap();
+            let body = &SCHEMA[start..];
+            let body = &body[..body.find(");").unwrap()];
+            assert!(body.contains("time TIMESTAMPTZ NOT NULL"), "{}", table);
+            let key = body.lines().find(|l| l.contains("PRIMARY KEY")).unwrap();
+            assert!(key.contains("time)"), "{}", table);
+        }
+    }
+
+    #[cfg(any(feature = "trades", feature = "ohlc"))]
+    #[test]
+    fn test_time_or_falls_back_to_recorded_at() {
+        let recorded_at = Utc::now();
+        let parsed = time_or("2024-01-15T10:30:00.123456Z", recorded_at);
+        assert_eq!(
+            parsed.to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            "2024-01-15T10:30:00.123456Z"
+        );
+        assert_eq!(time_or("not a time", recorded_at), recorded_at);
+    }
+}
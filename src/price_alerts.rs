@@ -0,0 +1,660 @@
+//! Price alerts driven by tickers
+//!
+//! [`AlertManager`] holds armed price alerts per symbol:
+//!
+//! - [`price_above`](AlertManager::price_above) fires once the price is at or above a level,
+//! - [`price_below`](AlertManager::price_below) once it is at or below a level,
+//! - [`price_crosses`](AlertManager::price_crosses) when the price moves
+//!   through a level in either direction,
+//! - [`falls_from_high`](AlertManager::falls_from_high) once the price is a
+//!   percentage below its rolling high, e.g. 5% below the 1h high,
+//! - [`rises_from_low`](AlertManager::rises_from_low) once it is a
+//!   percentage above its rolling low.
+//!
+//! Alerts are one-shot by default and are disarmed after firing. Repeating
+//! alerts stay armed: above, below and trailing alerts fire again after
+//! the price left the level, crossing alerts on every crossing. Every firing is sent
+//! to [`subscribe`](AlertManager::subscribe)rs as an [`AlertFired`].
+//!
+//! The armed alerts can be [`save`](AlertManager::save)d to a JSON file and
+//! [`load`](AlertManager::load)ed again, so they survive restarts.
+//!
+//! Requires the `price-alerts` feature flag.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::{AlertManager, KrakyClient, PriceAlert};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//!
+//! let mut alerts = AlertManager::new();
+//! alerts.price_above("BTC/USD", 100_000.0);
+//! alerts.price_below("BTC/USD", 90_000.0);
+//! alerts.add(PriceAlert::crosses("BTC/USD", 95_000.0).repeating());
+//! alerts.falls_from_high("BTC/USD", 5.0, Duration::from_secs(3600));
+//!
+//! let mut fired = alerts.subscribe();
+//! tokio::spawn(async move {
+//!     while let Some(alert) = fired.next().await {
+//!         println!("🔔 {}", alert);
+//!     }
+//! });
+//!
+//! let mut ticker = client.subscribe_ticker("BTC/USD").await?;
+//! while let Some(tick) = ticker.next().await {
+//!     if !alerts.on_ticker(&tick).is_empty() {
+//!         alerts.save("alerts.json")?;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::Result;
+use crate::models::Ticker;
+use crate::subscriptions::{Subscription, SubscriptionSender};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// When a price alert fires
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "level", rename_all = "snake_case")]
+pub enum PriceTrigger {
+    /// Price is at or above the level
+    Above(f64),
+    /// Price is at or below the level
+    Below(f64),
+    /// Price moved through the level in either direction
+    Crosses(f64),
+    /// Price is `pct` percent or more below its highest price within `window`
+    FallsFromHigh {
+        /// Distance from the high in percent
+        pct: f64,
+        /// Rolling window of the high
+        window: Duration,
+    },
+    /// Price is `pct` percent or more above its lowest price within `window`
+    RisesFromLow {
+        /// Distance from the low in percent
+        pct: f64,
+        /// Rolling window of the low
+        window: Duration,
+    },
+}
+
+impl PriceTrigger {
+    /// Get the fixed price level, or `None` for trailing triggers
+    pub fn level(&self) -> Option<f64> {
+        match self {
+            PriceTrigger::Above(level)
+            | PriceTrigger::Below(level)
+            | PriceTrigger::Crosses(level) => Some(*level),
+            PriceTrigger::FallsFromHigh { .. } | PriceTrigger::RisesFromLow { .. } => None,
+        }
+    }
+}
+
+/// Format a window as `1h`, `15m` or `90s`
+fn format_window(window: &Duration) -> String {
+    let secs = window.as_secs();
+    if secs > 0 && secs % 3600 == 0 {
+        format!("{}h", secs / 3600)
+    } else if secs > 0 && secs % 60 == 0 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+impl fmt::Display for PriceTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriceTrigger::Above(level) => write!(f, "price >= {}", level),
+            PriceTrigger::Below(level) => write!(f, "price <= {}", level),
+            PriceTrigger::Crosses(level) => write!(f, "price crosses {}", level),
+            PriceTrigger::FallsFromHigh { pct, window } => {
+                write!(f, "price {}% below {} high", pct, format_window(window))
+            }
+            PriceTrigger::RisesFromLow { pct, window } => {
+                write!(f, "price {}% above {} low", pct, format_window(window))
+            }
+        }
+    }
+}
+
+/// A price alert for one symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceAlert {
+    /// Trading pair
+    pub symbol: String,
+    /// When the alert fires
+    pub trigger: PriceTrigger,
+    /// Whether the alert stays armed after firing
+    #[serde(default)]
+    pub repeating: bool,
+    /// Optional note included in the fired alert
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl PriceAlert {
+    /// Create a one-shot alert
+    pub fn new(symbol: impl Into<String>, trigger: PriceTrigger) -> Self {
+        Self {
+            symbol: symbol.into(),
+            trigger,
+            repeating: false,
+            note: None,
+        }
+    }
+
+    /// Alert once the price is at or above `level`
+    pub fn above(symbol: impl Into<String>, level: f64) -> Self {
+        Self::new(symbol, PriceTrigger::Above(level))
+    }
+
+    /// Alert once the price is at or below `level`
+    pub fn below(symbol: impl Into<String>, level: f64) -> Self {
+        Self::new(symbol, PriceTrigger::Below(level))
+    }
+
+    /// Alert when the price moves through `level`
+    pub fn crosses(symbol: impl Into<String>, level: f64) -> Self {
+        Self::new(symbol, PriceTrigger::Crosses(level))
+    }
+
+    /// Alert once the price is `pct` percent below its high within `window`
+    pub fn falls_from_high(symbol: impl Into<String>, pct: f64, window: Duration) -> Self {
+        Self::new(symbol, PriceTrigger::FallsFromHigh { pct, window })
+    }
+
+    /// Alert once the price is `pct` percent above its low within `window`
+    pub fn rises_from_low(symbol: impl Into<String>, pct: f64, window: Duration) -> Self {
+        Self::new(symbol, PriceTrigger::RisesFromLow { pct, window })
+    }
+
+    /// Keep the alert armed after it fires
+    pub fn repeating(mut self) -> Self {
+        self.repeating = true;
+        self
+    }
+
+    /// Attach a note, included in the fired alert
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// A price alert that fired
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertFired {
+    /// ID of the alert (as returned by [`AlertManager::add`])
+    pub id: u64,
+    /// Trading pair
+    pub symbol: String,
+    /// Trigger that was met
+    pub trigger: PriceTrigger,
+    /// Price level that was reached; for trailing triggers, the level
+    /// `pct` percent from the rolling high or low
+    pub level: f64,
+    /// Price that triggered the alert
+    pub price: f64,
+    /// Previous price of the symbol, if any
+    pub previous: Option<f64>,
+    /// Whether the alert is still armed
+    pub repeating: bool,
+    /// Note of the alert, if set
+    pub note: Option<String>,
+    /// Time the alert fired
+    pub timestamp: DateTime<Utc>,
+}
+
+impl fmt::Display for AlertFired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (price: {})",
+            self.symbol, self.trigger, self.price
+        )?;
+        if let Some(note) = &self.note {
+            write!(f, " - {}", note)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rolling high or low of the prices within a window
+#[derive(Debug)]
+struct RollingExtremum {
+    window: Duration,
+    /// Track the high rather than the low
+    high: bool,
+    /// Candidate extremes, oldest and most extreme first
+    samples: VecDeque<(Instant, f64)>,
+}
+
+impl RollingExtremum {
+    fn new(window: Duration, high: bool) -> Self {
+        Self {
+            window,
+            high,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Add a price and get the extreme within the window
+    fn push(&mut self, at: Instant, price: f64) -> f64 {
+        // Older prices that are less extreme can never be the extreme again
+        while let Some(&(_, last)) = self.samples.back() {
+            let dominated = if self.high {
+                last <= price
+            } else {
+                last >= price
+            };
+            if !dominated {
+                break;
+            }
+            self.samples.pop_back();
+        }
+        self.samples.push_back((at, price));
+        while let Some(&(since, _)) = self.samples.front() {
+            if at.duration_since(since) <= self.window {
+                break;
+            }
+            self.samples.pop_front();
+        }
+        self.samples.front().map_or(price, |&(_, extreme)| extreme)
+    }
+}
+
+/// Armed alert with its evaluation state
+#[derive(Debug)]
+struct ArmedAlert {
+    alert: PriceAlert,
+    /// Whether an above, below or trailing condition currently holds
+    active: bool,
+    /// Rolling high or low of trailing alerts
+    extremum: Option<RollingExtremum>,
+}
+
+impl ArmedAlert {
+    fn new(alert: PriceAlert) -> Self {
+        let extremum = match alert.trigger {
+            PriceTrigger::FallsFromHigh { window, .. } => Some(RollingExtremum::new(window, true)),
+            PriceTrigger::RisesFromLow { window, .. } => Some(RollingExtremum::new(window, false)),
+            _ => None,
+        };
+        Self {
+            alert,
+            active: false,
+            extremum,
+        }
+    }
+
+    /// Update the state with a price
+    ///
+    /// Returns the level reached if the alert fires.
+    fn observe(&mut self, at: Instant, price: f64, previous: Option<f64>) -> Option<f64> {
+        let (holds, level) = match self.alert.trigger {
+            PriceTrigger::Above(level) => (price >= level, level),
+            PriceTrigger::Below(level) => (price <= level, level),
+            PriceTrigger::Crosses(level) => {
+                let crossed = previous.is_some_and(|previous| {
+                    (previous < level && price >= level) || (previous > level && price <= level)
+                });
+                return crossed.then_some(level);
+            }
+            PriceTrigger::FallsFromHigh { pct, .. } => {
+                let high = self.extremum.as_mut()?.push(at, price);
+                let level = high * (1.0 - pct / 100.0);
+                (price <= level, level)
+            }
+            PriceTrigger::RisesFromLow { pct, .. } => {
+                let low = self.extremum.as_mut()?.push(at, price);
+                let level = low * (1.0 + pct / 100.0);
+                (price >= level, level)
+            }
+        };
+        let fires = holds && !self.active;
+        self.active = holds;
+        fires.then_some(level)
+    }
+}
+
+/// Armed alerts as saved to disk
+#[derive(Serialize, Deserialize)]
+struct SavedAlerts {
+    next_id: u64,
+    alerts: BTreeMap<u64, PriceAlert>,
+}
+
+/// Armed price alerts evaluated against tickers
+///
+/// Only available when the `price-alerts` feature is enabled.
+#[derive(Default)]
+pub struct AlertManager {
+    alerts: BTreeMap<u64, ArmedAlert>,
+    next_id: u64,
+    /// Last price per symbol, for crossing alerts
+    last_prices: HashMap<String, f64>,
+    senders: Vec<SubscriptionSender<AlertFired>>,
+}
+
+impl fmt::Debug for AlertManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlertManager")
+            .field("alerts", &self.alerts.len())
+            .field("subscribers", &self.senders.len())
+            .finish()
+    }
+}
+
+impl AlertManager {
+    /// Create a manager with no alerts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Arm a one-shot alert for the price at or above `level`
+    pub fn price_above(&mut self, symbol: impl Into<String>, level: f64) -> u64 {
+        self.add(PriceAlert::above(symbol, level))
+    }
+
+    /// Arm a one-shot alert for the price at or below `level`
+    pub fn price_below(&mut self, symbol: impl Into<String>, level: f64) -> u64 {
+        self.add(PriceAlert::below(symbol, level))
+    }
+
+    /// Arm a one-shot alert for the price moving through `level`
+    pub fn price_crosses(&mut self, symbol: impl Into<String>, level: f64) -> u64 {
+        self.add(PriceAlert::crosses(symbol, level))
+    }
+
+    /// Arm a one-shot alert for the price falling `pct` percent from its
+    /// high within `window`
+    pub fn falls_from_high(
+        &mut self,
+        symbol: impl Into<String>,
+        pct: f64,
+        window: Duration,
+    ) -> u64 {
+        self.add(PriceAlert::falls_from_high(symbol, pct, window))
+    }
+
+    /// Arm a one-shot alert for the price rising `pct` percent from its
+    /// low within `window`
+    pub fn rises_from_low(&mut self, symbol: impl Into<String>, pct: f64, window: Duration) -> u64 {
+        self.add(PriceAlert::rises_from_low(symbol, pct, window))
+    }
+
+    /// Arm an alert and return its ID
+    ///
+    /// Trailing alerts track their high or low from the next price on.
+    pub fn add(&mut self, alert: PriceAlert) -> u64 {
+        self.next_id += 1;
+        self.alerts.insert(self.next_id, ArmedAlert::new(alert));
+        self.next_id
+    }
+
+    /// Disarm an alert by ID
+    ///
+    /// Returns `true` if the alert was armed.
+    pub fn remove(&mut self, id: u64) -> bool {
+        self.alerts.remove(&id).is_some()
+    }
+
+    /// Get an armed alert by ID
+    pub fn get(&self, id: u64) -> Option<&PriceAlert> {
+        self.alerts.get(&id).map(|armed| &armed.alert)
+    }
+
+    /// Get all armed alerts with their IDs
+    pub fn alerts(&self) -> Vec<(u64, &PriceAlert)> {
+        self.alerts
+            .iter()
+            .map(|(id, armed)| (*id, &armed.alert))
+            .collect()
+    }
+
+    /// Get the symbols with armed alerts, to subscribe to their tickers
+    pub fn symbols(&self) -> Vec<String> {
+        let symbols: BTreeSet<_> = self
+            .alerts
+            .values()
+            .map(|armed| armed.alert.symbol.clone())
+            .collect();
+        symbols.into_iter().collect()
+    }
+
+    /// Get the number of armed alerts
+    pub fn len(&self) -> usize {
+        self.alerts.len()
+    }
+
+    /// Check whether no alerts are armed
+    pub fn is_empty(&self) -> bool {
+        self.alerts.is_empty()
+    }
+
+    /// Disarm all alerts
+    pub fn clear(&mut self) {
+        self.alerts.clear();
+    }
+
+    /// Subscribe to fired alerts
+    ///
+    /// Every subscriber receives all alerts fired after it subscribed.
+    pub fn subscribe(&mut self) -> Subscription<AlertFired> {
+        let (sender, subscription) =
+            SubscriptionSender::new("price_alerts".to_string(), "*".to_string());
+        self.senders.push(sender);
+        subscription
+    }
+
+    /// Evaluate the alerts of `symbol` against a price
+    ///
+    /// Returns the alerts that fired (which are also sent to subscribers).
+    /// One-shot alerts among them are disarmed.
+    pub fn on_price(&mut self, symbol: &str, price: f64) -> Vec<AlertFired> {
+        self.on_price_at(symbol, price, Instant::now())
+    }
+
+    /// Evaluate the alerts of `symbol` against a price observed at `at`
+    ///
+    /// Like [`on_price`](Self::on_price), for replaying recorded prices
+    /// through trailing alerts.
+    pub fn on_price_at(&mut self, symbol: &str, price: f64, at: Instant) -> Vec<AlertFired> {
+        let previous = self.last_prices.insert(symbol.to_string(), price);
+        let mut fired = Vec::new();
+
+        for (id, armed) in self
+            .alerts
+            .iter_mut()
+            .filter(|(_, armed)| armed.alert.symbol == symbol)
+        {
+            if let Some(level) = armed.observe(at, price, previous) {
+                fired.push(AlertFired {
+                    id: *id,
+                    symbol: symbol.to_string(),
+                    trigger: armed.alert.trigger,
+                    level,
+                    price,
+                    previous,
+                    repeating: armed.alert.repeating,
+                    note: armed.alert.note.clone(),
+                    timestamp: Utc::now(),
+                });
+            }
+        }
+
+        for event in &fired {
+            if !event.repeating {
+                self.alerts.remove(&event.id);
+            }
+            self.senders.retain(|s| s.send(event.clone()).is_ok());
+        }
+        fired
+    }
+
+    /// Evaluate the alerts of the ticker's symbol against its last price
+    pub fn on_ticker(&mut self, ticker: &Ticker) -> Vec<AlertFired> {
+        self.on_price(&ticker.symbol, ticker.last)
+    }
+
+    /// Save the armed alerts to a JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let saved = SavedAlerts {
+            next_id: self.next_id,
+            alerts: self
+                .alerts
+                .iter()
+                .map(|(id, armed)| (*id, armed.alert.clone()))
+                .collect(),
+        };
+        std::fs::write(path, serde_json::to_vec_pretty(&saved)?)?;
+        Ok(())
+    }
+
+    /// Load alerts saved with [`save`](Self::save)
+    ///
+    /// Alerts keep their IDs. Prices are not saved, so an above or below
+    /// alert whose condition still holds fires again on the first price,
+    /// and trailing alerts start a new high or low.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let saved: SavedAlerts = serde_json::from_slice(&std::fs::read(path)?)?;
+        Ok(Self {
+            alerts: saved
+                .alerts
+                .into_iter()
+                .map(|(id, alert)| (id, ArmedAlert::new(alert)))
+                .collect(),
+            next_id: saved.next_id,
+            ..Self::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_shot_and_repeating() {
+        let mut manager = AlertManager::new();
+        let once = manager.price_above("BTC/USD", 100.0);
+        let again = manager.add(PriceAlert::below("BTC/USD", 90.0).repeating());
+
+        assert!(manager.on_price("BTC/USD", 95.0).is_empty());
+        let fired = manager.on_price("BTC/USD", 101.0);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].id, once);
+        assert_eq!(fired[0].previous, Some(95.0));
+        // One-shot alerts are disarmed after firing
+        assert!(manager.get(once).is_none());
+        assert!(manager.on_price("BTC/USD", 95.0).is_empty());
+        assert!(manager.on_price("BTC/USD", 101.0).is_empty());
+
+        // Repeating alerts re-arm once the price leaves the level
+        assert_eq!(manager.on_price("BTC/USD", 89.0)[0].id, again);
+        assert!(manager.on_price("BTC/USD", 88.0).is_empty());
+        assert!(manager.on_price("BTC/USD", 92.0).is_empty());
+        assert_eq!(manager.on_price("BTC/USD", 90.0).len(), 1);
+        assert_eq!(manager.len(), 1);
+
+        // Other symbols are ignored
+        assert!(manager.on_price("ETH/USD", 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_crossing() {
+        let mut manager = AlertManager::new();
+        manager.add(PriceAlert::crosses("BTC/USD", 100.0).repeating());
+
+        // The first price has nothing to cross from
+        assert!(manager.on_price("BTC/USD", 99.0).is_empty());
+        assert!(manager.on_price("BTC/USD", 99.5).is_empty());
+        assert_eq!(manager.on_price("BTC/USD", 100.0).len(), 1);
+        assert!(manager.on_price("BTC/USD", 100.5).is_empty());
+        let fired = manager.on_price("BTC/USD", 98.0);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(
+            fired[0].to_string(),
+            "BTC/USD: price crosses 100 (price: 98)"
+        );
+    }
+
+    #[test]
+    fn test_trailing_from_rolling_high_and_low() {
+        let hour = Duration::from_secs(3600);
+        let start = Instant::now();
+        let mut manager = AlertManager::new();
+        let falls = manager.add(PriceAlert::falls_from_high("BTC/USD", 5.0, hour).repeating());
+        let rises = manager.rises_from_low("BTC/USD", 10.0, hour);
+
+        assert!(manager.on_price_at("BTC/USD", 100.0, start).is_empty());
+        assert!(manager
+            .on_price_at("BTC/USD", 108.0, start + Duration::from_secs(600))
+            .is_empty());
+        // 108 high: 5% below is 102.6; 100 low: 10% above is 110
+        let fired = manager.on_price_at("BTC/USD", 102.0, start + Duration::from_secs(1200));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].id, falls);
+        assert!((fired[0].level - 102.6).abs() < 1e-9);
+        assert_eq!(fired[0].trigger.to_string(), "price 5% below 1h high");
+
+        // The 108 high leaves the window; 104.5 becomes the high
+        assert!(manager
+            .on_price_at("BTC/USD", 104.5, start + Duration::from_secs(4300))
+            .is_empty());
+        assert!(!manager.alerts.get(&falls).unwrap().active);
+        let fired = manager.on_price_at("BTC/USD", 99.0, start + Duration::from_secs(4400));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].id, falls);
+
+        // Low is now 99 within the window: 10% above is 108.9
+        let fired = manager.on_price_at("BTC/USD", 109.0, start + Duration::from_secs(4500));
+        assert_eq!(fired[0].id, rises);
+        assert!(manager.get(rises).is_none());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("kraky-alerts-{}.json", uuid::Uuid::new_v4()));
+        let mut manager = AlertManager::new();
+        manager.price_above("BTC/USD", 100.0);
+        let crosses = manager.add(PriceAlert::crosses("ETH/USD", 10.0).with_note("eth"));
+        manager.on_price("BTC/USD", 150.0);
+        manager.save(&path).unwrap();
+
+        let mut loaded = AlertManager::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.alerts(), manager.alerts());
+        assert_eq!(loaded.symbols(), ["ETH/USD"]);
+        assert_eq!(loaded.get(crosses).unwrap().note.as_deref(), Some("eth"));
+        // IDs continue after the saved ones
+        assert_eq!(loaded.price_below("BTC/USD", 1.0), crosses + 1);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let mut manager = AlertManager::new();
+        manager.add(PriceAlert::above("BTC/USD", 100.0).with_note("target"));
+        let mut fired = manager.subscribe();
+
+        manager.on_price("BTC/USD", 120.0);
+        let alert = fired.next().await.unwrap();
+        assert_eq!(
+            alert.to_string(),
+            "BTC/USD: price >= 100 (price: 120) - target"
+        );
+        assert!(manager.is_empty());
+    }
+}
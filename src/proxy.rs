@@ -0,0 +1,187 @@
+//! Proxy support for the WebSocket connection
+//!
+//! Routes the connection through an HTTP proxy (using `CONNECT`) or a
+//! SOCKS5 proxy, optionally with username/password authentication. TLS is
+//! still negotiated end to end with Kraken; the proxy only sees the tunnel.
+//!
+//! Requires the `proxy` feature flag.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use kraky::{KrakyClient, ProxyConfig};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::builder()
+//!     .proxy(ProxyConfig::socks5("127.0.0.1", 1080).with_auth("user", "secret"))
+//!     .connect()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{KrakyError, Result};
+use base64::Engine;
+use std::io;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_socks::tcp::Socks5Stream;
+
+/// Largest proxy response header accepted for `CONNECT`
+const MAX_RESPONSE_HEADER: usize = 8 * 1024;
+
+/// Proxy protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// HTTP proxy, tunnelling with `CONNECT`
+    Http,
+    /// SOCKS5 proxy
+    Socks5,
+}
+
+/// Proxy used to reach the WebSocket endpoint
+#[derive(Debug, Clone)]
+pub struct ProxyConfig {
+    /// Proxy protocol
+    pub kind: ProxyKind,
+    /// Proxy host name or IP address
+    pub host: String,
+    /// Proxy port
+    pub port: u16,
+    /// Username and password, if the proxy requires them
+    pub auth: Option<(String, String)>,
+}
+
+impl ProxyConfig {
+    /// Create an HTTP `CONNECT` proxy config
+    pub fn http(host: &str, port: u16) -> Self {
+        Self {
+            kind: ProxyKind::Http,
+            host: host.to_string(),
+            port,
+            auth: None,
+        }
+    }
+
+    /// Create a SOCKS5 proxy config
+    pub fn socks5(host: &str, port: u16) -> Self {
+        Self {
+            kind: ProxyKind::Socks5,
+            host: host.to_string(),
+            port,
+            auth: None,
+        }
+    }
+
+    /// Authenticate with a username and password
+    pub fn with_auth(mut self, username: &str, password: &str) -> Self {
+        self.auth = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Open a TCP stream to `host:port` through the proxy
+    pub(crate) async fn connect(&self, host: &str, port: u16) -> Result<TcpStream> {
+        let proxy = (self.host.as_str(), self.port);
+        match self.kind {
+            ProxyKind::Http => {
+                let mut stream = TcpStream::connect(proxy).await?;
+                self.http_connect(&mut stream, host, port).await?;
+                Ok(stream)
+            }
+            ProxyKind::Socks5 => {
+                let stream = match &self.auth {
+                    Some((username, password)) => {
+                        Socks5Stream::connect_with_password(proxy, (host, port), username, password)
+                            .await
+                    }
+                    None => Socks5Stream::connect(proxy, (host, port)).await,
+                }
+                .map_err(|e| proxy_error(format!("SOCKS5 proxy: {}", e)))?;
+                Ok(stream.into_inner())
+            }
+        }
+    }
+
+    /// Ask an HTTP proxy to open a tunnel to `host:port`
+    async fn http_connect(&self, stream: &mut TcpStream, host: &str, port: u16) -> Result<()> {
+        let mut request = format!(
+            "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n",
+            host = host,
+            port = port
+        );
+        if let Some((username, password)) = &self.auth {
+            let credentials = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", username, password));
+            request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", credentials));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        // Read byte by byte so nothing after the header is consumed
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            if response.len() > MAX_RESPONSE_HEADER {
+                return Err(proxy_error("HTTP proxy response too large".to_string()));
+            }
+            let byte = stream.read_u8().await?;
+            response.push(byte);
+        }
+
+        let response = String::from_utf8_lossy(&response);
+        let status = response.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some("200") => Ok(()),
+            _ => Err(proxy_error(format!(
+                "HTTP proxy refused CONNECT: {}",
+                status
+            ))),
+        }
+    }
+}
+
+fn proxy_error(message: String) -> KrakyError {
+    KrakyError::Io(io::Error::new(io::ErrorKind::Other, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Fake HTTP proxy answering one `CONNECT` with `reply`
+    async fn fake_proxy(reply: &'static str) -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                request.push(socket.read_u8().await.unwrap());
+            }
+            socket.write_all(reply.as_bytes()).await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (port, handle)
+    }
+
+    #[tokio::test]
+    async fn test_http_connect() {
+        let (port, proxy) = fake_proxy("HTTP/1.1 200 Connection established\r\n\r\n").await;
+        let config = ProxyConfig::http("127.0.0.1", port).with_auth("user", "pass");
+
+        config.connect("ws.kraken.com", 443).await.unwrap();
+
+        let request = proxy.await.unwrap();
+        assert!(request.starts_with("CONNECT ws.kraken.com:443 HTTP/1.1\r\n"));
+        assert!(request.contains("Proxy-Authorization: Basic dXNlcjpwYXNz\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_http_connect_refused() {
+        let (port, _proxy) = fake_proxy("HTTP/1.1 407 Proxy Authentication Required\r\n\r\n").await;
+        let config = ProxyConfig::http("127.0.0.1", port);
+
+        let err = config.connect("ws.kraken.com", 443).await.unwrap_err();
+        assert!(err.to_string().contains("407"));
+    }
+}
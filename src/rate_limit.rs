@@ -0,0 +1,220 @@
+//! Outgoing message rate limiting
+//!
+//! Kraken limits how many subscribe, unsubscribe and order messages a
+//! connection may send, and disconnects clients that exceed it with
+//! `EAPI:Rate limit exceeded`. The client passes outgoing messages through a
+//! token bucket: bursts up to the bucket size go out at once, the rest wait
+//! in a queue and are sent as tokens refill. Pings are not limited.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::{KrakyClient, RateLimitConfig};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::builder()
+//!     .rate_limit(RateLimitConfig {
+//!         burst: 10,
+//!         per_second: 5.0,
+//!         ..Default::default()
+//!     })
+//!     .connect()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Limits for outgoing messages on one connection
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Messages that can be sent at once before throttling starts
+    pub burst: u32,
+    /// Sustained messages per second
+    pub per_second: f64,
+    /// Messages held back while throttled; further messages are dropped
+    pub max_queue: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            burst: 20,
+            per_second: 10.0,
+            max_queue: 1000,
+        }
+    }
+}
+
+/// Token bucket for one connection
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter with a full bucket
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            tokens: config.burst as f64,
+            config,
+            refilled: Instant::now(),
+        }
+    }
+
+    /// Get the maximum queue length
+    pub fn max_queue(&self) -> usize {
+        self.config.max_queue
+    }
+
+    /// Take a token if one is available
+    pub fn try_acquire(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get the time until the next token is available
+    pub fn time_until_ready(&mut self) -> Duration {
+        self.refill();
+        if self.tokens >= 1.0 || self.config.per_second <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64((1.0 - self.tokens) / self.config.per_second)
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.refilled = now;
+        if self.config.per_second <= 0.0 {
+            // Zero rate means no limit
+            self.tokens = self.config.burst.max(1) as f64;
+            return;
+        }
+        self.tokens =
+            (self.tokens + elapsed * self.config.per_second).min(self.config.burst.max(1) as f64);
+    }
+}
+
+/// Outgoing messages waiting for the rate limiter
+#[derive(Debug)]
+pub(crate) struct Outbox {
+    limiter: Option<RateLimiter>,
+    queue: VecDeque<String>,
+}
+
+impl Outbox {
+    /// Create an outbox, unlimited when `config` is `None`
+    pub fn new(config: Option<RateLimitConfig>) -> Self {
+        Self {
+            limiter: config.map(RateLimiter::new),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Queue a message, returning `false` if the queue is full
+    pub fn push(&mut self, message: String) -> bool {
+        if let Some(limiter) = &self.limiter {
+            if self.queue.len() >= limiter.max_queue() {
+                return false;
+            }
+        }
+        self.queue.push_back(message);
+        true
+    }
+
+    /// Take the next message if the rate limit allows sending it now
+    pub fn pop_ready(&mut self) -> Option<String> {
+        if self.queue.is_empty() {
+            return None;
+        }
+        if let Some(limiter) = &mut self.limiter {
+            if !limiter.try_acquire() {
+                return None;
+            }
+        }
+        self.queue.pop_front()
+    }
+
+    /// Get how long until the next queued message may be sent
+    ///
+    /// Returns `None` when nothing is queued.
+    pub fn throttled_for(&mut self) -> Option<Duration> {
+        if self.queue.is_empty() {
+            return None;
+        }
+        Some(
+            self.limiter
+                .as_mut()
+                .map_or(Duration::ZERO, RateLimiter::time_until_ready),
+        )
+    }
+
+    /// Take every queued message, ignoring the limit
+    pub fn drain(&mut self) -> impl Iterator<Item = String> + '_ {
+        self.queue.drain(..)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_throttle() {
+        let mut limiter = RateLimiter::new(RateLimitConfig {
+            burst: 3,
+            per_second: 100.0,
+            ..Default::default()
+        });
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+
+        let wait = limiter.time_until_ready();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_millis(10));
+        std::thread::sleep(wait + Duration::from_millis(1));
+        assert!(limiter.try_acquire());
+    }
+
+    #[test]
+    fn test_zero_rate_is_unlimited() {
+        let mut limiter = RateLimiter::new(RateLimitConfig {
+            burst: 1,
+            per_second: 0.0,
+            ..Default::default()
+        });
+        for _ in 0..10 {
+            assert!(limiter.try_acquire());
+        }
+        assert_eq!(limiter.time_until_ready(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_outbox_queue() {
+        let mut outbox = Outbox::new(Some(RateLimitConfig {
+            burst: 1,
+            per_second: 1.0,
+            max_queue: 2,
+        }));
+        assert!(outbox.throttled_for().is_none());
+        assert!(outbox.push("a".to_string()));
+        assert!(outbox.push("b".to_string()));
+        assert!(!outbox.push("c".to_string()));
+
+        assert_eq!(outbox.pop_ready().as_deref(), Some("a"));
+        assert_eq!(outbox.pop_ready(), None);
+        assert!(outbox.throttled_for().unwrap() > Duration::ZERO);
+        assert_eq!(outbox.drain().collect::<Vec<_>>(), vec!["b".to_string()]);
+    }
+}
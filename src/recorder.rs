@@ -0,0 +1,726 @@
+//! Market data recorder
+//!
+//! [`Recorder`] subscribes to chosen channels and writes every message to
+//! timestamped CSV or JSON Lines files, so collecting datasets doesn't
+//! require custom code. Each channel and pair gets its own file, rotated by
+//! size and/or age.
+//!
+//! Requires the `recorder` feature flag, plus the feature of each recorded
+//! channel (`trades`, `ticker`, `ohlc`, `orderbook`).
+//!
+//! ## Files
+//!
+//! Files are named `{channel}_{PAIR}_{YYYYMMDD_HHMMSS}.{csv|jsonl}`, e.g.
+//! `trade_BTCUSD_20240115_103000.csv`, using the UTC time the file was opened.
+//!
+//! **CSV** files start with a header row. The first column is `recorded_at`
+//! (RFC 3339 receive time), followed by the message fields. Orderbook updates
+//! are written as one row per price level:
+//!
+//! ```csv
+//! recorded_at,symbol,side,price,qty,ord_type,trade_id,timestamp
+//! 2024-01-15T10:30:00.123456Z,BTC/USD,buy,42501.0,0.5,market,12345678,2024-01-15T10:30:00.120000Z
+//! ```
+//!
+//! **JSON Lines** files contain one object per message, with the message as
+//! delivered by its subscription:
+//!
+//! ```json
+//! {"recorded_at":"2024-01-15T10:30:00.123456Z","channel":"trade","data":{"symbol":"BTC/USD","side":"buy",...}}
+//! ```
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use kraky::recorder::{RecordFormat, Recorder};
+//! use kraky::KrakyClient;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Arc::new(KrakyClient::connect().await?);
+//!
+//!     let recorder = Recorder::new(client, "data")
+//!         .with_format(RecordFormat::JsonLines)
+//!         .rotate_interval(Duration::from_secs(3600))
+//!         .trades("BTC/USD")
+//!         .orderbook("BTC/USD", 10)
+//!         .start()
+//!         .await?;
+//!
+//!     tokio::time::sleep(Duration::from_secs(60)).await;
+//!
+//!     let files = recorder.stop().await?;
+//!     println!("Recorded {:?}", files);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::KrakyClient;
+use crate::error::{KrakyError, Result};
+use crate::subscriptions::Subscription;
+use chrono::{SecondsFormat, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+#[cfg(feature = "ohlc")]
+use crate::models::{Interval, OHLC};
+#[cfg(feature = "orderbook")]
+use crate::models::{OrderbookUpdate, OrderbookUpdateType};
+#[cfg(feature = "trades")]
+use crate::models::{Trade, TradeOrderType};
+
+/// How often buffered records are flushed to disk
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Output file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+impl RecordFormat {
+    /// File extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RecordFormat::Csv => "csv",
+            RecordFormat::JsonLines => "jsonl",
+        }
+    }
+}
+
+/// A channel to record
+#[derive(Debug, Clone)]
+enum Channel {
+    #[cfg(feature = "trades")]
+    Trades(String),
+    #[cfg(feature = "ticker")]
+    Ticker(String),
+    #[cfg(feature = "ohlc")]
+    Ohlc(String, Interval),
+    #[cfg(feature = "orderbook")]
+    Orderbook(String, u32),
+}
+
+/// Records live market data to CSV or JSON Lines files
+///
+/// Configure the channels to record, then [`start`](Self::start) it. See the
+/// [module documentation](self) for the file layout.
+pub struct Recorder {
+    client: Arc<KrakyClient>,
+    dir: PathBuf,
+    format: RecordFormat,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    channels: Vec<Channel>,
+}
+
+impl Recorder {
+    /// Create a recorder writing files to `dir` (created if missing)
+    pub fn new(client: Arc<KrakyClient>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            client,
+            dir: dir.into(),
+            format: RecordFormat::default(),
+            max_bytes: None,
+            max_age: None,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Set the output format (CSV by default)
+    pub fn with_format(mut self, format: RecordFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Start a new file once the current one reaches `max_bytes`
+    pub fn rotate_size(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Start a new file once the current one is `max_age` old
+    pub fn rotate_interval(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Record trades for a pair
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub fn trades(mut self, pair: &str) -> Self {
+        self.channels.push(Channel::Trades(pair.to_string()));
+        self
+    }
+
+    /// Record ticker updates for a pair
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub fn ticker(mut self, pair: &str) -> Self {
+        self.channels.push(Channel::Ticker(pair.to_string()));
+        self
+    }
+
+    /// Record OHLC candles for a pair
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub fn ohlc(mut self, pair: &str, interval: Interval) -> Self {
+        self.channels
+            .push(Channel::Ohlc(pair.to_string(), interval));
+        self
+    }
+
+    /// Record raw orderbook snapshots and updates for a pair
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    pub fn orderbook(mut self, pair: &str, depth: u32) -> Self {
+        self.channels
+            .push(Channel::Orderbook(pair.to_string(), depth));
+        self
+    }
+
+    /// Subscribe to the configured channels and start recording
+    ///
+    /// Recording continues until [`RecorderHandle::stop`] is called or the
+    /// client disconnects.
+    pub async fn start(self) -> Result<RecorderHandle> {
+        if self.channels.is_empty() {
+            return Err(KrakyError::InvalidMessage(
+                "Recorder has no channels configured".to_string(),
+            ));
+        }
+        std::fs::create_dir_all(&self.dir)?;
+
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let shared = Arc::new(Shared::default());
+        let mut tasks = Vec::new();
+
+        for channel in &self.channels {
+            let writer = |channel: &str, pair: &str| RotatingWriter {
+                dir: self.dir.clone(),
+                prefix: format!("{}_{}", channel, file_safe(pair)),
+                format: self.format,
+                max_bytes: self.max_bytes,
+                max_age: self.max_age,
+                current: None,
+                shared: Arc::clone(&shared),
+            };
+
+            let task = match channel {
+                #[cfg(feature = "trades")]
+                Channel::Trades(pair) => {
+                    let subscription = self.client.subscribe_trades(pair).await?;
+                    tokio::spawn(record(subscription, writer("trade", pair), stop_rx.clone()))
+                }
+                #[cfg(feature = "ticker")]
+                Channel::Ticker(pair) => {
+                    let subscription = self.client.subscribe_ticker(pair).await?;
+                    tokio::spawn(record(
+                        subscription,
+                        writer("ticker", pair),
+                        stop_rx.clone(),
+                    ))
+                }
+                #[cfg(feature = "ohlc")]
+                Channel::Ohlc(pair, interval) => {
+                    let subscription = self.client.subscribe_ohlc(pair, *interval).await?;
+                    tokio::spawn(record(subscription, writer("ohlc", pair), stop_rx.clone()))
+                }
+                #[cfg(feature = "orderbook")]
+                Channel::Orderbook(pair, depth) => {
+                    let subscription = self.client.subscribe_orderbook(pair, *depth).await?;
+                    tokio::spawn(record(subscription, writer("book", pair), stop_rx.clone()))
+                }
+            };
+            tasks.push(task);
+        }
+
+        Ok(RecorderHandle {
+            stop: stop_tx,
+            tasks,
+            shared,
+        })
+    }
+}
+
+/// Counters shared by the recording tasks
+#[derive(Default)]
+struct Shared {
+    records: AtomicU64,
+    files: Mutex<Vec<PathBuf>>,
+}
+
+/// Handle to a running [`Recorder`]
+///
+/// Dropping the handle without calling [`stop`](Self::stop) also stops
+/// recording, but may lose the last second of buffered records.
+pub struct RecorderHandle {
+    stop: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<Result<()>>>,
+    shared: Arc<Shared>,
+}
+
+impl RecorderHandle {
+    /// Number of records written so far
+    ///
+    /// An orderbook update counts once, even in CSV where it spans one row
+    /// per price level.
+    pub fn records(&self) -> u64 {
+        self.shared.records.load(Ordering::Relaxed)
+    }
+
+    /// Files created so far, in creation order
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.shared.files.lock().clone()
+    }
+
+    /// Stop recording, flush all files and return the files created
+    ///
+    /// Returns the first write error encountered by any channel.
+    pub async fn stop(self) -> Result<Vec<PathBuf>> {
+        let _ = self.stop.send(true);
+
+        let mut result = Ok(());
+        for task in self.tasks {
+            let outcome = task
+                .await
+                .map_err(|e| KrakyError::InvalidMessage(format!("Recorder task failed: {}", e)))
+                .and_then(|r| r);
+            result = result.and(outcome);
+        }
+        result.map(|_| self.shared.files.lock().clone())
+    }
+}
+
+/// Write a subscription's messages until stopped or closed
+async fn record<T: Recordable>(
+    mut subscription: Subscription<Arc<T>>,
+    mut writer: RotatingWriter,
+    mut stop: watch::Receiver<bool>,
+) -> Result<()> {
+    loop {
+        tokio::select! {
+            item = subscription.next() => match item {
+                Some(item) => {
+                    if let Err(e) = writer.write(item.as_ref()) {
+                        tracing::error!("Recorder failed to write {}: {}", writer.prefix, e);
+                        return Err(e);
+                    }
+                }
+                None => break,
+            },
+            _ = stop.changed() => break,
+        }
+    }
+    writer.close()
+}
+
+/// A message type the recorder can write
+trait Recordable: Serialize + Send + Sync + 'static {
+    /// Channel name used in file names and JSON Lines records
+    const CHANNEL: &'static str;
+    /// CSV column names (after `recorded_at`)
+    const HEADER: &'static [&'static str];
+    /// CSV rows for this message (after `recorded_at`)
+    fn rows(&self) -> Vec<Vec<String>>;
+}
+
+#[cfg(feature = "trades")]
+impl Recordable for Trade {
+    const CHANNEL: &'static str = "trade";
+    const HEADER: &'static [&'static str] = &[
+        "symbol",
+        "side",
+        "price",
+        "qty",
+        "ord_type",
+        "trade_id",
+        "timestamp",
+    ];
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let ord_type = match self.ord_type {
+            TradeOrderType::Market => "market",
+            TradeOrderType::Limit => "limit",
+        };
+        vec![vec![
+            self.symbol.clone(),
+            self.side.to_string(),
+            self.price.to_string(),
+            self.qty.to_string(),
+            ord_type.to_string(),
+            self.trade_id.to_string(),
+            self.timestamp.clone(),
+        ]]
+    }
+}
+
+#[cfg(feature = "ticker")]
+impl Recordable for Ticker {
+    const CHANNEL: &'static str = "ticker";
+    const HEADER: &'static [&'static str] = &[
+        "symbol",
+        "bid",
+        "bid_qty",
+        "ask",
+        "ask_qty",
+        "last",
+        "volume",
+        "vwap",
+        "low",
+        "high",
+        "change",
+        "change_pct",
+    ];
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let values = [
+            self.bid,
+            self.bid_qty,
+            self.ask,
+            self.ask_qty,
+            self.last,
+            self.volume,
+            self.vwap,
+            self.low,
+            self.high,
+            self.change,
+            self.change_pct,
+        ];
+        let mut row = vec![self.symbol.clone()];
+        row.extend(values.iter().map(|v| v.to_string()));
+        vec![row]
+    }
+}
+
+#[cfg(feature = "ohlc")]
+impl Recordable for OHLC {
+    const CHANNEL: &'static str = "ohlc";
+    const HEADER: &'static [&'static str] = &[
+        "symbol",
+        "open",
+        "high",
+        "low",
+        "close",
+        "vwap",
+        "volume",
+        "count",
+        "interval",
+        "interval_begin",
+        "timestamp",
+    ];
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.symbol.clone(),
+            self.open.to_string(),
+            self.high.to_string(),
+            self.low.to_string(),
+            self.close.to_string(),
+            self.vwap.to_string(),
+            self.volume.to_string(),
+            self.count.to_string(),
+            self.interval.to_string(),
+            self.interval_begin.clone(),
+            self.timestamp.clone(),
+        ]]
+    }
+}
+
+#[cfg(feature = "orderbook")]
+impl Recordable for OrderbookUpdate {
+    const CHANNEL: &'static str = "book";
+    const HEADER: &'static [&'static str] = &[
+        "symbol",
+        "type",
+        "side",
+        "price",
+        "qty",
+        "checksum",
+        "timestamp",
+    ];
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let update_type = match self.update_type {
+            OrderbookUpdateType::Snapshot => "snapshot",
+            OrderbookUpdateType::Update => "update",
+        };
+        let mut rows = Vec::new();
+        for data in &self.data {
+            let sides = [("bid", &data.bids), ("ask", &data.asks)];
+            for (side, levels) in sides {
+                for level in levels {
+                    rows.push(vec![
+                        data.symbol.clone(),
+                        update_type.to_string(),
+                        side.to_string(),
+                        level.price.to_string(),
+                        level.qty.to_string(),
+                        data.checksum.to_string(),
+                        data.timestamp.clone(),
+                    ]);
+                }
+            }
+        }
+        rows
+    }
+}
+
+/// A JSON Lines record
+#[derive(Serialize)]
+struct JsonLine<'a, T> {
+    recorded_at: &'a str,
+    channel: &'a str,
+    data: &'a T,
+}
+
+/// The file currently being written
+struct OpenFile {
+    writer: BufWriter<File>,
+    opened: Instant,
+    last_flush: Instant,
+    bytes: u64,
+}
+
+/// Writes records to a series of files, rotating by size and age
+struct RotatingWriter {
+    dir: PathBuf,
+    prefix: String,
+    format: RecordFormat,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    current: Option<OpenFile>,
+    shared: Arc<Shared>,
+}
+
+impl RotatingWriter {
+    /// Write one message
+    fn write<T: Recordable>(&mut self, item: &T) -> Result<()> {
+        let recorded_at = Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true);
+        let record = match self.format {
+            RecordFormat::Csv => {
+                let rows = item.rows();
+                let rows = rows
+                    .iter()
+                    .map(|row| std::iter::once(&recorded_at).chain(row));
+                csv_lines(rows)?
+            }
+            RecordFormat::JsonLines => {
+                let mut line = serde_json::to_vec(&JsonLine {
+                    recorded_at: &recorded_at,
+                    channel: T::CHANNEL,
+                    data: item,
+                })?;
+                line.push(b'\n');
+                line
+            }
+        };
+
+        if self.should_rotate(record.len() as u64) {
+            self.close()?;
+        }
+        if self.current.is_none() {
+            self.open::<T>()?;
+        }
+
+        let file = self.current.as_mut().expect("file opened above");
+        file.writer.write_all(&record)?;
+        file.bytes += record.len() as u64;
+        if file.last_flush.elapsed() >= FLUSH_INTERVAL {
+            file.writer.flush()?;
+            file.last_flush = Instant::now();
+        }
+        self.shared.records.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Check whether writing `len` more bytes should start a new file
+    fn should_rotate(&self, len: u64) -> bool {
+        let Some(file) = &self.current else {
+            return false;
+        };
+        let too_big = self
+            .max_bytes
+            .is_some_and(|max| file.bytes > 0 && file.bytes + len > max);
+        let too_old = self.max_age.is_some_and(|max| file.opened.elapsed() >= max);
+        too_big || too_old
+    }
+
+    /// Open a new file (writing the CSV header)
+    fn open<T: Recordable>(&mut self) -> Result<()> {
+        let path = unique_path(
+            &self.dir,
+            &format!("{}_{}", self.prefix, Utc::now().format("%Y%m%d_%H%M%S")),
+            self.format.extension(),
+        );
+        let mut writer = BufWriter::new(File::create(&path)?);
+
+        let mut bytes = 0;
+        if self.format == RecordFormat::Csv {
+            let header = std::iter::once(&"recorded_at").chain(T::HEADER);
+            let header = csv_lines(std::iter::once(header))?;
+            writer.write_all(&header)?;
+            bytes = header.len() as u64;
+        }
+
+        tracing::debug!("Recording to {}", path.display());
+        self.shared.files.lock().push(path);
+        let now = Instant::now();
+        self.current = Some(OpenFile {
+            writer,
+            opened: now,
+            last_flush: now,
+            bytes,
+        });
+        Ok(())
+    }
+
+    /// Flush and close the current file
+    fn close(&mut self) -> Result<()> {
+        if let Some(mut file) = self.current.take() {
+            file.writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Encode rows as CSV lines
+fn csv_lines<R, F>(rows: R) -> Result<Vec<u8>>
+where
+    R: IntoIterator<Item = F>,
+    F: IntoIterator,
+    F::Item: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        writer.write_record(row).map_err(std::io::Error::from)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| KrakyError::Io(e.into_error()))
+}
+
+/// `dir/name.ext`, or `dir/name_N.ext` if that file already exists
+fn unique_path(dir: &Path, name: &str, extension: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.{}", name, extension));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}_{}.{}", name, n, extension));
+        n += 1;
+    }
+    path
+}
+
+/// Strip characters that don't belong in file names (`BTC/USD` → `BTCUSD`)
+fn file_safe(pair: &str) -> String {
+    pair.chars().filter(|c| c.is_ascii_alphanumeric()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "trades")]
+    fn writer(dir: &Path, format: RecordFormat) -> RotatingWriter {
+        RotatingWriter {
+            dir: dir.to_path_buf(),
+            prefix: "trade_BTCUSD".to_string(),
+            format,
+            max_bytes: None,
+            max_age: None,
+            current: None,
+            shared: Arc::new(Shared::default()),
+        }
+    }
+
+    #[cfg(feature = "trades")]
+    fn trade(id: i64) -> Trade {
+        Trade {
+            symbol: "BTC/USD".to_string(),
+            side: crate::models::TradeSide::Buy,
+            price: 42501.0,
+            qty: 0.5,
+            ord_type: TradeOrderType::Market,
+            trade_id: id,
+            timestamp: "2024-01-15T10:30:00.120000Z".to_string(),
+        }
+    }
+
+    #[cfg(feature = "trades")]
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kraky-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_safe() {
+        assert_eq!(file_safe("BTC/USD"), "BTCUSD");
+    }
+
+    #[cfg(feature = "trades")]
+    #[test]
+    fn test_csv_output() {
+        let dir = temp_dir("csv");
+        let mut writer = writer(&dir, RecordFormat::Csv);
+        writer.write(&trade(1)).unwrap();
+        writer.write(&trade(2)).unwrap();
+        writer.close().unwrap();
+
+        let files = writer.shared.files.lock().clone();
+        assert_eq!(files.len(), 1);
+        let content = std::fs::read_to_string(&files[0]).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            "recorded_at,symbol,side,price,qty,ord_type,trade_id,timestamp"
+        );
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].ends_with(",BTC/USD,buy,42501,0.5,market,1,2024-01-15T10:30:00.120000Z"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "trades")]
+    #[test]
+    fn test_jsonl_output_and_size_rotation() {
+        let dir = temp_dir("jsonl");
+        let mut writer = writer(&dir, RecordFormat::JsonLines);
+        writer.max_bytes = Some(1);
+        writer.write(&trade(1)).unwrap();
+        writer.write(&trade(2)).unwrap();
+        writer.close().unwrap();
+
+        let files = writer.shared.files.lock().clone();
+        assert_eq!(files.len(), 2);
+        assert_eq!(writer.shared.records.load(Ordering::Relaxed), 2);
+
+        let line = std::fs::read_to_string(&files[1]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(value["channel"], "trade");
+        assert_eq!(value["data"]["trade_id"], 2);
+        assert!(value["recorded_at"].is_string());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
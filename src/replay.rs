@@ -0,0 +1,503 @@
+//! Replay recorded market data
+//!
+//! [`ReplayClient`] offers the same `subscribe_*` methods as
+//! [`KrakyClient`](crate::KrakyClient), but feeds them from JSON Lines files
+//! written by the `Recorder` (`recorder` feature) instead
+//! of a live connection. Strategies and alert rules can be tested offline and
+//! deterministically: messages are delivered in recorded order and never
+//! dropped.
+//!
+//! Requires the `replay` feature flag. Only the JSON Lines format can be
+//! replayed.
+//!
+//! ## Speed
+//!
+//! Messages are spaced by their original `recorded_at` gaps divided by the
+//! [`ReplaySpeed`] multiplier, or sent back to back with
+//! [`ReplaySpeed::AsFastAsPossible`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use kraky::replay::{ReplayClient, ReplaySpeed};
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let replay = Arc::new(
+//!         ReplayClient::open("recordings")?.with_speed(ReplaySpeed::Multiplier(10.0)),
+//!     );
+//!
+//!     // Subscribe before starting playback
+//!     let mut trades = replay.subscribe_trades("BTC/USD").await?;
+//!     let player = tokio::spawn({
+//!         let replay = Arc::clone(&replay);
+//!         async move { replay.run().await }
+//!     });
+//!
+//!     // Streams end when playback finishes
+//!     while let Some(trade) = trades.next().await {
+//!         println!("{} {} @ {}", trade.side, trade.qty, trade.price);
+//!     }
+//!     println!("Replayed {} messages", player.await??);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::error::{KrakyError, Result};
+use crate::subscriptions::{Subscription, SubscriptionSender};
+use crate::symbol::Symbol;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+#[cfg(feature = "trades")]
+use crate::models::Trade;
+#[cfg(feature = "ohlc")]
+use crate::models::{Interval, OHLC};
+#[cfg(feature = "orderbook")]
+use crate::models::{Orderbook, OrderbookUpdate, OrderbookUpdateType};
+
+/// Playback speed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Original timing
+    RealTime,
+    /// Original timing sped up by a factor (e.g. `10.0` for 10x)
+    Multiplier(f64),
+    /// No delays between messages
+    AsFastAsPossible,
+}
+
+impl ReplaySpeed {
+    /// Wall-clock delay for a gap in recorded time (`None` for no delay)
+    fn scale(&self, gap: Duration) -> Option<Duration> {
+        match self {
+            ReplaySpeed::RealTime => Some(gap),
+            ReplaySpeed::Multiplier(factor) if *factor > 0.0 => Some(gap.div_f64(*factor)),
+            ReplaySpeed::Multiplier(_) | ReplaySpeed::AsFastAsPossible => None,
+        }
+    }
+}
+
+/// A JSON Lines record as written by the recorder
+#[derive(Deserialize)]
+struct Line {
+    recorded_at: DateTime<Utc>,
+    channel: String,
+    data: serde_json::Value,
+}
+
+/// A recorded message
+#[derive(Debug, Clone)]
+enum Message {
+    #[cfg(feature = "trades")]
+    Trade(Arc<Trade>),
+    #[cfg(feature = "ticker")]
+    Ticker(Arc<Ticker>),
+    #[cfg(feature = "ohlc")]
+    Ohlc(Arc<OHLC>),
+    #[cfg(feature = "orderbook")]
+    Orderbook(Arc<OrderbookUpdate>),
+}
+
+/// Subscriptions waiting for playback
+#[derive(Default)]
+struct Senders {
+    #[cfg(feature = "trades")]
+    trades: Vec<SubscriptionSender<Arc<Trade>>>,
+    #[cfg(feature = "ticker")]
+    ticker: Vec<SubscriptionSender<Arc<Ticker>>>,
+    #[cfg(feature = "ohlc")]
+    ohlc: Vec<(u32, SubscriptionSender<Arc<OHLC>>)>,
+    #[cfg(feature = "orderbook")]
+    orderbook: Vec<SubscriptionSender<Arc<OrderbookUpdate>>>,
+}
+
+/// Client replaying recorded market data
+///
+/// Create it from recorded files, subscribe to the channels a strategy
+/// needs, then call [`run`](Self::run) to play the recording. Subscriptions
+/// made after playback starts receive nothing.
+pub struct ReplayClient {
+    messages: Vec<(DateTime<Utc>, Message)>,
+    speed: ReplaySpeed,
+    senders: Mutex<Senders>,
+    #[cfg(feature = "orderbook")]
+    orderbooks: RwLock<HashMap<String, Orderbook>>,
+}
+
+impl ReplayClient {
+    /// Load a recorded `.jsonl` file, or every `.jsonl` file in a directory
+    ///
+    /// Messages from all files are merged by `recorded_at`. Channels whose
+    /// data type feature is disabled are skipped.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut files = Vec::new();
+        if path.is_dir() {
+            for entry in std::fs::read_dir(path)? {
+                let file = entry?.path();
+                if file.extension().is_some_and(|ext| ext == "jsonl") {
+                    files.push(file);
+                }
+            }
+            files.sort();
+        } else {
+            files.push(path.to_path_buf());
+        }
+
+        let mut messages = Vec::new();
+        for file in &files {
+            let reader = std::io::BufReader::new(std::fs::File::open(file)?);
+            messages.extend(parse_lines(reader, &file.display().to_string())?);
+        }
+        Ok(Self::from_messages(messages))
+    }
+
+    /// Load recorded JSON Lines from any reader
+    pub fn from_reader(reader: impl BufRead) -> Result<Self> {
+        Ok(Self::from_messages(parse_lines(reader, "input")?))
+    }
+
+    fn from_messages(mut messages: Vec<(DateTime<Utc>, Message)>) -> Self {
+        // Stable, so messages recorded at the same instant keep file order
+        messages.sort_by_key(|(recorded_at, _)| *recorded_at);
+        Self {
+            messages,
+            speed: ReplaySpeed::AsFastAsPossible,
+            senders: Mutex::new(Senders::default()),
+            #[cfg(feature = "orderbook")]
+            orderbooks: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Set the playback speed (as fast as possible by default)
+    pub fn with_speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Number of recorded messages
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Check whether the recording is empty
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Time span covered by the recording
+    pub fn time_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        Some((self.messages.first()?.0, self.messages.last()?.0))
+    }
+
+    /// Recorded trades in playback order, without any pacing
+    #[cfg(feature = "backtest")]
+    pub(crate) fn recorded_trades(&self) -> impl Iterator<Item = (DateTime<Utc>, &Trade)> {
+        self.messages
+            .iter()
+            .filter_map(|(recorded_at, message)| match message {
+                Message::Trade(trade) => Some((*recorded_at, trade.as_ref())),
+                #[allow(unreachable_patterns)]
+                _ => None,
+            })
+    }
+
+    /// Subscribe to recorded trades for a pair (`"*"` for all pairs)
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub async fn subscribe_trades(
+        &self,
+        pair: impl Into<Symbol>,
+    ) -> Result<Subscription<Arc<Trade>>> {
+        let (sender, subscription) =
+            SubscriptionSender::new("trade".to_string(), pair.into().to_string());
+        self.senders.lock().trades.push(sender);
+        Ok(subscription)
+    }
+
+    /// Subscribe to recorded ticker updates for a pair (`"*"` for all pairs)
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub async fn subscribe_ticker(
+        &self,
+        pair: impl Into<Symbol>,
+    ) -> Result<Subscription<Arc<Ticker>>> {
+        let (sender, subscription) =
+            SubscriptionSender::new("ticker".to_string(), pair.into().to_string());
+        self.senders.lock().ticker.push(sender);
+        Ok(subscription)
+    }
+
+    /// Subscribe to recorded candles of one interval for a pair
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub async fn subscribe_ohlc(
+        &self,
+        pair: impl Into<Symbol>,
+        interval: Interval,
+    ) -> Result<Subscription<Arc<OHLC>>> {
+        let (sender, subscription) =
+            SubscriptionSender::new("ohlc".to_string(), pair.into().to_string());
+        self.senders.lock().ohlc.push((interval.minutes(), sender));
+        Ok(subscription)
+    }
+
+    /// Subscribe to recorded orderbook updates for a pair
+    ///
+    /// The orderbook state is available from
+    /// [`get_orderbook`](Self::get_orderbook) during playback. `depth` is
+    /// accepted for compatibility with [`KrakyClient`](crate::KrakyClient);
+    /// the recorded depth is replayed.
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    pub async fn subscribe_orderbook(
+        &self,
+        pair: impl Into<Symbol>,
+        _depth: u32,
+    ) -> Result<Subscription<Arc<OrderbookUpdate>>> {
+        let symbol = pair.into();
+        let pair = symbol.as_str();
+        let (sender, subscription) = SubscriptionSender::new("book".to_string(), pair.to_string());
+        self.orderbooks
+            .write()
+            .insert(pair.to_string(), Orderbook::new(pair.to_string()));
+        self.senders.lock().orderbook.push(sender);
+        Ok(subscription)
+    }
+
+    /// Get the replayed orderbook for a subscribed pair
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    pub fn get_orderbook(&self, pair: impl Into<Symbol>) -> Option<Orderbook> {
+        self.orderbooks.read().get(pair.into().as_str()).cloned()
+    }
+
+    /// Play the recording to all subscriptions
+    ///
+    /// Waits for slow consumers rather than dropping messages. Subscriptions
+    /// are closed when playback finishes. Returns the number of messages
+    /// played.
+    pub async fn run(&self) -> Result<usize> {
+        let mut senders = std::mem::take(&mut *self.senders.lock());
+        let start = tokio::time::Instant::now();
+        let first = self.messages.first().map(|(recorded_at, _)| *recorded_at);
+
+        for (recorded_at, message) in &self.messages {
+            let gap = first
+                .and_then(|first| (*recorded_at - first).to_std().ok())
+                .unwrap_or_default();
+            if let Some(delay) = self.speed.scale(gap) {
+                tokio::time::sleep_until(start + delay).await;
+            }
+            self.play(&mut senders, message).await;
+        }
+        Ok(self.messages.len())
+    }
+
+    async fn play(&self, senders: &mut Senders, message: &Message) {
+        match message {
+            #[cfg(feature = "trades")]
+            Message::Trade(trade) => dispatch(&mut senders.trades, &trade.symbol, trade).await,
+            #[cfg(feature = "ticker")]
+            Message::Ticker(ticker) => dispatch(&mut senders.ticker, &ticker.symbol, ticker).await,
+            #[cfg(feature = "ohlc")]
+            Message::Ohlc(candle) => {
+                let mut i = 0;
+                while i < senders.ohlc.len() {
+                    let (interval, sender) = &senders.ohlc[i];
+                    if *interval == candle.interval
+                        && matches_symbol(sender, &candle.symbol)
+                        && sender.send_wait(Arc::clone(candle)).await.is_err()
+                    {
+                        senders.ohlc.remove(i);
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            #[cfg(feature = "orderbook")]
+            Message::Orderbook(update) => {
+                {
+                    let mut orderbooks = self.orderbooks.write();
+                    for data in &update.data {
+                        if let Some(orderbook) = orderbooks.get_mut(&data.symbol) {
+                            if update.update_type == OrderbookUpdateType::Snapshot {
+                                *orderbook = Orderbook::new(data.symbol.clone());
+                            }
+                            orderbook.apply_update(data);
+                        }
+                    }
+                }
+                for data in &update.data {
+                    dispatch(&mut senders.orderbook, &data.symbol, update).await;
+                }
+            }
+        }
+    }
+}
+
+/// Check whether a subscription wants messages for `symbol`
+#[cfg(any(
+    feature = "trades",
+    feature = "ticker",
+    feature = "ohlc",
+    feature = "orderbook"
+))]
+fn matches_symbol<T>(sender: &SubscriptionSender<T>, symbol: &str) -> bool {
+    sender.symbol == symbol || sender.symbol == "*"
+}
+
+/// Send a message to matching subscriptions, removing closed ones
+#[cfg(any(feature = "trades", feature = "ticker", feature = "orderbook"))]
+async fn dispatch<T: Clone>(senders: &mut Vec<SubscriptionSender<T>>, symbol: &str, item: &T) {
+    let mut i = 0;
+    while i < senders.len() {
+        if matches_symbol(&senders[i], symbol) && senders[i].send_wait(item.clone()).await.is_err()
+        {
+            senders.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Parse recorded JSON Lines, skipping channels that can't be replayed
+fn parse_lines(reader: impl BufRead, source: &str) -> Result<Vec<(DateTime<Utc>, Message)>> {
+    let mut messages = Vec::new();
+    let mut skipped: HashMap<String, usize> = HashMap::new();
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |e: serde_json::Error| {
+            KrakyError::InvalidMessage(format!("{}:{}: {}", source, number + 1, e))
+        };
+
+        let line: Line = serde_json::from_str(&line).map_err(invalid)?;
+        let message = match line.channel.as_str() {
+            #[cfg(feature = "trades")]
+            "trade" => Message::Trade(Arc::new(
+                serde_json::from_value(line.data).map_err(invalid)?,
+            )),
+            #[cfg(feature = "ticker")]
+            "ticker" => Message::Ticker(Arc::new(
+                serde_json::from_value(line.data).map_err(invalid)?,
+            )),
+            #[cfg(feature = "ohlc")]
+            "ohlc" => Message::Ohlc(Arc::new(
+                serde_json::from_value(line.data).map_err(invalid)?,
+            )),
+            #[cfg(feature = "orderbook")]
+            "book" => Message::Orderbook(Arc::new(
+                serde_json::from_value(line.data).map_err(invalid)?,
+            )),
+            _ => {
+                *skipped.entry(line.channel).or_default() += 1;
+                continue;
+            }
+        };
+        messages.push((line.recorded_at, message));
+    }
+
+    for (channel, count) in skipped {
+        tracing::warn!(
+            "Skipped {} '{}' messages in {} (channel not supported or feature disabled)",
+            count,
+            channel,
+            source
+        );
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speed_scaling() {
+        let gap = Duration::from_secs(10);
+        assert_eq!(ReplaySpeed::RealTime.scale(gap), Some(gap));
+        assert_eq!(
+            ReplaySpeed::Multiplier(10.0).scale(gap),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(ReplaySpeed::AsFastAsPossible.scale(gap), None);
+    }
+
+    #[test]
+    fn test_invalid_line_reports_position() {
+        let input = "\n{\"recorded_at\":\"2024-01-15T10:30:00Z\",\"channel\":\"x\",\"data\":{}}\nnot json\n";
+        let error = ReplayClient::from_reader(input.as_bytes())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("input:3"), "{}", error);
+    }
+
+    #[cfg(feature = "trades")]
+    #[tokio::test]
+    async fn test_replay_trades_in_order() {
+        let line = |at: &str, id: i64, symbol: &str| {
+            format!(
+                r#"{{"recorded_at":"{}","channel":"trade","data":{{"symbol":"{}","side":"buy","price":100.0,"qty":1.0,"ord_type":"market","trade_id":{},"timestamp":"{}"}}}}"#,
+                at, symbol, id, at
+            )
+        };
+        // Out of order across "files"; ETH trade filtered by symbol
+        let input = [
+            line("2024-01-15T10:30:02Z", 3, "BTC/USD"),
+            line("2024-01-15T10:30:00Z", 1, "BTC/USD"),
+            line("2024-01-15T10:30:01Z", 2, "ETH/USD"),
+            line("2024-01-15T10:30:01Z", 4, "BTC/USD"),
+        ]
+        .join("\n");
+
+        let replay = ReplayClient::from_reader(input.as_bytes()).unwrap();
+        assert_eq!(replay.len(), 4);
+
+        let mut trades = replay.subscribe_trades("BTC/USD").await.unwrap();
+        assert_eq!(replay.run().await.unwrap(), 4);
+
+        let mut ids = Vec::new();
+        while let Some(trade) = trades.next().await {
+            ids.push(trade.trade_id);
+        }
+        assert_eq!(ids, vec![1, 4, 3]);
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[tokio::test]
+    async fn test_replay_orderbook_state() {
+        let input = [
+            r#"{"recorded_at":"2024-01-15T10:30:00Z","channel":"book","data":{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[{"price":100.0,"qty":1.0}],"asks":[{"price":101.0,"qty":1.0}]}]}}"#,
+            r#"{"recorded_at":"2024-01-15T10:30:01Z","channel":"book","data":{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":[{"price":100.5,"qty":2.0}],"asks":[]}]}}"#,
+        ]
+        .join("\n");
+
+        let replay = ReplayClient::from_reader(input.as_bytes()).unwrap();
+        let _book = replay.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+        replay.run().await.unwrap();
+
+        let orderbook = replay.get_orderbook("BTC/USD").unwrap();
+        assert_eq!(orderbook.best_bid(), Some(100.5));
+        assert_eq!(orderbook.best_ask(), Some(101.0));
+    }
+}
@@ -0,0 +1,1257 @@
+//! Kraken REST client for historical data
+//!
+//! [`RestClient`] fetches recent candles, trades and orderbook depth from
+//! Kraken's public REST API, so indicators, candle aggregators and local
+//! books can be seeded with history before live WebSocket data starts.
+//! Results use the same model types as the WebSocket subscriptions.
+//!
+//! Requires the `rest` feature flag, plus the feature of each data type
+//! (`ohlc`, `trades`, `orderbook`).
+//!
+//! Pairs are given in WebSocket form (`BTC/USD`) and converted to the REST
+//! names (`XBTUSD`); returned models carry the WebSocket symbol.
+//!
+//! With the `private` feature, [`RestClient`] retrieves the account's trade
+//! history and ledger for reconciliation; `KrakyClient::trade_history` and
+//! `KrakyClient::ledger` forward to it. It also fetches WebSocket tokens and
+//! checks API keys with [`RestClient::validate_credentials`].
+//!
+//! With the `trading` feature, [`RestClient`] can also place and cancel
+//! orders through the signed private endpoints. `KrakyClient` uses this as
+//! a fallback while the WebSocket is down, see
+//! `KrakyClient::set_order_fallback`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "ohlc")]
+//! # {
+//! use kraky::rest::RestClient;
+//! use kraky::Interval;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let rest = RestClient::new();
+//!
+//! // Up to 720 most recent candles; the last one is still forming
+//! let candles = rest.ohlc("BTC/USD", Interval::Hour1, None).await?;
+//! for candle in candles.iter().rev().take(3) {
+//!     println!("{} close {}", candle.interval_begin, candle.close);
+//! }
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+
+use crate::error::{KrakyError, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[cfg(any(feature = "trades", feature = "private"))]
+use serde::Serialize;
+
+#[cfg(feature = "private")]
+use crate::auth::Credentials;
+#[cfg(feature = "checksum")]
+use crate::models::BookPrecision;
+#[cfg(feature = "trading")]
+use crate::models::{
+    CancelOrderResponse, OrderParams, OrderResponse, OrderSide, OrderStatus, OrderType,
+};
+#[cfg(feature = "ohlc")]
+use crate::models::{Interval, OHLC};
+#[cfg(feature = "orderbook")]
+use crate::models::{Orderbook, OrderedFloat};
+#[cfg(feature = "trades")]
+use crate::models::{Trade, TradeOrderType, TradeSide};
+#[cfg(feature = "private")]
+use chrono::{DateTime, Utc};
+
+/// Kraken REST API base URL
+pub const KRAKEN_REST_URL: &str = "https://api.kraken.com";
+
+/// Envelope of every Kraken REST response
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    error: Vec<String>,
+    result: Option<Value>,
+}
+
+/// When order management falls back to REST
+///
+/// Only available when the `trading` feature is enabled.
+#[cfg(feature = "trading")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrderFallback {
+    /// Always use the WebSocket (default)
+    #[default]
+    Disabled,
+    /// Use `AddOrder`/`CancelOrder` while the WebSocket is not connected
+    WhenDisconnected,
+}
+
+/// A page of trades from [`RestClient::trades`]
+///
+/// Only available when the `trades` feature is enabled.
+#[cfg(feature = "trades")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentTrades {
+    /// Trades, oldest first
+    pub trades: Vec<Trade>,
+    /// Cursor to pass as `since` to fetch the next page
+    pub last: String,
+}
+
+/// Why a set of credentials was rejected
+///
+/// Returned by [`RestClient::validate_credentials`].
+///
+/// Only available when the `private` feature is enabled.
+#[cfg(feature = "private")]
+#[derive(Debug, thiserror::Error)]
+pub enum CredentialError {
+    /// The key, secret or signature was rejected (includes a wrong OTP)
+    #[error("invalid API key: {0}")]
+    InvalidKey(String),
+    /// The key lacks a permission the call needs
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    /// The nonce was not above the last one Kraken saw for this key
+    ///
+    /// Another process may be using the same key; give each its own key or
+    /// widen the nonce window in the key settings.
+    #[error("invalid nonce: {0}")]
+    InvalidNonce(String),
+    /// Kraken could not be reached
+    #[error("network error: {0}")]
+    Network(String),
+    /// Any other failure
+    #[error(transparent)]
+    Other(KrakyError),
+}
+
+#[cfg(feature = "private")]
+impl From<KrakyError> for CredentialError {
+    fn from(error: KrakyError) -> Self {
+        match error {
+            KrakyError::KrakenApi(api) => {
+                let message = api.message.to_ascii_lowercase();
+                if message.contains("nonce") {
+                    CredentialError::InvalidNonce(api.raw)
+                } else if message.contains("permission") {
+                    CredentialError::PermissionDenied(api.raw)
+                } else if message.contains("invalid key")
+                    || message.contains("invalid signature")
+                    || message.contains("otp")
+                {
+                    CredentialError::InvalidKey(api.raw)
+                } else {
+                    CredentialError::Other(KrakyError::KrakenApi(api))
+                }
+            }
+            // The secret could not be used for signing at all
+            KrakyError::InvalidMessage(message) if message.starts_with("Invalid API secret") => {
+                CredentialError::InvalidKey(message)
+            }
+            KrakyError::Api(message) => CredentialError::Network(message),
+            other => CredentialError::Other(other),
+        }
+    }
+}
+
+/// A tradable pair from the `AssetPairs` endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradingPair {
+    /// WebSocket symbol, e.g. `BTC/USD`
+    pub symbol: String,
+    /// Base asset, e.g. `BTC`
+    pub base: String,
+    /// Quote asset, e.g. `USD`
+    pub quote: String,
+    /// Trading status, e.g. `online` or `cancel_only`
+    pub status: String,
+}
+
+impl TradingPair {
+    /// Check whether the pair is open for trading
+    pub fn is_online(&self) -> bool {
+        self.status == "online"
+    }
+}
+
+/// Authentication token for private WebSocket channels
+///
+/// Only available when the `private` feature is enabled.
+#[cfg(feature = "private")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSocketToken {
+    /// Token to pass in private subscriptions
+    pub token: String,
+    /// Time left to make a first connection with the token
+    pub expires_in: std::time::Duration,
+}
+
+/// Time range for history queries
+///
+/// Only available when the `private` feature is enabled.
+#[cfg(feature = "private")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryRange {
+    /// Only entries after this time
+    pub start: Option<DateTime<Utc>>,
+    /// Only entries up to this time
+    pub end: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "private")]
+impl HistoryRange {
+    /// The whole account history
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Entries after `start`
+    pub fn since(start: DateTime<Utc>) -> Self {
+        Self {
+            start: Some(start),
+            end: None,
+        }
+    }
+
+    /// Entries between `start` and `end`
+    pub fn between(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            start: Some(start),
+            end: Some(end),
+        }
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(start) = self.start {
+            params.push(("start", start.timestamp().to_string()));
+        }
+        if let Some(end) = self.end {
+            params.push(("end", end.timestamp().to_string()));
+        }
+        params
+    }
+}
+
+/// A fill from the account's trade history
+///
+/// Only available when the `private` feature is enabled.
+#[cfg(feature = "private")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoricalTrade {
+    /// Trade transaction ID
+    pub txid: String,
+    /// ID of the order that was filled
+    pub order_id: String,
+    /// REST pair name (e.g. `XXBTZUSD`)
+    pub pair: String,
+    /// Execution time
+    pub time: DateTime<Utc>,
+    /// Side (buy/sell)
+    pub side: String,
+    /// Order type (market/limit/...)
+    pub order_type: String,
+    /// Execution price
+    pub price: f64,
+    /// Executed quantity
+    pub qty: f64,
+    /// Total cost in quote currency
+    pub cost: f64,
+    /// Fee in quote currency
+    pub fee: f64,
+    /// Whether the fill provided liquidity
+    pub maker: bool,
+}
+
+/// Kind of ledger entry
+///
+/// Only available when the `private` feature is enabled.
+#[cfg(feature = "private")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum LedgerType {
+    Trade,
+    Deposit,
+    Withdrawal,
+    Transfer,
+    Margin,
+    Rollover,
+    Spend,
+    Receive,
+    Settled,
+    Adjustment,
+    Staking,
+    /// Any type not listed above
+    Other(String),
+}
+
+#[cfg(feature = "private")]
+impl From<&str> for LedgerType {
+    fn from(kind: &str) -> Self {
+        match kind {
+            "trade" => Self::Trade,
+            "deposit" => Self::Deposit,
+            "withdrawal" => Self::Withdrawal,
+            "transfer" => Self::Transfer,
+            "margin" => Self::Margin,
+            "rollover" => Self::Rollover,
+            "spend" => Self::Spend,
+            "receive" => Self::Receive,
+            "settled" => Self::Settled,
+            "adjustment" => Self::Adjustment,
+            "staking" => Self::Staking,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "private")]
+impl From<String> for LedgerType {
+    fn from(kind: String) -> Self {
+        Self::from(kind.as_str())
+    }
+}
+
+#[cfg(feature = "private")]
+impl From<LedgerType> for String {
+    /// The type as Kraken names it (e.g. `"trade"`)
+    fn from(kind: LedgerType) -> Self {
+        match kind {
+            LedgerType::Trade => "trade",
+            LedgerType::Deposit => "deposit",
+            LedgerType::Withdrawal => "withdrawal",
+            LedgerType::Transfer => "transfer",
+            LedgerType::Margin => "margin",
+            LedgerType::Rollover => "rollover",
+            LedgerType::Spend => "spend",
+            LedgerType::Receive => "receive",
+            LedgerType::Settled => "settled",
+            LedgerType::Adjustment => "adjustment",
+            LedgerType::Staking => "staking",
+            LedgerType::Other(other) => return other,
+        }
+        .to_string()
+    }
+}
+
+/// A balance change from the account ledger
+///
+/// Only available when the `private` feature is enabled.
+#[cfg(feature = "private")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// Ledger entry ID
+    pub id: String,
+    /// Reference ID (the trade txid for trades)
+    pub ref_id: String,
+    /// Time of the change
+    pub time: DateTime<Utc>,
+    /// Kind of change
+    pub entry_type: LedgerType,
+    /// Additional classification (may be empty)
+    pub subtype: String,
+    /// REST asset name (e.g. `XXBT`, `ZUSD`)
+    pub asset: String,
+    /// Amount added (negative when removed)
+    pub amount: f64,
+    /// Fee paid in the asset
+    pub fee: f64,
+    /// Asset balance after the change
+    pub balance: f64,
+}
+
+/// Client for Kraken's public REST endpoints
+#[derive(Debug, Clone)]
+pub struct RestClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Default for RestClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RestClient {
+    /// Create a client for the production API
+    pub fn new() -> Self {
+        Self::with_base_url(KRAKEN_REST_URL)
+    }
+
+    /// Create a client for another base URL (for testing)
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Fetch recent candles for a pair
+    ///
+    /// Kraken returns at most 720 candles, starting after `since` (Unix
+    /// seconds) when given. The last candle is still forming.
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub async fn ohlc(
+        &self,
+        pair: &str,
+        interval: Interval,
+        since: Option<i64>,
+    ) -> Result<Vec<OHLC>> {
+        let mut params = vec![
+            ("pair", rest_pair(pair)),
+            ("interval", interval.to_api_string()),
+        ];
+        if let Some(since) = since {
+            params.push(("since", since.to_string()));
+        }
+        let result = self.public("OHLC", &params).await?;
+        parse_ohlc(&result, pair, interval)
+    }
+
+    /// Fetch up to 1000 trades for a pair
+    ///
+    /// Starts after the `since` cursor (the `last` of a previous page, or
+    /// Unix seconds) when given, otherwise returns the most recent trades.
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub async fn trades(&self, pair: &str, since: Option<&str>) -> Result<RecentTrades> {
+        let mut params = vec![("pair", rest_pair(pair))];
+        if let Some(since) = since {
+            params.push(("since", since.to_string()));
+        }
+        let result = self.public("Trades", &params).await?;
+        parse_trades(&result, pair)
+    }
+
+    /// Fetch the top `count` levels of a pair's orderbook
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    pub async fn depth(&self, pair: &str, count: u32) -> Result<Orderbook> {
+        let params = [("pair", rest_pair(pair)), ("count", count.to_string())];
+        let result = self.public("Depth", &params).await?;
+        parse_depth(&result, pair)
+    }
+
+    /// Fetch the price and quantity decimals of a pair
+    ///
+    /// Pass the result to `KrakyClient::set_book_precision` to validate a
+    /// book's checksums from its first snapshot.
+    ///
+    /// Only available when the `checksum` feature is enabled.
+    #[cfg(feature = "checksum")]
+    pub async fn book_precision(&self, pair: &str) -> Result<BookPrecision> {
+        let params = [("pair", rest_pair(pair))];
+        let result = self.public("AssetPairs", &params).await?;
+        parse_precision(&result)
+    }
+
+    /// Fetch every tradable pair
+    ///
+    /// Pairs without a WebSocket name, such as dark pool pairs, are left out.
+    pub async fn pairs(&self) -> Result<Vec<TradingPair>> {
+        let result = self.public("AssetPairs", &[]).await?;
+        Ok(parse_pairs(&result))
+    }
+
+    /// Call a public endpoint and return its `result`
+    async fn public(&self, method: &str, params: &[(&str, String)]) -> Result<Value> {
+        let url = format!("{}/0/public/{}", self.base_url, method);
+        let request = self.http.get(&url).query(params);
+        into_result(send(request, method).await?)
+    }
+
+    /// Place an order via the REST `AddOrder` endpoint
+    ///
+    /// Kraken's reply only acknowledges the order, so the returned status is
+    /// [`OrderStatus::Pending`]; follow the executions channel for fills.
+    ///
+    /// Only available when the `trading` feature is enabled.
+    #[cfg(feature = "trading")]
+    pub async fn add_order(
+        &self,
+        credentials: &Credentials,
+        params: &OrderParams,
+    ) -> Result<OrderResponse> {
+        let result = self
+            .private(credentials, "AddOrder", order_form(params))
+            .await?;
+        let order_id = result
+            .get("txid")
+            .and_then(|txid| txid.get(0))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+
+        Ok(OrderResponse {
+            order_id,
+            cl_ord_id: params.cl_ord_id.clone(),
+            order_status: OrderStatus::Pending,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Cancel an order via the REST `CancelOrder` endpoint
+    ///
+    /// `order_id` may be a transaction ID or a client order ID.
+    ///
+    /// Only available when the `trading` feature is enabled.
+    #[cfg(feature = "trading")]
+    pub async fn cancel_order(
+        &self,
+        credentials: &Credentials,
+        order_id: &str,
+    ) -> Result<CancelOrderResponse> {
+        let form = vec![("txid", order_id.to_string())];
+        let result = self.private(credentials, "CancelOrder", form).await?;
+
+        Ok(CancelOrderResponse {
+            order_id: order_id.to_string(),
+            success: result.get("count").and_then(Value::as_u64).unwrap_or(0) > 0,
+        })
+    }
+
+    /// Fetch a token for private WebSocket channels (`GetWebSocketsToken`)
+    ///
+    /// The key needs the "WebSocket interface" permission.
+    ///
+    /// Only available when the `private` feature is enabled.
+    #[cfg(feature = "private")]
+    pub async fn websocket_token(&self, credentials: &Credentials) -> Result<WebSocketToken> {
+        let result = self
+            .private(credentials, "GetWebSocketsToken", Vec::new())
+            .await?;
+        let token = result
+            .get("token")
+            .and_then(Value::as_str)
+            .ok_or_else(|| KrakyError::InvalidMessage("REST result has no token".to_string()))?;
+        let expires = result.get("expires").and_then(number).unwrap_or(0.0);
+        Ok(WebSocketToken {
+            token: token.to_string(),
+            expires_in: std::time::Duration::from_secs_f64(expires.max(0.0)),
+        })
+    }
+
+    /// Check that `credentials` can authenticate, with a cheap token fetch
+    ///
+    /// The error says whether the key itself, its permissions, the nonce or
+    /// the network is at fault.
+    ///
+    /// Only available when the `private` feature is enabled.
+    #[cfg(feature = "private")]
+    pub async fn validate_credentials(
+        &self,
+        credentials: &Credentials,
+    ) -> std::result::Result<(), CredentialError> {
+        self.websocket_token(credentials).await?;
+        Ok(())
+    }
+
+    /// Fetch the account's fills in `range`, oldest first
+    ///
+    /// Follows Kraken's 50-entry pages until the range is exhausted; each
+    /// page counts against the private API rate limit.
+    ///
+    /// Only available when the `private` feature is enabled.
+    #[cfg(feature = "private")]
+    pub async fn trade_history(
+        &self,
+        credentials: &Credentials,
+        range: HistoryRange,
+    ) -> Result<Vec<HistoricalTrade>> {
+        let mut trades = self
+            .history(credentials, "TradesHistory", "trades", range, parse_trade)
+            .await?;
+        trades.sort_by_key(|trade| trade.time);
+        Ok(trades)
+    }
+
+    /// Fetch the account's ledger entries in `range`, oldest first
+    ///
+    /// Follows Kraken's 50-entry pages like
+    /// [`trade_history`](Self::trade_history).
+    ///
+    /// Only available when the `private` feature is enabled.
+    #[cfg(feature = "private")]
+    pub async fn ledger(
+        &self,
+        credentials: &Credentials,
+        range: HistoryRange,
+    ) -> Result<Vec<LedgerEntry>> {
+        let mut entries = self
+            .history(credentials, "Ledgers", "ledger", range, parse_ledger_entry)
+            .await?;
+        entries.sort_by_key(|entry| entry.time);
+        Ok(entries)
+    }
+
+    /// Collect every page of a history endpoint keyed by entry ID
+    #[cfg(feature = "private")]
+    async fn history<T>(
+        &self,
+        credentials: &Credentials,
+        method: &str,
+        field: &str,
+        range: HistoryRange,
+        parse: fn(&str, &Value) -> Option<T>,
+    ) -> Result<Vec<T>> {
+        let mut entries = Vec::new();
+        loop {
+            let mut form = range.params();
+            form.push(("ofs", entries.len().to_string()));
+            let result = self.private(credentials, method, form).await?;
+
+            let page = result
+                .get(field)
+                .and_then(Value::as_object)
+                .ok_or_else(|| {
+                    KrakyError::InvalidMessage(format!("{} has no {}", method, field))
+                })?;
+            for (id, entry) in page {
+                entries.push(parse(id, entry).ok_or_else(|| bad_row(field, entry))?);
+            }
+
+            let count = result.get("count").and_then(Value::as_u64).unwrap_or(0);
+            if page.is_empty() || entries.len() as u64 >= count {
+                return Ok(entries);
+            }
+        }
+    }
+
+    /// Call a signed private endpoint and return its `result`
+    #[cfg(feature = "private")]
+    async fn private(
+        &self,
+        credentials: &Credentials,
+        method: &str,
+        form: Vec<(&str, String)>,
+    ) -> Result<Value> {
+        let path = format!("/0/private/{}", method);
+        let nonce = credentials.next_nonce();
+        let body = private_body(credentials, nonce, form);
+        let signature = credentials.sign_rest(&path, nonce, &body)?;
+
+        let request = self
+            .http
+            .post(format!("{}{}", self.base_url, path))
+            .header("API-Key", credentials.api_key())
+            .header("API-Sign", signature)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body);
+        into_result(send(request, method).await?)
+    }
+}
+
+/// Send a request and decode the reply, on a tokio runtime
+async fn send(request: reqwest::RequestBuilder, method: &str) -> Result<Response> {
+    let method = method.to_string();
+    crate::runtime::run(async move {
+        request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| KrakyError::Api(format!("REST {} failed: {}", method, e)))?
+            .json()
+            .await
+            .map_err(|e| KrakyError::Api(format!("REST {} returned invalid JSON: {}", method, e)))
+    })
+    .await?
+}
+
+/// URL-encoded body of a private request: nonce, OTP if set, then `form`
+#[cfg(feature = "private")]
+fn private_body(credentials: &Credentials, nonce: u64, form: Vec<(&str, String)>) -> String {
+    let mut body = url::form_urlencoded::Serializer::new(String::new());
+    body.append_pair("nonce", &nonce.to_string());
+    if let Some(otp) = credentials.otp() {
+        body.append_pair("otp", &otp);
+    }
+    body.extend_pairs(form).finish()
+}
+
+/// Kraken's errors, or the result
+fn into_result(response: Response) -> Result<Value> {
+    if let Some(error) = response.error.first() {
+        return Err(KrakyError::from_kraken_error(error));
+    }
+    response
+        .result
+        .ok_or_else(|| KrakyError::InvalidMessage("REST response has no result".to_string()))
+}
+
+/// REST pair name for a WebSocket symbol (`BTC/USD` -> `XBTUSD`)
+fn rest_pair(symbol: &str) -> String {
+    symbol
+        .split('/')
+        .map(|asset| match asset {
+            "BTC" => "XBT",
+            "DOGE" => "XDG",
+            other => other,
+        })
+        .collect()
+}
+
+/// The WebSocket v2 name of a REST asset, e.g. `BTC` for `XBT`
+fn ws_asset(asset: &str) -> &str {
+    match asset {
+        "XBT" => "BTC",
+        "XDG" => "DOGE",
+        other => other,
+    }
+}
+
+fn parse_pairs(result: &Value) -> Vec<TradingPair> {
+    let mut pairs: Vec<TradingPair> = result
+        .as_object()
+        .into_iter()
+        .flat_map(|entries| entries.values())
+        .filter_map(|entry| {
+            let (base, quote) = entry.get("wsname")?.as_str()?.split_once('/')?;
+            let (base, quote) = (ws_asset(base), ws_asset(quote));
+            Some(TradingPair {
+                symbol: format!("{}/{}", base, quote),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                status: entry
+                    .get("status")
+                    .and_then(Value::as_str)
+                    .unwrap_or("online")
+                    .to_string(),
+            })
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    pairs
+}
+
+/// The per-pair entry of a result (keyed by Kraken's pair name)
+fn pair_entry(result: &Value) -> Result<&Value> {
+    result
+        .as_object()
+        .and_then(|entries| {
+            entries
+                .iter()
+                .find(|(key, _)| key.as_str() != "last")
+                .map(|(_, value)| value)
+        })
+        .ok_or_else(|| KrakyError::InvalidMessage("REST result has no pair data".to_string()))
+}
+
+/// A number sent as either a JSON number or a string
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// RFC 3339 time for Unix seconds with fractions
+fn rfc3339(seconds: f64) -> String {
+    chrono::DateTime::from_timestamp_micros((seconds * 1e6).round() as i64)
+        .map(|time| time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true))
+        .unwrap_or_default()
+}
+
+fn rows(value: &Value) -> Result<&Vec<Value>> {
+    value
+        .as_array()
+        .ok_or_else(|| KrakyError::InvalidMessage("REST result is not a list".to_string()))
+}
+
+fn bad_row(kind: &str, row: &Value) -> KrakyError {
+    KrakyError::InvalidMessage(format!("Invalid {} row: {}", kind, row))
+}
+
+#[cfg(feature = "ohlc")]
+fn parse_ohlc(result: &Value, symbol: &str, interval: Interval) -> Result<Vec<OHLC>> {
+    let width = f64::from(interval.minutes()) * 60.0;
+    rows(pair_entry(result)?)?
+        .iter()
+        .map(|row| {
+            // [time, open, high, low, close, vwap, volume, count]
+            let field = |i: usize| row.get(i).and_then(number);
+            let (Some(begin), Some(open), Some(high), Some(low), Some(close)) =
+                (field(0), field(1), field(2), field(3), field(4))
+            else {
+                return Err(bad_row("OHLC", row));
+            };
+            Ok(OHLC {
+                symbol: symbol.to_string(),
+                open,
+                high,
+                low,
+                close,
+                vwap: field(5).unwrap_or(close),
+                volume: field(6).unwrap_or(0.0),
+                count: field(7).unwrap_or(0.0) as i64,
+                interval: interval.minutes(),
+                timestamp: rfc3339(begin + width),
+                interval_begin: rfc3339(begin),
+                synthetic: false,
+            })
+        })
+        .collect()
+}
+
+#[cfg(feature = "trades")]
+fn parse_trades(result: &Value, symbol: &str) -> Result<RecentTrades> {
+    let trades = rows(pair_entry(result)?)?
+        .iter()
+        .map(|row| {
+            // [price, volume, time, buy/sell, market/limit, miscellaneous, trade_id]
+            let field = |i: usize| row.get(i).and_then(number);
+            let flag = |i: usize| row.get(i).and_then(Value::as_str);
+            let (Some(price), Some(qty), Some(time), Some(side), Some(ord_type)) =
+                (field(0), field(1), field(2), flag(3), flag(4))
+            else {
+                return Err(bad_row("trade", row));
+            };
+            Ok(Trade {
+                symbol: symbol.to_string(),
+                side: if side == "s" {
+                    TradeSide::Sell
+                } else {
+                    TradeSide::Buy
+                },
+                price,
+                qty,
+                ord_type: if ord_type == "l" {
+                    TradeOrderType::Limit
+                } else {
+                    TradeOrderType::Market
+                },
+                trade_id: row.get(6).and_then(Value::as_i64).unwrap_or_default(),
+                timestamp: rfc3339(time),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let last = match result.get("last") {
+        Some(Value::String(last)) => last.clone(),
+        Some(last) => last.to_string(),
+        None => String::new(),
+    };
+    Ok(RecentTrades { trades, last })
+}
+
+#[cfg(feature = "checksum")]
+fn parse_precision(result: &Value) -> Result<BookPrecision> {
+    let entry = pair_entry(result)?;
+    let field = |name: &str| {
+        entry
+            .get(name)
+            .and_then(Value::as_u64)
+            .map(|decimals| decimals as u32)
+            .ok_or_else(|| KrakyError::InvalidMessage(format!("AssetPairs has no {}", name)))
+    };
+    Ok(BookPrecision::new(
+        field("pair_decimals")?,
+        field("lot_decimals")?,
+    ))
+}
+
+#[cfg(feature = "orderbook")]
+fn parse_depth(result: &Value, symbol: &str) -> Result<Orderbook> {
+    let entry = pair_entry(result)?;
+    let mut book = Orderbook::new(symbol.to_string());
+    let mut latest: f64 = 0.0;
+
+    for (side, levels) in [("bids", &mut book.bids), ("asks", &mut book.asks)] {
+        let Some(rows) = entry.get(side) else {
+            continue;
+        };
+        for row in self::rows(rows)? {
+            // [price, volume, timestamp]
+            let field = |i: usize| row.get(i).and_then(number);
+            let (Some(price), Some(qty)) = (field(0), field(1)) else {
+                return Err(bad_row("depth", row));
+            };
+            levels.insert(OrderedFloat(price), qty);
+            latest = latest.max(field(2).unwrap_or(0.0));
+        }
+    }
+    if latest > 0.0 {
+        book.timestamp = rfc3339(latest);
+    }
+    Ok(book)
+}
+
+/// UTC time for Unix seconds with fractions
+#[cfg(feature = "private")]
+fn utc(seconds: f64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_micros((seconds * 1e6).round() as i64)
+}
+
+#[cfg(feature = "private")]
+fn parse_trade(txid: &str, entry: &Value) -> Option<HistoricalTrade> {
+    let field = |name: &str| entry.get(name).and_then(number);
+    let text = |name: &str| entry.get(name).and_then(Value::as_str).map(str::to_string);
+    Some(HistoricalTrade {
+        txid: txid.to_string(),
+        order_id: text("ordertxid")?,
+        pair: text("pair")?,
+        time: utc(field("time")?)?,
+        side: text("type")?,
+        order_type: text("ordertype").unwrap_or_default(),
+        price: field("price")?,
+        qty: field("vol")?,
+        cost: field("cost").unwrap_or(0.0),
+        fee: field("fee").unwrap_or(0.0),
+        maker: entry.get("maker").and_then(Value::as_bool).unwrap_or(false),
+    })
+}
+
+#[cfg(feature = "private")]
+fn parse_ledger_entry(id: &str, entry: &Value) -> Option<LedgerEntry> {
+    let field = |name: &str| entry.get(name).and_then(number);
+    let text = |name: &str| entry.get(name).and_then(Value::as_str);
+    Some(LedgerEntry {
+        id: id.to_string(),
+        ref_id: text("refid").unwrap_or_default().to_string(),
+        time: utc(field("time")?)?,
+        entry_type: LedgerType::from(text("type")?),
+        subtype: text("subtype").unwrap_or_default().to_string(),
+        asset: text("asset")?.to_string(),
+        amount: field("amount")?,
+        fee: field("fee").unwrap_or(0.0),
+        balance: field("balance")?,
+    })
+}
+
+/// `AddOrder` form fields for WebSocket order parameters
+#[cfg(feature = "trading")]
+fn order_form(params: &OrderParams) -> Vec<(&'static str, String)> {
+    let order_type = match params.order_type {
+        OrderType::Market => "market",
+        OrderType::Limit => "limit",
+        OrderType::StopLoss => "stop-loss",
+        OrderType::StopLossLimit => "stop-loss-limit",
+        OrderType::TakeProfit => "take-profit",
+        OrderType::TakeProfitLimit => "take-profit-limit",
+        OrderType::TrailingStop => "trailing-stop",
+        OrderType::TrailingStopLimit => "trailing-stop-limit",
+        OrderType::Iceberg => "iceberg",
+    };
+    let side = match params.side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    };
+    let mut form = vec![
+        ("pair", rest_pair(&params.symbol)),
+        ("type", side.to_string()),
+        ("ordertype", order_type.to_string()),
+    ];
+    if let Some(qty) = params.order_qty {
+        form.push(("volume", qty.to_string()));
+    }
+    // Triggered orders take the trigger as `price` and the limit as `price2`
+    match (params.trigger_price, params.limit_price) {
+        (Some(trigger), limit) => {
+            form.push(("price", trigger.to_string()));
+            if let Some(limit) = limit {
+                form.push(("price2", limit.to_string()));
+            }
+        }
+        (None, Some(limit)) => form.push(("price", limit.to_string())),
+        (None, None) => {}
+    }
+    if let Some(tif) = &params.time_in_force {
+        form.push(("timeinforce", tif.as_rest_str().to_string()));
+    }
+    if let Some(expire_time) = params.expire_time {
+        form.push(("expiretm", expire_time.timestamp().to_string()));
+    }
+    if params.post_only == Some(true) {
+        form.push(("oflags", "post".to_string()));
+    }
+    if params.reduce_only == Some(true) {
+        form.push(("reduce_only", "true".to_string()));
+    }
+    if let Some(stp) = &params.stp {
+        form.push(("stptype", stp.as_rest_str().to_string()));
+    }
+    if let Some(id) = &params.cl_ord_id {
+        form.push(("cl_ord_id", id.clone()));
+    }
+    if params.validate == Some(true) {
+        form.push(("validate", "true".to_string()));
+    }
+    form
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rest_pair() {
+        assert_eq!(rest_pair("BTC/USD"), "XBTUSD");
+        assert_eq!(rest_pair("DOGE/EUR"), "XDGEUR");
+        assert_eq!(rest_pair("ETH/USDT"), "ETHUSDT");
+    }
+
+    #[test]
+    fn test_parse_pairs() {
+        let result = json!({
+            "XXBTZUSD": {"altname": "XBTUSD", "wsname": "XBT/USD", "status": "online"},
+            "XDGEUR": {"altname": "XDGEUR", "wsname": "XDG/EUR", "status": "cancel_only"},
+            "XXBTZUSD.d": {"altname": "XBTUSD.d"}
+        });
+        let pairs = parse_pairs(&result);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].symbol, "BTC/USD");
+        assert!(pairs[0].is_online());
+        assert_eq!(
+            (pairs[1].base.as_str(), pairs[1].quote.as_str()),
+            ("DOGE", "EUR")
+        );
+        assert!(!pairs[1].is_online());
+    }
+
+    #[test]
+    fn test_kraken_error() {
+        let response: Response =
+            serde_json::from_value(json!({"error": ["EQuery:Unknown asset pair"]})).unwrap();
+        assert!(matches!(
+            into_result(response),
+            Err(KrakyError::InvalidPair(_))
+        ));
+    }
+
+    #[cfg(feature = "ohlc")]
+    #[test]
+    fn test_parse_ohlc() {
+        let result = json!({
+            "XXBTZUSD": [
+                [1705312800, "42000.0", "42100.5", "41900.0", "42050.0", "42010.2", "12.5", 340],
+                [1705316400, "42050.0", "42200.0", "42000.0", "42150.0", "42100.0", "3.1", 95]
+            ],
+            "last": 1705312800
+        });
+        let candles = parse_ohlc(&result, "BTC/USD", Interval::Hour1).unwrap();
+
+        assert_eq!(candles.len(), 2);
+        let first = &candles[0];
+        assert_eq!(first.symbol, "BTC/USD");
+        assert_eq!(
+            (first.open, first.high, first.close),
+            (42000.0, 42100.5, 42050.0)
+        );
+        assert_eq!((first.count, first.interval), (340, 60));
+        assert_eq!(first.interval_begin, "2024-01-15T10:00:00.000000Z");
+        assert_eq!(first.timestamp, "2024-01-15T11:00:00.000000Z");
+    }
+
+    #[cfg(feature = "trades")]
+    #[test]
+    fn test_parse_trades() {
+        let result = json!({
+            "XXBTZUSD": [
+                ["42000.1", "0.015", 1705312800.1234, "s", "l", "", 67028120],
+                ["42001.0", "0.500", 1705312801.5, "b", "m", "", 67028121]
+            ],
+            "last": "1705312801500000000"
+        });
+        let page = parse_trades(&result, "BTC/USD").unwrap();
+
+        assert_eq!(page.last, "1705312801500000000");
+        assert_eq!(page.trades.len(), 2);
+        let trade = &page.trades[0];
+        assert_eq!(
+            (trade.side, trade.ord_type),
+            (TradeSide::Sell, TradeOrderType::Limit)
+        );
+        assert_eq!(
+            (trade.price, trade.qty, trade.trade_id),
+            (42000.1, 0.015, 67028120)
+        );
+        assert_eq!(trade.timestamp, "2024-01-15T10:00:00.123400Z");
+        assert_eq!(page.trades[1].side, TradeSide::Buy);
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_parse_depth() {
+        let result = json!({
+            "XXBTZUSD": {
+                "asks": [["42001.0", "1.5", 1705312800], ["42002.0", "0.2", 1705312801]],
+                "bids": [["42000.0", "2.0", 1705312799]]
+            }
+        });
+        let book = parse_depth(&result, "BTC/USD").unwrap();
+
+        assert_eq!(book.symbol, "BTC/USD");
+        assert_eq!(book.best_bid(), Some(42000.0));
+        assert_eq!(book.best_ask(), Some(42001.0));
+        assert_eq!(book.asks.len(), 2);
+        assert_eq!(book.timestamp, "2024-01-15T10:00:01.000000Z");
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_parse_precision() {
+        let result = json!({
+            "XXRPZEUR": {"altname": "XRPEUR", "pair_decimals": 5, "lot_decimals": 8}
+        });
+        assert_eq!(parse_precision(&result).unwrap(), BookPrecision::new(5, 8));
+        assert!(parse_precision(&json!({"XXRPZEUR": {}})).is_err());
+    }
+
+    #[cfg(feature = "private")]
+    #[test]
+    fn test_parse_history() {
+        let trade = json!({
+            "ordertxid": "OQCLML-BW3P3-BUCMWZ",
+            "postxid": "TKH2SE-M7IF5-CFI7LT",
+            "pair": "XXBTZUSD",
+            "time": 1705312800.5,
+            "type": "buy",
+            "ordertype": "limit",
+            "price": "42000.00000",
+            "cost": "420.00000",
+            "fee": "1.09200",
+            "vol": "0.01000000",
+            "margin": "0.00000",
+            "misc": "",
+            "maker": true
+        });
+        let trade = parse_trade("TXID-1", &trade).unwrap();
+        assert_eq!(trade.txid, "TXID-1");
+        assert_eq!((trade.price, trade.qty, trade.fee), (42000.0, 0.01, 1.092));
+        assert_eq!(trade.time.timestamp_millis(), 1705312800500);
+        assert!(trade.maker);
+
+        let entry = json!({
+            "aclass": "currency",
+            "amount": "-420.0000",
+            "asset": "ZUSD",
+            "balance": "9578.9080",
+            "fee": "1.0920",
+            "refid": "TXID-1",
+            "time": 1705312800.5,
+            "type": "trade",
+            "subtype": ""
+        });
+        let entry = parse_ledger_entry("L4UESK-KG3EQ-UFO4T5", &entry).unwrap();
+        assert_eq!(entry.entry_type, LedgerType::Trade);
+        assert_eq!((entry.amount, entry.balance), (-420.0, 9578.908));
+        assert_eq!(entry.ref_id, "TXID-1");
+
+        assert_eq!(
+            LedgerType::from("earn"),
+            LedgerType::Other("earn".to_string())
+        );
+        // Serialized as the Kraken type name
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.contains(r#""entry_type":"trade""#));
+        let parsed: LedgerEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, entry);
+        assert!(parse_ledger_entry("L1", &json!({"type": "trade"})).is_none());
+    }
+
+    #[cfg(feature = "private")]
+    #[test]
+    fn test_credential_errors() {
+        let classify = |error: &str| CredentialError::from(KrakyError::from_kraken_error(error));
+        assert!(matches!(
+            classify("EAPI:Invalid key"),
+            CredentialError::InvalidKey(_)
+        ));
+        assert!(matches!(
+            classify("EAPI:Invalid signature"),
+            CredentialError::InvalidKey(_)
+        ));
+        assert!(matches!(
+            classify("EGeneral:Permission denied"),
+            CredentialError::PermissionDenied(_)
+        ));
+        assert!(matches!(
+            classify("EAPI:Invalid nonce"),
+            CredentialError::InvalidNonce(_)
+        ));
+        assert!(matches!(
+            classify("EService:Unavailable"),
+            CredentialError::Other(KrakyError::KrakenApi(_))
+        ));
+        assert!(matches!(
+            CredentialError::from(KrakyError::Api("REST GetWebSocketsToken failed".into())),
+            CredentialError::Network(_)
+        ));
+
+        let bad_secret = Credentials::new("key", "not base64!")
+            .sign_rest("/0/private/GetWebSocketsToken", 1, "nonce=1")
+            .unwrap_err();
+        assert!(matches!(
+            CredentialError::from(bad_secret),
+            CredentialError::InvalidKey(_)
+        ));
+    }
+
+    #[cfg(feature = "private")]
+    #[test]
+    fn test_private_body() {
+        let creds = Credentials::new("key", "dGVzdF9zZWNyZXQ=");
+        let form = vec![("txid", "O1".to_string())];
+        assert_eq!(private_body(&creds, 42, form.clone()), "nonce=42&txid=O1");
+
+        let creds = creds.with_otp("12 34");
+        assert_eq!(private_body(&creds, 43, form), "nonce=43&otp=12+34&txid=O1");
+    }
+
+    #[cfg(feature = "trading")]
+    #[test]
+    fn test_order_form() {
+        use crate::models::TimeInForce;
+
+        let order = OrderParams::limit_buy("BTC/USD", 1.25, 37500.0)
+            .with_time_in_force(TimeInForce::IOC)
+            .with_post_only(true)
+            .with_client_id("my-order");
+        let form = order_form(&order);
+        let field = |name: &str| {
+            form.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        assert_eq!(field("pair"), Some("XBTUSD"));
+        assert_eq!(field("type"), Some("buy"));
+        assert_eq!(field("ordertype"), Some("limit"));
+        assert_eq!(field("volume"), Some("1.25"));
+        assert_eq!(field("price"), Some("37500"));
+        assert_eq!(field("price2"), None);
+        assert_eq!(field("timeinforce"), Some("IOC"));
+        assert_eq!(field("oflags"), Some("post"));
+        assert_eq!(field("cl_ord_id"), Some("my-order"));
+        assert_eq!(field("validate"), None);
+
+        let mut stop = OrderParams::market_sell("ETH/USD", 2.0);
+        stop.order_type = OrderType::StopLossLimit;
+        stop.trigger_price = Some(2400.0);
+        stop.limit_price = Some(2390.0);
+        let form = order_form(&stop);
+        assert!(form.contains(&("ordertype", "stop-loss-limit".to_string())));
+        assert!(form.contains(&("price", "2400".to_string())));
+        assert!(form.contains(&("price2", "2390".to_string())));
+
+        let expiry = chrono::DateTime::from_timestamp(1_717_243_200, 0).unwrap();
+        let gtd = OrderParams::limit_sell("BTC/USD", 0.1, 70000.0)
+            .with_expire_time(expiry)
+            .with_stp(crate::models::SelfTradePrevention::CancelOldest);
+        let form = order_form(&gtd);
+        assert!(form.contains(&("timeinforce", "GTD".to_string())));
+        assert!(form.contains(&("expiretm", "1717243200".to_string())));
+        assert!(form.contains(&("stptype", "cancel-oldest".to_string())));
+    }
+}
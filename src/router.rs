@@ -0,0 +1,279 @@
+//! Request IDs and response routing
+//!
+//! Kraken echoes the `req_id` of a request in every response to it.
+//! [`ReqIdRouter`] hands out request IDs and delivers the responses carrying
+//! them, either once or as a stream, so custom v2 methods get their answers
+//! without parsing the whole feed.
+//!
+//! The client routes with one router for all its connections, so its own
+//! subscribe requests and pings never share an ID with yours. Use
+//! [`KrakyClient::send_request`] to send a custom method and wait for the
+//! matched response.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::KrakyClient;
+//! use serde_json::json;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//!
+//! let response = client.send_request(json!({ "method": "ping" })).await?;
+//! println!("{} answered request {}", response["method"], response["req_id"]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`KrakyClient::send_request`]: crate::KrakyClient::send_request
+
+use futures_util::Stream;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::{mpsc, oneshot};
+
+/// Where responses to one request go
+#[derive(Debug)]
+enum Route {
+    /// The first response, then the route is removed
+    Once(oneshot::Sender<Value>),
+    /// Every response until the route is cancelled
+    Stream(mpsc::UnboundedSender<Value>),
+}
+
+/// Assigns request IDs and routes responses back by `req_id`
+///
+/// Cheap to share behind an `Arc`; all methods take `&self`.
+#[derive(Debug)]
+pub struct ReqIdRouter {
+    next_req_id: AtomicU64,
+    routes: Mutex<HashMap<u64, Route>>,
+}
+
+impl Default for ReqIdRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReqIdRouter {
+    /// Create a router handing out IDs from 1
+    pub fn new() -> Self {
+        Self {
+            next_req_id: AtomicU64::new(1),
+            routes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Allocate a request ID without routing its responses
+    pub fn next_req_id(&self) -> u64 {
+        self.next_req_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Allocate a request ID and receive its first response
+    ///
+    /// The receiver errors if the route is cancelled or cleared first.
+    pub fn register(&self) -> (u64, oneshot::Receiver<Value>) {
+        let req_id = self.next_req_id();
+        let (tx, rx) = oneshot::channel();
+        self.routes.lock().insert(req_id, Route::Once(tx));
+        (req_id, rx)
+    }
+
+    /// Allocate a request ID and receive every response to it
+    ///
+    /// The stream ends once the route is cancelled or cleared.
+    pub fn register_stream(&self) -> (u64, mpsc::UnboundedReceiver<Value>) {
+        let req_id = self.next_req_id();
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.routes.lock().insert(req_id, Route::Stream(tx));
+        (req_id, rx)
+    }
+
+    /// Deliver a message to the route of its `req_id`
+    ///
+    /// Returns whether the message was routed; messages without a `req_id`
+    /// or for an unknown one are left to the caller.
+    pub fn route(&self, message: &Value) -> bool {
+        let Some(req_id) = message.get("req_id").and_then(Value::as_u64) else {
+            return false;
+        };
+        let mut routes = self.routes.lock();
+        match routes.remove(&req_id) {
+            Some(Route::Once(tx)) => {
+                let _ = tx.send(message.clone());
+                true
+            }
+            Some(Route::Stream(tx)) => {
+                // Keep routing while someone is listening
+                if tx.send(message.clone()).is_ok() {
+                    routes.insert(req_id, Route::Stream(tx));
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Parse a text frame and route it, see [`route`](Self::route)
+    ///
+    /// Frames without a `req_id` are skipped without parsing. Otherwise only
+    /// the top-level `req_id` is read, and the frame is parsed in full just
+    /// when a route for it exists.
+    pub fn route_text(&self, text: &str) -> bool {
+        if !text.contains("\"req_id\"") || self.is_empty() {
+            return false;
+        }
+        match crate::messages::KrakyMessage::peek_req_id(text) {
+            Some(req_id) if self.is_routed(req_id) => {
+                serde_json::from_str(text).is_ok_and(|message| self.route(&message))
+            }
+            _ => false,
+        }
+    }
+
+    /// Check whether responses to a request ID are routed
+    pub fn is_routed(&self, req_id: u64) -> bool {
+        self.routes.lock().contains_key(&req_id)
+    }
+
+    /// Stop routing responses to a request
+    pub fn cancel(&self, req_id: u64) {
+        self.routes.lock().remove(&req_id);
+    }
+
+    /// Stop routing everything, so waiters see the connection closed
+    pub fn clear(&self) {
+        self.routes.lock().clear();
+    }
+
+    /// Get the number of requests whose responses are routed
+    pub fn len(&self) -> usize {
+        self.routes.lock().len()
+    }
+
+    /// Check whether no responses are routed
+    pub fn is_empty(&self) -> bool {
+        self.routes.lock().is_empty()
+    }
+}
+
+/// Responses to one request, from [`KrakyClient::send_request_stream`]
+///
+/// Dropping the stream stops routing responses to it.
+///
+/// [`KrakyClient::send_request_stream`]: crate::KrakyClient::send_request_stream
+#[derive(Debug)]
+pub struct ResponseStream {
+    req_id: u64,
+    receiver: mpsc::UnboundedReceiver<Value>,
+    router: Arc<ReqIdRouter>,
+}
+
+impl ResponseStream {
+    pub(crate) fn new(
+        req_id: u64,
+        receiver: mpsc::UnboundedReceiver<Value>,
+        router: Arc<ReqIdRouter>,
+    ) -> Self {
+        Self {
+            req_id,
+            receiver,
+            router,
+        }
+    }
+
+    /// Get the request ID the responses answer
+    pub fn req_id(&self) -> u64 {
+        self.req_id
+    }
+
+    /// Get the next response
+    ///
+    /// Returns `None` once the client shut down.
+    pub async fn next(&mut self) -> Option<Value> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for ResponseStream {
+    type Item = Value;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Value>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Drop for ResponseStream {
+    fn drop(&mut self) {
+        self.router.cancel(self.req_id);
+    }
+}
+
+/// Cancels a one-shot route when the waiting future is dropped
+pub(crate) struct RouteGuard<'a> {
+    pub(crate) router: &'a ReqIdRouter,
+    pub(crate) req_id: u64,
+}
+
+impl Drop for RouteGuard<'_> {
+    fn drop(&mut self) {
+        self.router.cancel(self.req_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_routes_responses_by_req_id() {
+        let router = ReqIdRouter::new();
+        let (once_id, once) = router.register();
+        let (stream_id, mut stream) = router.register_stream();
+        assert_ne!(once_id, stream_id);
+        assert_eq!(router.len(), 2);
+
+        assert!(!router.route(&json!({ "channel": "heartbeat" })));
+        assert!(!router.route_text(r#"{"method":"pong","req_id":999}"#));
+        assert!(router.route_text(&json!({ "req_id": once_id, "success": true }).to_string()));
+        assert_eq!(once.await.unwrap()["success"], true);
+        // One-shot routes are removed after the first response
+        assert!(!router.route(&json!({ "req_id": once_id })));
+
+        for n in 0..2 {
+            assert!(router.route(&json!({ "req_id": stream_id, "n": n })));
+        }
+        assert_eq!(stream.recv().await.unwrap()["n"], 0);
+        assert_eq!(stream.recv().await.unwrap()["n"], 1);
+
+        router.clear();
+        assert!(router.is_empty());
+        assert!(stream.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_route_text_needs_a_routed_top_level_req_id() {
+        let router = ReqIdRouter::new();
+        let (req_id, response) = router.register();
+
+        // Unknown ID
+        assert!(!router.route_text(&json!({ "method": "pong", "req_id": req_id + 1 }).to_string()));
+        // "req_id" only inside a string or a nested object
+        let quoted = json!({ "channel": "status", "note": format!("\"req_id\":{}", req_id) });
+        assert!(!router.route_text(&quoted.to_string()));
+        let nested = json!({ "channel": "status", "data": [{ "req_id": req_id }] });
+        assert!(!router.route_text(&nested.to_string()));
+        assert!(router.is_routed(req_id));
+
+        assert!(router.route_text(&json!({ "method": "pong", "req_id": req_id }).to_string()));
+        assert_eq!(response.await.unwrap()["req_id"], req_id);
+        assert!(!router.is_routed(req_id));
+    }
+}
@@ -0,0 +1,195 @@
+//! Executor the client's I/O and background tasks run on
+//!
+//! Sockets, timers and REST requests are always driven by tokio, which is a
+//! required dependency. Inside a tokio runtime, e.g. under `#[tokio::main]`,
+//! the client spawns its connection tasks onto that runtime.
+//!
+//! The `tokio-background` feature lets the client be awaited from other
+//! executors such as async-std, smol or `futures::executor::block_on`. When
+//! no tokio runtime is current, connecting, the background tasks and REST
+//! requests run on a small tokio runtime the crate starts on its own
+//! threads, and the caller only awaits channels and join handles, which
+//! don't depend on the executor. This is not runtime-agnostic I/O: tokio
+//! still owns every socket and timer. Integrations built on tokio libraries
+//! (Telegram, PostgreSQL, SQLite, the message bus bridge) still need a tokio
+//! runtime.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "orderbook")]
+//! # {
+//! use kraky::KrakyClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! // With `tokio-background`, also runs under async-std or smol
+//! let client = KrakyClient::connect().await?;
+//! let mut orderbook = client.subscribe_orderbook("BTC/USD", 10).await?;
+//! while let Some(update) = orderbook.next().await {
+//!     println!("{:?}", update.data[0].symbol);
+//! }
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+
+#[cfg(feature = "tokio-background")]
+use crate::error::KrakyError;
+use crate::error::Result;
+use std::future::Future;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+/// Spawn a background task on the current tokio runtime
+///
+/// With the `tokio-background` feature, outside a tokio runtime the task
+/// goes to the background runtime.
+pub(crate) fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    handle().spawn(future)
+}
+
+/// Run a future that needs tokio's reactor or timers
+///
+/// Inside a tokio runtime the future is awaited in place. With the
+/// `tokio-background` feature, outside one it runs on the background runtime
+/// and the result is passed back, or [`KrakyError::ConnectionClosed`] if
+/// the runtime cancelled the task while shutting down.
+pub(crate) async fn run<F>(future: F) -> Result<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(feature = "tokio-background")]
+    if Handle::try_current().is_err() {
+        return match background().spawn(future).await {
+            Ok(output) => Ok(output),
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(_) => Err(KrakyError::ConnectionClosed),
+        };
+    }
+    Ok(future.await)
+}
+
+/// Wait out `duration` on tokio's timer, yielding its [`Elapsed`] error
+///
+/// Like [`run`], this works outside a tokio runtime with the
+/// `tokio-background` feature, for timeouts around executor-neutral futures,
+/// and fails the same way if the runtime shuts down.
+///
+/// [`Elapsed`]: tokio::time::error::Elapsed
+pub(crate) async fn deadline(duration: std::time::Duration) -> Result<tokio::time::error::Elapsed> {
+    // Built inside the task, as tokio timers need the runtime on creation
+    let timeout =
+        run(async move { tokio::time::timeout(duration, std::future::pending::<()>()).await })
+            .await?;
+    Ok(timeout.expect_err("a pending future never completes"))
+}
+
+/// The runtime background tasks are spawned on
+fn handle() -> Handle {
+    #[cfg(feature = "tokio-background")]
+    {
+        Handle::try_current().unwrap_or_else(|_| background().clone())
+    }
+    #[cfg(not(feature = "tokio-background"))]
+    {
+        Handle::current()
+    }
+}
+
+/// Background runtime for callers outside tokio, started on first use
+#[cfg(feature = "tokio-background")]
+fn background() -> &'static Handle {
+    static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(2)
+                .thread_name("kraky-runtime")
+                .enable_all()
+                .build()
+                .expect("failed to start the Kraky runtime")
+        })
+        .handle()
+}
+
+#[cfg(all(test, feature = "tokio-background"))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::Thread;
+
+    /// Minimal executor without a tokio context
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct Unpark(Thread);
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_runs_outside_tokio() {
+        assert!(Handle::try_current().is_err());
+        let slept = block_on(run(async {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            "slept"
+        }));
+        assert_eq!(slept.unwrap(), "slept");
+        assert_eq!(block_on(spawn(async { 42 })).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_next_timeout_outside_tokio() {
+        use crate::subscriptions::SubscriptionSender;
+        use std::time::Duration;
+
+        let (sender, mut subscription) =
+            SubscriptionSender::<u32>::new("trade".to_string(), "BTC/USD".to_string());
+        sender.send(1).unwrap();
+        block_on(async {
+            let first = subscription.next_timeout(Duration::from_secs(5)).await;
+            assert_eq!(first.unwrap(), Some(1));
+            let timed_out = subscription.next_timeout(Duration::from_millis(10)).await;
+            assert!(timed_out.is_err());
+        });
+    }
+
+    #[cfg(all(feature = "orderbook", feature = "testing"))]
+    #[test]
+    fn test_client_outside_tokio() {
+        use crate::testing::{MockScript, MockServer};
+        use crate::KrakyClient;
+
+        let server = block_on(run(MockServer::start(
+            MockScript::new().message(crate::testing::fixtures::get("book_snapshot").unwrap().text),
+        )))
+        .unwrap()
+        .unwrap();
+        let url = server.url().to_string();
+
+        block_on(async {
+            let client = KrakyClient::connect_with_url(&url).await.unwrap();
+            let mut book = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+            let update = book.next().await.unwrap();
+            assert_eq!(update.data[0].symbol, "BTC/USD");
+            client.shutdown().await;
+        });
+    }
+}
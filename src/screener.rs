@@ -0,0 +1,518 @@
+//! Market screener across tradable pairs
+//!
+//! [`Screener`] subscribes to the tickers of every online pair from
+//! Kraken's instrument list (the REST `AssetPairs` endpoint), narrowed by
+//! quote currency, a filter or an explicit list. For each pair it keeps
+//! rolling metrics:
+//!
+//! - the 24h price change,
+//! - the 24h volume and a volume spike: the volume traded within a recent
+//!   window relative to the 24h average rate,
+//! - the spread in basis points.
+//!
+//! Every interval it ranks the pairs by the chosen [`ScreenMetric`] and
+//! emits a [`ScreenResult`].
+//!
+//! Requires the `screener` feature flag.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::{KrakyClient, ScreenMetric, Screener};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Arc::new(KrakyClient::connect().await?);
+//! let mut screener = Screener::new(client)
+//!     .quote("USD")
+//!     .rank_by(ScreenMetric::VolumeSpike)
+//!     .top(10)
+//!     .interval(Duration::from_secs(30))
+//!     .start()
+//!     .await?;
+//!
+//! while let Some(result) = screener.next().await {
+//!     for row in &result.rows {
+//!         println!(
+//!             "{:<10} {:+6.2}% spike {:.1}x spread {:.1} bps",
+//!             row.symbol, row.change_pct, row.volume_spike, row.spread_bps
+//!         );
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::KrakyClient;
+use crate::error::{KrakyError, Result};
+use crate::models::Ticker;
+use crate::rest::{RestClient, TradingPair};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Results buffered before new ones are skipped
+const RESULT_BUFFER: usize = 16;
+
+/// Metric pairs are ranked by, highest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenMetric {
+    /// 24h price change in percent
+    #[default]
+    ChangePct,
+    /// 24h price change in percent, either direction
+    AbsChangePct,
+    /// 24h volume in the quote currency
+    QuoteVolume,
+    /// Recent volume relative to the 24h average
+    VolumeSpike,
+    /// Spread in basis points
+    SpreadBps,
+}
+
+impl ScreenMetric {
+    /// Get the value of this metric for a pair
+    pub fn value(&self, metrics: &PairMetrics) -> f64 {
+        match self {
+            ScreenMetric::ChangePct => metrics.change_pct,
+            ScreenMetric::AbsChangePct => metrics.change_pct.abs(),
+            ScreenMetric::QuoteVolume => metrics.quote_volume,
+            ScreenMetric::VolumeSpike => metrics.volume_spike,
+            ScreenMetric::SpreadBps => metrics.spread_bps,
+        }
+    }
+}
+
+/// Rolling metrics of one pair
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairMetrics {
+    /// Trading pair
+    pub symbol: String,
+    /// Last trade price
+    pub last: f64,
+    /// 24h price change in percent
+    pub change_pct: f64,
+    /// 24h volume in the base asset
+    pub volume: f64,
+    /// 24h volume in the quote currency, at the VWAP
+    pub quote_volume: f64,
+    /// Volume traded within the spike window relative to the 24h average
+    /// rate; 1.0 is average, 0.0 until the window is covered
+    pub volume_spike: f64,
+    /// Spread in basis points
+    pub spread_bps: f64,
+}
+
+/// Ranked pairs at one point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScreenResult {
+    /// When the pairs were ranked
+    pub timestamp: DateTime<Utc>,
+    /// Metric the rows are ranked by
+    pub metric: ScreenMetric,
+    /// Pairs with a ticker, best first
+    pub rows: Vec<PairMetrics>,
+}
+
+/// Volume samples of one pair for the spike window
+#[derive(Debug)]
+struct PairState {
+    ticker: Ticker,
+    /// 24h volume readings, oldest first
+    volumes: VecDeque<(Instant, f64)>,
+}
+
+/// Rolling metrics of every screened pair
+#[derive(Debug)]
+struct MetricsTable {
+    window: Duration,
+    pairs: HashMap<String, PairState>,
+}
+
+impl MetricsTable {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pairs: HashMap::new(),
+        }
+    }
+
+    fn update(&mut self, ticker: &Ticker, now: Instant) {
+        let state = self
+            .pairs
+            .entry(ticker.symbol.clone())
+            .or_insert_with(|| PairState {
+                ticker: ticker.clone(),
+                volumes: VecDeque::new(),
+            });
+        state.ticker = ticker.clone();
+        state.volumes.push_back((now, ticker.volume));
+        // Keep one sample at or before the window start as the baseline
+        while state.volumes.len() > 1 && now.duration_since(state.volumes[1].0) >= self.window {
+            state.volumes.pop_front();
+        }
+    }
+
+    fn metrics(&self, state: &PairState, now: Instant) -> PairMetrics {
+        let ticker = &state.ticker;
+        let volume_spike = match state.volumes.front() {
+            Some((since, volume))
+                if now.duration_since(*since) >= self.window && ticker.volume > 0.0 =>
+            {
+                let recent = (ticker.volume - volume).max(0.0);
+                let average = ticker.volume * self.window.as_secs_f64() / 86_400.0;
+                recent / average
+            }
+            _ => 0.0,
+        };
+        PairMetrics {
+            symbol: ticker.symbol.clone(),
+            last: ticker.last,
+            change_pct: ticker.change_pct,
+            volume: ticker.volume,
+            quote_volume: ticker.volume * ticker.vwap,
+            volume_spike,
+            spread_bps: ticker.spread_bps().unwrap_or_default(),
+        }
+    }
+
+    fn rank(&self, metric: ScreenMetric, top: Option<usize>, now: Instant) -> ScreenResult {
+        let mut rows: Vec<_> = self
+            .pairs
+            .values()
+            .map(|state| self.metrics(state, now))
+            .collect();
+        rows.sort_by(|a, b| {
+            metric
+                .value(b)
+                .total_cmp(&metric.value(a))
+                .then_with(|| a.symbol.cmp(&b.symbol))
+        });
+        if let Some(top) = top {
+            rows.truncate(top);
+        }
+        ScreenResult {
+            timestamp: Utc::now(),
+            metric,
+            rows,
+        }
+    }
+}
+
+/// Filter on the instrument list
+type PairFilter = Box<dyn Fn(&TradingPair) -> bool + Send + Sync>;
+
+/// Ranks pairs by ticker metrics
+///
+/// Configure the pairs and ranking, then [`start`](Self::start) it.
+pub struct Screener {
+    client: Arc<KrakyClient>,
+    rest: RestClient,
+    pairs: Option<Vec<String>>,
+    quote: Option<String>,
+    filter: Option<PairFilter>,
+    metric: ScreenMetric,
+    top: Option<usize>,
+    interval: Duration,
+    spike_window: Duration,
+}
+
+impl std::fmt::Debug for Screener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Screener")
+            .field("pairs", &self.pairs)
+            .field("quote", &self.quote)
+            .field("metric", &self.metric)
+            .field("top", &self.top)
+            .field("interval", &self.interval)
+            .field("spike_window", &self.spike_window)
+            .finish()
+    }
+}
+
+impl Screener {
+    /// Create a screener over every online pair
+    pub fn new(client: Arc<KrakyClient>) -> Self {
+        Self {
+            client,
+            rest: RestClient::new(),
+            pairs: None,
+            quote: None,
+            filter: None,
+            metric: ScreenMetric::default(),
+            top: None,
+            interval: Duration::from_secs(60),
+            spike_window: Duration::from_secs(300),
+        }
+    }
+
+    /// Fetch the instrument list through `rest` (for testing)
+    pub fn rest(mut self, rest: RestClient) -> Self {
+        self.rest = rest;
+        self
+    }
+
+    /// Screen exactly these pairs instead of the instrument list
+    pub fn pairs<I, S>(mut self, pairs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.pairs = Some(pairs.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Only screen pairs quoted in this currency, e.g. `USD`
+    pub fn quote(mut self, quote: impl Into<String>) -> Self {
+        self.quote = Some(quote.into());
+        self
+    }
+
+    /// Only screen pairs `filter` returns `true` for
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&TradingPair) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Rank by this metric (24h change by default)
+    pub fn rank_by(mut self, metric: ScreenMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Only include the best `top` pairs in results
+    pub fn top(mut self, top: usize) -> Self {
+        self.top = Some(top);
+        self
+    }
+
+    /// Emit results this often (default 60 seconds)
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Measure volume spikes over this window (default 5 minutes)
+    pub fn spike_window(mut self, window: Duration) -> Self {
+        self.spike_window = window;
+        self
+    }
+
+    /// Subscribe to the tickers and start emitting results
+    pub async fn start(self) -> Result<ScreenerHandle> {
+        let pairs = match &self.pairs {
+            Some(pairs) => pairs.clone(),
+            None => self
+                .rest
+                .pairs()
+                .await?
+                .into_iter()
+                .filter(|pair| pair.is_online())
+                .filter(|pair| self.quote.as_ref().map_or(true, |q| pair.quote == *q))
+                .filter(|pair| self.filter.as_ref().map_or(true, |f| f(pair)))
+                .map(|pair| pair.symbol)
+                .collect(),
+        };
+        if pairs.is_empty() {
+            return Err(KrakyError::InvalidMessage(
+                "Screener has no pairs to screen".to_string(),
+            ));
+        }
+
+        let table = Arc::new(Mutex::new(MetricsTable::new(self.spike_window)));
+        let mut tasks = Vec::new();
+        for pair in &pairs {
+            let mut tickers = self.client.subscribe_ticker(pair.as_str()).await?;
+            let table = Arc::clone(&table);
+            tasks.push(crate::runtime::spawn(async move {
+                while let Some(ticker) = tickers.next().await {
+                    table.lock().update(&ticker, Instant::now());
+                }
+            }));
+        }
+
+        let (tx, rx) = mpsc::channel(RESULT_BUFFER);
+        let (metric, top, interval) = (self.metric, self.top, self.interval);
+        let ranking = Arc::clone(&table);
+        tasks.push(crate::runtime::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let result = ranking.lock().rank(metric, top, Instant::now());
+                if let Err(mpsc::error::TrySendError::Closed(_)) = tx.try_send(result) {
+                    break;
+                }
+            }
+        }));
+
+        Ok(ScreenerHandle {
+            _client: self.client,
+            pairs,
+            metric,
+            top,
+            table,
+            results: rx,
+            tasks,
+        })
+    }
+}
+
+/// Running [`Screener`]
+pub struct ScreenerHandle {
+    /// Keeps the connection open while screening
+    _client: Arc<KrakyClient>,
+    pairs: Vec<String>,
+    metric: ScreenMetric,
+    top: Option<usize>,
+    table: Arc<Mutex<MetricsTable>>,
+    results: mpsc::Receiver<ScreenResult>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for ScreenerHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScreenerHandle")
+            .field("pairs", &self.pairs.len())
+            .field("metric", &self.metric)
+            .finish()
+    }
+}
+
+impl ScreenerHandle {
+    /// Get the screened pairs
+    pub fn pairs(&self) -> &[String] {
+        &self.pairs
+    }
+
+    /// Wait for the next periodic result
+    pub async fn next(&mut self) -> Option<ScreenResult> {
+        self.results.recv().await
+    }
+
+    /// Rank the pairs now
+    pub fn snapshot(&self) -> ScreenResult {
+        self.table
+            .lock()
+            .rank(self.metric, self.top, Instant::now())
+    }
+
+    /// Stop screening
+    ///
+    /// The ticker subscriptions end with the tasks forwarding them.
+    pub fn stop(&self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for ScreenerHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticker(symbol: &str, change_pct: f64, volume: f64, bid: f64, ask: f64) -> Ticker {
+        Ticker {
+            symbol: symbol.to_string(),
+            bid,
+            bid_qty: 1.0,
+            ask,
+            ask_qty: 1.0,
+            last: ask,
+            volume,
+            vwap: 10.0,
+            low: bid,
+            high: ask,
+            change: 0.0,
+            change_pct,
+        }
+    }
+
+    #[test]
+    fn test_metrics_and_ranking() {
+        let window = Duration::from_secs(300);
+        let mut table = MetricsTable::new(window);
+        let start = Instant::now();
+
+        table.update(&ticker("BTC/USD", 2.0, 1_000.0, 99.0, 101.0), start);
+        table.update(&ticker("ETH/USD", -5.0, 2_880.0, 99.9, 100.1), start);
+        let later = start + window;
+        // 1000 over 24h averages ~3.47 per 5 minutes; 1010 - 1000 is ~2.9x that
+        table.update(&ticker("BTC/USD", 2.5, 1_010.0, 99.0, 101.0), later);
+        table.update(&ticker("ETH/USD", -5.0, 2_880.0, 99.9, 100.1), later);
+
+        let result = table.rank(ScreenMetric::VolumeSpike, None, later);
+        assert_eq!(result.rows[0].symbol, "BTC/USD");
+        assert!((result.rows[0].volume_spike - 10.0 / (1_010.0 / 288.0)).abs() < 1e-9);
+        assert_eq!(result.rows[1].volume_spike, 0.0);
+
+        let result = table.rank(ScreenMetric::AbsChangePct, Some(1), later);
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(result.rows[0].symbol, "ETH/USD");
+
+        let result = table.rank(ScreenMetric::SpreadBps, None, later);
+        assert!((result.rows[0].spread_bps - 200.0).abs() < 1e-9);
+        assert_eq!(result.rows[1].quote_volume, 28_800.0);
+
+        // Only the last sample before the window start is kept
+        table.update(
+            &ticker("BTC/USD", 2.5, 1_020.0, 99.0, 101.0),
+            later + window,
+        );
+        assert_eq!(table.pairs["BTC/USD"].volumes.len(), 2);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_screener_emits_results() {
+        use crate::testing::{fixtures, MockScript, MockServer};
+
+        let server = MockServer::start(
+            MockScript::new().message(fixtures::get("ticker_snapshot").unwrap().text),
+        )
+        .await
+        .unwrap();
+        let client = Arc::new(KrakyClient::connect_with_url(server.url()).await.unwrap());
+        let mut screener = Screener::new(client)
+            .pairs(["BTC/USD"])
+            .interval(Duration::from_millis(20))
+            .start()
+            .await
+            .unwrap();
+        assert_eq!(screener.pairs(), ["BTC/USD"]);
+
+        let result = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let result = screener.next().await.unwrap();
+                if !result.rows.is_empty() {
+                    return result;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(result.metric, ScreenMetric::ChangePct);
+        let ticker = &fixtures::get("ticker_snapshot").unwrap().json()["data"][0];
+        assert_eq!(
+            Some(result.rows[0].change_pct),
+            ticker["change_pct"].as_f64()
+        );
+        assert_eq!(screener.snapshot().rows.len(), 1);
+    }
+}
@@ -0,0 +1,577 @@
+//! Local fan-out server for market data
+//!
+//! [`ProxyServer`] keeps one Kraken connection and re-serves its book,
+//! trade and ticker streams to local processes over WebSocket, so several
+//! bots on one machine share a connection instead of each opening their
+//! own. Each upstream channel is subscribed once, on the first local
+//! subscription, and unsubscribed when the last local subscriber is gone.
+//!
+//! # Protocol
+//!
+//! Local clients send JSON requests:
+//!
+//! ```json
+//! {"method":"subscribe","channel":"ticker","symbol":"BTC/USD"}
+//! {"method":"unsubscribe","channel":"ticker","symbol":"BTC/USD"}
+//! ```
+//!
+//! and receive [`ProxyMessage`]s tagged by `type`: `subscribed`,
+//! `unsubscribed` and `error` replies, and normalized `book`, `trade` and
+//! `ticker` data. Books are sent whole, at the server's depth, after every
+//! update, so clients need no book-keeping of their own.
+//!
+//! The server manages its client's subscriptions of the served channels,
+//! so give it a client of its own.
+//!
+//! Requires the `server` feature flag.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::{KrakyClient, ProxyServer};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Arc::new(KrakyClient::connect().await?);
+//! let server = ProxyServer::bind(client, "127.0.0.1:9001").await?;
+//! println!("Serving market data on {}", server.url());
+//!
+//! // Serve until the process exits
+//! std::future::pending::<()>().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{KrakyClient, StoredSubscription};
+use crate::error::Result;
+#[cfg(feature = "orderbook")]
+use crate::models::PriceLevel;
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+#[cfg(feature = "trades")]
+use crate::models::Trade;
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, warn};
+
+/// Default depth of served books
+const DEFAULT_BOOK_DEPTH: u32 = 10;
+
+/// Messages buffered per stream before slow clients skip ahead
+const STREAM_BUFFER: usize = 256;
+
+/// Messages buffered per local client connection
+const CLIENT_BUFFER: usize = 256;
+
+/// Channel served to local clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyChannel {
+    /// Orderbook
+    #[cfg(feature = "orderbook")]
+    Book,
+    /// Trades
+    #[cfg(feature = "trades")]
+    Trade,
+    /// Ticker
+    #[cfg(feature = "ticker")]
+    Ticker,
+}
+
+/// Request from a local client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum ProxyRequest {
+    /// Start receiving a channel for a symbol
+    Subscribe {
+        /// Channel
+        channel: ProxyChannel,
+        /// Trading pair
+        symbol: String,
+    },
+    /// Stop receiving a channel for a symbol
+    Unsubscribe {
+        /// Channel
+        channel: ProxyChannel,
+        /// Trading pair
+        symbol: String,
+    },
+}
+
+/// Orderbook as served to local clients
+#[cfg(feature = "orderbook")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxyBook {
+    /// Trading pair
+    pub symbol: String,
+    /// Bid levels, best first
+    pub bids: Vec<PriceLevel>,
+    /// Ask levels, best first
+    pub asks: Vec<PriceLevel>,
+    /// Sequence number of the managed book
+    pub sequence: u64,
+}
+
+/// Message sent to local clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProxyMessage {
+    /// A subscription was started
+    Subscribed {
+        /// Channel
+        channel: ProxyChannel,
+        /// Trading pair
+        symbol: String,
+    },
+    /// A subscription was ended
+    Unsubscribed {
+        /// Channel
+        channel: ProxyChannel,
+        /// Trading pair
+        symbol: String,
+    },
+    /// A request failed
+    Error {
+        /// What went wrong
+        message: String,
+    },
+    /// Orderbook after an update
+    #[cfg(feature = "orderbook")]
+    Book(ProxyBook),
+    /// Trade
+    #[cfg(feature = "trades")]
+    Trade(Trade),
+    /// Ticker
+    #[cfg(feature = "ticker")]
+    Ticker(Ticker),
+}
+
+impl ProxyMessage {
+    /// Serialize to the JSON text sent over the wire
+    fn to_text(&self) -> Arc<str> {
+        serde_json::to_string(self)
+            .expect("proxy messages serialize")
+            .into()
+    }
+}
+
+/// Upstream stream shared by local subscribers
+type StreamKey = (ProxyChannel, String);
+
+/// Shared state of the server and its connections
+struct ProxyState {
+    client: Arc<KrakyClient>,
+    book_depth: u32,
+    streams: Mutex<HashMap<StreamKey, broadcast::Sender<Arc<str>>>>,
+    clients: AtomicUsize,
+}
+
+impl ProxyState {
+    /// Join a stream, subscribing upstream if it isn't served yet
+    async fn join(
+        self: &Arc<Self>,
+        channel: ProxyChannel,
+        symbol: &str,
+    ) -> Result<broadcast::Receiver<Arc<str>>> {
+        let key = (channel, symbol.to_string());
+        if let Some(sender) = self.streams.lock().get(&key) {
+            return Ok(sender.subscribe());
+        }
+
+        let (sender, receiver) = broadcast::channel(STREAM_BUFFER);
+        let forward = self.upstream(channel, symbol, sender.clone()).await?;
+        // Another connection may have subscribed while this one waited
+        let mut streams = self.streams.lock();
+        if let Some(existing) = streams.get(&key) {
+            forward.abort();
+            return Ok(existing.subscribe());
+        }
+        streams.insert(key, sender);
+        Ok(receiver)
+    }
+
+    /// Subscribe upstream and publish each message to `sender`
+    async fn upstream(
+        self: &Arc<Self>,
+        channel: ProxyChannel,
+        symbol: &str,
+        sender: broadcast::Sender<Arc<str>>,
+    ) -> Result<JoinHandle<()>> {
+        let state = Arc::clone(self);
+        let key = (channel, symbol.to_string());
+        let task = match channel {
+            #[cfg(feature = "orderbook")]
+            ProxyChannel::Book => {
+                let mut updates = self
+                    .client
+                    .subscribe_orderbook(symbol, self.book_depth)
+                    .await?;
+                crate::runtime::spawn(async move {
+                    let depth = state.book_depth as usize;
+                    while updates.next().await.is_some() {
+                        let Some(book) = state.book(&key.1, depth) else {
+                            continue;
+                        };
+                        if !state.publish(&key, &sender, book.to_text()) {
+                            break;
+                        }
+                    }
+                })
+            }
+            #[cfg(feature = "trades")]
+            ProxyChannel::Trade => {
+                let mut trades = self.client.subscribe_trades(symbol).await?;
+                crate::runtime::spawn(async move {
+                    while let Some(trade) = trades.next().await {
+                        let text = ProxyMessage::Trade((*trade).clone()).to_text();
+                        if !state.publish(&key, &sender, text) {
+                            break;
+                        }
+                    }
+                })
+            }
+            #[cfg(feature = "ticker")]
+            ProxyChannel::Ticker => {
+                let mut tickers = self.client.subscribe_ticker(symbol).await?;
+                crate::runtime::spawn(async move {
+                    while let Some(ticker) = tickers.next().await {
+                        let text = ProxyMessage::Ticker((*ticker).clone()).to_text();
+                        if !state.publish(&key, &sender, text) {
+                            break;
+                        }
+                    }
+                })
+            }
+        };
+        Ok(task)
+    }
+
+    /// Send a message to a stream's subscribers
+    ///
+    /// Returns `false` once the last subscriber is gone, after ending the
+    /// upstream subscription.
+    fn publish(
+        &self,
+        key: &StreamKey,
+        sender: &broadcast::Sender<Arc<str>>,
+        text: Arc<str>,
+    ) -> bool {
+        if sender.send(text).is_ok() {
+            return true;
+        }
+        let mut streams = self.streams.lock();
+        if sender.receiver_count() > 0 {
+            return true;
+        }
+        streams.remove(key);
+        drop(streams);
+
+        let (channel, symbol) = key;
+        debug!("No local subscribers left for {:?} {}", channel, symbol);
+        if let Err(e) =
+            self.client
+                .unsubscribe(&stored_subscription(*channel, symbol, self.book_depth))
+        {
+            warn!("Failed to unsubscribe {:?} {}: {}", channel, symbol, e);
+        }
+        false
+    }
+
+    /// The managed book of a symbol at `depth`
+    #[cfg(feature = "orderbook")]
+    fn book(&self, symbol: &str, depth: usize) -> Option<ProxyMessage> {
+        let book = self.client.get_orderbook(symbol)?;
+        Some(ProxyMessage::Book(ProxyBook {
+            symbol: book.symbol.clone(),
+            bids: book.top_bids(depth),
+            asks: book.top_asks(depth),
+            sequence: book.sequence,
+        }))
+    }
+}
+
+/// The client subscription behind a served stream
+fn stored_subscription(channel: ProxyChannel, symbol: &str, book_depth: u32) -> StoredSubscription {
+    let pair = symbol.to_string();
+    match channel {
+        #[cfg(feature = "orderbook")]
+        ProxyChannel::Book => StoredSubscription::Orderbook {
+            pair,
+            depth: book_depth,
+        },
+        #[cfg(feature = "trades")]
+        ProxyChannel::Trade => StoredSubscription::Trades { pair },
+        #[cfg(feature = "ticker")]
+        ProxyChannel::Ticker => StoredSubscription::Ticker { pair },
+    }
+}
+
+/// WebSocket server re-serving a client's market data
+///
+/// Stops accepting connections when dropped; open connections end with
+/// the upstream streams.
+pub struct ProxyServer {
+    addr: SocketAddr,
+    state: Arc<ProxyState>,
+    task: JoinHandle<()>,
+}
+
+impl std::fmt::Debug for ProxyServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyServer")
+            .field("addr", &self.addr)
+            .field("clients", &self.clients())
+            .finish()
+    }
+}
+
+impl ProxyServer {
+    /// Start serving `client`'s market data on `addr`
+    ///
+    /// Books are served at depth 10; see [`bind_with_depth`](Self::bind_with_depth).
+    pub async fn bind(client: Arc<KrakyClient>, addr: impl ToSocketAddrs) -> Result<Self> {
+        Self::bind_with_depth(client, addr, DEFAULT_BOOK_DEPTH).await
+    }
+
+    /// Start serving with books at `book_depth` (10, 25, 100, 500 or 1000)
+    pub async fn bind_with_depth(
+        client: Arc<KrakyClient>,
+        addr: impl ToSocketAddrs,
+        book_depth: u32,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(ProxyState {
+            client,
+            book_depth,
+            streams: Mutex::new(HashMap::new()),
+            clients: AtomicUsize::new(0),
+        });
+
+        let server_state = Arc::clone(&state);
+        let task = crate::runtime::spawn(async move {
+            while let Ok((stream, peer)) = listener.accept().await {
+                debug!("Local client connected from {}", peer);
+                crate::runtime::spawn(serve(stream, Arc::clone(&server_state)));
+            }
+        });
+
+        Ok(Self { addr, state, task })
+    }
+
+    /// Get the address the server listens on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Get the `ws://` URL local clients connect to
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Get the number of connected local clients
+    pub fn clients(&self) -> usize {
+        self.state.clients.load(Ordering::SeqCst)
+    }
+
+    /// Get the streams currently subscribed upstream
+    pub fn streams(&self) -> Vec<(ProxyChannel, String)> {
+        self.state.streams.lock().keys().cloned().collect()
+    }
+}
+
+impl Drop for ProxyServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Serve one local client until it disconnects
+async fn serve(stream: TcpStream, state: Arc<ProxyState>) {
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    state.clients.fetch_add(1, Ordering::SeqCst);
+    let (mut sink, mut incoming) = ws.split();
+    let (out, mut outgoing) = mpsc::channel::<Arc<str>>(CLIENT_BUFFER);
+    let mut forwards: HashMap<StreamKey, JoinHandle<()>> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            message = incoming.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let reply = match serde_json::from_str::<ProxyRequest>(&text) {
+                    Ok(request) => handle(request, &state, &out, &mut forwards).await,
+                    Err(e) => ProxyMessage::Error {
+                        message: format!("Invalid request: {}", e),
+                    },
+                };
+                if sink.send(Message::Text(reply.to_text().to_string())).await.is_err() {
+                    break;
+                }
+            }
+            Some(text) = outgoing.recv() => {
+                if sink.send(Message::Text(text.to_string())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    for forward in forwards.into_values() {
+        forward.abort();
+    }
+    state.clients.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Apply a request of a local client and build the reply
+async fn handle(
+    request: ProxyRequest,
+    state: &Arc<ProxyState>,
+    out: &mpsc::Sender<Arc<str>>,
+    forwards: &mut HashMap<StreamKey, JoinHandle<()>>,
+) -> ProxyMessage {
+    match request {
+        ProxyRequest::Subscribe { channel, symbol } => {
+            let key = (channel, symbol.clone());
+            if forwards.contains_key(&key) {
+                return ProxyMessage::Subscribed { channel, symbol };
+            }
+            let mut receiver = match state.join(channel, &symbol).await {
+                Ok(receiver) => receiver,
+                Err(e) => {
+                    return ProxyMessage::Error {
+                        message: format!("Failed to subscribe to {}: {}", symbol, e),
+                    }
+                }
+            };
+
+            // Late joiners get the current book right away
+            #[cfg(feature = "orderbook")]
+            if channel == ProxyChannel::Book {
+                if let Some(book) = state.book(&symbol, state.book_depth as usize) {
+                    let _ = out.try_send(book.to_text());
+                }
+            }
+
+            let out = out.clone();
+            forwards.insert(
+                key,
+                crate::runtime::spawn(async move {
+                    loop {
+                        match receiver.recv().await {
+                            Ok(text) => {
+                                if out.send(text).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                warn!("Local client lagged, skipped {} messages", skipped);
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
+                }),
+            );
+            ProxyMessage::Subscribed { channel, symbol }
+        }
+        ProxyRequest::Unsubscribe { channel, symbol } => {
+            if let Some(forward) = forwards.remove(&(channel, symbol.clone())) {
+                forward.abort();
+            }
+            ProxyMessage::Unsubscribed { channel, symbol }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ticker"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_messages() {
+        let request: ProxyRequest =
+            serde_json::from_str(r#"{"method":"subscribe","channel":"ticker","symbol":"BTC/USD"}"#)
+                .unwrap();
+        assert_eq!(
+            request,
+            ProxyRequest::Subscribe {
+                channel: ProxyChannel::Ticker,
+                symbol: "BTC/USD".to_string(),
+            }
+        );
+        assert!(serde_json::from_str::<ProxyRequest>(r#"{"method":"subscribe"}"#).is_err());
+
+        let reply = ProxyMessage::Unsubscribed {
+            channel: ProxyChannel::Ticker,
+            symbol: "BTC/USD".to_string(),
+        };
+        assert_eq!(
+            &*reply.to_text(),
+            r#"{"type":"unsubscribed","channel":"ticker","symbol":"BTC/USD"}"#
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_fan_out_ticker() {
+        use crate::testing::{fixtures, MockScript, MockServer};
+        use std::time::Duration;
+
+        let upstream = MockServer::start(
+            MockScript::new().message(fixtures::get("ticker_snapshot").unwrap().text),
+        )
+        .await
+        .unwrap();
+        let client = Arc::new(KrakyClient::connect_with_url(upstream.url()).await.unwrap());
+        let server = ProxyServer::bind(client, "127.0.0.1:0").await.unwrap();
+
+        let subscribe = r#"{"method":"subscribe","channel":"ticker","symbol":"BTC/USD"}"#;
+        let mut locals = Vec::new();
+        for _ in 0..2 {
+            let (mut ws, _) = tokio_tungstenite::connect_async(server.url())
+                .await
+                .unwrap();
+            ws.send(Message::Text(subscribe.to_string())).await.unwrap();
+            locals.push(ws);
+        }
+
+        for ws in &mut locals {
+            let ticker = tokio::time::timeout(Duration::from_secs(5), async {
+                loop {
+                    let Some(Ok(Message::Text(text))) = ws.next().await else {
+                        panic!("local connection closed");
+                    };
+                    if let ProxyMessage::Ticker(ticker) = serde_json::from_str(&text).unwrap() {
+                        return ticker;
+                    }
+                }
+            })
+            .await
+            .unwrap();
+            assert_eq!(ticker.symbol, "BTC/USD");
+        }
+
+        assert_eq!(server.clients(), 2);
+        assert_eq!(server.streams().len(), 1);
+        let upstream_subscribes = upstream
+            .received()
+            .iter()
+            .filter(|text| text.contains("\"subscribe\""))
+            .count();
+        assert_eq!(upstream_subscribes, 1);
+    }
+}
@@ -0,0 +1,283 @@
+//! Slack notification integration for Kraky SDK
+//!
+//! Sends market alerts to a Slack channel through an incoming webhook.
+//! [`SlackNotifier`] implements the [`Notifier`] trait and formats messages
+//! with [Block Kit](https://api.slack.com/block-kit): the first line of an
+//! alert becomes a header, and price, imbalance, whale and rule-engine alerts
+//! show their metrics as fields.
+//!
+//! ## Quick Start
+//!
+//! Create an app with *Incoming Webhooks* enabled and copy the webhook URL.
+//!
+//! ```no_run
+//! use kraky::{Notifier, SlackNotifier};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let slack = SlackNotifier::new("https://hooks.slack.com/services/T000/B000/XXXX");
+//!
+//!     slack.send_price_alert("BTC/USD", 100_000.0, "Target reached!").await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::error::{KrakyError, Result};
+use crate::notifier::{truncate, Notifier};
+use crate::templates::AlertTemplates;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+#[cfg(feature = "alerts")]
+use crate::alerts::AlertEvent;
+#[cfg(feature = "analytics")]
+use crate::models::{ImbalanceMetrics, ImbalanceSignal};
+
+/// Maximum length of a header block's text
+const SLACK_MAX_HEADER: usize = 150;
+
+/// Maximum length of a section block's text
+const SLACK_MAX_SECTION: usize = 3000;
+
+/// Slack incoming-webhook notifier for real-time market alerts
+///
+/// See [`Notifier`] for the available alerts.
+pub struct SlackNotifier {
+    client: reqwest::Client,
+    webhook_url: String,
+    templates: Option<AlertTemplates>,
+}
+
+impl SlackNotifier {
+    /// Create a new Slack notifier
+    ///
+    /// # Arguments
+    /// * `webhook_url` - Slack incoming webhook URL (`https://hooks.slack.com/services/...`)
+    pub fn new(webhook_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhook_url: webhook_url.to_string(),
+            templates: None,
+        }
+    }
+
+    /// Replace built-in alert texts with templates
+    ///
+    /// See [`templates`](crate::templates) for alert types and variables.
+    pub fn with_templates(mut self, templates: AlertTemplates) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Post a Block Kit message
+    ///
+    /// `text` is the notification fallback shown where blocks can't be rendered.
+    async fn send_blocks(&self, text: &str, blocks: Vec<Value>) -> Result<()> {
+        let payload = json!({
+            "text": truncate(text, SLACK_MAX_SECTION),
+            "blocks": blocks,
+        });
+
+        let response = self
+            .client
+            .post(&self.webhook_url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| KrakyError::InvalidMessage(format!("Slack error: {}", e)))?;
+
+        let status = response.status();
+        if status.as_u16() == 429 {
+            return Err(KrakyError::RateLimited);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(KrakyError::InvalidMessage(format!(
+                "Slack error: {} {}",
+                status, body
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Header block
+fn header(text: &str) -> Value {
+    json!({
+        "type": "header",
+        "text": { "type": "plain_text", "text": truncate(text, SLACK_MAX_HEADER), "emoji": true },
+    })
+}
+
+/// Section block with markdown text
+fn section(text: &str) -> Value {
+    json!({
+        "type": "section",
+        "text": { "type": "mrkdwn", "text": truncate(text, SLACK_MAX_SECTION) },
+    })
+}
+
+/// Section block with labelled fields (rendered in two columns)
+fn fields(fields: &[(&str, String)]) -> Value {
+    let fields: Vec<Value> = fields
+        .iter()
+        .take(10) // Slack allows at most 10 fields per section
+        .map(
+            |(label, value)| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", label, value) }),
+        )
+        .collect();
+    json!({ "type": "section", "fields": fields })
+}
+
+/// Context block (small grey text)
+fn context(text: &str) -> Value {
+    json!({
+        "type": "context",
+        "elements": [{ "type": "mrkdwn", "text": truncate(text, SLACK_MAX_SECTION) }],
+    })
+}
+
+/// Convert a plain-text alert into blocks: first line as header, rest as a section
+fn text_blocks(message: &str) -> Vec<Value> {
+    let (title, body) = message.split_once('\n').unwrap_or((message, ""));
+    let mut blocks = vec![header(title.trim())];
+    let body = body.trim();
+    if !body.is_empty() {
+        blocks.push(section(body));
+    }
+    blocks
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    fn templates(&self) -> Option<&AlertTemplates> {
+        self.templates.as_ref()
+    }
+
+    async fn send_alert(&self, message: &str) -> Result<()> {
+        self.send_blocks(message, text_blocks(message)).await
+    }
+
+    #[cfg(feature = "alerts")]
+    async fn send_alert_event(&self, event: &AlertEvent) -> Result<()> {
+        let title = format!(
+            "🔔 {} - {}",
+            event.name.as_deref().unwrap_or("Alert"),
+            event.symbol
+        );
+        let blocks = vec![
+            header(&title),
+            fields(&[
+                ("Condition", event.condition.to_string()),
+                ("Value", format!("{:.4}", event.value)),
+            ]),
+            context(&event.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+        ];
+        self.send_blocks(&event.to_string(), blocks).await
+    }
+
+    async fn send_price_alert(&self, symbol: &str, price: f64, context_text: &str) -> Result<()> {
+        let title = format!("💰 {} Price Alert", symbol);
+        let mut blocks = vec![
+            header(&title),
+            fields(&[("Price", format!("${:.2}", price))]),
+        ];
+        if !context_text.is_empty() {
+            blocks.push(section(context_text));
+        }
+        self.send_blocks(&format!("{}: ${:.2}", title, price), blocks)
+            .await
+    }
+
+    #[cfg(feature = "analytics")]
+    async fn send_imbalance_alert(
+        &self,
+        symbol: &str,
+        metrics: &ImbalanceMetrics,
+        signal: ImbalanceSignal,
+    ) -> Result<()> {
+        let (emoji, signal_name) = match signal {
+            ImbalanceSignal::Bullish => ("🟢", "BULLISH"),
+            ImbalanceSignal::Bearish => ("🔴", "BEARISH"),
+            ImbalanceSignal::Neutral => ("⚪", "NEUTRAL"),
+        };
+
+        let title = format!("{} {} Orderbook Imbalance", emoji, symbol);
+        let blocks = vec![
+            header(&title),
+            fields(&[
+                ("Signal", signal_name.to_string()),
+                (
+                    "Imbalance",
+                    format!("{:+.2}%", metrics.imbalance_ratio * 100.0),
+                ),
+                ("Bid Volume", format!("{:.4}", metrics.bid_volume)),
+                ("Ask Volume", format!("{:.4}", metrics.ask_volume)),
+                ("Bid/Ask Ratio", format!("{:.2}", metrics.bid_ask_ratio)),
+            ]),
+        ];
+        self.send_blocks(&format!("{}: {}", title, signal_name), blocks)
+            .await
+    }
+
+    async fn send_whale_alert(
+        &self,
+        symbol: &str,
+        side: &str,
+        price: f64,
+        volume: f64,
+    ) -> Result<()> {
+        let (emoji, direction) = if side.to_lowercase() == "bid" {
+            ("🟢", "BUY")
+        } else {
+            ("🔴", "SELL")
+        };
+
+        let title = format!("🐋 {} Whale Alert", symbol);
+        let blocks = vec![
+            header(&title),
+            fields(&[
+                ("Side", format!("{} {}", emoji, direction)),
+                ("Price", format!("${:.2}", price)),
+                ("Volume", format!("{:.4}", volume)),
+                ("Total Value", format!("${:.2}", price * volume)),
+            ]),
+            context("A large order has appeared in the orderbook."),
+        ];
+        self.send_blocks(&format!("{}: {} {:.4}", title, direction, volume), blocks)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_blocks() {
+        let blocks = text_blocks("💰 BTC/USD Price Alert\nPrice: $100000.00\nTarget reached!");
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0]["type"], "header");
+        assert_eq!(blocks[0]["text"]["text"], "💰 BTC/USD Price Alert");
+        assert_eq!(
+            blocks[1]["text"]["text"],
+            "Price: $100000.00\nTarget reached!"
+        );
+
+        let blocks = text_blocks("single line");
+        assert_eq!(blocks.len(), 1);
+    }
+
+    #[test]
+    fn test_fields_block() {
+        let block = fields(&[("Price", "$1.00".to_string())]);
+        assert_eq!(block["fields"][0]["text"], "*Price*\n$1.00");
+
+        let long = header(&"x".repeat(500));
+        assert_eq!(
+            long["text"]["text"].as_str().unwrap().chars().count(),
+            SLACK_MAX_HEADER
+        );
+    }
+}
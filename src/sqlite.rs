@@ -0,0 +1,675 @@
+//! SQLite market data sink
+//!
+//! [`SqliteSink`] subscribes to chosen channels and persists every message
+//! into a local SQLite database, for lightweight storage without running a
+//! database server. Orderbooks are stored as periodic snapshots of the
+//! maintained book rather than as raw updates.
+//!
+//! Requires the `sqlite` feature flag, plus the feature of each stored
+//! channel (`trades`, `ticker`, `ohlc`, `orderbook`). SQLite is compiled in,
+//! so no system library is needed.
+//!
+//! ## Schema
+//!
+//! | Table | Key | Notes |
+//! |-------|-----|-------|
+//! | `trades` | `(symbol, trade_id)` | Duplicates after a reconnect are ignored |
+//! | `tickers` | rowid | Indexed by `(symbol, recorded_at)` |
+//! | `candles` | `(symbol, interval, interval_begin)` | Keeps the latest update of each candle |
+//! | `book_snapshots` | `id` | Indexed by `(symbol, timestamp)` |
+//! | `book_levels` | `(snapshot_id, side, level)` | Level 0 is the best price |
+//!
+//! Times are RFC 3339 strings in UTC, so they sort chronologically.
+//! `recorded_at` is the time the message was received.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use kraky::sqlite::SqliteSink;
+//! use kraky::KrakyClient;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Arc::new(KrakyClient::connect().await?);
+//!
+//!     let sink = SqliteSink::new(client, "market.db")
+//!         .trades("BTC/USD")
+//!         .orderbook("BTC/USD", 10)
+//!         .snapshot_interval(Duration::from_secs(5))
+//!         .start()
+//!         .await?;
+//!
+//!     tokio::time::sleep(Duration::from_secs(60)).await;
+//!
+//!     sink.stop().await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::KrakyClient;
+use crate::error::{KrakyError, Result};
+use crate::subscriptions::Subscription;
+use chrono::{SecondsFormat, Utc};
+use rusqlite::{params, Connection, Transaction};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+#[cfg(feature = "ohlc")]
+use crate::models::{Interval, OHLC};
+#[cfg(feature = "orderbook")]
+use crate::models::{OrderbookSnapshot, OrderbookUpdate};
+#[cfg(feature = "trades")]
+use crate::models::{Trade, TradeOrderType};
+
+/// Rows buffered between the subscriptions and the database writer
+const QUEUE_SIZE: usize = 10_000;
+
+/// Most rows written in one transaction
+const BATCH_SIZE: usize = 1_000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS trades (
+    symbol TEXT NOT NULL,
+    trade_id INTEGER NOT NULL,
+    side TEXT NOT NULL,
+    price REAL NOT NULL,
+    qty REAL NOT NULL,
+    ord_type TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    recorded_at TEXT NOT NULL,
+    PRIMARY KEY (symbol, trade_id)
+);
+CREATE INDEX IF NOT EXISTS trades_symbol_timestamp ON trades (symbol, timestamp);
+
+CREATE TABLE IF NOT EXISTS tickers (
+    id INTEGER PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    bid REAL NOT NULL,
+    bid_qty REAL NOT NULL,
+    ask REAL NOT NULL,
+    ask_qty REAL NOT NULL,
+    last REAL NOT NULL,
+    volume REAL NOT NULL,
+    vwap REAL NOT NULL,
+    low REAL NOT NULL,
+    high REAL NOT NULL,
+    change REAL NOT NULL,
+    change_pct REAL NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS tickers_symbol_recorded_at ON tickers (symbol, recorded_at);
+
+CREATE TABLE IF NOT EXISTS candles (
+    symbol TEXT NOT NULL,
+    interval INTEGER NOT NULL,
+    interval_begin TEXT NOT NULL,
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    vwap REAL NOT NULL,
+    volume REAL NOT NULL,
+    count INTEGER NOT NULL,
+    timestamp TEXT NOT NULL,
+    recorded_at TEXT NOT NULL,
+    PRIMARY KEY (symbol, interval, interval_begin)
+);
+
+CREATE TABLE IF NOT EXISTS book_snapshots (
+    id TEXT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    sequence INTEGER NOT NULL,
+    timestamp TEXT NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS book_snapshots_symbol_timestamp ON book_snapshots (symbol, timestamp);
+
+CREATE TABLE IF NOT EXISTS book_levels (
+    snapshot_id TEXT NOT NULL REFERENCES book_snapshots (id) ON DELETE CASCADE,
+    side TEXT NOT NULL,
+    level INTEGER NOT NULL,
+    price REAL NOT NULL,
+    qty REAL NOT NULL,
+    PRIMARY KEY (snapshot_id, side, level)
+) WITHOUT ROWID;
+";
+
+/// A channel to store
+#[derive(Debug, Clone)]
+enum Channel {
+    #[cfg(feature = "trades")]
+    Trades(String),
+    #[cfg(feature = "ticker")]
+    Ticker(String),
+    #[cfg(feature = "ohlc")]
+    Ohlc(String, Interval),
+    #[cfg(feature = "orderbook")]
+    Orderbook(String, u32),
+}
+
+/// A message waiting to be written
+#[derive(Debug)]
+enum Row {
+    #[cfg(feature = "trades")]
+    Trade(Trade),
+    #[cfg(feature = "ticker")]
+    Ticker(Ticker),
+    #[cfg(feature = "ohlc")]
+    Candle(OHLC),
+    #[cfg(feature = "orderbook")]
+    Book(OrderbookSnapshot),
+}
+
+/// Persists live market data into a SQLite database
+///
+/// Configure the channels to store, then [`start`](Self::start) it. See the
+/// [module documentation](self) for the schema.
+pub struct SqliteSink {
+    client: Arc<KrakyClient>,
+    path: PathBuf,
+    snapshot_interval: Duration,
+    channels: Vec<Channel>,
+}
+
+impl SqliteSink {
+    /// Create a sink writing to the database at `path` (created if missing)
+    pub fn new(client: Arc<KrakyClient>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            client,
+            path: path.into(),
+            snapshot_interval: Duration::from_secs(10),
+            channels: Vec::new(),
+        }
+    }
+
+    /// Set how often orderbook snapshots are stored (10 seconds by default)
+    pub fn snapshot_interval(mut self, interval: Duration) -> Self {
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// Store trades for a pair
+    ///
+    /// Only available when the `trades` feature is enabled.
+    #[cfg(feature = "trades")]
+    pub fn trades(mut self, pair: &str) -> Self {
+        self.channels.push(Channel::Trades(pair.to_string()));
+        self
+    }
+
+    /// Store ticker updates for a pair
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub fn ticker(mut self, pair: &str) -> Self {
+        self.channels.push(Channel::Ticker(pair.to_string()));
+        self
+    }
+
+    /// Store OHLC candles for a pair
+    ///
+    /// Only available when the `ohlc` feature is enabled.
+    #[cfg(feature = "ohlc")]
+    pub fn ohlc(mut self, pair: &str, interval: Interval) -> Self {
+        self.channels
+            .push(Channel::Ohlc(pair.to_string(), interval));
+        self
+    }
+
+    /// Store periodic snapshots of the top `depth` levels of a pair's book
+    ///
+    /// Only available when the `orderbook` feature is enabled.
+    #[cfg(feature = "orderbook")]
+    pub fn orderbook(mut self, pair: &str, depth: u32) -> Self {
+        self.channels
+            .push(Channel::Orderbook(pair.to_string(), depth));
+        self
+    }
+
+    /// Create the schema, subscribe to the configured channels and start
+    /// writing
+    ///
+    /// Writing continues until [`SqliteSinkHandle::stop`] is called or the
+    /// client disconnects.
+    pub async fn start(self) -> Result<SqliteSinkHandle> {
+        if self.channels.is_empty() {
+            return Err(KrakyError::InvalidMessage(
+                "SQLite sink has no channels configured".to_string(),
+            ));
+        }
+        let conn = open(&self.path)?;
+
+        let (rows_tx, rows_rx) = mpsc::channel(QUEUE_SIZE);
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let mut tasks = Vec::new();
+
+        for channel in &self.channels {
+            let task = match channel {
+                #[cfg(feature = "trades")]
+                Channel::Trades(pair) => {
+                    let subscription = self.client.subscribe_trades(pair).await?;
+                    tokio::spawn(forward(
+                        subscription,
+                        Row::Trade,
+                        rows_tx.clone(),
+                        stop_rx.clone(),
+                    ))
+                }
+                #[cfg(feature = "ticker")]
+                Channel::Ticker(pair) => {
+                    let subscription = self.client.subscribe_ticker(pair).await?;
+                    tokio::spawn(forward(
+                        subscription,
+                        Row::Ticker,
+                        rows_tx.clone(),
+                        stop_rx.clone(),
+                    ))
+                }
+                #[cfg(feature = "ohlc")]
+                Channel::Ohlc(pair, interval) => {
+                    let subscription = self.client.subscribe_ohlc(pair, *interval).await?;
+                    tokio::spawn(forward(
+                        subscription,
+                        Row::Candle,
+                        rows_tx.clone(),
+                        stop_rx.clone(),
+                    ))
+                }
+                #[cfg(feature = "orderbook")]
+                Channel::Orderbook(pair, depth) => {
+                    let subscription = self.client.subscribe_orderbook(pair, *depth).await?;
+                    tokio::spawn(snapshot(
+                        Arc::clone(&self.client),
+                        pair.clone(),
+                        *depth as usize,
+                        self.snapshot_interval,
+                        subscription,
+                        rows_tx.clone(),
+                        stop_rx.clone(),
+                    ))
+                }
+            };
+            tasks.push(task);
+        }
+        // The writer finishes once every channel task has dropped its sender
+        drop(rows_tx);
+
+        let records = Arc::new(AtomicU64::new(0));
+        let writer = tokio::task::spawn_blocking({
+            let records = Arc::clone(&records);
+            let mut conn = conn;
+            move || write_rows(&mut conn, rows_rx, &records)
+        });
+
+        Ok(SqliteSinkHandle {
+            stop: stop_tx,
+            tasks,
+            writer,
+            records,
+        })
+    }
+}
+
+/// Handle to a running [`SqliteSink`]
+pub struct SqliteSinkHandle {
+    stop: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+    writer: JoinHandle<Result<()>>,
+    records: Arc<AtomicU64>,
+}
+
+impl SqliteSinkHandle {
+    /// Number of messages written so far
+    ///
+    /// An orderbook snapshot counts once, regardless of its depth.
+    pub fn records(&self) -> u64 {
+        self.records.load(Ordering::Relaxed)
+    }
+
+    /// Stop all subscriptions and wait until queued messages are written
+    ///
+    /// Returns the database error that stopped the writer, if any.
+    pub async fn stop(self) -> Result<()> {
+        let _ = self.stop.send(true);
+        for task in self.tasks {
+            let _ = task.await;
+        }
+        self.writer
+            .await
+            .map_err(|e| KrakyError::Storage(format!("SQLite writer failed: {}", e)))?
+    }
+}
+
+/// Open a database and create the schema
+fn open(path: &std::path::Path) -> Result<Connection> {
+    let conn = Connection::open(path).map_err(storage_error)?;
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")
+        .map_err(storage_error)?;
+    conn.execute_batch(SCHEMA).map_err(storage_error)?;
+    Ok(conn)
+}
+
+fn storage_error(e: rusqlite::Error) -> KrakyError {
+    KrakyError::Storage(e.to_string())
+}
+
+/// Queue a subscription's messages until stopped or closed
+#[cfg(any(feature = "trades", feature = "ticker", feature = "ohlc"))]
+async fn forward<T: Clone>(
+    mut subscription: Subscription<Arc<T>>,
+    row: fn(T) -> Row,
+    rows: mpsc::Sender<(String, Row)>,
+    mut stop: watch::Receiver<bool>,
+) {
+    loop {
+        tokio::select! {
+            item = subscription.next() => match item {
+                Some(item) => {
+                    if rows.send((now(), row(Arc::try_unwrap(item).unwrap_or_else(|item| (*item).clone())))).await.is_err() {
+                        break;
+                    }
+                }
+                None => break,
+            },
+            _ = stop.changed() => break,
+        }
+    }
+}
+
+/// Queue snapshots of a maintained orderbook until stopped or closed
+#[cfg(feature = "orderbook")]
+async fn snapshot(
+    client: Arc<KrakyClient>,
+    pair: String,
+    depth: usize,
+    every: Duration,
+    mut subscription: Subscription<Arc<OrderbookUpdate>>,
+    rows: mpsc::Sender<(String, Row)>,
+    mut stop: watch::Receiver<bool>,
+) {
+    let mut ticks = tokio::time::interval(every);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        tokio::select! {
+            // The client maintains the book; updates only need draining
+            update = subscription.next() => if update.is_none() {
+                break;
+            },
+            _ = ticks.tick() => {
+                let Some(book) = client.get_orderbook(&pair) else {
+                    continue;
+                };
+                if book.bids.is_empty() && book.asks.is_empty() {
+                    continue;
+                }
+                let snapshot = OrderbookSnapshot::from_orderbook(&book, depth);
+                if rows.send((now(), Row::Book(snapshot))).await.is_err() {
+                    break;
+                }
+            }
+            _ = stop.changed() => break,
+        }
+    }
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Write queued rows in batches until every sender is dropped
+fn write_rows(
+    conn: &mut Connection,
+    mut rows: mpsc::Receiver<(String, Row)>,
+    records: &AtomicU64,
+) -> Result<()> {
+    while let Some(first) = rows.blocking_recv() {
+        let tx = conn.transaction().map_err(storage_error)?;
+        let mut written = 0;
+        let mut next = Some(first);
+        while let Some((recorded_at, row)) = next {
+            if let Err(e) = insert(&tx, &recorded_at, &row) {
+                tracing::error!("SQLite sink failed to write: {}", e);
+                return Err(storage_error(e));
+            }
+            written += 1;
+            next = if written < BATCH_SIZE {
+                rows.try_recv().ok()
+            } else {
+                None
+            };
+        }
+        tx.commit().map_err(storage_error)?;
+        records.fetch_add(written as u64, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+fn insert(tx: &Transaction, recorded_at: &str, row: &Row) -> rusqlite::Result<()> {
+    match row {
+        #[cfg(feature = "trades")]
+        Row::Trade(trade) => {
+            let ord_type = match trade.ord_type {
+                TradeOrderType::Market => "market",
+                TradeOrderType::Limit => "limit",
+            };
+            tx.prepare_cached(
+                "INSERT OR IGNORE INTO trades
+                 (symbol, trade_id, side, price, qty, ord_type, timestamp, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?
+            .execute(params![
+                trade.symbol,
+                trade.trade_id,
+                trade.side.to_string(),
+                trade.price,
+                trade.qty,
+                ord_type,
+                trade.timestamp,
+                recorded_at,
+            ])?;
+        }
+        #[cfg(feature = "ticker")]
+        Row::Ticker(ticker) => {
+            tx.prepare_cached(
+                "INSERT INTO tickers
+                 (symbol, bid, bid_qty, ask, ask_qty, last, volume, vwap, low, high,
+                  change, change_pct, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            )?
+            .execute(params![
+                ticker.symbol,
+                ticker.bid,
+                ticker.bid_qty,
+                ticker.ask,
+                ticker.ask_qty,
+                ticker.last,
+                ticker.volume,
+                ticker.vwap,
+                ticker.low,
+                ticker.high,
+                ticker.change,
+                ticker.change_pct,
+                recorded_at,
+            ])?;
+        }
+        #[cfg(feature = "ohlc")]
+        Row::Candle(candle) => {
+            tx.prepare_cached(
+                "INSERT OR REPLACE INTO candles
+                 (symbol, interval, interval_begin, open, high, low, close, vwap, volume,
+                  count, timestamp, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?
+            .execute(params![
+                candle.symbol,
+                candle.interval,
+                candle.interval_begin,
+                candle.open,
+                candle.high,
+                candle.low,
+                candle.close,
+                candle.vwap,
+                candle.volume,
+                candle.count,
+                candle.timestamp,
+                recorded_at,
+            ])?;
+        }
+        #[cfg(feature = "orderbook")]
+        Row::Book(snapshot) => {
+            tx.prepare_cached(
+                "INSERT INTO book_snapshots (id, symbol, sequence, timestamp, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?
+            .execute(params![
+                snapshot.id,
+                snapshot.symbol,
+                snapshot.sequence as i64,
+                snapshot
+                    .timestamp
+                    .to_rfc3339_opts(SecondsFormat::Micros, true),
+                recorded_at,
+            ])?;
+            let mut level = tx.prepare_cached(
+                "INSERT INTO book_levels (snapshot_id, side, level, price, qty)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (side, levels) in [("bid", &snapshot.bids), ("ask", &snapshot.asks)] {
+                for (i, l) in levels.iter().enumerate() {
+                    level.execute(params![snapshot.id, side, i as i64, l.price, l.qty])?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write rows through the batching writer into an in-memory database
+    fn write(rows: Vec<Row>) -> (Connection, u64) {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        for row in rows {
+            tx.try_send((now(), row)).unwrap();
+        }
+        drop(tx);
+
+        let records = AtomicU64::new(0);
+        write_rows(&mut conn, rx, &records).unwrap();
+        (conn, records.load(Ordering::Relaxed))
+    }
+
+    fn count(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_schema_is_idempotent() {
+        let dir = std::env::temp_dir().join(format!("kraky-sqlite-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("market.db");
+
+        drop(open(&path).unwrap());
+        let conn = open(&path).unwrap();
+        assert_eq!(count(&conn, "book_levels"), 0);
+
+        drop(conn);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "trades")]
+    #[test]
+    fn test_duplicate_trades_ignored() {
+        let trade = |trade_id| {
+            Row::Trade(Trade {
+                symbol: "BTC/USD".to_string(),
+                side: crate::models::TradeSide::Buy,
+                price: 42000.0,
+                qty: 0.5,
+                ord_type: TradeOrderType::Limit,
+                trade_id,
+                timestamp: "2024-01-15T10:30:00.000000Z".to_string(),
+            })
+        };
+        let (conn, records) = write(vec![trade(1), trade(2), trade(1)]);
+
+        assert_eq!(records, 3);
+        assert_eq!(count(&conn, "trades"), 2);
+        let ord_type: String = conn
+            .query_row("SELECT ord_type FROM trades WHERE trade_id = 2", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(ord_type, "limit");
+    }
+
+    #[cfg(feature = "ohlc")]
+    #[test]
+    fn test_candle_updates_replace() {
+        let candle = |close, count| {
+            Row::Candle(OHLC {
+                symbol: "BTC/USD".to_string(),
+                open: 100.0,
+                high: 110.0,
+                low: 95.0,
+                close,
+                vwap: 102.0,
+                volume: 12.0,
+                count,
+                interval: 1,
+                timestamp: "2024-01-15T10:31:00.000000Z".to_string(),
+                interval_begin: "2024-01-15T10:30:00.000000Z".to_string(),
+                synthetic: false,
+            })
+        };
+        let (conn, _) = write(vec![candle(101.0, 3), candle(104.0, 5)]);
+
+        assert_eq!(count(&conn, "candles"), 1);
+        let (close, count): (f64, i64) = conn
+            .query_row("SELECT close, count FROM candles", [], |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((close, count), (104.0, 5));
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_book_snapshot_levels() {
+        let mut book = crate::models::Orderbook::new("BTC/USD".to_string());
+        for (i, price) in [100.0, 99.0, 98.0].into_iter().enumerate() {
+            book.bids
+                .insert(crate::models::OrderedFloat(price), 1.0 + i as f64);
+        }
+        book.asks.insert(crate::models::OrderedFloat(101.0), 2.0);
+        let snapshot = OrderbookSnapshot::from_orderbook(&book, 2);
+        let id = snapshot.id.clone();
+
+        let (conn, records) = write(vec![Row::Book(snapshot)]);
+
+        assert_eq!(records, 1);
+        assert_eq!(count(&conn, "book_snapshots"), 1);
+        assert_eq!(count(&conn, "book_levels"), 3);
+        let best_bid: f64 = conn
+            .query_row(
+                "SELECT price FROM book_levels WHERE snapshot_id = ?1 AND side = 'bid' AND level = 0",
+                [&id],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(best_bid, 100.0);
+    }
+}
@@ -12,6 +12,20 @@
 //!
 //! Default buffer size: 1000 messages
 //!
+//! Dropping arbitrary orderbook deltas corrupts a book rebuilt from them.
+//! Conflated subscriptions ([`KrakyClient::subscribe_orderbook_conflated`],
+//! [`KrakyClient::subscribe_ticker_conflated`]) instead fold updates
+//! arriving while the buffer is full into one pending update, the latest
+//! ticker or the merged book delta, so a slow consumer skips intermediate
+//! states but stays correct.
+//!
+//! # Shared Updates
+//!
+//! Market data subscriptions yield `Arc<T>` (e.g. `Arc<Trade>`), so every
+//! subscriber to the same update shares one allocation instead of receiving
+//! its own copy. Fields are reached through `Deref`; clone the inner value
+//! (`(*trade).clone()`) when an owned one is needed.
+//!
 //! # Example Usage
 //!
 //! ```no_run
@@ -51,6 +65,13 @@
 //! println!("Delivered: {}", stats.delivered());
 //! println!("Dropped: {}", stats.dropped());
 //! println!("Drop rate: {:.2}%", stats.drop_rate());
+//!
+//! // Check feed freshness
+//! println!("Rate: {:.1} msg/s", stats.messages_per_sec());
+//! println!("Max gap: {:?}", stats.max_gap());
+//! if stats.is_stale(std::time::Duration::from_secs(30)) {
+//!     println!("No orderbook updates for 30s");
+//! }
 //! # Ok(())
 //! # }
 //! # }
@@ -86,14 +107,110 @@
 //! # }
 //! # }
 //! ```
+//!
+//! # Callbacks
+//!
+//! Instead of driving the stream yourself, register a handler: the client
+//! calls it for every update from a background task. Handlers can be plain
+//! closures or return a future; see [`KrakyClient::on_trade`].
+//!
+//! ```no_run
+//! # #[cfg(feature = "trades")]
+//! # {
+//! use kraky::KrakyClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//!
+//! let handle = client
+//!     .on_trade("BTC/USD", |trade| println!("{} @ {}", trade.qty, trade.price))
+//!     .await?;
+//! # let _ = handle;
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+//!
+//! [`KrakyClient::on_trade`]: crate::KrakyClient::on_trade
+//! [`KrakyClient::subscribe_orderbook_conflated`]: crate::KrakyClient::subscribe_orderbook_conflated
+//! [`KrakyClient::subscribe_ticker_conflated`]: crate::KrakyClient::subscribe_ticker_conflated
+//!
+//! # Pausing
+//!
+//! [`Subscription::pause`] stops delivery to one subscription while keeping
+//! it and the client state (e.g. the managed orderbook) alive, so a UI can
+//! park a background tab. [`Subscription::pause_upstream`] also asks Kraken
+//! to stop sending the channel once every subscription to it is paused;
+//! [`Subscription::resume`] subscribes again.
+//!
+//! ```no_run
+//! # #[cfg(feature = "trades")]
+//! # {
+//! use kraky::KrakyClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! let trades = client.subscribe_trades("BTC/USD").await?;
+//!
+//! // Tab hidden
+//! trades.pause_upstream()?;
+//! // Tab shown again
+//! trades.resume()?;
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+//!
+//! # Ticking and Batching
+//!
+//! [`Subscription::next_timeout`] gives up after a deadline, so a loop can
+//! do periodic work even when the feed is quiet, and
+//! [`Subscription::recv_many`] drains everything already buffered in one
+//! call.
+//!
+//! ```no_run
+//! # #[cfg(feature = "trades")]
+//! # {
+//! use kraky::KrakyClient;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! let mut trades = client.subscribe_trades("BTC/USD").await?;
+//! let mut batch = Vec::new();
+//!
+//! loop {
+//!     match trades.next_timeout(Duration::from_secs(1)).await {
+//!         Ok(Some(trade)) => batch.push(trade),
+//!         Ok(None) => break,
+//!         Err(_) => println!("Tick: no trades for 1s"),
+//!     }
+//!     // Take whatever else arrived in the meantime
+//!     trades.recv_many(&mut batch, 100).await;
+//!     println!("Processing {} trades", batch.len());
+//!     batch.clear();
+//! }
+//! # Ok(())
+//! # }
+//! # }
+//! ```
 
 use crate::error::{KrakyError, Result};
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
 use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
 use std::pin::Pin;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::error::Elapsed;
+use tracing::{trace, warn, Instrument, Span};
 
 /// Default buffer size for subscription channels
 pub const DEFAULT_BUFFER_SIZE: usize = 1000;
@@ -120,13 +237,213 @@ impl BackpressureConfig {
     }
 }
 
+/// Updates that can be coalesced when a subscriber falls behind
+///
+/// A conflated subscription folds updates arriving while its buffer is
+/// full into one pending update instead of dropping them, so a slow
+/// consumer skips intermediate states but never ends up with a wrong one.
+pub trait Conflate {
+    /// Fold a newer update into this one
+    fn conflate(&mut self, newer: Self);
+}
+
+#[cfg(feature = "orderbook")]
+impl Conflate for Arc<crate::models::OrderbookUpdate> {
+    fn conflate(&mut self, newer: Self) {
+        Arc::make_mut(self).merge(&newer);
+    }
+}
+
+#[cfg(feature = "ticker")]
+impl Conflate for Arc<crate::models::Ticker> {
+    fn conflate(&mut self, newer: Self) {
+        // Every ticker carries the full state
+        *self = newer;
+    }
+}
+
+/// Update held back while a conflated subscription's buffer is full
+struct Overflow<T> {
+    pending: parking_lot::Mutex<Option<T>>,
+    merge: fn(&mut T, T),
+}
+
+/// Result of a receive from a shared feed, handing back the receiver
+type Received<T> = (
+    std::result::Result<T, broadcast::error::RecvError>,
+    broadcast::Receiver<T>,
+);
+
+/// Receive the next update from a shared feed
+fn recv_shared<T: Clone + Send + 'static>(
+    mut receiver: broadcast::Receiver<T>,
+) -> BoxFuture<'static, Received<T>> {
+    Box::pin(async move {
+        let result = receiver.recv().await;
+        (result, receiver)
+    })
+}
+
+/// Feed a forked subscription shares with its forks
+struct Shared<T> {
+    /// Pending receive, owning this consumer's receiver
+    recv: parking_lot::Mutex<BoxFuture<'static, Received<T>>>,
+    /// Receiver new forks are started from
+    origin: broadcast::Receiver<T>,
+    /// Start the next receive
+    rearm: fn(broadcast::Receiver<T>) -> BoxFuture<'static, Received<T>>,
+    /// Start a receiver at the end of the feed
+    resubscribe: fn(&broadcast::Receiver<T>) -> broadcast::Receiver<T>,
+    /// Whether deliveries are counted here rather than by the client
+    counts_delivery: bool,
+}
+
+impl<T: Clone + Send + 'static> Shared<T> {
+    fn new(receiver: broadcast::Receiver<T>, counts_delivery: bool) -> Self {
+        Self {
+            origin: receiver.resubscribe(),
+            recv: parking_lot::Mutex::new(recv_shared(receiver)),
+            rearm: recv_shared,
+            resubscribe: broadcast::Receiver::resubscribe,
+            counts_delivery,
+        }
+    }
+}
+
+impl<T> Shared<T> {
+    /// Drop everything not received yet
+    fn skip_ahead(&self) {
+        *self.recv.lock() = (self.rearm)((self.resubscribe)(&self.origin));
+    }
+
+    fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+        stats: &SubscriptionStats,
+        paused: &AtomicBool,
+    ) -> Poll<Option<T>> {
+        loop {
+            let recv = self.recv.get_mut();
+            let Poll::Ready((result, receiver)) = recv.as_mut().poll(cx) else {
+                return Poll::Pending;
+            };
+            *recv = (self.rearm)(receiver);
+            match result {
+                Ok(update) => {
+                    if paused.load(Ordering::Acquire) {
+                        continue;
+                    }
+                    if self.counts_delivery {
+                        stats.record_message();
+                        stats.delivered.fetch_add(1, Ordering::Relaxed);
+                    }
+                    return Poll::Ready(Some(update));
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    stats.lagged.fetch_add(skipped, Ordering::Relaxed);
+                }
+                Err(broadcast::error::RecvError::Closed) => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// A receiver whose sender is gone, left behind once a subscription is shared
+fn closed_receiver<T>() -> mpsc::Receiver<T> {
+    mpsc::channel(1).1
+}
+
+/// Description of an open subscription, from [`KrakyClient::subscriptions`]
+///
+/// [`KrakyClient::subscriptions`]: crate::KrakyClient::subscriptions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionInfo {
+    /// Subscription ID, as returned by [`Subscription::id`]
+    pub id: String,
+    /// Channel name, e.g. `"book"` or a client-side feed like `"imbalance"`
+    pub channel: String,
+    /// Trading pair symbol, `"*"` for feeds covering every pair
+    pub symbol: String,
+    /// Orderbook depth, for subscriptions backed by the book channel
+    pub depth: Option<u32>,
+    /// Candle interval in minutes, for OHLC subscriptions
+    pub interval: Option<u32>,
+    /// Maximum number of buffered messages
+    pub buffer_size: usize,
+    /// Whether delivery is paused
+    pub paused: bool,
+    /// Whether updates are conflated instead of dropped under backpressure
+    pub conflated: bool,
+    /// Delivery statistics
+    pub stats: SubscriptionStatsSnapshot,
+    /// When the subscription was created
+    pub created_at: DateTime<Utc>,
+}
+
+/// Time window the message rate is smoothed over
+const RATE_WINDOW_SECS: f64 = 10.0;
+
+/// Point-in-time copy of [`SubscriptionStats`], for logging or export
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionStatsSnapshot {
+    /// Number of messages successfully delivered
+    pub delivered: u64,
+    /// Number of messages dropped due to backpressure
+    pub dropped: u64,
+    /// Number of messages folded into a newer one due to backpressure
+    #[serde(default)]
+    pub conflated: u64,
+    /// Number of messages missed by lagging behind a shared feed
+    #[serde(default)]
+    pub lagged: u64,
+    /// Dropped messages as a percentage of all messages
+    pub drop_rate: f64,
+    /// Smoothed message rate per second
+    pub messages_per_sec: f64,
+    /// Time since the last message, if one arrived
+    pub since_last_message: Option<Duration>,
+    /// Longest gap between two consecutive messages
+    pub max_gap: Duration,
+}
+
 /// Statistics for a subscription
-#[derive(Debug, Default)]
+///
+/// Besides delivery counters, the stats track when the last message arrived,
+/// a smoothed message rate and the longest gap seen between two messages, so
+/// a stalled feed can be detected without waiting on `next()`.
+#[derive(Debug)]
 pub struct SubscriptionStats {
     /// Number of messages successfully delivered
     pub delivered: AtomicU64,
     /// Number of messages dropped due to backpressure
     pub dropped: AtomicU64,
+    /// Number of messages folded into a newer one due to backpressure
+    pub conflated: AtomicU64,
+    /// Number of messages missed by lagging behind a shared feed
+    pub lagged: AtomicU64,
+    /// Reference point for the timing fields below
+    started: Instant,
+    /// Nanoseconds since `started` of the last message, plus one (0 = none yet)
+    last_message: AtomicU64,
+    /// Messages per second as of the last message, stored as `f64` bits
+    rate: AtomicU64,
+    /// Longest gap between two messages, in nanoseconds
+    max_gap: AtomicU64,
+}
+
+impl Default for SubscriptionStats {
+    fn default() -> Self {
+        Self {
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            conflated: AtomicU64::new(0),
+            lagged: AtomicU64::new(0),
+            started: Instant::now(),
+            last_message: AtomicU64::new(0),
+            rate: AtomicU64::new(0f64.to_bits()),
+            max_gap: AtomicU64::new(0),
+        }
+    }
 }
 
 impl SubscriptionStats {
@@ -140,6 +457,21 @@ impl SubscriptionStats {
         self.dropped.load(Ordering::Relaxed)
     }
 
+    /// Get the number of messages folded into a newer one
+    ///
+    /// Only conflated subscriptions coalesce messages; others drop them.
+    pub fn conflated(&self) -> u64 {
+        self.conflated.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of messages missed by lagging behind a shared feed
+    ///
+    /// Only forked subscriptions (see [`Subscription::fork`]) share a feed;
+    /// one that falls more than a buffer behind the others skips ahead.
+    pub fn lagged(&self) -> u64 {
+        self.lagged.load(Ordering::Relaxed)
+    }
+
     /// Get the drop rate as a percentage
     pub fn drop_rate(&self) -> f64 {
         let delivered = self.delivered() as f64;
@@ -151,6 +483,141 @@ impl SubscriptionStats {
             (dropped / total) * 100.0
         }
     }
+
+    /// Get the time the last message arrived, delivered or dropped
+    ///
+    /// Returns `None` if no message has arrived yet.
+    pub fn last_message_at(&self) -> Option<Instant> {
+        match self.last_message.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(self.started + Duration::from_nanos(nanos - 1)),
+        }
+    }
+
+    /// Get the time elapsed since the last message
+    ///
+    /// Returns `None` if no message has arrived yet.
+    pub fn since_last_message(&self) -> Option<Duration> {
+        self.last_message_at().map(|at| at.elapsed())
+    }
+
+    /// Get the message rate per second
+    ///
+    /// This is an exponential moving average over roughly the last ten
+    /// seconds. It keeps decaying while no messages arrive, so a stalled
+    /// feed trends towards zero.
+    pub fn messages_per_sec(&self) -> f64 {
+        let rate = f64::from_bits(self.rate.load(Ordering::Relaxed));
+        match self.since_last_message() {
+            Some(idle) => rate * (-idle.as_secs_f64() / RATE_WINDOW_SECS).exp(),
+            None => 0.0,
+        }
+    }
+
+    /// Get the longest gap observed between two consecutive messages
+    pub fn max_gap(&self) -> Duration {
+        Duration::from_nanos(self.max_gap.load(Ordering::Relaxed))
+    }
+
+    /// Check whether the feed has gone quiet for longer than `threshold`
+    ///
+    /// A subscription that never received a message counts as stale once
+    /// `threshold` has passed since it was created.
+    pub fn is_stale(&self, threshold: Duration) -> bool {
+        self.since_last_message()
+            .unwrap_or_else(|| self.started.elapsed())
+            > threshold
+    }
+
+    /// Take a serializable copy of the current values
+    pub fn snapshot(&self) -> SubscriptionStatsSnapshot {
+        SubscriptionStatsSnapshot {
+            delivered: self.delivered(),
+            dropped: self.dropped(),
+            conflated: self.conflated(),
+            lagged: self.lagged(),
+            drop_rate: self.drop_rate(),
+            messages_per_sec: self.messages_per_sec(),
+            since_last_message: self.since_last_message(),
+            max_gap: self.max_gap(),
+        }
+    }
+
+    /// Record a message arrival for the timing stats
+    fn record_message(&self) {
+        let now = self.started.elapsed().as_nanos() as u64 + 1;
+        let previous = self.last_message.swap(now, Ordering::Relaxed);
+        let gap = if previous == 0 {
+            None
+        } else {
+            Some(now.saturating_sub(previous))
+        };
+
+        if let Some(gap) = gap {
+            self.max_gap.fetch_max(gap, Ordering::Relaxed);
+        }
+
+        let decay = gap.map_or(0.0, |gap| (-(gap as f64 / 1e9) / RATE_WINDOW_SECS).exp());
+        let _ = self
+            .rate
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                let rate = f64::from_bits(bits) * decay + 1.0 / RATE_WINDOW_SECS;
+                Some(rate.to_bits())
+            });
+    }
+}
+
+/// Server answer to a subscribe request, `None` until it arrives
+type AckState = Option<std::result::Result<(), String>>;
+
+/// Receiver for the acknowledgment of one subscribe request
+pub(crate) type AckReceiver = watch::Receiver<AckState>;
+
+/// Subscribe requests waiting for their acknowledgment, keyed by `req_id`
+#[derive(Debug)]
+pub(crate) struct AckRegistry {
+    /// Allocates request IDs shared with other requests
+    router: Arc<crate::router::ReqIdRouter>,
+    pending: parking_lot::Mutex<HashMap<u64, watch::Sender<AckState>>>,
+}
+
+impl AckRegistry {
+    /// Create a registry taking request IDs from `router`
+    pub fn new(router: Arc<crate::router::ReqIdRouter>) -> Self {
+        Self {
+            router,
+            pending: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Allocate a request ID and the receiver for its acknowledgment
+    pub fn register(&self) -> (u64, AckReceiver) {
+        let req_id = self.router.next_req_id();
+        let (tx, rx) = watch::channel(None);
+        self.pending.lock().insert(req_id, tx);
+        (req_id, rx)
+    }
+
+    /// Record the server's answer; unknown request IDs are ignored
+    pub fn resolve(&self, req_id: u64, result: std::result::Result<(), String>) {
+        if let Some(tx) = self.pending.lock().remove(&req_id) {
+            tx.send_replace(Some(result));
+        }
+    }
+
+    /// Drop every pending request, so waiters see the connection closed
+    pub fn clear(&self) {
+        self.pending.lock().clear();
+    }
+}
+
+/// Subscribe and unsubscribe at the server for a paused subscription
+pub(crate) trait Upstream: Send + Sync {
+    /// Unsubscribe at the server if every subscription to the stream is paused
+    fn pause(&self) -> Result<()>;
+
+    /// Subscribe again at the server if the stream was paused there
+    fn resume(&self) -> Result<()>;
 }
 
 /// A subscription to a Kraken data stream
@@ -191,6 +658,20 @@ pub struct Subscription<T> {
     id: String,
     /// Statistics for this subscription
     stats: Arc<SubscriptionStats>,
+    /// Server acknowledgment, for subscriptions backed by a subscribe request
+    ack: Option<AckReceiver>,
+    /// Why the client ended the subscription, shared with the sender
+    failure: Arc<parking_lot::Mutex<Option<String>>>,
+    /// Whether delivery is paused, shared with the sender
+    paused: Arc<AtomicBool>,
+    /// Server-side control, for subscriptions backed by a subscribe request
+    upstream: Option<Arc<dyn Upstream>>,
+    /// Coalesced update waiting behind the buffer, for conflated subscriptions
+    overflow: Option<Arc<Overflow<T>>>,
+    /// Shared feed replacing the receiver once forked
+    shared: Option<Shared<T>>,
+    /// Tracing span carrying the channel and symbol
+    span: Span,
 }
 
 impl<T> Subscription<T> {
@@ -199,11 +680,99 @@ impl<T> Subscription<T> {
         receiver: mpsc::Receiver<T>,
         id: String,
         stats: Arc<SubscriptionStats>,
+        failure: Arc<parking_lot::Mutex<Option<String>>>,
+        paused: Arc<AtomicBool>,
+        span: Span,
     ) -> Self {
         Self {
             receiver,
             id,
             stats,
+            ack: None,
+            failure,
+            paused,
+            upstream: None,
+            overflow: None,
+            shared: None,
+            span,
+        }
+    }
+
+    /// Attach the acknowledgment of the subscribe request
+    pub(crate) fn with_ack(mut self, ack: AckReceiver) -> Self {
+        self.ack = Some(ack);
+        self
+    }
+
+    /// Attach server-side control of the stream
+    pub(crate) fn with_upstream(mut self, upstream: Arc<dyn Upstream>) -> Self {
+        self.upstream = Some(upstream);
+        self
+    }
+
+    /// Take the coalesced update waiting behind the buffer, if any
+    fn take_overflow(&self) -> Option<T> {
+        let update = self.overflow.as_ref()?.pending.lock().take()?;
+        self.stats.delivered.fetch_add(1, Ordering::Relaxed);
+        Some(update)
+    }
+
+    /// Poll for the next item, buffered ones before the overflow
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        if let Some(shared) = &mut self.shared {
+            return shared.poll_recv(cx, &self.stats, &self.paused);
+        }
+        match self.receiver.poll_recv(cx) {
+            Poll::Ready(Some(update)) => Poll::Ready(Some(update)),
+            polled => self
+                .take_overflow()
+                .map_or(polled, |u| Poll::Ready(Some(u))),
+        }
+    }
+
+    /// Get the acknowledgment of the subscribe request, if any
+    #[cfg(all(feature = "ohlc", feature = "rest"))]
+    pub(crate) fn ack(&self) -> Option<AckReceiver> {
+        self.ack.clone()
+    }
+
+    /// Wait until Kraken confirmed the subscription
+    ///
+    /// Returns [`KrakyError::Subscription`] if Kraken rejected it (for
+    /// example an unknown pair) and [`KrakyError::ConnectionClosed`] if the
+    /// client shut down first. Subscriptions that didn't send a request of
+    /// their own, such as replays or derived signals sharing an existing
+    /// orderbook, are ready immediately. Wrap it in `tokio::time::timeout`
+    /// to bound the wait.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "orderbook")]
+    /// # {
+    /// use kraky::KrakyClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let orderbook = client.subscribe_orderbook("BTC/USD", 10).await?;
+    /// orderbook.ready().await?;
+    /// // The book channel is live from here on
+    /// # Ok(())
+    /// # }
+    /// # }
+    /// ```
+    pub async fn ready(&self) -> Result<()> {
+        let Some(ack) = &self.ack else {
+            return Ok(());
+        };
+        let mut ack = ack.clone();
+        let state = ack
+            .wait_for(Option::is_some)
+            .await
+            .map_err(|_| KrakyError::ConnectionClosed)?;
+        match state.as_ref() {
+            Some(Err(error)) => Err(KrakyError::Subscription(error.clone())),
+            _ => Ok(()),
         }
     }
 
@@ -211,7 +780,84 @@ impl<T> Subscription<T> {
     ///
     /// Returns `None` if the subscription has been closed.
     pub async fn next(&mut self) -> Option<T> {
-        self.receiver.recv().await
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Get the next item, waiting at most `timeout`
+    ///
+    /// Returns `Ok(None)` if the subscription has been closed, or the
+    /// runtime driving its timer shut down, and [`Elapsed`] if nothing
+    /// arrived in time.
+    pub async fn next_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> std::result::Result<Option<T>, Elapsed> {
+        // The timer comes from the runtime shim, so this works outside tokio
+        let next = std::pin::pin!(self.next());
+        let deadline = std::pin::pin!(crate::runtime::deadline(timeout));
+        match futures_util::future::select(next, deadline).await {
+            futures_util::future::Either::Left((update, _)) => Ok(update),
+            futures_util::future::Either::Right((Ok(elapsed), _)) => Err(elapsed),
+            futures_util::future::Either::Right((Err(_), _)) => Ok(None),
+        }
+    }
+
+    /// Append up to `max` items to `buffer`
+    ///
+    /// Waits for at least one item, then takes whatever else is already
+    /// buffered without waiting again. Returns the number of items added,
+    /// which is 0 only once the subscription has been closed (or if `max`
+    /// is 0).
+    pub async fn recv_many(&mut self, buffer: &mut Vec<T>, max: usize) -> usize {
+        if max == 0 {
+            return 0;
+        }
+        if self.shared.is_some() {
+            let Some(update) = self.next().await else {
+                return 0;
+            };
+            buffer.push(update);
+            let mut received = 1;
+            while received < max {
+                match std::future::poll_fn(|cx| Poll::Ready(self.poll_recv(cx))).await {
+                    Poll::Ready(Some(update)) => buffer.push(update),
+                    _ => break,
+                }
+                received += 1;
+            }
+            return received;
+        }
+        std::future::poll_fn(|cx| match self.receiver.poll_recv_many(cx, buffer, max) {
+            Poll::Ready(n) if n == max => Poll::Ready(n),
+            polled => match self.take_overflow() {
+                Some(update) => {
+                    buffer.push(update);
+                    Poll::Ready(if let Poll::Ready(n) = polled {
+                        n + 1
+                    } else {
+                        1
+                    })
+                }
+                None => polled,
+            },
+        })
+        .await
+    }
+
+    /// Get the next item if one is buffered, without waiting
+    #[cfg(feature = "blocking")]
+    pub(crate) fn try_next(&mut self) -> std::result::Result<T, mpsc::error::TryRecvError> {
+        if self.shared.is_some() {
+            let mut cx = Context::from_waker(futures_util::task::noop_waker_ref());
+            return match self.poll_recv(&mut cx) {
+                Poll::Ready(Some(update)) => Ok(update),
+                Poll::Ready(None) => Err(mpsc::error::TryRecvError::Disconnected),
+                Poll::Pending => Err(mpsc::error::TryRecvError::Empty),
+            };
+        }
+        self.receiver
+            .try_recv()
+            .or_else(|e| self.take_overflow().ok_or(e))
     }
 
     /// Get the subscription ID
@@ -223,31 +869,285 @@ impl<T> Subscription<T> {
 
     /// Get subscription statistics
     ///
-    /// Returns stats including delivered and dropped message counts.
-    /// Use this to monitor backpressure and adjust consumption rate if needed.
+    /// Returns stats including delivered and dropped message counts, the
+    /// message rate and time since the last message. Use this to monitor
+    /// backpressure and detect stale feeds.
+    pub fn stats(&self) -> &SubscriptionStats {
+        &self.stats
+    }
+
+    /// Get the error that ended the subscription, if any
+    ///
+    /// Set in strict mode when a message meant for this subscription could
+    /// not be parsed (see [`StrictConfig`]). The subscription yields `None`
+    /// once the updates buffered before the failure are drained.
+    ///
+    /// [`StrictConfig`]: crate::StrictConfig
+    pub fn error(&self) -> Option<KrakyError> {
+        self.failure.lock().clone().map(KrakyError::InvalidMessage)
+    }
+
+    /// Stop delivering updates to this subscription
+    ///
+    /// Updates arriving while paused are discarded rather than buffered;
+    /// updates already buffered can still be read. The server keeps
+    /// sending the channel, so client state such as the managed orderbook
+    /// stays current. Other subscriptions to the same channel are not
+    /// affected.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Release);
+    }
+
+    /// Pause, and unsubscribe at the server once every subscription to the
+    /// channel and pair is paused
+    ///
+    /// While unsubscribed, the channel is not restored after a reconnect and
+    /// the managed orderbook goes stale. [`resume`](Self::resume) subscribes
+    /// again; for books, the managed orderbook is rebuilt from the new
+    /// snapshot. Subscriptions not made through a subscribe request only
+    /// pause locally.
+    pub fn pause_upstream(&self) -> Result<()> {
+        self.pause();
+        match &self.upstream {
+            Some(upstream) => upstream.pause(),
+            None => Ok(()),
+        }
+    }
+
+    /// Resume delivering updates, subscribing again at the server if the
+    /// channel was paused there
+    pub fn resume(&self) -> Result<()> {
+        let was_paused = self.paused.swap(false, Ordering::AcqRel);
+        if let (true, Some(shared)) = (was_paused, &self.shared) {
+            // Skip what the shared feed buffered meanwhile
+            shared.skip_ahead();
+        }
+        match &self.upstream {
+            Some(upstream) => upstream.resume(),
+            None => Ok(()),
+        }
+    }
+
+    /// Check whether delivery is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Get the tracing span of this subscription
+    ///
+    /// The span has `channel` and `symbol` fields and, for subscriptions
+    /// made through a [`KrakyClient`], the client's span as parent. The
+    /// client logs dropped and failed messages inside it; instrument the
+    /// consuming task with it to tag your own log lines the same way.
+    ///
+    /// [`KrakyClient`]: crate::KrakyClient
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl<T: Clone + Send + 'static> Subscription<T> {
+    /// Create another subscription to the same feed
+    ///
+    /// Every fork and this subscription receive each update, so a recorder
+    /// and a strategy can consume one feed without subscribing twice. The
+    /// feed is buffered once for all of them: one that falls more than the
+    /// buffer size behind skips ahead, counted in
+    /// [`SubscriptionStats::lagged`]. Forks start with the next update and
+    /// pause independently; only this subscription can pause the feed at
+    /// the server, and only once nothing else consumes it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "trades")]
+    /// # {
+    /// use kraky::KrakyClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = KrakyClient::connect().await?;
+    /// let mut trades = client.subscribe_trades("BTC/USD").await?;
+    /// let mut recorder = trades.fork();
+    ///
+    /// tokio::spawn(async move {
+    ///     while let Some(trade) = recorder.next().await {
+    ///         println!("Recording {:?}", trade);
+    ///     }
+    /// });
+    /// while let Some(trade) = trades.next().await {
+    ///     println!("Trading on {}", trade.price);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # }
+    /// ```
+    pub fn fork(&mut self) -> Subscription<T> {
+        let origin = match &self.shared {
+            Some(shared) => shared.origin.resubscribe(),
+            None => self.share(),
+        };
+        Subscription {
+            receiver: closed_receiver(),
+            id: format!("{}-fork-{}", self.id, uuid::Uuid::new_v4()),
+            stats: Arc::new(SubscriptionStats::default()),
+            ack: self.ack.clone(),
+            failure: Arc::clone(&self.failure),
+            paused: Arc::new(AtomicBool::new(false)),
+            upstream: None,
+            overflow: None,
+            shared: Some(Shared::new(origin, true)),
+            span: self.span.clone(),
+        }
+    }
+
+    /// Move the client feed into a pump broadcasting it to all forks
+    ///
+    /// Returns a receiver to start the first fork from.
+    fn share(&mut self) -> broadcast::Receiver<T> {
+        let (sender, receiver) = broadcast::channel(self.receiver.max_capacity().max(1));
+        // The feed keeps flowing while this subscription alone is paused
+        let paused = Arc::new(AtomicBool::new(self.is_paused()));
+        let feed_paused = std::mem::replace(&mut self.paused, paused);
+        feed_paused.store(false, Ordering::Release);
+
+        let mut source = Subscription::new(
+            std::mem::replace(&mut self.receiver, closed_receiver()),
+            self.id.clone(),
+            Arc::clone(&self.stats),
+            Arc::clone(&self.failure),
+            feed_paused,
+            self.span.clone(),
+        );
+        source.overflow = self.overflow.take();
+        let first = receiver.resubscribe();
+        self.shared = Some(Shared::new(receiver, false));
+
+        crate::runtime::spawn(
+            async move {
+                while let Some(update) = source.next().await {
+                    if sender.send(update).is_err() {
+                        // Every consumer is gone
+                        break;
+                    }
+                }
+            }
+            .instrument(self.span.clone()),
+        );
+        first
+    }
+}
+
+impl<T: Send + 'static> Subscription<T> {
+    /// Call `handler` for every update from a background task
+    ///
+    /// The task runs inside the subscription's span until the subscription
+    /// closes or the returned handle is cancelled. A panicking handler ends
+    /// the task.
+    pub fn for_each<F>(mut self, mut handler: F) -> CallbackHandle
+    where
+        F: FnMut(T) + Send + 'static,
+    {
+        let id = self.id.clone();
+        let stats = Arc::clone(&self.stats);
+        let span = self.span.clone();
+        let task = crate::runtime::spawn(
+            async move {
+                while let Some(update) = self.next().await {
+                    handler(update);
+                }
+            }
+            .instrument(span),
+        );
+        CallbackHandle { id, stats, task }
+    }
+
+    /// Call an async `handler` for every update from a background task
+    ///
+    /// Each returned future is awaited before the next update is handled,
+    /// so updates are processed in order; updates arriving meanwhile are
+    /// buffered, and dropped once the buffer is full.
+    pub fn for_each_async<F, Fut>(mut self, mut handler: F) -> CallbackHandle
+    where
+        F: FnMut(T) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let id = self.id.clone();
+        let stats = Arc::clone(&self.stats);
+        let span = self.span.clone();
+        let task = crate::runtime::spawn(
+            async move {
+                while let Some(update) = self.next().await {
+                    handler(update).await;
+                }
+            }
+            .instrument(span),
+        );
+        CallbackHandle { id, stats, task }
+    }
+}
+
+/// A registered subscription callback
+///
+/// The callback keeps running when the handle is dropped; call
+/// [`cancel`](Self::cancel) to remove it.
+#[derive(Debug)]
+pub struct CallbackHandle {
+    id: String,
+    stats: Arc<SubscriptionStats>,
+    task: JoinHandle<()>,
+}
+
+impl CallbackHandle {
+    /// Get the ID of the underlying subscription
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get statistics of the underlying subscription
     pub fn stats(&self) -> &SubscriptionStats {
         &self.stats
     }
+
+    /// Check whether the callback stopped, because the subscription closed,
+    /// the handler panicked or the handle was cancelled
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Stop calling the handler and drop the subscription
+    pub fn cancel(&self) {
+        self.task.abort();
+    }
 }
 
 impl<T> Stream for Subscription<T> {
     type Item = T;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Pin::new(&mut self.receiver).poll_recv(cx)
+        self.poll_recv(cx)
     }
 }
 
 /// Subscription sender for internal use
 pub(crate) struct SubscriptionSender<T> {
     sender: mpsc::Sender<T>,
-    #[allow(dead_code)]
     id: String,
-    #[allow(dead_code)]
     channel: String,
     pub(crate) symbol: String,
+    /// Orderbook depth, for subscriptions backed by the book channel
+    depth: Option<u32>,
+    /// Candle interval in minutes, for OHLC subscriptions
+    interval: Option<u32>,
+    created_at: DateTime<Utc>,
     /// Statistics shared with the subscription receiver
     stats: Arc<SubscriptionStats>,
+    /// Failure reason shared with the subscription receiver
+    failure: Arc<parking_lot::Mutex<Option<String>>>,
+    /// Pause flag shared with the subscription receiver
+    paused: Arc<AtomicBool>,
+    /// Overflow slot shared with the subscription receiver, if conflated
+    overflow: Option<Arc<Overflow<T>>>,
+    span: Span,
 }
 
 impl<T> SubscriptionSender<T> {
@@ -265,24 +1165,104 @@ impl<T> SubscriptionSender<T> {
         let (sender, receiver) = mpsc::channel(config.buffer_size);
         let id = format!("{}-{}-{}", channel, symbol, uuid::Uuid::new_v4());
         let stats = Arc::new(SubscriptionStats::default());
+        let failure = Arc::new(parking_lot::Mutex::new(None));
+        let paused = Arc::new(AtomicBool::new(false));
+        let span = tracing::debug_span!("subscription", channel = %channel, symbol = %symbol);
 
-        let subscription = Subscription::new(receiver, id.clone(), Arc::clone(&stats));
+        let subscription = Subscription::new(
+            receiver,
+            id.clone(),
+            Arc::clone(&stats),
+            Arc::clone(&failure),
+            Arc::clone(&paused),
+            span.clone(),
+        );
         let sender = Self {
             sender,
             id,
             channel,
             symbol,
+            depth: None,
+            interval: None,
+            created_at: Utc::now(),
             stats,
+            failure,
+            paused,
+            overflow: None,
+            span,
         };
 
         (sender, subscription)
     }
 
+    /// Record the orderbook depth the subscription was made with
+    pub fn with_depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Record the candle interval the subscription was made with
+    #[cfg(feature = "ohlc")]
+    pub fn with_interval(mut self, minutes: u32) -> Self {
+        self.interval = Some(minutes);
+        self
+    }
+
+    /// Describe the subscription
+    pub fn info(&self) -> SubscriptionInfo {
+        SubscriptionInfo {
+            id: self.id.clone(),
+            channel: self.channel.clone(),
+            symbol: self.symbol.clone(),
+            depth: self.depth,
+            interval: self.interval,
+            buffer_size: self.sender.max_capacity(),
+            paused: self.is_paused(),
+            conflated: self.overflow.is_some(),
+            stats: self.stats.snapshot(),
+            created_at: self.created_at,
+        }
+    }
+
+    /// Create a conflated subscription pair
+    ///
+    /// Updates arriving while the buffer is full are folded into one pending
+    /// update with [`Conflate::conflate`] instead of being dropped.
+    pub fn conflated(
+        channel: String,
+        symbol: String,
+        config: BackpressureConfig,
+    ) -> (Self, Subscription<T>)
+    where
+        T: Conflate,
+    {
+        let (mut sender, mut subscription) = Self::with_config(channel, symbol, config);
+        let overflow = Arc::new(Overflow {
+            pending: parking_lot::Mutex::new(None),
+            merge: T::conflate,
+        });
+        sender.overflow = Some(Arc::clone(&overflow));
+        subscription.overflow = Some(overflow);
+        (sender, subscription)
+    }
+
     /// Send data to the subscription (non-blocking with backpressure)
     ///
     /// If the channel buffer is full, this will drop the message and
     /// increment the dropped counter. The WebSocket handler is never blocked.
+    /// While the subscription is paused, data is discarded.
     pub fn send(&self, data: T) -> Result<()> {
+        if self.is_paused() {
+            return if self.sender.is_closed() {
+                Err(KrakyError::ChannelSend("subscription closed".to_string()))
+            } else {
+                Ok(())
+            };
+        }
+        self.stats.record_message();
+        if let Some(overflow) = &self.overflow {
+            return self.send_conflated(overflow, data);
+        }
         match self.sender.try_send(data) {
             Ok(()) => {
                 self.stats.delivered.fetch_add(1, Ordering::Relaxed);
@@ -291,6 +1271,7 @@ impl<T> SubscriptionSender<T> {
             Err(mpsc::error::TrySendError::Full(_)) => {
                 // Backpressure: drop the message to avoid blocking
                 self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                trace!(parent: &self.span, "Subscription buffer full, dropping message");
                 Ok(()) // Not an error - this is expected behavior
             }
             Err(mpsc::error::TrySendError::Closed(_)) => {
@@ -299,27 +1280,98 @@ impl<T> SubscriptionSender<T> {
         }
     }
 
+    /// Send data behind any pending update, holding it back if the buffer is full
+    fn send_conflated(&self, overflow: &Overflow<T>, data: T) -> Result<()> {
+        let mut pending = overflow.pending.lock();
+        let data = match pending.take() {
+            Some(mut held) => {
+                (overflow.merge)(&mut held, data);
+                self.stats.conflated.fetch_add(1, Ordering::Relaxed);
+                held
+            }
+            None => data,
+        };
+        match self.sender.try_send(data) {
+            Ok(()) => {
+                self.stats.delivered.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(mpsc::error::TrySendError::Full(data)) => {
+                trace!(parent: &self.span, "Subscription buffer full, conflating message");
+                *pending = Some(data);
+                Ok(())
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                Err(KrakyError::ChannelSend("subscription closed".to_string()))
+            }
+        }
+    }
+
+    /// Send data, waiting for buffer space instead of dropping
+    ///
+    /// Used where completeness matters more than latency, such as replaying
+    /// recorded data or backfilled candles.
+    #[cfg(any(feature = "replay", all(feature = "ohlc", feature = "rest")))]
+    pub async fn send_wait(&self, data: T) -> Result<()> {
+        self.stats.record_message();
+        self.sender
+            .send(data)
+            .await
+            .map_err(|_| KrakyError::ChannelSend("subscription closed".to_string()))?;
+        self.stats.delivered.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
     /// Check if the subscription is still active
-    #[allow(dead_code)]
     pub fn is_closed(&self) -> bool {
         self.sender.is_closed()
     }
+
+    /// Check whether the subscription is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Record why the subscription ends; it closes when the sender is dropped
+    pub fn fail(&self, error: &str) {
+        warn!(parent: &self.span, "Ending subscription: {}", error);
+        *self.failure.lock() = Some(error.to_string());
+    }
 }
 
 /// Manager for multiple subscriptions
 pub(crate) struct SubscriptionManager {
     /// Active orderbook subscriptions
     #[cfg(feature = "orderbook")]
-    pub orderbook: Vec<SubscriptionSender<crate::models::OrderbookUpdate>>,
+    pub orderbook: Vec<SubscriptionSender<Arc<crate::models::OrderbookUpdate>>>,
     /// Active trade subscriptions
     #[cfg(feature = "trades")]
-    pub trades: Vec<SubscriptionSender<crate::models::Trade>>,
+    pub trades: Vec<SubscriptionSender<Arc<crate::models::Trade>>>,
     /// Active ticker subscriptions
     #[cfg(feature = "ticker")]
-    pub ticker: Vec<SubscriptionSender<crate::models::Ticker>>,
+    pub ticker: Vec<SubscriptionSender<Arc<crate::models::Ticker>>>,
     /// Active OHLC subscriptions
     #[cfg(feature = "ohlc")]
-    pub ohlc: Vec<SubscriptionSender<crate::models::OHLC>>,
+    pub ohlc: Vec<SubscriptionSender<Arc<crate::models::OHLC>>>,
+    /// Active imbalance signal subscriptions
+    #[cfg(feature = "analytics")]
+    pub imbalance: Vec<ImbalanceSubscription>,
+    /// Raw text frame subscriptions
+    #[cfg(feature = "bridge")]
+    pub frames: Vec<SubscriptionSender<String>>,
+    /// Strict mode parse diagnostic subscriptions
+    pub diagnostics: Vec<SubscriptionSender<crate::messages::ParseDiagnostic>>,
+    /// Untyped subscriptions to channels without typed support
+    pub raw: Vec<SubscriptionSender<serde_json::Value>>,
+}
+
+/// Imbalance signal subscription with one tracker per symbol
+#[cfg(feature = "analytics")]
+pub(crate) struct ImbalanceSubscription {
+    pub sender: SubscriptionSender<crate::analytics::ImbalanceTransition>,
+    pub config: crate::analytics::ImbalanceConfig,
+    pub trackers:
+        parking_lot::Mutex<std::collections::HashMap<String, crate::analytics::ImbalanceTracker>>,
 }
 
 impl Default for SubscriptionManager {
@@ -340,29 +1392,219 @@ impl SubscriptionManager {
             ticker: Vec::new(),
             #[cfg(feature = "ohlc")]
             ohlc: Vec::new(),
+            #[cfg(feature = "analytics")]
+            imbalance: Vec::new(),
+            #[cfg(feature = "bridge")]
+            frames: Vec::new(),
+            diagnostics: Vec::new(),
+            raw: Vec::new(),
         }
     }
 
-    /// Clean up closed subscriptions
-    #[allow(dead_code)]
-    pub fn cleanup(&mut self) {
+    /// Dispatch a rejected frame to diagnostic subscriptions
+    pub fn dispatch_diagnostic(&self, diagnostic: &crate::messages::ParseDiagnostic) {
+        for sub in &self.diagnostics {
+            let _ = sub.send(diagnostic.clone());
+        }
+    }
+
+    /// End the subscriptions of a channel for the given symbols
+    ///
+    /// Wildcard subscriptions are always affected, and every subscription
+    /// of the channel is when no symbols are known.
+    pub fn fail(&mut self, channel: &str, symbols: &[String], error: &str) {
+        let affected = |sub_symbol: &str| {
+            sub_symbol == "*" || symbols.is_empty() || symbols.iter().any(|s| s == sub_symbol)
+        };
+        match channel {
+            #[cfg(feature = "orderbook")]
+            "book" => {
+                fail_matching(&mut self.orderbook, affected, error);
+                #[cfg(feature = "analytics")]
+                self.imbalance.retain(|sub| {
+                    let failed = affected(&sub.sender.symbol);
+                    if failed {
+                        sub.sender.fail(error);
+                    }
+                    !failed
+                });
+            }
+            #[cfg(feature = "trades")]
+            "trade" => fail_matching(&mut self.trades, affected, error),
+            #[cfg(feature = "ticker")]
+            "ticker" => fail_matching(&mut self.ticker, affected, error),
+            #[cfg(feature = "ohlc")]
+            "ohlc" => fail_matching(&mut self.ohlc, affected, error),
+            _ => {}
+        }
+        self.raw.retain(|sub| {
+            let failed = sub.channel == channel && affected(&sub.symbol);
+            if failed {
+                sub.fail(error);
+            }
+            !failed
+        });
+    }
+
+    /// End the subscriptions of a channel for one symbol
+    ///
+    /// Their streams end without an error; wildcard subscriptions stay.
+    pub fn close(&mut self, channel: &str, symbol: &str) {
+        match channel {
+            #[cfg(feature = "orderbook")]
+            "book" => {
+                self.orderbook.retain(|sub| sub.symbol != symbol);
+                #[cfg(feature = "analytics")]
+                self.imbalance.retain(|sub| sub.sender.symbol != symbol);
+            }
+            #[cfg(feature = "trades")]
+            "trade" => self.trades.retain(|sub| sub.symbol != symbol),
+            #[cfg(feature = "ticker")]
+            "ticker" => self.ticker.retain(|sub| sub.symbol != symbol),
+            #[cfg(feature = "ohlc")]
+            "ohlc" => self.ohlc.retain(|sub| sub.symbol != symbol),
+            _ => {}
+        }
+        self.raw
+            .retain(|sub| sub.channel != channel || sub.symbol != symbol);
+    }
+
+    /// Describe the open subscriptions, oldest first
+    pub fn infos(&self) -> Vec<SubscriptionInfo> {
+        fn open<T>(
+            senders: &[SubscriptionSender<T>],
+        ) -> impl Iterator<Item = SubscriptionInfo> + '_ {
+            senders
+                .iter()
+                .filter(|sub| !sub.is_closed())
+                .map(SubscriptionSender::info)
+        }
+        let mut infos = Vec::new();
         #[cfg(feature = "orderbook")]
-        self.orderbook.retain(|s| !s.is_closed());
+        infos.extend(open(&self.orderbook));
         #[cfg(feature = "trades")]
-        self.trades.retain(|s| !s.is_closed());
+        infos.extend(open(&self.trades));
         #[cfg(feature = "ticker")]
-        self.ticker.retain(|s| !s.is_closed());
+        infos.extend(open(&self.ticker));
         #[cfg(feature = "ohlc")]
-        self.ohlc.retain(|s| !s.is_closed());
+        infos.extend(open(&self.ohlc));
+        #[cfg(feature = "analytics")]
+        infos.extend(
+            self.imbalance
+                .iter()
+                .filter(|sub| !sub.sender.is_closed())
+                .map(|sub| sub.sender.info()),
+        );
+        #[cfg(feature = "bridge")]
+        infos.extend(open(&self.frames));
+        infos.extend(open(&self.diagnostics));
+        infos.extend(open(&self.raw));
+        infos.sort_by_key(|info| info.created_at);
+        infos
+    }
+
+    /// Get the pause flags of the open subscriptions to a channel and symbol
+    pub fn paused_flags(&self, channel: &str, symbol: &str) -> Vec<bool> {
+        fn flags<T>(senders: &[SubscriptionSender<T>], symbol: &str) -> Vec<bool> {
+            senders
+                .iter()
+                .filter(|sub| sub.symbol == symbol && !sub.is_closed())
+                .map(SubscriptionSender::is_paused)
+                .collect()
+        }
+        let mut paused = match channel {
+            #[cfg(feature = "orderbook")]
+            "book" => {
+                let paused = flags(&self.orderbook, symbol);
+                // Imbalance signals are derived from the book and can't pause
+                #[cfg(feature = "analytics")]
+                let paused = paused
+                    .into_iter()
+                    .chain(
+                        self.imbalance
+                            .iter()
+                            .filter(|sub| sub.sender.symbol == symbol && !sub.sender.is_closed())
+                            .map(|_| false),
+                    )
+                    .collect();
+                paused
+            }
+            #[cfg(feature = "trades")]
+            "trade" => flags(&self.trades, symbol),
+            #[cfg(feature = "ticker")]
+            "ticker" => flags(&self.ticker, symbol),
+            #[cfg(feature = "ohlc")]
+            "ohlc" => flags(&self.ohlc, symbol),
+            _ => Vec::new(),
+        };
+        paused.extend(
+            self.raw
+                .iter()
+                .filter(|sub| sub.channel == channel && sub.symbol == symbol && !sub.is_closed())
+                .map(SubscriptionSender::is_paused),
+        );
+        paused
+    }
+
+    /// Dispatch a message of a channel without typed support to raw subscriptions
+    ///
+    /// Subscriptions get the messages whose data carries their symbol, or
+    /// all messages of the channel for the `*` symbol or symbol-less data.
+    /// Returns whether any raw subscription covers the channel.
+    pub fn dispatch_raw(&self, message: &serde_json::Value) -> bool {
+        let Some(channel) = message.get("channel").and_then(serde_json::Value::as_str) else {
+            return false;
+        };
+        let symbols: Vec<&str> = match message.get("data") {
+            Some(serde_json::Value::Array(data)) => data
+                .iter()
+                .filter_map(|item| item.get("symbol").and_then(serde_json::Value::as_str))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let mut covered = false;
+        for sub in self.raw.iter().filter(|sub| sub.channel == channel) {
+            covered = true;
+            if sub.symbol == "*" || symbols.is_empty() || symbols.contains(&sub.symbol.as_str()) {
+                let _ = sub.send(message.clone());
+            }
+        }
+        covered
+    }
+
+    /// Dispatch a raw text frame to frame subscriptions
+    #[cfg(feature = "bridge")]
+    pub fn dispatch_frame(&self, text: &str) {
+        for sub in &self.frames {
+            let _ = sub.send(text.to_string());
+        }
     }
 
     /// Dispatch orderbook update to relevant subscriptions
+    ///
+    /// Subscribers share one allocation of the update.
     #[cfg(feature = "orderbook")]
-    pub fn dispatch_orderbook(&self, update: &crate::models::OrderbookUpdate) {
+    pub fn dispatch_orderbook(&self, update: Arc<crate::models::OrderbookUpdate>) {
         for data in &update.data {
             for sub in &self.orderbook {
                 if sub.symbol == data.symbol || sub.symbol == "*" {
-                    let _ = sub.send(update.clone());
+                    let _ = sub.send(Arc::clone(&update));
+                }
+            }
+        }
+    }
+
+    /// Feed an updated orderbook to imbalance subscriptions, emitting signal transitions
+    #[cfg(feature = "analytics")]
+    pub fn dispatch_imbalance(&self, orderbook: &crate::models::Orderbook) {
+        for sub in &self.imbalance {
+            if sub.sender.symbol == orderbook.symbol || sub.sender.symbol == "*" {
+                let mut trackers = sub.trackers.lock();
+                let tracker = trackers
+                    .entry(orderbook.symbol.clone())
+                    .or_insert_with(|| crate::analytics::ImbalanceTracker::new(sub.config));
+                if let Some(transition) = tracker.update_orderbook(orderbook) {
+                    let _ = sub.sender.send(transition);
                 }
             }
         }
@@ -372,10 +1614,10 @@ impl SubscriptionManager {
     #[cfg(feature = "trades")]
     pub fn dispatch_trade(&self, update: &crate::models::TradeUpdate) {
         for data in &update.data {
-            let trade = data.to_trade();
+            let trade = Arc::new(data.to_trade());
             for sub in &self.trades {
                 if sub.symbol == trade.symbol || sub.symbol == "*" {
-                    let _ = sub.send(trade.clone());
+                    let _ = sub.send(Arc::clone(&trade));
                 }
             }
         }
@@ -385,10 +1627,10 @@ impl SubscriptionManager {
     #[cfg(feature = "ticker")]
     pub fn dispatch_ticker(&self, update: &crate::models::TickerUpdate) {
         for data in &update.data {
-            let ticker = data.to_ticker();
+            let ticker = Arc::new(data.to_ticker());
             for sub in &self.ticker {
                 if sub.symbol == ticker.symbol || sub.symbol == "*" {
-                    let _ = sub.send(ticker.clone());
+                    let _ = sub.send(Arc::clone(&ticker));
                 }
             }
         }
@@ -398,16 +1640,37 @@ impl SubscriptionManager {
     #[cfg(feature = "ohlc")]
     pub fn dispatch_ohlc(&self, update: &crate::models::OHLCUpdate) {
         for data in &update.data {
-            let ohlc = data.to_ohlc();
+            let ohlc = Arc::new(data.to_ohlc());
             for sub in &self.ohlc {
                 if sub.symbol == ohlc.symbol || sub.symbol == "*" {
-                    let _ = sub.send(ohlc.clone());
+                    let _ = sub.send(Arc::clone(&ohlc));
                 }
             }
         }
     }
 }
 
+/// Record the error on matching senders and drop them
+#[cfg(any(
+    feature = "orderbook",
+    feature = "trades",
+    feature = "ticker",
+    feature = "ohlc"
+))]
+fn fail_matching<T>(
+    subs: &mut Vec<SubscriptionSender<T>>,
+    affected: impl Fn(&str) -> bool,
+    error: &str,
+) {
+    subs.retain(|sub| {
+        let failed = affected(&sub.symbol);
+        if failed {
+            sub.fail(error);
+        }
+        !failed
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -425,6 +1688,176 @@ mod tests {
         assert_eq!(msg, Some("hello".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_next_timeout_and_recv_many() {
+        let (sender, mut subscription) =
+            SubscriptionSender::<u32>::new("trade".to_string(), "BTC/USD".to_string());
+
+        let quiet = subscription.next_timeout(Duration::from_millis(10)).await;
+        assert!(quiet.is_err());
+        sender.send(1).unwrap();
+        let next = subscription.next_timeout(Duration::from_millis(10)).await;
+        assert_eq!(next.unwrap(), Some(1));
+
+        for n in 2..=5 {
+            sender.send(n).unwrap();
+        }
+        let mut batch = vec![0];
+        assert_eq!(subscription.recv_many(&mut batch, 3).await, 3);
+        assert_eq!(batch, vec![0, 2, 3, 4]);
+        assert_eq!(subscription.recv_many(&mut batch, 3).await, 1);
+        assert_eq!(batch, vec![0, 2, 3, 4, 5]);
+
+        drop(sender);
+        assert_eq!(subscription.recv_many(&mut batch, 3).await, 0);
+        let closed = subscription.next_timeout(Duration::from_millis(10)).await;
+        assert_eq!(closed.unwrap(), None);
+    }
+
+    /// Updates merging into the list of everything they replaced
+    impl Conflate for Vec<u32> {
+        fn conflate(&mut self, newer: Self) {
+            self.extend(newer);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conflated_subscription_merges_overflow() {
+        let (sender, mut subscription) = SubscriptionSender::<Vec<u32>>::conflated(
+            "book".to_string(),
+            "BTC/USD".to_string(),
+            BackpressureConfig::with_buffer_size(1),
+        );
+
+        for n in 1..=4 {
+            sender.send(vec![n]).unwrap();
+        }
+        assert_eq!(subscription.stats().conflated(), 2);
+        assert_eq!(subscription.stats().dropped(), 0);
+
+        // Buffered updates come first, then the merged overflow
+        assert_eq!(subscription.next().await, Some(vec![1]));
+        sender.send(vec![5]).unwrap();
+        assert_eq!(subscription.next().await, Some(vec![2, 3, 4, 5]));
+        sender.send(vec![6]).unwrap();
+        let mut batch = Vec::new();
+        assert_eq!(subscription.recv_many(&mut batch, 10).await, 1);
+        assert_eq!(batch, vec![vec![6]]);
+        assert_eq!(subscription.stats().delivered(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_forks_share_the_feed() {
+        let (sender, mut original) = SubscriptionSender::<u32>::with_config(
+            "trade".to_string(),
+            "BTC/USD".to_string(),
+            BackpressureConfig::with_buffer_size(4),
+        );
+        let mut fork = original.fork();
+        let mut paused = fork.fork();
+        paused.pause();
+        assert!(fork.id().starts_with(original.id()));
+
+        for n in 1..=4 {
+            sender.send(n).unwrap();
+        }
+        let mut batch = Vec::new();
+        while batch.len() < 4 {
+            original.recv_many(&mut batch, 4).await;
+        }
+        assert_eq!(batch, vec![1, 2, 3, 4]);
+        for n in 5..=8 {
+            sender.send(n).unwrap();
+        }
+        for n in 5..=8 {
+            assert_eq!(original.next().await, Some(n));
+        }
+
+        // The fork fell a full buffer behind and skipped ahead
+        assert_eq!(fork.next().await, Some(5));
+        assert_eq!(fork.stats().lagged(), 4);
+        assert_eq!(fork.stats().delivered(), 1);
+        assert_eq!(original.stats().lagged(), 0);
+        assert_eq!(original.stats().delivered(), 8);
+
+        paused.resume().unwrap();
+        sender.send(9).unwrap();
+        assert_eq!(paused.next().await, Some(9));
+
+        drop(sender);
+        assert_eq!(original.next().await, Some(9));
+        assert_eq!(original.next().await, None);
+        assert_eq!(fork.next_timeout(Duration::from_secs(1)).await, Ok(Some(6)));
+    }
+
+    #[tokio::test]
+    async fn test_paused_subscription_drops_updates() {
+        let (sender, mut subscription) =
+            SubscriptionSender::<u32>::new("trade".to_string(), "BTC/USD".to_string());
+
+        subscription.pause();
+        assert!(subscription.is_paused() && sender.is_paused());
+        sender.send(1).unwrap();
+        assert_eq!(subscription.stats().delivered(), 0);
+
+        // Resuming without an upstream only restarts delivery
+        subscription.pause_upstream().unwrap();
+        subscription.resume().unwrap();
+        sender.send(2).unwrap();
+        assert_eq!(subscription.next().await, Some(2));
+
+        // A closed subscription still reports the error while paused
+        subscription.pause();
+        drop(subscription);
+        assert!(sender.send(3).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_callbacks_receive_updates() {
+        let (sender, subscription) =
+            SubscriptionSender::<u32>::new("trade".to_string(), "BTC/USD".to_string());
+        let (seen, mut rx) = mpsc::unbounded_channel();
+        let handle = subscription.for_each(move |n| seen.send(n).unwrap());
+        assert!(handle.id().starts_with("trade-BTC/USD-"));
+
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert_eq!((rx.recv().await, rx.recv().await), (Some(1), Some(2)));
+        assert_eq!(handle.stats().delivered(), 2);
+
+        // The handler stops once the subscription closes
+        drop(sender);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !handle.is_finished() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let (sender, subscription) =
+            SubscriptionSender::<u32>::new("trade".to_string(), "BTC/USD".to_string());
+        let (seen, mut rx) = mpsc::unbounded_channel();
+        let handle = subscription.for_each_async(move |n| {
+            let seen = seen.clone();
+            async move {
+                tokio::task::yield_now().await;
+                seen.send(n * 10).unwrap();
+            }
+        });
+        sender.send(3).unwrap();
+        assert_eq!(rx.recv().await, Some(30));
+
+        handle.cancel();
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !sender.is_closed() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
     #[test]
     fn test_subscription_id_format() {
         let (sender, subscription) =
@@ -434,6 +1867,28 @@ mod tests {
         assert!(sender.symbol == "BTC/USD");
     }
 
+    #[test]
+    fn test_stats_snapshot_serializes() {
+        let (sender, _subscription) =
+            SubscriptionSender::<String>::new("test".to_string(), "BTC/USD".to_string());
+        sender.send("msg".to_string()).unwrap();
+
+        let snapshot = sender.stats.snapshot();
+        assert_eq!((snapshot.delivered, snapshot.dropped), (1, 0));
+
+        let json = serde_json::to_value(snapshot).unwrap();
+        for field in [
+            "delivered",
+            "dropped",
+            "drop_rate",
+            "messages_per_sec",
+            "since_last_message",
+            "max_gap",
+        ] {
+            assert!(json.get(field).is_some(), "missing {}", field);
+        }
+    }
+
     #[tokio::test]
     async fn test_backpressure_drops_messages() {
         // Create a subscription with a small buffer
@@ -477,4 +1932,75 @@ mod tests {
         // 20 / 100 = 20%
         assert!((stats.drop_rate() - 20.0).abs() < 0.001);
     }
+
+    #[test]
+    fn test_staleness_stats() {
+        let (sender, subscription) =
+            SubscriptionSender::<u32>::new("test".to_string(), "BTC/USD".to_string());
+        let stats = subscription.stats();
+
+        assert!(stats.last_message_at().is_none());
+        assert_eq!(stats.messages_per_sec(), 0.0);
+        assert!(!stats.is_stale(Duration::from_secs(60)));
+        assert!(stats.is_stale(Duration::ZERO));
+
+        sender.send(1).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        sender.send(2).unwrap();
+
+        assert!(stats.last_message_at().is_some());
+        assert!(stats.max_gap() >= Duration::from_millis(20));
+        assert!(stats.messages_per_sec() > 0.0);
+        assert!(!stats.is_stale(Duration::from_secs(60)));
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(stats.is_stale(Duration::from_millis(10)));
+    }
+
+    #[cfg(feature = "trades")]
+    #[tokio::test]
+    async fn test_dispatch_shares_updates() {
+        use crate::models::TradeUpdate;
+
+        let mut manager = SubscriptionManager::new();
+        let (first, mut first_sub) =
+            SubscriptionSender::new("trade".to_string(), "BTC/USD".to_string());
+        let (second, mut second_sub) =
+            SubscriptionSender::new("trade".to_string(), "*".to_string());
+        manager.trades.push(first);
+        manager.trades.push(second);
+
+        let update: TradeUpdate = serde_json::from_str(
+            r#"{"channel":"trade","type":"update","data":[{"symbol":"BTC/USD","side":"buy","price":42000.0,"qty":0.5,"ord_type":"limit","trade_id":1,"timestamp":"2024-01-15T10:00:00.000000Z"}]}"#,
+        )
+        .unwrap();
+        manager.dispatch_trade(&update);
+
+        let a = first_sub.next().await.unwrap();
+        let b = second_sub.next().await.unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(a.price, 42000.0);
+    }
+
+    #[tokio::test]
+    async fn test_ready_after_ack() {
+        let acks = AckRegistry::new(Default::default());
+        let (_sender, subscription) =
+            SubscriptionSender::<u32>::new("book".to_string(), "BTC/USD".to_string());
+        let (req_id, ack) = acks.register();
+        let subscription = subscription.with_ack(ack);
+
+        acks.resolve(req_id, Ok(()));
+        assert!(subscription.ready().await.is_ok());
+
+        let (_sender, pending) =
+            SubscriptionSender::<u32>::new("book".to_string(), "ETH/USD".to_string());
+        let (_, ack) = acks.register();
+        let pending = pending.with_ack(ack);
+        acks.clear();
+        assert!(matches!(
+            pending.ready().await,
+            Err(KrakyError::ConnectionClosed)
+        ));
+    }
 }
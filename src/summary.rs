@@ -0,0 +1,470 @@
+//! Scheduled activity summaries
+//!
+//! [`SummaryScheduler`] gathers what happened since the last summary and
+//! sends a [`PeriodSummary`] to its notifiers on a schedule: every few
+//! minutes, at a minute past each hour, or daily at a fixed UTC time. A
+//! summary covers
+//!
+//! - messages received by the client,
+//! - the price change of chosen pairs, from their tickers,
+//! - alerts and trades recorded through [`SummaryCounters`], with P&L once
+//!   a trade was recorded.
+//!
+//! Summaries are also available as a stream from the [`SummaryHandle`].
+//!
+//! Requires the `notify` feature flag; price changes need `ticker`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(all(feature = "telegram", feature = "ticker"))]
+//! # {
+//! use kraky::{KrakyClient, SummarySchedule, SummaryScheduler, TelegramNotifier};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Arc::new(KrakyClient::connect().await?);
+//! let _ticker = client.subscribe_ticker("BTC/USD").await?;
+//! let bot = TelegramNotifier::new("bot_token", 123456789);
+//!
+//! let summaries = SummaryScheduler::new(client, SummarySchedule::Daily { hour: 21, minute: 0 })
+//!     .pair("BTC/USD")
+//!     .notifier(Arc::new(bot))
+//!     .spawn();
+//!
+//! // Count alerts wherever they are sent
+//! let counters = summaries.counters();
+//! counters.alert();
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+
+use crate::client::KrakyClient;
+use crate::notifier::Notifier;
+use chrono::{DateTime, Duration as ChronoDuration, DurationRound, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Summaries buffered before new ones are skipped
+const SUMMARY_BUFFER: usize = 16;
+
+/// When summaries are sent
+///
+/// Times are in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SummarySchedule {
+    /// After each interval, starting from when the scheduler was spawned
+    Every(Duration),
+    /// Every hour at this minute
+    Hourly {
+        /// Minute of the hour (0-59)
+        minute: u32,
+    },
+    /// Every day at this time
+    Daily {
+        /// Hour of the day (0-23)
+        hour: u32,
+        /// Minute of the hour (0-59)
+        minute: u32,
+    },
+}
+
+impl SummarySchedule {
+    /// Get the first time after `now` a summary is due
+    pub fn next_after(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let (step, offset) = match *self {
+            SummarySchedule::Every(interval) => {
+                return now + ChronoDuration::from_std(interval).unwrap_or(ChronoDuration::MAX)
+            }
+            SummarySchedule::Hourly { minute } => (
+                ChronoDuration::hours(1),
+                ChronoDuration::minutes(minute.min(59) as i64),
+            ),
+            SummarySchedule::Daily { hour, minute } => (
+                ChronoDuration::days(1),
+                ChronoDuration::hours(hour.min(23) as i64)
+                    + ChronoDuration::minutes(minute.min(59) as i64),
+            ),
+        };
+        let start = now.duration_trunc(step).unwrap_or(now) + offset;
+        if start > now {
+            start
+        } else {
+            start + step
+        }
+    }
+}
+
+/// Alert and trade counts included in the next summary
+///
+/// Shared between the scheduler and the code sending alerts or trading.
+#[derive(Debug, Default)]
+pub struct SummaryCounters {
+    alerts: AtomicU64,
+    trades: AtomicU64,
+    /// Traded volume and realized P&L
+    trading: Mutex<Option<(f64, f64)>>,
+}
+
+impl SummaryCounters {
+    /// Count an alert that was sent
+    pub fn alert(&self) {
+        self.alerts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a trade with its quote volume and realized P&L
+    pub fn trade(&self, volume: f64, pnl: f64) {
+        self.trades.fetch_add(1, Ordering::Relaxed);
+        let mut trading = self.trading.lock();
+        let (total_volume, total_pnl) = trading.get_or_insert((0.0, 0.0));
+        *total_volume += volume;
+        *total_pnl += pnl;
+    }
+
+    /// Return the counts and start from zero
+    fn take(&self) -> (u64, u64, Option<(f64, f64)>) {
+        (
+            self.alerts.swap(0, Ordering::Relaxed),
+            self.trades.swap(0, Ordering::Relaxed),
+            self.trading.lock().take(),
+        )
+    }
+}
+
+/// Price change of a pair over a summary period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceChange {
+    /// Trading pair
+    pub pair: String,
+    /// Last price at the start of the period
+    pub open: f64,
+    /// Last price at the end of the period
+    pub close: f64,
+    /// Change in percent
+    pub change_pct: f64,
+}
+
+/// Activity over one summary period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodSummary {
+    /// Start of the period
+    pub start: DateTime<Utc>,
+    /// End of the period
+    pub end: DateTime<Utc>,
+    /// Messages received across all connections
+    pub messages: u64,
+    /// Price changes of pairs with a ticker
+    pub prices: Vec<PriceChange>,
+    /// Alerts sent
+    pub alerts: u64,
+    /// Trades made
+    pub trades: u64,
+    /// Traded quote volume
+    pub volume: f64,
+    /// Realized P&L, if any trade was recorded
+    pub pnl: Option<f64>,
+}
+
+/// Sends periodic [`PeriodSummary`]s to notifiers
+pub struct SummaryScheduler {
+    client: Arc<KrakyClient>,
+    schedule: SummarySchedule,
+    pairs: Vec<String>,
+    notifiers: Vec<Arc<dyn Notifier>>,
+    counters: Arc<SummaryCounters>,
+}
+
+impl std::fmt::Debug for SummaryScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SummaryScheduler")
+            .field("schedule", &self.schedule)
+            .field("pairs", &self.pairs)
+            .field("notifiers", &self.notifiers.len())
+            .finish()
+    }
+}
+
+impl SummaryScheduler {
+    /// Create a scheduler for `client`'s activity
+    pub fn new(client: Arc<KrakyClient>, schedule: SummarySchedule) -> Self {
+        Self {
+            client,
+            schedule,
+            pairs: Vec::new(),
+            notifiers: Vec::new(),
+            counters: Arc::new(SummaryCounters::default()),
+        }
+    }
+
+    /// Include the price change of a pair
+    ///
+    /// Prices come from the client's latest tickers, so the pair needs a
+    /// ticker subscription.
+    ///
+    /// Only available when the `ticker` feature is enabled.
+    #[cfg(feature = "ticker")]
+    pub fn pair(mut self, pair: impl Into<String>) -> Self {
+        self.pairs.push(pair.into());
+        self
+    }
+
+    /// Send summaries through a notifier
+    pub fn notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    /// Get the counters to record alerts and trades in
+    pub fn counters(&self) -> Arc<SummaryCounters> {
+        Arc::clone(&self.counters)
+    }
+
+    /// Start sending summaries in the background
+    pub fn spawn(self) -> SummaryHandle {
+        let (tx, rx) = mpsc::channel(SUMMARY_BUFFER);
+        let counters = Arc::clone(&self.counters);
+        let task = crate::runtime::spawn(self.run(tx));
+        SummaryHandle {
+            summaries: rx,
+            counters,
+            task,
+        }
+    }
+
+    async fn run(self, tx: mpsc::Sender<PeriodSummary>) {
+        let mut start = Utc::now();
+        let mut messages = self.messages();
+        let mut opens: HashMap<String, f64> = HashMap::new();
+        self.update_opens(&mut opens);
+
+        loop {
+            let due = self.schedule.next_after(Utc::now());
+            let wait = (due - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            let end = Utc::now();
+            let total = self.messages();
+            let (alerts, trades, trading) = self.counters.take();
+            let summary = PeriodSummary {
+                start,
+                end,
+                messages: total.saturating_sub(messages),
+                prices: self.price_changes(&opens),
+                alerts,
+                trades,
+                volume: trading.map(|(volume, _)| volume).unwrap_or_default(),
+                pnl: trading.map(|(_, pnl)| pnl),
+            };
+            start = end;
+            messages = total;
+            opens.clear();
+            self.update_opens(&mut opens);
+
+            for notifier in &self.notifiers {
+                if let Err(e) = notifier.send_period_summary(&summary).await {
+                    warn!("Failed to send summary: {}", e);
+                }
+            }
+            // Keep running for the notifiers when nobody reads the stream
+            if let Err(mpsc::error::TrySendError::Closed(_)) = tx.try_send(summary) {
+                if self.notifiers.is_empty() {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Messages received across the client's connections
+    fn messages(&self) -> u64 {
+        self.client
+            .pool_health()
+            .iter()
+            .map(|health| health.messages_received)
+            .sum()
+    }
+
+    /// Record the current price of each pair as its open
+    fn update_opens(&self, opens: &mut HashMap<String, f64>) {
+        for pair in &self.pairs {
+            if let Some(price) = self.last_price(pair) {
+                opens.insert(pair.clone(), price);
+            }
+        }
+    }
+
+    /// Price changes of the pairs with an open and a current price
+    fn price_changes(&self, opens: &HashMap<String, f64>) -> Vec<PriceChange> {
+        self.pairs
+            .iter()
+            .filter_map(|pair| {
+                let close = self.last_price(pair)?;
+                // A pair without a price at the start opens at its first one
+                let open = opens.get(pair).copied().unwrap_or(close);
+                Some(PriceChange {
+                    pair: pair.clone(),
+                    open,
+                    close,
+                    change_pct: if open != 0.0 {
+                        (close - open) / open * 100.0
+                    } else {
+                        0.0
+                    },
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "ticker")]
+    fn last_price(&self, pair: &str) -> Option<f64> {
+        self.client.last_price(pair)
+    }
+
+    #[cfg(not(feature = "ticker"))]
+    fn last_price(&self, _pair: &str) -> Option<f64> {
+        None
+    }
+}
+
+/// Running [`SummaryScheduler`]
+#[derive(Debug)]
+pub struct SummaryHandle {
+    summaries: mpsc::Receiver<PeriodSummary>,
+    counters: Arc<SummaryCounters>,
+    task: JoinHandle<()>,
+}
+
+impl SummaryHandle {
+    /// Get the counters to record alerts and trades in
+    pub fn counters(&self) -> Arc<SummaryCounters> {
+        Arc::clone(&self.counters)
+    }
+
+    /// Wait for the next summary
+    ///
+    /// Summaries nobody waits for are skipped once a few are buffered.
+    pub async fn next(&mut self) -> Option<PeriodSummary> {
+        self.summaries.recv().await
+    }
+
+    /// Stop sending summaries
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_schedule_next_after() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 1, 15, h, m, 0).unwrap();
+
+        assert_eq!(
+            SummarySchedule::Every(Duration::from_secs(90)).next_after(now),
+            now + ChronoDuration::seconds(90)
+        );
+        assert_eq!(
+            SummarySchedule::Hourly { minute: 45 }.next_after(now),
+            at(10, 45)
+        );
+        assert_eq!(
+            SummarySchedule::Hourly { minute: 30 }.next_after(now),
+            at(11, 30)
+        );
+        assert_eq!(
+            SummarySchedule::Daily {
+                hour: 21,
+                minute: 0
+            }
+            .next_after(now),
+            at(21, 0)
+        );
+        assert_eq!(
+            SummarySchedule::Daily { hour: 8, minute: 0 }.next_after(now),
+            Utc.with_ymd_and_hms(2024, 1, 16, 8, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_counters_reset_per_period() {
+        let counters = SummaryCounters::default();
+        counters.alert();
+        counters.alert();
+        assert_eq!(counters.take(), (2, 0, None));
+
+        counters.trade(1_000.0, 25.0);
+        counters.trade(500.0, -10.0);
+        assert_eq!(counters.take(), (0, 2, Some((1_500.0, 15.0))));
+        assert_eq!(counters.take(), (0, 0, None));
+    }
+
+    #[cfg(all(feature = "testing", feature = "ticker"))]
+    #[tokio::test]
+    async fn test_scheduler_emits_summaries() {
+        use crate::error::Result;
+        use crate::testing::{fixtures, MockScript, MockServer};
+        use async_trait::async_trait;
+
+        #[derive(Default)]
+        struct RecordingNotifier {
+            sent: Mutex<Vec<String>>,
+        }
+
+        #[async_trait]
+        impl Notifier for RecordingNotifier {
+            async fn send_alert(&self, message: &str) -> Result<()> {
+                self.sent.lock().push(message.to_string());
+                Ok(())
+            }
+        }
+
+        let server = MockServer::start(
+            MockScript::new().message(fixtures::get("ticker_snapshot").unwrap().text),
+        )
+        .await
+        .unwrap();
+        let client = Arc::new(KrakyClient::connect_with_url(server.url()).await.unwrap());
+        let ticker = client.subscribe_ticker("BTC/USD").await.unwrap();
+        ticker.ready().await.unwrap();
+
+        let notifier = Arc::new(RecordingNotifier::default());
+        let mut summaries = SummaryScheduler::new(
+            Arc::clone(&client),
+            SummarySchedule::Every(Duration::from_millis(50)),
+        )
+        .pair("BTC/USD")
+        .notifier(Arc::clone(&notifier) as Arc<dyn Notifier>)
+        .spawn();
+        summaries.counters().alert();
+        summaries.counters().trade(100.0, 2.5);
+
+        let summary = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let summary = summaries.next().await.unwrap();
+                if summary.alerts > 0 && !summary.prices.is_empty() {
+                    return summary;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(summary.trades, 1);
+        assert_eq!(summary.pnl, Some(2.5));
+        assert_eq!(summary.prices[0].close, 42000.2);
+        assert!(client.connection_health().messages_received > 0);
+
+        summaries.stop();
+        assert!(notifier.sent.lock()[0].contains("Summary"));
+    }
+}
@@ -0,0 +1,238 @@
+//! Trading pair symbols
+//!
+//! Kraken's WebSocket API names pairs `BASE/QUOTE` in upper case, with
+//! `BTC` rather than the legacy `XBT`. [`Symbol`] normalizes the other
+//! spellings people use (`btc/usd`, `XBT/USD`, `BTC-USD`, `BTCUSD`) to that
+//! form, so the same pair always maps to the same orderbook and
+//! subscription. Every client method taking a pair accepts a `&str` or a
+//! `Symbol`.
+//!
+//! # Example
+//!
+//! ```
+//! use kraky::Symbol;
+//!
+//! let symbol = Symbol::from("xbt-usd");
+//! assert_eq!(symbol, "BTC/USD");
+//! assert_eq!(symbol.base(), "BTC");
+//! assert_eq!(symbol.quote(), "USD");
+//!
+//! // Strict parsing rejects strings that aren't a pair
+//! assert!("BTC".parse::<Symbol>().is_err());
+//! ```
+
+use crate::error::KrakyError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Legacy asset codes and their WebSocket v2 names
+const ALIASES: &[(&str, &str)] = &[("XBT", "BTC"), ("XDG", "DOGE")];
+
+/// Quote currencies recognized in pairs written without a separator,
+/// longest first so `USDT` wins over `USD`
+const QUOTES: &[&str] = &[
+    "USDT", "USDC", "PYUSD", "USD", "EUR", "GBP", "CAD", "JPY", "CHF", "AUD", "XBT", "BTC", "ETH",
+    "DAI",
+];
+
+/// Normalized trading pair, e.g. `BTC/USD`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", from = "String")]
+pub struct Symbol {
+    /// Normalized `BASE/QUOTE` name
+    name: String,
+    /// Byte offset of the `/`, if the pair has a quote
+    split: Option<usize>,
+}
+
+impl Symbol {
+    /// Create a symbol from a base and quote asset
+    pub fn new(base: &str, quote: &str) -> Self {
+        Self::from_parts(&normalize_asset(base), &normalize_asset(quote))
+    }
+
+    fn from_parts(base: &str, quote: &str) -> Self {
+        if quote.is_empty() {
+            return Self {
+                name: base.to_string(),
+                split: None,
+            };
+        }
+        Self {
+            name: format!("{}/{}", base, quote),
+            split: Some(base.len()),
+        }
+    }
+
+    /// Get the normalized name, e.g. `BTC/USD`
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the base asset, e.g. `BTC`
+    pub fn base(&self) -> &str {
+        match self.split {
+            Some(split) => &self.name[..split],
+            None => &self.name,
+        }
+    }
+
+    /// Get the quote asset, e.g. `USD`
+    ///
+    /// Empty if the symbol could not be split into base and quote.
+    pub fn quote(&self) -> &str {
+        match self.split {
+            Some(split) => &self.name[split + 1..],
+            None => "",
+        }
+    }
+
+    /// Split and normalize a pair, returning `None` if it has no quote
+    fn parse_parts(s: &str) -> Option<(String, String)> {
+        let s = s.trim().to_ascii_uppercase();
+        if let Some((base, quote)) = s.split_once(['/', '-', '_', ':']) {
+            let (base, quote) = (base.trim(), quote.trim());
+            if base.is_empty() || quote.is_empty() {
+                return None;
+            }
+            return Some((normalize_asset(base), normalize_asset(quote)));
+        }
+        QUOTES.iter().find_map(|quote| {
+            let base = s.strip_suffix(quote)?;
+            (!base.is_empty()).then(|| (normalize_asset(base), normalize_asset(quote)))
+        })
+    }
+}
+
+fn normalize_asset(asset: &str) -> String {
+    let asset = asset.trim().to_ascii_uppercase();
+    ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == asset)
+        .map_or(asset, |(_, name)| name.to_string())
+}
+
+impl FromStr for Symbol {
+    type Err = KrakyError;
+
+    /// Parse a pair strictly, failing with [`KrakyError::InvalidPair`] if
+    /// it can't be split into base and quote
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_parts(s)
+            .map(|(base, quote)| Self::from_parts(&base, &quote))
+            .ok_or_else(|| KrakyError::InvalidPair(s.to_string()))
+    }
+}
+
+impl From<&str> for Symbol {
+    /// Normalize a pair, keeping it whole (upper-cased) if it can't be split
+    fn from(s: &str) -> Self {
+        s.parse()
+            .unwrap_or_else(|_| Self::from_parts(&normalize_asset(s), ""))
+    }
+}
+
+// Lets `for pair in &["BTC/USD", "ETH/USD"]` pass `pair` directly
+impl From<&&str> for Symbol {
+    fn from(s: &&str) -> Self {
+        Self::from(*s)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(s: &String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<&Symbol> for Symbol {
+    fn from(symbol: &Symbol) -> Self {
+        symbol.clone()
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.name
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.name == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.name == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization() {
+        for input in [
+            "BTC/USD",
+            "btc/usd",
+            "XBT/USD",
+            " xbt-usd ",
+            "BTC_USD",
+            "XBTUSD",
+        ] {
+            assert_eq!(Symbol::from(input), "BTC/USD", "{}", input);
+        }
+        assert_eq!(Symbol::from("ethusdt"), "ETH/USDT");
+        assert_eq!(Symbol::from("XDG/EUR").base(), "DOGE");
+        assert_eq!(Symbol::new("xbt", "eur"), Symbol::from("BTC/EUR"));
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let symbol: Symbol = "eth/btc".parse().unwrap();
+        assert_eq!((symbol.base(), symbol.quote()), ("ETH", "BTC"));
+
+        assert!(matches!(
+            "BTC".parse::<Symbol>(),
+            Err(KrakyError::InvalidPair(_))
+        ));
+        assert!("/USD".parse::<Symbol>().is_err());
+
+        // The lenient conversion keeps unsplittable names whole
+        let whole = Symbol::from("foo");
+        assert_eq!(
+            (whole.as_str(), whole.base(), whole.quote()),
+            ("FOO", "FOO", "")
+        );
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let json = serde_json::to_string(&Symbol::from("xbt/usd")).unwrap();
+        assert_eq!(json, "\"BTC/USD\"");
+        let symbol: Symbol = serde_json::from_str("\"eth-eur\"").unwrap();
+        assert_eq!(symbol, "ETH/EUR");
+    }
+}
@@ -1,19 +1,25 @@
 //! Telegram notification integration for Kraky SDK
 //!
 //! This module provides real-time market alerts via Telegram, leveraging
-//! Kraky's advanced orderbook analytics.
+//! Kraky's advanced orderbook analytics. [`TelegramNotifier`] implements the
+//! [`Notifier`] trait, which provides all typed alert methods.
 //!
 //! ## Features
 //! - Price alerts (above/below thresholds)
 //! - Orderbook imbalance signals (bullish/bearish/neutral)
 //! - Customizable alert formatting
+//! - Interactive commands (`/price`, `/book`, `/imbalance`, `/orders`, `/cancel`)
+//!   via [`TelegramCommandBot`]
+//! - Multiple chats with per-alert-type routing ([`TelegramRoutes`])
+//! - Order confirmations with "Cancel order" / "Amend +1%" buttons
+//!   (`trading` feature)
 //! - Async/await compatible
 //!
 //! ## Quick Start
 //!
 //! ```no_run
 //! use kraky::telegram::TelegramNotifier;
-//! use kraky::{KrakyClient, ImbalanceSignal};
+//! use kraky::{KrakyClient, ImbalanceSignal, Notifier};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -36,19 +42,319 @@
 //! }
 //! ```
 
+#[cfg(feature = "charts")]
+use crate::charts::{render_candlestick_png, ChartConfig};
 use crate::error::{KrakyError, Result};
+use crate::notifier::Notifier;
+use crate::templates::AlertTemplates;
+use async_trait::async_trait;
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use teloxide::RequestError;
+
+#[cfg(feature = "private")]
+use parking_lot::RwLock;
+#[cfg(feature = "orderbook")]
+use std::sync::Arc;
+
+/// Default maximum messages per second (Telegram's global bot limit)
+pub const DEFAULT_TELEGRAM_PER_SECOND: usize = 30;
+
+/// Default maximum messages per minute (Telegram's per-group limit)
+pub const DEFAULT_TELEGRAM_PER_MINUTE: usize = 20;
+
+/// Default number of retries after a failed send
+pub const DEFAULT_TELEGRAM_RETRIES: u32 = 3;
+
+/// Sliding-window rate limiter over recent send times
+#[derive(Debug)]
+struct RateLimiter {
+    per_second: usize,
+    per_minute: usize,
+    /// Send times within the last minute, oldest first
+    sent: VecDeque<Instant>,
+}
 
-#[cfg(feature = "analytics")]
-use crate::models::{ImbalanceMetrics, ImbalanceSignal};
+impl RateLimiter {
+    fn new(per_second: usize, per_minute: usize) -> Self {
+        Self {
+            per_second: per_second.max(1),
+            per_minute: per_minute.max(1),
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// How long to wait before the next send is allowed
+    fn delay(&self, now: Instant) -> Duration {
+        let wait = |window: Duration, limit: usize| {
+            let recent: Vec<&Instant> = self
+                .sent
+                .iter()
+                .filter(|t| now.duration_since(**t) < window)
+                .collect();
+            if recent.len() < limit {
+                return Duration::ZERO;
+            }
+            (*recent[recent.len() - limit] + window).saturating_duration_since(now)
+        };
+        wait(Duration::from_secs(1), self.per_second)
+            .max(wait(Duration::from_secs(60), self.per_minute))
+    }
+
+    fn record(&mut self, now: Instant) {
+        while self
+            .sent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= Duration::from_secs(60))
+        {
+            self.sent.pop_front();
+        }
+        self.sent.push_back(now);
+    }
+
+    /// Wait until a send is allowed, then record it
+    async fn acquire(&mut self) {
+        loop {
+            let delay = self.delay(Instant::now());
+            if delay.is_zero() {
+                break;
+            }
+            tokio::time::sleep(delay).await;
+        }
+        self.record(Instant::now());
+    }
+}
+
+/// Per-alert-type cooldowns
+#[derive(Debug, Default)]
+struct Cooldowns {
+    /// Cooldown per alert type
+    durations: HashMap<String, Duration>,
+    /// Cooldown for alert types without their own
+    default: Option<Duration>,
+    /// Last send time per (alert type, subject)
+    last_sent: HashMap<(String, String), Instant>,
+}
+
+impl Cooldowns {
+    /// Check whether an alert may be sent now, recording it if so
+    fn allow(&mut self, alert_type: &str, subject: &str, now: Instant) -> bool {
+        let Some(cooldown) = self.durations.get(alert_type).copied().or(self.default) else {
+            return true;
+        };
+        let key = (alert_type.to_string(), subject.to_string());
+        if let Some(last) = self.last_sent.get(&key) {
+            if now.duration_since(*last) < cooldown {
+                return false;
+            }
+        }
+        self.last_sent.insert(key, now);
+        true
+    }
+}
+
+/// Markup used to render Telegram messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TelegramFormat {
+    /// Plain text (no markup)
+    #[default]
+    Plain,
+    /// Telegram's [MarkdownV2](https://core.telegram.org/bots/api#markdownv2-style)
+    MarkdownV2,
+    /// Telegram's [HTML subset](https://core.telegram.org/bots/api#html-style)
+    Html,
+}
+
+impl TelegramFormat {
+    /// Escape text so it renders literally in this format
+    pub fn escape(&self, text: &str) -> String {
+        match self {
+            TelegramFormat::Plain => text.to_string(),
+            TelegramFormat::MarkdownV2 => escape_markdown_v2(text),
+            TelegramFormat::Html => escape_html(text),
+        }
+    }
+
+    /// Escape text and make it bold
+    pub fn bold(&self, text: &str) -> String {
+        match self {
+            TelegramFormat::Plain => text.to_string(),
+            TelegramFormat::MarkdownV2 => format!("*{}*", escape_markdown_v2(text)),
+            TelegramFormat::Html => format!("<b>{}</b>", escape_html(text)),
+        }
+    }
+
+    /// Escape text and format it as inline code
+    pub fn code(&self, text: &str) -> String {
+        match self {
+            TelegramFormat::Plain => text.to_string(),
+            TelegramFormat::MarkdownV2 => {
+                format!("`{}`", text.replace('\\', "\\\\").replace('`', "\\`"))
+            }
+            TelegramFormat::Html => format!("<code>{}</code>", escape_html(text)),
+        }
+    }
+
+    /// Escape text and format it as a preformatted code block
+    pub fn code_block(&self, text: &str) -> String {
+        match self {
+            TelegramFormat::Plain => text.to_string(),
+            TelegramFormat::MarkdownV2 => format!(
+                "```\n{}\n```",
+                text.replace('\\', "\\\\").replace('`', "\\`")
+            ),
+            TelegramFormat::Html => format!("<pre>{}</pre>", escape_html(text)),
+        }
+    }
+
+    fn parse_mode(&self) -> Option<ParseMode> {
+        match self {
+            TelegramFormat::Plain => None,
+            TelegramFormat::MarkdownV2 => Some(ParseMode::MarkdownV2),
+            TelegramFormat::Html => Some(ParseMode::Html),
+        }
+    }
+}
+
+/// Escape all characters reserved in Telegram MarkdownV2
+pub fn escape_markdown_v2(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '_' | '*'
+                | '['
+                | ']'
+                | '('
+                | ')'
+                | '~'
+                | '`'
+                | '>'
+                | '#'
+                | '+'
+                | '-'
+                | '='
+                | '|'
+                | '{'
+                | '}'
+                | '.'
+                | '!'
+                | '\\'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape `&`, `<` and `>` for Telegram HTML
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
 
 /// Telegram notification client for real-time market alerts
 ///
-/// Provides methods to send formatted alerts to a Telegram chat,
-/// including price updates and orderbook imbalance signals.
+/// Alert routing table for [`TelegramNotifier`]
+///
+/// Maps alert types (as passed to
+/// [`send_typed_alert`](Notifier::send_typed_alert), e.g. `"whale"` or
+/// `"execution"`) to the chats that receive them. Alert types without a route,
+/// and plain [`send_alert`](Notifier::send_alert) messages, go to the default
+/// chats. A route with no chats mutes that alert type.
+///
+/// Routes can be built in code or loaded from a config file:
+///
+/// ```
+/// use kraky::telegram::TelegramRoutes;
+///
+/// let routes: TelegramRoutes = serde_json::from_str(r#"{
+///     "default": [-1001234567890],
+///     "routes": { "whale": [-1009876543210], "execution": [123456789] }
+/// }"#).unwrap();
+///
+/// assert_eq!(routes.recipients(Some("whale")), &[-1009876543210]);
+/// assert_eq!(routes.recipients(Some("price")), &[-1001234567890]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelegramRoutes {
+    /// Chats receiving plain messages and alert types without a route
+    #[serde(default)]
+    pub default: Vec<i64>,
+    /// Chats per alert type
+    #[serde(default)]
+    pub routes: HashMap<String, Vec<i64>>,
+}
+
+impl TelegramRoutes {
+    /// Create an empty routing table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a default chat (builder style)
+    pub fn with_default(mut self, chat_id: i64) -> Self {
+        if !self.default.contains(&chat_id) {
+            self.default.push(chat_id);
+        }
+        self
+    }
+
+    /// Route an alert type to a chat (builder style)
+    ///
+    /// Can be called repeatedly to send one alert type to several chats.
+    pub fn with_route(mut self, alert_type: &str, chat_id: i64) -> Self {
+        let chats = self.routes.entry(alert_type.to_string()).or_default();
+        if !chats.contains(&chat_id) {
+            chats.push(chat_id);
+        }
+        self
+    }
+
+    /// Get the chats for an alert type (`None` for plain messages)
+    pub fn recipients(&self, alert_type: Option<&str>) -> &[i64] {
+        alert_type
+            .and_then(|t| self.routes.get(t))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Sends formatted alerts to a Telegram chat, including price updates and
+/// orderbook imbalance signals. See [`Notifier`] for the available alerts.
+///
+/// Telegram throttles bots (about 30 messages per second overall and 20 per
+/// minute per group), so messages are queued and sent in order within those
+/// limits. Sends rejected with "retry after" or failing on network errors are
+/// retried with backoff. Cooldowns can suppress repeats of the same alert
+/// type for the same subject (e.g. one whale alert per symbol per minute).
+///
+/// Messages can use MarkdownV2 or HTML markup
+/// ([`with_format`](Self::with_format)), be delivered silently (always or
+/// during quiet hours), and be posted to a forum topic.
+///
+/// Alerts can be broadcast to several chats and routed by type, e.g. whale
+/// alerts to a public channel and executions to a private chat (see
+/// [`with_route`](Self::with_route) and [`TelegramRoutes`]).
 pub struct TelegramNotifier {
     bot: Bot,
     chat_id: ChatId,
+    routes: TelegramRoutes,
+    /// Send queue (the async mutex is fair, so sends go out in order)
+    limiter: tokio::sync::Mutex<RateLimiter>,
+    cooldowns: parking_lot::Mutex<Cooldowns>,
+    max_retries: u32,
+    templates: Option<AlertTemplates>,
+    format: TelegramFormat,
+    silent: bool,
+    /// Quiet hours as (start, end) UTC hours; messages are silent in between
+    quiet_hours: Option<(u32, u32)>,
+    thread_id: Option<i32>,
 }
 
 impl TelegramNotifier {
@@ -68,1078 +374,1131 @@ impl TelegramNotifier {
         Self {
             bot: Bot::new(token),
             chat_id: ChatId(chat_id),
+            routes: TelegramRoutes::new().with_default(chat_id),
+            limiter: tokio::sync::Mutex::new(RateLimiter::new(
+                DEFAULT_TELEGRAM_PER_SECOND,
+                DEFAULT_TELEGRAM_PER_MINUTE,
+            )),
+            cooldowns: parking_lot::Mutex::new(Cooldowns::default()),
+            max_retries: DEFAULT_TELEGRAM_RETRIES,
+            templates: None,
+            format: TelegramFormat::Plain,
+            silent: false,
+            quiet_hours: None,
+            thread_id: None,
         }
     }
 
-    /// Send a basic text alert
-    ///
-    /// # Arguments
-    /// * `message` - The message to send
+    /// Set the maximum messages per second and per minute
     ///
-    /// # Example
-    /// ```no_run
-    /// # use kraky::telegram::TelegramNotifier;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// bot.send_alert("BTC/USD reached $100,000!").await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub async fn send_alert(&self, message: &str) -> Result<()> {
-        self.bot
-            .send_message(self.chat_id, message)
-            .await
-            .map_err(|e| KrakyError::InvalidMessage(format!("Telegram error: {}", e)))?;
-        Ok(())
+    /// The defaults suit group chats; private chats can allow more per minute.
+    pub fn with_rate_limit(self, per_second: usize, per_minute: usize) -> Self {
+        Self {
+            limiter: tokio::sync::Mutex::new(RateLimiter::new(per_second, per_minute)),
+            ..self
+        }
     }
 
-    /// Send a price alert with formatting
+    /// Suppress repeats of an alert type for the same subject within `cooldown`
     ///
-    /// # Arguments
-    /// * `symbol` - Trading pair (e.g., "BTC/USD")
-    /// * `price` - Current price
-    /// * `context` - Additional context (e.g., "above threshold")
+    /// Alert types are those passed to
+    /// [`send_typed_alert`](Notifier::send_typed_alert), e.g. `"whale"`,
+    /// `"imbalance"` or `"price"`.
     ///
     /// # Example
     /// ```no_run
-    /// # use kraky::telegram::TelegramNotifier;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// bot.send_price_alert("BTC/USD", 100000.0, "Target reached!").await?;
-    /// # Ok(())
-    /// # }
+    /// use kraky::telegram::TelegramNotifier;
+    /// use std::time::Duration;
+    ///
+    /// let bot = TelegramNotifier::new("123456:ABC-DEF", 987654321)
+    ///     .with_cooldown("whale", Duration::from_secs(60))
+    ///     .with_cooldown("imbalance", Duration::from_secs(300));
     /// ```
-    pub async fn send_price_alert(&self, symbol: &str, price: f64, context: &str) -> Result<()> {
-        let message = format!(
-            "💰 {} Price Alert\n\
-            Price: ${:.2}\n\
-            {}",
-            symbol, price, context
-        );
-        self.send_alert(&message).await
+    pub fn with_cooldown(self, alert_type: &str, cooldown: Duration) -> Self {
+        self.cooldowns
+            .lock()
+            .durations
+            .insert(alert_type.to_string(), cooldown);
+        self
     }
 
-    /// Send an orderbook imbalance alert (requires 'analytics' feature)
-    ///
-    /// This showcases Kraky's unique orderbook analytics capabilities by
-    /// sending detailed imbalance metrics and trading signals.
-    ///
-    /// # Arguments
-    /// * `symbol` - Trading pair (e.g., "BTC/USD")
-    /// * `metrics` - Imbalance metrics from orderbook
-    /// * `signal` - Trading signal (Bullish/Bearish/Neutral)
-    ///
-    /// # Example
-    /// ```no_run
-    /// # use kraky::telegram::TelegramNotifier;
-    /// # use kraky::{KrakyClient, ImbalanceSignal};
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// let client = KrakyClient::connect().await?;
-    ///
-    /// if let Some(ob) = client.get_orderbook("BTC/USD") {
-    ///     let metrics = ob.imbalance_metrics();
-    ///     let signal = metrics.signal(0.15);
-    ///     bot.send_imbalance_alert("BTC/USD", &metrics, signal).await?;
-    /// }
-    /// # Ok(())
-    /// # }
-    /// ```
-    #[cfg(feature = "analytics")]
-    pub async fn send_imbalance_alert(
-        &self,
-        symbol: &str,
-        metrics: &ImbalanceMetrics,
-        signal: ImbalanceSignal,
-    ) -> Result<()> {
-        let (emoji, signal_name, description) = match signal {
-            ImbalanceSignal::Bullish => (
-                "🟢",
-                "BULLISH",
-                "Strong buy pressure detected - more bids than asks",
-            ),
-            ImbalanceSignal::Bearish => (
-                "🔴",
-                "BEARISH",
-                "Strong sell pressure detected - more asks than bids",
-            ),
-            ImbalanceSignal::Neutral => (
-                "⚪",
-                "NEUTRAL",
-                "Balanced orderbook - no clear directional bias",
-            ),
-        };
+    /// Cooldown for alert types without their own
+    pub fn with_default_cooldown(self, cooldown: Duration) -> Self {
+        self.cooldowns.lock().default = Some(cooldown);
+        self
+    }
 
-        let message = format!(
-            "{} {} Orderbook Imbalance Alert\n\
-            \n\
-            📊 Signal: {}\n\
-            {}\n\
-            \n\
-            📈 Metrics:\n\
-            • Bid Volume: {:.4} BTC\n\
-            • Ask Volume: {:.4} BTC\n\
-            • Bid/Ask Ratio: {:.2}\n\
-            • Imbalance: {:+.2}%\n\
-            \n\
-            💡 Interpretation:\n\
-            {}",
-            emoji,
-            symbol,
-            signal_name,
-            "─".repeat(30),
-            metrics.bid_volume,
-            metrics.ask_volume,
-            metrics.bid_ask_ratio,
-            metrics.imbalance_ratio * 100.0,
-            description
-        );
+    /// Set the number of retries after a failed send (0 disables retries)
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
 
-        self.send_alert(&message).await
+    /// Replace built-in alert texts with templates
+    ///
+    /// See [`templates`](crate::templates) for alert types and variables.
+    pub fn with_templates(mut self, templates: AlertTemplates) -> Self {
+        self.templates = Some(templates);
+        self
     }
 
-    /// Send a threshold-based price alert
+    /// Send messages with MarkdownV2 or HTML markup
     ///
-    /// # Arguments
-    /// * `symbol` - Trading pair
-    /// * `price` - Current price
-    /// * `threshold` - Threshold price
-    /// * `above` - True if price is above threshold, false if below
+    /// Built-in alert texts and template variables are escaped automatically;
+    /// text passed to [`send_alert`](Notifier::send_alert) and template text
+    /// are sent as written, so they can contain markup (use
+    /// [`format`](Self::format) to escape and style values).
     ///
     /// # Example
     /// ```no_run
-    /// # use kraky::telegram::TelegramNotifier;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// bot.send_threshold_alert("BTC/USD", 100500.0, 100000.0, true).await?;
+    /// use kraky::telegram::{TelegramFormat, TelegramNotifier};
+    /// use kraky::{AlertTemplates, Notifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bot = TelegramNotifier::new("123456:ABC-DEF", 987654321)
+    ///     .with_format(TelegramFormat::MarkdownV2)
+    ///     .with_templates(AlertTemplates::new().with("price", "*{symbol}* at `{price}`"));
+    ///
+    /// let fmt = bot.format();
+    /// bot.send_alert(&format!("Price: {}", fmt.bold("$100,000.00"))).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn send_threshold_alert(
-        &self,
-        symbol: &str,
-        price: f64,
-        threshold: f64,
-        above: bool,
-    ) -> Result<()> {
-        let (emoji, direction) = if above {
-            ("📈", "above")
-        } else {
-            ("📉", "below")
-        };
-
-        let change_pct = ((price - threshold) / threshold * 100.0).abs();
+    pub fn with_format(mut self, format: TelegramFormat) -> Self {
+        self.format = format;
+        self
+    }
 
-        let message = format!(
-            "{} {} Threshold Alert\n\
-            \n\
-            Current Price: ${:.2}\n\
-            Threshold: ${:.2}\n\
-            Status: Price is {} threshold\n\
-            Change: {:.2}%",
-            emoji, symbol, price, threshold, direction, change_pct
-        );
+    /// Get the message format
+    pub fn format(&self) -> TelegramFormat {
+        self.format
+    }
 
-        self.send_alert(&message).await
+    /// Deliver all messages without sound
+    pub fn with_silent(mut self, silent: bool) -> Self {
+        self.silent = silent;
+        self
     }
 
-    /// Send a formatted orderbook snapshot summary
-    ///
-    /// # Arguments
-    /// * `symbol` - Trading pair
-    /// * `best_bid` - Best bid price
-    /// * `best_ask` - Best ask price
-    /// * `spread` - Bid-ask spread
-    /// * `mid_price` - Mid price
+    /// Deliver messages without sound between `start` and `end` (UTC hours, 0-23)
     ///
-    /// # Example
-    /// ```no_run
-    /// # use kraky::telegram::TelegramNotifier;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// bot.send_orderbook_summary("BTC/USD", 99500.0, 99505.0, 5.0, 99502.5).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub async fn send_orderbook_summary(
-        &self,
-        symbol: &str,
-        best_bid: f64,
-        best_ask: f64,
-        spread: f64,
-        mid_price: f64,
-    ) -> Result<()> {
-        let spread_bps = (spread / mid_price) * 10000.0;
+    /// The range may wrap around midnight, e.g. `(22, 7)`.
+    pub fn with_quiet_hours(mut self, start: u32, end: u32) -> Self {
+        self.quiet_hours = Some((start % 24, end % 24));
+        self
+    }
 
-        let message = format!(
-            "📖 {} Orderbook Update\n\
-            \n\
-            Best Bid: ${:.2}\n\
-            Best Ask: ${:.2}\n\
-            Mid Price: ${:.2}\n\
-            Spread: ${:.2} ({:.1} bps)",
-            symbol, best_bid, best_ask, mid_price, spread, spread_bps
-        );
+    /// Post to a forum topic (message thread) in the chat
+    ///
+    /// Only applies to the chat passed to [`new`](Self::new).
+    pub fn with_thread_id(mut self, thread_id: i32) -> Self {
+        self.thread_id = Some(thread_id);
+        self
+    }
 
-        self.send_alert(&message).await
+    /// Also send plain messages and unrouted alerts to another chat
+    pub fn with_chat(mut self, chat_id: i64) -> Self {
+        self.routes = self.routes.with_default(chat_id);
+        self
     }
 
-    /// Send a connection status update
+    /// Send an alert type to a chat instead of the default chats
     ///
-    /// # Arguments
-    /// * `connected` - Whether the client is connected
-    /// * `details` - Additional details about the connection
+    /// Can be called repeatedly to send one alert type to several chats.
     ///
     /// # Example
     /// ```no_run
-    /// # use kraky::telegram::TelegramNotifier;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// bot.send_connection_status(true, "Connected to Kraken WebSocket").await?;
-    /// # Ok(())
-    /// # }
+    /// use kraky::telegram::TelegramNotifier;
+    ///
+    /// // Whale alerts to a public channel, executions to a private chat,
+    /// // everything else to the group passed to `new`
+    /// let bot = TelegramNotifier::new("123456:ABC-DEF", -1001234567890)
+    ///     .with_route("whale", -1009876543210)
+    ///     .with_route("execution", 123456789);
     /// ```
-    pub async fn send_connection_status(&self, connected: bool, details: &str) -> Result<()> {
-        let (emoji, status) = if connected {
-            ("✅", "Connected")
-        } else {
-            ("❌", "Disconnected")
-        };
+    pub fn with_route(mut self, alert_type: &str, chat_id: i64) -> Self {
+        self.routes = self.routes.with_route(alert_type, chat_id);
+        self
+    }
 
-        let message = format!(
-            "{} Connection Status: {}\n\
-            {}",
-            emoji, status, details
-        );
+    /// Replace the routing table (including the default chats)
+    pub fn with_routes(mut self, routes: TelegramRoutes) -> Self {
+        self.routes = routes;
+        self
+    }
 
-        self.send_alert(&message).await
+    /// Get the routing table
+    pub fn routes(&self) -> &TelegramRoutes {
+        &self.routes
     }
 
-    /// Send a whale alert for large orders
+    /// Send a candlestick chart image with an optional caption
     ///
-    /// Detects and reports significant order placements in the orderbook,
-    /// helping traders identify when large players ("whales") are active.
+    /// Renders `candles` (oldest first) with [`ChartConfig::default`] and
+    /// sends it as a photo. Respects the notifier's format, silent and topic
+    /// settings; the caption is sent as written.
     ///
-    /// # Arguments
-    /// * `symbol` - Trading pair (e.g., "BTC/USD")
-    /// * `side` - Order side ("bid" or "ask")
-    /// * `price` - Price level of the large order
-    /// * `volume` - Size of the order
+    /// Only available when the `charts` feature is enabled.
     ///
     /// # Example
     /// ```no_run
     /// # use kraky::telegram::TelegramNotifier;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// bot.send_whale_alert("BTC/USD", "bid", 95000.0, 50.0).await?;
+    /// # async fn example(bot: &TelegramNotifier, candles: Vec<kraky::OHLC>) -> Result<(), Box<dyn std::error::Error>> {
+    /// bot.send_chart("BTC/USD", &candles).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn send_whale_alert(
-        &self,
-        symbol: &str,
-        side: &str,
-        price: f64,
-        volume: f64,
-    ) -> Result<()> {
-        let (emoji, direction) = if side.to_lowercase() == "bid" {
-            ("🟢", "BUY")
-        } else {
-            ("🔴", "SELL")
-        };
-
-        let message = format!(
-            "🐋 {} Whale Alert!\n\
-            \n\
-            {} Large {} Order Detected\n\
-            {}\n\
-            \n\
-            Price: ${:.2}\n\
-            Volume: {:.4} {}\n\
-            Total Value: ${:.2}\n\
-            \n\
-            💡 A large {} order has appeared in the orderbook.\n\
-            This could indicate institutional activity.",
-            symbol,
-            emoji,
-            direction,
-            "─".repeat(30),
-            price,
-            volume,
-            symbol.split('/').next().unwrap_or(""),
-            price * volume,
-            side.to_lowercase()
-        );
-
-        self.send_alert(&message).await
+    #[cfg(feature = "charts")]
+    pub async fn send_chart(&self, symbol: &str, candles: &[crate::models::OHLC]) -> Result<()> {
+        self.send_chart_with_config(symbol, candles, &ChartConfig::default(), "")
+            .await
     }
 
-    /// Send a spread volatility alert
+    /// Send a candlestick chart rendered with a custom config and caption
     ///
-    /// Alerts when the bid-ask spread widens significantly beyond normal levels,
-    /// which often indicates decreasing liquidity or upcoming volatility.
-    ///
-    /// # Arguments
-    /// * `symbol` - Trading pair
-    /// * `current_spread_bps` - Current spread in basis points
-    /// * `normal_spread_bps` - Normal/average spread in basis points
-    /// * `multiplier` - How many times wider than normal (e.g., 3.5x)
-    ///
-    /// # Example
-    /// ```no_run
-    /// # use kraky::telegram::TelegramNotifier;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// bot.send_spread_alert("BTC/USD", 15.0, 5.0, 3.0).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub async fn send_spread_alert(
+    /// Only available when the `charts` feature is enabled.
+    #[cfg(feature = "charts")]
+    pub async fn send_chart_with_config(
         &self,
         symbol: &str,
-        current_spread_bps: f64,
-        normal_spread_bps: f64,
-        multiplier: f64,
+        candles: &[crate::models::OHLC],
+        config: &ChartConfig,
+        caption: &str,
     ) -> Result<()> {
-        let severity = if multiplier >= 5.0 {
-            ("🚨", "CRITICAL")
-        } else if multiplier >= 3.0 {
-            ("⚠️", "HIGH")
-        } else {
-            ("⚡", "MODERATE")
-        };
-
-        let message = format!(
-            "{} {} Spread Volatility Alert\n\
-            \n\
-            Severity: {}\n\
-            {}\n\
-            \n\
-            Current Spread: {:.1} bps\n\
-            Normal Spread: {:.1} bps\n\
-            Multiplier: {:.1}x normal\n\
-            \n\
-            💡 Interpretation:\n\
-            The bid-ask spread has widened significantly, indicating\n\
-            reduced liquidity. This often precedes increased volatility\n\
-            or large price movements.",
-            severity.0,
-            symbol,
-            severity.1,
-            "─".repeat(30),
-            current_spread_bps,
-            normal_spread_bps,
-            multiplier
-        );
-
-        self.send_alert(&message).await
+        let png = render_candlestick_png(symbol, candles, config)?;
+        self.send_photo(png, caption).await
     }
 
-    /// Send an order flow divergence alert
-    ///
-    /// Detects when price action diverges from orderbook pressure, which can
-    /// signal potential reversals or unusual market dynamics.
+    /// Send a PNG image with an optional caption
     ///
-    /// # Arguments
-    /// * `symbol` - Trading pair
-    /// * `price_change` - Recent price change percentage
-    /// * `orderbook_signal` - Current orderbook imbalance signal
+    /// Images are routed as the `"chart"` alert type.
     ///
-    /// # Example
-    /// ```no_run
-    /// # use kraky::telegram::TelegramNotifier;
-    /// # use kraky::ImbalanceSignal;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// bot.send_divergence_alert("BTC/USD", 2.5, ImbalanceSignal::Bearish).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    #[cfg(feature = "analytics")]
-    pub async fn send_divergence_alert(
+    /// Only available when the `charts` feature is enabled.
+    #[cfg(feature = "charts")]
+    pub async fn send_photo(&self, png: Vec<u8>, caption: &str) -> Result<()> {
+        let photo = teloxide::types::InputFile::memory(png).file_name("chart.png");
+        let mut result = Ok(());
+        for &chat_id in self.routes.recipients(Some("chart")) {
+            let sent = self
+                .send_photo_to(ChatId(chat_id), photo.clone(), caption)
+                .await;
+            result = result.and(sent);
+        }
+        result
+    }
+
+    #[cfg(feature = "charts")]
+    async fn send_photo_to(
         &self,
-        symbol: &str,
-        price_change: f64,
-        orderbook_signal: ImbalanceSignal,
+        chat_id: ChatId,
+        photo: teloxide::types::InputFile,
+        caption: &str,
     ) -> Result<()> {
-        let price_direction = if price_change > 0.0 { "UP" } else { "DOWN" };
-        let price_emoji = if price_change > 0.0 { "📈" } else { "📉" };
-
-        let (ob_emoji, ob_signal) = match orderbook_signal {
-            ImbalanceSignal::Bullish => ("🟢", "BULLISH"),
-            ImbalanceSignal::Bearish => ("🔴", "BEARISH"),
-            ImbalanceSignal::Neutral => ("⚪", "NEUTRAL"),
-        };
+        self.limiter.lock().await.acquire().await;
 
-        // Determine if this is a divergence
-        let is_divergence = (price_change > 0.0
-            && matches!(orderbook_signal, ImbalanceSignal::Bearish))
-            || (price_change < 0.0 && matches!(orderbook_signal, ImbalanceSignal::Bullish));
-
-        if !is_divergence {
-            return Ok(()); // Only send alerts on actual divergence
+        let mut request = self.bot.send_photo(chat_id, photo);
+        if !caption.is_empty() {
+            request = request.caption(caption);
+            if let Some(mode) = self.format.parse_mode() {
+                request = request.parse_mode(mode);
+            }
+        }
+        if self.is_silent(chrono::Utc::now().hour()) {
+            request = request.disable_notification(true);
+        }
+        if let Some(thread_id) = self.thread_id(chat_id) {
+            request = request.message_thread_id(thread_id);
         }
 
-        let message = format!(
-            "⚡ {} Order Flow DIVERGENCE Alert\n\
-            \n\
-            🎯 Divergence Detected!\n\
-            {}\n\
-            \n\
-            {} Price Action: {} ({:+.2}%)\n\
-            {} Orderbook: {}\n\
-            \n\
-            💡 Interpretation:\n\
-            Price is moving {} but orderbook shows {} pressure.\n\
-            This divergence could indicate:\n\
-            • Potential trend reversal\n\
-            • Large hidden orders executing\n\
-            • Market maker positioning\n\
-            \n\
-            ⚠️ Exercise caution - divergences often precede volatility.",
-            symbol,
-            "─".repeat(30),
-            price_emoji,
-            price_direction,
-            price_change,
-            ob_emoji,
-            ob_signal,
-            price_direction,
-            ob_signal
-        );
+        request
+            .await
+            .map_err(|e| KrakyError::InvalidMessage(format!("Telegram error: {}", e)))?;
+        Ok(())
+    }
+
+    /// Forum topic for a chat (only set for the chat passed to `new`)
+    fn thread_id(&self, chat_id: ChatId) -> Option<i32> {
+        self.thread_id.filter(|_| chat_id == self.chat_id)
+    }
 
-        self.send_alert(&message).await
+    /// Check whether a message sent at `hour` (UTC) should be silent
+    fn is_silent(&self, hour: u32) -> bool {
+        if self.silent {
+            return true;
+        }
+        match self.quiet_hours {
+            Some((start, end)) if start <= end => hour >= start && hour < end,
+            Some((start, end)) => hour >= start || hour < end,
+            None => false,
+        }
     }
 
-    /// Send a trade execution alert
+    /// Send a message with inline buttons to cancel or amend an order
     ///
-    /// Reports when significant trades execute, helping track market activity
-    /// and large player movements.
+    /// Offers "Cancel order" and, for limit orders, "Amend +1%" (moves the
+    /// limit price 1% up). Button presses are handled by a
+    /// [`TelegramCommandBot`] running with the same bot token, allowed chat and
+    /// credentials. Routed as the `"order_placed"` alert type.
     ///
-    /// # Arguments
-    /// * `symbol` - Trading pair
-    /// * `side` - Trade side ("buy" or "sell")
-    /// * `price` - Execution price
-    /// * `volume` - Trade volume
+    /// Only available when the `trading` feature is enabled.
     ///
     /// # Example
     /// ```no_run
     /// # use kraky::telegram::TelegramNotifier;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// bot.send_trade_alert("BTC/USD", "buy", 96500.0, 25.5).await?;
+    /// # async fn example(bot: &TelegramNotifier, response: kraky::OrderResponse, params: kraky::OrderParams) -> Result<(), Box<dyn std::error::Error>> {
+    /// bot.send_order_confirmation(&response, &params).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn send_trade_alert(
+    #[cfg(feature = "trading")]
+    pub async fn send_order_confirmation(
         &self,
-        symbol: &str,
-        side: &str,
-        price: f64,
-        volume: f64,
+        response: &crate::models::OrderResponse,
+        params: &crate::models::OrderParams,
     ) -> Result<()> {
-        let (emoji, direction) = if side.to_lowercase() == "buy" {
-            ("🟢", "BUY")
-        } else {
-            ("🔴", "SELL")
-        };
-
-        let total_value = price * volume;
-
         let message = format!(
-            "💥 {} Large Trade Executed\n\
-            \n\
-            {} {} Order Filled\n\
-            {}\n\
-            \n\
-            Price: ${:.2}\n\
-            Volume: {:.4} {}\n\
-            Total Value: ${:.2}\n\
+            "✅ Order Placed\n\
             \n\
-            💡 A significant {} trade just executed.\n\
-            This represents real market activity.",
-            symbol,
-            emoji,
-            direction,
-            "─".repeat(30),
-            price,
-            volume,
-            symbol.split('/').next().unwrap_or(""),
-            total_value,
-            side.to_lowercase()
+            {} {:?} {} {}\n\
+            Order ID: {}\n\
+            Status: {:?}",
+            params.symbol,
+            params.side,
+            params
+                .order_qty
+                .map_or("N/A".to_string(), |q| format!("{:.6}", q)),
+            params
+                .limit_price
+                .map_or("@ market".to_string(), |p| format!("@ ${}", p)),
+            response.order_id,
+            response.order_status,
         );
-
-        self.send_alert(&message).await
+        let keyboard = order_keyboard(&response.order_id, params.limit_price);
+        self.broadcast(Some("order_placed"), &self.escape(&message), Some(keyboard))
+            .await
     }
 
-    // ═══════════════════════════════════════════════════════════════════════
-    // PRIVATE WEBSOCKET NOTIFICATIONS (requires 'private' feature)
-    // ═══════════════════════════════════════════════════════════════════════
-
-    /// Send a balance update notification
-    ///
-    /// Alerts when your account balance changes.
-    /// Requires both `telegram` and `private` features.
+    /// Send a message with a custom inline keyboard to the default chats
     ///
-    /// # Arguments
-    /// * `balance_update` - Balance update data from private WebSocket
+    /// Only available when the `trading` feature is enabled.
+    #[cfg(feature = "trading")]
+    pub async fn send_with_keyboard(
+        &self,
+        message: &str,
+        keyboard: teloxide::types::InlineKeyboardMarkup,
+    ) -> Result<()> {
+        self.broadcast(None, message, Some(keyboard)).await
+    }
+
+    /// Send a message to every chat routed for `alert_type`
     ///
-    /// # Example
-    /// ```no_run
-    /// # use kraky::{TelegramNotifier, BalanceUpdate};
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// // Assuming you received a balance update from WebSocket
-    /// # let balance_update = serde_json::from_str::<BalanceUpdate>(r#"{"channel":"balances","type":"update","data":[{"BTC":"1.5","USD":"50000"}]}"#)?;
-    /// bot.send_balance_update(&balance_update).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    #[cfg(feature = "private")]
-    pub async fn send_balance_update(&self, update: &crate::models::BalanceUpdate) -> Result<()> {
-        if let Some(data) = update.data.first() {
-            let mut balance_lines = Vec::new();
-
-            for (asset, amount) in &data.balances {
-                balance_lines.push(format!("  {} {}", amount, asset));
-            }
-
-            let message = format!(
-                "💰 Balance Update\n\
-                \n\
-                {}\n\
-                {}\n\
-                \n\
-                🕐 {}\n\
-                \n\
-                Your account balances have been updated.",
-                "─".repeat(30),
-                balance_lines.join("\n"),
-                chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-            );
-
-            self.send_alert(&message).await
-        } else {
-            Ok(())
+    /// A failure for one chat doesn't stop delivery to the others; the first
+    /// error is returned.
+    async fn broadcast(
+        &self,
+        alert_type: Option<&str>,
+        message: &str,
+        keyboard: Option<teloxide::types::InlineKeyboardMarkup>,
+    ) -> Result<()> {
+        let mut result = Ok(());
+        for &chat_id in self.routes.recipients(alert_type) {
+            let sent = self
+                .send_message(ChatId(chat_id), message, keyboard.clone())
+                .await;
+            result = result.and(sent);
         }
+        result
     }
 
-    /// Send an order update notification
-    ///
-    /// Alerts when your order status changes (opened, filled, cancelled).
-    /// Requires both `telegram` and `private` features.
-    ///
-    /// # Arguments
-    /// * `order_update` - Order update data from private WebSocket
-    ///
-    /// # Example
-    /// ```no_run
-    /// # use kraky::{TelegramNotifier, OrderUpdate};
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// # let order_update = serde_json::from_str::<OrderUpdate>(r#"{"channel":"orders","type":"update","data":[{"order_id":"O123","symbol":"BTC/USD","side":"buy","order_type":"limit","limit_price":"95000","order_qty":"0.5","filled_qty":"0","status":"open","timestamp":"2024-01-01T00:00:00Z"}]}"#)?;
-    /// bot.send_order_update(&order_update).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    #[cfg(feature = "private")]
-    pub async fn send_order_update(&self, update: &crate::models::OrderUpdate) -> Result<()> {
-        if let Some(order) = update.data.first() {
-            let emoji = match order.status.as_str() {
-                "open" => "🟢",
-                "closed" => "✅",
-                "cancelled" => "❌",
-                "pending" => "⏳",
-                _ => "📋",
-            };
+    /// Send a message to one chat, queueing behind the rate limiter and
+    /// retrying transient failures
+    async fn send_message(
+        &self,
+        chat_id: ChatId,
+        message: &str,
+        keyboard: Option<teloxide::types::InlineKeyboardMarkup>,
+    ) -> Result<()> {
+        let mut limiter = self.limiter.lock().await;
+        let mut backoff = Duration::from_secs(1);
+        let mut attempt = 0;
+
+        loop {
+            limiter.acquire().await;
+            let mut request = self.bot.send_message(chat_id, message);
+            if let Some(mode) = self.format.parse_mode() {
+                request = request.parse_mode(mode);
+            }
+            if self.is_silent(chrono::Utc::now().hour()) {
+                request = request.disable_notification(true);
+            }
+            if let Some(thread_id) = self.thread_id(chat_id) {
+                request = request.message_thread_id(thread_id);
+            }
+            if let Some(keyboard) = &keyboard {
+                request = request.reply_markup(keyboard.clone());
+            }
 
-            let status_text = match order.status.as_str() {
-                "open" => "OPENED".to_string(),
-                "closed" => "FILLED".to_string(),
-                "cancelled" => "CANCELLED".to_string(),
-                "pending" => "PENDING".to_string(),
-                _ => order.status.to_uppercase(),
+            let error = match request.await {
+                Ok(_) => return Ok(()),
+                Err(e) => e,
             };
 
-            let side_emoji = if order.side.to_lowercase() == "buy" {
-                "🟢"
-            } else {
-                "🔴"
+            let wait = match &error {
+                RequestError::RetryAfter(wait) => *wait,
+                RequestError::Network(_) | RequestError::Io(_) => backoff,
+                _ => {
+                    return Err(KrakyError::InvalidMessage(format!(
+                        "Telegram error: {}",
+                        error
+                    )))
+                }
             };
-
-            let mut details = vec![
-                format!("{} {} Order", side_emoji, order.side.to_uppercase()),
-                format!("Order ID: {}", order.order_id),
-                format!("Type: {}", order.order_type),
-            ];
-
-            if let Some(limit_price) = &order.limit_price {
-                details.push(format!("Limit Price: ${}", limit_price));
+            if attempt >= self.max_retries {
+                return Err(match error {
+                    RequestError::RetryAfter(_) => KrakyError::RateLimited,
+                    e => KrakyError::InvalidMessage(format!("Telegram error: {}", e)),
+                });
             }
 
-            details.push(format!("Quantity: {}", order.order_qty));
+            tracing::warn!(
+                "Telegram send failed (attempt {}/{}): {}, retrying in {:?}",
+                attempt + 1,
+                self.max_retries + 1,
+                error,
+                wait
+            );
+            tokio::time::sleep(wait).await;
+            backoff *= 2;
+            attempt += 1;
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    async fn send_alert(&self, message: &str) -> Result<()> {
+        self.broadcast(None, message, None).await
+    }
 
-            if !order.filled_qty.is_empty() && order.filled_qty != "0" && order.filled_qty != "0.0"
-            {
-                details.push(format!("Filled: {}", order.filled_qty));
-            }
+    fn templates(&self) -> Option<&AlertTemplates> {
+        self.templates.as_ref()
+    }
 
-            let message = format!(
-                "{} {} Order {}\n\
-                \n\
-                📊 {}\n\
-                {}\n\
-                \n\
-                {}\n\
-                \n\
-                🕐 {}",
-                emoji,
-                order.symbol,
-                status_text,
-                "─".repeat(30),
-                details.join("\n"),
-                "─".repeat(30),
-                if order.timestamp.is_empty() {
-                    chrono::Utc::now()
-                        .format("%Y-%m-%d %H:%M:%S UTC")
-                        .to_string()
-                } else {
-                    order.timestamp.clone()
-                }
-            );
+    fn escape(&self, text: &str) -> String {
+        self.format.escape(text)
+    }
 
-            self.send_alert(&message).await
-        } else {
-            Ok(())
+    async fn send_typed_alert(&self, alert_type: &str, subject: &str, message: &str) -> Result<()> {
+        if !self
+            .cooldowns
+            .lock()
+            .allow(alert_type, subject, Instant::now())
+        {
+            tracing::debug!("Suppressed {} alert for {} (cooldown)", alert_type, subject);
+            return Ok(());
         }
+        self.broadcast(Some(alert_type), message, None).await
     }
+}
 
-    /// Send an execution (trade fill) alert
-    ///
-    /// Alerts when your order is executed (filled).
-    /// Requires both `telegram` and `private` features.
-    ///
-    /// # Arguments
-    /// * `execution_update` - Execution update data from private WebSocket
-    ///
-    /// # Example
-    /// ```no_run
-    /// # use kraky::{TelegramNotifier, ExecutionUpdate};
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// # let execution = serde_json::from_str::<ExecutionUpdate>(r#"{"channel":"executions","type":"update","data":[{"exec_id":"E123","order_id":"O123","symbol":"BTC/USD","side":"buy","exec_qty":"0.5","exec_price":"95000","timestamp":"2024-01-01T00:00:00Z","liquidity":"taker"}]}"#)?;
-    /// bot.send_execution_alert(&execution).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
+/// Number of levels per side shown by `/book`
+#[cfg(feature = "orderbook")]
+const BOOK_COMMAND_DEPTH: usize = 5;
+
+/// A command understood by [`TelegramCommandBot`]
+///
+/// Pair arguments are optional; without one the bot's default pair is used.
+#[cfg(feature = "orderbook")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BotCommand {
+    /// `/help` or `/start` - list available commands
+    Help,
+    /// `/price [PAIR]` - best bid, best ask and mid price
+    Price(Option<String>),
+    /// `/book [PAIR]` - top orderbook levels
+    Book(Option<String>),
+    /// `/imbalance [PAIR]` - orderbook imbalance metrics
+    #[cfg(feature = "analytics")]
+    Imbalance(Option<String>),
+    /// `/orders` - open orders seen on the private `orders` channel
     #[cfg(feature = "private")]
-    pub async fn send_execution_alert(
-        &self,
-        update: &crate::models::ExecutionUpdate,
-    ) -> Result<()> {
-        if let Some(exec) = update.data.first() {
-            let (side_emoji, side_text) = if exec.side.to_lowercase() == "buy" {
-                ("🟢", "BOUGHT")
-            } else {
-                ("🔴", "SOLD")
-            };
+    Orders,
+    /// `/cancel <ORDER_ID>` - cancel an order
+    #[cfg(feature = "trading")]
+    Cancel(String),
+}
 
-            let qty: f64 = exec.exec_qty.parse().unwrap_or(0.0);
-            let price: f64 = exec.exec_price.parse().unwrap_or(0.0);
-            let total_value = qty * price;
+#[cfg(feature = "orderbook")]
+impl BotCommand {
+    /// Parse a message like `/price BTC/USD` or `/book@MyBot eth/usd`
+    ///
+    /// Returns `None` for messages that aren't known commands.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split_whitespace();
+        let command = parts.next()?.strip_prefix('/')?;
+        // Commands in groups may be addressed as /command@BotName
+        let command = command.split('@').next()?.to_lowercase();
+        let pair = parts.next().map(|p| p.to_uppercase());
+
+        match command.as_str() {
+            "help" | "start" => Some(BotCommand::Help),
+            "price" => Some(BotCommand::Price(pair)),
+            "book" => Some(BotCommand::Book(pair)),
+            #[cfg(feature = "analytics")]
+            "imbalance" => Some(BotCommand::Imbalance(pair)),
+            #[cfg(feature = "private")]
+            "orders" => Some(BotCommand::Orders),
+            #[cfg(feature = "trading")]
+            "cancel" => Some(BotCommand::Cancel(
+                text.split_whitespace().nth(1)?.to_string(),
+            )),
+            _ => None,
+        }
+    }
 
-            let asset = exec.symbol.split('/').next().unwrap_or("BTC");
+    /// Help text listing the available commands
+    pub fn help() -> String {
+        let commands = [
+            "/price [PAIR] - Best bid/ask and mid price",
+            "/book [PAIR] - Top orderbook levels",
+            #[cfg(feature = "analytics")]
+            "/imbalance [PAIR] - Orderbook imbalance",
+            #[cfg(feature = "private")]
+            "/orders - Open orders",
+            #[cfg(feature = "trading")]
+            "/cancel <ORDER_ID> - Cancel an order",
+        ];
+        format!("🤖 Kraky Bot Commands\n\n{}", commands.join("\n"))
+    }
+}
 
-            let liquidity_emoji = if exec.liquidity.to_lowercase() == "maker" {
-                "🏭" // Maker (provided liquidity)
-            } else {
-                "⚡" // Taker (removed liquidity)
-            };
+/// Percentage step of the "Amend" button added by [`order_keyboard`]
+#[cfg(feature = "trading")]
+const AMEND_STEP_PCT: f64 = 1.0;
 
-            let message = format!(
-                "💥 {} Trade Executed!\n\
-                \n\
-                {} {} {} {}\n\
-                {}\n\
-                \n\
-                Execution ID: {}\n\
-                Order ID: {}\n\
-                \n\
-                Price: ${}\n\
-                Quantity: {} {}\n\
-                Total Value: ${:.2}\n\
-                \n\
-                {} Liquidity: {}\n\
-                \n\
-                🕐 {}",
-                exec.symbol,
-                side_emoji,
-                side_text,
-                exec.exec_qty,
-                asset,
-                "─".repeat(30),
-                exec.exec_id,
-                exec.order_id,
-                exec.exec_price,
-                exec.exec_qty,
-                asset,
-                total_value,
-                liquidity_emoji,
-                exec.liquidity.to_uppercase(),
-                if exec.timestamp.is_empty() {
-                    chrono::Utc::now()
-                        .format("%Y-%m-%d %H:%M:%S UTC")
-                        .to_string()
-                } else {
-                    exec.timestamp.clone()
-                }
-            );
+/// An order action triggered from an inline keyboard button
+///
+/// Actions are encoded in the button's callback data (at most 64 bytes), so
+/// the bot handling the press needs no state from the message that sent it.
+#[cfg(feature = "trading")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderAction {
+    /// Cancel an order
+    Cancel {
+        /// Order to cancel
+        order_id: String,
+    },
+    /// Amend an order's limit price
+    Amend {
+        /// Order to amend
+        order_id: String,
+        /// New limit price
+        limit_price: f64,
+    },
+}
 
-            self.send_alert(&message).await
-        } else {
-            Ok(())
+#[cfg(feature = "trading")]
+impl OrderAction {
+    /// Encode the action as button callback data
+    pub fn callback_data(&self) -> String {
+        match self {
+            OrderAction::Cancel { order_id } => format!("cancel:{}", order_id),
+            OrderAction::Amend {
+                order_id,
+                limit_price,
+            } => format!("amend:{}:{}", order_id, limit_price),
         }
     }
 
-    /// Send a combined portfolio summary
+    /// Decode button callback data
     ///
-    /// Sends a formatted summary of all balances.
-    /// Useful for periodic portfolio updates.
-    /// Requires both `telegram` and `private` features.
-    ///
-    /// # Arguments
-    /// * `balance_update` - Balance update data
-    ///
-    /// # Example
-    /// ```no_run
-    /// # use kraky::{TelegramNotifier, BalanceUpdate};
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let bot = TelegramNotifier::new("token", 123);
-    /// # let balance_update = serde_json::from_str::<BalanceUpdate>(r#"{"channel":"balances","type":"update","data":[{"BTC":"1.5","ETH":"10.0","USD":"50000"}]}"#)?;
-    /// bot.send_portfolio_summary(&balance_update).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    #[cfg(feature = "private")]
-    pub async fn send_portfolio_summary(
-        &self,
-        update: &crate::models::BalanceUpdate,
-    ) -> Result<()> {
-        if let Some(data) = update.data.first() {
-            let mut crypto_balances = Vec::new();
-            let mut fiat_balances = Vec::new();
-
-            for (asset, amount) in &data.balances {
-                let line = format!("  {} {}", amount, asset);
-
-                // Separate crypto from fiat
-                if asset == "USD" || asset == "EUR" || asset == "GBP" {
-                    fiat_balances.push(line);
-                } else {
-                    crypto_balances.push(line);
-                }
+    /// Returns `None` for data that isn't an order action.
+    pub fn parse(data: &str) -> Option<Self> {
+        let (action, rest) = data.split_once(':')?;
+        match action {
+            "cancel" if !rest.is_empty() => Some(OrderAction::Cancel {
+                order_id: rest.to_string(),
+            }),
+            "amend" => {
+                let (order_id, price) = rest.rsplit_once(':')?;
+                let limit_price: f64 = price.parse().ok()?;
+                (!order_id.is_empty() && limit_price.is_finite() && limit_price > 0.0).then(|| {
+                    OrderAction::Amend {
+                        order_id: order_id.to_string(),
+                        limit_price,
+                    }
+                })
             }
+            _ => None,
+        }
+    }
+}
 
-            let mut message = format!(
-                "📊 Portfolio Summary\n\
-                {}\n\
-                \n",
-                "═".repeat(30)
-            );
+/// Build the inline keyboard sent with order confirmations
+///
+/// Contains a "Cancel order" button and, when the order has a limit price, an
+/// "Amend +1%" button moving it 1% up (rounded to the limit price's
+/// decimals).
+///
+/// Only available when the `trading` feature is enabled.
+#[cfg(feature = "trading")]
+pub fn order_keyboard(
+    order_id: &str,
+    limit_price: Option<f64>,
+) -> teloxide::types::InlineKeyboardMarkup {
+    use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+    let mut row = vec![InlineKeyboardButton::callback(
+        "❌ Cancel order",
+        OrderAction::Cancel {
+            order_id: order_id.to_string(),
+        }
+        .callback_data(),
+    )];
+    if let Some(price) = limit_price {
+        let action = OrderAction::Amend {
+            order_id: order_id.to_string(),
+            limit_price: round_like(price * (1.0 + AMEND_STEP_PCT / 100.0), price),
+        };
+        row.push(InlineKeyboardButton::callback(
+            format!("✏️ Amend {:+}%", AMEND_STEP_PCT),
+            action.callback_data(),
+        ));
+    }
+    InlineKeyboardMarkup::new([row])
+}
 
-            if !crypto_balances.is_empty() {
-                message.push_str("💎 Crypto Assets:\n");
-                message.push_str(&crypto_balances.join("\n"));
-                message.push_str("\n\n");
-            }
+/// Round `value` to the number of decimals `reference` is written with
+#[cfg(feature = "trading")]
+fn round_like(value: f64, reference: f64) -> f64 {
+    let decimals = reference
+        .to_string()
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len().min(8));
+    let scale = 10f64.powi(decimals as i32);
+    (value * scale).round() / scale
+}
 
-            if !fiat_balances.is_empty() {
-                message.push_str("💵 Fiat Balances:\n");
-                message.push_str(&fiat_balances.join("\n"));
-                message.push_str("\n\n");
-            }
+/// Interactive Telegram bot answering commands from live client state
+///
+/// Replies to [`BotCommand`]s using the orderbooks maintained by a
+/// [`KrakyClient`](crate::KrakyClient). Only pairs the client is subscribed to
+/// can be queried.
+///
+/// Requires the `telegram` and `orderbook` features. `/imbalance` needs
+/// `analytics`, `/orders` needs `private` (fed via
+/// [`track_orders`](Self::track_orders)) and `/cancel` needs `trading` plus
+/// [`with_credentials`](Self::with_credentials).
+///
+/// With `trading`, the bot also handles presses of the inline buttons sent by
+/// [`TelegramNotifier::send_order_confirmation`] (see [`OrderAction`]); they
+/// are only accepted from allowed chats.
+///
+/// Account commands (`/orders`, `/cancel`) are only answered in chats added
+/// with [`allow_chat`](Self::allow_chat). Once any chat is allowed, messages
+/// from all other chats are ignored.
+///
+/// # Example
+///
+/// ```no_run
+/// use kraky::telegram::TelegramCommandBot;
+/// use kraky::KrakyClient;
+/// use std::sync::Arc;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = Arc::new(KrakyClient::connect().await?);
+///     let _btc = client.subscribe_orderbook("BTC/USD", 10).await?;
+///
+///     TelegramCommandBot::new("YOUR_BOT_TOKEN", client)
+///         .with_default_pair("BTC/USD")
+///         .allow_chat(123456789)
+///         .run()
+///         .await;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "orderbook")]
+pub struct TelegramCommandBot {
+    bot: Bot,
+    client: Arc<crate::KrakyClient>,
+    default_pair: Option<String>,
+    allowed_chats: Vec<ChatId>,
+    #[cfg(feature = "private")]
+    orders: Arc<RwLock<HashMap<String, crate::models::OrderData>>>,
+    #[cfg(feature = "trading")]
+    credentials: Option<crate::auth::Credentials>,
+}
 
-            message.push_str(&format!(
-                "{}\n\
-                🕐 {}\n\
-                \n\
-                Total Assets: {}",
-                "═".repeat(30),
-                chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
-                data.balances.len()
-            ));
-
-            self.send_alert(&message).await
-        } else {
-            Ok(())
+#[cfg(feature = "orderbook")]
+impl TelegramCommandBot {
+    /// Create a command bot answering from `client`'s state
+    ///
+    /// # Arguments
+    /// * `token` - Telegram bot token from @BotFather
+    /// * `client` - Connected client with the pairs to query subscribed
+    pub fn new(token: &str, client: Arc<crate::KrakyClient>) -> Self {
+        Self {
+            bot: Bot::new(token),
+            client,
+            default_pair: None,
+            allowed_chats: Vec::new(),
+            #[cfg(feature = "private")]
+            orders: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(feature = "trading")]
+            credentials: None,
         }
     }
 
-    // ============================================================================
-    // Trading Notifications (requires 'trading' feature)
-    // ============================================================================
+    /// Pair used when a command has no pair argument
+    pub fn with_default_pair(mut self, pair: &str) -> Self {
+        self.default_pair = Some(pair.to_string());
+        self
+    }
+
+    /// Allow a chat to use the bot (including account commands)
+    pub fn allow_chat(mut self, chat_id: i64) -> Self {
+        self.allowed_chats.push(ChatId(chat_id));
+        self
+    }
 
-    /// Send order placement notification
+    /// Credentials used by `/cancel`
     ///
-    /// Alerts when an order has been successfully placed.
+    /// Only available when the `trading` feature is enabled.
     #[cfg(feature = "trading")]
-    pub async fn send_order_placed(
-        &self,
-        response: &crate::models::OrderResponse,
-        params: &crate::models::OrderParams,
-    ) -> Result<()> {
-        let side_emoji = match params.side {
-            crate::models::OrderSide::Buy => "🟢",
-            crate::models::OrderSide::Sell => "🔴",
-        };
-
-        let order_type = format!("{:?}", params.order_type);
+    pub fn with_credentials(mut self, credentials: crate::auth::Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
 
-        let message = format!(
-            "{} Order Placed\n\
-            {}\n\
-            \n\
-            Order ID: {}\n\
-            Symbol: {}\n\
-            Side: {} {:?}\n\
-            Type: {}\n\
-            Quantity: {}\n\
-            {}\n\
-            Status: {:?}\n\
-            \n\
-            {} Order successfully submitted to exchange",
-            side_emoji,
-            "═".repeat(35),
-            response.order_id,
-            params.symbol,
-            side_emoji,
-            params.side,
-            order_type,
-            params
-                .order_qty
-                .map(|q| format!("{:.6}", q))
-                .unwrap_or("N/A".to_string()),
-            match params.limit_price {
-                Some(price) => format!("Limit Price: ${:.2}", price),
-                None => "Market Price".to_string(),
-            },
-            response.order_status,
-            if params.validate.unwrap_or(false) {
-                "✓"
-            } else {
-                "💸"
+    /// Record an update from the private `orders` channel for `/orders`
+    ///
+    /// Orders are listed until they are closed, cancelled or expired.
+    ///
+    /// Only available when the `private` feature is enabled.
+    #[cfg(feature = "private")]
+    pub fn track_orders(&self, update: &crate::models::OrderUpdate) {
+        let mut orders = self.orders.write();
+        for order in &update.data {
+            match order.status.as_str() {
+                "closed" | "canceled" | "cancelled" | "expired" => {
+                    orders.remove(&order.order_id);
+                }
+                _ => {
+                    orders.insert(order.order_id.clone(), order.clone());
+                }
             }
-        );
+        }
+    }
 
-        self.send_alert(&message).await
+    /// Produce the reply for a command
+    pub async fn handle(&self, command: BotCommand) -> String {
+        match command {
+            BotCommand::Help => BotCommand::help(),
+            BotCommand::Price(pair) => self.with_orderbook(pair, format_price),
+            BotCommand::Book(pair) => {
+                self.with_orderbook(pair, |ob| format_book(ob, BOOK_COMMAND_DEPTH))
+            }
+            #[cfg(feature = "analytics")]
+            BotCommand::Imbalance(pair) => self.with_orderbook(pair, format_imbalance),
+            #[cfg(feature = "private")]
+            BotCommand::Orders => format_orders(&self.orders.read()),
+            #[cfg(feature = "trading")]
+            BotCommand::Cancel(order_id) => {
+                self.handle_action(OrderAction::Cancel { order_id }).await
+            }
+        }
     }
 
-    /// Send order filled notification
+    /// Perform an order action from an inline keyboard button
     ///
-    /// Alerts when an order has been fully or partially filled.
+    /// Only available when the `trading` feature is enabled.
     #[cfg(feature = "trading")]
-    pub async fn send_order_filled(
-        &self,
-        symbol: &str,
-        side: &crate::models::OrderSide,
-        quantity: f64,
-        price: f64,
-        order_id: &str,
-    ) -> Result<()> {
-        let side_emoji = match side {
-            crate::models::OrderSide::Buy => "🟢",
-            crate::models::OrderSide::Sell => "🔴",
+    pub async fn handle_action(&self, action: OrderAction) -> String {
+        let Some(credentials) = &self.credentials else {
+            return "❌ Trading credentials are not configured".to_string();
         };
-
-        let total_value = quantity * price;
-
-        let message = format!(
-            "✅ Order Filled\n\
-            {}\n\
-            \n\
-            Symbol: {}\n\
-            Side: {} {:?}\n\
-            Filled: {:.6}\n\
-            Price: ${:.2}\n\
-            Total: ${:.2}\n\
-            \n\
-            Order ID: {}\n\
-            \n\
-            💰 Trade executed successfully",
-            "═".repeat(35),
-            symbol,
-            side_emoji,
-            side,
-            quantity,
-            price,
-            total_value,
-            order_id
-        );
-
-        self.send_alert(&message).await
+        match action {
+            OrderAction::Cancel { order_id } => {
+                match self
+                    .client
+                    .cancel_order(credentials, order_id.as_str())
+                    .await
+                {
+                    Ok(_) => format!("✅ Cancel requested for order {}", order_id),
+                    Err(e) => format!("❌ Failed to cancel {}: {}", order_id, e),
+                }
+            }
+            OrderAction::Amend {
+                order_id,
+                limit_price,
+            } => {
+                let params = crate::models::AmendOrderParams {
+                    order_id: order_id.clone(),
+                    order_qty: None,
+                    limit_price: Some(limit_price),
+                    trigger_price: None,
+                };
+                match self.client.amend_order(credentials, params).await {
+                    Ok(response) if response.success => {
+                        format!("✅ Order {} amended to ${}", order_id, limit_price)
+                    }
+                    Ok(response) => format!(
+                        "❌ Failed to amend {}: {}",
+                        order_id,
+                        response.error.unwrap_or_else(|| "rejected".to_string())
+                    ),
+                    Err(e) => format!("❌ Failed to amend {}: {}", order_id, e),
+                }
+            }
+        }
     }
 
-    /// Send order cancelled notification
+    /// Listen for commands until the process is stopped
     ///
-    /// Alerts when an order has been cancelled.
-    #[cfg(feature = "trading")]
-    pub async fn send_order_cancelled(
-        &self,
-        symbol: &str,
-        order_id: &str,
-        reason: Option<&str>,
-    ) -> Result<()> {
-        let message = format!(
-            "🚫 Order Cancelled\n\
-            {}\n\
-            \n\
-            Symbol: {}\n\
-            Order ID: {}\n\
-            {}\n\
-            \n\
-            ℹ️  Order removed from orderbook",
-            "═".repeat(35),
-            symbol,
-            order_id,
-            reason.map(|r| format!("Reason: {}", r)).unwrap_or_default()
-        );
-
-        self.send_alert(&message).await
+    /// Uses long polling; unknown messages are ignored. With `trading`,
+    /// inline keyboard presses are handled as well.
+    pub async fn run(self) {
+        let handler = Arc::new(self);
+        let bot = handler.bot.clone();
+
+        let tree = dptree::entry().branch(Update::filter_message().endpoint(Self::on_message));
+        #[cfg(feature = "trading")]
+        let tree = tree.branch(Update::filter_callback_query().endpoint(Self::on_callback));
+
+        Dispatcher::builder(bot, tree)
+            .dependencies(dptree::deps![handler])
+            .enable_ctrlc_handler()
+            .build()
+            .dispatch()
+            .await;
     }
 
-    /// Send order failed notification
-    ///
-    /// Alerts when an order placement has failed.
-    #[cfg(feature = "trading")]
-    pub async fn send_order_failed(
-        &self,
-        params: &crate::models::OrderParams,
-        error: &str,
-    ) -> Result<()> {
-        let message = format!(
-            "❌ Order Failed\n\
-            {}\n\
-            \n\
-            Symbol: {}\n\
-            Side: {:?}\n\
-            Type: {:?}\n\
-            \n\
-            Error: {}\n\
-            \n\
-            ⚠️  Please check order parameters and try again",
-            "═".repeat(35),
-            params.symbol,
-            params.side,
-            params.order_type,
-            error
-        );
+    async fn on_message(bot: Bot, msg: Message, handler: Arc<Self>) -> ResponseResult<()> {
+        let Some(command) = msg.text().and_then(BotCommand::parse) else {
+            return Ok(());
+        };
+        if !handler.is_allowed(msg.chat.id, &command) {
+            tracing::warn!(
+                "Ignoring {:?} from unauthorized chat {}",
+                command,
+                msg.chat.id
+            );
+            return Ok(());
+        }
 
-        self.send_alert(&message).await
+        let reply = handler.handle(command).await;
+        bot.send_message(msg.chat.id, reply).await?;
+        Ok(())
     }
 
-    /// Send order amended notification
-    ///
-    /// Alerts when an order has been successfully modified.
     #[cfg(feature = "trading")]
-    pub async fn send_order_amended(
-        &self,
-        response: &crate::models::AmendOrderResponse,
-        params: &crate::models::AmendOrderParams,
-    ) -> Result<()> {
-        let mut changes = Vec::new();
+    async fn on_callback(bot: Bot, query: CallbackQuery, handler: Arc<Self>) -> ResponseResult<()> {
+        let action = query.data.as_deref().and_then(OrderAction::parse);
+        let (Some(action), Some(message)) = (action, query.message) else {
+            bot.answer_callback_query(query.id).await?;
+            return Ok(());
+        };
+        if !handler.allowed_chats.contains(&message.chat.id) {
+            tracing::warn!(
+                "Ignoring {:?} from unauthorized chat {}",
+                action,
+                message.chat.id
+            );
+            bot.answer_callback_query(query.id)
+                .text("Not authorized")
+                .await?;
+            return Ok(());
+        }
+
+        bot.answer_callback_query(query.id).await?;
+        let reply = handler.handle_action(action).await;
+        bot.send_message(message.chat.id, reply)
+            .reply_to_message_id(message.id)
+            .await?;
+        Ok(())
+    }
 
-        if let Some(qty) = params.order_qty {
-            changes.push(format!("Quantity: {:.6}", qty));
+    /// Check whether a chat may run a command
+    fn is_allowed(&self, chat_id: ChatId, command: &BotCommand) -> bool {
+        if self.allowed_chats.contains(&chat_id) {
+            return true;
         }
-        if let Some(price) = params.limit_price {
-            changes.push(format!("Limit Price: ${:.2}", price));
+        #[cfg(feature = "private")]
+        if matches!(command, BotCommand::Orders) {
+            return false;
         }
-        if let Some(trigger) = params.trigger_price {
-            changes.push(format!("Trigger Price: ${:.2}", trigger));
+        #[cfg(feature = "trading")]
+        if matches!(command, BotCommand::Cancel(_)) {
+            return false;
         }
-
-        let message = format!(
-            "📝 Order Amended\n\
-            {}\n\
-            \n\
-            Order ID: {}\n\
-            \n\
-            Changes:\n\
-            {}\n\
-            \n\
-            {} Order successfully modified",
-            "═".repeat(35),
-            response.order_id,
-            changes.join("\n"),
-            if response.success { "✅" } else { "❌" }
-        );
-
-        self.send_alert(&message).await
+        let _ = command;
+        self.allowed_chats.is_empty()
     }
 
-    /// Send daily trading summary
-    ///
-    /// Provides a summary of trading activity.
-    #[cfg(feature = "trading")]
-    pub async fn send_trading_summary(
+    fn with_orderbook(
         &self,
-        total_trades: usize,
-        total_volume: f64,
-        profit_loss: f64,
-        win_rate: f64,
-    ) -> Result<()> {
-        let pl_emoji = if profit_loss >= 0.0 { "📈" } else { "📉" };
-        let pl_sign = if profit_loss >= 0.0 { "+" } else { "" };
+        pair: Option<String>,
+        format: impl FnOnce(&crate::models::Orderbook) -> String,
+    ) -> String {
+        let Some(pair) = pair.or_else(|| self.default_pair.clone()) else {
+            return "Please specify a pair, e.g. BTC/USD".to_string();
+        };
+        match self.client.get_orderbook(&pair) {
+            Some(ob) if !ob.bids.is_empty() || !ob.asks.is_empty() => format(&ob),
+            _ => format!("No orderbook data for {} (not subscribed?)", pair),
+        }
+    }
+}
 
-        let message = format!(
-            "📊 Daily Trading Summary\n\
-            {}\n\
-            {}\n\
-            \n\
-            Total Trades: {}\n\
-            Total Volume: ${:.2}\n\
-            \n\
-            {} P&L: {}{:.2}\n\
-            Win Rate: {:.1}%\n\
-            \n\
-            {} End of day report",
-            "═".repeat(35),
-            chrono::Utc::now().format("%Y-%m-%d"),
-            total_trades,
-            total_volume,
-            pl_emoji,
-            pl_sign,
-            profit_loss,
-            win_rate,
-            "📋"
-        );
+#[cfg(feature = "orderbook")]
+fn format_price(ob: &crate::models::Orderbook) -> String {
+    let fmt = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("${:.2}", v));
+    format!(
+        "💰 {}\n\
+        \n\
+        Bid: {}\n\
+        Ask: {}\n\
+        Mid: {}\n\
+        Spread: {}",
+        ob.symbol,
+        fmt(ob.best_bid()),
+        fmt(ob.best_ask()),
+        fmt(ob.mid_price()),
+        ob.spread_bps()
+            .map_or("-".to_string(), |bps| format!("{:.2} bps", bps)),
+    )
+}
+
+#[cfg(feature = "orderbook")]
+fn format_book(ob: &crate::models::Orderbook, depth: usize) -> String {
+    let mut message = format!("📖 {} Orderbook\n\nAsks:\n", ob.symbol);
+    for level in ob.top_asks(depth).iter().rev() {
+        message.push_str(&format!("  {:.2} × {:.4}\n", level.price, level.qty));
+    }
+    message.push_str("Bids:\n");
+    for level in ob.top_bids(depth) {
+        message.push_str(&format!("  {:.2} × {:.4}\n", level.price, level.qty));
+    }
+    message.trim_end().to_string()
+}
 
-        self.send_alert(&message).await
+#[cfg(feature = "analytics")]
+fn format_imbalance(ob: &crate::models::Orderbook) -> String {
+    let metrics = ob.imbalance_metrics();
+    format!(
+        "📊 {} Imbalance\n\
+        \n\
+        Imbalance: {:+.2}%\n\
+        Bid Volume: {:.4}\n\
+        Ask Volume: {:.4}\n\
+        Bid/Ask Ratio: {:.2}",
+        ob.symbol,
+        metrics.imbalance_ratio * 100.0,
+        metrics.bid_volume,
+        metrics.ask_volume,
+        metrics.bid_ask_ratio,
+    )
+}
+
+#[cfg(feature = "private")]
+fn format_orders(orders: &HashMap<String, crate::models::OrderData>) -> String {
+    if orders.is_empty() {
+        return "📋 No open orders".to_string();
+    }
+    let mut orders: Vec<_> = orders.values().collect();
+    orders.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let mut message = format!("📋 Open Orders ({})\n", orders.len());
+    for order in orders {
+        message.push_str(&format!(
+            "\n{} {} {} {} @ {}\n  ID: {}",
+            order.side.to_uppercase(),
+            order.order_qty,
+            order.symbol,
+            order.order_type,
+            order.limit_price.as_deref().unwrap_or("market"),
+            order.order_id,
+        ));
     }
+    message
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "analytics")]
+    use crate::models::ImbalanceSignal;
 
     #[test]
     fn test_notifier_creation() {
         let notifier = TelegramNotifier::new("test_token", 12345);
         assert_eq!(notifier.chat_id, ChatId(12345));
+        assert_eq!(notifier.routes().recipients(None), &[12345]);
+    }
+
+    #[test]
+    fn test_routing() {
+        let notifier = TelegramNotifier::new("test_token", 1)
+            .with_thread_id(7)
+            .with_chat(2)
+            .with_route("whale", 3)
+            .with_route("whale", 4)
+            .with_route("execution", 5);
+        let routes = notifier.routes();
+
+        assert_eq!(routes.recipients(None), &[1, 2]);
+        assert_eq!(routes.recipients(Some("price")), &[1, 2]);
+        assert_eq!(routes.recipients(Some("whale")), &[3, 4]);
+        assert_eq!(routes.recipients(Some("execution")), &[5]);
+
+        assert_eq!(notifier.thread_id(ChatId(1)), Some(7));
+        assert_eq!(notifier.thread_id(ChatId(2)), None);
+
+        // An empty route mutes the alert type
+        let mut muted = TelegramRoutes::new().with_default(1);
+        muted.routes.insert("trade".to_string(), Vec::new());
+        assert!(muted.recipients(Some("trade")).is_empty());
+    }
+
+    #[test]
+    fn test_rate_limiter_delay() {
+        let mut limiter = RateLimiter::new(2, 3);
+        let start = Instant::now();
+
+        limiter.record(start);
+        assert_eq!(limiter.delay(start), Duration::ZERO);
+        limiter.record(start);
+        // Per-second limit reached
+        assert_eq!(limiter.delay(start), Duration::from_secs(1));
+
+        limiter.record(start + Duration::from_secs(1));
+        // Per-minute limit reached: wait for the oldest send to leave the window
+        assert_eq!(
+            limiter.delay(start + Duration::from_secs(2)),
+            Duration::from_secs(58)
+        );
+        assert_eq!(
+            limiter.delay(start + Duration::from_secs(60)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(
+            escape_markdown_v2("BTC/USD +1.5% (24h)!"),
+            "BTC/USD \\+1\\.5% \\(24h\\)\\!"
+        );
+        assert_eq!(escape_html("<b>&</b>"), "&lt;b&gt;&amp;&lt;/b&gt;");
+        assert_eq!(TelegramFormat::Html.bold("a<b"), "<b>a&lt;b</b>");
+        assert_eq!(TelegramFormat::MarkdownV2.bold("1.5"), "*1\\.5*");
+        assert_eq!(TelegramFormat::Plain.code("x"), "x");
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let bot = TelegramNotifier::new("test_token", 1).with_quiet_hours(22, 7);
+        assert!(bot.is_silent(23));
+        assert!(bot.is_silent(3));
+        assert!(!bot.is_silent(12));
+
+        let bot = TelegramNotifier::new("test_token", 1).with_quiet_hours(1, 5);
+        assert!(bot.is_silent(1));
+        assert!(!bot.is_silent(5));
+        assert!(bot.with_silent(true).is_silent(12));
+    }
+
+    #[test]
+    fn test_cooldowns() {
+        let mut cooldowns = Cooldowns::default();
+        cooldowns
+            .durations
+            .insert("whale".to_string(), Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(cooldowns.allow("whale", "BTC/USD", start));
+        assert!(!cooldowns.allow("whale", "BTC/USD", start + Duration::from_secs(30)));
+        // Different subject and type without a cooldown are unaffected
+        assert!(cooldowns.allow("whale", "ETH/USD", start));
+        assert!(cooldowns.allow("price", "BTC/USD", start));
+        assert!(cooldowns.allow("whale", "BTC/USD", start + Duration::from_secs(60)));
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_command_parsing() {
+        assert_eq!(
+            BotCommand::parse("/price btc/usd"),
+            Some(BotCommand::Price(Some("BTC/USD".to_string())))
+        );
+        assert_eq!(
+            BotCommand::parse("/book@KrakyBot"),
+            Some(BotCommand::Book(None))
+        );
+        assert_eq!(BotCommand::parse("/start"), Some(BotCommand::Help));
+        assert_eq!(BotCommand::parse("/unknown"), None);
+        assert_eq!(BotCommand::parse("price"), None);
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_book_formatting() {
+        use crate::models::{Orderbook, OrderedFloat};
+
+        let mut ob = Orderbook::new("BTC/USD".to_string());
+        ob.bids.insert(OrderedFloat(100.0), 1.0);
+        ob.bids.insert(OrderedFloat(99.0), 2.0);
+        ob.asks.insert(OrderedFloat(101.0), 0.5);
+
+        let book = format_book(&ob, 5);
+        assert!(book.contains("101.00 × 0.5000"));
+        assert!(book.find("100.00").unwrap() < book.find("99.00").unwrap());
+
+        let price = format_price(&ob);
+        assert!(price.contains("Mid: $100.50"));
+    }
+
+    #[cfg(feature = "trading")]
+    #[test]
+    fn test_order_action_callback_data() {
+        let amend = OrderAction::Amend {
+            order_id: "OUF4EM-FRGI2-MQMWZD".to_string(),
+            limit_price: 50500.0,
+        };
+        assert_eq!(amend.callback_data(), "amend:OUF4EM-FRGI2-MQMWZD:50500");
+        assert_eq!(OrderAction::parse(&amend.callback_data()), Some(amend));
+        assert_eq!(
+            OrderAction::parse("cancel:OUF4EM-FRGI2-MQMWZD"),
+            Some(OrderAction::Cancel {
+                order_id: "OUF4EM-FRGI2-MQMWZD".to_string()
+            })
+        );
+        assert!(OrderAction::parse("cancel:").is_none());
+        assert!(OrderAction::parse("amend:ID:-1").is_none());
+        assert!(OrderAction::parse("unknown:ID").is_none());
+    }
+
+    #[cfg(feature = "trading")]
+    #[test]
+    fn test_order_keyboard() {
+        use teloxide::types::InlineKeyboardButtonKind;
+
+        let keyboard = order_keyboard("OUF4EM-FRGI2-MQMWZD", Some(0.1234));
+        let buttons = &keyboard.inline_keyboard[0];
+        assert_eq!(buttons.len(), 2);
+        assert_eq!(buttons[1].text, "✏️ Amend +1%");
+        match &buttons[1].kind {
+            InlineKeyboardButtonKind::CallbackData(data) => {
+                assert!(data.len() <= 64);
+                assert_eq!(data, "amend:OUF4EM-FRGI2-MQMWZD:0.1246");
+            }
+            kind => panic!("unexpected button kind {:?}", kind),
+        }
+
+        // Market orders can only be cancelled
+        let keyboard = order_keyboard("OUF4EM-FRGI2-MQMWZD", None);
+        assert_eq!(keyboard.inline_keyboard[0].len(), 1);
     }
 
     #[cfg(feature = "analytics")]
@@ -0,0 +1,240 @@
+//! Customizable alert message templates
+//!
+//! Every typed [`Notifier`](crate::Notifier) alert has a built-in text. An
+//! [`AlertTemplate`] replaces it for one alert type, so wording, language and
+//! verbosity can be changed without forking the crate. Templates use
+//! `{name}` placeholders, filled from the variables each alert provides;
+//! `{{` and `}}` produce literal braces.
+//!
+//! Register templates in an [`AlertTemplates`] set and attach it to a
+//! notifier (e.g. `TelegramNotifier::with_templates`). Alert types without a
+//! template keep the built-in text.
+//!
+//! # Variables
+//!
+//! | Alert type | Variables |
+//! |------------|-----------|
+//! | `alert` | `name`, `symbol`, `condition`, `value`, `time` |
+//! | `price` | `symbol`, `price`, `context` |
+//! | `imbalance` | `symbol`, `emoji`, `signal`, `imbalance`, `bid_volume`, `ask_volume`, `ratio` |
+//! | `threshold` | `symbol`, `emoji`, `price`, `threshold`, `direction`, `change_pct` |
+//! | `orderbook_summary` | `symbol`, `best_bid`, `best_ask`, `mid_price`, `spread`, `spread_bps` |
+//! | `connection` | `emoji`, `status`, `details` |
+//! | `data_gap` | `symbol`, `channel`, `emoji`, `status`, `idle` |
+//! | `checksum` | `symbol`, `emoji`, `details` |
+//! | `whale` | `symbol`, `emoji`, `side`, `price`, `volume`, `value` |
+//! | `spread` | `symbol`, `emoji`, `severity`, `spread_bps`, `normal_bps`, `multiplier` |
+//! | `divergence` | `symbol`, `direction`, `price_change`, `signal` |
+//! | `trade` | `symbol`, `emoji`, `side`, `price`, `volume`, `value` |
+//! | `balance`, `portfolio` | `balances`, `time` |
+//! | `order_update` | `symbol`, `order_id`, `side`, `type`, `status`, `qty`, `filled`, `limit_price` |
+//! | `execution` | `symbol`, `order_id`, `exec_id`, `side`, `price`, `qty`, `value`, `liquidity` |
+//! | `order_placed` | `symbol`, `order_id`, `side`, `type`, `qty`, `limit_price`, `status` |
+//! | `order_filled` | `symbol`, `order_id`, `side`, `qty`, `price`, `value` |
+//! | `order_cancelled` | `symbol`, `order_id`, `reason` |
+//! | `order_failed` | `symbol`, `side`, `type`, `error` |
+//! | `order_amended` | `order_id`, `changes`, `success` |
+//! | `trading_summary` | `trades`, `volume`, `pnl`, `win_rate` |
+//! | `summary` | `period`, `messages`, `alerts`, `prices`, `trades`, `volume`, `pnl` |
+//!
+//! # Example
+//!
+//! ```
+//! use kraky::{AlertTemplate, AlertTemplates};
+//!
+//! let templates = AlertTemplates::new()
+//!     .with("price", AlertTemplate::new("{symbol} ist jetzt ${price}"))
+//!     .with("whale", AlertTemplate::new("🐋 {side} {volume} {symbol} @ {price}"));
+//!
+//! let text = templates.render("price", &[("symbol", "BTC/USD".into()), ("price", "100000.00".into())]);
+//! assert_eq!(text.as_deref(), Some("BTC/USD ist jetzt $100000.00"));
+//! ```
+
+use std::collections::HashMap;
+
+/// A message template with `{name}` placeholders
+///
+/// Unknown placeholders are left in the output unchanged, so typos are
+/// visible in the delivered message rather than silently dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertTemplate {
+    template: String,
+}
+
+impl AlertTemplate {
+    /// Create a template from a string
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+        }
+    }
+
+    /// Get the template source
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// Fill in the placeholders
+    pub fn render(&self, vars: &[(&str, String)]) -> String {
+        let mut output = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+
+        while let Some(pos) = rest.find(['{', '}']) {
+            output.push_str(&rest[..pos]);
+            rest = &rest[pos..];
+
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                output.push_str(&rest[..1]);
+                rest = &rest[2..];
+                continue;
+            }
+            if rest.starts_with('}') {
+                output.push('}');
+                rest = &rest[1..];
+                continue;
+            }
+
+            match rest.find('}') {
+                Some(end) => {
+                    let name = &rest[1..end];
+                    match vars.iter().find(|(key, _)| *key == name) {
+                        Some((_, value)) => output.push_str(value),
+                        None => output.push_str(&rest[..=end]),
+                    }
+                    rest = &rest[end + 1..];
+                }
+                None => break,
+            }
+        }
+
+        output.push_str(rest);
+        output
+    }
+}
+
+impl From<&str> for AlertTemplate {
+    fn from(template: &str) -> Self {
+        Self::new(template)
+    }
+}
+
+impl From<String> for AlertTemplate {
+    fn from(template: String) -> Self {
+        Self::new(template)
+    }
+}
+
+/// A set of templates keyed by alert type
+///
+/// See the [module documentation](self) for alert types and their variables.
+#[derive(Debug, Clone, Default)]
+pub struct AlertTemplates {
+    templates: HashMap<String, AlertTemplate>,
+}
+
+impl AlertTemplates {
+    /// Create an empty set (all alerts use their built-in text)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a template for an alert type (builder style)
+    pub fn with(mut self, alert_type: &str, template: impl Into<AlertTemplate>) -> Self {
+        self.set(alert_type, template);
+        self
+    }
+
+    /// Set the template for an alert type
+    pub fn set(&mut self, alert_type: &str, template: impl Into<AlertTemplate>) {
+        self.templates
+            .insert(alert_type.to_string(), template.into());
+    }
+
+    /// Remove the template for an alert type
+    pub fn remove(&mut self, alert_type: &str) -> Option<AlertTemplate> {
+        self.templates.remove(alert_type)
+    }
+
+    /// Get the template for an alert type
+    pub fn get(&self, alert_type: &str) -> Option<&AlertTemplate> {
+        self.templates.get(alert_type)
+    }
+
+    /// Render an alert type's template, if one is set
+    pub fn render(&self, alert_type: &str, vars: &[(&str, String)]) -> Option<String> {
+        self.get(alert_type).map(|t| t.render(vars))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_placeholders() {
+        let template = AlertTemplate::new("{symbol}: {price} ({missing}) {{literal}}");
+        let text = template.render(&[
+            ("symbol", "BTC/USD".to_string()),
+            ("price", "100.00".to_string()),
+        ]);
+        assert_eq!(text, "BTC/USD: 100.00 ({missing}) {literal}");
+    }
+
+    #[test]
+    fn test_unclosed_brace() {
+        let template = AlertTemplate::new("price {price");
+        assert_eq!(
+            template.render(&[("price", "1".to_string())]),
+            "price {price"
+        );
+    }
+
+    #[test]
+    fn test_template_set() {
+        let mut templates = AlertTemplates::new().with("price", "{price}");
+        assert_eq!(
+            templates.render("price", &[("price", "1".to_string())]),
+            Some("1".to_string())
+        );
+        assert!(templates.render("whale", &[]).is_none());
+
+        templates.remove("price");
+        assert!(templates.get("price").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_notifier_uses_templates() {
+        use crate::{Notifier, Result};
+
+        struct Capture(AlertTemplates, parking_lot::Mutex<Vec<String>>);
+
+        #[async_trait::async_trait]
+        impl Notifier for Capture {
+            fn templates(&self) -> Option<&AlertTemplates> {
+                Some(&self.0)
+            }
+
+            async fn send_alert(&self, message: &str) -> Result<()> {
+                self.1.lock().push(message.to_string());
+                Ok(())
+            }
+        }
+
+        let notifier = Capture(
+            AlertTemplates::new().with("price", "{symbol} @ {price}"),
+            parking_lot::Mutex::new(Vec::new()),
+        );
+        notifier
+            .send_price_alert("BTC/USD", 100.0, "")
+            .await
+            .unwrap();
+        notifier
+            .send_whale_alert("BTC/USD", "bid", 100.0, 50.0)
+            .await
+            .unwrap();
+
+        let sent = notifier.1.lock();
+        assert_eq!(sent[0], "BTC/USD @ 100.00");
+        assert!(sent[1].contains("Whale Alert"));
+    }
+}
@@ -0,0 +1,251 @@
+//! Kraken v2 payloads for regression tests
+//!
+//! One frame per message kind of the v2 API (status, heartbeats, method
+//! responses and every public channel), embedded in the crate so downstream
+//! tests can feed their handlers messages without a network connection.
+//!
+//! The corpus is refreshed from the live public feed with
+//! `cargo run --example capture_fixtures`, which writes each frame exactly as
+//! Kraken sent it, extra fields and number formats included. Until it is next
+//! run, the checked-in frames are hand-written in the v2 shapes. The tests
+//! here derive their expectations from the frames, so a refreshed corpus
+//! drops in without edits. [`load_corpus`] reads a corpus of your own
+//! captured frames.
+//!
+//! # Example
+//!
+//! ```
+//! use kraky::messages::KrakyMessage;
+//! use kraky::testing::fixtures;
+//!
+//! let snapshot = fixtures::get("book_snapshot").unwrap();
+//! assert_eq!(snapshot.channel(), "book");
+//!
+//! for fixture in fixtures::all() {
+//!     let message: KrakyMessage = fixture.parse().unwrap();
+//!     # let _ = message;
+//! }
+//! ```
+
+use crate::error::Result;
+use crate::messages::KrakyMessage;
+use serde_json::Value;
+use std::path::Path;
+
+/// A fixture message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixture {
+    /// Name, e.g. `book_snapshot`
+    pub name: &'static str,
+    /// Raw frame text
+    pub text: &'static str,
+}
+
+impl Fixture {
+    /// Parse the frame the way the client does
+    pub fn parse(&self) -> std::result::Result<KrakyMessage, serde_json::Error> {
+        KrakyMessage::parse(self.text)
+    }
+
+    /// Get the frame as a JSON value
+    pub fn json(&self) -> Value {
+        serde_json::from_str(self.text).expect("fixtures are valid JSON")
+    }
+
+    /// Get the channel, or the method for method responses
+    pub fn channel(&self) -> String {
+        let json = self.json();
+        json["channel"]
+            .as_str()
+            .or_else(|| json["method"].as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+macro_rules! fixture {
+    ($name:literal) => {
+        Fixture {
+            name: $name,
+            text: include_str!(concat!("../../fixtures/v2/", $name, ".json")),
+        }
+    };
+}
+
+static FIXTURES: &[Fixture] = &[
+    fixture!("status_update"),
+    fixture!("heartbeat"),
+    fixture!("pong"),
+    fixture!("subscribe_book_ack"),
+    fixture!("subscribe_error"),
+    fixture!("book_snapshot"),
+    fixture!("book_update"),
+    fixture!("trade_snapshot"),
+    fixture!("trade_update"),
+    fixture!("ticker_snapshot"),
+    fixture!("ohlc_snapshot"),
+    fixture!("ohlc_update"),
+];
+
+/// Get every fixture
+pub fn all() -> &'static [Fixture] {
+    FIXTURES
+}
+
+/// Get a fixture by name
+pub fn get(name: &str) -> Option<&'static Fixture> {
+    FIXTURES.iter().find(|fixture| fixture.name == name)
+}
+
+/// Load captured frames, one per line, from a file or a directory of files
+///
+/// Blank lines are skipped. Directories are read in file name order.
+pub fn load_corpus(path: impl AsRef<Path>) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let files = if path.is_dir() {
+        let mut files = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        files.retain(|file| file.is_file());
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut frames = Vec::new();
+    for file in files {
+        let contents = std::fs::read_to_string(file)?;
+        frames.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string),
+        );
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_fixture_parses() {
+        for fixture in all() {
+            let message = fixture
+                .parse()
+                .unwrap_or_else(|e| panic!("{}: {}", fixture.name, e));
+            let known = !matches!(message, KrakyMessage::Unknown(_));
+            let channel = fixture.channel();
+            let enabled = match channel.as_str() {
+                "book" => cfg!(feature = "orderbook"),
+                "trade" => cfg!(feature = "trades"),
+                "ticker" => cfg!(feature = "ticker"),
+                "ohlc" => cfg!(feature = "ohlc"),
+                _ => true,
+            };
+            assert_eq!(known, enabled, "{} ({})", fixture.name, channel);
+        }
+    }
+
+    #[test]
+    fn test_method_responses() {
+        assert!(matches!(
+            get("status_update").unwrap().parse().unwrap(),
+            KrakyMessage::SystemStatus(_)
+        ));
+        assert!(matches!(
+            get("pong").unwrap().parse().unwrap(),
+            KrakyMessage::Pong { req_id: Some(101) }
+        ));
+        match get("subscribe_error").unwrap().parse().unwrap() {
+            KrakyMessage::SubscriptionStatus { success, error, .. } => {
+                assert!(!success);
+                assert!(error.unwrap().contains("XYZ/USD"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "orderbook")]
+    #[test]
+    fn test_book_fixtures() {
+        use crate::models::Orderbook;
+
+        use crate::models::OrderedFloat;
+
+        let mut book = Orderbook::new("BTC/USD".to_string());
+        let mut last = None;
+        for name in ["book_snapshot", "book_update"] {
+            match get(name).unwrap().parse().unwrap() {
+                KrakyMessage::Orderbook(update) => {
+                    for data in &update.data {
+                        book.apply_update(data);
+                    }
+                    last = update.data.last().cloned();
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        assert!(book.best_bid().unwrap() < book.best_ask().unwrap());
+
+        // Every level in the update is applied: zero quantities remove it
+        let update = last.unwrap();
+        for (levels, side) in [(&update.bids, &book.bids), (&update.asks, &book.asks)] {
+            for level in levels {
+                let qty = side.get(&OrderedFloat(level.price)).copied();
+                let expected = (level.qty > 0.0).then_some(level.qty);
+                assert_eq!(qty, expected, "level {}", level.price);
+            }
+        }
+    }
+
+    #[cfg(all(feature = "trades", feature = "ohlc"))]
+    #[test]
+    fn test_trade_and_ohlc_fixtures() {
+        let KrakyMessage::Trade(trades) = get("trade_snapshot").unwrap().parse().unwrap() else {
+            panic!("not a trade");
+        };
+        let json = get("trade_snapshot").unwrap().json();
+        assert_eq!(trades.data.len(), json["data"].as_array().unwrap().len());
+        for (trade, raw) in trades.data.iter().zip(json["data"].as_array().unwrap()) {
+            let trade = trade.to_trade();
+            assert_eq!(Some(trade.trade_id), raw["trade_id"].as_i64());
+            assert_eq!(Some(trade.price), raw["price"].as_f64());
+        }
+
+        let KrakyMessage::OHLC(candles) = get("ohlc_update").unwrap().parse().unwrap() else {
+            panic!("not a candle");
+        };
+        let candle = candles.data[0].to_ohlc();
+        let raw = &get("ohlc_update").unwrap().json()["data"][0];
+        assert_eq!(candle.interval, 1);
+        assert_eq!(Some(candle.count), raw["trades"].as_i64());
+        assert_eq!(
+            Some(candle.interval_begin.as_str()),
+            raw["interval_begin"].as_str()
+        );
+    }
+
+    #[test]
+    fn test_load_corpus() {
+        let dir = std::env::temp_dir().join(format!("kraky-corpus-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("b.jsonl"),
+            format!("{}\n\n", get("heartbeat").unwrap().text.trim()),
+        )
+        .unwrap();
+        std::fs::write(dir.join("a.jsonl"), get("pong").unwrap().text).unwrap();
+
+        let frames = load_corpus(&dir).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].contains("pong"));
+        assert_eq!(load_corpus(dir.join("b.jsonl")).unwrap().len(), 1);
+        assert!(load_corpus(dir.join("missing")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
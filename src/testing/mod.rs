@@ -0,0 +1,395 @@
+//! Scripted mock server with fault injection
+//!
+//! [`MockServer`] is a local WebSocket server that speaks enough of the
+//! Kraken v2 protocol to drive a [`KrakyClient`]: it acknowledges
+//! subscriptions and then plays a script of messages. [`Fault`]s break the
+//! script in controlled ways, to exercise reconnect, backpressure and
+//! resync logic without waiting for the real exchange to misbehave.
+//!
+//! [`fixtures`] holds Kraken v2 payloads for parser and handler
+//! tests.
+//!
+//! Requires the `testing` feature flag.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::testing::{Fault, MockScript, MockServer};
+//! use kraky::{KrakyClient, ReconnectConfig};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let server = MockServer::start(
+//!     MockScript::new()
+//!         .message(r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[],"asks":[]}]}"#)
+//!         .fault(Fault::MalformedAt(0))
+//!         .fault(Fault::DropAfter(1)),
+//! )
+//! .await?;
+//!
+//! let client = KrakyClient::builder()
+//!     .url(server.url())
+//!     .reconnect(ReconnectConfig::aggressive())
+//!     .connect()
+//!     .await?;
+//! let _book = client.subscribe_orderbook("BTC/USD", 10).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`KrakyClient`]: crate::KrakyClient
+
+pub mod fixtures;
+
+use crate::error::Result;
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Text sent for [`Fault::MalformedAt`]
+const MALFORMED: &str = r#"{"channel":"book","type":"update","data":[{"symbol""#;
+
+/// A way for the mock server to misbehave
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    /// Drop the first connection, without a close frame, after sending this
+    /// many scripted messages; later connections play the whole script
+    DropAfter(usize),
+    /// Send truncated JSON before the scripted message at this index
+    MalformedAt(usize),
+    /// Send every book update twice
+    DuplicateDeltas,
+    /// Wait this long before acknowledging each subscription
+    DelayAcks(Duration),
+    /// Send book messages with a wrong checksum
+    BadChecksums,
+}
+
+/// Messages and faults played by a [`MockServer`]
+#[derive(Debug, Clone, Default)]
+pub struct MockScript {
+    messages: Vec<String>,
+    faults: Vec<Fault>,
+    interval: Duration,
+}
+
+impl MockScript {
+    /// Create an empty script
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a message, sent as-is once the first subscription was acknowledged
+    pub fn message(mut self, text: impl Into<String>) -> Self {
+        self.messages.push(text.into());
+        self
+    }
+
+    /// Add several messages
+    pub fn messages<I, S>(mut self, texts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.messages.extend(texts.into_iter().map(Into::into));
+        self
+    }
+
+    /// Wait this long between scripted messages (default: none)
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Inject a fault
+    pub fn fault(mut self, fault: Fault) -> Self {
+        self.faults.push(fault);
+        self
+    }
+
+    fn drop_after(&self) -> Option<usize> {
+        self.faults.iter().find_map(|fault| match fault {
+            Fault::DropAfter(count) => Some(*count),
+            _ => None,
+        })
+    }
+
+    fn malformed_at(&self, index: usize) -> bool {
+        self.faults.contains(&Fault::MalformedAt(index))
+    }
+
+    fn ack_delay(&self) -> Duration {
+        self.faults
+            .iter()
+            .find_map(|fault| match fault {
+                Fault::DelayAcks(delay) => Some(*delay),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// The frames to send for one scripted message
+    fn frames(&self, text: &str) -> Vec<String> {
+        let mut value: Value = match serde_json::from_str(text) {
+            Ok(value) => value,
+            Err(_) => return vec![text.to_string()],
+        };
+        if value["channel"] != "book" {
+            return vec![text.to_string()];
+        }
+        let mut frame = text.to_string();
+        if self.faults.contains(&Fault::BadChecksums) {
+            if let Some(entries) = value["data"].as_array_mut() {
+                for entry in entries {
+                    let checksum = entry["checksum"].as_u64().unwrap_or(0) as u32;
+                    entry["checksum"] = json!(checksum.wrapping_add(1));
+                }
+            }
+            frame = value.to_string();
+        }
+        if self.faults.contains(&Fault::DuplicateDeltas) && value["type"] == "update" {
+            vec![frame.clone(), frame]
+        } else {
+            vec![frame]
+        }
+    }
+}
+
+/// What the server saw, shared with its connection tasks
+#[derive(Debug, Default)]
+struct ServerLog {
+    connections: AtomicUsize,
+    received: Mutex<Vec<String>>,
+}
+
+/// Local WebSocket server playing a [`MockScript`]
+///
+/// Stops when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    url: String,
+    log: Arc<ServerLog>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Start listening on a free local port
+    pub async fn start(script: MockScript) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let log = Arc::new(ServerLog::default());
+        let script = Arc::new(script);
+
+        let server_log = Arc::clone(&log);
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let index = server_log.connections.fetch_add(1, Ordering::SeqCst);
+                let script = Arc::clone(&script);
+                let log = Arc::clone(&server_log);
+                tokio::spawn(serve(stream, index, script, log));
+            }
+        });
+
+        Ok(Self { url, log, task })
+    }
+
+    /// Get the `ws://` URL to connect to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Get the number of connections accepted so far
+    pub fn connections(&self) -> usize {
+        self.log.connections.load(Ordering::SeqCst)
+    }
+
+    /// Get every text message received from clients, in order
+    pub fn received(&self) -> Vec<String> {
+        self.log.received.lock().clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Play the script on one connection
+async fn serve(stream: TcpStream, index: usize, script: Arc<MockScript>, log: Arc<ServerLog>) {
+    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let drop_after = script.drop_after().filter(|_| index == 0);
+    let mut streaming = false;
+    let mut sent = 0;
+
+    loop {
+        if streaming && drop_after == Some(sent) {
+            // Dropping the socket without a close frame looks like a network failure
+            return;
+        }
+        let pending = streaming && sent < script.messages.len();
+        tokio::select! {
+            incoming = ws.next() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(_)) => continue,
+                    _ => return,
+                };
+                log.received.lock().push(text.clone());
+                let Ok(request) = serde_json::from_str::<Value>(&text) else {
+                    continue;
+                };
+                if request["method"] != "subscribe" {
+                    continue;
+                }
+                tokio::time::sleep(script.ack_delay()).await;
+                let ack = json!({
+                    "method": "subscribe",
+                    "req_id": request["req_id"],
+                    "success": true,
+                    "result": {
+                        "channel": request["params"]["channel"],
+                        "symbol": request["params"]["symbol"][0],
+                    },
+                });
+                if ws.send(Message::Text(ack.to_string())).await.is_err() {
+                    return;
+                }
+                streaming = true;
+            }
+            _ = tokio::time::sleep(script.interval), if pending => {
+                if script.malformed_at(sent)
+                    && ws.send(Message::Text(MALFORMED.to_string())).await.is_err()
+                {
+                    return;
+                }
+                for frame in script.frames(&script.messages[sent]) {
+                    if ws.send(Message::Text(frame)).await.is_err() {
+                        return;
+                    }
+                }
+                sent += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::connect_async;
+
+    const SNAPSHOT: &str = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[{"price":100.0,"qty":1.0}],"asks":[{"price":101.0,"qty":1.0}],"checksum":7}]}"#;
+    const UPDATE: &str = r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":[{"price":100.5,"qty":2.0}],"asks":[],"checksum":9}]}"#;
+    const SUBSCRIBE: &str =
+        r#"{"method":"subscribe","params":{"channel":"book","symbol":["BTC/USD"]},"req_id":1}"#;
+
+    /// Subscribe with a raw socket and collect `count` frames after the ack
+    async fn frames(server: &MockServer, count: usize) -> Vec<String> {
+        let (mut ws, _) = connect_async(server.url()).await.unwrap();
+        ws.send(Message::Text(SUBSCRIBE.to_string())).await.unwrap();
+        let mut frames = Vec::new();
+        while frames.len() <= count {
+            match tokio::time::timeout(Duration::from_secs(5), ws.next()).await {
+                Ok(Some(Ok(Message::Text(text)))) => frames.push(text),
+                _ => break,
+            }
+        }
+        // Skip the ack
+        frames.split_off(1)
+    }
+
+    #[tokio::test]
+    async fn test_script_and_faults() {
+        let server = MockServer::start(
+            MockScript::new()
+                .messages([SNAPSHOT, UPDATE])
+                .fault(Fault::MalformedAt(1))
+                .fault(Fault::DuplicateDeltas)
+                .fault(Fault::BadChecksums),
+        )
+        .await
+        .unwrap();
+
+        let frames = frames(&server, 4).await;
+        assert_eq!(frames.len(), 4);
+        assert!(frames[0].contains(r#""checksum":8"#));
+        assert_eq!(frames[1], MALFORMED);
+        assert!(frames[2].contains(r#""checksum":10"#));
+        assert_eq!(frames[2], frames[3]);
+        assert_eq!(server.received(), vec![SUBSCRIBE.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_drop_after_only_first_connection() {
+        let server = MockServer::start(
+            MockScript::new()
+                .messages([SNAPSHOT, UPDATE])
+                .fault(Fault::DropAfter(1)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(frames(&server, 2).await, vec![SNAPSHOT.to_string()]);
+        assert_eq!(frames(&server, 2).await.len(), 2);
+        assert_eq!(server.connections(), 2);
+    }
+
+    #[cfg(all(feature = "orderbook", feature = "reconnect"))]
+    #[tokio::test]
+    async fn test_client_recovers_from_faults() {
+        use crate::{KrakyClient, ReconnectConfig};
+        use std::time::Instant;
+
+        let delay = Duration::from_millis(100);
+        let server = MockServer::start(
+            MockScript::new()
+                .messages([SNAPSHOT, UPDATE])
+                .fault(Fault::MalformedAt(1))
+                .fault(Fault::DelayAcks(delay))
+                .fault(Fault::DropAfter(1)),
+        )
+        .await
+        .unwrap();
+        let client = KrakyClient::builder()
+            .url(server.url())
+            .reconnect(ReconnectConfig::aggressive().with_jitter(0.0))
+            .connect()
+            .await
+            .unwrap();
+
+        let started = Instant::now();
+        let book = client.subscribe_orderbook("BTC/USD", 10).await.unwrap();
+        book.ready().await.unwrap();
+        assert!(started.elapsed() >= delay);
+
+        // The client reconnects, resubscribes and gets past the bad frame
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let best_bid = client
+                    .get_orderbook("BTC/USD")
+                    .and_then(|book| book.best_bid());
+                if best_bid == Some(100.5) {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(server.connections(), 2);
+        let subscribes = server
+            .received()
+            .iter()
+            .filter(|text| text.contains(r#""method":"subscribe""#))
+            .count();
+        assert_eq!(subscribes, 2);
+    }
+}
@@ -0,0 +1,266 @@
+//! Cached WebSocket tokens for private sessions
+//!
+//! Private channels and WebSocket trading authenticate with a token from
+//! Kraken's `GetWebSocketsToken` REST endpoint. A token has to be used
+//! within 15 minutes of being issued, so a bot that reconnects hours later
+//! needs a new one. [`TokenManager`] caches the token and fetches a new one
+//! shortly before it expires.
+//!
+//! Passed to [`ClientBuilder::token_manager`], it is refreshed after every
+//! reconnect, before subscriptions are restored, and the client's trading
+//! requests for the same key use its token.
+//!
+//! Requires the `private` and `rest` feature flags.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::{Credentials, KrakyClient, TokenManager};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let tokens = Arc::new(TokenManager::new(Credentials::from_env()?));
+//! let refresher = tokens.spawn_refresh();
+//!
+//! let client = KrakyClient::builder()
+//!     .token_manager(Arc::clone(&tokens))
+//!     .connect()
+//!     .await?;
+//! println!("Token: {}", tokens.token().await?);
+//! # refresher.abort();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ClientBuilder::token_manager`]: crate::ClientBuilder::token_manager
+
+use crate::auth::Credentials;
+use crate::error::Result;
+use crate::rest::{RestClient, WebSocketToken};
+use futures_util::future::BoxFuture;
+use parking_lot::Mutex;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Time before expiry at which a token is replaced
+const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Wait before retrying a failed background refresh
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Fetches a new token
+type TokenFetcher = Arc<dyn Fn() -> BoxFuture<'static, Result<WebSocketToken>> + Send + Sync>;
+
+/// A token and when it stops being usable
+struct CachedToken {
+    token: String,
+    expires_at: Instant,
+}
+
+/// Caches the WebSocket token for one API key
+pub struct TokenManager {
+    api_key: String,
+    fetch: TokenFetcher,
+    refresh_margin: Duration,
+    cached: Mutex<Option<CachedToken>>,
+    /// Held while fetching, so concurrent callers share one request
+    fetching: tokio::sync::Mutex<()>,
+}
+
+impl std::fmt::Debug for TokenManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenManager")
+            .field("api_key", &self.api_key)
+            .field("refresh_margin", &self.refresh_margin)
+            .field("expires_in", &self.expires_in())
+            .finish()
+    }
+}
+
+impl TokenManager {
+    /// Create a manager fetching tokens from the production REST API
+    pub fn new(credentials: Credentials) -> Self {
+        Self::with_rest(RestClient::new(), credentials)
+    }
+
+    /// Create a manager fetching tokens through `rest`
+    pub fn with_rest(rest: RestClient, credentials: Credentials) -> Self {
+        let api_key = credentials.api_key().to_string();
+        Self::with_fetcher(api_key, move || {
+            let rest = rest.clone();
+            let credentials = credentials.clone();
+            async move { rest.websocket_token(&credentials).await }
+        })
+    }
+
+    /// Create a manager with another token source (for testing)
+    pub fn with_fetcher<F, Fut>(api_key: impl Into<String>, fetch: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<WebSocketToken>> + Send + 'static,
+    {
+        Self {
+            api_key: api_key.into(),
+            fetch: Arc::new(move || Box::pin(fetch())),
+            refresh_margin: DEFAULT_REFRESH_MARGIN,
+            cached: Mutex::new(None),
+            fetching: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Replace tokens this long before they expire (default 60 seconds)
+    pub fn refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// Get the API key the tokens belong to
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    /// Get a usable token, fetching one if none is cached or it expires soon
+    pub async fn token(&self) -> Result<String> {
+        if let Some(token) = self.fresh() {
+            return Ok(token);
+        }
+        let _fetching = self.fetching.lock().await;
+        // Another caller may have fetched while this one waited
+        if let Some(token) = self.fresh() {
+            return Ok(token);
+        }
+        self.fetch().await
+    }
+
+    /// Fetch a new token, replacing the cached one
+    pub async fn refresh(&self) -> Result<String> {
+        let _fetching = self.fetching.lock().await;
+        self.fetch().await
+    }
+
+    /// Drop the cached token, e.g. after Kraken rejected it
+    pub fn invalidate(&self) {
+        *self.cached.lock() = None;
+    }
+
+    /// Get the time until the cached token expires, if one is cached
+    pub fn expires_in(&self) -> Option<Duration> {
+        self.cached
+            .lock()
+            .as_ref()
+            .map(|cached| cached.expires_at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Keep a fresh token cached in the background
+    ///
+    /// Fetches a token now and again `refresh_margin` before each expiry,
+    /// retrying failures every few seconds. Abort the handle to stop.
+    pub fn spawn_refresh(self: &Arc<Self>) -> JoinHandle<()> {
+        let manager = Arc::clone(self);
+        crate::runtime::spawn(async move {
+            loop {
+                let wait = match manager.refresh().await {
+                    Ok(_) => manager
+                        .expires_in()
+                        .unwrap_or_default()
+                        .saturating_sub(manager.refresh_margin),
+                    Err(e) => {
+                        warn!("WebSocket token refresh failed: {}", e);
+                        RETRY_DELAY
+                    }
+                };
+                tokio::time::sleep(wait.max(RETRY_DELAY)).await;
+            }
+        })
+    }
+
+    /// The cached token, unless it expires within the margin
+    fn fresh(&self) -> Option<String> {
+        let cached = self.cached.lock();
+        let cached = cached.as_ref()?;
+        (Instant::now() + self.refresh_margin < cached.expires_at).then(|| cached.token.clone())
+    }
+
+    async fn fetch(&self) -> Result<String> {
+        let fetched = (self.fetch)().await?;
+        *self.cached.lock() = Some(CachedToken {
+            token: fetched.token.clone(),
+            expires_at: Instant::now() + fetched.expires_in,
+        });
+        Ok(fetched.token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::KrakyError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Manager issuing `token-1`, `token-2`, ... valid for `lifetime`
+    fn counting_manager(lifetime: Duration) -> (TokenManager, Arc<AtomicU32>) {
+        let fetches = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&fetches);
+        let manager = TokenManager::with_fetcher("key", move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                Ok(WebSocketToken {
+                    token: format!("token-{}", n),
+                    expires_in: lifetime,
+                })
+            }
+        })
+        .refresh_margin(Duration::from_secs(60));
+        (manager, fetches)
+    }
+
+    #[tokio::test]
+    async fn test_token_is_cached_until_near_expiry() {
+        let (manager, fetches) = counting_manager(Duration::from_secs(900));
+        assert_eq!(manager.expires_in(), None);
+        assert_eq!(manager.token().await.unwrap(), "token-1");
+        assert_eq!(manager.token().await.unwrap(), "token-1");
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(manager.expires_in().unwrap() > Duration::from_secs(800));
+
+        assert_eq!(manager.refresh().await.unwrap(), "token-2");
+        manager.invalidate();
+        assert_eq!(manager.token().await.unwrap(), "token-3");
+
+        // Tokens inside the refresh margin are replaced on use
+        let (manager, fetches) = counting_manager(Duration::from_secs(30));
+        manager.token().await.unwrap();
+        assert_eq!(manager.token().await.unwrap(), "token-2");
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_callers_share_one_fetch() {
+        let (manager, fetches) = counting_manager(Duration::from_secs(900));
+        let manager = Arc::new(manager);
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let manager = Arc::clone(&manager);
+                tokio::spawn(async move { manager.token().await.unwrap() })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), "token-1");
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_errors_are_returned() {
+        let manager = TokenManager::with_fetcher("key", || async {
+            Err(KrakyError::Api(
+                "REST GetWebSocketsToken failed".to_string(),
+            ))
+        });
+        assert!(manager.token().await.is_err());
+        assert_eq!(manager.expires_in(), None);
+    }
+}
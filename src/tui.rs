@@ -0,0 +1,392 @@
+//! Terminal dashboard for one pair
+//!
+//! [`Dashboard`] takes over the terminal and shows a live view of a pair:
+//! the ticker, the book ladder, recent trades, an imbalance gauge over the
+//! displayed levels and the connection stats (state, messages received,
+//! ping round-trip, time since the last message). Press `q` or `Esc` to
+//! quit.
+//!
+//! Built on [ratatui](https://ratatui.rs) with the crossterm backend.
+//!
+//! Requires the `tui` feature flag.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraky::{Dashboard, KrakyClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = KrakyClient::connect().await?;
+//! Dashboard::new("BTC/USD").depth(25).run(&client).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{ConnectionState, KrakyClient};
+use crate::error::Result;
+use crate::health::ConnectionHealth;
+use crate::models::{Orderbook, Ticker, Trade};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Gauge, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Imbalance beyond which the book counts as one-sided
+const IMBALANCE_THRESHOLD: f64 = 0.1;
+
+/// Live terminal view of a pair
+#[derive(Debug, Clone)]
+pub struct Dashboard {
+    symbol: String,
+    depth: u32,
+    trades: usize,
+    refresh: Duration,
+}
+
+impl Dashboard {
+    /// Create a dashboard for a pair with a 10-level book
+    pub fn new(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            depth: 10,
+            trades: 50,
+            refresh: Duration::from_millis(100),
+        }
+    }
+
+    /// Subscribe to and show this many book levels (10, 25, 100, 500 or 1000)
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Keep this many recent trades (default 50)
+    pub fn trades(mut self, trades: usize) -> Self {
+        self.trades = trades;
+        self
+    }
+
+    /// Redraw this often (default 100ms)
+    pub fn refresh(mut self, refresh: Duration) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Subscribe to the pair and show the dashboard until the user quits
+    ///
+    /// The terminal is restored on return, including on errors.
+    pub async fn run(self, client: &KrakyClient) -> Result<()> {
+        let mut book = client
+            .subscribe_orderbook(self.symbol.as_str(), self.depth)
+            .await?;
+        let mut trades = client.subscribe_trades(self.symbol.as_str()).await?;
+        let mut tickers = client.subscribe_ticker(self.symbol.as_str()).await?;
+
+        let mut state = DashboardState::new(&self.symbol, self.depth as usize, self.trades);
+        let mut terminal = ratatui::init();
+        let mut ticks = tokio::time::interval(self.refresh);
+        let result = loop {
+            tokio::select! {
+                Some(_) = book.next() => state.book = client.get_orderbook(self.symbol.as_str()),
+                Some(trade) = trades.next() => state.push_trade(&trade),
+                Some(ticker) = tickers.next() => state.ticker = Some((*ticker).clone()),
+                _ = ticks.tick() => {
+                    state.connection = client.connection_state();
+                    state.health = client.connection_health();
+                    if let Err(e) = terminal.draw(|frame| state.render(frame)) {
+                        break Err(e.into());
+                    }
+                    match quit_requested() {
+                        Ok(false) => {}
+                        Ok(true) => break Ok(()),
+                        Err(e) => break Err(e.into()),
+                    }
+                }
+            }
+        };
+        ratatui::restore();
+        result
+    }
+}
+
+/// Drain pending key presses, checking for `q`, `Esc` or `Ctrl-C`
+fn quit_requested() -> std::io::Result<bool> {
+    while event::poll(Duration::ZERO)? {
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Everything the dashboard shows
+#[derive(Debug)]
+struct DashboardState {
+    symbol: String,
+    depth: usize,
+    max_trades: usize,
+    book: Option<Orderbook>,
+    /// Recent trades, newest first
+    trades: VecDeque<Trade>,
+    ticker: Option<Ticker>,
+    connection: ConnectionState,
+    health: ConnectionHealth,
+    started: Instant,
+}
+
+impl DashboardState {
+    fn new(symbol: &str, depth: usize, max_trades: usize) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            depth,
+            max_trades,
+            book: None,
+            trades: VecDeque::new(),
+            ticker: None,
+            connection: ConnectionState::Connecting,
+            health: ConnectionHealth::default(),
+            started: Instant::now(),
+        }
+    }
+
+    fn push_trade(&mut self, trade: &Trade) {
+        self.trades.push_front(trade.clone());
+        self.trades.truncate(self.max_trades);
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [header, body, gauge, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .areas(frame.area());
+        let [book, trades] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(body);
+
+        self.render_ticker(frame, header);
+        self.render_book(frame, book);
+        self.render_trades(frame, trades);
+        self.render_imbalance(frame, gauge);
+        self.render_stats(frame, footer);
+    }
+
+    fn render_ticker(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", self.symbol));
+        let line = match &self.ticker {
+            Some(t) => Line::from(vec![
+                Span::styled(
+                    format!("{:.2}", t.last),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("  {:+.2}%", t.change_pct),
+                    Style::default().fg(change_color(t.change_pct)),
+                ),
+                Span::raw(format!(
+                    "  bid {:.2}  ask {:.2}  spread {:.2}  24h {:.2}-{:.2}  vol {:.2}",
+                    t.bid,
+                    t.ask,
+                    t.spread(),
+                    t.low,
+                    t.high,
+                    t.volume
+                )),
+            ]),
+            None => Line::from("Waiting for ticker..."),
+        };
+        frame.render_widget(Paragraph::new(line).block(block), area);
+    }
+
+    fn render_book(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::default().borders(Borders::ALL).title(" Book ");
+        let Some(book) = &self.book else {
+            frame.render_widget(Paragraph::new("Waiting for book...").block(block), area);
+            return;
+        };
+
+        // Show as many levels per side as fit, asks above bids
+        let per_side = (area.height.saturating_sub(3) / 2) as usize;
+        let per_side = per_side.min(self.depth);
+        let asks = book.top_asks(per_side);
+        let bids = book.top_bids(per_side);
+        let rows = asks
+            .iter()
+            .rev()
+            .map(|level| level_row(level.price, level.qty, Color::Red))
+            .chain(
+                bids.iter()
+                    .map(|level| level_row(level.price, level.qty, Color::Green)),
+            );
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Fill(1)])
+            .header(Row::new(["Price", "Qty"]).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(block);
+        frame.render_widget(table, area);
+    }
+
+    fn render_trades(&self, frame: &mut Frame, area: Rect) {
+        let rows = self.trades.iter().map(|trade| {
+            let color = if trade.is_buy() {
+                Color::Green
+            } else {
+                Color::Red
+            };
+            let time = trade.timestamp.get(11..23).unwrap_or(&trade.timestamp);
+            Row::new([
+                Cell::from(time.to_string()),
+                Cell::from(trade.side.to_string()),
+                Cell::from(format!("{:.2}", trade.price)),
+                Cell::from(format!("{:.8}", trade.qty)),
+            ])
+            .style(Style::default().fg(color))
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(12),
+                Constraint::Length(4),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(["Time", "Side", "Price", "Qty"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(" Trades "));
+        frame.render_widget(table, area);
+    }
+
+    fn render_imbalance(&self, frame: &mut Frame, area: Rect) {
+        let imbalance = self
+            .book
+            .as_ref()
+            .map(|book| book.imbalance_top_n(self.depth))
+            .unwrap_or_default();
+        let (label, color) = if imbalance > IMBALANCE_THRESHOLD {
+            ("bid-heavy", Color::Green)
+        } else if imbalance < -IMBALANCE_THRESHOLD {
+            ("ask-heavy", Color::Red)
+        } else {
+            ("balanced", Color::Yellow)
+        };
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title(" Imbalance "))
+            .gauge_style(Style::default().fg(color))
+            .ratio(((imbalance + 1.0) / 2.0).clamp(0.0, 1.0))
+            .label(format!("{:+.3} {}", imbalance, label));
+        frame.render_widget(gauge, area);
+    }
+
+    fn render_stats(&self, frame: &mut Frame, area: Rect) {
+        let (state, color) = match self.connection {
+            ConnectionState::Connected => ("Connected", Color::Green),
+            ConnectionState::Connecting => ("Connecting", Color::Yellow),
+            ConnectionState::Reconnecting => ("Reconnecting", Color::Yellow),
+            ConnectionState::Disconnected => ("Disconnected", Color::Red),
+        };
+        let health = &self.health;
+        let uptime = self.started.elapsed().as_secs();
+        let line = Line::from(vec![
+            Span::styled(state, Style::default().fg(color)),
+            Span::raw(format!(
+                "  msgs {}  rtt p50 {}  last msg {}  up {:02}:{:02}:{:02}  q quits",
+                health.messages_received,
+                format_duration(health.rtt_p50),
+                format_duration(health.since_last_message),
+                uptime / 3600,
+                uptime / 60 % 60,
+                uptime % 60
+            )),
+        ]);
+        let block = Block::default().borders(Borders::ALL).title(" Connection ");
+        frame.render_widget(Paragraph::new(line).block(block), area);
+    }
+}
+
+/// One row of the book ladder
+fn level_row(price: f64, qty: f64, color: Color) -> Row<'static> {
+    Row::new([format!("{:.2}", price), format!("{:.8}", qty)]).style(Style::default().fg(color))
+}
+
+/// Color of a price change
+fn change_color(change: f64) -> Color {
+    if change >= 0.0 {
+        Color::Green
+    } else {
+        Color::Red
+    }
+}
+
+/// Milliseconds, or `-` when unknown
+fn format_duration(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!("{}ms", duration.as_millis()),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderbookData;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn test_render_dashboard() {
+        let mut state = DashboardState::new("BTC/USD", 10, 2);
+        let mut book = Orderbook::new("BTC/USD".to_string());
+        let data: OrderbookData = serde_json::from_value(serde_json::json!({
+            "symbol": "BTC/USD",
+            "bids": [{"price": 41999.5, "qty": 3.0}],
+            "asks": [{"price": 42000.5, "qty": 1.0}]
+        }))
+        .unwrap();
+        book.apply_update(&data);
+        state.book = Some(book);
+        state.connection = ConnectionState::Connected;
+
+        let trade: Trade = serde_json::from_value(serde_json::json!({
+            "symbol": "BTC/USD", "side": "buy", "price": 42000.1, "qty": 0.25,
+            "ord_type": "market", "trade_id": 1, "timestamp": "2024-01-01T12:34:56.789Z"
+        }))
+        .unwrap();
+        for _ in 0..3 {
+            state.push_trade(&trade);
+        }
+        assert_eq!(state.trades.len(), 2);
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| state.render(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(screen.contains("Waiting for ticker"));
+        assert!(screen.contains("42000.50"));
+        assert!(screen.contains("41999.50"));
+        assert!(screen.contains("12:34:56.789"));
+        assert!(screen.contains("+0.500 bid-heavy"));
+        assert!(screen.contains("Connected"));
+    }
+}
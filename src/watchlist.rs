@@ -0,0 +1,396 @@
+//! Watchlists of symbols that change at runtime
+//!
+//! A [`Watchlist`] keeps the configured channels subscribed for a set of
+//! symbols. Adding a symbol subscribes to each channel for it, removing one
+//! unsubscribes, and [`set`](Watchlist::set) rotates to a new set in one
+//! call, e.g. for a screener moving through many pairs. Updates of every
+//! channel and symbol arrive on one merged [`Subscription`] of
+//! [`WatchUpdate`]s.
+//!
+//! Removing a symbol ends every subscription of the watched channels for
+//! it, including ones made outside the watchlist.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(all(feature = "ticker", feature = "trades"))]
+//! # {
+//! use kraky::{KrakyClient, WatchChannel, WatchUpdate, Watchlist};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Arc::new(KrakyClient::connect().await?);
+//! let watchlist = Watchlist::new(client)
+//!     .channel(WatchChannel::Ticker)
+//!     .channel(WatchChannel::Trades);
+//! let mut updates = watchlist.subscribe();
+//!
+//! watchlist.set(["BTC/USD", "ETH/USD"]).await?;
+//! watchlist.add("SOL/USD").await?;
+//! watchlist.remove("ETH/USD")?;
+//!
+//! while let Some(update) = updates.next().await {
+//!     match update {
+//!         WatchUpdate::Ticker(ticker) => println!("{}: {}", ticker.symbol, ticker.last),
+//!         WatchUpdate::Trade(trade) => println!("{}: {} @ {}", trade.symbol, trade.qty, trade.price),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+
+use crate::client::{KrakyClient, StoredSubscription};
+use crate::error::Result;
+use crate::subscriptions::{Subscription, SubscriptionSender};
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "orderbook")]
+use crate::models::OrderbookUpdate;
+
+#[cfg(feature = "trades")]
+use crate::models::Trade;
+
+#[cfg(feature = "ticker")]
+use crate::models::Ticker;
+
+#[cfg(feature = "ohlc")]
+use crate::models::{Interval, OHLC};
+
+/// Channel subscribed for every symbol of a [`Watchlist`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchChannel {
+    /// Orderbook with this depth
+    #[cfg(feature = "orderbook")]
+    Orderbook(u32),
+    /// Trades
+    #[cfg(feature = "trades")]
+    Trades,
+    /// Ticker
+    #[cfg(feature = "ticker")]
+    Ticker,
+    /// Candles with this interval
+    #[cfg(feature = "ohlc")]
+    Ohlc(Interval),
+}
+
+impl WatchChannel {
+    /// The subscription of this channel for a symbol
+    fn subscription(&self, symbol: &str) -> StoredSubscription {
+        let pair = symbol.to_string();
+        match *self {
+            #[cfg(feature = "orderbook")]
+            WatchChannel::Orderbook(depth) => StoredSubscription::Orderbook { pair, depth },
+            #[cfg(feature = "trades")]
+            WatchChannel::Trades => StoredSubscription::Trades { pair },
+            #[cfg(feature = "ticker")]
+            WatchChannel::Ticker => StoredSubscription::Ticker { pair },
+            #[cfg(feature = "ohlc")]
+            WatchChannel::Ohlc(interval) => StoredSubscription::OHLC {
+                pair,
+                interval: interval.minutes(),
+            },
+        }
+    }
+}
+
+/// Update of a watched symbol
+#[derive(Debug, Clone)]
+pub enum WatchUpdate {
+    /// Orderbook snapshot or update
+    #[cfg(feature = "orderbook")]
+    Orderbook(Arc<OrderbookUpdate>),
+    /// Trade
+    #[cfg(feature = "trades")]
+    Trade(Arc<Trade>),
+    /// Ticker
+    #[cfg(feature = "ticker")]
+    Ticker(Arc<Ticker>),
+    /// Candle
+    #[cfg(feature = "ohlc")]
+    Ohlc(Arc<OHLC>),
+}
+
+impl WatchUpdate {
+    /// Get the symbol the update is for
+    pub fn symbol(&self) -> &str {
+        match self {
+            #[cfg(feature = "orderbook")]
+            WatchUpdate::Orderbook(update) => update
+                .data
+                .first()
+                .map(|data| data.symbol.as_str())
+                .unwrap_or_default(),
+            #[cfg(feature = "trades")]
+            WatchUpdate::Trade(trade) => &trade.symbol,
+            #[cfg(feature = "ticker")]
+            WatchUpdate::Ticker(ticker) => &ticker.symbol,
+            #[cfg(feature = "ohlc")]
+            WatchUpdate::Ohlc(candle) => &candle.symbol,
+        }
+    }
+}
+
+/// Merged streams of the watchlist
+type Senders = Arc<RwLock<Vec<SubscriptionSender<WatchUpdate>>>>;
+
+/// Symbols whose channels are kept subscribed
+pub struct Watchlist {
+    client: Arc<KrakyClient>,
+    channels: Vec<WatchChannel>,
+    /// Forwarding tasks per watched symbol
+    symbols: Mutex<HashMap<String, Vec<JoinHandle<()>>>>,
+    senders: Senders,
+}
+
+impl std::fmt::Debug for Watchlist {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watchlist")
+            .field("channels", &self.channels)
+            .field("symbols", &self.symbols())
+            .finish()
+    }
+}
+
+impl Watchlist {
+    /// Create an empty watchlist without channels
+    pub fn new(client: Arc<KrakyClient>) -> Self {
+        Self {
+            client,
+            channels: Vec::new(),
+            symbols: Mutex::new(HashMap::new()),
+            senders: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Subscribe to `channel` for every symbol
+    pub fn channel(mut self, channel: WatchChannel) -> Self {
+        if !self.channels.contains(&channel) {
+            self.channels.push(channel);
+        }
+        self
+    }
+
+    /// Receive the updates of every watched symbol and channel
+    ///
+    /// Symbols added later are included.
+    pub fn subscribe(&self) -> Subscription<WatchUpdate> {
+        let (sender, subscription) =
+            SubscriptionSender::new("watchlist".to_string(), "*".to_string());
+        let mut senders = self.senders.write();
+        senders.retain(|sender| !sender.is_closed());
+        senders.push(sender);
+        subscription
+    }
+
+    /// Get the watched symbols, sorted
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<_> = self.symbols.lock().keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Check whether a symbol is watched
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols.lock().contains_key(symbol)
+    }
+
+    /// Watch a symbol, subscribing to every channel for it
+    ///
+    /// Returns `false` if it was already watched. If a subscription fails,
+    /// the ones already made for the symbol are undone.
+    pub async fn add(&self, symbol: impl Into<String>) -> Result<bool> {
+        let symbol = symbol.into();
+        if self.contains(&symbol) {
+            return Ok(false);
+        }
+
+        let mut tasks = Vec::new();
+        for channel in &self.channels {
+            match self.forward(*channel, &symbol).await {
+                Ok(task) => tasks.push(task),
+                Err(e) => {
+                    for task in tasks {
+                        task.abort();
+                    }
+                    self.unsubscribe(&symbol)?;
+                    return Err(e);
+                }
+            }
+        }
+
+        let mut symbols = self.symbols.lock();
+        if symbols.contains_key(&symbol) {
+            // Added concurrently; the other call's subscriptions stay
+            for task in tasks {
+                task.abort();
+            }
+            return Ok(false);
+        }
+        symbols.insert(symbol, tasks);
+        Ok(true)
+    }
+
+    /// Stop watching a symbol, unsubscribing from every channel for it
+    ///
+    /// Returns `false` if it wasn't watched.
+    pub fn remove(&self, symbol: &str) -> Result<bool> {
+        let Some(tasks) = self.symbols.lock().remove(symbol) else {
+            return Ok(false);
+        };
+        for task in tasks {
+            task.abort();
+        }
+        self.unsubscribe(symbol)?;
+        Ok(true)
+    }
+
+    /// Watch exactly these symbols, adding and removing as needed
+    pub async fn set<I, S>(&self, symbols: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let symbols: Vec<String> = symbols.into_iter().map(Into::into).collect();
+        for watched in self.symbols() {
+            if !symbols.contains(&watched) {
+                self.remove(&watched)?;
+            }
+        }
+        for symbol in symbols {
+            self.add(symbol).await?;
+        }
+        Ok(())
+    }
+
+    /// Stop watching every symbol
+    pub fn clear(&self) -> Result<()> {
+        for symbol in self.symbols() {
+            self.remove(&symbol)?;
+        }
+        Ok(())
+    }
+
+    /// Subscribe to a channel for a symbol and forward its updates
+    async fn forward(&self, channel: WatchChannel, symbol: &str) -> Result<JoinHandle<()>> {
+        let senders = Arc::clone(&self.senders);
+        let task = match channel {
+            #[cfg(feature = "orderbook")]
+            WatchChannel::Orderbook(depth) => {
+                let subscription = self.client.subscribe_orderbook(symbol, depth).await?;
+                spawn_forward(subscription, senders, WatchUpdate::Orderbook)
+            }
+            #[cfg(feature = "trades")]
+            WatchChannel::Trades => {
+                let subscription = self.client.subscribe_trades(symbol).await?;
+                spawn_forward(subscription, senders, WatchUpdate::Trade)
+            }
+            #[cfg(feature = "ticker")]
+            WatchChannel::Ticker => {
+                let subscription = self.client.subscribe_ticker(symbol).await?;
+                spawn_forward(subscription, senders, WatchUpdate::Ticker)
+            }
+            #[cfg(feature = "ohlc")]
+            WatchChannel::Ohlc(interval) => {
+                let subscription = self.client.subscribe_ohlc(symbol, interval).await?;
+                spawn_forward(subscription, senders, WatchUpdate::Ohlc)
+            }
+        };
+        Ok(task)
+    }
+
+    /// Unsubscribe from every channel for a symbol
+    fn unsubscribe(&self, symbol: &str) -> Result<()> {
+        for channel in &self.channels {
+            self.client.unsubscribe(&channel.subscription(symbol))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Watchlist {
+    fn drop(&mut self) {
+        for tasks in self.symbols.get_mut().values() {
+            for task in tasks {
+                task.abort();
+            }
+        }
+    }
+}
+
+/// Forward a subscription into the merged streams until it ends
+fn spawn_forward<T: Send + 'static>(
+    mut subscription: Subscription<T>,
+    senders: Senders,
+    wrap: fn(T) -> WatchUpdate,
+) -> JoinHandle<()> {
+    crate::runtime::spawn(async move {
+        while let Some(update) = subscription.next().await {
+            let update = wrap(update);
+            for sender in senders.read().iter() {
+                let _ = sender.send(update.clone());
+            }
+        }
+    })
+}
+
+#[cfg(all(test, feature = "ticker", feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::{fixtures, MockScript, MockServer};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_watchlist_add_remove() {
+        let server = MockServer::start(
+            MockScript::new().message(fixtures::get("ticker_snapshot").unwrap().text),
+        )
+        .await
+        .unwrap();
+        let client = Arc::new(KrakyClient::connect_with_url(server.url()).await.unwrap());
+        let watchlist = Watchlist::new(Arc::clone(&client)).channel(WatchChannel::Ticker);
+        let mut updates = watchlist.subscribe();
+
+        watchlist.set(["BTC/USD", "ETH/USD"]).await.unwrap();
+        assert!(!watchlist.add("BTC/USD").await.unwrap());
+        assert_eq!(watchlist.symbols(), ["BTC/USD", "ETH/USD"]);
+        assert_eq!(client.stored_subscriptions().len(), 2);
+
+        let update = tokio::time::timeout(Duration::from_secs(5), updates.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.symbol(), "BTC/USD");
+
+        watchlist.set(["ETH/USD"]).await.unwrap();
+        assert!(!watchlist.remove("BTC/USD").unwrap());
+        assert_eq!(
+            client.stored_subscriptions(),
+            [StoredSubscription::Ticker {
+                pair: "ETH/USD".to_string()
+            }]
+        );
+
+        let unsubscribed = || {
+            server
+                .received()
+                .iter()
+                .any(|frame| frame.contains("\"unsubscribe\"") && frame.contains("BTC/USD"))
+        };
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !unsubscribed() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        watchlist.clear().unwrap();
+        assert!(watchlist.symbols().is_empty());
+        assert!(client.stored_subscriptions().is_empty());
+    }
+}
@@ -0,0 +1,369 @@
+//! Generic HTTP webhook notifier for Kraky SDK
+//!
+//! [`WebhookNotifier`] POSTs structured JSON alerts to any URL, so they can be
+//! consumed by PagerDuty, n8n, Zapier, custom dashboards, etc. Every request
+//! body is an envelope:
+//!
+//! ```json
+//! {
+//!   "type": "whale",
+//!   "timestamp": "2024-01-01T12:00:00Z",
+//!   "data": { "symbol": "BTC/USD", "side": "buy", "price": 97000.0, "qty": 25.0, "kind": "book_level" }
+//! }
+//! ```
+//!
+//! Typed [`Notifier`] alerts use their own `type` (`price`, `threshold`,
+//! `imbalance`, `whale`, `alert`); all other alerts are sent as `message`
+//! with the formatted text. Any serializable value, such as a
+//! `WhaleEvent` or `DivergenceEvent` (`analytics` feature),
+//! can be sent with [`WebhookNotifier::send_event`].
+//!
+//! ## Delivery
+//!
+//! Failed deliveries (connection errors, HTTP 429 and 5xx) are retried with
+//! exponential backoff. Other HTTP errors are returned immediately.
+//!
+//! ## Signing
+//!
+//! With a secret configured, each request carries two headers:
+//!
+//! - `X-Kraky-Timestamp` - Unix time in seconds
+//! - `X-Kraky-Signature` - `sha256=<hex>`, the HMAC-SHA256 of `"{timestamp}.{body}"`
+//!
+//! Receivers should recompute the signature with [`sign_payload`] and reject
+//! stale timestamps to prevent replays.
+//!
+//! ## Quick Start
+//!
+//! ```no_run
+//! use kraky::{Notifier, WebhookNotifier};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let webhook = WebhookNotifier::new("https://example.com/hooks/kraky")
+//!         .with_secret("my-signing-secret")
+//!         .with_retries(5);
+//!
+//!     webhook.send_price_alert("BTC/USD", 100_000.0, "Target reached!").await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::error::{KrakyError, Result};
+use crate::notifier::Notifier;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::json;
+use sha2::Sha256;
+use std::time::Duration;
+
+#[cfg(feature = "alerts")]
+use crate::alerts::AlertEvent;
+#[cfg(feature = "analytics")]
+use crate::models::{ImbalanceMetrics, ImbalanceSignal};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Default number of retries after a failed delivery
+pub const DEFAULT_WEBHOOK_RETRIES: u32 = 3;
+
+/// Default delay before the first retry (doubled on each attempt)
+pub const DEFAULT_WEBHOOK_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Header carrying the signing timestamp
+pub const TIMESTAMP_HEADER: &str = "X-Kraky-Timestamp";
+
+/// Header carrying the payload signature
+pub const SIGNATURE_HEADER: &str = "X-Kraky-Signature";
+
+/// Request body sent for every alert
+#[derive(Serialize)]
+struct Envelope<'a, T: Serialize + ?Sized> {
+    #[serde(rename = "type")]
+    event_type: &'a str,
+    timestamp: DateTime<Utc>,
+    data: &'a T,
+}
+
+/// Outcome of a failed delivery attempt
+enum Failure {
+    /// Worth retrying (network error, rate limit, server error)
+    Retryable(KrakyError),
+    /// Will not succeed on retry (e.g. 4xx)
+    Fatal(KrakyError),
+}
+
+/// HTTP webhook notifier posting JSON alerts
+///
+/// See the [module documentation](self) for the payload format and signing
+/// scheme, and [`Notifier`] for the available alerts.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+    secret: Option<Vec<u8>>,
+    headers: Vec<(String, String)>,
+    max_retries: u32,
+    backoff: Duration,
+}
+
+impl WebhookNotifier {
+    /// Create a new webhook notifier posting to `url`
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            secret: None,
+            headers: Vec::new(),
+            max_retries: DEFAULT_WEBHOOK_RETRIES,
+            backoff: DEFAULT_WEBHOOK_BACKOFF,
+        }
+    }
+
+    /// Sign requests with an HMAC-SHA256 secret
+    pub fn with_secret(mut self, secret: &str) -> Self {
+        self.secret = Some(secret.as_bytes().to_vec());
+        self
+    }
+
+    /// Add a header to every request (e.g. an `Authorization` token)
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the number of retries after a failed delivery (0 disables retries)
+    pub fn with_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay before the first retry (doubled on each attempt)
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Send any serializable value as an event of the given type
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use kraky::WebhookNotifier;
+    /// # async fn example(webhook: &WebhookNotifier) -> Result<(), Box<dyn std::error::Error>> {
+    /// #[derive(serde::Serialize)]
+    /// struct Fill {
+    ///     symbol: String,
+    ///     qty: f64,
+    /// }
+    ///
+    /// let fill = Fill { symbol: "BTC/USD".into(), qty: 0.5 };
+    /// webhook.send_event("fill", &fill).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_event<T: Serialize + Sync + ?Sized>(
+        &self,
+        event_type: &str,
+        data: &T,
+    ) -> Result<()> {
+        let body = serde_json::to_vec(&Envelope {
+            event_type,
+            timestamp: Utc::now(),
+            data,
+        })?;
+
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match self.post(&body).await {
+                Ok(()) => return Ok(()),
+                Err(Failure::Retryable(e)) if attempt < self.max_retries => {
+                    tracing::warn!(
+                        "Webhook delivery failed (attempt {}/{}): {}",
+                        attempt + 1,
+                        self.max_retries + 1,
+                        e
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(Failure::Retryable(e)) | Err(Failure::Fatal(e)) => return Err(e),
+            }
+        }
+    }
+
+    /// Make a single delivery attempt
+    async fn post(&self, body: &[u8]) -> std::result::Result<(), Failure> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .body(body.to_vec());
+
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        if let Some(secret) = &self.secret {
+            let timestamp = Utc::now().timestamp();
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(
+                    SIGNATURE_HEADER,
+                    format!("sha256={}", sign_payload(secret, timestamp, body)),
+                );
+        }
+
+        let response = request.send().await.map_err(|e| {
+            Failure::Retryable(KrakyError::InvalidMessage(format!("Webhook error: {}", e)))
+        })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        if status.as_u16() == 429 {
+            return Err(Failure::Retryable(KrakyError::RateLimited));
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        let error = KrakyError::InvalidMessage(format!("Webhook error: {} {}", status, body));
+        if status.is_server_error() {
+            Err(Failure::Retryable(error))
+        } else {
+            Err(Failure::Fatal(error))
+        }
+    }
+}
+
+/// Compute the hex HMAC-SHA256 signature of a webhook body
+///
+/// The signed message is `"{timestamp}.{body}"`, matching the
+/// `X-Kraky-Signature` header (without its `sha256=` prefix).
+pub fn sign_payload(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn send_alert(&self, message: &str) -> Result<()> {
+        self.send_event("message", &json!({ "message": message }))
+            .await
+    }
+
+    #[cfg(feature = "alerts")]
+    async fn send_alert_event(&self, event: &AlertEvent) -> Result<()> {
+        self.send_event("alert", event).await
+    }
+
+    async fn send_price_alert(&self, symbol: &str, price: f64, context: &str) -> Result<()> {
+        let data = json!({ "symbol": symbol, "price": price, "context": context });
+        self.send_event("price", &data).await
+    }
+
+    async fn send_threshold_alert(
+        &self,
+        symbol: &str,
+        price: f64,
+        threshold: f64,
+        above: bool,
+    ) -> Result<()> {
+        let data = json!({
+            "symbol": symbol,
+            "price": price,
+            "threshold": threshold,
+            "above": above,
+        });
+        self.send_event("threshold", &data).await
+    }
+
+    #[cfg(feature = "analytics")]
+    async fn send_imbalance_alert(
+        &self,
+        symbol: &str,
+        metrics: &ImbalanceMetrics,
+        signal: ImbalanceSignal,
+    ) -> Result<()> {
+        let data = json!({ "symbol": symbol, "signal": signal, "metrics": metrics });
+        self.send_event("imbalance", &data).await
+    }
+
+    async fn send_whale_alert(
+        &self,
+        symbol: &str,
+        side: &str,
+        price: f64,
+        volume: f64,
+    ) -> Result<()> {
+        let data = json!({ "symbol": symbol, "side": side, "price": price, "volume": volume });
+        self.send_event("whale", &data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload() {
+        let signature = sign_payload(b"secret", 1_700_000_000, br#"{"a":1}"#);
+        assert_eq!(
+            signature,
+            "49f24e537407743fa4a0242bb63b94b9a47ee99cbbe071ccd8a22550ae411686"
+        );
+    }
+
+    #[test]
+    fn test_envelope() {
+        let data = json!({ "symbol": "BTC/USD" });
+        let envelope = Envelope {
+            event_type: "price",
+            timestamp: Utc::now(),
+            data: &data,
+        };
+        let value = serde_json::to_value(&envelope).unwrap();
+
+        assert_eq!(value["type"], "price");
+        assert_eq!(value["data"]["symbol"], "BTC/USD");
+        assert!(value["timestamp"].is_string());
+    }
+
+    #[cfg(feature = "alerts")]
+    #[test]
+    fn test_alert_event_serialization() {
+        use crate::alerts::AlertCondition;
+
+        let event = AlertEvent {
+            rule_id: 1,
+            name: None,
+            symbol: "BTC/USD".to_string(),
+            condition: AlertCondition::PriceAbove(100_000.0),
+            value: 100_500.0,
+            timestamp: Utc::now(),
+        };
+        let value = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(value["condition"]["type"], "price_above");
+        assert_eq!(value["condition"]["value"], 100_000.0);
+        assert_eq!(serde_json::from_value::<AlertEvent>(value).unwrap(), event);
+    }
+
+    #[tokio::test]
+    async fn test_retries_then_fails() {
+        // Nothing listens on the discard port
+        let notifier = WebhookNotifier::new("http://127.0.0.1:9/hook")
+            .with_retries(2)
+            .with_backoff(Duration::from_millis(1));
+
+        assert!(notifier.send_alert("test").await.is_err());
+    }
+}